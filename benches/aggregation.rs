@@ -0,0 +1,234 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Benchmarks of interaction extraction, window pruning and ranking computation
+//! over synthetic checkpoints of 1k to 100k events, of checkpoint throughput
+//! with sequential versus pipelined extraction, and of concurrent ingestion
+//! workers sharing a locked indexer versus feeding the checkpoint aggregator.
+//!
+//! Run with `cargo bench --features test-utils`.
+
+use chrono::Utc;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use futures_util::future::join_all;
+use futures_util::stream;
+use prometheus::Registry;
+use std::sync::Arc;
+use std::time::Duration;
+use sui_data_ingestion_core::Worker;
+use tokio::runtime::Runtime;
+use tokio::sync::Mutex;
+use suins_indexer::aggregator::{
+    checkpoint_channel, start_checkpoint_aggregator, CheckpointQueueMetrics, CheckpointWorkerMetrics, DAppIndexerWorker,
+};
+use suins_indexer::test_utils::{dapp_event, user_address, CheckpointBuilder, InteractionBuilder};
+use suins_indexer::{CheckpointData, DAppIndexer, DAppInteraction};
+
+const EVENT_COUNTS: [usize; 3] = [1_000, 10_000, 100_000];
+
+/// Checkpoints per throughput run
+const PIPELINE_CHECKPOINTS: u64 = 64;
+
+/// Events per checkpoint of the throughput runs
+const PIPELINE_EVENTS: usize = 1_000;
+
+/// Concurrent ingestion workers of the worker runs
+const WORKERS: usize = 8;
+
+/// Queue capacity between workers and the aggregator in the worker runs
+const QUEUE_CAPACITY: usize = 64;
+
+/// Events per synthetic transaction
+const EVENTS_PER_TRANSACTION: usize = 10;
+
+/// Distinct users sending the synthetic events
+const USERS: u64 = 5_000;
+
+/// Tracked packages (Cetus AMM twice, Pyth, Momentum) and one untracked package
+const PACKAGES: [&str; 5] = [
+    "0x6f5e582ede61fe5395b50c4a449ec11479a54d7ff8e0158247adfda60d98970b",
+    "0x3864c7c59a4889fec05d1aae4bc9dba5a0e0940594b424fbed44cb3f6ac4c032",
+    "0x04e20ddf36af412a4096f9014f4a565af9e812db9a05cc40254846cf6ed0ad91",
+    "0x9c12f3aa14a449a0a23c066589e269086f021a98939f21158cfacb16d19787c3",
+    "0x0000000000000000000000000000000000000000000000000000000000000abc",
+];
+
+/// A recent checkpoint with `events` DApp events spread over transactions and users
+fn synthetic_checkpoint(sequence_number: u64, events: usize) -> CheckpointData {
+    let mut builder = CheckpointBuilder::new(sequence_number);
+    for transaction in 0..events.div_ceil(EVENTS_PER_TRANSACTION) {
+        let events = (0..EVENTS_PER_TRANSACTION)
+            .map(|offset| transaction * EVENTS_PER_TRANSACTION + offset)
+            .take_while(|&event| event < events)
+            .map(|event| dapp_event(PACKAGES[event % PACKAGES.len()], user_address(event as u64 % USERS)))
+            .collect();
+        builder = builder.transaction_with_events((transaction % 256) as u8, events);
+    }
+    builder.build()
+}
+
+/// `count` interactions with tracked DApps spread over the last `window`
+fn synthetic_interactions(count: usize, window: Duration) -> Vec<DAppInteraction> {
+    let now = Utc::now();
+    (0..count)
+        .map(|index| {
+            InteractionBuilder::new()
+                .package(PACKAGES[index % (PACKAGES.len() - 1)])
+                .sender(&user_address(index as u64 % USERS).to_string())
+                .digest(&format!("tx{}", index / EVENTS_PER_TRANSACTION))
+                .gas_used(1_000_000)
+                .at(now - window.mul_f64(index as f64 / count as f64))
+                .build()
+        })
+        .collect()
+}
+
+fn bench_extraction(c: &mut Criterion) {
+    let extractor = DAppIndexer::new().extractor();
+    let mut group = c.benchmark_group("extract_dapp_interactions");
+    for events in EVENT_COUNTS {
+        let checkpoint = synthetic_checkpoint(1, events);
+        group.throughput(Throughput::Elements(events as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(events), &checkpoint, |b, checkpoint| {
+            b.iter(|| extractor.extract(checkpoint));
+        });
+    }
+    group.finish();
+}
+
+fn bench_pruning(c: &mut Criterion) {
+    let mut group = c.benchmark_group("prune_old_interactions");
+    for events in EVENT_COUNTS {
+        // Half of the interactions are older than the 1h window
+        let interactions = synthetic_interactions(events, Duration::from_secs(2 * 60 * 60));
+        let mut indexer = DAppIndexer::new();
+        group.throughput(Throughput::Elements(events as u64));
+        group.bench_function(BenchmarkId::from_parameter(events), |b| {
+            b.iter_batched(
+                || interactions.clone(),
+                |interactions| {
+                    indexer.dapp_interactions = interactions;
+                    indexer.prune_old_interactions();
+                },
+                BatchSize::LargeInput,
+            );
+        });
+    }
+    group.finish();
+}
+
+fn bench_rankings(c: &mut Criterion) {
+    let mut group = c.benchmark_group("update_dapp_rankings");
+    for events in EVENT_COUNTS {
+        let mut indexer = DAppIndexer::new();
+        indexer.dapp_interactions = synthetic_interactions(events, Duration::from_secs(30 * 60));
+        group.throughput(Throughput::Elements(events as u64));
+        group.bench_function(BenchmarkId::from_parameter(events), |b| {
+            b.iter(|| indexer.update_dapp_rankings());
+        });
+    }
+    group.finish();
+}
+
+fn bench_pipeline(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let checkpoints: Vec<CheckpointData> =
+        (1..=PIPELINE_CHECKPOINTS).map(|sequence_number| synthetic_checkpoint(sequence_number, PIPELINE_EVENTS)).collect();
+    let mut group = c.benchmark_group("checkpoint_pipeline");
+    group.throughput(Throughput::Elements(PIPELINE_CHECKPOINTS));
+    group.bench_function("sequential", |b| {
+        b.iter_batched(
+            || (DAppIndexer::new(), checkpoints.clone()),
+            |(mut indexer, checkpoints)| {
+                runtime.block_on(async {
+                    for checkpoint in &checkpoints {
+                        indexer.process_checkpoint(checkpoint, None).await;
+                    }
+                })
+            },
+            BatchSize::LargeInput,
+        );
+    });
+    group.bench_function("pipelined", |b| {
+        b.iter_batched(
+            || (DAppIndexer::new(), checkpoints.clone()),
+            |(mut indexer, checkpoints)| runtime.block_on(indexer.process_stream(stream::iter(checkpoints), None)),
+            BatchSize::LargeInput,
+        );
+    });
+    group.finish();
+}
+
+/// Split `checkpoints` round-robin over the workers
+fn worker_shares(checkpoints: &[CheckpointData]) -> Vec<Vec<CheckpointData>> {
+    let mut shares = vec![Vec::new(); WORKERS];
+    for (index, checkpoint) in checkpoints.iter().enumerate() {
+        shares[index % WORKERS].push(checkpoint.clone());
+    }
+    shares
+}
+
+fn bench_workers(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_multi_thread().enable_all().build().unwrap();
+    let checkpoints: Vec<CheckpointData> =
+        (1..=PIPELINE_CHECKPOINTS).map(|sequence_number| synthetic_checkpoint(sequence_number, PIPELINE_EVENTS)).collect();
+    let mut group = c.benchmark_group("checkpoint_workers");
+    group.throughput(Throughput::Elements(PIPELINE_CHECKPOINTS));
+
+    // Every worker extracts and applies its checkpoints while holding the indexer lock
+    group.bench_function("locked_indexer", |b| {
+        b.iter_batched(
+            || (Arc::new(Mutex::new(DAppIndexer::new())), worker_shares(&checkpoints)),
+            |(indexer, shares)| {
+                runtime.block_on(async {
+                    let workers = shares.into_iter().map(|share| {
+                        let indexer = indexer.clone();
+                        tokio::spawn(async move {
+                            for checkpoint in &share {
+                                indexer.lock().await.process_checkpoint(checkpoint, None).await;
+                            }
+                        })
+                    });
+                    join_all(workers).await;
+                })
+            },
+            BatchSize::LargeInput,
+        );
+    });
+
+    // Workers extract without a lock and queue the results to the single aggregator task
+    group.bench_function("aggregator", |b| {
+        b.iter_batched(
+            || (Arc::new(Mutex::new(DAppIndexer::new())), worker_shares(&checkpoints)),
+            |(indexer, shares)| {
+                runtime.block_on(async {
+                    let registry = Registry::new();
+                    let queue_metrics = CheckpointQueueMetrics::new(&registry).unwrap();
+                    let (sender, receiver) = checkpoint_channel(QUEUE_CAPACITY, queue_metrics.clone());
+                    let aggregator =
+                        start_checkpoint_aggregator(indexer.clone(), None, None, None, receiver, queue_metrics, u64::MAX).await;
+                    let extractor = indexer.lock().await.extractor();
+                    let worker_metrics = CheckpointWorkerMetrics::new(&registry).unwrap();
+                    let worker = Arc::new(DAppIndexerWorker::new(extractor, sender, worker_metrics));
+                    let workers = shares.into_iter().map(|share| {
+                        let worker = worker.clone();
+                        tokio::spawn(async move {
+                            for checkpoint in &share {
+                                worker.process_checkpoint(checkpoint).await.unwrap();
+                            }
+                        })
+                    });
+                    join_all(workers).await;
+                    // The aggregator finishes once the last sender is gone and the queue is drained
+                    drop(worker);
+                    aggregator.await.unwrap();
+                })
+            },
+            BatchSize::LargeInput,
+        );
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_extraction, bench_pruning, bench_rankings, bench_pipeline, bench_workers);
+criterion_main!(benches);
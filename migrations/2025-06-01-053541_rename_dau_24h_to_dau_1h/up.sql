@@ -1,4 +1,13 @@
 -- Your SQL goes here
 
 -- Rename dau_24h column to dau_1h to better reflect 1-hour HAU calculation
-ALTER TABLE dapp_rankings RENAME COLUMN dau_24h TO dau_1h;
+-- Databases created from the consolidated schema already have dau_1h
+DO $$
+BEGIN
+    IF EXISTS (
+        SELECT 1 FROM information_schema.columns
+        WHERE table_name = 'dapp_rankings' AND column_name = 'dau_24h'
+    ) THEN
+        ALTER TABLE dapp_rankings RENAME COLUMN dau_24h TO dau_1h;
+    END IF;
+END $$;
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE dapp_rankings DROP COLUMN IF EXISTS filtered_users_1h;
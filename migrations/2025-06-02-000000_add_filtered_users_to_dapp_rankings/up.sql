@@ -0,0 +1,4 @@
+-- Your SQL goes here
+
+-- Number of senders excluded from HAU by the bot/sybil filter chain
+ALTER TABLE dapp_rankings ADD COLUMN IF NOT EXISTS filtered_users_1h INTEGER NOT NULL DEFAULT 0;
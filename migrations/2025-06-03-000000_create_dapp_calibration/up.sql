@@ -0,0 +1,15 @@
+-- Your SQL goes here
+
+-- Divergence between our DAU and an external data source, sampled over time
+CREATE TABLE IF NOT EXISTS dapp_calibration (
+    id SERIAL PRIMARY KEY,
+    dapp_name VARCHAR NOT NULL,
+    external_source VARCHAR NOT NULL,
+    external_dau INTEGER NOT NULL,
+    indexed_dau INTEGER NOT NULL,
+    divergence_pct DOUBLE PRECISION NOT NULL,
+    recorded_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_dapp_calibration_dapp_recorded_at
+    ON dapp_calibration (dapp_name, recorded_at);
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+
+-- Whether the DApp is actively counted ('active') or its tracking is paused ('paused')
+ALTER TABLE dapp_rankings ADD COLUMN IF NOT EXISTS tracking_status VARCHAR NOT NULL DEFAULT 'active';
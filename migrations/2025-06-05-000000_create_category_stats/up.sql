@@ -0,0 +1,16 @@
+-- Your SQL goes here
+
+-- Per-category (dapp_type) rollups, appended on every ranking update
+CREATE TABLE IF NOT EXISTS category_stats (
+    id SERIAL PRIMARY KEY,
+    dapp_type VARCHAR NOT NULL,
+    window_label VARCHAR NOT NULL DEFAULT '1h',
+    active_users INTEGER NOT NULL DEFAULT 0,
+    transaction_count INTEGER NOT NULL DEFAULT 0,
+    interaction_count INTEGER NOT NULL DEFAULT 0,
+    dapp_count INTEGER NOT NULL DEFAULT 0,
+    computed_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_category_stats_type_computed_at
+    ON category_stats (dapp_type, computed_at DESC);
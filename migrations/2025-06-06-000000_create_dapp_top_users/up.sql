@@ -0,0 +1,14 @@
+-- Your SQL goes here
+
+-- Most active senders per DApp per UTC day
+CREATE TABLE IF NOT EXISTS dapp_top_users (
+    day DATE NOT NULL,
+    dapp_name VARCHAR NOT NULL,
+    sender VARCHAR NOT NULL,
+    interaction_count BIGINT NOT NULL DEFAULT 0,
+    rank_position INTEGER NOT NULL,
+    PRIMARY KEY (day, dapp_name, sender)
+);
+
+CREATE INDEX IF NOT EXISTS idx_dapp_top_users_dapp_day
+    ON dapp_top_users (dapp_name, day DESC, rank_position);
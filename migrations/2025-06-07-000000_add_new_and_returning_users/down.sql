@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE dapp_rankings DROP COLUMN IF EXISTS returning_users_24h;
+ALTER TABLE dapp_rankings DROP COLUMN IF EXISTS new_users_24h;
+DROP TABLE IF EXISTS dapp_user_first_seen;
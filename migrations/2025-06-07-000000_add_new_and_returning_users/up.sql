@@ -0,0 +1,13 @@
+-- Your SQL goes here
+
+-- First time each sender was seen interacting with each DApp
+CREATE TABLE IF NOT EXISTS dapp_user_first_seen (
+    dapp_name VARCHAR NOT NULL,
+    sender VARCHAR NOT NULL,
+    first_seen TIMESTAMP NOT NULL,
+    PRIMARY KEY (dapp_name, sender)
+);
+
+-- Split of active users into new (first seen within 24h) and returning users
+ALTER TABLE dapp_rankings ADD COLUMN IF NOT EXISTS new_users_24h INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE dapp_rankings ADD COLUMN IF NOT EXISTS returning_users_24h INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+DROP INDEX IF EXISTS idx_dapp_user_first_seen_first_seen;
+DROP TABLE IF EXISTS dapp_retention;
+DROP TABLE IF EXISTS dapp_user_activity;
@@ -0,0 +1,31 @@
+-- Your SQL goes here
+
+-- Distinct active senders per DApp per UTC day
+CREATE TABLE IF NOT EXISTS dapp_user_activity (
+    day DATE NOT NULL,
+    dapp_name VARCHAR NOT NULL,
+    sender VARCHAR NOT NULL,
+    PRIMARY KEY (day, dapp_name, sender)
+);
+
+CREATE INDEX IF NOT EXISTS idx_dapp_user_activity_dapp_sender
+    ON dapp_user_activity (dapp_name, sender, day);
+
+-- D1/D7/D30 return rates of each daily cohort of first-time users per DApp
+-- Rates stay NULL until the corresponding day has fully elapsed
+CREATE TABLE IF NOT EXISTS dapp_retention (
+    cohort_day DATE NOT NULL,
+    dapp_name VARCHAR NOT NULL,
+    cohort_size INTEGER NOT NULL DEFAULT 0,
+    d1_returned INTEGER NOT NULL DEFAULT 0,
+    d7_returned INTEGER NOT NULL DEFAULT 0,
+    d30_returned INTEGER NOT NULL DEFAULT 0,
+    d1_rate DOUBLE PRECISION,
+    d7_rate DOUBLE PRECISION,
+    d30_rate DOUBLE PRECISION,
+    computed_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (cohort_day, dapp_name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_dapp_user_first_seen_first_seen
+    ON dapp_user_first_seen (first_seen);
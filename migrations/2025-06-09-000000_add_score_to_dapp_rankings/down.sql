@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE dapp_rankings DROP COLUMN IF EXISTS score;
+ALTER TABLE dapp_rankings DROP COLUMN IF EXISTS gas_used_1h;
+ALTER TABLE dapp_rankings DROP COLUMN IF EXISTS tx_count_1h;
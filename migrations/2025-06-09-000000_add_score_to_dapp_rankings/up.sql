@@ -0,0 +1,6 @@
+-- Your SQL goes here
+
+-- Metrics feeding the configurable ranking score, and the score itself
+ALTER TABLE dapp_rankings ADD COLUMN IF NOT EXISTS tx_count_1h INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE dapp_rankings ADD COLUMN IF NOT EXISTS gas_used_1h BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE dapp_rankings ADD COLUMN IF NOT EXISTS score DOUBLE PRECISION NOT NULL DEFAULT 0;
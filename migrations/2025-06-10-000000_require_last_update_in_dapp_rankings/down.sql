@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE dapp_rankings ALTER COLUMN last_update DROP NOT NULL;
+ALTER TABLE dapp_rankings ALTER COLUMN last_update DROP DEFAULT;
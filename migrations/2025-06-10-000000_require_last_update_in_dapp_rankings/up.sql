@@ -0,0 +1,7 @@
+-- Your SQL goes here
+
+-- last_update now always holds the time the ranking was computed
+-- Rows written before it was maintained are stamped with the migration time
+UPDATE dapp_rankings SET last_update = NOW() WHERE last_update IS NULL;
+ALTER TABLE dapp_rankings ALTER COLUMN last_update SET DEFAULT NOW();
+ALTER TABLE dapp_rankings ALTER COLUMN last_update SET NOT NULL;
@@ -0,0 +1,8 @@
+-- Your SQL goes here
+
+-- Last processed checkpoint (watermark) per ingestion task
+CREATE TABLE IF NOT EXISTS checkpoint_progress (
+    task_name VARCHAR PRIMARY KEY,
+    checkpoint_number BIGINT NOT NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
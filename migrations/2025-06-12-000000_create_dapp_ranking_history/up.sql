@@ -0,0 +1,17 @@
+-- Your SQL goes here
+
+-- Snapshot of every DApp's ranking each time rankings are persisted
+CREATE TABLE IF NOT EXISTS dapp_ranking_history (
+    id BIGSERIAL PRIMARY KEY,
+    dapp_name VARCHAR NOT NULL,
+    dapp_type VARCHAR NOT NULL,
+    rank_position INTEGER NOT NULL,
+    dau_1h INTEGER NOT NULL,
+    tx_count_1h INTEGER NOT NULL,
+    gas_used_1h BIGINT NOT NULL,
+    score DOUBLE PRECISION NOT NULL,
+    recorded_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_dapp_ranking_history_dapp_recorded_at
+    ON dapp_ranking_history (dapp_name, recorded_at);
@@ -0,0 +1,7 @@
+-- Your SQL goes here
+
+-- Trigram index backing fuzzy DApp name search
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX IF NOT EXISTS idx_dapp_rankings_dapp_name_trgm
+    ON dapp_rankings USING GIN (dapp_name gin_trgm_ops);
@@ -0,0 +1,17 @@
+-- Your SQL goes here
+
+-- Current 1h activity of every tracked package, so traffic can be compared
+-- between packages of the same DApp (e.g. AMM v1 vs v2) while rankings stay
+-- aggregated by DApp name
+CREATE TABLE IF NOT EXISTS dapp_package_stats (
+    package_id VARCHAR PRIMARY KEY,
+    dapp_name VARCHAR NOT NULL,
+    dapp_type VARCHAR NOT NULL,
+    dau_1h INTEGER NOT NULL DEFAULT 0,
+    tx_count_1h INTEGER NOT NULL DEFAULT 0,
+    interaction_count_1h INTEGER NOT NULL DEFAULT 0,
+    last_update TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_dapp_package_stats_dapp_name
+    ON dapp_package_stats (dapp_name);
@@ -0,0 +1,11 @@
+-- Your SQL goes here
+
+-- Interactions per event type (module::struct) of every tracked DApp over the
+-- last 24 hours, replaced on every ranking update
+CREATE TABLE IF NOT EXISTS dapp_event_stats (
+    dapp_name VARCHAR NOT NULL,
+    event_type VARCHAR NOT NULL,
+    count_24h BIGINT NOT NULL DEFAULT 0,
+    last_update TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (dapp_name, event_type)
+);
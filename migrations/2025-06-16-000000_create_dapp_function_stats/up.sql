@@ -0,0 +1,12 @@
+-- Your SQL goes here
+
+-- Most called Move functions of every tracked DApp over the last 24 hours,
+-- replaced on every ranking update
+CREATE TABLE IF NOT EXISTS dapp_function_stats (
+    dapp_name VARCHAR NOT NULL,
+    function_name VARCHAR NOT NULL,
+    call_count_24h BIGINT NOT NULL DEFAULT 0,
+    rank_position INTEGER NOT NULL,
+    last_update TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (dapp_name, function_name)
+);
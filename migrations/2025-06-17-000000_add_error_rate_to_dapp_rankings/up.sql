@@ -0,0 +1,4 @@
+-- Your SQL goes here
+
+-- Share of the DApp's transactions that failed over the last 24 hours
+ALTER TABLE dapp_rankings ADD COLUMN IF NOT EXISTS error_rate_24h DOUBLE PRECISION NOT NULL DEFAULT 0;
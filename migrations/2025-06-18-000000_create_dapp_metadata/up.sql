@@ -0,0 +1,11 @@
+-- Your SQL goes here
+
+-- Optional presentation metadata of every DApp, joined into API responses
+-- so leaderboard frontends don't need a separate metadata service
+CREATE TABLE IF NOT EXISTS dapp_metadata (
+    dapp_name VARCHAR PRIMARY KEY,
+    website_url VARCHAR,
+    icon_url VARCHAR,
+    twitter VARCHAR,
+    last_update TIMESTAMP NOT NULL DEFAULT NOW()
+);
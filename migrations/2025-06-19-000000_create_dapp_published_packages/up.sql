@@ -0,0 +1,10 @@
+-- Your SQL goes here
+
+-- Packages attributed to a DApp because one of its publisher addresses
+-- published or upgraded them, reloaded into the registry at startup
+CREATE TABLE IF NOT EXISTS dapp_published_packages (
+    package_id VARCHAR PRIMARY KEY,
+    dapp_name VARCHAR NOT NULL,
+    publisher VARCHAR NOT NULL,
+    discovered_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
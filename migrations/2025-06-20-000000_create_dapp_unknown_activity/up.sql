@@ -0,0 +1,12 @@
+-- Your SQL goes here
+
+-- Rankings of untracked packages, moved out of dapp_rankings instead of being
+-- deleted so maintainers can review which DApps the registry is missing
+CREATE TABLE IF NOT EXISTS dapp_unknown_activity (
+    package_id VARCHAR PRIMARY KEY,
+    dapp_name VARCHAR NOT NULL,
+    observed_dau INTEGER NOT NULL DEFAULT 0,
+    last_seen TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_dapp_unknown_activity_last_seen ON dapp_unknown_activity (last_seen DESC);
@@ -0,0 +1,11 @@
+-- Your SQL goes here
+
+-- Registry changes made through the admin API, applied over the built-in
+-- registry at startup. Inactive rows remove the package from tracking
+CREATE TABLE IF NOT EXISTS dapp_registry (
+    package_id VARCHAR PRIMARY KEY,
+    dapp_name VARCHAR NOT NULL,
+    dapp_type VARCHAR NOT NULL,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,14 @@
+-- Your SQL goes here
+
+-- Active users and transactions of every tracked DApp per Sui epoch, updated
+-- while the epoch is in progress. observed_from is the first interaction the
+-- indexer saw in the epoch, so epochs it joined midway are marked as partial
+CREATE TABLE IF NOT EXISTS epoch_stats (
+    epoch BIGINT NOT NULL,
+    dapp_name VARCHAR NOT NULL,
+    active_users INTEGER NOT NULL DEFAULT 0,
+    transaction_count INTEGER NOT NULL DEFAULT 0,
+    observed_from TIMESTAMP NOT NULL,
+    last_update TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (epoch, dapp_name)
+);
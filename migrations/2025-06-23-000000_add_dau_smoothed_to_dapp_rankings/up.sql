@@ -0,0 +1,5 @@
+-- Your SQL goes here
+
+-- Moving average of Hourly Active Users over the last ranking computations
+-- (equal to dau_1h when smoothing is disabled)
+ALTER TABLE dapp_rankings ADD COLUMN IF NOT EXISTS dau_smoothed DOUBLE PRECISION NOT NULL DEFAULT 0;
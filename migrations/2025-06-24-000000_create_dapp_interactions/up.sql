@@ -0,0 +1,20 @@
+-- Your SQL goes here
+
+-- Raw DApp interactions, written only when PERSIST_INTERACTIONS is enabled
+-- Partitioned by UTC day of the interaction; the indexer creates the daily
+-- partitions (dapp_interactions_YYYYMMDD) as needed and drops the ones older
+-- than INTERACTIONS_RETENTION_DAYS
+CREATE TABLE IF NOT EXISTS dapp_interactions (
+    timestamp TIMESTAMP NOT NULL,
+    transaction_digest VARCHAR NOT NULL,
+    -- Position of the interaction among those of its transaction
+    interaction_index INTEGER NOT NULL,
+    package_id VARCHAR NOT NULL,
+    dapp_name VARCHAR,
+    sender VARCHAR NOT NULL,
+    gas_used BIGINT NOT NULL,
+    event_type VARCHAR NOT NULL,
+    PRIMARY KEY (timestamp, transaction_digest, interaction_index)
+) PARTITION BY RANGE (timestamp);
+
+CREATE INDEX IF NOT EXISTS idx_dapp_interactions_dapp_timestamp ON dapp_interactions (dapp_name, timestamp);
@@ -0,0 +1,9 @@
+-- This file should undo anything in `up.sql`
+
+-- Only the live 1h rankings fit the package ID key
+DROP INDEX IF EXISTS idx_dapp_rankings_window_rank;
+DELETE FROM dapp_rankings WHERE window_label <> '1h';
+ALTER TABLE dapp_rankings DROP CONSTRAINT dapp_rankings_pkey;
+ALTER TABLE dapp_rankings ADD PRIMARY KEY (package_id);
+ALTER TABLE dapp_rankings ALTER COLUMN dapp_name DROP NOT NULL;
+ALTER TABLE dapp_rankings DROP COLUMN window_label;
@@ -0,0 +1,20 @@
+-- Your SQL goes here
+
+-- Rankings are aggregated per DApp name over a window, so key them by
+-- (dapp_name, window_label) instead of a single package ID. Existing rows
+-- are the live 1h rankings.
+ALTER TABLE dapp_rankings ADD COLUMN IF NOT EXISTS window_label VARCHAR NOT NULL DEFAULT '1h';
+
+-- Keep the best ranked row of a DApp stored under several packages
+DELETE FROM dapp_rankings duplicate
+USING dapp_rankings kept
+WHERE duplicate.dapp_name = kept.dapp_name
+  AND (duplicate.rank_position, duplicate.package_id) > (kept.rank_position, kept.package_id);
+DELETE FROM dapp_rankings WHERE dapp_name IS NULL;
+
+ALTER TABLE dapp_rankings ALTER COLUMN dapp_name SET NOT NULL;
+ALTER TABLE dapp_rankings DROP CONSTRAINT dapp_rankings_pkey;
+ALTER TABLE dapp_rankings ADD PRIMARY KEY (dapp_name, window_label);
+
+CREATE INDEX IF NOT EXISTS idx_dapp_rankings_window_rank
+    ON dapp_rankings (window_label, rank_position);
@@ -0,0 +1,14 @@
+-- Your SQL goes here
+
+-- Distinct users of every tracked DApp per hour of the day (UTC) over the
+-- trailing 7 days, for activity heat maps. Rewritten on every save;
+-- observed_from is the first interaction counted, so a heat map covering
+-- less than 7 days after a restart is marked as partial
+CREATE TABLE IF NOT EXISTS dapp_hourly_activity (
+    dapp_name VARCHAR NOT NULL,
+    hour_of_day INTEGER NOT NULL CHECK (hour_of_day BETWEEN 0 AND 23),
+    unique_users INTEGER NOT NULL DEFAULT 0,
+    observed_from TIMESTAMP NOT NULL,
+    last_update TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (dapp_name, hour_of_day)
+);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+DROP TABLE IF EXISTS dapp_ranking_monthly;
+DROP TABLE IF EXISTS dapp_ranking_weekly;
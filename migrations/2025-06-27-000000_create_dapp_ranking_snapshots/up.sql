@@ -0,0 +1,26 @@
+-- Your SQL goes here
+
+-- Top DApps frozen at the end of every week (Monday to Sunday) and calendar
+-- month, in the stats timezone. period_start is the first day of the period
+-- the snapshot closes; ranks are renumbered without "Others" and paused DApps
+CREATE TABLE IF NOT EXISTS dapp_ranking_weekly (
+    period_start DATE NOT NULL,
+    rank_position INTEGER NOT NULL,
+    dapp_name VARCHAR NOT NULL,
+    dapp_type VARCHAR NOT NULL,
+    active_users INTEGER NOT NULL DEFAULT 0,
+    score DOUBLE PRECISION NOT NULL DEFAULT 0,
+    recorded_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (period_start, dapp_name)
+);
+
+CREATE TABLE IF NOT EXISTS dapp_ranking_monthly (
+    period_start DATE NOT NULL,
+    rank_position INTEGER NOT NULL,
+    dapp_name VARCHAR NOT NULL,
+    dapp_type VARCHAR NOT NULL,
+    active_users INTEGER NOT NULL DEFAULT 0,
+    score DOUBLE PRECISION NOT NULL DEFAULT 0,
+    recorded_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (period_start, dapp_name)
+);
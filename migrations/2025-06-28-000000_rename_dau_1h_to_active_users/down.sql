@@ -0,0 +1,14 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE dapp_package_stats RENAME COLUMN interaction_count TO interaction_count_1h;
+ALTER TABLE dapp_package_stats RENAME COLUMN tx_count TO tx_count_1h;
+ALTER TABLE dapp_package_stats RENAME COLUMN active_users TO dau_1h;
+
+ALTER TABLE dapp_ranking_history RENAME COLUMN gas_used TO gas_used_1h;
+ALTER TABLE dapp_ranking_history RENAME COLUMN tx_count TO tx_count_1h;
+ALTER TABLE dapp_ranking_history RENAME COLUMN active_users TO dau_1h;
+
+ALTER TABLE dapp_rankings RENAME COLUMN gas_used TO gas_used_1h;
+ALTER TABLE dapp_rankings RENAME COLUMN tx_count TO tx_count_1h;
+ALTER TABLE dapp_rankings RENAME COLUMN filtered_users TO filtered_users_1h;
+ALTER TABLE dapp_rankings RENAME COLUMN active_users TO dau_1h;
@@ -0,0 +1,16 @@
+-- Your SQL goes here
+
+-- Rankings can be counted over any window (RANKING_WINDOW, recompute --window),
+-- so the ranking figures are no longer named after the 1h window
+ALTER TABLE dapp_rankings RENAME COLUMN dau_1h TO active_users;
+ALTER TABLE dapp_rankings RENAME COLUMN filtered_users_1h TO filtered_users;
+ALTER TABLE dapp_rankings RENAME COLUMN tx_count_1h TO tx_count;
+ALTER TABLE dapp_rankings RENAME COLUMN gas_used_1h TO gas_used;
+
+ALTER TABLE dapp_ranking_history RENAME COLUMN dau_1h TO active_users;
+ALTER TABLE dapp_ranking_history RENAME COLUMN tx_count_1h TO tx_count;
+ALTER TABLE dapp_ranking_history RENAME COLUMN gas_used_1h TO gas_used;
+
+ALTER TABLE dapp_package_stats RENAME COLUMN dau_1h TO active_users;
+ALTER TABLE dapp_package_stats RENAME COLUMN tx_count_1h TO tx_count;
+ALTER TABLE dapp_package_stats RENAME COLUMN interaction_count_1h TO interaction_count;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE dapp_rankings DROP COLUMN IF EXISTS sponsored_user_pct;
+ALTER TABLE dapp_interactions DROP COLUMN IF EXISTS sponsored;
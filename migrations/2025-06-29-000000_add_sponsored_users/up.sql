@@ -0,0 +1,7 @@
+-- Your SQL goes here
+
+-- Whether the interaction's gas was paid by a sponsor rather than the sender
+ALTER TABLE dapp_interactions ADD COLUMN IF NOT EXISTS sponsored BOOLEAN NOT NULL DEFAULT false;
+
+-- Share (%) of the active users whose transactions had their gas sponsored
+ALTER TABLE dapp_rankings ADD COLUMN IF NOT EXISTS sponsored_user_pct DOUBLE PRECISION NOT NULL DEFAULT 0;
@@ -0,0 +1,11 @@
+-- Your SQL goes here
+
+-- Mints, distinct minters and transfers of every NFT DApp over the last 24
+-- hours, decoded from their mint and transfer events. Rewritten on every save
+CREATE TABLE IF NOT EXISTS dapp_nft_stats (
+    dapp_name VARCHAR PRIMARY KEY,
+    mints_24h BIGINT NOT NULL DEFAULT 0,
+    unique_minters_24h INTEGER NOT NULL DEFAULT 0,
+    transfers_24h BIGINT NOT NULL DEFAULT 0,
+    last_update TIMESTAMP NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+
+-- USD value swapped through a DEX or aggregator over the last 24 hours
+ALTER TABLE dapp_rankings ADD COLUMN IF NOT EXISTS volume_24h_usd DOUBLE PRECISION NOT NULL DEFAULT 0;
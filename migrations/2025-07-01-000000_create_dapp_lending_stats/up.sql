@@ -0,0 +1,15 @@
+-- Your SQL goes here
+
+-- Deposits, withdrawals, borrows and repayments of every lending DApp over the
+-- last 24 hours, with distinct depositors and borrowers, decoded from their
+-- lending events. Rewritten on every save
+CREATE TABLE IF NOT EXISTS dapp_lending_stats (
+    dapp_name VARCHAR PRIMARY KEY,
+    deposits_24h BIGINT NOT NULL DEFAULT 0,
+    withdrawals_24h BIGINT NOT NULL DEFAULT 0,
+    borrows_24h BIGINT NOT NULL DEFAULT 0,
+    repays_24h BIGINT NOT NULL DEFAULT 0,
+    unique_depositors_24h INTEGER NOT NULL DEFAULT 0,
+    unique_borrowers_24h INTEGER NOT NULL DEFAULT 0,
+    last_update TIMESTAMP NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,12 @@
+-- Your SQL goes here
+
+-- Inbound and outbound transfers of every bridge over the last 24 hours,
+-- decoded from their events, with the outbound amount in Wormhole's normalized
+-- 8-decimal units summed over all tokens. Rewritten on every save
+CREATE TABLE IF NOT EXISTS bridge_stats (
+    dapp_name VARCHAR PRIMARY KEY,
+    inbound_transfers_24h BIGINT NOT NULL DEFAULT 0,
+    outbound_transfers_24h BIGINT NOT NULL DEFAULT 0,
+    outbound_amount_24h DOUBLE PRECISION NOT NULL DEFAULT 0,
+    last_update TIMESTAMP NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE dapp_package_stats DROP COLUMN IF EXISTS avg_gas_per_tx;
+ALTER TABLE dapp_rankings DROP COLUMN IF EXISTS avg_gas_per_tx;
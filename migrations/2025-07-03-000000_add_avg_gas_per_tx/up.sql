@@ -0,0 +1,5 @@
+-- Your SQL goes here
+
+-- Average gas (MIST) per distinct transaction over the window
+ALTER TABLE dapp_rankings ADD COLUMN IF NOT EXISTS avg_gas_per_tx DOUBLE PRECISION NOT NULL DEFAULT 0;
+ALTER TABLE dapp_package_stats ADD COLUMN IF NOT EXISTS avg_gas_per_tx DOUBLE PRECISION NOT NULL DEFAULT 0;
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE dapp_rankings DROP COLUMN IF EXISTS interactions_p99;
+ALTER TABLE dapp_rankings DROP COLUMN IF EXISTS interactions_p90;
+ALTER TABLE dapp_rankings DROP COLUMN IF EXISTS interactions_p50;
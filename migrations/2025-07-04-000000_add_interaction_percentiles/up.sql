@@ -0,0 +1,6 @@
+-- Your SQL goes here
+
+-- Interactions per active user over the window: median, 90th and 99th percentile
+ALTER TABLE dapp_rankings ADD COLUMN IF NOT EXISTS interactions_p50 INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE dapp_rankings ADD COLUMN IF NOT EXISTS interactions_p90 INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE dapp_rankings ADD COLUMN IF NOT EXISTS interactions_p99 INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+
+-- Share (%) of the interactions made by the most active 1% of users (at least one)
+ALTER TABLE dapp_rankings ADD COLUMN IF NOT EXISTS top_users_interaction_pct DOUBLE PRECISION NOT NULL DEFAULT 0;
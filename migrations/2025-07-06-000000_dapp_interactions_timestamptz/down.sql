@@ -0,0 +1,48 @@
+-- This file should undo anything in `up.sql`
+
+DO $$
+DECLARE
+    partitions TEXT[];
+    partition_name TEXT;
+    day DATE;
+BEGIN
+    SELECT COALESCE(array_agg(child.relname::text), '{}') INTO partitions
+    FROM pg_inherits
+    JOIN pg_class parent ON parent.oid = pg_inherits.inhparent
+    JOIN pg_class child ON child.oid = pg_inherits.inhrelid
+    WHERE parent.relname = 'dapp_interactions';
+
+    FOREACH partition_name IN ARRAY partitions LOOP
+        EXECUTE format('ALTER TABLE dapp_interactions DETACH PARTITION %I', partition_name);
+    END LOOP;
+    DROP TABLE IF EXISTS dapp_interactions;
+
+    CREATE TABLE dapp_interactions (
+        timestamp TIMESTAMP NOT NULL,
+        transaction_digest VARCHAR NOT NULL,
+        interaction_index INTEGER NOT NULL,
+        package_id VARCHAR NOT NULL,
+        dapp_name VARCHAR,
+        sender VARCHAR NOT NULL,
+        gas_used BIGINT NOT NULL,
+        event_type VARCHAR NOT NULL,
+        sponsored BOOLEAN NOT NULL DEFAULT false,
+        PRIMARY KEY (timestamp, transaction_digest, interaction_index)
+    ) PARTITION BY RANGE (timestamp);
+
+    FOREACH partition_name IN ARRAY partitions LOOP
+        day := to_date(right(partition_name, 8), 'YYYYMMDD');
+        EXECUTE format(
+            'ALTER TABLE %I ALTER COLUMN timestamp TYPE TIMESTAMP USING timestamp AT TIME ZONE ''UTC''',
+            partition_name
+        );
+        EXECUTE format(
+            'ALTER TABLE dapp_interactions ATTACH PARTITION %I FOR VALUES FROM (%L) TO (%L)',
+            partition_name,
+            day::timestamp,
+            (day + 1)::timestamp
+        );
+    END LOOP;
+END $$;
+
+CREATE INDEX IF NOT EXISTS idx_dapp_interactions_dapp_timestamp ON dapp_interactions (dapp_name, timestamp);
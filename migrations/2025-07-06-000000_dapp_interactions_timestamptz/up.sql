@@ -0,0 +1,53 @@
+-- Your SQL goes here
+
+-- Interaction times are stored as TIMESTAMPTZ with millisecond precision.
+-- The partition key's type cannot be altered in place, so the daily partitions
+-- are detached, converted (existing values are UTC) and attached to a new
+-- partitioned table with the same UTC day bounds.
+DO $$
+DECLARE
+    partitions TEXT[];
+    partition_name TEXT;
+    day DATE;
+BEGIN
+    SELECT COALESCE(array_agg(child.relname::text), '{}') INTO partitions
+    FROM pg_inherits
+    JOIN pg_class parent ON parent.oid = pg_inherits.inhparent
+    JOIN pg_class child ON child.oid = pg_inherits.inhrelid
+    WHERE parent.relname = 'dapp_interactions';
+
+    FOREACH partition_name IN ARRAY partitions LOOP
+        EXECUTE format('ALTER TABLE dapp_interactions DETACH PARTITION %I', partition_name);
+    END LOOP;
+    DROP TABLE IF EXISTS dapp_interactions;
+
+    CREATE TABLE dapp_interactions (
+        timestamp TIMESTAMPTZ NOT NULL,
+        transaction_digest VARCHAR NOT NULL,
+        -- Position of the interaction among those of its transaction
+        interaction_index INTEGER NOT NULL,
+        package_id VARCHAR NOT NULL,
+        dapp_name VARCHAR,
+        sender VARCHAR NOT NULL,
+        gas_used BIGINT NOT NULL,
+        event_type VARCHAR NOT NULL,
+        sponsored BOOLEAN NOT NULL DEFAULT false,
+        PRIMARY KEY (timestamp, transaction_digest, interaction_index)
+    ) PARTITION BY RANGE (timestamp);
+
+    FOREACH partition_name IN ARRAY partitions LOOP
+        day := to_date(right(partition_name, 8), 'YYYYMMDD');
+        EXECUTE format(
+            'ALTER TABLE %I ALTER COLUMN timestamp TYPE TIMESTAMPTZ USING timestamp AT TIME ZONE ''UTC''',
+            partition_name
+        );
+        EXECUTE format(
+            'ALTER TABLE dapp_interactions ATTACH PARTITION %I FOR VALUES FROM (%L) TO (%L)',
+            partition_name,
+            day::timestamp AT TIME ZONE 'UTC',
+            (day + 1)::timestamp AT TIME ZONE 'UTC'
+        );
+    END LOOP;
+END $$;
+
+CREATE INDEX IF NOT EXISTS idx_dapp_interactions_dapp_timestamp ON dapp_interactions (dapp_name, timestamp);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE dapp_rankings ALTER COLUMN last_update TYPE TIMESTAMP USING last_update AT TIME ZONE 'UTC';
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+
+-- Computation times of rankings are stored with their time zone; existing values are UTC
+ALTER TABLE dapp_rankings ALTER COLUMN last_update TYPE TIMESTAMPTZ USING last_update AT TIME ZONE 'UTC';
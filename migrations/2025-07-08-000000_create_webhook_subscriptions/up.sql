@@ -0,0 +1,35 @@
+-- Your SQL goes here
+
+-- Webhooks scoped to a single DApp, registered through the admin API. A
+-- subscription fires when the DApp's active users move more than threshold_pct
+-- away from baseline_users, the count it last fired at (NULL until the first
+-- rankings after it was created). Deleted subscriptions are deactivated
+CREATE TABLE IF NOT EXISTS webhook_subscriptions (
+    id SERIAL PRIMARY KEY,
+    dapp_name VARCHAR NOT NULL,
+    url VARCHAR NOT NULL,
+    threshold_pct DOUBLE PRECISION NOT NULL,
+    baseline_users INTEGER,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_subscriptions_dapp_name ON webhook_subscriptions (dapp_name) WHERE active;
+
+-- Every notification of a subscription with its delivery state: pending until
+-- the URL answers 2xx (delivered) or the attempts run out (failed)
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id BIGSERIAL PRIMARY KEY,
+    subscription_id INTEGER NOT NULL REFERENCES webhook_subscriptions (id) ON DELETE CASCADE,
+    payload TEXT NOT NULL,
+    status VARCHAR NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error VARCHAR,
+    response_status INTEGER,
+    next_attempt_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    delivered_at TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_due ON webhook_deliveries (next_attempt_at) WHERE status = 'pending';
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_subscription ON webhook_deliveries (subscription_id, created_at DESC);
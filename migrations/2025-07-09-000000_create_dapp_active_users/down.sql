@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE dapp_rankings DROP COLUMN IF EXISTS mau;
+ALTER TABLE dapp_rankings DROP COLUMN IF EXISTS wau;
+DROP INDEX IF EXISTS idx_dapp_user_activity_dapp_day;
+DROP TABLE IF EXISTS dapp_active_users;
@@ -0,0 +1,17 @@
+-- Your SQL goes here
+
+-- Distinct senders per DApp over the last 7 (WAU) and 30 (MAU) days, merged
+-- from the daily sets of dapp_user_activity; DApps idle for 30 days have no row
+CREATE TABLE IF NOT EXISTS dapp_active_users (
+    dapp_name VARCHAR PRIMARY KEY,
+    wau INTEGER NOT NULL DEFAULT 0,
+    mau INTEGER NOT NULL DEFAULT 0,
+    as_of DATE NOT NULL,
+    computed_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_dapp_user_activity_dapp_day
+    ON dapp_user_activity (dapp_name, day);
+
+ALTER TABLE dapp_rankings ADD COLUMN IF NOT EXISTS wau INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE dapp_rankings ADD COLUMN IF NOT EXISTS mau INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+DROP INDEX IF EXISTS idx_daily_user_sketches_dapp_day;
+DROP TABLE IF EXISTS daily_user_sketches;
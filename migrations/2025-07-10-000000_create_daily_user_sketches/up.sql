@@ -0,0 +1,14 @@
+-- Your SQL goes here
+
+-- HyperLogLog sketch of the distinct senders of a DApp per day of the stats timezone
+-- Sketches of several days are merged in Rust to estimate their unique users
+CREATE TABLE IF NOT EXISTS daily_user_sketches (
+    day DATE NOT NULL,
+    dapp_name VARCHAR NOT NULL,
+    sketch BYTEA NOT NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (day, dapp_name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_daily_user_sketches_dapp_day
+    ON daily_user_sketches (dapp_name, day);
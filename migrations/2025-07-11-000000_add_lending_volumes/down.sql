@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE dapp_rankings DROP COLUMN IF EXISTS borrow_volume_24h_usd;
+ALTER TABLE dapp_rankings DROP COLUMN IF EXISTS deposit_volume_24h_usd;
+ALTER TABLE dapp_lending_stats DROP COLUMN IF EXISTS borrow_volume_24h_usd;
+ALTER TABLE dapp_lending_stats DROP COLUMN IF EXISTS deposit_volume_24h_usd;
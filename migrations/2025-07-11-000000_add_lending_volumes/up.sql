@@ -0,0 +1,8 @@
+-- Your SQL goes here
+
+-- USD value deposited into and borrowed from a lending DApp over the last 24
+-- hours, decoded from its deposit and borrow events
+ALTER TABLE dapp_lending_stats ADD COLUMN IF NOT EXISTS deposit_volume_24h_usd DOUBLE PRECISION NOT NULL DEFAULT 0;
+ALTER TABLE dapp_lending_stats ADD COLUMN IF NOT EXISTS borrow_volume_24h_usd DOUBLE PRECISION NOT NULL DEFAULT 0;
+ALTER TABLE dapp_rankings ADD COLUMN IF NOT EXISTS deposit_volume_24h_usd DOUBLE PRECISION NOT NULL DEFAULT 0;
+ALTER TABLE dapp_rankings ADD COLUMN IF NOT EXISTS borrow_volume_24h_usd DOUBLE PRECISION NOT NULL DEFAULT 0;
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE bridge_stats DROP COLUMN IF EXISTS outbound_volume_24h_usd;
+ALTER TABLE bridge_stats DROP COLUMN IF EXISTS inbound_volume_24h_usd;
+ALTER TABLE bridge_stats ADD COLUMN IF NOT EXISTS outbound_amount_24h DOUBLE PRECISION NOT NULL DEFAULT 0;
@@ -0,0 +1,8 @@
+-- Your SQL goes here
+
+-- Bridged amounts are now kept per coin and valued in USD, in both directions.
+-- The old outbound amount summed Wormhole's normalized units over all tokens and
+-- cannot be converted; the table is rewritten on every save anyway
+ALTER TABLE bridge_stats DROP COLUMN IF EXISTS outbound_amount_24h;
+ALTER TABLE bridge_stats ADD COLUMN IF NOT EXISTS inbound_volume_24h_usd DOUBLE PRECISION NOT NULL DEFAULT 0;
+ALTER TABLE bridge_stats ADD COLUMN IF NOT EXISTS outbound_volume_24h_usd DOUBLE PRECISION NOT NULL DEFAULT 0;
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE category_stats DROP COLUMN IF EXISTS volume_24h_usd;
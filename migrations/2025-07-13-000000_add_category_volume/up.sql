@@ -0,0 +1,5 @@
+-- Your SQL goes here
+
+-- USD value swapped through the DApps of a DEX or Aggregator category over the
+-- last 24 hours, the sum of their rankings' volume_24h_usd
+ALTER TABLE category_stats ADD COLUMN IF NOT EXISTS volume_24h_usd DOUBLE PRECISION NOT NULL DEFAULT 0;
@@ -0,0 +1,78 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Rolling weekly and monthly active users.
+//!
+//! Keeping 30 days of interactions in memory to count WAU and MAU is not
+//! feasible. Instead, the first interaction of every sender with a DApp on a
+//! day (in the stats timezone) is persisted in `dapp_user_activity`, so each
+//! DApp has one set of senders per day. Every hour the leader merges the last 7
+//! and 30 daily sets, today included, into distinct user counts stored in
+//! `dapp_active_users` and on the DApp's rows of `dapp_rankings` (`wau`, `mau`).
+//! Rankings saved in between pick up the latest counts.
+
+use crate::clock::Clock;
+use crate::database::DatabaseManager;
+use crate::leader_election::Leadership;
+use crate::ranking_snapshots::duration_until_next_hour;
+use anyhow::Result;
+use chrono::{Days, NaiveDate};
+use chrono_tz::Tz;
+use std::sync::Arc;
+use tracing::{error, info};
+
+/// Days counted in the weekly active users, today included
+pub const WAU_DAYS: u64 = 7;
+
+/// Days counted in the monthly active users, today included
+pub const MAU_DAYS: u64 = 30;
+
+/// First day of the `days` long window ending on `today`
+pub fn window_start(today: NaiveDate, days: u64) -> NaiveDate {
+    today.checked_sub_days(Days::new(days.saturating_sub(1))).unwrap_or(NaiveDate::MIN)
+}
+
+/// Recompute WAU and MAU of every DApp over the windows ending on `today`
+pub async fn update_active_users(db_manager: &DatabaseManager, today: NaiveDate) -> Result<usize> {
+    db_manager
+        .compute_rolling_active_users(window_start(today, WAU_DAYS), window_start(today, MAU_DAYS), today)
+        .await
+}
+
+/// Start the job recomputing WAU and MAU at every full hour
+/// Days are taken in `timezone`; only the leader runs it
+pub async fn start_active_users_job(
+    db_manager: Arc<DatabaseManager>,
+    timezone: Tz,
+    leadership: Leadership,
+    clock: Arc<dyn Clock>,
+) {
+    tokio::spawn(async move {
+        loop {
+            let seconds = clock.now().timestamp().max(0) as u64;
+            tokio::time::sleep(duration_until_next_hour(seconds)).await;
+            if !leadership.is_leader() {
+                continue;
+            }
+
+            let today = clock.now().with_timezone(&timezone).date_naive();
+            match update_active_users(&db_manager, today).await {
+                Ok(dapps) => info!("📆 Updated WAU and MAU of {} DApps", dapps),
+                Err(err) => error!("❌ Failed to update WAU and MAU: {}", err),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_windows_end_on_today() {
+        let today: NaiveDate = "2025-07-09".parse().unwrap();
+        assert_eq!(window_start(today, WAU_DAYS), "2025-07-03".parse::<NaiveDate>().unwrap());
+        assert_eq!(window_start(today, MAU_DAYS), "2025-06-10".parse::<NaiveDate>().unwrap());
+        assert_eq!(window_start(today, 1), today);
+    }
+}
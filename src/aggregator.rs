@@ -0,0 +1,581 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Checkpoint aggregation.
+//!
+//! Ingestion workers extract DApp interactions from checkpoints in parallel
+//! with an `InteractionExtractor`, without touching shared state, and send the
+//! results over a bounded channel. Each worker returns a `CheckpointSummary` of
+//! what it found, which is recorded in per-checkpoint metrics.
+//!
+//! While catching up from an old watermark, checkpoints older than both the
+//! ranking window and the 24h statistics window are neither decoded nor queued: the worker returns right away and the
+//! executor only advances the watermark. A single aggregator task owns every mutation
+//! of the `DAppIndexer` and applies queued checkpoints in batches, so the
+//! indexer lock is taken once per batch instead of once per checkpoint and
+//! workers never wait on each other.
+//!
+//! Applied checkpoints are logged as one line each, for one checkpoint in
+//! CHECKPOINT_LOG_SAMPLE_RATE and for every anomalous one: slow to apply, with
+//! a spike of interactions, or changing the top DApp. The per-DApp breakdown
+//! and the current top DApps are logged at debug level.
+//!
+//! When archiving or interaction persistence is enabled, the interactions of
+//! every applied checkpoint are passed on to the archiver and the interaction
+//! store after the lock is released.
+//!
+//! Rankings are persisted by the aggregator, so a slow database slows it down;
+//! the queue then fills up and workers wait for room, which stops the executor
+//! from consuming further checkpoints instead of piling them up in memory.
+
+use crate::dapp_indexer::{DAppIndexer, ExtractedCheckpoint, InteractionExtractor};
+use crate::database::DatabaseManager;
+use crate::models::DAppInteraction;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use prometheus::{
+    register_histogram_with_registry, register_int_counter_vec_with_registry, register_int_counter_with_registry,
+    register_int_gauge_with_registry, Histogram, IntCounter, IntCounterVec, IntGauge, Registry,
+};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use sui_data_ingestion_core::Worker;
+use sui_types::full_checkpoint_content::CheckpointData;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, instrument, warn, Level};
+
+/// Maximum number of queued checkpoints applied under one indexer lock
+const MAX_BATCH_SIZE: usize = 64;
+
+/// Checkpoints taking longer than this to apply are always logged
+const SLOW_CHECKPOINT: Duration = Duration::from_secs(2);
+
+/// Checkpoints with this many times the average interactions are always logged...
+const INTERACTION_SPIKE_FACTOR: f64 = 10.0;
+/// ...if they have at least this many
+const INTERACTION_SPIKE_MIN: usize = 100;
+
+/// Weight of the latest checkpoint in the moving average of interactions
+const INTERACTION_AVERAGE_WEIGHT: f64 = 0.01;
+
+/// Prometheus metrics of the queue between workers and the aggregator
+#[derive(Clone)]
+pub struct CheckpointQueueMetrics {
+    depth: IntGauge,
+    capacity: IntGauge,
+    throttled: IntCounter,
+    wait_seconds: Histogram,
+}
+
+impl CheckpointQueueMetrics {
+    pub fn new(registry: &Registry) -> Result<Self> {
+        Ok(Self {
+            depth: register_int_gauge_with_registry!(
+                "checkpoint_queue_depth",
+                "Extracted checkpoints waiting for the aggregator",
+                registry
+            )?,
+            capacity: register_int_gauge_with_registry!(
+                "checkpoint_queue_capacity",
+                "Maximum number of extracted checkpoints waiting for the aggregator",
+                registry
+            )?,
+            throttled: register_int_counter_with_registry!(
+                "checkpoint_queue_throttled",
+                "Checkpoints whose worker had to wait for room in the queue",
+                registry
+            )?,
+            wait_seconds: register_histogram_with_registry!(
+                "checkpoint_queue_wait_seconds",
+                "Time workers waited for room in the queue",
+                registry
+            )?,
+        })
+    }
+}
+
+/// Sending half of the checkpoint queue, shared by the ingestion workers
+#[derive(Clone)]
+pub struct CheckpointSender {
+    sender: mpsc::Sender<ExtractedCheckpoint>,
+    metrics: CheckpointQueueMetrics,
+    /// Whether the queue was full on the last send, to log throttling once
+    throttling: Arc<AtomicBool>,
+}
+
+impl CheckpointSender {
+    /// Queue an extracted checkpoint, waiting for room when the aggregator falls behind
+    pub async fn send(&self, checkpoint: ExtractedCheckpoint) -> Result<()> {
+        let checkpoint = match self.sender.try_send(checkpoint) {
+            Ok(()) => {
+                self.metrics.depth.inc();
+                if self.throttling.swap(false, Ordering::Relaxed) {
+                    info!("✅ Checkpoint queue has room again, ingestion resumed at full speed");
+                }
+                return Ok(());
+            }
+            Err(TrySendError::Full(checkpoint)) => checkpoint,
+            Err(TrySendError::Closed(_)) => return Err(anyhow!("Checkpoint aggregator is not running")),
+        };
+
+        if !self.throttling.swap(true, Ordering::Relaxed) {
+            warn!("🐢 Checkpoint queue is full, throttling ingestion until the aggregator catches up");
+        }
+        self.metrics.throttled.inc();
+        let started = Instant::now();
+        self.sender
+            .send(checkpoint)
+            .await
+            .map_err(|_| anyhow!("Checkpoint aggregator is not running"))?;
+        self.metrics.wait_seconds.observe(started.elapsed().as_secs_f64());
+        self.metrics.depth.inc();
+        Ok(())
+    }
+}
+
+/// What a worker found in one checkpoint, returned as its `Worker::Result`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CheckpointSummary {
+    pub checkpoint_number: u64,
+    /// DApp interactions extracted from the checkpoint
+    pub interactions: usize,
+    /// Interactions per DApp (package ID for unnamed packages)
+    pub dapp_interactions: BTreeMap<String, usize>,
+    /// Time spent extracting the checkpoint and queueing it for aggregation
+    pub processing_time: Duration,
+}
+
+impl CheckpointSummary {
+    fn new(checkpoint: &ExtractedCheckpoint, processing_time: Duration) -> Self {
+        let mut dapp_interactions = BTreeMap::new();
+        for interaction in &checkpoint.interactions {
+            let dapp_name = interaction.dapp_name.as_deref().unwrap_or(&interaction.package_id);
+            *dapp_interactions.entry(dapp_name.to_string()).or_default() += 1;
+        }
+        Self {
+            checkpoint_number: checkpoint.checkpoint_number,
+            interactions: checkpoint.interactions.len(),
+            dapp_interactions,
+            processing_time,
+        }
+    }
+}
+
+/// Prometheus metrics of the checkpoints processed by the workers
+#[derive(Clone)]
+pub struct CheckpointWorkerMetrics {
+    interactions: IntCounterVec,
+    processing_seconds: Histogram,
+    skipped: IntCounter,
+}
+
+impl CheckpointWorkerMetrics {
+    pub fn new(registry: &Registry) -> Result<Self> {
+        Ok(Self {
+            interactions: register_int_counter_vec_with_registry!(
+                "checkpoint_worker_interactions",
+                "DApp interactions extracted by the workers, per DApp",
+                &["dapp"],
+                registry
+            )?,
+            processing_seconds: register_histogram_with_registry!(
+                "checkpoint_worker_processing_seconds",
+                "Time workers spent extracting and queueing a checkpoint",
+                registry
+            )?,
+            skipped: register_int_counter_with_registry!(
+                "checkpoint_worker_skipped",
+                "Checkpoints older than the extraction window skipped without extraction",
+                registry
+            )?,
+        })
+    }
+
+    fn record(&self, summary: &CheckpointSummary) {
+        for (dapp_name, count) in &summary.dapp_interactions {
+            self.interactions.with_label_values(&[dapp_name]).inc_by(*count as u64);
+        }
+        self.processing_seconds.observe(summary.processing_time.as_secs_f64());
+    }
+}
+
+/**
+ * DAppIndexerWorker is the main worker that processes each checkpoint for DApp ranking
+ * It implements the Worker trait to handle checkpoint data processing
+ * Extraction runs without any lock; results are handed to the checkpoint aggregator
+ */
+pub struct DAppIndexerWorker {
+    // Lock-free snapshot of the extraction rules
+    extractor: InteractionExtractor,
+    // Queue to the aggregator task that owns the indexer state
+    sender: CheckpointSender,
+    metrics: CheckpointWorkerMetrics,
+    // Whether the last checkpoint was skipped as older than the extraction window
+    catching_up: AtomicBool,
+}
+
+impl DAppIndexerWorker {
+    /// Creates a new DAppIndexerWorker instance
+    ///
+    /// # Arguments
+    /// * `extractor` - Extraction rules shared by all workers
+    /// * `sender` - Queue to the checkpoint aggregator
+    /// * `metrics` - Metrics the summary of every checkpoint is recorded in
+    pub fn new(extractor: InteractionExtractor, sender: CheckpointSender, metrics: CheckpointWorkerMetrics) -> Self {
+        Self {
+            extractor,
+            sender,
+            metrics,
+            catching_up: AtomicBool::new(false),
+        }
+    }
+}
+
+/**
+ * Implementation of the Worker trait for processing checkpoints
+ * This is called for each checkpoint that needs to be processed
+ */
+#[async_trait]
+impl Worker for DAppIndexerWorker {
+    type Result = CheckpointSummary;
+
+    /// Extract DApp interactions from a single checkpoint and queue them for aggregation
+    ///
+    /// # Arguments
+    /// * `checkpoint` - The checkpoint data containing all transactions
+    ///
+    /// # Returns
+    /// * `Result<CheckpointSummary>` - The interactions found, or an error result
+    #[instrument(skip_all, fields(checkpoint = checkpoint.checkpoint_summary.sequence_number))]
+    async fn process_checkpoint(&self, checkpoint: &CheckpointData) -> Result<CheckpointSummary> {
+        let started = Instant::now();
+        let checkpoint_number = checkpoint.checkpoint_summary.sequence_number;
+
+        // Catch-up fast path: nothing to extract, only the watermark moves on
+        if self.extractor.can_skip(checkpoint) {
+            if !self.catching_up.swap(true, Ordering::Relaxed) {
+                info!("⏩ Catching up: skipping checkpoints older than the extraction window from {}", checkpoint_number);
+            }
+            self.metrics.skipped.inc();
+            return Ok(CheckpointSummary { checkpoint_number, processing_time: started.elapsed(), ..Default::default() });
+        }
+        if self.catching_up.swap(false, Ordering::Relaxed) {
+            info!("✅ Reached the extraction window at checkpoint {}", checkpoint_number);
+        }
+
+        let extracted = self.extractor.extract(checkpoint);
+        let mut summary = CheckpointSummary::new(&extracted, Duration::ZERO);
+
+        // Waits only when the aggregator falls behind (bounded queue), which
+        // throttles checkpoint consumption while the database is slow
+        self.sender.send(extracted).await?;
+        summary.processing_time = started.elapsed();
+        self.metrics.record(&summary);
+        Ok(summary)
+    }
+}
+
+/// Create the queue between ingestion workers and the aggregator
+pub fn checkpoint_channel(
+    capacity: usize,
+    metrics: CheckpointQueueMetrics,
+) -> (CheckpointSender, mpsc::Receiver<ExtractedCheckpoint>) {
+    let (sender, receiver) = mpsc::channel(capacity.max(1));
+    metrics.capacity.set(capacity.max(1) as i64);
+    let sender = CheckpointSender {
+        sender,
+        metrics,
+        throttling: Arc::new(AtomicBool::new(false)),
+    };
+    (sender, receiver)
+}
+
+/// Start the task applying extracted checkpoints to the indexer
+/// The task stops once every sender has been dropped and the queue is drained
+///
+/// # Returns
+/// * Handle of the task, finished once the queue is drained
+pub async fn start_checkpoint_aggregator(
+    indexer: Arc<Mutex<DAppIndexer>>,
+    db_manager: Option<Arc<DatabaseManager>>,
+    archive: Option<mpsc::Sender<Vec<DAppInteraction>>>,
+    interaction_store: Option<mpsc::Sender<Vec<DAppInteraction>>>,
+    mut receiver: mpsc::Receiver<ExtractedCheckpoint>,
+    metrics: CheckpointQueueMetrics,
+    log_sample_rate: u64,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut log_sampler = CheckpointLogSampler::new(log_sample_rate);
+        while let Some(first) = receiver.recv().await {
+            // Take whatever else is already queued so it is applied under the same lock
+            let mut batch = vec![first];
+            while batch.len() < MAX_BATCH_SIZE {
+                match receiver.try_recv() {
+                    Ok(checkpoint) => batch.push(checkpoint),
+                    Err(_) => break,
+                }
+            }
+            metrics.depth.sub(batch.len() as i64);
+
+            let keep_interactions = archive.is_some() || interaction_store.is_some();
+            let mut applied = Vec::new();
+            let mut indexer_guard = indexer.lock().await;
+            for checkpoint in batch {
+                let checkpoint_number = checkpoint.checkpoint_number;
+                let started = Instant::now();
+                let interactions = indexer_guard
+                    .apply_checkpoint(checkpoint, db_manager.as_deref())
+                    .await;
+                log_checkpoint_report(checkpoint_number, &interactions, started.elapsed(), &indexer_guard, &mut log_sampler);
+                // Only the leader archives, so replicas do not write the same files
+                if keep_interactions && indexer_guard.is_leader() {
+                    applied.extend(interactions);
+                }
+            }
+            drop(indexer_guard);
+            if applied.is_empty() {
+                continue;
+            }
+
+            // Both wait only when their writer falls behind (bounded queues)
+            if let Some(interaction_store) = &interaction_store {
+                let interactions = if archive.is_some() { applied.clone() } else { std::mem::take(&mut applied) };
+                if interaction_store.send(interactions).await.is_err() {
+                    error!("❌ Interaction store is not running, interactions are not persisted");
+                }
+            }
+            if let Some(archive) = &archive {
+                if archive.send(applied).await.is_err() {
+                    error!("❌ Interaction archiver is not running, interactions are not archived");
+                }
+            }
+        }
+
+        warn!("⚠️ Checkpoint aggregator stopped: all workers disconnected");
+    })
+}
+
+/// Why an applied checkpoint is logged
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LogReason {
+    /// One of every sample rate checkpoints
+    Sampled,
+    /// Took longer than SLOW_CHECKPOINT to apply
+    Slow,
+    /// Far more interactions than usual
+    Spike,
+    /// The top DApp changed
+    NewLeader,
+}
+
+impl LogReason {
+    fn as_str(&self) -> &'static str {
+        match self {
+            LogReason::Sampled => "sampled",
+            LogReason::Slow => "slow",
+            LogReason::Spike => "spike",
+            LogReason::NewLeader => "new_leader",
+        }
+    }
+}
+
+/// Picks the applied checkpoints worth a log line
+#[derive(Debug)]
+struct CheckpointLogSampler {
+    sample_rate: u64,
+    /// Moving average of interactions per checkpoint
+    average_interactions: Option<f64>,
+    /// Top DApp when the previous checkpoint was applied
+    top_dapp: Option<String>,
+}
+
+impl CheckpointLogSampler {
+    fn new(sample_rate: u64) -> Self {
+        Self { sample_rate: sample_rate.max(1), average_interactions: None, top_dapp: None }
+    }
+
+    /// Why the checkpoint should be logged, None if it should not
+    /// Anomalies take precedence over sampling
+    fn reason(
+        &mut self,
+        checkpoint_number: u64,
+        interactions: usize,
+        apply_time: Duration,
+        top_dapp: Option<&str>,
+    ) -> Option<LogReason> {
+        let spike = self.average_interactions.is_some_and(|average| {
+            interactions >= INTERACTION_SPIKE_MIN && interactions as f64 > average * INTERACTION_SPIKE_FACTOR
+        });
+        self.average_interactions = Some(match self.average_interactions {
+            Some(average) => average + (interactions as f64 - average) * INTERACTION_AVERAGE_WEIGHT,
+            None => interactions as f64,
+        });
+        let new_leader = top_dapp.is_some() && self.top_dapp.as_deref() != top_dapp;
+        if new_leader {
+            self.top_dapp = top_dapp.map(str::to_string);
+        }
+
+        if apply_time >= SLOW_CHECKPOINT {
+            Some(LogReason::Slow)
+        } else if spike {
+            Some(LogReason::Spike)
+        } else if new_leader {
+            Some(LogReason::NewLeader)
+        } else if checkpoint_number.is_multiple_of(self.sample_rate) {
+            Some(LogReason::Sampled)
+        } else {
+            None
+        }
+    }
+}
+
+/// Log an applied checkpoint on one line if the sampler picks it, and its
+/// interactions per DApp and the current top DApps at debug level
+fn log_checkpoint_report(
+    checkpoint_number: u64,
+    interactions: &[DAppInteraction],
+    apply_time: Duration,
+    indexer: &DAppIndexer,
+    sampler: &mut CheckpointLogSampler,
+) {
+    let rankings = indexer.get_dapp_rankings();
+    let top = rankings.first();
+    let reason = sampler.reason(
+        checkpoint_number,
+        interactions.len(),
+        apply_time,
+        top.map(|ranking| ranking.dapp_name.as_str()),
+    );
+    let detailed = tracing::enabled!(Level::DEBUG);
+    if reason.is_none() && !detailed {
+        return;
+    }
+
+    let mut dapp_counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for interaction in interactions {
+        let dapp_name = interaction.dapp_name.as_deref().unwrap_or(&interaction.package_id);
+        *dapp_counts.entry(dapp_name).or_default() += 1;
+    }
+
+    // Values are also attached as fields so JSON logs can be queried by them
+    if let Some(reason) = reason {
+        info!(checkpoint = checkpoint_number, interactions = interactions.len(), dapps = dapp_counts.len(),
+              apply_ms = apply_time.as_millis() as u64, top_dapp = top.map(|ranking| ranking.dapp_name.as_str()),
+              top_hau = top.map(|ranking| ranking.active_users), reason = reason.as_str(),
+              "📦 Checkpoint {}: {} interactions across {} DApps in {}ms, top {} ({})",
+              checkpoint_number, interactions.len(), dapp_counts.len(), apply_time.as_millis(),
+              top.map_or("none", |ranking| ranking.dapp_name.as_str()), reason.as_str());
+    }
+
+    if !detailed || interactions.is_empty() {
+        return;
+    }
+    for (dapp_name, count) in &dapp_counts {
+        debug!(checkpoint = checkpoint_number, dapp = %dapp_name, interactions = count,
+               "  📱 {}: {} interactions", dapp_name, count);
+    }
+    for ranking in rankings.iter().take(10) {
+        debug!(rank = ranking.rank, dapp = %ranking.dapp_name, hau = ranking.active_users,
+               "  🏆 {}. {} - {} HAU", ranking.rank, ranking.dapp_name, ranking.active_users);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::CheckpointBuilder;
+
+    fn checkpoint(checkpoint_number: u64) -> ExtractedCheckpoint {
+        ExtractedCheckpoint {
+            checkpoint_number,
+            epoch: 0,
+            interactions: Vec::new(),
+            function_calls: Vec::new(),
+            bridge_transfers: Vec::new(),
+            swap_legs: Vec::new(),
+            lending_flows: Vec::new(),
+            transaction_outcomes: Vec::new(),
+            published_packages: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_worker_summarizes_checkpoint() {
+        const CETUS: &str = "0x6f5e582ede61fe5395b50c4a449ec11479a54d7ff8e0158247adfda60d98970b";
+        const PYTH: &str = "0x04e20ddf36af412a4096f9014f4a565af9e812db9a05cc40254846cf6ed0ad91";
+        let registry = Registry::new();
+        let (sender, mut receiver) = checkpoint_channel(4, CheckpointQueueMetrics::new(&registry).unwrap());
+        let metrics = CheckpointWorkerMetrics::new(&registry).unwrap();
+        let worker = DAppIndexerWorker::new(DAppIndexer::new().extractor(), sender, metrics.clone());
+
+        let data = CheckpointBuilder::new(7)
+            .transaction(1, &[CETUS, PYTH])
+            .transaction(2, &[CETUS])
+            .build();
+        let summary = worker.process_checkpoint(&data).await.unwrap();
+        assert_eq!(summary.checkpoint_number, 7);
+        assert_eq!(summary.interactions, 3);
+        assert_eq!(summary.dapp_interactions, BTreeMap::from([
+            ("Cetus AMM".to_string(), 2),
+            ("Pyth".to_string(), 1),
+        ]));
+        assert_eq!(metrics.interactions.with_label_values(&["Cetus AMM"]).get(), 2);
+        assert_eq!(receiver.recv().await.unwrap().interactions.len(), 3);
+
+        // Checkpoints older than the extraction window are not queued
+        let stale = CheckpointBuilder::new(8).minutes_ago(25 * 60).transaction(1, &[CETUS]).build();
+        let summary = worker.process_checkpoint(&stale).await.unwrap();
+        assert_eq!((summary.checkpoint_number, summary.interactions), (8, 0));
+        assert_eq!(metrics.skipped.get(), 1);
+        assert!(receiver.try_recv().is_err());
+
+        // Older than the ranking window, but needed by the 24h statistics
+        let earlier = CheckpointBuilder::new(9).minutes_ago(90).transaction(1, &[CETUS]).build();
+        let summary = worker.process_checkpoint(&earlier).await.unwrap();
+        assert_eq!((summary.checkpoint_number, summary.interactions), (9, 1));
+        assert_eq!(metrics.skipped.get(), 1);
+        assert_eq!(receiver.recv().await.unwrap().interactions.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_throttles_workers() {
+        let metrics = CheckpointQueueMetrics::new(&Registry::new()).unwrap();
+        let (sender, mut receiver) = checkpoint_channel(1, metrics.clone());
+
+        sender.send(checkpoint(1)).await.unwrap();
+        assert_eq!(metrics.depth.get(), 1);
+
+        // The second checkpoint waits until the aggregator takes the first one
+        let waiting = tokio::spawn({
+            let sender = sender.clone();
+            async move { sender.send(checkpoint(2)).await }
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(!waiting.is_finished());
+
+        assert_eq!(receiver.recv().await.unwrap().checkpoint_number, 1);
+        metrics.depth.dec();
+        waiting.await.unwrap().unwrap();
+        assert_eq!(metrics.throttled.get(), 1);
+        assert_eq!(metrics.depth.get(), 1);
+    }
+
+    #[test]
+    fn test_samples_checkpoint_logs_and_keeps_anomalies() {
+        let fast = Duration::from_millis(5);
+        let mut sampler = CheckpointLogSampler::new(100);
+        assert_eq!(sampler.reason(1, 2, fast, None), None);
+        assert_eq!(sampler.reason(2, 2, fast, Some("Cetus AMM")), Some(LogReason::NewLeader));
+        assert_eq!(sampler.reason(3, 2, fast, Some("Cetus AMM")), None);
+        assert_eq!(sampler.reason(100, 2, fast, Some("Cetus AMM")), Some(LogReason::Sampled));
+        assert_eq!(sampler.reason(101, 2, SLOW_CHECKPOINT, Some("Cetus AMM")), Some(LogReason::Slow));
+        // Far above the average, but too few interactions to matter
+        assert_eq!(sampler.reason(102, 99, fast, Some("Cetus AMM")), None);
+        assert_eq!(sampler.reason(103, 500, fast, Some("Cetus AMM")), Some(LogReason::Spike));
+        assert_eq!(sampler.reason(104, 2, fast, Some("Pyth")), Some(LogReason::NewLeader));
+    }
+}
@@ -0,0 +1,244 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Detection of sudden HAU spikes and drops.
+//!
+//! Every ranking computation compares each DApp's HAU against its trailing
+//! baseline, the HAU of the last computations. A value more than the configured
+//! z-score away from the baseline mean is reported: drops usually mean an
+//! outage or broken attribution, spikes often mean airdrop farming. Anomalies
+//! are logged, counted in Prometheus and optionally posted to a webhook.
+
+use crate::leader_election::Leadership;
+use crate::models::{DAppRanking, OTHERS_DAPP_NAME};
+use anyhow::Result;
+use prometheus::{register_int_counter_vec_with_registry, IntCounterVec, Registry};
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{error, info, warn};
+
+/// Computations a DApp needs in its baseline before it is checked
+const MIN_BASELINE_SAMPLES: usize = 5;
+
+/// Lower bound of the baseline standard deviation, so a DApp with a flat
+/// baseline is not flagged for a change of a single user
+const MIN_STDDEV: f64 = 1.0;
+
+/// Timeout of a single webhook request
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Settings for HAU anomaly detection
+#[derive(Debug, Clone)]
+pub struct AnomalySettings {
+    /// Deviation from the baseline mean, in standard deviations, that is reported
+    pub z_score_threshold: f64,
+    /// Number of past computations forming the baseline
+    pub baseline_window: usize,
+    /// URL anomalies are POSTed to as JSON, if any
+    pub webhook_url: Option<String>,
+    /// HAU below which DApps are collapsed into "Others" and missing from the rankings
+    pub min_ranked_hau: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnomalyDirection {
+    Spike,
+    Drop,
+}
+
+impl AnomalyDirection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnomalyDirection::Spike => "spike",
+            AnomalyDirection::Drop => "drop",
+        }
+    }
+}
+
+/// HAU of a DApp that deviates from its baseline
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DauAnomaly {
+    pub dapp_name: String,
+    pub direction: AnomalyDirection,
+    pub hau: u32,
+    pub baseline_mean: f64,
+    pub baseline_stddev: f64,
+    pub z_score: f64,
+}
+
+/// Trailing HAU baselines of every DApp seen in the rankings
+#[derive(Debug, Clone)]
+pub struct AnomalyDetector {
+    z_score_threshold: f64,
+    baseline_window: usize,
+    min_ranked_hau: u32,
+    /// DApp name -> HAU of the last computations, oldest first
+    history: HashMap<String, VecDeque<u32>>,
+}
+
+impl AnomalyDetector {
+    /// Detector for rankings that leave out DApps below `min_ranked_hau` (0 if none are left out)
+    pub fn new(z_score_threshold: f64, baseline_window: usize, min_ranked_hau: u32) -> Self {
+        Self {
+            z_score_threshold,
+            baseline_window: baseline_window.max(MIN_BASELINE_SAMPLES),
+            min_ranked_hau,
+            history: HashMap::new(),
+        }
+    }
+
+    /// Check the HAU of a ranking computation against the baselines, then add it to them
+    /// DApps missing from the computation had fewer active users than the rankings
+    /// include; that upper bound is used as their HAU, so only clear drops are reported
+    pub fn observe<'a>(&mut self, haus: impl IntoIterator<Item = (&'a str, u32)>) -> Vec<DauAnomaly> {
+        let missing_hau = self.min_ranked_hau.saturating_sub(1);
+        let mut observed: HashMap<String, u32> =
+            self.history.keys().map(|dapp_name| (dapp_name.clone(), missing_hau)).collect();
+        for (dapp_name, hau) in haus {
+            if dapp_name != OTHERS_DAPP_NAME {
+                observed.insert(dapp_name.to_string(), hau);
+            }
+        }
+
+        let mut anomalies = Vec::new();
+        for (dapp_name, hau) in observed {
+            let history = self.history.entry(dapp_name.clone()).or_default();
+            if history.len() >= MIN_BASELINE_SAMPLES {
+                let count = history.len() as f64;
+                let mean = history.iter().map(|&value| value as f64).sum::<f64>() / count;
+                let variance = history.iter().map(|&value| (value as f64 - mean).powi(2)).sum::<f64>() / count;
+                let stddev = variance.sqrt();
+                let z_score = (hau as f64 - mean) / stddev.max(MIN_STDDEV);
+                if z_score.abs() > self.z_score_threshold {
+                    anomalies.push(DauAnomaly {
+                        dapp_name,
+                        direction: if z_score > 0.0 { AnomalyDirection::Spike } else { AnomalyDirection::Drop },
+                        hau,
+                        baseline_mean: mean,
+                        baseline_stddev: stddev,
+                        z_score,
+                    });
+                }
+            }
+
+            history.push_back(hau);
+            if history.len() > self.baseline_window {
+                history.pop_front();
+            }
+        }
+
+        // Forget DApps without any activity in the whole baseline
+        self.history.retain(|_, history| history.iter().any(|&hau| hau > 0));
+        anomalies.sort_by(|a, b| b.z_score.abs().total_cmp(&a.z_score.abs()));
+        anomalies
+    }
+}
+
+/// Prometheus counter of detected anomalies
+#[derive(Clone)]
+pub struct AnomalyMetrics {
+    anomalies: IntCounterVec,
+}
+
+impl AnomalyMetrics {
+    pub fn new(registry: &Registry) -> Result<Self> {
+        Ok(Self {
+            anomalies: register_int_counter_vec_with_registry!(
+                "dapp_hau_anomalies",
+                "HAU spikes and drops detected per DApp",
+                &["dapp", "direction"],
+                registry
+            )?,
+        })
+    }
+}
+
+/// POST the anomalies of one computation to the webhook
+async fn send_webhook(client: &reqwest::Client, url: &str, anomalies: &[DauAnomaly]) -> Result<()> {
+    let lines: Vec<String> = anomalies.iter().map(describe).collect();
+    let payload = json!({
+        "text": format!("HAU anomalies detected:\n{}", lines.join("\n")),
+        "anomalies": anomalies,
+    });
+    client.post(url).json(&payload).send().await?.error_for_status()?;
+    Ok(())
+}
+
+fn describe(anomaly: &DauAnomaly) -> String {
+    format!(
+        "{} {}: {} HAU vs baseline {:.1} ± {:.1} (z = {:.1})",
+        anomaly.dapp_name,
+        anomaly.direction.as_str(),
+        anomaly.hau,
+        anomaly.baseline_mean,
+        anomaly.baseline_stddev,
+        anomaly.z_score
+    )
+}
+
+/// Start a background task checking every ranking computation for anomalies
+/// Only the leader posts to the webhook, so replicas do not alert twice
+pub async fn start_anomaly_detection_job(
+    mut rankings_updates: watch::Receiver<Arc<Vec<DAppRanking>>>,
+    settings: AnomalySettings,
+    metrics: AnomalyMetrics,
+    leadership: Leadership,
+) -> Result<()> {
+    let client = reqwest::Client::builder().timeout(WEBHOOK_TIMEOUT).build()?;
+    let mut detector = AnomalyDetector::new(settings.z_score_threshold, settings.baseline_window, settings.min_ranked_hau);
+    info!("🚨 Starting HAU anomaly detection (|z| > {}, baseline of {} computations)",
+          settings.z_score_threshold, settings.baseline_window);
+
+    tokio::spawn(async move {
+        while rankings_updates.changed().await.is_ok() {
+            let rankings = rankings_updates.borrow_and_update().clone();
+            let anomalies = detector.observe(rankings.iter().map(|ranking| (ranking.dapp_name.as_str(), ranking.active_users)));
+            if anomalies.is_empty() {
+                continue;
+            }
+
+            for anomaly in &anomalies {
+                warn!("🚨 HAU {}", describe(anomaly));
+                metrics
+                    .anomalies
+                    .with_label_values(&[&anomaly.dapp_name, anomaly.direction.as_str()])
+                    .inc();
+            }
+            if let Some(url) = settings.webhook_url.as_ref().filter(|_| leadership.is_leader()) {
+                if let Err(err) = send_webhook(&client, url, &anomalies).await {
+                    error!("❌ Failed to send anomaly webhook: {}", err);
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reports_spikes_and_drops_against_baseline() {
+        let mut detector = AnomalyDetector::new(3.0, 10, 0);
+        for hau in [100, 104, 96, 102, 98] {
+            assert!(detector.observe([("Cetus AMM", hau), ("Pyth", 50)]).is_empty());
+        }
+
+        // Within the noise of the baseline
+        assert!(detector.observe([("Cetus AMM", 105), ("Pyth", 50)]).is_empty());
+
+        // Cetus spikes, Pyth disappears from the rankings
+        let anomalies = detector.observe([("Cetus AMM", 400)]);
+        let found: Vec<(&str, AnomalyDirection)> = anomalies
+            .iter()
+            .map(|anomaly| (anomaly.dapp_name.as_str(), anomaly.direction))
+            .collect();
+        assert_eq!(found, [("Cetus AMM", AnomalyDirection::Spike), ("Pyth", AnomalyDirection::Drop)]);
+    }
+}
@@ -0,0 +1,752 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! HTTP API serving DApp rankings and category statistics from the database.
+//!
+//! Endpoints:
+//! - `GET /rankings?offset=N&limit=N&sort=score&order=desc&dapp_type=DEX&min_dau=N&window=wau` - current
+//!   DApp rankings, all parameters optional; `window` selects a ranking profile or recomputed window
+//!   instead of the live rankings; the number of matches is returned in `X-Total-Count`
+//! - `GET /categories` - latest per-category statistics
+//! - `GET /categories/{dapp_type}/history?limit=N` - statistics history of one category, one snapshot
+//!   per finished hour over the last 90 days
+//! - `GET /epochs?epoch=N` - active users and transactions per DApp in one Sui epoch (default latest)
+//! - `GET /retention?window=d1|d7|d30` - DApps ranked by return rate of their latest complete cohort
+//! - `GET /dapps/{dapp_name}` - current rankings of one DApp's packages, 404 if it is not ranked
+//! - `GET /dapps/{dapp_name}/packages` - current activity of each package of one DApp over the ranking window
+//! - `GET /dapps/{dapp_name}/events` - 24h interaction counts per event type of one DApp
+//! - `GET /dapps/{dapp_name}/functions` - most called Move functions of one DApp over 24h
+//! - `GET /dapps/{dapp_name}/hourly-activity` - distinct users per hour of the day (UTC) over 7 days
+//! - `GET /dapps/{dapp_name}/nft` - 24h mints, unique minters and transfers of an NFT DApp, 404 without any
+//! - `GET /dapps/{dapp_name}/lending` - 24h deposits, withdrawals, borrows and repayments of a lending DApp, 404 without any
+//! - `GET /dapps/{dapp_name}/bridge` - 24h inbound and outbound transfers of a bridge, 404 without any
+//! - `GET /dapps/{dapp_name}/retention?limit=N` - retention cohorts of one DApp
+//! - `GET /dapps/{dapp_name}/unique-users?from=YYYY-MM-DD&to=YYYY-MM-DD` - estimated distinct users
+//!   of one DApp over a range of days, merged from its daily sketches (see `user_sketch`)
+//! - `GET /dapps/search?q=cet&limit=N` - DApps matching a name, for autocomplete
+//! - `POST /graphql` - GraphQL queries (see `graphql`), `GET /graphql` serves GraphiQL
+//!
+//! Rankings carry the DApp's metadata (`website_url`, `icon_url`, `twitter`), null when unset.
+//!
+//! When API keys or an anonymous rate limit are configured (see `rate_limit`),
+//! these endpoints require `X-API-Key` and answer 429 with `Retry-After` once
+//! the caller's requests per minute are used up.
+//!
+//! GET responses other than GraphQL are cached in memory until the next ranking
+//! update (see `response_cache`) and carry an `ETag` for `If-None-Match`.
+//!
+//! Admin endpoints (mounted only when an admin token is configured, require
+//! `Authorization: Bearer <token>`):
+//! - `GET /admin/runtime` - current runtime-tunable settings
+//! - `PUT /admin/runtime` - update runtime-tunable settings
+//! - `POST /admin/dapps` - track a package (`{"package_id", "dapp_name", "dapp_type"}`)
+//! - `DELETE /admin/dapps/{package_id}` - stop tracking a package
+//! - `POST /admin/recompute` - recompute and persist the rankings now
+//! - `POST /admin/webhooks` - notify a URL when a DApp's active users change by more than a
+//!   percentage (`{"dapp_name", "url", "threshold_pct"}`, see `dapp_webhooks`)
+//! - `GET /admin/webhooks?dapp_name=X` - active webhook subscriptions
+//! - `DELETE /admin/webhooks/{id}` - delete a webhook subscription
+//! - `GET /admin/webhooks/{id}/deliveries?limit=N` - latest notifications of a subscription and their delivery status
+//!
+//! Registry changes are stored in `dapp_registry` and recompute the rankings
+//! immediately; they need the API to run inside the indexer that is the current
+//! leader (503 otherwise).
+//!
+//! Profiling endpoints (mounted only when profiling is enabled, same auth as admin):
+//! - `GET /debug/pprof/profile?seconds=N&frequency=HZ&format=pprof|flamegraph` - CPU profile
+//! - `GET /debug/pprof/heap` - jemalloc heap profile (requires the `heap-profiling` feature)
+
+use crate::dapp_search::{merge_results, DAppNameIndex, DAppSearchResult};
+use crate::dapp_indexer::DAppIndexer;
+use crate::database::DatabaseManager;
+use crate::graphql::{self, RankingSchema};
+use crate::models::{
+    CategoryStatsRecord, DAppEventStatsRecord, DAppFunctionStatsRecord, DAppHourlyActivityRecord, DAppMetadata, DAppPackageStatsRecord,
+    DAppRankingRecord, DAppRegistryRecord, DAppRetentionRecord, EpochStatsRecord, NewWebhookSubscription, WebhookDeliveryRecord,
+    WebhookSubscriptionRecord,
+};
+use crate::profiling::{self, CpuProfileFormat};
+use crate::rate_limit::{ApiRateLimiter, RateLimitDecision, API_KEY_HEADER};
+use crate::response_cache::ResponseCache;
+use crate::ranking_query::{RankingFilter, RankingSortColumn, SortOrder, MAX_PAGE_SIZE};
+use crate::retention::RetentionWindow;
+use crate::runtime_config::{RuntimeConfig, RuntimeSettings, RuntimeSettingsUpdate};
+use crate::suins::SuinsResolver;
+use crate::user_sketch::union_estimate;
+use axum::extract::{ConnectInfo, Path, Query, Request, State};
+use axum::http::{header, Method, StatusCode};
+use axum::middleware::{self, Next};
+use async_graphql::http::GraphiQLSource;
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::{delete, get, post};
+use axum::{Json, Router};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use sui_types::base_types::ObjectID;
+use tokio::sync::Mutex;
+use tracing::{error, info};
+
+/// Shared state of the API handlers
+#[derive(Clone)]
+pub struct ApiState {
+    pub db_manager: Arc<DatabaseManager>,
+    pub runtime_config: RuntimeConfig,
+    pub admin_token: Option<String>,
+    pub pprof_enabled: bool,
+    /// Tracked DApp names searched before the database
+    pub dapp_index: Arc<DAppNameIndex>,
+    /// Resolves top users to SuiNS names in GraphQL responses, if enabled
+    pub suins_resolver: Option<Arc<SuinsResolver>>,
+    /// Running indexer updated by registry changes, None when serving from the database only
+    pub indexer: Option<Arc<Mutex<DAppIndexer>>>,
+    /// API keys and rate limits of the public endpoints
+    pub rate_limiter: Arc<ApiRateLimiter>,
+    /// Cached GET responses, None when caching is disabled
+    pub response_cache: Option<ResponseCache>,
+}
+
+/// Error returned by handlers, rendered as a 500 response
+pub struct ApiError(anyhow::Error);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        error!("❌ API request failed: {}", self.0);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error").into_response()
+    }
+}
+
+impl<E: Into<anyhow::Error>> From<E> for ApiError {
+    fn from(err: E) -> Self {
+        Self(err.into())
+    }
+}
+
+/// A ranking with the metadata of its DApp
+#[derive(Debug, Serialize)]
+pub struct RankingResponse {
+    #[serde(flatten)]
+    pub ranking: DAppRankingRecord,
+    #[serde(flatten)]
+    pub metadata: DAppMetadata,
+}
+
+/// Join the stored metadata of each DApp into its rankings
+async fn with_metadata(db_manager: &DatabaseManager, rankings: Vec<DAppRankingRecord>) -> anyhow::Result<Vec<RankingResponse>> {
+    let mut dapp_names: Vec<String> = rankings.iter().map(|ranking| ranking.dapp_name.clone()).collect();
+    dapp_names.sort();
+    dapp_names.dedup();
+    let metadata: HashMap<String, DAppMetadata> = db_manager
+        .get_dapp_metadata(&dapp_names)
+        .await?
+        .into_iter()
+        .map(|record| (record.dapp_name.clone(), DAppMetadata::from(record)))
+        .collect();
+
+    Ok(rankings
+        .into_iter()
+        .map(|ranking| RankingResponse {
+            metadata: metadata.get(&ranking.dapp_name).cloned().unwrap_or_default(),
+            ranking,
+        })
+        .collect())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HistoryParams {
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RankingParams {
+    pub offset: Option<i64>,
+    pub limit: Option<i64>,
+    pub sort: Option<String>,
+    pub order: Option<String>,
+    pub dapp_type: Option<String>,
+    pub min_dau: Option<i32>,
+    pub window: Option<String>,
+}
+
+impl RankingParams {
+    fn into_filter(self) -> anyhow::Result<RankingFilter> {
+        let sort_by = self.sort.as_deref().map(str::parse::<RankingSortColumn>).transpose()?.unwrap_or_default();
+        Ok(RankingFilter {
+            dapp_type: self.dapp_type,
+            min_dau: self.min_dau,
+            window: self.window,
+            sort_by,
+            order: self.order.as_deref().map(str::parse::<SortOrder>).transpose()?,
+            offset: self.offset.unwrap_or(0),
+            // All rankings unless a page size is requested
+            limit: self.limit.unwrap_or(MAX_PAGE_SIZE),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchParams {
+    pub q: String,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EpochParams {
+    pub epoch: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RetentionParams {
+    pub window: Option<String>,
+}
+
+/// Longest range of days of `GET /dapps/{dapp_name}/unique-users`
+const MAX_UNIQUE_USERS_DAYS: i64 = 366;
+
+#[derive(Debug, Deserialize)]
+pub struct UniqueUsersParams {
+    pub from: NaiveDate,
+    /// Last day of the range, `from` if unset
+    pub to: Option<NaiveDate>,
+}
+
+/// Estimated distinct users of a DApp over a range of days
+#[derive(Debug, Serialize)]
+pub struct UniqueUsersResponse {
+    pub dapp_name: String,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// Days of the range with activity
+    pub active_days: usize,
+    pub unique_users: u64,
+}
+
+/// Body of `POST /admin/dapps`
+#[derive(Debug, Deserialize)]
+pub struct RegistryEntryRequest {
+    pub package_id: String,
+    pub dapp_name: String,
+    pub dapp_type: String,
+}
+
+/// Body of `POST /admin/webhooks`
+#[derive(Debug, Deserialize)]
+pub struct WebhookSubscriptionRequest {
+    pub dapp_name: String,
+    pub url: String,
+    /// Change of active users, in percent, that fires the webhook
+    pub threshold_pct: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookListParams {
+    pub dapp_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CpuProfileParams {
+    pub seconds: Option<u64>,
+    pub frequency: Option<i32>,
+    pub format: Option<String>,
+}
+
+/// Build the API router
+pub fn router(state: ApiState) -> Router {
+    let mut router = Router::new()
+        .route("/rankings", get(get_rankings))
+        .route("/categories", get(get_categories))
+        .route("/categories/{dapp_type}/history", get(get_category_history))
+        .route("/epochs", get(get_epoch_stats))
+        .route("/retention", get(get_retention_ranking))
+        .route("/dapps/search", get(search_dapps))
+        .route("/dapps/{dapp_name}", get(get_dapp_ranking))
+        .route("/dapps/{dapp_name}/packages", get(get_dapp_packages))
+        .route("/dapps/{dapp_name}/events", get(get_dapp_events))
+        .route("/dapps/{dapp_name}/functions", get(get_dapp_functions))
+        .route("/dapps/{dapp_name}/hourly-activity", get(get_dapp_hourly_activity))
+        .route("/dapps/{dapp_name}/nft", get(get_dapp_nft_stats))
+        .route("/dapps/{dapp_name}/lending", get(get_dapp_lending_stats))
+        .route("/dapps/{dapp_name}/bridge", get(get_dapp_bridge_stats))
+        .route("/dapps/{dapp_name}/retention", get(get_dapp_retention))
+        .route("/dapps/{dapp_name}/unique-users", get(get_dapp_unique_users))
+        .route_layer(middleware::from_fn_with_state(state.clone(), cache_responses))
+        .merge(
+            Router::new()
+                .route("/graphql", get(graphiql).post(graphql_query))
+                .with_state(graphql::build_schema(state.db_manager.clone(), state.suins_resolver.clone())),
+        );
+    if state.rate_limiter.is_enabled() {
+        router = router.route_layer(middleware::from_fn_with_state(state.clone(), limit_requests));
+    }
+
+    if state.admin_token.is_some() {
+        let mut admin = Router::new()
+            .route("/admin/runtime", get(get_runtime_settings).put(update_runtime_settings))
+            .route("/admin/dapps", post(add_registry_entry))
+            .route("/admin/dapps/{package_id}", delete(remove_registry_entry))
+            .route("/admin/recompute", post(recompute_rankings))
+            .route("/admin/webhooks", get(list_webhook_subscriptions).post(create_webhook_subscription))
+            .route("/admin/webhooks/{id}", delete(delete_webhook_subscription))
+            .route("/admin/webhooks/{id}/deliveries", get(get_webhook_deliveries));
+        if state.pprof_enabled {
+            admin = admin
+                .route("/debug/pprof/profile", get(get_cpu_profile))
+                .route("/debug/pprof/heap", get(get_heap_profile));
+        }
+        let admin = admin
+            .route_layer(middleware::from_fn_with_state(state.clone(), require_admin_token));
+        router = router.merge(admin);
+    }
+
+    router.with_state(state)
+}
+
+/// Reject requests with a missing or unknown API key, or over their rate limit
+async fn limit_requests(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    let api_key = request.headers().get(API_KEY_HEADER).and_then(|value| value.to_str().ok());
+    let client_ip = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(address)| address.ip().to_string())
+        .unwrap_or_default();
+
+    match state.rate_limiter.check(api_key, &client_ip, Instant::now()) {
+        RateLimitDecision::Allowed => next.run(request).await,
+        RateLimitDecision::Unauthorized => (StatusCode::UNAUTHORIZED, "Missing or unknown API key").into_response(),
+        RateLimitDecision::Limited(retry_after) => (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(header::RETRY_AFTER, retry_after.as_secs_f64().ceil().to_string())],
+            "Rate limit exceeded",
+        )
+            .into_response(),
+    }
+}
+
+/// Serve GET requests from the response cache, caching successful responses
+/// until the next ranking update is due
+async fn cache_responses(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    let Some(cache) = state.response_cache.clone().filter(|_| request.method() == Method::GET) else {
+        return next.run(request).await;
+    };
+    let key = request.uri().to_string();
+    let if_none_match = request.headers().get(header::IF_NONE_MATCH).cloned();
+    if let Some(cached) = cache.get(&key).await {
+        return cached.to_response(if_none_match.as_ref());
+    }
+
+    let response = next.run(request).await;
+    let ttl = state.runtime_config.current().ranking_interval;
+    match cache.store(key, response, ttl).await {
+        Ok(cached) => cached.to_response(if_none_match.as_ref()),
+        Err(response) => response,
+    }
+}
+
+/// Drop cached responses after rankings changed out of schedule
+fn invalidate_cached_responses(state: &ApiState) {
+    if let Some(cache) = &state.response_cache {
+        cache.invalidate_all();
+    }
+}
+
+/// Reject admin requests without the configured bearer token
+async fn require_admin_token(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match (&state.admin_token, provided) {
+        (Some(expected), Some(provided)) if expected == provided => next.run(request).await,
+        _ => StatusCode::UNAUTHORIZED.into_response(),
+    }
+}
+
+async fn get_rankings(
+    State(state): State<ApiState>,
+    Query(params): Query<RankingParams>,
+) -> Result<Response, ApiError> {
+    let filter = match params.into_filter() {
+        Ok(filter) => filter,
+        Err(err) => return Ok((StatusCode::BAD_REQUEST, err.to_string()).into_response()),
+    };
+    let page = state.db_manager.query_rankings(&filter).await?;
+    let rankings = with_metadata(&state.db_manager, page.rankings).await?;
+    Ok(([("x-total-count", page.total.to_string())], Json(rankings)).into_response())
+}
+
+async fn get_dapp_ranking(
+    State(state): State<ApiState>,
+    Path(dapp_name): Path<String>,
+) -> Result<Response, ApiError> {
+    let rankings = state.db_manager.get_dapp_ranking(&dapp_name).await?;
+    if rankings.is_empty() {
+        return Ok((StatusCode::NOT_FOUND, format!("DApp '{}' is not ranked", dapp_name)).into_response());
+    }
+    Ok(Json(with_metadata(&state.db_manager, rankings).await?).into_response())
+}
+
+async fn get_dapp_packages(
+    State(state): State<ApiState>,
+    Path(dapp_name): Path<String>,
+) -> Result<Json<Vec<DAppPackageStatsRecord>>, ApiError> {
+    Ok(Json(state.db_manager.get_package_stats(&dapp_name).await?))
+}
+
+async fn get_dapp_events(
+    State(state): State<ApiState>,
+    Path(dapp_name): Path<String>,
+) -> Result<Json<Vec<DAppEventStatsRecord>>, ApiError> {
+    Ok(Json(state.db_manager.get_event_stats(&dapp_name).await?))
+}
+
+async fn get_dapp_functions(
+    State(state): State<ApiState>,
+    Path(dapp_name): Path<String>,
+) -> Result<Json<Vec<DAppFunctionStatsRecord>>, ApiError> {
+    Ok(Json(state.db_manager.get_function_stats(&dapp_name).await?))
+}
+
+async fn get_dapp_hourly_activity(
+    State(state): State<ApiState>,
+    Path(dapp_name): Path<String>,
+) -> Result<Json<Vec<DAppHourlyActivityRecord>>, ApiError> {
+    Ok(Json(state.db_manager.get_hourly_activity(&dapp_name).await?))
+}
+
+async fn get_dapp_nft_stats(
+    State(state): State<ApiState>,
+    Path(dapp_name): Path<String>,
+) -> Result<Response, ApiError> {
+    match state.db_manager.get_nft_stats(&dapp_name).await? {
+        Some(stats) => Ok(Json(stats).into_response()),
+        None => Ok((StatusCode::NOT_FOUND, format!("DApp '{}' has no NFT activity in the last 24h", dapp_name)).into_response()),
+    }
+}
+
+async fn get_dapp_lending_stats(
+    State(state): State<ApiState>,
+    Path(dapp_name): Path<String>,
+) -> Result<Response, ApiError> {
+    match state.db_manager.get_lending_stats(&dapp_name).await? {
+        Some(stats) => Ok(Json(stats).into_response()),
+        None => Ok((StatusCode::NOT_FOUND, format!("DApp '{}' has no lending activity in the last 24h", dapp_name)).into_response()),
+    }
+}
+
+async fn get_dapp_bridge_stats(
+    State(state): State<ApiState>,
+    Path(dapp_name): Path<String>,
+) -> Result<Response, ApiError> {
+    match state.db_manager.get_bridge_stats(&dapp_name).await? {
+        Some(stats) => Ok(Json(stats).into_response()),
+        None => Ok((StatusCode::NOT_FOUND, format!("DApp '{}' has no bridge transfers in the last 24h", dapp_name)).into_response()),
+    }
+}
+
+async fn get_categories(State(state): State<ApiState>) -> Result<Json<Vec<CategoryStatsRecord>>, ApiError> {
+    Ok(Json(state.db_manager.get_latest_category_stats().await?))
+}
+
+async fn get_epoch_stats(
+    State(state): State<ApiState>,
+    Query(params): Query<EpochParams>,
+) -> Result<Json<Vec<EpochStatsRecord>>, ApiError> {
+    Ok(Json(state.db_manager.get_epoch_stats(params.epoch).await?))
+}
+
+async fn get_category_history(
+    State(state): State<ApiState>,
+    Path(dapp_type): Path<String>,
+    Query(params): Query<HistoryParams>,
+) -> Result<Json<Vec<CategoryStatsRecord>>, ApiError> {
+    let limit = params.limit.unwrap_or(100).clamp(1, 1000);
+    Ok(Json(state.db_manager.get_category_stats_history(&dapp_type, limit).await?))
+}
+
+async fn get_retention_ranking(
+    State(state): State<ApiState>,
+    Query(params): Query<RetentionParams>,
+) -> Result<Response, ApiError> {
+    let window = match params.window.as_deref().unwrap_or("d7").parse::<RetentionWindow>() {
+        Ok(window) => window,
+        Err(err) => return Ok((StatusCode::BAD_REQUEST, err.to_string()).into_response()),
+    };
+    Ok(Json(state.db_manager.get_retention_ranking(window).await?).into_response())
+}
+
+async fn get_dapp_retention(
+    State(state): State<ApiState>,
+    Path(dapp_name): Path<String>,
+    Query(params): Query<HistoryParams>,
+) -> Result<Json<Vec<DAppRetentionRecord>>, ApiError> {
+    let limit = params.limit.unwrap_or(30).clamp(1, 365);
+    Ok(Json(state.db_manager.get_retention(&dapp_name, limit).await?))
+}
+
+async fn get_dapp_unique_users(
+    State(state): State<ApiState>,
+    Path(dapp_name): Path<String>,
+    Query(params): Query<UniqueUsersParams>,
+) -> Result<Response, ApiError> {
+    let to = params.to.unwrap_or(params.from);
+    let days = (to - params.from).num_days() + 1;
+    if !(1..=MAX_UNIQUE_USERS_DAYS).contains(&days) {
+        let message = format!("'to' must be between 'from' and {} days after it", MAX_UNIQUE_USERS_DAYS - 1);
+        return Ok((StatusCode::BAD_REQUEST, message).into_response());
+    }
+    let sketches = state.db_manager.get_daily_user_sketches(&dapp_name, params.from, to).await?;
+    let unique_users = union_estimate(sketches.iter().map(|record| record.sketch.as_slice()))?;
+    Ok(Json(UniqueUsersResponse { dapp_name, from: params.from, to, active_days: sketches.len(), unique_users }).into_response())
+}
+
+/// Tracked DApps first, then ranked DApps found in the database (including fuzzy matches)
+async fn search_dapps(
+    State(state): State<ApiState>,
+    Query(params): Query<SearchParams>,
+) -> Result<Json<Vec<DAppSearchResult>>, ApiError> {
+    let query = params.q.trim();
+    let limit = params.limit.unwrap_or(10).clamp(1, 50);
+    if query.is_empty() {
+        return Ok(Json(Vec::new()));
+    }
+
+    let results = state.dapp_index.search(query, limit);
+    if results.len() >= limit {
+        return Ok(Json(results));
+    }
+    let ranked = state.db_manager.search_dapps(query, limit as i64).await?;
+    Ok(Json(merge_results(results, ranked, limit)))
+}
+
+async fn graphql_query(
+    State(schema): State<RankingSchema>,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    Json(schema.execute(request).await)
+}
+
+async fn graphiql() -> Html<String> {
+    Html(GraphiQLSource::build().endpoint("/graphql").finish())
+}
+
+async fn get_runtime_settings(State(state): State<ApiState>) -> Json<RuntimeSettings> {
+    Json(state.runtime_config.current())
+}
+
+async fn update_runtime_settings(
+    State(state): State<ApiState>,
+    Json(update): Json<RuntimeSettingsUpdate>,
+) -> Response {
+    match state.runtime_config.update(&update) {
+        Ok(settings) => Json(settings).into_response(),
+        Err(err) => (StatusCode::BAD_REQUEST, err.to_string()).into_response(),
+    }
+}
+
+fn indexer_unavailable() -> Response {
+    (StatusCode::SERVICE_UNAVAILABLE, "Registry changes need the API to run inside the indexer").into_response()
+}
+
+fn standby_instance() -> Response {
+    (StatusCode::SERVICE_UNAVAILABLE, "This instance is a standby; send admin writes to the leader").into_response()
+}
+
+async fn add_registry_entry(
+    State(state): State<ApiState>,
+    Json(request): Json<RegistryEntryRequest>,
+) -> Result<Response, ApiError> {
+    let Some(indexer) = &state.indexer else {
+        return Ok(indexer_unavailable());
+    };
+    let Ok(package_id) = ObjectID::from_str(request.package_id.trim()) else {
+        return Ok((StatusCode::BAD_REQUEST, format!("Invalid package ID '{}'", request.package_id)).into_response());
+    };
+    let (dapp_name, dapp_type) = (request.dapp_name.trim(), request.dapp_type.trim());
+    if dapp_name.is_empty() || dapp_type.is_empty() {
+        return Ok((StatusCode::BAD_REQUEST, "dapp_name and dapp_type must not be empty").into_response());
+    }
+
+    let record = DAppRegistryRecord {
+        package_id: package_id.to_string(),
+        dapp_name: dapp_name.to_string(),
+        dapp_type: dapp_type.to_string(),
+        active: true,
+        updated_at: chrono::Utc::now().naive_utc(),
+    };
+    let mut indexer = indexer.lock().await;
+    if !indexer.is_leader() {
+        return Ok(standby_instance());
+    }
+    state.db_manager.save_registry_entry(&record).await?;
+    indexer.upsert_registry_entry(&record.package_id, &record.dapp_name, &record.dapp_type);
+    indexer.recompute_rankings_now(&state.db_manager).await?;
+    invalidate_cached_responses(&state);
+    Ok(Json(record).into_response())
+}
+
+async fn remove_registry_entry(
+    State(state): State<ApiState>,
+    Path(package_id): Path<String>,
+) -> Result<Response, ApiError> {
+    let Some(indexer) = &state.indexer else {
+        return Ok(indexer_unavailable());
+    };
+    let Ok(package_id) = ObjectID::from_str(package_id.trim()) else {
+        return Ok((StatusCode::BAD_REQUEST, format!("Invalid package ID '{}'", package_id)).into_response());
+    };
+    let package_id = package_id.to_string();
+
+    let mut indexer = indexer.lock().await;
+    if !indexer.is_leader() {
+        return Ok(standby_instance());
+    }
+    let Some(entry) = indexer.dapp_names.get(&package_id).cloned() else {
+        return Ok((StatusCode::NOT_FOUND, format!("Package {} is not tracked", package_id)).into_response());
+    };
+    let record = DAppRegistryRecord {
+        package_id,
+        dapp_name: entry.dapp_name,
+        dapp_type: entry.dapp_type,
+        active: false,
+        updated_at: chrono::Utc::now().naive_utc(),
+    };
+    state.db_manager.save_registry_entry(&record).await?;
+
+    indexer.remove_registry_entry(&record.package_id);
+    indexer.recompute_rankings_now(&state.db_manager).await?;
+    invalidate_cached_responses(&state);
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+async fn recompute_rankings(State(state): State<ApiState>) -> Result<Response, ApiError> {
+    let Some(indexer) = &state.indexer else {
+        return Ok(indexer_unavailable());
+    };
+
+    let mut indexer = indexer.lock().await;
+    if !indexer.is_leader() {
+        return Ok(standby_instance());
+    }
+    indexer.recompute_rankings_now(&state.db_manager).await?;
+    invalidate_cached_responses(&state);
+    info!("🔁 Rankings recomputed on admin request");
+    Ok(Json(serde_json::json!({ "rankings": indexer.get_dapp_rankings().len() })).into_response())
+}
+
+async fn create_webhook_subscription(
+    State(state): State<ApiState>,
+    Json(request): Json<WebhookSubscriptionRequest>,
+) -> Result<Response, ApiError> {
+    let Some(dapp_name) = state.dapp_index.get(&request.dapp_name) else {
+        return Ok((StatusCode::NOT_FOUND, format!("DApp '{}' is not tracked", request.dapp_name)).into_response());
+    };
+    let url = request.url.trim();
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Ok((StatusCode::BAD_REQUEST, "url must be an HTTP/HTTPS URL").into_response());
+    }
+    if !request.threshold_pct.is_finite() || request.threshold_pct <= 0.0 {
+        return Ok((StatusCode::BAD_REQUEST, "threshold_pct must be greater than 0").into_response());
+    }
+
+    let subscription = NewWebhookSubscription {
+        dapp_name: dapp_name.to_string(),
+        url: url.to_string(),
+        threshold_pct: request.threshold_pct,
+    };
+    let record = state.db_manager.create_webhook_subscription(&subscription).await?;
+    info!("🪝 Webhook subscription {} registered for {} (±{}%)", record.id, record.dapp_name, record.threshold_pct);
+    Ok((StatusCode::CREATED, Json(record)).into_response())
+}
+
+async fn list_webhook_subscriptions(
+    State(state): State<ApiState>,
+    Query(params): Query<WebhookListParams>,
+) -> Result<Json<Vec<WebhookSubscriptionRecord>>, ApiError> {
+    Ok(Json(state.db_manager.get_webhook_subscriptions(params.dapp_name.as_deref()).await?))
+}
+
+async fn delete_webhook_subscription(State(state): State<ApiState>, Path(id): Path<i32>) -> Result<Response, ApiError> {
+    if !state.db_manager.deactivate_webhook_subscription(id).await? {
+        return Ok((StatusCode::NOT_FOUND, format!("Webhook subscription {} does not exist", id)).into_response());
+    }
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+async fn get_webhook_deliveries(
+    State(state): State<ApiState>,
+    Path(id): Path<i32>,
+    Query(params): Query<HistoryParams>,
+) -> Result<Json<Vec<WebhookDeliveryRecord>>, ApiError> {
+    let limit = params.limit.unwrap_or(50).clamp(1, 1000);
+    Ok(Json(state.db_manager.get_webhook_deliveries(id, limit).await?))
+}
+
+async fn get_cpu_profile(Query(params): Query<CpuProfileParams>) -> Response {
+    let duration = Duration::from_secs(params.seconds.unwrap_or(30).clamp(1, 300));
+    let frequency = params.frequency.unwrap_or(99).clamp(1, 1000);
+    let format = match params.format.as_deref() {
+        None | Some("pprof") => CpuProfileFormat::Pprof,
+        Some("flamegraph") => CpuProfileFormat::Flamegraph,
+        Some(other) => {
+            return (StatusCode::BAD_REQUEST, format!("Unknown profile format '{}'", other)).into_response();
+        }
+    };
+
+    info!("🔬 Capturing {}s CPU profile at {}Hz", duration.as_secs(), frequency);
+    match profiling::capture_cpu_profile(duration, frequency, format).await {
+        Ok(Some(body)) => {
+            let content_type = match format {
+                CpuProfileFormat::Pprof => "application/octet-stream",
+                CpuProfileFormat::Flamegraph => "image/svg+xml",
+            };
+            ([(header::CONTENT_TYPE, content_type)], body).into_response()
+        }
+        Ok(None) => (StatusCode::CONFLICT, "A CPU profile is already in progress").into_response(),
+        Err(err) => ApiError(err).into_response(),
+    }
+}
+
+async fn get_heap_profile() -> Response {
+    if !profiling::HEAP_PROFILING_AVAILABLE {
+        return (StatusCode::NOT_IMPLEMENTED, "Built without the heap-profiling feature").into_response();
+    }
+
+    match profiling::dump_heap_profile() {
+        Ok(body) => ([(header::CONTENT_TYPE, "application/octet-stream")], body).into_response(),
+        Err(err) => ApiError(err).into_response(),
+    }
+}
+
+/// Start the HTTP API server in the background
+pub async fn start_api_server(state: ApiState, bind_address: SocketAddr) -> anyhow::Result<()> {
+    let listener = tokio::net::TcpListener::bind(bind_address).await?;
+
+    info!("🌐 API server listening on {}", bind_address);
+    tokio::spawn(async move {
+        if let Err(err) = serve_api(state, listener, std::future::pending()).await {
+            error!("❌ API server stopped: {}", err);
+        }
+    });
+
+    Ok(())
+}
+
+/// Serve the API on `listener` until `shutdown` completes, then finish in-flight requests
+pub async fn serve_api(
+    state: ApiState,
+    listener: tokio::net::TcpListener,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> anyhow::Result<()> {
+    // Client addresses key the anonymous rate limit
+    axum::serve(listener, router(state).into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(shutdown)
+        .await?;
+    Ok(())
+}
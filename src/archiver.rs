@@ -0,0 +1,289 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Parquet archive of raw DApp interactions.
+//!
+//! The live database only keeps aggregates, so the archiver keeps the raw
+//! interactions for offline analysis with DuckDB or Spark. Interactions are
+//! buffered per UTC hour of their checkpoint timestamp and, once the hour is
+//! over, written as one Parquet file per DApp with a Hive-style layout:
+//!
+//! `{location}/date=YYYY-MM-DD/hour=HH/dapp={dapp_name}/part-{written_at_ms}.parquet`
+//!
+//! An hour is over when interactions of a later hour arrive or when the wall
+//! clock has moved past it, so backfills are archived as fast as they are
+//! processed. Interactions arriving for an hour that was already written end up
+//! in an additional part file. Hours that fail to upload stay buffered and are
+//! retried on the next flush.
+
+use crate::models::DAppInteraction;
+use anyhow::{anyhow, Result};
+use arrow_array::{ArrayRef, BooleanArray, RecordBatch, StringArray, TimestampMillisecondArray, UInt64Array};
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use chrono::{DateTime, Duration as ChronoDuration, DurationRound, Utc};
+use object_store::aws::AmazonS3Builder;
+use object_store::local::LocalFileSystem;
+use object_store::path::Path as ObjectPath;
+use object_store::ObjectStore;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{error, info};
+
+/// How often buffered hours are checked against the wall clock
+const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Where archive files are written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArchiveLocation {
+    /// Local directory, created if missing
+    Local(PathBuf),
+    /// S3 bucket and key prefix; credentials and region come from the standard AWS_* variables
+    S3 { bucket: String, prefix: String },
+}
+
+impl FromStr for ArchiveLocation {
+    type Err = anyhow::Error;
+
+    /// Parse `s3://bucket/prefix`, `file:///path` or a plain path
+    fn from_str(value: &str) -> Result<Self> {
+        if let Some(rest) = value.strip_prefix("s3://") {
+            let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+            if bucket.is_empty() {
+                return Err(anyhow!("Archive location '{}' has no bucket", value));
+            }
+            return Ok(ArchiveLocation::S3 {
+                bucket: bucket.to_string(),
+                prefix: prefix.trim_matches('/').to_string(),
+            });
+        }
+        if value.contains("://") && !value.starts_with("file://") {
+            return Err(anyhow!("Unsupported archive location '{}', expected a path or s3://bucket/prefix", value));
+        }
+        let path = value.strip_prefix("file://").unwrap_or(value);
+        if path.is_empty() {
+            return Err(anyhow!("Archive location must not be empty"));
+        }
+        Ok(ArchiveLocation::Local(PathBuf::from(path)))
+    }
+}
+
+impl std::fmt::Display for ArchiveLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArchiveLocation::Local(path) => write!(f, "{}", path.display()),
+            ArchiveLocation::S3 { bucket, prefix } => write!(f, "s3://{}/{}", bucket, prefix),
+        }
+    }
+}
+
+impl ArchiveLocation {
+    /// Object store and key prefix the files are written under
+    fn open(&self) -> Result<(Arc<dyn ObjectStore>, ObjectPath)> {
+        match self {
+            ArchiveLocation::Local(path) => {
+                std::fs::create_dir_all(path)?;
+                Ok((Arc::new(LocalFileSystem::new_with_prefix(path)?), ObjectPath::default()))
+            }
+            ArchiveLocation::S3 { bucket, prefix } => {
+                let store = AmazonS3Builder::from_env().with_bucket_name(bucket).build()?;
+                Ok((Arc::new(store), ObjectPath::from(prefix.as_str())))
+            }
+        }
+    }
+}
+
+/// Schema of the archived interactions
+fn interaction_schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        Field::new("timestamp", DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())), false),
+        Field::new("dapp_name", DataType::Utf8, false),
+        Field::new("package_id", DataType::Utf8, false),
+        Field::new("sender", DataType::Utf8, false),
+        Field::new("transaction_digest", DataType::Utf8, false),
+        Field::new("gas_used", DataType::UInt64, false),
+        Field::new("event_type", DataType::Utf8, false),
+        Field::new("sponsored", DataType::Boolean, false),
+    ]))
+}
+
+fn dapp_name(interaction: &DAppInteraction) -> &str {
+    interaction.dapp_name.as_deref().unwrap_or("Unknown")
+}
+
+fn interaction_hour(interaction: &DAppInteraction) -> DateTime<Utc> {
+    let timestamp = interaction.timestamp;
+    timestamp.duration_trunc(ChronoDuration::hours(1)).unwrap_or(timestamp)
+}
+
+/// Encode interactions as a Snappy-compressed Parquet file
+pub fn encode_parquet(interactions: &[&DAppInteraction]) -> Result<Vec<u8>> {
+    let timestamps = TimestampMillisecondArray::from_iter_values(
+        interactions.iter().map(|interaction| interaction.timestamp.timestamp_millis()),
+    )
+    .with_timezone("UTC");
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(timestamps),
+        Arc::new(StringArray::from_iter_values(interactions.iter().map(|interaction| dapp_name(interaction)))),
+        Arc::new(StringArray::from_iter_values(interactions.iter().map(|interaction| &interaction.package_id))),
+        Arc::new(StringArray::from_iter_values(interactions.iter().map(|interaction| &interaction.sender))),
+        Arc::new(StringArray::from_iter_values(interactions.iter().map(|interaction| &interaction.transaction_digest))),
+        Arc::new(UInt64Array::from_iter_values(interactions.iter().map(|interaction| interaction.gas_used))),
+        Arc::new(StringArray::from_iter_values(interactions.iter().map(|interaction| &interaction.event_type))),
+        Arc::new(BooleanArray::from_iter(interactions.iter().map(|interaction| Some(interaction.sponsored)))),
+    ];
+    let batch = RecordBatch::try_new(interaction_schema(), columns)?;
+
+    let properties = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+    let mut buffer = Vec::new();
+    let mut writer = ArrowWriter::try_new(&mut buffer, batch.schema(), Some(properties))?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(buffer)
+}
+
+/// Buffers interactions per hour and writes finished hours to the archive
+pub struct InteractionArchiver {
+    store: Arc<dyn ObjectStore>,
+    prefix: ObjectPath,
+    hours: BTreeMap<DateTime<Utc>, Vec<DAppInteraction>>,
+}
+
+impl InteractionArchiver {
+    pub fn new(location: &ArchiveLocation) -> Result<Self> {
+        let (store, prefix) = location.open()?;
+        Ok(Self { store, prefix, hours: BTreeMap::new() })
+    }
+
+    /// Buffer interactions until their hour is over
+    pub fn add(&mut self, interactions: Vec<DAppInteraction>) {
+        for interaction in interactions {
+            self.hours.entry(interaction_hour(&interaction)).or_default().push(interaction);
+        }
+    }
+
+    /// Write every buffered hour that started before `current_hour`
+    /// Hours that fail to upload stay buffered
+    pub async fn flush_before(&mut self, current_hour: DateTime<Utc>) {
+        let finished: Vec<DateTime<Utc>> = self.hours.range(..current_hour).map(|(hour, _)| *hour).collect();
+        for hour in finished {
+            let interactions = self.hours.remove(&hour).unwrap_or_default();
+            match self.write_hour(hour, &interactions).await {
+                Ok(files) => info!(hour = %hour, interactions = interactions.len(), files,
+                                   "🗃️ Archived {} interactions of {} in {} Parquet file(s)",
+                                   interactions.len(), hour.format("%Y-%m-%d %H:00"), files),
+                Err(err) => {
+                    error!("❌ Failed to archive interactions of {}: {}", hour.format("%Y-%m-%d %H:00"), err);
+                    self.hours.insert(hour, interactions);
+                }
+            }
+        }
+    }
+
+    /// Write everything that is buffered, e.g. on shutdown
+    pub async fn flush_all(&mut self) {
+        if let Some(last_hour) = self.hours.keys().next_back().copied() {
+            self.flush_before(last_hour + ChronoDuration::hours(1)).await;
+        }
+    }
+
+    /// Latest buffered hour, used as the clock during backfills
+    fn latest_hour(&self) -> Option<DateTime<Utc>> {
+        self.hours.keys().next_back().copied()
+    }
+
+    /// Write one Parquet file per DApp of `hour`
+    async fn write_hour(&self, hour: DateTime<Utc>, interactions: &[DAppInteraction]) -> Result<usize> {
+        let mut by_dapp: HashMap<&str, Vec<&DAppInteraction>> = HashMap::new();
+        for interaction in interactions {
+            by_dapp.entry(dapp_name(interaction)).or_default().push(interaction);
+        }
+
+        let written_at = Utc::now().timestamp_millis();
+        for (dapp, dapp_interactions) in &by_dapp {
+            let location = self
+                .prefix
+                .child(format!("date={}", hour.format("%Y-%m-%d")))
+                .child(format!("hour={}", hour.format("%H")))
+                .child(format!("dapp={}", dapp))
+                .child(format!("part-{}.parquet", written_at));
+            let bytes = encode_parquet(dapp_interactions)?;
+            self.store.put(&location, bytes.into()).await?;
+        }
+        Ok(by_dapp.len())
+    }
+}
+
+/// Start the archiver task
+///
+/// # Returns
+/// * Sender the checkpoint aggregator passes the interactions of every applied checkpoint to
+pub async fn start_interaction_archiver(location: ArchiveLocation) -> Result<mpsc::Sender<Vec<DAppInteraction>>> {
+    let mut archiver = InteractionArchiver::new(&location)?;
+    let (sender, mut receiver) = mpsc::channel::<Vec<DAppInteraction>>(256);
+    info!("🗃️ Archiving interactions as Parquet to {}", location);
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(FLUSH_INTERVAL);
+        loop {
+            tokio::select! {
+                received = receiver.recv() => match received {
+                    Some(interactions) => {
+                        archiver.add(interactions);
+                        // Interactions of a later hour mean the earlier hours are complete
+                        if let Some(latest_hour) = archiver.latest_hour() {
+                            archiver.flush_before(latest_hour).await;
+                        }
+                    }
+                    None => {
+                        archiver.flush_all().await;
+                        break;
+                    }
+                },
+                _ = ticker.tick() => {
+                    let now = Utc::now();
+                    archiver.flush_before(now.duration_trunc(ChronoDuration::hours(1)).unwrap_or(now)).await;
+                }
+            }
+        }
+    });
+
+    Ok(sender)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::InteractionBuilder;
+
+    #[tokio::test]
+    async fn test_archiver_writes_finished_hours_partitioned() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut archiver = InteractionArchiver::new(&ArchiveLocation::Local(dir.path().to_path_buf())).unwrap();
+
+        // 1970-01-01 00:xx and 01:xx
+        archiver.add(vec![
+            InteractionBuilder::new().dapp("Cetus AMM").digest("tx60").gas_used(10).seconds(60).build(),
+            InteractionBuilder::new().dapp("Suilend").digest("tx120").gas_used(10).seconds(120).build(),
+            InteractionBuilder::new().dapp("Suilend").digest("tx3700").gas_used(10).seconds(3_700).build(),
+        ]);
+        archiver.flush_before(archiver.latest_hour().unwrap()).await;
+
+        let hour_dir = dir.path().join("date=1970-01-01").join("hour=00");
+        assert_eq!(std::fs::read_dir(hour_dir.join("dapp=Cetus AMM")).unwrap().count(), 1);
+        assert_eq!(std::fs::read_dir(hour_dir.join("dapp=Suilend")).unwrap().count(), 1);
+        assert!(!dir.path().join("date=1970-01-01").join("hour=01").exists());
+        assert_eq!(archiver.hours.len(), 1);
+
+        assert_eq!(
+            "s3://bucket/archive/".parse::<ArchiveLocation>().unwrap(),
+            ArchiveLocation::S3 { bucket: "bucket".to_string(), prefix: "archive".to_string() }
+        );
+    }
+}
@@ -0,0 +1,319 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Backtest of the rankings over archived checkpoints.
+//!
+//! `backtest --from <date> --to <date>` replays the checkpoints of a range of
+//! UTC days from remote storage and ranks the DApps of every day over that
+//! day's interactions with `compute_rankings`, so the effect of a registry or
+//! extraction change can be compared with past leaderboards. The first
+//! checkpoint of `--from` and of the day after `--to` are located through a
+//! fullnode's JSON-RPC like the warm-up. The executor reads the checkpoints in
+//! between with a progress store of its own, so the indexer's saved progress is
+//! left untouched. Interactions are kept by day until the replay is over, so
+//! memory grows with the length of the range.
+
+use crate::clock::MockClock;
+use crate::dapp_indexer::{compute_rankings, DAppIndexer, InteractionExtractor};
+use crate::models::{DAppInteraction, DAppRegistryEntry};
+use crate::warm_up::CheckpointLocator;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use prometheus::Registry;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use sui_data_ingestion_core::{
+    DataIngestionMetrics, IndexerExecutor, ProgressStore, ReaderOptions, Worker, WorkerPool,
+};
+use sui_types::full_checkpoint_content::CheckpointData;
+use sui_types::messages_checkpoint::CheckpointSequenceNumber;
+use tokio::sync::{mpsc, oneshot};
+use tracing::info;
+
+/// Extracted checkpoints waiting to be bucketed by day
+const BACKTEST_QUEUE_SIZE: usize = 256;
+/// Replay progress is logged every this many checkpoints
+const PROGRESS_LOG_INTERVAL: u64 = 100_000;
+
+/// Parse a UTC day given as `YYYY-MM-DD`
+pub fn parse_day(value: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+        .map_err(|_| anyhow!("Invalid day '{}', expected YYYY-MM-DD", value))
+}
+
+/// UTC days to backtest, both ends included
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BacktestRange {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl BacktestRange {
+    pub fn new(from: NaiveDate, to: NaiveDate) -> Result<Self> {
+        if to < from {
+            return Err(anyhow!("Backtest range ends ({}) before it starts ({})", to, from));
+        }
+        Ok(Self { from, to })
+    }
+
+    /// Midnight starting `from`
+    pub fn start(&self) -> DateTime<Utc> {
+        day_start(self.from)
+    }
+
+    /// Midnight ending `to`
+    pub fn end(&self) -> DateTime<Utc> {
+        day_start(self.to) + Duration::from_secs(24 * 60 * 60)
+    }
+
+    /// Number of days in the range
+    pub fn days(&self) -> i64 {
+        (self.to - self.from).num_days() + 1
+    }
+
+    /// Indexer whose extractor yields the interactions of every checkpoint of the
+    /// range: its clock stands at the end of the range and its window covers it
+    pub fn indexer(&self) -> DAppIndexer {
+        DAppIndexer::builder()
+            .clock(Arc::new(MockClock::new(self.end())))
+            .ranking_window((self.end() - self.start()).to_std().unwrap_or_default())
+            .build()
+    }
+}
+
+fn day_start(day: NaiveDate) -> DateTime<Utc> {
+    day.and_time(NaiveTime::MIN).and_utc()
+}
+
+/// One DApp of the top-N of a day
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DailyRankingRow {
+    pub day: String,
+    pub rank: u32,
+    pub dapp_name: String,
+    pub package_id: String,
+    pub dapp_type: String,
+    /// Distinct users over the day
+    pub active_users: u32,
+    pub tx_count: u32,
+    pub gas_used: u64,
+    pub score: f64,
+}
+
+/// Interactions of a backtest range by UTC day
+#[derive(Debug, Clone)]
+pub struct DailyInteractions {
+    range: BacktestRange,
+    days: BTreeMap<NaiveDate, Vec<DAppInteraction>>,
+}
+
+impl DailyInteractions {
+    pub fn new(range: BacktestRange) -> Self {
+        Self { range, days: BTreeMap::new() }
+    }
+
+    /// Keep the interactions that fall within the range
+    pub fn record(&mut self, interactions: Vec<DAppInteraction>) {
+        let (start, end) = (self.range.start(), self.range.end());
+        for interaction in interactions {
+            if interaction.timestamp >= start && interaction.timestamp < end {
+                self.days.entry(interaction.timestamp.date_naive()).or_default().push(interaction);
+            }
+        }
+    }
+
+    /// Top `top_n` DApps of every day with interactions, by day then rank
+    pub fn report(&self, registry: &HashMap<String, DAppRegistryEntry>, top_n: usize) -> Vec<DailyRankingRow> {
+        let mut rows = Vec::new();
+        for (day, interactions) in &self.days {
+            let day_end = day_start(*day) + Duration::from_secs(24 * 60 * 60);
+            let rankings = compute_rankings(interactions, registry, Duration::from_secs(24 * 60 * 60), day_end);
+            rows.extend(rankings.into_iter().take(top_n).map(|ranking| DailyRankingRow {
+                day: day.to_string(),
+                rank: ranking.rank,
+                dapp_name: ranking.dapp_name,
+                package_id: ranking.package_id,
+                dapp_type: ranking.dapp_type,
+                active_users: ranking.active_users,
+                tx_count: ranking.tx_count,
+                gas_used: ranking.gas_used,
+                score: ranking.score,
+            }));
+        }
+        rows
+    }
+}
+
+/// Progress store starting the replay at a fixed checkpoint and saving nothing
+struct StartAt(CheckpointSequenceNumber);
+
+#[async_trait]
+impl ProgressStore for StartAt {
+    async fn load(&mut self, _task_name: String) -> Result<CheckpointSequenceNumber> {
+        Ok(self.0)
+    }
+
+    async fn save(&mut self, _task_name: String, _checkpoint_number: CheckpointSequenceNumber) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Ingestion worker passing the interactions of every checkpoint before `end` on
+struct BacktestWorker {
+    extractor: InteractionExtractor,
+    end: CheckpointSequenceNumber,
+    sender: mpsc::Sender<Vec<DAppInteraction>>,
+}
+
+#[async_trait]
+impl Worker for BacktestWorker {
+    type Result = ();
+
+    async fn process_checkpoint(&self, checkpoint: &CheckpointData) -> Result<()> {
+        if checkpoint.checkpoint_summary.sequence_number >= self.end {
+            return Ok(());
+        }
+        let extracted = self.extractor.extract(checkpoint);
+        self.sender
+            .send(extracted.interactions)
+            .await
+            .map_err(|_| anyhow!("Backtest collector is not running"))
+    }
+}
+
+/// Where the backtest reads archived checkpoints from
+#[derive(Debug, Clone)]
+pub struct BacktestSource {
+    /// Local checkpoint files, read before remote storage
+    pub checkpoints_dir: PathBuf,
+    pub remote_storage: Option<String>,
+    pub executor_jobs: usize,
+    pub concurrency: usize,
+    pub data_limit: usize,
+    /// Registry the executor's ingestion metrics are registered in
+    pub registry: Registry,
+}
+
+/// Replay the checkpoints of `range` and bucket the interactions `extractor` finds by day
+/// The extractor should come from `BacktestRange::indexer`, so no checkpoint of the range is skipped
+pub async fn replay_range(
+    range: BacktestRange,
+    locator: &CheckpointLocator,
+    extractor: InteractionExtractor,
+    source: BacktestSource,
+) -> Result<DailyInteractions> {
+    if range.end() > Utc::now() {
+        return Err(anyhow!("Backtest range must end with a day that is over, {} is not", range.to));
+    }
+    let latest = locator.latest_checkpoint().await?;
+    let first = locator.first_checkpoint_at(range.start(), latest).await?;
+    let end = locator.first_checkpoint_at(range.end(), latest).await?;
+    if end <= first {
+        return Ok(DailyInteractions::new(range));
+    }
+    info!("🧪 Backtest: replaying checkpoints {} to {} ({} days)", first, end - 1, range.days());
+
+    let (sender, mut receiver) = mpsc::channel(BACKTEST_QUEUE_SIZE);
+    let (exit_sender, exit_receiver) = oneshot::channel();
+    let mut executor = IndexerExecutor::new(StartAt(first), source.executor_jobs, DataIngestionMetrics::new(&source.registry));
+    executor
+        .register(WorkerPool::new(
+            BacktestWorker { extractor, end, sender },
+            "dapp_ranking_backtest".to_string(),
+            source.concurrency,
+        ))
+        .await?;
+
+    // Every checkpoint of the range sends exactly once; the executor is stopped after the last
+    let collector = tokio::spawn(async move {
+        let mut days = DailyInteractions::new(range);
+        let mut remaining = end - first;
+        while remaining > 0 {
+            let Some(interactions) = receiver.recv().await else {
+                break;
+            };
+            days.record(interactions);
+            remaining -= 1;
+            if remaining % PROGRESS_LOG_INTERVAL == 0 && remaining > 0 {
+                info!("🧪 Backtest: {} checkpoints left", remaining);
+            }
+        }
+        let _ = exit_sender.send(());
+        (days, remaining)
+    });
+
+    executor
+        .run(
+            source.checkpoints_dir,
+            source.remote_storage,
+            vec![],
+            ReaderOptions {
+                data_limit: source.data_limit,
+                upper_limit: Some(end),
+                ..ReaderOptions::default()
+            },
+            exit_receiver,
+        )
+        .await?;
+
+    let (days, remaining) = collector.await?;
+    if remaining > 0 {
+        return Err(anyhow!("Backtest stopped with {} checkpoints of the range left", remaining));
+    }
+    Ok(days)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::InteractionBuilder;
+
+    #[test]
+    fn test_ranks_each_day_over_its_own_interactions() {
+        let range = BacktestRange::new(parse_day("2025-06-01").unwrap(), parse_day("2025-06-02").unwrap()).unwrap();
+        assert_eq!(range.days(), 2);
+        assert!(BacktestRange::new(range.to, range.from).is_err());
+        assert!(parse_day("06/01/2025").is_err());
+
+        let registry = HashMap::from([
+            ("0xa".to_string(), DAppRegistryEntry::new("Cetus AMM", "DEX")),
+            ("0xb".to_string(), DAppRegistryEntry::new("Pyth", "Oracle")),
+        ]);
+        let interaction = |package_id: &str, sender: &str, hours: u64| {
+            InteractionBuilder::new()
+                .package(package_id)
+                .sender(sender)
+                .digest(&format!("{}-{}-{}", package_id, sender, hours))
+                .gas_used(10)
+                .at(range.start() + Duration::from_secs(hours * 3600))
+                .build()
+        };
+        let mut days = DailyInteractions::new(range);
+        days.record(vec![
+            interaction("0xa", "0x1", 1),
+            interaction("0xa", "0x2", 23),
+            interaction("0xb", "0x3", 2),
+            // A user of the first day is not active on the second
+            interaction("0xb", "0x1", 25),
+            interaction("0xb", "0x4", 47),
+            interaction("0xa", "0x5", 30),
+            // After the range
+            interaction("0xa", "0x6", 48),
+        ]);
+
+        let rows: Vec<(String, u32, String, u32)> = days
+            .report(&registry, 1)
+            .into_iter()
+            .map(|row| (row.day, row.rank, row.dapp_name, row.active_users))
+            .collect();
+        assert_eq!(rows, [
+            ("2025-06-01".to_string(), 1, "Cetus AMM".to_string(), 2),
+            ("2025-06-02".to_string(), 1, "Pyth".to_string(), 2),
+        ]);
+        assert_eq!(days.report(&registry, 10).len(), 4);
+    }
+}
@@ -5,15 +5,31 @@
  * DAPP RANKING CHECKPOINT PROCESSOR
  * 
  * This binary is the main entry point for processing Sui blockchain checkpoints
- * to extract and index DApp interaction data for ranking based on Hourly Active Users (HAU).
+ * to extract and index DApp interaction data for ranking based on active users over the
+ * ranking window (RANKING_WINDOW, 1h by default: Hourly Active Users, HAU).
  * 
  * Key functionalities:
  * - Processes Sui blockchain checkpoints sequentially
  * - Extracts DApp interactions from all events
- * - Calculates 1h HAU metrics for each DApp
- * - Ranks DApps based on their HAU
+ * - Calculates active user metrics over the ranking window for each DApp
+ * - Ranks DApps based on their active users
  * - Stores data in PostgreSQL database
  * - Provides real-time monitoring via logging
+ *
+ * `dapp_checkpoint_processor export <path> [--format csv|json] [--top-users]` instead
+ * writes the rankings (or the latest top users, with SuiNS names when enabled)
+ * stored in the database to a file and exits.
+ *
+ * `dapp_checkpoint_processor recompute [--window 24h]` rebuilds the rankings from
+ * the interactions persisted with PERSIST_INTERACTIONS and exits.
+ *
+ * `dapp_checkpoint_processor backtest --from <date> --to <date> [--top N] [--output <path>]`
+ * replays the archived checkpoints of those days, writes each day's top DApps to a
+ * CSV or JSON report and exits.
+ *
+ * `dapp_checkpoint_processor simulate [--events-per-second N] [--users N] [--mix <name=weight,...>]`
+ * (built with `--features simulation`) feeds synthetic checkpoints through the whole
+ * pipeline instead of ingesting the chain, to load-test aggregation and the database.
  */
 
 use dotenvy::dotenv;
@@ -22,106 +38,287 @@ use prometheus::Registry;
 use std::env;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use sui_data_ingestion_core::{
-    DataIngestionMetrics, FileProgressStore, IndexerExecutor, ReaderOptions, Worker, WorkerPool,
+    DataIngestionMetrics, FileProgressStore, IndexerExecutor, ReaderOptions, WorkerPool,
 };
-use sui_types::full_checkpoint_content::CheckpointData;
 use tokio::sync::{oneshot, Mutex};
-use tracing::{info, Level, error};
-use async_trait::async_trait;
+use tracing::{info, error, warn};
 use anyhow::Result;
-use suins_indexer::dapp_indexer::{
-    DAppIndexer,
+use suins_indexer::aggregator::{
+    checkpoint_channel, start_checkpoint_aggregator, CheckpointQueueMetrics, CheckpointWorkerMetrics, DAppIndexerWorker,
 };
+use suins_indexer::anomaly::{start_anomaly_detection_job, AnomalyMetrics};
+use suins_indexer::archiver::start_interaction_archiver;
+use suins_indexer::backtest::{parse_day, replay_range, BacktestRange, BacktestSource};
+use suins_indexer::interaction_store::{parse_window, start_interaction_store};
+use suins_indexer::notifier::notifier;
+use suins_indexer::dapp_indexer::DAppIndexer;
 use suins_indexer::{init_config, get_config};
+use suins_indexer::config::Config;
+use suins_indexer::config_file::{apply_config_file, take_config_arg};
+use suins_indexer::api::ApiState;
+use suins_indexer::runtime_config::{start_runtime_config_file_watcher, RuntimeConfig};
+use suins_indexer::runtime_metrics::start_runtime_metrics_job;
 use suins_indexer::database::DatabaseManager;
+use suins_indexer::export::{write_rows, ExportFormat, TopUserRow};
+use suins_indexer::fullnode_subscription::{
+    start_fullnode_subscription, FullnodeCheckpointSource, FullnodeSubscriptionMetrics,
+};
+use suins_indexer::leader_election::{start_leader_election, Leadership};
+use suins_indexer::error_reporting::ErrorReportingSettings;
+use suins_indexer::logging::{init_logging, LogFormat, OtlpSettings};
+use suins_indexer::memory_guard::{start_memory_report_job, MemoryMetrics};
+use suins_indexer::checkpoint_gc::{start_checkpoint_gc_job, CheckpointGcMetrics};
+use suins_indexer::progress_store::{
+    IndexerProgressStore, PostgresProgressStore, ProgressStoreKind, RewoundProgressStore, WatchedProgressStore,
+};
+use suins_indexer::pushgateway::start_metrics_push_job;
+use suins_indexer::redis_cache::{start_redis_rankings_mirror, RedisMirrorMetrics};
+use suins_indexer::leaderboard_bot::start_leaderboard_bot;
+use suins_indexer::snapshot_export::start_snapshot_export_job;
+use suins_indexer::remote_storage::{start_remote_storage_proxy, RemoteStorageFailover};
+#[cfg(feature = "simulation")]
+use suins_indexer::simulation::{
+    default_dapp_mix, parse_dapp_mix, run_simulation, CheckpointGenerator, SimulationSettings,
+};
+use suins_indexer::startup::restore_state;
+use suins_indexer::warm_up::CheckpointLocator;
+
+// jemalloc with heap profiling enabled, sampling roughly every 512 KiB allocated
+#[cfg(feature = "heap-profiling")]
+#[global_allocator]
+static GLOBAL: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+
+#[cfg(feature = "heap-profiling")]
+#[allow(non_upper_case_globals)]
+#[export_name = "_rjem_malloc_conf"]
+pub static malloc_conf: &[u8] = b"prof:true,prof_active:true,lg_prof_sample:19\0";
 
 /**
- * DAppIndexerWorker is the main worker that processes each checkpoint for DApp ranking
- * It implements the Worker trait to handle checkpoint data processing
+ * Export subcommand - writes the persisted rankings to a CSV or JSON file
+ * The format follows `--format`, or the file extension when omitted
+ * With `--top-users` the latest top users of every DApp are exported instead
  */
-struct DAppIndexerWorker {
-    // Thread-safe reference to the DApp indexer instance
-    indexer: Arc<Mutex<DAppIndexer>>,
-    // Database manager for storing processed data
-    db_manager: Arc<DatabaseManager>,
+async fn run_export(config: &Config, args: &[String]) -> Result<()> {
+    let usage = || anyhow::anyhow!("Usage: dapp_checkpoint_processor export <path> [--format csv|json] [--top-users]");
+    let mut path = None;
+    let mut format = None;
+    let mut top_users = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => format = Some(args.next().ok_or_else(usage)?.parse::<ExportFormat>()?),
+            "--top-users" => top_users = true,
+            _ if path.is_none() => path = Some(PathBuf::from(arg)),
+            _ => return Err(usage()),
+        }
+    }
+    let path = path.ok_or_else(usage)?;
+    let format = format.unwrap_or_else(|| ExportFormat::from_path(&path));
+
+    let db_manager = DatabaseManager::with_settings(&config.database_url, config.db_connection.clone())
+        .await?
+        .with_live_window(config.live_window_label());
+    db_manager.check_schema_version().await?;
+    if top_users {
+        let mut rows: Vec<TopUserRow> = db_manager.get_latest_top_users().await?.into_iter().map(TopUserRow::from).collect();
+        if let Some(suins_resolver) = config.suins_resolver()? {
+            let names = suins_resolver.resolve_all(rows.iter().map(|row| row.sender.as_str())).await;
+            for row in &mut rows {
+                row.suins_name = names.get(&row.sender).cloned();
+            }
+        }
+        write_rows(&rows, &path, format).await?;
+        info!("📤 Exported {} top users to {}", rows.len(), path.display());
+        return Ok(());
+    }
+
+    let mut dapp_indexer = DAppIndexer::new();
+    dapp_indexer
+        .get_data_from_database(&db_manager)
+        .await
+        .map_err(|err| anyhow::anyhow!("Failed to load rankings: {}", err))?;
+    dapp_indexer.export_rankings(&path, format).await?;
+
+    info!("📤 Exported {} DApp rankings to {}", dapp_indexer.get_dapp_rankings().len(), path.display());
+    Ok(())
 }
 
-impl DAppIndexerWorker {
-    /// Creates a new DAppIndexerWorker instance
-    /// 
-    /// # Arguments
-    /// * `indexer` - Arc<Mutex<DAppIndexer>> for thread-safe access to the indexer
-    /// * `db_manager` - Database manager instance
-    fn new(indexer: Arc<Mutex<DAppIndexer>>, db_manager: Arc<DatabaseManager>) -> Self {
-        Self {
-            indexer,
-            db_manager,
+/**
+ * Recompute subcommand - rebuilds the rankings from the interactions persisted
+ * over the last `--window` (RANKING_WINDOW by default), without replaying checkpoints
+ * Windows other than the live RANKING_WINDOW are stored as rankings of their own
+ */
+async fn run_recompute(config: &Config, args: &[String]) -> Result<()> {
+    let usage = || anyhow::anyhow!("Usage: dapp_checkpoint_processor recompute [--window <30m|1h|24h|7d>]");
+    let live_window = config.live_window_label();
+    let mut window_label = live_window.as_str();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--window" => window_label = args.next().ok_or_else(usage)?,
+            _ => return Err(usage()),
         }
     }
+    let window_label = window_label.trim();
+    let window = parse_window(window_label)?;
+    let retention = Duration::from_secs(config.interactions_retention_days as u64 * 24 * 60 * 60);
+    if config.interactions_retention_days > 0 && window > retention {
+        warn!("⚠️ Interactions are only kept for {} days, the start of the window has been dropped",
+              config.interactions_retention_days);
+    }
+
+    let db_manager = DatabaseManager::with_settings(&config.database_url, config.db_connection.clone())
+        .await?
+        .with_live_window(config.live_window_label());
+    db_manager.check_schema_version().await?;
+
+    let mut dapp_indexer = DAppIndexer::new();
+    dapp_indexer.set_min_dau_for_ranking(config.min_dau_for_ranking);
+    dapp_indexer.set_score_formula(config.ranking_score_formula.clone());
+    dapp_indexer
+        .get_data_from_database(&db_manager)
+        .await
+        .map_err(|err| anyhow::anyhow!("Failed to load rankings: {}", err))?;
+    for dapp_name in &config.paused_dapps {
+        dapp_indexer.set_dapp_enabled(dapp_name, false);
+    }
+    let rankings = dapp_indexer
+        .recompute_rankings_from_interactions(&db_manager, window_label, window)
+        .await?;
+
+    info!("🔁 Recomputed {} DApp rankings from the interactions of the last {}", rankings.len(), window_label);
+    Ok(())
 }
 
 /**
- * Implementation of the Worker trait for processing checkpoints
- * This is called for each checkpoint that needs to be processed
+ * Backtest subcommand - replays the archived checkpoints of the days from `--from`
+ * to `--to` (UTC, both included) and writes the top `--top` DApps of every day
+ * The registry is loaded from the database unless in dry-run mode
  */
-#[async_trait]
-impl Worker for DAppIndexerWorker {
-    type Result = ();
-    
-    /// Process a single checkpoint and extract DApp interactions
-    /// 
-    /// # Arguments
-    /// * `checkpoint` - The checkpoint data containing all transactions
-    /// 
-    /// # Returns
-    /// * `Result<()>` - Success or error result
-    async fn process_checkpoint(&self, checkpoint: &CheckpointData) -> Result<()> {
-        // Acquire exclusive access to the indexer (thread-safe)
-        let mut indexer = self.indexer.lock().await;
-        
-        // Process the checkpoint and extract DApp interactions
-        let dapp_interactions = indexer.process_checkpoint(checkpoint, Some(&self.db_manager)).await;
-        
-        // Log detailed information if any DApp interactions were found
-        if !dapp_interactions.is_empty() {
-            info!("------------------------------------");
-            info!("CHECKPOINT: {}", checkpoint.checkpoint_summary.sequence_number);
-            info!("Timestamp: {}", checkpoint.checkpoint_summary.timestamp_ms);
-            
-            // Log detailed information about DApp interactions
-            info!("Found {} DApp interactions", dapp_interactions.len());
-            
-            // Group interactions by DApp for better logging
-            let mut dapp_counts = std::collections::HashMap::new();
-            for interaction in &dapp_interactions {
-                let dapp_name = interaction.dapp_name.as_ref()
-                    .unwrap_or(&interaction.package_id);
-                *dapp_counts.entry(dapp_name.clone()).or_insert(0) += 1;
-            }
-            
-            // Log interactions per DApp
-            for (dapp_name, count) in &dapp_counts {
-                info!("  📱 {}: {} interactions", dapp_name, count);
-            }
-            
-            // Display current DApp rankings
-            let rankings = indexer.get_dapp_rankings();
-            if !rankings.is_empty() {
-                info!("🏆 Current Top DApps (1h HAU):");
-                for (idx, ranking) in rankings.iter().take(10).enumerate() {
-                    info!("  {}. {} - {} HAU", 
-                        idx + 1, 
-                        ranking.dapp_name, 
-                        ranking.dau_1h
-                    );
-                }
-            }
-            
-            info!("------------------------------------");
+async fn run_backtest(config: &Config, args: &[String]) -> Result<()> {
+    let usage = || anyhow::anyhow!(
+        "Usage: dapp_checkpoint_processor backtest --from <YYYY-MM-DD> --to <YYYY-MM-DD> [--top N] [--output <path>] [--format csv|json]"
+    );
+    let mut from = None;
+    let mut to = None;
+    let mut top_n = 10;
+    let mut path = PathBuf::from("backtest.csv");
+    let mut format = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--from" => from = Some(parse_day(args.next().ok_or_else(usage)?)?),
+            "--to" => to = Some(parse_day(args.next().ok_or_else(usage)?)?),
+            "--top" => top_n = args.next().ok_or_else(usage)?.parse()?,
+            "--output" => path = PathBuf::from(args.next().ok_or_else(usage)?),
+            "--format" => format = Some(args.next().ok_or_else(usage)?.parse::<ExportFormat>()?),
+            _ => return Err(usage()),
+        }
+    }
+    let range = BacktestRange::new(from.ok_or_else(usage)?, to.ok_or_else(usage)?)?;
+    let format = format.unwrap_or_else(|| ExportFormat::from_path(&path));
+
+    let mut dapp_indexer = range.indexer();
+    if !config.dry_run {
+        let db_manager = DatabaseManager::with_settings(&config.database_url, config.db_connection.clone()).await?;
+        db_manager.check_schema_version().await?;
+        dapp_indexer.load_registry_from_database(&db_manager).await?;
+    }
+    config.prepare_paths()?;
+    // Metrics of a backtest only reach Prometheus through the Pushgateway
+    let registry = Registry::new();
+    let pusher = match config.pushgateway_settings() {
+        Some(settings) => Some(start_metrics_push_job(registry.clone(), settings).await?),
+        None => None,
+    };
+    let source = BacktestSource {
+        checkpoints_dir: PathBuf::from(&config.checkpoints_dir),
+        remote_storage: remote_storage_url(config, &registry).await?,
+        executor_jobs: config.executor_jobs,
+        concurrency: config.worker_pool_concurrency,
+        data_limit: config.ingestion_data_limit,
+        registry,
+    };
+    let days = replay_range(range, &CheckpointLocator::new(&config.rpc_url)?, dapp_indexer.extractor(), source).await;
+    if let Some(pusher) = pusher {
+        if let Err(err) = pusher.push().await {
+            error!("❌ Failed to push the final metrics to the Pushgateway: {}", err);
+        }
+    }
+    let days = days?;
+
+    let rows = days.report(&dapp_indexer.dapp_names, top_n);
+    write_rows(&rows, &path, format).await?;
+    info!("🧪 Wrote the daily top {} of {} days to {}", top_n, range.days(), path.display());
+    Ok(())
+}
+
+/// Arguments of the simulate subcommand
+#[cfg(feature = "simulation")]
+struct SimulateArgs {
+    settings: SimulationSettings,
+    /// `name=weight` pairs, every DApp of the registry when omitted
+    dapp_mix: Option<String>,
+    duration: Option<Duration>,
+}
+
+/**
+ * Simulate subcommand - parses the load to generate
+ * The pipeline is set up as for indexing, then fed by the generator instead of the executor
+ */
+#[cfg(feature = "simulation")]
+fn parse_simulate_args(args: &[String]) -> Result<SimulateArgs> {
+    let usage = || anyhow::anyhow!(
+        "Usage: dapp_checkpoint_processor simulate [--events-per-second N] [--users N] [--mix <name=weight,...>] [--duration <30m|1h>] [--seed N]"
+    );
+    let mut simulate = SimulateArgs { settings: SimulationSettings::default(), dapp_mix: None, duration: None };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--events-per-second" => simulate.settings.events_per_second = args.next().ok_or_else(usage)?.parse()?,
+            "--users" => simulate.settings.users = args.next().ok_or_else(usage)?.parse()?,
+            "--mix" => simulate.dapp_mix = Some(args.next().ok_or_else(usage)?.clone()),
+            "--duration" => simulate.duration = Some(parse_window(args.next().ok_or_else(usage)?)?),
+            "--seed" => simulate.settings.seed = args.next().ok_or_else(usage)?.parse()?,
+            _ => return Err(usage()),
+        }
+    }
+    Ok(simulate)
+}
+
+/// Remote checkpoint storage for the executor
+/// Several endpoints are served through a local failover proxy
+async fn remote_storage_url(config: &Config, registry: &Registry) -> Result<Option<String>> {
+    if config.remote_storage.len() > 1 {
+        let failover = RemoteStorageFailover::new(config.remote_storage.clone(), registry)?;
+        let proxy_url = start_remote_storage_proxy(failover).await?;
+        info!("🔀 Remote storage failover across {} endpoints via {}", config.remote_storage.len(), proxy_url);
+        Ok(Some(proxy_url))
+    } else {
+        Ok(config.remote_storage.first().cloned())
+    }
+}
+
+/**
+ * Config subcommand - `config validate` checks the configuration without starting
+ * Prints the configuration summary, or the first invalid setting
+ */
+fn run_config_command(args: &[String]) -> Result<()> {
+    if args != ["validate"] {
+        return Err(anyhow::anyhow!("Usage: dapp_checkpoint_processor [--config <path>] config validate"));
+    }
+    match Config::from_env() {
+        Ok(config) => {
+            config.print_summary();
+            println!("✅ Configuration is valid");
+            Ok(())
+        }
+        Err(err) => {
+            eprintln!("❌ Invalid configuration: {:#}", err);
+            std::process::exit(1);
         }
-        
-        Ok(())
     }
 }
 
@@ -131,16 +328,35 @@ impl Worker for DAppIndexerWorker {
  */
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize structured logging with INFO level and timestamps
-    tracing_subscriber::fmt()
-        .with_max_level(Level::INFO)        
-        .with_target(false)  // Don't show module targets
-        .with_ansi(true)     // Enable colored output
-        .init();
-    
     // Load environment variables from .env file
     dotenv().ok();
     
+    // A --config file fills in the settings the environment leaves unset
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let config_file = take_config_arg(&mut args)?;
+    if let Some(path) = &config_file {
+        if let Err(err) = apply_config_file(path) {
+            eprintln!("❌ {:#}", err);
+            std::process::exit(1);
+        }
+    }
+    if args.first().map(String::as_str) == Some("config") {
+        return run_config_command(&args[1..]);
+    }
+    
+    // Initialize logging with INFO level and timestamps, as text or JSON lines,
+    // optionally exporting spans over OTLP and reporting errors to a tracker
+    // An invalid LOG_FORMAT is reported by the configuration validation below
+    let log_format = env::var("LOG_FORMAT")
+        .ok()
+        .and_then(|format| format.parse::<LogFormat>().ok())
+        .unwrap_or_default();
+    let (log_level_handle, _tracing_guard) = init_logging(
+        log_format,
+        OtlpSettings::from_env().as_ref(),
+        ErrorReportingSettings::from_env()?.as_ref(),
+    )?;
+    
     // Initialize application configuration from environment variables
     if let Err(err) = init_config() {
         error!("❌ Failed to initialize configuration: {}", err);
@@ -150,99 +366,406 @@ async fn main() -> Result<()> {
     // Get the validated configuration
     let config = get_config();
     
-    // Use default paths since we removed config options
-    let checkpoints_dir = env::var("CHECKPOINTS_DIR")
-        .unwrap_or("/home/hungez/Documents/surfsui-indexer/checkpoints".to_string());
+    if let Some(path) = &config_file {
+        info!("📄 Loaded configuration file {}", path.display());
+    }
+    
+    // One-off export instead of indexing
+    if args.first().map(String::as_str) == Some("export") {
+        return run_export(config, &args[1..]).await;
+    }
+    if args.first().map(String::as_str) == Some("recompute") {
+        return run_recompute(config, &args[1..]).await;
+    }
+    if args.first().map(String::as_str) == Some("backtest") {
+        return run_backtest(config, &args[1..]).await;
+    }
+    
+    // Synthetic checkpoints replace the executor, everything else runs as usual
+    let simulating = args.first().map(String::as_str) == Some("simulate");
+    #[cfg(not(feature = "simulation"))]
+    if simulating {
+        return Err(anyhow::anyhow!("simulate requires a build with `--features simulation`"));
+    }
+    #[cfg(feature = "simulation")]
+    let simulation = if simulating { Some(parse_simulate_args(&args[1..])?) } else { None };
     
-    // Use default remote storage
-    let remote_storage = env::var("REMOTE_STORAGE")
-        .ok(); // This returns Option<String>
+    // Publish runtime-tunable settings and apply log level changes as they arrive
+    let runtime_config = RuntimeConfig::new(config.runtime_settings());
+    let mut log_level_updates = runtime_config.subscribe();
+    log_level_handle.modify(|filter| *filter = log_level_updates.borrow_and_update().log_level_filter())?;
+    tokio::spawn(async move {
+        while log_level_updates.changed().await.is_ok() {
+            let level = log_level_updates.borrow_and_update().log_level_filter();
+            if let Err(err) = log_level_handle.modify(|filter| *filter = level) {
+                error!("❌ Failed to change log level: {}", err);
+            }
+        }
+    });
+    if let Some(path) = &config.runtime_config_file {
+        start_runtime_config_file_watcher(runtime_config.clone(), PathBuf::from(path), Duration::from_secs(10)).await;
+    }
     
-    // Use default backfill progress file path
-    let backfill_progress_file_path = env::var("BACKFILL_PROGRESS_FILE")
-        .unwrap_or("/home/hungez/Documents/surfsui-indexer/backfill_progress/backfill_progress".to_string());
+    // Paths come from the configuration; create missing directories up front
+    config.prepare_paths()?;
+    let checkpoints_dir = &config.checkpoints_dir;
     
     // Get database connection string from configuration
     let database_url = &config.database_url;
     
     // Check if database functionality should be enabled
-    let use_database = env::var("USE_DATABASE")
-        .unwrap_or("true".to_string())
-        .parse::<bool>()
-        .unwrap_or(true);
+    // Dry-run mode never connects to the database
+    let use_database = !config.dry_run
+        && env::var("USE_DATABASE")
+            .unwrap_or("true".to_string())
+            .parse::<bool>()
+            .unwrap_or(true);
 
     // Log startup information
-    info!("🚀 Starting DApp Ranking Indexer (1h HAU)"); 
+    info!("🚀 Starting DApp Ranking Indexer ({} active users)", config.live_window_label());
     info!("📁 Checkpoints dir: {}", checkpoints_dir);
     info!("💾 Database enabled: {}", use_database);
+    if config.dry_run {
+        info!("🧪 Dry run: rankings are computed and logged but never written to the database");
+    }
     info!("📱 Tracking DApp interactions for ranking");
 
     // Create channels for graceful shutdown
     let (_exit_sender, exit_receiver) = oneshot::channel();
     
+    // Setup database manager (not even connected in dry-run mode)
+    let db_manager = if config.dry_run {
+        None
+    } else {
+        Some(Arc::new(
+            DatabaseManager::with_settings(database_url, config.db_connection.clone())
+                .await?
+                .with_retry_policy(config.db_retry_policy)
+                .with_live_window(config.live_window_label()),
+        ))
+    };
+    
+    // Bring the schema up to date, or make sure it already is
+    if let Some(db_manager) = &db_manager {
+        if config.run_migrations {
+            let applied = db_manager.run_migrations().await?;
+            info!("🗄️ Applied {} database migration(s)", applied.len());
+        }
+        db_manager.check_schema_version().await?;
+    }
+    
+    // Only the elected leader among replicas writes to the database
+    let leadership = match (config.leader_election_settings(), &db_manager) {
+        (Some(settings), Some(db_manager)) => {
+            info!("🗳️ Leader election enabled (lock key {})", settings.lock_key);
+            start_leader_election(db_manager.clone(), settings).await
+        }
+        _ => Leadership::default(),
+    };
+    
     // Set up progress tracking (remembers last processed checkpoint)
-    let progress_store = FileProgressStore::new(PathBuf::from(backfill_progress_file_path));
+    let progress_store = match (config.progress_store, &db_manager) {
+        (ProgressStoreKind::Postgres, Some(db_manager)) => {
+            info!("📄 Storing checkpoint progress in Postgres");
+            IndexerProgressStore::Postgres(PostgresProgressStore::new(db_manager.clone()).with_leadership(leadership.clone()))
+        }
+        _ => {
+            IndexerProgressStore::File(FileProgressStore::new(PathBuf::from(&config.backfill_progress_file_path)))
+        }
+    };
 
     // Initialize Prometheus metrics server for monitoring
     let registry: Registry = start_basic_prometheus_server();
     let metrics = DataIngestionMetrics::new(&registry);
+    start_runtime_metrics_job(&registry).await?;
+    if let Some(settings) = config.pushgateway_settings() {
+        start_metrics_push_job(registry.clone(), settings).await?;
+    }
+    
+    // Several remote endpoints are served through a local failover proxy
+    let remote_storage = remote_storage_url(config, &registry).await?;
     
-    // Create the main executor with 1 worker thread
-    let mut executor = IndexerExecutor::new(progress_store, 1, metrics);
+    // The warm-up replays the checkpoints of the last WARM_UP_HOURS, reconcile mode a
+    // fixed number of them; either rebuilds the in-memory windows
+    let warm_up = match config.warm_up_window.filter(|_| !simulating) {
+        Some(window) => match CheckpointLocator::new(&config.rpc_url)?.plan_warm_up(window).await {
+            Ok(plan) => Some(plan),
+            Err(err) => {
+                error!("❌ Failed to locate warm-up checkpoints, starting without warm-up: {}", err);
+                None
+            }
+        },
+        None => None,
+    };
+    let progress_store = match warm_up {
+        Some(plan) => RewoundProgressStore::to_checkpoint(progress_store, plan.start_checkpoint),
+        None => RewoundProgressStore::new(progress_store, config.startup_replay_checkpoints()),
+    };
+    let (progress_store, committed_watermark) = WatchedProgressStore::new(progress_store);
+    
+    // New checkpoints near the head come straight from the fullnode; the executor
+    // reads them from the checkpoints directory before asking remote storage
+    if let Some(url) = config.fullnode_checkpoint_url.as_ref().filter(|_| !simulating) {
+        start_fullnode_subscription(
+            FullnodeCheckpointSource::new(url)?,
+            CheckpointLocator::new(&config.rpc_url)?,
+            PathBuf::from(checkpoints_dir),
+            committed_watermark.clone(),
+            FullnodeSubscriptionMetrics::new(&registry)?,
+        ).await;
+    }
+    
+    // Delete downloaded checkpoint files once the watermark has moved past them
+    if let Some(gc_interval) = config.checkpoint_gc_interval {
+        start_checkpoint_gc_job(
+            PathBuf::from(checkpoints_dir),
+            committed_watermark,
+            config.checkpoint_gc_retention,
+            gc_interval,
+            CheckpointGcMetrics::new(&registry)?,
+        ).await;
+    }
+    
+    // Create the main executor
+    let mut executor = IndexerExecutor::new(progress_store, config.executor_jobs, metrics);
 
     // Create a new DAppIndexer instance wrapped in Arc<Mutex> for thread safety
-    let indexer = Arc::new(Mutex::new(DAppIndexer::new()));
+    let mut dapp_indexer = config
+        .ranking_profiles
+        .iter()
+        .fold(DAppIndexer::builder().ranking_window(config.ranking_window), |builder, (name, window)| {
+            builder.ranking_profile(name.clone(), *window)
+        })
+        .build();
+    if config.anonymize_senders {
+        if let Some(salt) = &config.sender_hash_salt {
+            dapp_indexer.enable_sender_anonymization(salt.clone());
+            info!("🕶️ Sender anonymization enabled");
+        }
+    }
+    let sender_filters = config.sender_filter_chain();
+    if !sender_filters.is_empty() {
+        info!("🤖 {} HAU sender filter rule(s) enabled", sender_filters.rules.len());
+    }
+    dapp_indexer.set_sender_filters(sender_filters);
+    let sender_access = config.sender_access_list()?;
+    if !sender_access.is_empty() {
+        info!("🚫 Sender access list loaded: {} denylisted, allowlist {}",
+              sender_access.denylist.len(),
+              sender_access.allowlist.as_ref().map_or("disabled".to_string(), |list| format!("{} addresses", list.len())));
+    }
+    dapp_indexer.set_sender_access_list(sender_access);
+    dapp_indexer.set_user_attribution(config.user_attribution);
+    dapp_indexer.set_top_users_limit(config.top_users_per_dapp);
+    dapp_indexer.set_min_dau_for_ranking(config.min_dau_for_ranking);
+    dapp_indexer.set_stats_timezone(config.stats_timezone);
+    dapp_indexer.set_top_functions_limit(config.top_functions_per_dapp);
+    dapp_indexer.set_price_source(Arc::new(config.coin_prices.clone()));
+    dapp_indexer.set_score_formula(config.ranking_score_formula.clone());
+    dapp_indexer.set_dau_smoothing_window(config.ranking_smoothing_window);
+    dapp_indexer.set_min_write_interval(config.db_write_min_interval);
+    dapp_indexer.set_runtime_settings(runtime_config.subscribe());
+    dapp_indexer.set_leadership(leadership.clone());
+    if let Some(plan) = warm_up {
+        dapp_indexer.set_warm_up_until(plan.live_checkpoint);
+    }
+    if use_database {
+        dapp_indexer.enable_retention_tracking();
+    }
+    for dapp_name in &config.paused_dapps {
+        if dapp_indexer.set_dapp_enabled(dapp_name, false) == 0 {
+            error!("❌ PAUSED_DAPPS contains unknown DApp: {}", dapp_name);
+        }
+    }
+    for (publisher, dapp_name) in &config.dapp_publishers {
+        if !dapp_indexer.add_publisher(publisher, dapp_name) {
+            error!("❌ DAPP_PUBLISHERS contains unknown DApp: {}", dapp_name);
+        }
+    }
+    let extractor = dapp_indexer.extractor();
+    let rankings_updates = dapp_indexer.subscribe_rankings();
+    if let Some(anomaly_settings) = config.anomaly_settings() {
+        start_anomaly_detection_job(
+            dapp_indexer.subscribe_rankings(),
+            anomaly_settings,
+            AnomalyMetrics::new(&registry)?,
+            leadership.clone(),
+        ).await?;
+    }
+    if let Some(redis_settings) = config.redis_settings() {
+        start_redis_rankings_mirror(
+            dapp_indexer.subscribe_rankings(),
+            redis_settings,
+            RedisMirrorMetrics::new(&registry)?,
+            leadership.clone(),
+        ).await?;
+    }
+    if let Some(bot_settings) = config.leaderboard_bot_settings() {
+        start_leaderboard_bot(dapp_indexer.subscribe_rankings(), bot_settings, leadership.clone()).await?;
+    }
+    let indexer = Arc::new(Mutex::new(dapp_indexer));
+    start_memory_report_job(indexer.clone(), config.memory_cap_bytes(), MemoryMetrics::new(&registry)?).await;
+    if let Some(export_settings) = config.snapshot_export_settings() {
+        start_snapshot_export_job(indexer.clone(), export_settings, config.stats_timezone, leadership.clone()).await?;
+    }
     
-    // Setup database manager
-    let db_manager = Arc::new(DatabaseManager::new(database_url).await?);
+    // Database used to persist rankings, if enabled
+    let persist_db = db_manager.clone().filter(|_| use_database);
     
-    // Initialize database and load existing data if database is enabled
-    if use_database {
+    // Restore or reset the persisted state according to the startup mode
+    if let Some(db_manager) = &persist_db {
         info!("✅ Database manager initialized");
+        let startup_mode = config.resolved_startup_mode();
+        info!("🔄 Startup mode: {}", startup_mode);
+        if let Err(err) = restore_state(&mut *indexer.lock().await, db_manager, startup_mode).await {
+            error!("❌ Failed to prepare the database ({} mode): {}", startup_mode, err);
+            return Err(err);
+        }
+    }
+
+    // Start the HTTP API if a bind address is configured
+    // The API serves data from the database, so it is not started in dry-run mode
+    if let (Some(bind_address), None) = (config.api_bind_address, &db_manager) {
+        info!("ℹ️ Dry run: HTTP API on {} is disabled", bind_address);
+    }
+    if let (Some(bind_address), Some(db_manager)) = (config.api_bind_address, &db_manager) {
+        let dapp_index = indexer.lock().await.name_index.clone();
+        let api_state = ApiState {
+            db_manager: db_manager.clone(),
+            runtime_config: runtime_config.clone(),
+            admin_token: config.admin_api_token.clone(),
+            pprof_enabled: config.pprof_enabled,
+            dapp_index,
+            suins_resolver: config.suins_resolver()?.map(Arc::new),
+            // Registry changes are only applied when rankings are persisted
+            indexer: persist_db.as_ref().map(|_| indexer.clone()),
+            rate_limiter: Arc::new(config.api_rate_limiter()),
+            response_cache: config.response_cache(),
+        };
+        suins_indexer::api::start_api_server(api_state, bind_address).await?;
+    }
+    
+    // Start the gRPC API if a bind address is configured
+    // It serves the rankings from memory, so it also runs in dry-run mode
+    if let Some(bind_address) = config.grpc_bind_address {
+        suins_indexer::grpc::start_grpc_server(rankings_updates, bind_address).await?;
+    }
+
+    // Create worker pool processing checkpoints concurrently
+    // Workers extract in parallel; a single aggregator task applies the results
+    let queue_metrics = CheckpointQueueMetrics::new(&registry)?;
+    let (checkpoint_sender, checkpoint_receiver) =
+        checkpoint_channel(config.checkpoint_queue_capacity(), queue_metrics.clone());
+    let archive = match &config.archive_location {
+        Some(location) => Some(start_interaction_archiver(location.clone()).await?),
+        None => None,
+    };
+    let interaction_store = match (&persist_db, config.persist_interactions) {
+        (Some(db_manager), true) => {
+            Some(start_interaction_store(
+                db_manager.clone(),
+                config.interactions_retention_days,
+                indexer.lock().await.clock(),
+            ).await)
+        }
+        _ => None,
+    };
+    start_checkpoint_aggregator(
+        indexer.clone(),
+        persist_db.clone(),
+        archive,
+        interaction_store,
+        checkpoint_receiver,
+        queue_metrics,
+        config.checkpoint_log_sample_rate,
+    ).await;
+    let worker = DAppIndexerWorker::new(extractor, checkpoint_sender, CheckpointWorkerMetrics::new(&registry)?);
+    
+    // Start background job to update rankings periodically
+    // Rankings are saved to the database, or only logged and exported in dry-run mode
+    info!("🔄 Starting background ranking update job (every {}s)",
+          runtime_config.current().ranking_interval.as_secs());
+    let export_path = config.dry_run_export_path.as_ref().filter(|_| config.dry_run).map(PathBuf::from);
+    suins_indexer::dapp_indexer::start_ranking_update_job(
+        indexer.clone(),
+        persist_db.clone(),
+        export_path,
+        runtime_config.subscribe(),
+    ).await;
+    
+    if let Some(db_manager) = &persist_db {
+        // Start the optional calibration job against an external DAU source
+        if let Some(calibration_settings) = config.calibration_settings() {
+            info!("📐 Starting calibration job for {} DApps", calibration_settings.dapps.len());
+            suins_indexer::calibration::start_calibration_job(
+                indexer.clone(),
+                db_manager.clone(),
+                calibration_settings,
+                leadership.clone(),
+            ).await;
+        }
         
-        // Reset all data to start fresh
-        let mut indexer_locked = indexer.lock().await;
-        match indexer_locked.reset_database_and_memory(&db_manager).await {
-            Ok(()) => {
-                info!("✅ Loaded DApp rankings from database");
-                
-                // Display top 5 DApps
-                let rankings = indexer_locked.get_dapp_rankings();
-                if !rankings.is_empty() {
-                    info!("🏆 Current Top DApps (1h HAU):");
-                    for (idx, ranking) in rankings.iter().take(5).enumerate() {
-                        info!("  {}. {} - {} HAU", idx + 1, ranking.dapp_name, ranking.dau_1h);
-                    }
-                } else {
-                    info!("ℹ️ No existing DApp rankings found in database");
-                }
-            }
-            Err(err) => {
-                error!("❌ Failed to reset database: {}", err);
-                return Err(err.into());
-            }
+        // Start the nightly retention cohort job
+        info!("📈 Starting retention job (daily at {:02}:00 UTC)", config.retention_job_hour_utc);
+        suins_indexer::retention::start_retention_job(
+            db_manager.clone(),
+            config.retention_job_hour_utc,
+            config.stats_timezone,
+            leadership.clone(),
+            indexer.lock().await.clock(),
+        ).await;
+        
+        // Start the hourly job merging the daily user sets into WAU and MAU
+        info!("📆 Starting WAU/MAU job (hourly)");
+        suins_indexer::active_users::start_active_users_job(
+            db_manager.clone(),
+            config.stats_timezone,
+            leadership.clone(),
+            indexer.lock().await.clock(),
+        ).await;
+        
+        // Start the job freezing the weekly and monthly top DApps
+        if config.ranking_snapshot_top_n > 0 {
+            info!("🏆 Starting ranking snapshot job (weekly and monthly top {})", config.ranking_snapshot_top_n);
+            suins_indexer::ranking_snapshots::start_ranking_snapshot_job(
+                db_manager.clone(),
+                config.ranking_snapshot_top_n,
+                config.stats_timezone,
+                leadership.clone(),
+                indexer.lock().await.clock(),
+                notifier(config.notification_webhook_url.as_deref())?,
+            ).await;
         }
-        drop(indexer_locked); // Release the lock
         
-        info!("🚀 Starting fresh with clean database and memory");
+        // Start notifying per-DApp webhook subscriptions of active user changes
+        suins_indexer::dapp_webhooks::start_dapp_webhooks(
+            db_manager.clone(),
+            indexer.lock().await.subscribe_rankings(),
+            config.live_window_label(),
+            leadership.clone(),
+        ).await?;
     }
-
-    // Create worker pool with 25 concurrent workers for processing
+    
+    #[cfg(feature = "simulation")]
+    if let Some(simulate) = simulation {
+        let mut settings = simulate.settings;
+        let registry = indexer.lock().await.dapp_names.clone();
+        settings.dapp_mix = match &simulate.dapp_mix {
+            Some(mix) => parse_dapp_mix(mix, &registry)?,
+            None => default_dapp_mix(&registry),
+        };
+        warn!("🧪 Simulation: synthetic interactions are written to the configured database");
+        return run_simulation(CheckpointGenerator::new(settings)?, &worker, simulate.duration).await;
+    }
+    
+    // Register the worker pool with the executor
     let worker_pool = WorkerPool::new(
-        DAppIndexerWorker::new(indexer.clone(), db_manager.clone()),
+        worker,
         "dapp_ranking_indexing".to_string(),
-        25, // Number of concurrent workers
+        config.worker_pool_concurrency, // Number of concurrent workers
     );
-    
-    // Register the worker pool with the executor
     executor.register(worker_pool).await?;
     
-    // Start background job to update database rankings every 2 minutes
-    if use_database {
-        info!("🔄 Starting background database update job (every 2 minutes)");
-        suins_indexer::dapp_indexer::start_ranking_update_job(indexer.clone(), db_manager.clone()).await;
-    }
-    
     info!("⏳ Starting DApp ranking checkpoint processing...");
     
     // Start processing checkpoints
@@ -252,7 +775,10 @@ async fn main() -> Result<()> {
             PathBuf::from(checkpoints_dir),    // Local checkpoint storage
             remote_storage,                     // Remote checkpoint source
             vec![],                            // Additional checkpoint sources (empty)
-            ReaderOptions::default(),          // Default reading options
+            ReaderOptions {                    // Bound checkpoint data buffered ahead of the workers
+                data_limit: config.ingestion_data_limit,
+                ..ReaderOptions::default()
+            },
             exit_receiver,                     // Graceful shutdown receiver
         )
         .await?;
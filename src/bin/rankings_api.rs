@@ -0,0 +1,115 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Read-only rankings API server.
+//!
+//! Serves the HTTP API (rankings, categories, retention, search, GraphQL) from
+//! the database written by a `dapp_checkpoint_processor` running elsewhere, so
+//! the API can be deployed and scaled separately from the ingester.
+//!
+//! - Never writes: admin, registry and profiling endpoints are not mounted
+//! - Configured by `ApiServerConfig` (API_BIND_ADDRESS, API_DB_POOL_SIZE, API_KEYS, ...)
+//! - Stops accepting connections on SIGINT/SIGTERM and finishes in-flight requests
+//! - Accepts `--config <path>` like the indexer, so both can share one TOML file
+
+use anyhow::Result;
+use dotenvy::dotenv;
+use std::sync::Arc;
+use suins_indexer::api::{serve_api, ApiState};
+use suins_indexer::config::ApiServerConfig;
+use suins_indexer::config_file::{apply_config_file, take_config_arg};
+use suins_indexer::dapp_indexer::DAppIndexer;
+use suins_indexer::database::DatabaseManager;
+use suins_indexer::error_reporting::ErrorReportingSettings;
+use suins_indexer::logging::{init_logging, OtlpSettings};
+use suins_indexer::runtime_config::RuntimeConfig;
+use tracing::{error, info};
+
+/// Completes on Ctrl+C or, on Unix, SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            error!("❌ Failed to listen for Ctrl+C: {}", err);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(err) => {
+                error!("❌ Failed to listen for SIGTERM: {}", err);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+    info!("🛑 Shutdown requested, finishing in-flight requests");
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv().ok();
+
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(path) = take_config_arg(&mut args)? {
+        if let Err(err) = apply_config_file(&path) {
+            eprintln!("❌ {:#}", err);
+            std::process::exit(1);
+        }
+    }
+
+    let config = match ApiServerConfig::from_env() {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("❌ Failed to initialize configuration: {:#}", err);
+            std::process::exit(1);
+        }
+    };
+    let (_log_level_handle, _tracing_guard) = init_logging(
+        config.log_format,
+        OtlpSettings::from_env().as_ref(),
+        ErrorReportingSettings::from_env()?.as_ref(),
+    )?;
+
+    info!("🚀 Starting read-only rankings API");
+    let db_manager = Arc::new(
+        DatabaseManager::with_settings(&config.database_url, config.db_connection.clone())
+            .await?
+            .with_live_window(config.live_window_label()),
+    );
+    db_manager.check_schema_version().await?;
+    info!("💾 Connected to database (pool of {} connections)", config.db_connection.pool_size);
+
+    // DApp search covers the built-in registry plus the changes stored by the indexer
+    let mut dapp_indexer = DAppIndexer::new();
+    dapp_indexer.load_registry_from_database(&db_manager).await?;
+
+    let state = ApiState {
+        db_manager,
+        runtime_config: RuntimeConfig::new(config.runtime_settings()),
+        admin_token: None,
+        pprof_enabled: false,
+        dapp_index: dapp_indexer.name_index.clone(),
+        suins_resolver: config.suins_resolver()?.map(Arc::new),
+        indexer: None,
+        rate_limiter: Arc::new(config.api_rate_limiter()),
+        response_cache: config.response_cache(),
+    };
+
+    let listener = tokio::net::TcpListener::bind(config.bind_address).await?;
+    info!("🌐 API server listening on {}", config.bind_address);
+    serve_api(state, listener, shutdown_signal()).await?;
+
+    info!("👋 API server stopped");
+    Ok(())
+}
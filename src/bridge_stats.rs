@@ -0,0 +1,233 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Inbound and outbound transfers of bridges over the last 24 hours.
+//!
+//! HAU says little about a bridge, where a handful of users can move most of
+//! the volume, so DApps of the `Bridge` category also get transfer flows.
+//! Their events are decoded from the checkpoint: a Wormhole `WormholeMessage`
+//! carrying a token transfer (Portal) is an outbound transfer; an event whose
+//! struct name contains `Redeem` (e.g. the token bridge's `TransferRedeemed`)
+//! is an inbound transfer. Amounts are read from the coin objects of the
+//! transaction rather than from the events, which carry no coin type (Wormhole
+//! payloads) or no amount (redeems): an outbound transfer moves the coins the
+//! sender spent, an inbound one the coins that came into address-owned
+//! balances. Like swap volume, amounts are counted raw per coin type and only
+//! valued in USD when the stats are computed, through a `PriceSource`.
+
+use crate::event_stats::ActivityCounter;
+use crate::models::BridgeStats;
+use crate::swap_volume::PriceSource;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+
+/// Registry category whose DApps get bridge stats
+pub const BRIDGE_DAPP_TYPE: &str = "Bridge";
+
+/// Label of inbound transfers in the hourly counts
+const INBOUND: &str = "inbound";
+/// Label of outbound transfers in the hourly counts
+const OUTBOUND: &str = "outbound";
+
+/// Event Wormhole's core bridge emits for every published message
+const WORMHOLE_MESSAGE: &str = "WormholeMessage";
+/// Token bridge payload ids of a transfer and of a transfer with payload
+const TOKEN_TRANSFER_PAYLOAD_IDS: [u8; 2] = [1, 3];
+
+/// Direction of a bridge transfer, seen from Sui
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BridgeDirection {
+    /// Tokens arriving on Sui
+    Inbound,
+    /// Tokens leaving Sui
+    Outbound,
+}
+
+/**
+ * BridgeTransfer is a transfer decoded from an event of a tracked bridge package
+ */
+#[derive(Debug, Clone)]
+pub struct BridgeTransfer {
+    pub dapp_name: String,              // Bridge the event belongs to
+    pub direction: BridgeDirection,     // Inbound or outbound
+    pub amounts: Vec<(String, u64)>,    // Moved coins as (canonical coin type, base units)
+    pub timestamp: DateTime<Utc>,       // When the transaction occurred
+}
+
+/// Decode a bridge event given its struct name and BCS contents
+/// Returns None for events that are not transfers
+pub fn decode_bridge_event(event_name: &str, contents: &[u8]) -> Option<BridgeDirection> {
+    if event_name == WORMHOLE_MESSAGE {
+        // Attestations and other messages are not transfers
+        let payload_id = wormhole_payload(contents)?.first()?;
+        return TOKEN_TRANSFER_PAYLOAD_IDS.contains(payload_id).then_some(BridgeDirection::Outbound);
+    }
+    if event_name.to_lowercase().contains("redeem") {
+        return Some(BridgeDirection::Inbound);
+    }
+    None
+}
+
+/// Payload of a message published through Wormhole
+///
+/// `WormholeMessage` is `{ sender: ID, sequence: u64, nonce: u32, payload: vector<u8>, .. }`
+/// and a token bridge payload starts with its payload id
+fn wormhole_payload(contents: &[u8]) -> Option<&[u8]> {
+    let mut rest = contents.get(32 + 8 + 4..)?;
+    let mut length = 0usize;
+    for shift in (0..64).step_by(7) {
+        let (&byte, tail) = rest.split_first()?;
+        rest = tail;
+        length |= ((byte & 0x7f) as usize) << shift;
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+    rest.get(..length)
+}
+
+/// Rolling 24h transfers and moved amounts per bridge
+#[derive(Debug, Clone, Default)]
+pub struct BridgeActivityCounter {
+    /// Hourly inbound and outbound transfer counts per bridge
+    counts: ActivityCounter,
+    /// Hourly raw inbound amounts, labelled by coin type
+    inbound_amounts: ActivityCounter,
+    /// Hourly raw outbound amounts, labelled by coin type
+    outbound_amounts: ActivityCounter,
+}
+
+impl BridgeActivityCounter {
+    pub fn record(&mut self, transfers: &[BridgeTransfer]) {
+        for transfer in transfers {
+            let (label, amounts) = match transfer.direction {
+                BridgeDirection::Inbound => (INBOUND, &mut self.inbound_amounts),
+                BridgeDirection::Outbound => (OUTBOUND, &mut self.outbound_amounts),
+            };
+            self.counts.add(&transfer.dapp_name, label, transfer.timestamp);
+            for (coin_type, amount) in &transfer.amounts {
+                amounts.add_amount(&transfer.dapp_name, coin_type, transfer.timestamp, *amount);
+            }
+        }
+    }
+
+    /// Forget transfers older than 24 hours
+    pub fn prune(&mut self, now: DateTime<Utc>) {
+        self.counts.prune(now);
+        self.inbound_amounts.prune(now);
+        self.outbound_amounts.prune(now);
+    }
+
+    /// Stats of every bridge with transfers in the last 24 hours, by DApp,
+    /// with the moved amounts valued by `prices`
+    pub fn stats(&self, prices: &dyn PriceSource, now: DateTime<Utc>) -> Vec<BridgeStats> {
+        let mut stats: BTreeMap<String, BridgeStats> = BTreeMap::new();
+        for (dapp_name, label, count) in self.counts.totals(now) {
+            let entry = stats.entry(dapp_name.clone()).or_insert_with(|| BridgeStats {
+                dapp_name,
+                inbound_transfers_24h: 0,
+                outbound_transfers_24h: 0,
+                inbound_volume_24h_usd: 0.0,
+                outbound_volume_24h_usd: 0.0,
+                last_update: now,
+            });
+            match label.as_str() {
+                INBOUND => entry.inbound_transfers_24h = count,
+                _ => entry.outbound_transfers_24h = count,
+            }
+        }
+        // Every transfer is counted, so its bridge already has an entry
+        for (dapp_name, coin_type, amount) in self.inbound_amounts.totals(now) {
+            if let Some(entry) = stats.get_mut(&dapp_name) {
+                entry.inbound_volume_24h_usd += prices.usd_value(&coin_type, amount).unwrap_or(0.0);
+            }
+        }
+        for (dapp_name, coin_type, amount) in self.outbound_amounts.totals(now) {
+            if let Some(entry) = stats.get_mut(&dapp_name) {
+                entry.outbound_volume_24h_usd += prices.usd_value(&coin_type, amount).unwrap_or(0.0);
+            }
+        }
+        stats.into_values().collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.counts.clear();
+        self.inbound_amounts.clear();
+        self.outbound_amounts.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swap_volume::{normalize_coin_type, StaticPrices};
+    use std::time::Duration;
+
+    const USDC: &str = "0xdba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7::usdc::USDC";
+    const WETH: &str = "0xaf8cd5edc19c4512f4259f0bee101a40d41ebed738ade5874359610ef8eeced5::coin::COIN";
+
+    /// BCS of a `WormholeMessage` with the given payload
+    fn wormhole_message(payload: &[u8]) -> Vec<u8> {
+        let mut contents = vec![0u8; 32 + 8 + 4];
+        // ULEB128 length
+        let mut length = payload.len();
+        while length >= 0x80 {
+            contents.push((length as u8 & 0x7f) | 0x80);
+            length >>= 7;
+        }
+        contents.push(length as u8);
+        contents.extend_from_slice(payload);
+        contents.push(1);
+        contents.extend_from_slice(&[0u8; 8]);
+        contents
+    }
+
+    fn token_transfer(amount: u64) -> Vec<u8> {
+        let mut payload = vec![1u8];
+        payload.extend_from_slice(&[0u8; 24]);
+        payload.extend_from_slice(&amount.to_be_bytes());
+        payload.extend_from_slice(&[0u8; 32 + 2 + 32 + 2 + 32]);
+        payload
+    }
+
+    #[test]
+    fn test_decodes_wormhole_transfers_and_redeems() {
+        assert_eq!(
+            decode_bridge_event("WormholeMessage", &wormhole_message(&token_transfer(150_000_000))),
+            Some(BridgeDirection::Outbound)
+        );
+        // Attestations and other messages are not transfers
+        assert_eq!(decode_bridge_event("WormholeMessage", &wormhole_message(&[2, 0, 0])), None);
+        assert_eq!(decode_bridge_event("WormholeMessage", &[0u8; 10]), None);
+        assert_eq!(decode_bridge_event("TransferRedeemed", &[]), Some(BridgeDirection::Inbound));
+        assert_eq!(decode_bridge_event("GuardianSetAdded", &[]), None);
+    }
+
+    #[test]
+    fn test_values_transfers_per_coin_over_24h() {
+        let now = DateTime::UNIX_EPOCH + Duration::from_secs(100 * 3600);
+        let prices = StaticPrices::parse(&format!("{}=1:6,{}=2500:8", USDC, WETH)).unwrap();
+        let transfer = |direction, amounts: &[(&str, u64)], hours_ago: u64| BridgeTransfer {
+            dapp_name: "Portal".to_string(),
+            direction,
+            amounts: amounts.iter().map(|(coin_type, amount)| (normalize_coin_type(coin_type).unwrap(), *amount)).collect(),
+            timestamp: now - Duration::from_secs(hours_ago * 3600),
+        };
+        let mut counter = BridgeActivityCounter::default();
+        counter.record(&[
+            // 150 USDC and 0.5 WETH out: coins of different decimals are not added raw
+            transfer(BridgeDirection::Outbound, &[(USDC, 150_000_000)], 1),
+            transfer(BridgeDirection::Outbound, &[(WETH, 50_000_000)], 2),
+            transfer(BridgeDirection::Outbound, &[(USDC, 900_000_000)], 30),
+            transfer(BridgeDirection::Inbound, &[(USDC, 20_000_000)], 1),
+            // No price: counted as a transfer, left out of the volume
+            transfer(BridgeDirection::Inbound, &[("0x2::unpriced::COIN", 7)], 1),
+        ]);
+        counter.prune(now);
+
+        let stats = counter.stats(&prices, now);
+        assert_eq!(stats.len(), 1);
+        assert_eq!((stats[0].inbound_transfers_24h, stats[0].outbound_transfers_24h), (2, 2));
+        assert_eq!((stats[0].inbound_volume_24h_usd, stats[0].outbound_volume_24h_usd), (20.0, 1_400.0));
+    }
+}
@@ -0,0 +1,141 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Garbage collection of downloaded checkpoint files.
+//!
+//! Checkpoints are downloaded to `{checkpoints_dir}/{sequence_number}.chk`
+//! and are not needed anymore once the progress store has committed a later
+//! watermark. A periodic task deletes every file more than the configured
+//! retention below the committed watermark and counts the reclaimed bytes.
+
+use anyhow::Result;
+use prometheus::{register_int_counter_with_registry, IntCounter, Registry};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use sui_types::messages_checkpoint::CheckpointSequenceNumber;
+use tokio::sync::watch;
+use tracing::{error, info};
+
+/// Extension of downloaded checkpoint files
+const CHECKPOINT_FILE_EXTENSION: &str = "chk";
+
+/// What one collection run deleted
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct GcStats {
+    pub files: u64,
+    pub bytes: u64,
+}
+
+/// Prometheus counters of the checkpoint GC
+#[derive(Clone)]
+pub struct CheckpointGcMetrics {
+    files_deleted: IntCounter,
+    bytes_reclaimed: IntCounter,
+}
+
+impl CheckpointGcMetrics {
+    pub fn new(registry: &Registry) -> Result<Self> {
+        Ok(Self {
+            files_deleted: register_int_counter_with_registry!(
+                "checkpoint_gc_files_deleted",
+                "Checkpoint files deleted below the committed watermark",
+                registry
+            )?,
+            bytes_reclaimed: register_int_counter_with_registry!(
+                "checkpoint_gc_bytes_reclaimed",
+                "Disk space reclaimed by deleting checkpoint files",
+                registry
+            )?,
+        })
+    }
+}
+
+/// Sequence number of a downloaded checkpoint file, if `path` is one
+fn checkpoint_number(path: &Path) -> Option<CheckpointSequenceNumber> {
+    if path.extension()? != CHECKPOINT_FILE_EXTENSION {
+        return None;
+    }
+    path.file_stem()?.to_str()?.parse().ok()
+}
+
+/// Delete checkpoint files numbered below `watermark - retention`
+/// Files that vanish or cannot be deleted are skipped
+pub fn collect_checkpoint_files(dir: &Path, watermark: CheckpointSequenceNumber, retention: u64) -> Result<GcStats> {
+    let cutoff = watermark.saturating_sub(retention);
+    let mut stats = GcStats::default();
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        match checkpoint_number(&path) {
+            Some(number) if number < cutoff => {}
+            _ => continue,
+        }
+
+        let size = fs::metadata(&path).map(|metadata| metadata.len()).unwrap_or(0);
+        if fs::remove_file(&path).is_ok() {
+            stats.files += 1;
+            stats.bytes += size;
+        }
+    }
+
+    Ok(stats)
+}
+
+/// Start the task deleting checkpoint files below the committed watermark every `interval`
+pub async fn start_checkpoint_gc_job(
+    checkpoints_dir: PathBuf,
+    watermark: watch::Receiver<CheckpointSequenceNumber>,
+    retention: u64,
+    interval: Duration,
+    metrics: CheckpointGcMetrics,
+) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+
+        loop {
+            ticker.tick().await;
+
+            let committed = *watermark.borrow();
+            if committed == 0 {
+                continue;
+            }
+
+            let dir = checkpoints_dir.clone();
+            let result = tokio::task::spawn_blocking(move || collect_checkpoint_files(&dir, committed, retention)).await;
+            match result {
+                Ok(Ok(stats)) if stats.files > 0 => {
+                    metrics.files_deleted.inc_by(stats.files);
+                    metrics.bytes_reclaimed.inc_by(stats.bytes);
+                    info!("🧹 Deleted {} checkpoint files below {} ({} bytes)", stats.files, committed, stats.bytes);
+                }
+                Ok(Ok(_)) => {}
+                Ok(Err(err)) => error!("❌ Checkpoint GC failed: {}", err),
+                Err(err) => error!("❌ Checkpoint GC task panicked: {}", err),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_checkpoint_files_below_watermark() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["5.chk", "9.chk", "10.chk", "12.chk", "notes.txt", "3.tmp"] {
+            fs::write(dir.path().join(name), b"data").unwrap();
+        }
+
+        let stats = collect_checkpoint_files(dir.path(), 12, 2).unwrap();
+        assert_eq!(stats, GcStats { files: 2, bytes: 8 });
+
+        let mut remaining: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        remaining.sort();
+        assert_eq!(remaining, vec!["10.chk", "12.chk", "3.tmp", "notes.txt"]);
+    }
+}
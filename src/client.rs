@@ -0,0 +1,137 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Async client for the indexer's HTTP API (requires the `client` feature).
+//!
+//! Lets other Rust services consume the rankings without hand-rolling HTTP
+//! calls:
+//!
+//! ```no_run
+//! # async fn example() -> anyhow::Result<()> {
+//! use futures_util::StreamExt;
+//! use suins_indexer::client::SurfSuiClient;
+//!
+//! let client = SurfSuiClient::new("http://localhost:8080")?;
+//! let top = client.get_rankings().await?;
+//! let cetus = client.get_dapp("Cetus AMM").await?;
+//!
+//! let mut updates = Box::pin(client.watch_rankings(std::time::Duration::from_secs(30)));
+//! while let Some(rankings) = updates.next().await {
+//!     println!("{} DApps ranked", rankings?.len());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::models::DAppRankingRecord;
+use crate::ranking_query::{RankingFilter, MAX_PAGE_SIZE};
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use futures_util::stream::{self, Stream};
+use reqwest::StatusCode;
+use std::time::Duration;
+
+/// Timeout of a single API request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Client of the rankings HTTP API
+#[derive(Debug, Clone)]
+pub struct SurfSuiClient {
+    base_url: String,
+    http: reqwest::Client,
+}
+
+impl SurfSuiClient {
+    /// Create a client for the API at `base_url`, e.g. http://localhost:8080
+    pub fn new(base_url: impl Into<String>) -> Result<Self> {
+        let http = reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build()?;
+        Ok(Self::with_http_client(base_url, http))
+    }
+
+    /// Create a client sharing an existing reqwest client (connection pool, proxies, TLS)
+    pub fn with_http_client(base_url: impl Into<String>, http: reqwest::Client) -> Self {
+        let base_url = base_url.into().trim_end_matches('/').to_string();
+        Self { base_url, http }
+    }
+
+    /// Current rankings of all DApps, best first
+    pub async fn get_rankings(&self) -> Result<Vec<DAppRankingRecord>> {
+        self.query_rankings(&RankingFilter { limit: MAX_PAGE_SIZE, ..RankingFilter::default() }).await
+    }
+
+    /// One page of rankings matching `filter`
+    pub async fn query_rankings(&self, filter: &RankingFilter) -> Result<Vec<DAppRankingRecord>> {
+        let mut params = vec![
+            ("offset", filter.offset.to_string()),
+            ("limit", filter.limit.to_string()),
+            ("sort", filter.sort_by.as_str().to_string()),
+            ("order", filter.sort_order().as_str().to_string()),
+        ];
+        if let Some(dapp_type) = &filter.dapp_type {
+            params.push(("dapp_type", dapp_type.clone()));
+        }
+        if let Some(min_dau) = filter.min_dau {
+            params.push(("min_dau", min_dau.to_string()));
+        }
+        if let Some(window) = &filter.window {
+            params.push(("window", window.clone()));
+        }
+
+        let response = self.http.get(format!("{}/rankings", self.base_url)).query(&params).send().await?;
+        parse_json(response).await
+    }
+
+    /// Current rankings of every package of one DApp; empty if it is not ranked
+    pub async fn get_dapp(&self, dapp_name: &str) -> Result<Vec<DAppRankingRecord>> {
+        // Path segments are percent-encoded, so names with spaces or slashes work
+        let mut url = reqwest::Url::parse(&self.base_url)
+            .with_context(|| format!("Invalid API base URL {}", self.base_url))?;
+        url.path_segments_mut()
+            .map_err(|_| anyhow!("Invalid API base URL {}", self.base_url))?
+            .pop_if_empty()
+            .extend(["dapps", dapp_name]);
+
+        let response = self.http.get(url).send().await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(Vec::new());
+        }
+        parse_json(response).await
+    }
+
+    /// Stream of the rankings: the current ones, then every time they are recomputed
+    /// The API is polled every `poll_interval`; failed polls are yielded as errors
+    /// and polling continues
+    pub fn watch_rankings(&self, poll_interval: Duration) -> impl Stream<Item = Result<Vec<DAppRankingRecord>>> + '_ {
+        // Rankings are identified by their computation time and size
+        let last_seen: Option<(Option<DateTime<Utc>>, usize)> = None;
+        stream::unfold((last_seen, false), move |(mut last_seen, mut polled)| async move {
+            loop {
+                if polled {
+                    tokio::time::sleep(poll_interval).await;
+                }
+                polled = true;
+
+                match self.get_rankings().await {
+                    Ok(rankings) => {
+                        let version = (rankings.iter().map(|ranking| ranking.last_update).max(), rankings.len());
+                        if last_seen != Some(version) {
+                            last_seen = Some(version);
+                            return Some((Ok(rankings), (last_seen, polled)));
+                        }
+                    }
+                    Err(err) => return Some((Err(err), (last_seen, polled))),
+                }
+            }
+        })
+    }
+}
+
+/// Decode a successful JSON response, or turn an error status into an error
+async fn parse_json(response: reqwest::Response) -> Result<Vec<DAppRankingRecord>> {
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(anyhow!("API request failed with HTTP {}: {}", status, body));
+    }
+    Ok(response.json().await?)
+}
@@ -0,0 +1,282 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Configuration file support.
+//!
+//! Settings can be kept in a TOML file passed with `--config <path>`. Every key
+//! names the environment variable it sets; keys of a table are prefixed with
+//! the table name, so `[api] bind_address` sets `API_BIND_ADDRESS`. Arrays are
+//! joined with commas. Variables already set in the environment (or in `.env`)
+//! take precedence over the file, so a single setting can be overridden per
+//! deployment without editing the file.
+//!
+//! ```toml
+//! checkpoints_dir = "./checkpoints"
+//!
+//! [database]
+//! url = "postgres://indexer@localhost/rankings"
+//!
+//! [api]
+//! bind_address = "0.0.0.0:8080"
+//! keys = ["partner-a=600", "partner-b=600"]
+//!
+//! [archive]
+//! location = "s3://rankings-archive/interactions"
+//! ```
+
+use crate::config::SECRET_VARIABLES;
+use anyhow::{anyhow, Context, Result};
+use std::env;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+/// Environment variables that can be set from a configuration file
+pub const CONFIG_VARIABLES: &[&str] = &[
+    "ACTIVE_USER_ATTRIBUTION",
+    "ADMIN_API_TOKEN",
+    "ANOMALY_BASELINE_WINDOW",
+    "ANOMALY_WEBHOOK_URL",
+    "ANOMALY_Z_SCORE",
+    "ANONYMIZE_SENDERS",
+    "API_ANONYMOUS_RATE_LIMIT",
+    "API_BIND_ADDRESS",
+    "API_CACHE_MAX_ENTRIES",
+    "API_DB_POOL_SIZE",
+    "API_KEYS",
+    "ARCHIVE_LOCATION",
+    "BACKFILL_PROGRESS_FILE_PATH",
+    "CALIBRATION_API_URL",
+    "CALIBRATION_DAPPS",
+    "CALIBRATION_DAU_FIELD",
+    "CALIBRATION_INTERVAL_SECONDS",
+    "CHECKPOINTS_DIR",
+    "CHECKPOINT_GC_INTERVAL_SECONDS",
+    "CHECKPOINT_GC_RETENTION",
+    "CHECKPOINT_LOG_SAMPLE_RATE",
+    "CHECKPOINT_QUEUE_SIZE",
+    "COIN_PRICES_USD",
+    "DAPP_PUBLISHERS",
+    "DATABASE_SSL_ROOT_CERT",
+    "DATABASE_URL",
+    "DAU_MAX_INTERACTIONS_PER_MINUTE",
+    "DAU_MIN_DISTINCT_TRANSACTIONS",
+    "DAU_SENDER_DENYLIST",
+    "DB_CIRCUIT_COOLDOWN_SECONDS",
+    "DB_CONNECT_TIMEOUT_SECONDS",
+    "DB_POOL_SIZE",
+    "DB_RETRY_BASE_DELAY_MS",
+    "DB_RETRY_MAX_ATTEMPTS",
+    "DB_RETRY_MAX_DELAY_MS",
+    "DB_STATEMENT_TIMEOUT_MS",
+    "DB_WRITE_MIN_INTERVAL_SECONDS",
+    "DISCORD_WEBHOOK_URL",
+    "DRY_RUN",
+    "DRY_RUN_EXPORT_PATH",
+    "ERROR_REPORTING_ENVIRONMENT",
+    "ERROR_REPORTING_URL",
+    "EXECUTOR_JOBS",
+    "FULLNODE_CHECKPOINT_URL",
+    "GRPC_BIND_ADDRESS",
+    "INGESTION_DATA_LIMIT_MB",
+    "INTERACTIONS_RETENTION_DAYS",
+    "LEADERBOARD_MOVE_THRESHOLD",
+    "LEADERBOARD_POST_INTERVAL",
+    "LEADER_CHECK_INTERVAL_SECONDS",
+    "LEADER_ELECTION",
+    "LEADER_LOCK_KEY",
+    "LOG_FORMAT",
+    "LOG_LEVEL",
+    "MEMORY_CAP_MB",
+    "MIN_DAU_FOR_RANKING",
+    "NOTIFICATION_WEBHOOK_URL",
+    "OTEL_EXPORTER_OTLP_ENDPOINT",
+    "OTEL_SERVICE_NAME",
+    "PAUSED_DAPPS",
+    "PERSIST_INTERACTIONS",
+    "PPROF_ENABLED",
+    "PROGRESS_STORE",
+    "PROMETHEUS_PUSHGATEWAY_INTERVAL_SECONDS",
+    "PROMETHEUS_PUSHGATEWAY_JOB",
+    "PROMETHEUS_PUSHGATEWAY_URL",
+    "RANKING_CHECKPOINT_INTERVAL",
+    "RANKING_MIN_INTERACTIONS",
+    "RANKING_PROFILES",
+    "RANKING_RECOMPUTE_INTERVAL_SECONDS",
+    "RANKING_SCORE_FORMULA",
+    "RANKING_SMOOTHING_WINDOW",
+    "RANKING_SNAPSHOT_TOP_N",
+    "RANKING_WINDOW",
+    "REDIS_KEY_PREFIX",
+    "REDIS_URL",
+    "RECONCILE_CHECKPOINTS",
+    "REMOTE_STORAGE",
+    "RETENTION_JOB_HOUR_UTC",
+    "RPC_URL",
+    "RUNTIME_CONFIG_FILE",
+    "RUN_MIGRATIONS",
+    "SENDER_ALLOWLIST_FILE",
+    "SENDER_DENYLIST_FILE",
+    "SENDER_HASH_SALT",
+    "SNAPSHOT_EXPORT_ACCESS_KEY_ID",
+    "SNAPSHOT_EXPORT_BUCKET",
+    "SNAPSHOT_EXPORT_ENDPOINT",
+    "SNAPSHOT_EXPORT_FORMAT",
+    "SNAPSHOT_EXPORT_PATH",
+    "SNAPSHOT_EXPORT_REGION",
+    "SNAPSHOT_EXPORT_SECRET_ACCESS_KEY",
+    "STARTUP_MODE",
+    "STATS_TIMEZONE",
+    "SUINS_CACHE_TTL_SECONDS",
+    "SUINS_RPC_URL",
+    "TELEGRAM_BOT_TOKEN",
+    "TELEGRAM_CHAT_ID",
+    "TOP_FUNCTIONS_PER_DAPP",
+    "TOP_USERS_PER_DAPP",
+    "UPDATE_INTERVAL_SECONDS",
+    "USE_DATABASE",
+    "WARM_UP_HOURS",
+    "WORKER_POOL_CONCURRENCY",
+];
+
+/// Remove `--config <path>` (or `--config=<path>`) from the arguments
+/// Returns the path, if given
+pub fn take_config_arg(args: &mut Vec<String>) -> Result<Option<PathBuf>> {
+    let Some(position) = args.iter().position(|arg| arg == "--config" || arg.starts_with("--config=")) else {
+        return Ok(None);
+    };
+    let arg = args.remove(position);
+    let path = match arg.strip_prefix("--config=") {
+        Some(path) => path.to_string(),
+        None if position < args.len() => args.remove(position),
+        None => return Err(anyhow!("--config requires a file path")),
+    };
+    Ok(Some(PathBuf::from(path)))
+}
+
+/// Parse a configuration file into (environment variable, value) pairs
+pub fn parse_config(contents: &str) -> Result<Vec<(String, String)>> {
+    let table: Table = contents.parse()?;
+    let mut variables = Vec::new();
+    flatten("", &table, &mut variables)?;
+
+    let unknown: Vec<&str> = variables
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .filter(|name| !is_config_variable(name))
+        .collect();
+    if !unknown.is_empty() {
+        return Err(anyhow!("unknown settings: {}", unknown.join(", ")));
+    }
+    Ok(variables)
+}
+
+/// Returns true for known settings and the `_FILE` variants of secrets
+fn is_config_variable(name: &str) -> bool {
+    CONFIG_VARIABLES.contains(&name)
+        || name.strip_suffix("_FILE").is_some_and(|secret| SECRET_VARIABLES.contains(&secret))
+}
+
+fn flatten(prefix: &str, table: &Table, variables: &mut Vec<(String, String)>) -> Result<()> {
+    for (key, value) in table {
+        let name = format!("{}{}", prefix, key.to_uppercase().replace('-', "_"));
+        let value = match value {
+            Value::Table(table) => {
+                flatten(&format!("{}_", name), table, variables)?;
+                continue;
+            }
+            Value::Array(items) => items.iter().map(scalar).collect::<Result<Vec<_>>>()?.join(","),
+            value => scalar(value)?,
+        };
+        variables.push((name, value));
+    }
+    Ok(())
+}
+
+fn scalar(value: &Value) -> Result<String> {
+    match value {
+        Value::String(value) => Ok(value.clone()),
+        Value::Integer(value) => Ok(value.to_string()),
+        Value::Float(value) => Ok(value.to_string()),
+        Value::Boolean(value) => Ok(value.to_string()),
+        value => Err(anyhow!("unsupported value {} (use strings, numbers, booleans or arrays of them)", value)),
+    }
+}
+
+/// Set the environment variables of a configuration file that are not set yet
+/// Returns the number of variables taken from the file
+pub fn apply_config_file(path: &Path) -> Result<usize> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file {}", path.display()))?;
+    let variables = parse_config(&contents)
+        .with_context(|| format!("Invalid config file {}", path.display()))?;
+
+    let mut applied = 0;
+    for (name, value) in variables {
+        if env::var_os(&name).is_none() {
+            env::set_var(name, value);
+            applied += 1;
+        }
+    }
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tables_and_arrays_map_to_variables() {
+        let variables = parse_config(
+            r#"
+            checkpoints_dir = "./checkpoints"
+            worker_pool_concurrency = 50
+
+            [api]
+            bind_address = "0.0.0.0:8080"
+            keys = ["a=600", "b=60"]
+
+            [leader]
+            election = true
+            "#,
+        )
+        .unwrap();
+        let mut variables: Vec<(&str, &str)> =
+            variables.iter().map(|(name, value)| (name.as_str(), value.as_str())).collect();
+        variables.sort();
+        assert_eq!(variables, [
+            ("API_BIND_ADDRESS", "0.0.0.0:8080"),
+            ("API_KEYS", "a=600,b=60"),
+            ("CHECKPOINTS_DIR", "./checkpoints"),
+            ("LEADER_ELECTION", "true"),
+            ("WORKER_POOL_CONCURRENCY", "50"),
+        ]);
+
+        let err = parse_config("[api]\nbind_adress = \"0.0.0.0:8080\"").unwrap_err();
+        assert!(err.to_string().contains("API_BIND_ADRESS"));
+    }
+
+    #[test]
+    fn test_environment_overrides_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("indexer.toml");
+        std::fs::write(&path, "calibration_dau_field = \"active_users\"\n\n[snapshot_export]\nregion = \"eu-west-1\"\n").unwrap();
+        env::set_var("SNAPSHOT_EXPORT_REGION", "us-east-1");
+        env::remove_var("CALIBRATION_DAU_FIELD");
+
+        assert_eq!(apply_config_file(&path).unwrap(), 1);
+        assert_eq!(env::var("SNAPSHOT_EXPORT_REGION").unwrap(), "us-east-1");
+        assert_eq!(env::var("CALIBRATION_DAU_FIELD").unwrap(), "active_users");
+        assert!(apply_config_file(&dir.path().join("missing.toml")).is_err());
+    }
+
+    #[test]
+    fn test_take_config_arg() {
+        let mut args = vec!["--config".to_string(), "indexer.toml".to_string(), "export".to_string()];
+        assert_eq!(take_config_arg(&mut args).unwrap(), Some(PathBuf::from("indexer.toml")));
+        assert_eq!(args, ["export"]);
+
+        let mut args = vec!["--config=indexer.toml".to_string()];
+        assert_eq!(take_config_arg(&mut args).unwrap(), Some(PathBuf::from("indexer.toml")));
+        assert!(take_config_arg(&mut vec!["--config".to_string()]).is_err());
+    }
+}
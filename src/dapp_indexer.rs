@@ -4,26 +4,83 @@
 /**
  * DAPP RANKING INDEXER MODULE
  * 
- * This module contains the core logic for ranking DApps on Sui blockchain based on Hourly Active Users (HAU).
- * It processes checkpoints to extract DApp interactions, calculates HAU metrics,
+ * This module contains the core logic for ranking DApps on Sui blockchain based on their active users
+ * over the ranking window (RANKING_WINDOW, 1h by default, hence Hourly Active Users or HAU).
+ * It processes checkpoints to extract DApp interactions, calculates active user metrics,
  * and manages database storage for rankings.
  * 
  * Key components:
  * - DApp interaction extraction from blockchain transactions
- * - Hourly Active Users (HAU) calculation
- * - DApp ranking based on 1h HAU
+ * - Active users calculation over the ranking window
+ * - DApp ranking based on those active users
  * - Database interaction for persistence
  */
 
+use sui_types::effects::TransactionEffectsAPI;
+use sui_types::transaction::TransactionDataAPI;
 use sui_types::full_checkpoint_content::{CheckpointData, CheckpointTransaction};
-use tracing::{info, error};
-use tokio::sync::Mutex;
+use sui_types::base_types::SuiAddress;
+use sui_types::object::{Object, Owner};
+use tracing::{debug, info, error, instrument};
+use tokio::sync::{watch, Mutex};
+use futures_util::{Stream, StreamExt};
 use std::sync::Arc;
+use crate::clock::{system_clock, Clock};
 use crate::database::DatabaseManager;
-use crate::models::{DAppInteraction, DAppRanking};
+use crate::leader_election::Leadership;
+use crate::models::{
+    BridgeStats, CategoryStats, DAppEpochStats, DAppEventStats, DAppFunctionCall, DAppFunctionStats, DAppHourlyActivity, DAppInteraction, DAppLendingStats, DAppMetadata, DAppMetadataRecord, DAppNftStats,
+    DAppPackageStats, DAppPublishedPackage, DAppRanking, DAppRegistryEntry, DAppRegistryRecord, DAppTopUser, DAppTransactionOutcome, NewDAppUserActivity, NewDAppUserFirstSeen, OTHERS_DAPP_NAME, OTHERS_PACKAGE_ID, TOP_USERS_PERCENT, TRACKING_ACTIVE, TRACKING_PAUSED,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
+use crate::ranking_score::{DauSmoother, RankingMetrics, ScoreFormula};
+use crate::runtime_config::RuntimeSettings;
+use crate::write_coalescer::WriteCoalescer;
+use crate::dapp_search::{DAppNameIndex, DAppSearchResult};
+use crate::event_stats::{error_rates, event_stats, top_functions, ActivityCounter, EVENT_STATS_WINDOW_HOURS};
+use crate::epoch_stats::EpochCounter;
+use crate::hourly_activity::HourlyActivityCounter;
+use crate::interaction_store::window_label;
+use crate::bridge_stats::{decode_bridge_event, BridgeActivityCounter, BridgeDirection, BridgeTransfer, BRIDGE_DAPP_TYPE};
+use crate::lending_stats::{decode_lending_amount, LendingActivityCounter, LendingFlow, LENDING_DAPP_TYPE};
+use crate::nft_stats::{NftActivityCounter, NFT_DAPP_TYPE};
+use crate::swap_volume::{attribute_swap, is_swap_event, received_amounts, spent_amounts, sui_coin_type, PriceSource, StaticPrices, SwapLeg, SwapVolumeCounter, SWAP_DAPP_TYPES};
+use crate::ranking_profiles::RankingProfile;
+use crate::export::{aggregate_interactions, write_rows, ExportFormat, RankingRow};
+use crate::sender_filter::{SenderAccessList, SenderFilterChain, UserAttribution};
 use anyhow::Result;
-use std::collections::{HashMap, HashSet};
-use std::time::{Duration, SystemTime};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::time::Duration;
+use std::path::{Path, PathBuf};
+use sha2::{Digest, Sha256};
+use rayon::prelude::*;
+
+/// Window HAU and the rankings are computed over unless configured otherwise
+pub const DEFAULT_RANKING_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// Window of the rolling 24h statistics (events, error rates, NFT, lending, bridge and swap activity)
+const STATS_WINDOW: Duration = Duration::from_secs(EVENT_STATS_WINDOW_HOURS * 60 * 60);
+
+/// Checkpoints with at least this many transactions are extracted on the rayon thread pool
+const PARALLEL_EXTRACTION_MIN_TRANSACTIONS: usize = 64;
+
+/// Window new and returning users are counted over unless configured otherwise
+pub const DEFAULT_NEW_USERS_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How many interactions a transaction yields for the same DApp and user
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InteractionDedup {
+    /// One interaction per event, so event stats count every emitted event
+    #[default]
+    PerEvent,
+    /// One interaction per (transaction, DApp, user), whatever the number of events
+    PerTransaction,
+}
 
 /**
  * DAppIndexer is the main struct that processes blockchain data for DApp ranking
@@ -31,48 +88,346 @@ use std::time::{Duration, SystemTime};
  */
 #[derive(Clone)]
 pub struct DAppIndexer {
-    pub dapp_interactions: Vec<DAppInteraction>,  // All processed DApp interactions (1h only)
-    pub dapp_rankings: Vec<DAppRanking>,         // Current 1h DApp rankings
-    pub dapp_names: HashMap<String, (String, String)>,       // package_id -> (dapp_name, dapp_type) mapping
+    pub dapp_interactions: Vec<DAppInteraction>,  // All processed DApp interactions (ranking window only)
+    pub dapp_rankings: Vec<DAppRanking>,         // Current DApp rankings over the ranking window
+    pub category_stats: Vec<CategoryStats>,      // Current per-category rollups over the ranking window
+    pub package_stats: Vec<DAppPackageStats>,    // Current per-package activity over the ranking window
+    pub event_counts: ActivityCounter,           // Hourly interaction counts per (DApp, event type)
+    pub event_stats: Vec<DAppEventStats>,        // Current 24h per-event-type counts
+    pub function_counts: ActivityCounter,        // Hourly MoveCall counts per (DApp, function)
+    pub function_stats: Vec<DAppFunctionStats>,  // Current 24h most called functions per DApp
+    pub top_functions_limit: usize,              // Number of most called functions kept per DApp
+    pub outcome_counts: ActivityCounter,         // Hourly successful/failed transactions per DApp
+    pub epoch_counts: EpochCounter,              // Distinct senders/transactions per (epoch, DApp)
+    pub epoch_stats: Vec<DAppEpochStats>,        // Current stats of the current and previous epoch
+    pub current_epoch: Option<u64>,              // Latest Sui epoch seen in a checkpoint
+    pub hourly_activity_counts: HourlyActivityCounter, // Distinct senders per (DApp, hour of day) over 7 days
+    pub hourly_activity: Vec<DAppHourlyActivity>, // Current 7-day activity heat map
+    pub nft_counts: NftActivityCounter,          // Hourly mints/transfers and minters of NFT DApps
+    pub nft_stats: Vec<DAppNftStats>,            // Current 24h NFT stats
+    pub lending_counts: LendingActivityCounter,  // Hourly lending actions, depositors and borrowers of lending DApps
+    pub lending_stats: Vec<DAppLendingStats>,    // Current 24h lending stats
+    pub bridge_counts: BridgeActivityCounter,    // Hourly transfers and moved amounts per coin of bridges
+    pub bridge_stats: Vec<BridgeStats>,          // Current 24h bridge stats
+    pub swap_volume_counts: SwapVolumeCounter,   // Hourly spent amounts per coin of DEX and aggregator swaps
+    pub price_source: Arc<dyn PriceSource>,      // Values swapped coins in USD
+    pub dapp_names: HashMap<String, DAppRegistryEntry>,      // package_id -> registry entry (name, type, enabled)
+    pub publishers: HashMap<String, DAppRegistryEntry>,      // publisher address -> registry entry of its DApp
+    pub registry_updates: watch::Sender<Arc<HashMap<String, DAppRegistryEntry>>>, // Publishes registry changes to extractors
     pub last_processed_checkpoint: u64,           // Last checkpoint number processed
+    pub sender_hash_salt: Option<String>,         // When set, senders are stored as salted hashes
+    pub sender_filters: SenderFilterChain,        // Bot/sybil filters applied before counting HAU
+    pub sender_access: SenderAccessList,          // Allowlist/denylist applied during extraction
+    pub user_attribution: UserAttribution,        // Whether senders, sponsors or both count as active users
+    pub daily_sender_counts: HashMap<String, HashMap<String, u64>>, // dapp_name -> sender -> interactions today
+    pub daily_counts_day: Option<NaiveDate>,      // Day (in stats_timezone) the daily counts belong to
+    pub stats_timezone: Tz,                       // Timezone whose midnight starts a new day
+    pub completed_day_top_users: Vec<DAppTopUser>, // Final top users of the previous day
+    pub top_users_limit: usize,                   // Number of top users kept per DApp per day
+    pub min_dau_for_ranking: u32,                 // DApps below this HAU are collapsed into "Others" (0 disables)
+    pub score_formula: ScoreFormula,              // Weighted metrics the rankings are ordered by
+    pub dau_smoother: Option<DauSmoother>,        // Moving average of HAU across computations, if enabled
+    pub write_coalescer: WriteCoalescer,          // Limits how often state is persisted
+    pub pending_write: bool,                      // Latest state failed to persist and awaits a retry
+    pub leadership: Leadership,                   // Only the leader writes to the database
+    pub retention_tracking: bool,                 // Collect daily (DApp, sender) activity for retention cohorts
+    pub pending_daily_activity: Vec<NewDAppUserActivity>, // Daily activity not yet persisted
+    pub runtime_settings: watch::Receiver<RuntimeSettings>, // Runtime-tunable ranking thresholds
+    pub rankings_updates: watch::Sender<Arc<Vec<DAppRanking>>>, // Publishes every newly computed ranking
+    pub name_index: Arc<DAppNameIndex>,           // Search index over the registry's DApp names
+    pub last_ranking_update: Option<DateTime<Utc>>, // When rankings were last recomputed
+    pub warm_up_until: Option<u64>,               // Rankings are held back until this checkpoint is processed
+    pub ranking_window: Duration,                 // Window HAU and the rankings are computed over
+    pub ranking_profiles: Vec<RankingProfile>,    // Rankings over further windows, stored under their own name
+    pub new_users_window: Duration,               // Window new and returning users are counted over
+    pub interaction_dedup: InteractionDedup,      // Whether every event or every transaction is an interaction
+    pub clock: Arc<dyn Clock>,                    // Current time the windows are measured against
 }
 
 impl DAppIndexer {
     /// Creates a new DAppIndexer instance with default values
     /// All rankings start empty and will be calculated as interactions are processed
     pub fn new() -> Self {
-        Self {
-            dapp_interactions: Vec::new(),
-            dapp_rankings: Vec::new(),
-            dapp_names: Self::initialize_dapp_mapping(),
-            last_processed_checkpoint: 0,
+        Self::builder().build()
+    }
+
+    /// Start customizing the registry, windows and extraction rules of a new indexer
+    pub fn builder() -> DAppIndexerBuilder {
+        DAppIndexerBuilder::default()
+    }
+
+    fn build_name_index(dapp_names: &HashMap<String, DAppRegistryEntry>) -> DAppNameIndex {
+        DAppNameIndex::new(
+            dapp_names
+                .values()
+                .map(|entry| (entry.dapp_name.as_str(), entry.dapp_type.as_str())),
+        )
+    }
+
+    /// Tracked DApps whose name matches `query`, for autocomplete
+    pub fn search_dapps(&self, query: &str, limit: usize) -> Vec<DAppSearchResult> {
+        self.name_index.search(query, limit)
+    }
+
+    /// Store salted hashes of sender addresses instead of raw addresses
+    /// Hashing is deterministic for a given salt, so unique-user counts are unaffected
+    pub fn enable_sender_anonymization(&mut self, salt: String) {
+        self.sender_hash_salt = Some(salt);
+    }
+
+    /// Set the filter chain senders must pass before they count toward HAU
+    pub fn set_sender_filters(&mut self, sender_filters: SenderFilterChain) {
+        self.sender_filters = sender_filters;
+    }
+
+    /// Pause or resume tracking for every package of a DApp
+    /// Paused DApps keep their registry entry, interactions and last ranking, but new
+    /// interactions are no longer counted
+    ///
+    /// # Returns
+    /// * Number of registry entries that matched `dapp_name`
+    pub fn set_dapp_enabled(&mut self, dapp_name: &str, enabled: bool) -> usize {
+        let mut matched = 0;
+        for entry in self.dapp_names.values_mut() {
+            if entry.dapp_name == dapp_name {
+                entry.enabled = enabled;
+                matched += 1;
+            }
+        }
+        
+        for entry in self.publishers.values_mut().filter(|entry| entry.dapp_name == dapp_name) {
+            entry.enabled = enabled;
+        }
+        
+        if matched > 0 {
+            info!("{} tracking for {} ({} package IDs)",
+                  if enabled { "▶️ Resumed" } else { "⏸️ Paused" }, dapp_name, matched);
+            self.publish_registry();
+        }
+        matched
+    }
+
+    /// Attribute every package published (or upgraded) by `publisher` to the DApp `dapp_name`
+    /// Publishers are part of the extraction rules, so they must be added before `extractor()`
+    ///
+    /// # Returns
+    /// * false if `dapp_name` is not in the registry
+    pub fn add_publisher(&mut self, publisher: &str, dapp_name: &str) -> bool {
+        let Some(entry) = self.dapp_names.values().find(|entry| entry.dapp_name == dapp_name).cloned() else {
+            return false;
+        };
+        self.publishers.insert(publisher.to_lowercase(), entry);
+        true
+    }
+
+    /// Add packages attributed through their publisher to the registry
+    /// Packages of DApps that are no longer in the registry are skipped
+    ///
+    /// # Returns
+    /// * Number of packages added
+    pub fn register_published_packages(&mut self, packages: &[DAppPublishedPackage]) -> usize {
+        let mut added = 0;
+        for package in packages {
+            if self.dapp_names.contains_key(&package.package_id) {
+                continue;
+            }
+            let Some(entry) = self.dapp_names.values().find(|entry| entry.dapp_name == package.dapp_name).cloned() else {
+                continue;
+            };
+            info!("📦 Attributed package {} to {} (published by {})",
+                  package.package_id, package.dapp_name, package.publisher);
+            self.dapp_names.insert(package.package_id.clone(), entry);
+            added += 1;
+        }
+        
+        if added > 0 {
+            self.publish_registry();
+        }
+        added
+    }
+
+    /// Track `package_id` as part of the DApp `dapp_name`, or move it to that DApp
+    /// The package shares the metadata and pause state of the DApp's other packages
+    pub fn upsert_registry_entry(&mut self, package_id: &str, dapp_name: &str, dapp_type: &str) {
+        self.insert_registry_entry(package_id, dapp_name, dapp_type);
+        info!("➕ Tracking package {} as {} ({})", package_id, dapp_name, dapp_type);
+        self.publish_registry();
+    }
+
+    /// Stop tracking `package_id`
+    /// Its interactions stay in memory but no longer count toward any ranking
+    ///
+    /// # Returns
+    /// * The removed registry entry, None if the package was not tracked
+    pub fn remove_registry_entry(&mut self, package_id: &str) -> Option<DAppRegistryEntry> {
+        let removed = self.dapp_names.remove(package_id)?;
+        info!("➖ Stopped tracking package {} of {}", package_id, removed.dapp_name);
+        self.publish_registry();
+        Some(removed)
+    }
+
+    /// Apply admin registry changes over the current registry, oldest first
+    pub fn apply_registry_records(&mut self, records: &[DAppRegistryRecord]) {
+        for record in records {
+            if record.active {
+                self.insert_registry_entry(&record.package_id, &record.dapp_name, &record.dapp_type);
+            } else {
+                self.dapp_names.remove(&record.package_id);
+            }
         }
+        
+        if !records.is_empty() {
+            self.publish_registry();
+        }
+    }
+
+    fn insert_registry_entry(&mut self, package_id: &str, dapp_name: &str, dapp_type: &str) {
+        let mut entry = DAppRegistryEntry::new(dapp_name, dapp_type);
+        if let Some(sibling) = self.dapp_names.values().find(|entry| entry.dapp_name == dapp_name) {
+            entry.enabled = sibling.enabled;
+            entry.metadata = sibling.metadata.clone();
+        }
+        self.dapp_names.insert(package_id.to_string(), entry);
+    }
+
+    /// Hand the current package registry to every extractor and refresh the name index
+    fn publish_registry(&mut self) {
+        self.name_index = Arc::new(Self::build_name_index(&self.dapp_names));
+        self.registry_updates.send_replace(Arc::new(self.dapp_names.clone()));
+    }
+
+    /// Names of DApps whose tracking is currently paused
+    pub fn paused_dapp_names(&self) -> HashSet<String> {
+        self.dapp_names
+            .values()
+            .filter(|entry| !entry.enabled)
+            .map(|entry| entry.dapp_name.clone())
+            .collect()
+    }
+
+    /// Follow runtime-tunable settings published by a `RuntimeConfig`
+    pub fn set_runtime_settings(&mut self, runtime_settings: watch::Receiver<RuntimeSettings>) {
+        self.runtime_settings = runtime_settings;
+    }
+
+    /// Follow the rankings as they are recomputed
+    /// The receiver starts at the current rankings
+    pub fn subscribe_rankings(&self) -> watch::Receiver<Arc<Vec<DAppRanking>>> {
+        self.rankings_updates.subscribe()
+    }
+
+    /// Set how many of the most active senders are kept per DApp per day
+    pub fn set_top_users_limit(&mut self, top_users_limit: usize) {
+        self.top_users_limit = top_users_limit;
+    }
+
+    /// Collapse DApps with fewer than `min_dau` HAU into a single "Others" ranking row (0 disables)
+    pub fn set_min_dau_for_ranking(&mut self, min_dau: u32) {
+        self.min_dau_for_ranking = min_dau;
+    }
+
+    /// Set the timezone whose midnight starts a new day for daily top users and activity
+    pub fn set_stats_timezone(&mut self, stats_timezone: Tz) {
+        self.stats_timezone = stats_timezone;
+    }
+
+    /// Set how many of the most called functions are kept per DApp
+    pub fn set_top_functions_limit(&mut self, top_functions_limit: usize) {
+        self.top_functions_limit = top_functions_limit;
+    }
+
+    /// Value the coins swapped through DEXes and aggregators with `price_source`
+    pub fn set_price_source(&mut self, price_source: Arc<dyn PriceSource>) {
+        self.price_source = price_source;
+    }
+
+    /// Set the composite score formula rankings are ordered by
+    pub fn set_score_formula(&mut self, score_formula: ScoreFormula) {
+        self.score_formula = score_formula;
+    }
+
+    /// Clock the ranking window and pruning are measured against
+    pub fn clock(&self) -> Arc<dyn Clock> {
+        self.clock.clone()
+    }
+
+    /// Hold rankings back while replaying checkpoints up to `checkpoint_number`
+    /// The window is incomplete until then, so rankings are neither recomputed nor written
+    pub fn set_warm_up_until(&mut self, checkpoint_number: u64) {
+        self.warm_up_until = Some(checkpoint_number);
+    }
+
+    /// True while the warm-up replay has not reached its last checkpoint
+    pub fn is_warming_up(&self) -> bool {
+        self.warm_up_until.is_some()
+    }
+
+    /// Only write to the database while `leadership` says this instance is the leader
+    /// Standbys keep computing rankings in memory
+    pub fn set_leadership(&mut self, leadership: Leadership) {
+        self.leadership = leadership;
+    }
+
+    /// Returns true if this instance may write to the database
+    pub fn is_leader(&self) -> bool {
+        self.leadership.is_leader()
+    }
+
+    /// Smooth HAU over about the last `window` ranking computations (0 disables smoothing)
+    pub fn set_dau_smoothing_window(&mut self, window: u32) {
+        self.dau_smoother = (window > 0).then(|| DauSmoother::new(window));
+    }
+
+    /// Persist state at most once per `min_interval`, and only when it changed
+    pub fn set_min_write_interval(&mut self, min_interval: Duration) {
+        self.write_coalescer = WriteCoalescer::new(min_interval);
+    }
+
+    /// Distinct users of a DApp so far in the current day (in stats_timezone)
+    pub fn daily_active_users(&self, dapp_name: &str) -> usize {
+        self.daily_sender_counts.get(dapp_name).map_or(0, HashMap::len)
+    }
+
+    /// Collect the senders active on each DApp per day so retention cohorts can be computed
+    /// Only enable when the activity is persisted, since it is buffered until then
+    pub fn enable_retention_tracking(&mut self) {
+        self.retention_tracking = true;
+    }
+
+    /// Set the allowlist/denylist used to drop known wallets during extraction
+    pub fn set_sender_access_list(&mut self, sender_access: SenderAccessList) {
+        self.sender_access = sender_access;
+    }
+
+    /// Set whether the sender, the gas sponsor or both count as the active user of a transaction
+    pub fn set_user_attribution(&mut self, user_attribution: UserAttribution) {
+        self.user_attribution = user_attribution;
     }
 
     /// Initialize the DApp name and type mapping based on the provided list
-    fn initialize_dapp_mapping() -> HashMap<String, (String, String)> {
+    fn initialize_dapp_mapping() -> HashMap<String, DAppRegistryEntry> {
         let mut mapping = HashMap::new();
         
         // Existing DApp mappings with types
-        mapping.insert("0xda12d621169da92ed8af5f6b332b7bec64c840bb49bb3d4206d6739cd76bad14".to_string(), ("FanTV AI".to_string(), "AI".to_string()));
-        mapping.insert("0x2cdcc3b1306a49fcd5b8ccded57116ad86ab37a93ba9d91fa1ce06a8d22a21e9".to_string(), ("6degrees".to_string(), "Marketing".to_string()));
-        mapping.insert("0xa2f06318d797e3a2ba734069165e164870677f705d95d8a18b6d9aabbd588709".to_string(), ("Aftermath AMM".to_string(), "DEX".to_string()));
-        mapping.insert("0x04e20ddf36af412a4096f9014f4a565af9e812db9a05cc40254846cf6ed0ad91".to_string(), ("Pyth".to_string(), "Infra".to_string()));
-        mapping.insert("0x9c12f3aa14a449a0a23c066589e269086f021a98939f21158cfacb16d19787c3".to_string(), ("Momentum".to_string(), "DEX".to_string()));
-        mapping.insert("0x7ea6e27ad7af6f3b8671d59df1aaebd7c03dddab893e52a714227b2f4fe91519".to_string(), ("7K Aggregator".to_string(), "Aggregator".to_string()));
-        mapping.insert("0xb908f3c6fea6865d32e2048c520cdfe3b5c5bbcebb658117c41bad70f52b7ccc".to_string(), ("Claynosaurz".to_string(), "NFT".to_string()));
-        mapping.insert("0x21f544aff826a48e6bd5364498454d8487c4a90f84995604cd5c947c06b596c3".to_string(), ("Suilend".to_string(), "Lending".to_string()));
-        mapping.insert("0x9df4666296ee324a6f11e9f664e35e7fd6b6e8c9e9058ce6ee9ad5c5343c2f87".to_string(), ("Ika".to_string(), "Infra".to_string()));
+        mapping.insert("0xda12d621169da92ed8af5f6b332b7bec64c840bb49bb3d4206d6739cd76bad14".to_string(), DAppRegistryEntry::new("FanTV AI", "AI"));
+        mapping.insert("0x2cdcc3b1306a49fcd5b8ccded57116ad86ab37a93ba9d91fa1ce06a8d22a21e9".to_string(), DAppRegistryEntry::new("6degrees", "Marketing"));
+        mapping.insert("0xa2f06318d797e3a2ba734069165e164870677f705d95d8a18b6d9aabbd588709".to_string(), DAppRegistryEntry::new("Aftermath AMM", "DEX"));
+        mapping.insert("0x04e20ddf36af412a4096f9014f4a565af9e812db9a05cc40254846cf6ed0ad91".to_string(), DAppRegistryEntry::new("Pyth", "Infra"));
+        mapping.insert("0x9c12f3aa14a449a0a23c066589e269086f021a98939f21158cfacb16d19787c3".to_string(), DAppRegistryEntry::new("Momentum", "DEX"));
+        mapping.insert("0x7ea6e27ad7af6f3b8671d59df1aaebd7c03dddab893e52a714227b2f4fe91519".to_string(), DAppRegistryEntry::new("7K Aggregator", "Aggregator"));
+        mapping.insert("0xb908f3c6fea6865d32e2048c520cdfe3b5c5bbcebb658117c41bad70f52b7ccc".to_string(), DAppRegistryEntry::new("Claynosaurz", "NFT"));
+        mapping.insert("0x21f544aff826a48e6bd5364498454d8487c4a90f84995604cd5c947c06b596c3".to_string(), DAppRegistryEntry::new("Suilend", "Lending"));
+        mapping.insert("0x9df4666296ee324a6f11e9f664e35e7fd6b6e8c9e9058ce6ee9ad5c5343c2f87".to_string(), DAppRegistryEntry::new("Ika", "Infra"));
         
         
-        mapping.insert("0x5306f64e312b581766351c07af79c72fcb1cd25147157fdc2f8ad76de9a3fb6a".to_string(), ("Portal".to_string(), "Bridge".to_string()));
-        mapping.insert("0x2476333f61ab625ae25205b6726048295fe8b356d26ca841ddf93c69bbd616c8".to_string(), ("Turbos".to_string(), "DEX".to_string()));
-        mapping.insert("0x6f5e582ede61fe5395b50c4a449ec11479a54d7ff8e0158247adfda60d98970b".to_string(), ("Cetus AMM".to_string(), "DEX".to_string()));
-        mapping.insert("0x3864c7c59a4889fec05d1aae4bc9dba5a0e0940594b424fbed44cb3f6ac4c032".to_string(), ("Cetus AMM".to_string(), "DEX".to_string()));
-        mapping.insert("0x51966dc1d9d3e6d85aed55aa87eb9e78e928b4e74b4844a15ef7e3dfb5af3bae".to_string(), ("Cetus Aggregator".to_string(), "Aggregator".to_string()));
-        mapping.insert("0x7cdd26c4aa40c990d5ca780e0919b2de796be9bb41fba461d133bfacb0f677bc".to_string(), ("Cetus Aggregator".to_string(), "Aggregator".to_string()));
-        mapping.insert("0x2c68443db9e8c813b194010c11040a3ce59f47e4eb97a2ec805371505dad7459".to_string(), ("Wave".to_string(), "Infra".to_string()));
-        mapping.insert("0x8d196820b321bb3c56863b3eb0dd90a49f9eb52e3473373efcebf4388bf04416".to_string(), ("SpringSui".to_string(), "Liquid Staking".to_string()));        
+        mapping.insert("0x5306f64e312b581766351c07af79c72fcb1cd25147157fdc2f8ad76de9a3fb6a".to_string(), DAppRegistryEntry::new("Portal", "Bridge"));
+        mapping.insert("0x2476333f61ab625ae25205b6726048295fe8b356d26ca841ddf93c69bbd616c8".to_string(), DAppRegistryEntry::new("Turbos", "DEX"));
+        mapping.insert("0x6f5e582ede61fe5395b50c4a449ec11479a54d7ff8e0158247adfda60d98970b".to_string(), DAppRegistryEntry::new("Cetus AMM", "DEX"));
+        mapping.insert("0x3864c7c59a4889fec05d1aae4bc9dba5a0e0940594b424fbed44cb3f6ac4c032".to_string(), DAppRegistryEntry::new("Cetus AMM", "DEX"));
+        mapping.insert("0x51966dc1d9d3e6d85aed55aa87eb9e78e928b4e74b4844a15ef7e3dfb5af3bae".to_string(), DAppRegistryEntry::new("Cetus Aggregator", "Aggregator"));
+        mapping.insert("0x7cdd26c4aa40c990d5ca780e0919b2de796be9bb41fba461d133bfacb0f677bc".to_string(), DAppRegistryEntry::new("Cetus Aggregator", "Aggregator"));
+        mapping.insert("0x2c68443db9e8c813b194010c11040a3ce59f47e4eb97a2ec805371505dad7459".to_string(), DAppRegistryEntry::new("Wave", "Infra"));
+        mapping.insert("0x8d196820b321bb3c56863b3eb0dd90a49f9eb52e3473373efcebf4388bf04416".to_string(), DAppRegistryEntry::new("SpringSui", "Liquid Staking"));        
         mapping
     }
     
@@ -85,49 +440,193 @@ impl DAppIndexer {
     /// 
     /// # Returns
     /// * Vec<DAppInteraction> containing all DApp interactions found in this checkpoint
+    #[instrument(skip_all, fields(checkpoint = data.checkpoint_summary.sequence_number))]
     pub async fn process_checkpoint(
         &mut self, 
         data: &CheckpointData, 
         db_manager: Option<&DatabaseManager>
     ) -> Vec<DAppInteraction> {
-        let mut all_interactions = Vec::new();
-        let checkpoint_number = data.checkpoint_summary.sequence_number;
-        let checkpoint_timestamp = data.checkpoint_summary.timestamp();
+        let checkpoint = extract_checkpoint(
+            &self.dapp_names,
+            &self.publishers,
+            self.sender_hash_salt.as_deref(),
+            &self.sender_access,
+            self.user_attribution,
+            self.interaction_dedup,
+            self.clock.now() - self.extraction_window(),
+            data,
+        );
+        self.apply_checkpoint(checkpoint, db_manager).await
+    }
+
+    /// Process checkpoints from any source (a subscription, local files, test fixtures)
+    /// in the order the stream yields them, until it ends, without the ingestion executor
+    /// Rankings are refreshed and persisted exactly as for executor-driven checkpoints
+    ///
+    /// Checkpoints are extracted ahead on blocking threads, one per available core, while
+    /// only the aggregation into the window runs here in stream order. With publishers
+    /// registered, checkpoints are extracted one at a time so packages published in one
+    /// checkpoint are tracked in the next.
+    ///
+    /// # Returns
+    /// * Number of checkpoints processed
+    pub async fn process_stream(
+        &mut self,
+        checkpoints: impl Stream<Item = CheckpointData>,
+        db_manager: Option<&DatabaseManager>,
+    ) -> usize {
+        let extractor = self.extractor();
+        let concurrency = if self.publishers.is_empty() {
+            std::thread::available_parallelism().map_or(1, |cores| cores.get())
+        } else {
+            1
+        };
+        let extracted = checkpoints
+            .map(|data| {
+                let extractor = extractor.clone();
+                tokio::task::spawn_blocking(move || extractor.extract(&data))
+            })
+            .buffered(concurrency);
+        let mut extracted = std::pin::pin!(extracted);
 
-        // Skip checkpoints older than 1 hour to ensure we only process recent data
-        let one_hour_ago = SystemTime::now() - Duration::from_secs(60 * 60);
-        if checkpoint_timestamp < one_hour_ago {
-            // Skip this checkpoint as it's too old for our 1h HAU calculation
-            return all_interactions;
+        let mut processed = 0;
+        while let Some(checkpoint) = extracted.next().await {
+            match checkpoint {
+                Ok(checkpoint) => {
+                    self.apply_checkpoint(checkpoint, db_manager).await;
+                    processed += 1;
+                }
+                Err(err) => error!("❌ Checkpoint extraction task panicked: {}", err),
+            }
         }
+        processed
+    }
 
-        // Process each transaction in the checkpoint
-        for (_tx_index, transaction) in data.transactions.iter().enumerate() {
-            // Extract DApp interactions from this transaction
-            let interactions = self.extract_dapp_interactions(transaction, checkpoint_timestamp);
-            
-            // Add to our collection
-            self.dapp_interactions.extend(interactions.clone());
-            all_interactions.extend(interactions);
+    /// Snapshot of the extraction rules, so workers can extract interactions without
+    /// holding the indexer lock
+    pub fn extractor(&self) -> InteractionExtractor {
+        InteractionExtractor {
+            dapp_names: self.registry_updates.subscribe(),
+            publishers: Arc::new(self.publishers.clone()),
+            sender_hash_salt: self.sender_hash_salt.clone(),
+            sender_access: Arc::new(self.sender_access.clone()),
+            user_attribution: self.user_attribution,
+            interaction_dedup: self.interaction_dedup,
+            extraction_window: self.extraction_window(),
+            clock: self.clock.clone(),
+        }
+    }
+
+    /// Longest of the ranking window, the windows of the ranking profiles and the
+    /// 24h statistics window; checkpoints older than this cannot add to any rankings or statistics
+    pub fn extraction_window(&self) -> Duration {
+        self.ranking_profiles
+            .iter()
+            .map(|profile| profile.window)
+            .fold(self.ranking_window.max(STATS_WINDOW), Duration::max)
+    }
+
+    /// Add the interactions of an already extracted checkpoint to the window
+    /// and refresh rankings when due
+    ///
+    /// # Returns
+    /// * The checkpoint's DApp interactions
+    #[instrument(skip_all, fields(checkpoint = checkpoint.checkpoint_number, interactions = checkpoint.interactions.len()))]
+    pub async fn apply_checkpoint(
+        &mut self,
+        checkpoint: ExtractedCheckpoint,
+        db_manager: Option<&DatabaseManager>,
+    ) -> Vec<DAppInteraction> {
+        let ExtractedCheckpoint {
+            checkpoint_number,
+            epoch,
+            interactions: all_interactions,
+            function_calls,
+            transaction_outcomes,
+            bridge_transfers,
+            swap_legs,
+            lending_flows,
+            published_packages,
+        } = checkpoint;
+        self.dapp_interactions.extend(all_interactions.iter().cloned());
+        let now = self.clock.now();
+        for profile in &mut self.ranking_profiles {
+            profile.record(&all_interactions, now);
         }
+        
+        // Track newly published packages of known publishers from now on
+        if self.register_published_packages(&published_packages) > 0 {
+            if let Some(db_manager) = db_manager.filter(|_| self.is_leader()) {
+                let saved = db_manager
+                    .with_retry("save published packages", || db_manager.save_published_packages(&published_packages))
+                    .await;
+                if let Err(err) = saved {
+                    error!("❌ Failed to save published packages: {}", err);
+                }
+            }
+        }
+
+        // Accumulate per-day interaction counts for the top users report
+        self.record_daily_activity(&all_interactions);
+        self.event_counts.record_events(&all_interactions);
+        self.function_counts.record_calls(&function_calls);
+        self.outcome_counts.record_outcomes(&transaction_outcomes);
+        self.bridge_counts.record(&bridge_transfers);
+        self.swap_volume_counts.record(&swap_legs);
+
+        // Follow epoch boundaries; the previous epoch stays open for late checkpoints
+        if self.current_epoch.is_none_or(|current| epoch > current) {
+            if let Some(previous) = self.current_epoch {
+                info!("🗓️ Epoch {} started at checkpoint {}, epoch {} is closing", epoch, checkpoint_number, previous);
+            }
+            self.current_epoch = Some(epoch);
+        }
+        self.epoch_counts.record(epoch, &all_interactions);
+        self.hourly_activity_counts.record(&all_interactions);
+        self.nft_counts.record(all_interactions.iter().filter(|interaction| {
+            self.dapp_names
+                .get(&interaction.package_id)
+                .is_some_and(|entry| entry.dapp_type == NFT_DAPP_TYPE)
+        }));
+        self.lending_counts.record(all_interactions.iter().filter(|interaction| {
+            self.dapp_names
+                .get(&interaction.package_id)
+                .is_some_and(|entry| entry.dapp_type == LENDING_DAPP_TYPE)
+        }));
+        self.lending_counts.record_flows(&lending_flows);
+        self.epoch_counts.prune(self.current_epoch.unwrap_or(epoch));
 
-        // Log only if we found interactions
+        // Log only if we found interactions; the aggregator logs sampled summaries
         if !all_interactions.is_empty() {
-            info!("📦 Checkpoint {}: {} DApp interactions found", 
+            debug!("📦 Checkpoint {}: {} DApp interactions found", 
                   checkpoint_number, all_interactions.len());
         }
 
-        // Always prune old interactions and update rankings to ensure 1h window
+        // Always prune old interactions and update rankings to ensure the ranking window
         self.prune_old_interactions();
         
-        // Update rankings every 10 checkpoints or if we have significant interactions
-        // This ensures rankings stay fresh and reflect recent 1h data
-        if checkpoint_number % 10 == 0 || all_interactions.len() > 5 {
-            self.update_dapp_rankings_1h();
+        // Update rankings every N checkpoints, on significant interaction volume or once
+        // they are too old, as configured in the runtime settings
+        // This ensures rankings stay fresh and reflect the data of the ranking window
+        let since_last_update = self.last_ranking_update
+            .map(|updated| (self.clock.now() - updated).to_std().unwrap_or_default());
+        let recompute = self.runtime_settings.borrow().should_recompute_rankings(
+            checkpoint_number,
+            all_interactions.len(),
+            since_last_update,
+        );
+        // The first rankings after the warm-up replay are computed right away
+        let warmed_up = self.warm_up_until.is_some_and(|until| checkpoint_number >= until);
+        if warmed_up {
+            info!("🔥 Warm-up replay finished at checkpoint {}, switching to live rankings", checkpoint_number);
+            self.warm_up_until = None;
+        }
+        if (recompute && !self.is_warming_up()) || warmed_up {
+            self.update_dapp_rankings();
             
-            // Save to database if available
+            // Save to database if available, coalescing frequent updates
             if let Some(db_manager) = db_manager {
-                if let Err(err) = self.update_data_in_database(db_manager).await {
+                if let Err(err) = self.persist_coalesced(db_manager).await {
                     error!("❌ Failed to update database: {}", err);
                 }
             }
@@ -139,211 +638,868 @@ impl DAppIndexer {
         all_interactions
     }
     
-    /// Extract DApp interactions from a checkpoint transaction
-    /// Identifies when users interact with DApps by analyzing transaction events
-    /// 
-    /// # Arguments
-    /// * `transaction` - The checkpoint transaction to analyze
-    /// * `checkpoint_timestamp` - When the checkpoint occurred
-    /// 
-    /// # Returns
-    /// * Vec<DAppInteraction> containing all DApp interactions found
-    fn extract_dapp_interactions(&self, transaction: &CheckpointTransaction, checkpoint_timestamp: SystemTime) -> Vec<DAppInteraction> {
-        let mut interactions = Vec::new();
-        let tx_digest = transaction.transaction.digest().to_string();
-        
-        // Process events to extract DApp interactions and senders
-        if let Some(events) = &transaction.events {
-            for event in &events.data {
-                // Extract package_id from event
-                let package_id = event.package_id.to_string();
-                
-                // Only process events from our tracked DApps
-                if let Some((dapp_name, _dapp_type)) = self.dapp_names.get(&package_id) {
-                    // Extract sender from event
-                    let sender = event.sender.to_string();
-                    
-                    if sender.is_empty() {
-                        continue;
-                    }
-                    
-                    // Create DApp interaction
-                    interactions.push(DAppInteraction {
-                        package_id,
-                        sender,
-                        timestamp: checkpoint_timestamp,
-                        transaction_digest: tx_digest.clone(),
-                        dapp_name: Some(dapp_name.clone()),
-                    });
+    /// Calculate and update DApp rankings based on the active users of the ranking window (HAU by default)
+    #[instrument(skip_all, fields(interactions = self.dapp_interactions.len()))]
+    pub fn update_dapp_rankings(&mut self) {
+        let now = self.clock.now();
+        let window_start = now - self.ranking_window;
+
+        // Share of failed transactions per DApp over 24h
+        self.outcome_counts.prune(now);
+        let error_rates = error_rates(&self.outcome_counts, now);
+        // USD swap volume of DEXes and aggregators over 24h
+        self.swap_volume_counts.prune(now);
+        let swap_volumes = self.swap_volume_counts.volumes(self.price_source.as_ref(), now);
+        // Lending stats first, their USD volumes go into the rankings of lending DApps
+        self.lending_counts.prune(now);
+        self.lending_stats = self.lending_counts.stats(self.price_source.as_ref(), now);
+
+        let mut rankings = count_rankings(
+            &self.dapp_interactions,
+            &self.dapp_names,
+            &self.sender_filters,
+            window_start,
+            now,
+            &error_rates,
+            &swap_volumes,
+            &self.lending_stats,
+        );
+
+        // Smooth HAU across computations to avoid rank flapping
+        if let Some(smoother) = &mut self.dau_smoother {
+            for ranking in &mut rankings {
+                ranking.dau_smoothed = smoother.update(&ranking.dapp_name, ranking.active_users);
+            }
+            smoother.retain(&rankings.iter().map(|ranking| ranking.dapp_name.as_str()).collect());
+        }
+
+        let rankings = self.rank_dapps(rankings, &self.dapp_rankings, now);
+
+        // Log top 5 DApps if we have rankings
+        if !rankings.is_empty() {
+            info!("🏆 Top DApps ({} active users, score = {}):", window_label(self.ranking_window), self.score_formula);
+            for ranking in rankings.iter().take(5) {
+                info!("  {}. {} - {} users ({} filtered), {} txs, score {:.3}",
+                      ranking.rank, ranking.dapp_name, ranking.active_users, ranking.filtered_users,
+                      ranking.tx_count, ranking.score);
+            }
+        }
+
+        self.dapp_rankings = rankings;
+        self.update_profile_rankings(&error_rates, &swap_volumes, now);
+        self.category_stats = self.compute_category_stats(window_start, now, &swap_volumes);
+        self.package_stats = self.compute_package_stats(window_start, now);
+        self.event_counts.prune(now);
+        self.event_stats = event_stats(&self.event_counts, now);
+        self.epoch_stats = self.epoch_counts.stats();
+        self.hourly_activity_counts.prune(now);
+        self.hourly_activity = self.hourly_activity_counts.stats(now);
+        self.nft_counts.prune(now);
+        self.nft_stats = self.nft_counts.stats(now);
+        self.bridge_counts.prune(now);
+        self.bridge_stats = self.bridge_counts.stats(self.price_source.as_ref(), now);
+        self.function_counts.prune(now);
+        self.function_stats = top_functions(&self.function_counts, self.top_functions_limit, now);
+        self.rankings_updates.send_replace(Arc::new(self.dapp_rankings.clone()));
+        self.last_ranking_update = Some(now);
+
+        // Note: prune_old_interactions is now called in process_checkpoint
+        // to ensure it runs every checkpoint, not just when rankings are updated
+    }
+
+    /// Rank every ranking profile over its own window; profiles are not smoothed
+    fn update_profile_rankings(&mut self, error_rates: &HashMap<String, f64>, swap_volumes: &HashMap<String, f64>, now: DateTime<Utc>) {
+        let profile_rankings: Vec<Vec<DAppRanking>> = self.ranking_profiles
+            .iter()
+            .map(|profile| {
+                let rankings = count_rankings(
+                    &profile.interactions,
+                    &self.dapp_names,
+                    &self.sender_filters,
+                    profile.window_start(now),
+                    now,
+                    error_rates,
+                    swap_volumes,
+                    &self.lending_stats,
+                );
+                self.rank_dapps(rankings, &profile.rankings, now)
+            })
+            .collect();
+        for (profile, rankings) in self.ranking_profiles.iter_mut().zip(profile_rankings) {
+            profile.rankings = rankings;
+        }
+    }
+
+    /// Score, sort and number the counted rankings
+    /// DApps below the minimum HAU are collapsed into "Others" and paused DApps
+    /// keep their last known figures from `previous` after all actively tracked DApps
+    fn rank_dapps(&self, mut rankings: Vec<DAppRanking>, previous: &[DAppRanking], now: DateTime<Utc>) -> Vec<DAppRanking> {
+        // Paused DApps stay visible with their last known figures but are no longer recounted
+        let paused_names = self.paused_dapp_names();
+        let mut paused_rankings: Vec<DAppRanking> = previous
+            .iter()
+            .filter(|ranking| paused_names.contains(&ranking.dapp_name))
+            .cloned()
+            .map(|mut ranking| {
+                ranking.tracking_status = TRACKING_PAUSED.to_string();
+                ranking
+            })
+            .collect();
+        paused_rankings.sort_by(compare_rankings);
+
+        // Score active DApps with the configured formula and sort them
+        // Barely-used DApps are collapsed into "Others" after the ranked DApps,
+        // paused DApps are placed after all actively tracked DApps
+        score_rankings(&mut rankings, &self.score_formula);
+        let mut rankings = collapse_below_min_dau(rankings, self.min_dau_for_ranking, now);
+        rankings.extend(paused_rankings);
+        for (index, ranking) in rankings.iter_mut().enumerate() {
+            ranking.rank = (index + 1) as u32;
+        }
+        rankings
+    }
+
+    /// Count interactions per (DApp, sender) for the current day in the stats timezone
+    /// When the day changes, the final top users of the finished day are kept
+    /// for persistence and the counters start over
+    fn record_daily_activity(&mut self, interactions: &[DAppInteraction]) {
+        for interaction in interactions {
+            let day = interaction.timestamp.with_timezone(&self.stats_timezone).date_naive();
+            match self.daily_counts_day {
+                Some(current_day) if day < current_day => continue, // Late data from a finished day
+                Some(current_day) if day > current_day => {
+                    self.completed_day_top_users = self.compute_top_users();
+                    self.daily_sender_counts.clear();
+                    self.daily_counts_day = Some(day);
                 }
-                // Skip all other package_ids that are not in our tracked list
+                None => self.daily_counts_day = Some(day),
+                _ => {}
+            }
+
+            let Some(dapp_name) = &interaction.dapp_name else {
+                continue;
+            };
+            let count = self.daily_sender_counts
+                .entry(dapp_name.clone())
+                .or_default()
+                .entry(interaction.sender.clone())
+                .or_default();
+            *count += 1;
+
+            // First interaction of this sender with the DApp today
+            if *count == 1 && self.retention_tracking {
+                self.pending_daily_activity.push(NewDAppUserActivity {
+                    day,
+                    dapp_name: dapp_name.clone(),
+                    sender: interaction.sender.clone(),
+                });
             }
         }
+    }
 
-        interactions
+    /// Compute the most active senders per DApp for the day currently being counted
+    pub fn compute_top_users(&self) -> Vec<DAppTopUser> {
+        let Some(day) = self.daily_counts_day else {
+            return Vec::new();
+        };
+
+        let mut top_users = Vec::new();
+        for (dapp_name, senders) in &self.daily_sender_counts {
+            let mut sorted: Vec<(&String, &u64)> = senders.iter().collect();
+            sorted.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+
+            for (index, (sender, count)) in sorted.into_iter().take(self.top_users_limit).enumerate() {
+                top_users.push(DAppTopUser {
+                    day,
+                    dapp_name: dapp_name.clone(),
+                    sender: sender.clone(),
+                    interaction_count: *count,
+                    rank: (index + 1) as u32,
+                });
+            }
+        }
+        top_users
     }
 
-    /// Calculate and update 1-hour DApp rankings based on Hourly Active Users (HAU)
-    fn update_dapp_rankings_1h(&mut self) {
-        let now = SystemTime::now();
-        let one_hour_ago = now - Duration::from_secs(60 * 60); // Changed from 24 * 60 * 60 to 60 * 60
+    /// Roll up active users, transactions and interactions per DApp type
+    /// Users are deduplicated across all DApps of a category, so a sender using
+    /// two DEXes counts once toward DEX category HAU
+    /// DEX and Aggregator categories also sum the swap volume of their DApps, the
+    /// figure of their rankings, including DApps collapsed into "Others"
+    fn compute_category_stats(&self, window_start: DateTime<Utc>, now: DateTime<Utc>, swap_volumes: &HashMap<String, f64>) -> Vec<CategoryStats> {
+        let mut category_senders: HashMap<String, HashMap<String, Vec<&DAppInteraction>>> = HashMap::new();
+        let mut category_transactions: HashMap<String, HashSet<&str>> = HashMap::new();
+        let mut category_dapps: HashMap<String, HashSet<&str>> = HashMap::new();
 
-        // Count unique users per DApp NAME (not package_id) in the last 1 hour
-        // This ensures DApps with multiple package IDs are counted as one unified DApp
-        let mut dapp_user_counts: HashMap<String, HashSet<String>> = HashMap::new();
+        // Every DApp counted once, however many packages it has
+        let swap_dapps: HashMap<&str, &str> = self.dapp_names
+            .values()
+            .filter(|entry| entry.enabled && SWAP_DAPP_TYPES.contains(&entry.dapp_type.as_str()))
+            .map(|entry| (entry.dapp_name.as_str(), entry.dapp_type.as_str()))
+            .collect();
+        let mut category_volumes: HashMap<&str, f64> = HashMap::new();
+        for (dapp_name, dapp_type) in swap_dapps {
+            if let Some(volume) = swap_volumes.get(dapp_name).filter(|volume| **volume > 0.0) {
+                *category_volumes.entry(dapp_type).or_default() += volume;
+                // Like their rankings, categories with volume get a row without users in the window
+                category_senders.entry(dapp_type.to_string()).or_default();
+                category_dapps.entry(dapp_type.to_string()).or_default().insert(dapp_name);
+            }
+        }
 
-        // Process all DApp interactions from the last 1 hour
         for interaction in &self.dapp_interactions {
-            if interaction.timestamp >= one_hour_ago {
-                // Only count interactions for DApps that are in our tracked mapping
-                if let Some((dapp_name, _dapp_type)) = self.dapp_names.get(&interaction.package_id) {
-                    // Count unique users by DApp NAME, not package_id
-                    // This fixes the issue where DApps with multiple package IDs 
-                    // would have inflated HAU counts
-                    dapp_user_counts
-                        .entry(dapp_name.clone()) // Use dapp_name as key instead of package_id
-                        .or_insert_with(HashSet::new)
-                        .insert(interaction.sender.clone());
-                }
+            if interaction.timestamp < window_start {
+                continue;
+            }
+            if let Some(entry) = self.dapp_names.get(&interaction.package_id).filter(|entry| entry.enabled) {
+                category_senders
+                    .entry(entry.dapp_type.clone())
+                    .or_default()
+                    .entry(interaction.sender.clone())
+                    .or_default()
+                    .push(interaction);
+                category_transactions
+                    .entry(entry.dapp_type.clone())
+                    .or_default()
+                    .insert(interaction.transaction_digest.as_str());
+                category_dapps
+                    .entry(entry.dapp_type.clone())
+                    .or_default()
+                    .insert(entry.dapp_name.as_str());
             }
         }
 
-        // Convert to rankings - group by DApp name
-        let mut rankings: Vec<DAppRanking> = dapp_user_counts
+        let mut stats: Vec<CategoryStats> = category_senders
             .into_iter()
-            .map(|(dapp_name, users)| {
-                // Find the first package_id for this dapp_name (for reference)
-                let package_id = self.dapp_names
-                    .iter()
-                    .find(|(_, (name, _))| name == &dapp_name)
-                    .map(|(id, _)| id.clone())
-                    .unwrap_or_else(|| "unknown".to_string());
-                
-                // Get dapp_type for this dapp_name
-                let dapp_type = self.dapp_names
+            .map(|(dapp_type, senders)| {
+                let active_users = senders
                     .iter()
-                    .find(|(_, (name, _))| name == &dapp_name)
-                    .map(|(_, (_, type_name))| type_name.clone())
-                    .unwrap_or_else(|| "Unknown".to_string());
-
-                DAppRanking {
-                    rank: 0, // Will be set after sorting
-                    package_id, // Use first package_id as reference
-                    dapp_name,
-                    dau_1h: users.len() as u32, // 1-hour Hourly Active Users count
+                    .filter(|(sender, interactions)| self.sender_filters.accepts(sender, interactions))
+                    .count();
+                let interaction_count: usize = senders.values().map(Vec::len).sum();
+
+                CategoryStats {
+                    active_users: active_users as u32,
+                    transaction_count: category_transactions.get(&dapp_type).map_or(0, HashSet::len) as u32,
+                    interaction_count: interaction_count as u32,
+                    dapp_count: category_dapps.get(&dapp_type).map_or(0, HashSet::len) as u32,
+                    volume_24h_usd: category_volumes.get(dapp_type.as_str()).copied().unwrap_or(0.0),
                     last_update: now,
                     dapp_type,
                 }
             })
             .collect();
 
-        // Sort by HAU (descending) and assign ranks
-        rankings.sort_by(|a, b| b.dau_1h.cmp(&a.dau_1h));
-        for (index, ranking) in rankings.iter_mut().enumerate() {
-            ranking.rank = (index + 1) as u32;
-        }
+        stats.sort_by_key(|category| std::cmp::Reverse(category.active_users));
+        stats
+    }
 
-        // Log top 5 DApps if we have rankings
-        if !rankings.is_empty() {
-            info!("🏆 Top DApps (1h HAU - Hourly Active Users):");
-            for ranking in rankings.iter().take(5) {
-                info!("  {}. {} - {} HAU", ranking.rank, ranking.dapp_name, ranking.dau_1h);
+    /// Active users, transactions and interactions of every package on its own
+    /// A sender using two packages of the same DApp counts toward both packages
+    fn compute_package_stats(&self, window_start: DateTime<Utc>, now: DateTime<Utc>) -> Vec<DAppPackageStats> {
+        let mut package_senders: HashMap<&str, HashMap<&str, Vec<&DAppInteraction>>> = HashMap::new();
+
+        for interaction in &self.dapp_interactions {
+            if interaction.timestamp < window_start {
+                continue;
+            }
+            if self.dapp_names.get(&interaction.package_id).is_some_and(|entry| entry.enabled) {
+                package_senders
+                    .entry(interaction.package_id.as_str())
+                    .or_default()
+                    .entry(interaction.sender.as_str())
+                    .or_default()
+                    .push(interaction);
             }
         }
 
-        self.dapp_rankings = rankings;
+        let mut stats: Vec<DAppPackageStats> = package_senders
+            .into_iter()
+            .map(|(package_id, senders)| {
+                let entry = &self.dapp_names[package_id];
+                let accepted: Vec<&Vec<&DAppInteraction>> = senders
+                    .iter()
+                    .filter(|(sender, interactions)| self.sender_filters.accepts(sender, interactions))
+                    .map(|(_, interactions)| interactions)
+                    .collect();
+                // Gas of each transaction, counted once however many events it emitted
+                let transactions: HashMap<&str, u64> = accepted
+                    .iter()
+                    .flat_map(|interactions| interactions.iter())
+                    .map(|interaction| (interaction.transaction_digest.as_str(), interaction.gas_used))
+                    .collect();
 
-        // Note: prune_old_interactions is now called in process_checkpoint
-        // to ensure it runs every checkpoint, not just when rankings are updated
+                DAppPackageStats {
+                    package_id: package_id.to_string(),
+                    dapp_name: entry.dapp_name.clone(),
+                    dapp_type: entry.dapp_type.clone(),
+                    active_users: accepted.len() as u32,
+                    transaction_count: transactions.len() as u32,
+                    interaction_count: accepted.iter().map(|interactions| interactions.len()).sum::<usize>() as u32,
+                    avg_gas_per_tx: average_gas(transactions.values().sum(), transactions.len()),
+                    last_update: now,
+                }
+            })
+            .collect();
+
+        stats.sort_by(|a, b| {
+            b.active_users
+                .cmp(&a.active_users)
+                .then_with(|| a.package_id.cmp(&b.package_id))
+        });
+        stats
     }
 
-    /// Remove interactions older than 1 hour and from untracked DApps to prevent memory growth
-    fn prune_old_interactions(&mut self) {
-        let one_hour_ago = SystemTime::now() - Duration::from_secs(60 * 60); // Changed from 24 * 60 * 60 to 60 * 60
+    /// Remove interactions older than the ranking window and from untracked DApps to prevent memory growth
+    pub fn prune_old_interactions(&mut self) {
+        let window_start = self.clock.now() - self.ranking_window;
         let initial_count = self.dapp_interactions.len();
         
         self.dapp_interactions.retain(|interaction| {
             // Keep only interactions that are:
-            // 1. Within the last 1 hour
+            // 1. Within the ranking window
             // 2. From tracked DApps
-            interaction.timestamp >= one_hour_ago && 
+            interaction.timestamp >= window_start && 
             self.dapp_names.contains_key(&interaction.package_id)
         });
         
         let removed_count = initial_count - self.dapp_interactions.len();
         if removed_count > 0 {
-            info!("🗑️ Pruned {} old interactions, {} remaining", removed_count, self.dapp_interactions.len());
+            debug!("🗑️ Pruned {} old interactions, {} remaining", removed_count, self.dapp_interactions.len());
         }
-    }
-
-    /// Save current state to database
-    pub async fn update_data_in_database(&self, db_manager: &DatabaseManager) -> Result<()> {
-        // Clean up Unknown DApps and untracked interactions first
-        db_manager.cleanup_unknown_dapps().await?;
-        
-        // Save current in-memory rankings directly to database
-        // This replaces the database calculation since we don't store interactions in DB
-        db_manager.save_rankings_from_memory(&self.dapp_rankings).await?;
-        info!("💾 Updated DApp rankings in database");
 
-        Ok(())
+        let now = self.clock.now();
+        for profile in &mut self.ranking_profiles {
+            profile.prune(now, &self.dapp_names);
+        }
     }
 
-    /// Load existing data from database
-    pub async fn get_data_from_database(&mut self, db_manager: &DatabaseManager) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // Load existing DApp rankings from database
-        let ranking_records = db_manager.get_dapp_rankings().await?;
-        
-        self.dapp_rankings = ranking_records.into_iter().map(|record| {
-            // Convert database NaiveDateTime to SystemTime, fallback to now if None
-            let last_update = record.last_update
-                .map(|naive_dt| {
-                    // Convert NaiveDateTime to SystemTime
-                    let timestamp = naive_dt.and_utc().timestamp() as u64;
-                    SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(timestamp)
-                })
-                .unwrap_or_else(SystemTime::now);
-                
-            DAppRanking {
-                rank: record.rank_position as u32,
-                package_id: record.package_id,
-                dapp_name: record.dapp_name,
-                dau_1h: record.dau_1h as u32, // 1-hour Hourly Active Users count
-                last_update, // Use actual timestamp from database
-                dapp_type: record.dapp_type,
-            }
-        }).collect();
-             
-        info!("Loaded {} DApp rankings from database", self.dapp_rankings.len());
-        Ok(())
-    }
+    /// Senders counted toward HAU per DApp with their earliest in-window interaction
+    fn active_senders(&self) -> Vec<(String, String, DateTime<Utc>)> {
+        let window_start = self.clock.now() - self.ranking_window;
+        let mut dapp_sender_interactions: HashMap<&str, HashMap<&str, Vec<&DAppInteraction>>> = HashMap::new();
 
-    /// Get all DApp interactions
-    pub fn get_dapp_interactions(&self) -> &Vec<DAppInteraction> {
-        &self.dapp_interactions
-    }
+        for interaction in &self.dapp_interactions {
+            if interaction.timestamp < window_start {
+                continue;
+            }
+            if let Some(entry) = self.dapp_names.get(&interaction.package_id).filter(|entry| entry.enabled) {
+                dapp_sender_interactions
+                    .entry(entry.dapp_name.as_str())
+                    .or_default()
+                    .entry(interaction.sender.as_str())
+                    .or_default()
+                    .push(interaction);
+            }
+        }
 
-    /// Get all DApp rankings
-    pub fn get_dapp_rankings(&self) -> &Vec<DAppRanking> {
-        &self.dapp_rankings
+        let mut active_senders = Vec::new();
+        for (dapp_name, senders) in dapp_sender_interactions {
+            for (sender, interactions) in senders {
+                if !self.sender_filters.accepts(sender, &interactions) {
+                    continue;
+                }
+                if let Some(first_seen) = interactions.iter().map(|interaction| interaction.timestamp).min() {
+                    active_senders.push((dapp_name.to_string(), sender.to_string(), first_seen));
+                }
+            }
+        }
+        active_senders
     }
 
-    /// Get top N DApps by ranking
-    pub fn get_top_dapps(&self, limit: usize) -> Vec<DAppRanking> {
-        self.dapp_rankings
+    /// Record first-seen timestamps of active users and refresh the
+    /// new_users_24h / returning_users_24h columns of the current rankings
+    async fn update_new_and_returning_users(&mut self, db_manager: &DatabaseManager) -> Result<()> {
+        let active_senders = self.active_senders();
+        if active_senders.is_empty() {
+            return Ok(());
+        }
+
+        let first_seen: Vec<NewDAppUserFirstSeen> = active_senders
             .iter()
-            .take(limit)
-            .cloned()
-            .collect()
-    }
+            .map(|(dapp_name, sender, timestamp)| NewDAppUserFirstSeen {
+                dapp_name: dapp_name.clone(),
+                sender: sender.clone(),
+                first_seen: timestamp.naive_utc(),
+            })
+            .collect();
+        db_manager
+            .with_retry("record first seen", || db_manager.record_first_seen(&first_seen))
+            .await?;
 
-    /// Clear all existing data and reset to only track the 10 specified DApps
-    pub fn reset_to_tracked_dapps_only(&mut self) {
+        let since = (self.clock.now() - self.new_users_window).naive_utc();
+        let (dapp_names, senders): (Vec<String>, Vec<String>) = active_senders
+            .into_iter()
+            .map(|(dapp_name, sender, _)| (dapp_name, sender))
+            .unzip();
+        let breakdown = db_manager
+            .with_retry("count new and returning users", || {
+                db_manager.count_new_and_returning_users(dapp_names.clone(), senders.clone(), since)
+            })
+            .await?;
+
+        for ranking in self.dapp_rankings.iter_mut() {
+            if let Some((new_users, returning_users)) = breakdown.get(&ranking.dapp_name) {
+                ranking.new_users_24h = *new_users;
+                ranking.returning_users_24h = *returning_users;
+            }
+        }
+
+        // "Others" adds up the DApps collapsed into it, i.e. those without a ranking row
+        let ranked: HashSet<&str> = self.dapp_rankings.iter().map(|ranking| ranking.dapp_name.as_str()).collect();
+        let (new_users, returning_users) = breakdown
+            .iter()
+            .filter(|(dapp_name, _)| !ranked.contains(dapp_name.as_str()))
+            .fold((0, 0), |(new, returning), (_, counts)| (new + counts.0, returning + counts.1));
+        if let Some(others) = self.dapp_rankings.iter_mut().find(|ranking| ranking.package_id == OTHERS_PACKAGE_ID) {
+            others.new_users_24h = new_users;
+            others.returning_users_24h = returning_users;
+        }
+        Ok(())
+    }
+
+    /// Save current state to database through the write coalescer
+    /// Skips the write when the persisted state would not change or the last
+    /// write was too recent; skipped changes are written by a later call.
+    /// A failed write stays pending and is flushed by the first call after the
+    /// database recovers, regardless of the write interval
+    ///
+    /// # Returns
+    /// * true if the state was written
+    #[instrument(skip_all)]
+    pub async fn persist_coalesced(&mut self, db_manager: &DatabaseManager) -> Result<bool> {
+        // Standbys never write; a new leader's first call writes its current state
+        if !self.is_leader() {
+            return Ok(false);
+        }
+
+        let fingerprint = self.state_fingerprint();
+        if !self.pending_write && !self.write_coalescer.should_write(fingerprint, self.clock.now()) {
+            return Ok(false);
+        }
+
+        // Keep the latest state pending while operations are paused
+        if !db_manager.is_available() {
+            self.pending_write = true;
+            return Ok(false);
+        }
+
+        if let Err(err) = self.update_data_in_database(db_manager).await {
+            self.pending_write = true;
+            return Err(err);
+        }
+        if self.pending_write {
+            info!("💾 Flushed pending DApp rankings after database recovery");
+            self.pending_write = false;
+        }
+        self.write_coalescer.record_write(fingerprint, self.clock.now());
+        Ok(true)
+    }
+
+    /// Returns true if the latest state could not be written yet
+    pub fn has_pending_write(&self) -> bool {
+        self.pending_write
+    }
+
+    /// Fingerprint of the state written by `update_data_in_database`
+    /// Computation timestamps are left out so recomputing identical rankings is not a change
+    fn state_fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        for ranking in &self.dapp_rankings {
+            ranking.rank.hash(&mut hasher);
+            ranking.dapp_name.hash(&mut hasher);
+            ranking.active_users.hash(&mut hasher);
+            ranking.filtered_users.hash(&mut hasher);
+            ranking.tracking_status.hash(&mut hasher);
+            ranking.tx_count.hash(&mut hasher);
+            ranking.gas_used.hash(&mut hasher);
+            ranking.score.to_bits().hash(&mut hasher);
+            ranking.error_rate_24h.to_bits().hash(&mut hasher);
+            ranking.dau_smoothed.to_bits().hash(&mut hasher);
+            ranking.sponsored_user_pct.to_bits().hash(&mut hasher);
+            ranking.avg_gas_per_tx.to_bits().hash(&mut hasher);
+            ranking.interactions_p50.hash(&mut hasher);
+            ranking.interactions_p90.hash(&mut hasher);
+            ranking.interactions_p99.hash(&mut hasher);
+            ranking.top_users_interaction_pct.to_bits().hash(&mut hasher);
+            ranking.volume_24h_usd.to_bits().hash(&mut hasher);
+            ranking.deposit_volume_24h_usd.to_bits().hash(&mut hasher);
+            ranking.borrow_volume_24h_usd.to_bits().hash(&mut hasher);
+        }
+        for profile in &self.ranking_profiles {
+            profile.name.hash(&mut hasher);
+            for ranking in &profile.rankings {
+                ranking.rank.hash(&mut hasher);
+                ranking.dapp_name.hash(&mut hasher);
+                ranking.active_users.hash(&mut hasher);
+                ranking.tracking_status.hash(&mut hasher);
+                ranking.tx_count.hash(&mut hasher);
+                ranking.gas_used.hash(&mut hasher);
+                ranking.score.to_bits().hash(&mut hasher);
+                ranking.sponsored_user_pct.to_bits().hash(&mut hasher);
+            }
+        }
+        for stats in &self.category_stats {
+            stats.dapp_type.hash(&mut hasher);
+            stats.active_users.hash(&mut hasher);
+            stats.transaction_count.hash(&mut hasher);
+            stats.interaction_count.hash(&mut hasher);
+            stats.dapp_count.hash(&mut hasher);
+            stats.volume_24h_usd.to_bits().hash(&mut hasher);
+        }
+        for stats in &self.package_stats {
+            stats.package_id.hash(&mut hasher);
+            stats.active_users.hash(&mut hasher);
+            stats.transaction_count.hash(&mut hasher);
+            stats.interaction_count.hash(&mut hasher);
+        }
+        for stats in &self.event_stats {
+            stats.dapp_name.hash(&mut hasher);
+            stats.event_type.hash(&mut hasher);
+            stats.count_24h.hash(&mut hasher);
+        }
+        for stats in &self.epoch_stats {
+            stats.epoch.hash(&mut hasher);
+            stats.dapp_name.hash(&mut hasher);
+            stats.active_users.hash(&mut hasher);
+            stats.transaction_count.hash(&mut hasher);
+        }
+        for activity in &self.hourly_activity {
+            activity.dapp_name.hash(&mut hasher);
+            activity.hour_of_day.hash(&mut hasher);
+            activity.unique_users.hash(&mut hasher);
+        }
+        for stats in &self.nft_stats {
+            stats.dapp_name.hash(&mut hasher);
+            stats.mints_24h.hash(&mut hasher);
+            stats.unique_minters_24h.hash(&mut hasher);
+            stats.transfers_24h.hash(&mut hasher);
+        }
+        for stats in &self.lending_stats {
+            stats.dapp_name.hash(&mut hasher);
+            stats.deposits_24h.hash(&mut hasher);
+            stats.withdrawals_24h.hash(&mut hasher);
+            stats.borrows_24h.hash(&mut hasher);
+            stats.repays_24h.hash(&mut hasher);
+            stats.unique_depositors_24h.hash(&mut hasher);
+            stats.unique_borrowers_24h.hash(&mut hasher);
+            stats.deposit_volume_24h_usd.to_bits().hash(&mut hasher);
+            stats.borrow_volume_24h_usd.to_bits().hash(&mut hasher);
+        }
+        for stats in &self.bridge_stats {
+            stats.dapp_name.hash(&mut hasher);
+            stats.inbound_transfers_24h.hash(&mut hasher);
+            stats.outbound_transfers_24h.hash(&mut hasher);
+            stats.inbound_volume_24h_usd.to_bits().hash(&mut hasher);
+            stats.outbound_volume_24h_usd.to_bits().hash(&mut hasher);
+        }
+        for stats in &self.function_stats {
+            stats.dapp_name.hash(&mut hasher);
+            stats.function.hash(&mut hasher);
+            stats.call_count_24h.hash(&mut hasher);
+        }
+        self.pending_daily_activity.len().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Save current state to database
+    #[instrument(skip_all, fields(dapps = self.dapp_rankings.len()))]
+    pub async fn update_data_in_database(&mut self, db_manager: &DatabaseManager) -> Result<()> {
+        // Move Unknown DApps and untracked rankings out of the rankings first
+        let mut tracked_package_ids: Vec<String> = self.dapp_names.keys().cloned().collect();
+        tracked_package_ids.push(OTHERS_PACKAGE_ID.to_string());
+        db_manager
+            .with_retry("cleanup unknown dapps", || db_manager.cleanup_unknown_dapps(&tracked_package_ids))
+            .await?;
+        
+        // Split active users into new and returning users using persisted first-seen data
+        self.update_new_and_returning_users(db_manager).await?;
+        
+        // Save current in-memory rankings directly to database
+        // This replaces the database calculation since we don't store interactions in DB
+        db_manager
+            .with_retry("save rankings", || db_manager.save_rankings_from_memory(db_manager.live_window(), &self.dapp_rankings))
+            .await?;
+        db_manager
+            .with_retry("save ranking history", || db_manager.save_ranking_history(&self.dapp_rankings))
+            .await?;
+        // Profiles take new and returning users from the live rankings
+        let live: HashMap<&str, &DAppRanking> = self.dapp_rankings
+            .iter()
+            .map(|ranking| (ranking.dapp_name.as_str(), ranking))
+            .collect();
+        for profile in &mut self.ranking_profiles {
+            for ranking in &mut profile.rankings {
+                if let Some(live) = live.get(ranking.dapp_name.as_str()) {
+                    ranking.new_users_24h = live.new_users_24h;
+                    ranking.returning_users_24h = live.returning_users_24h;
+                }
+            }
+        }
+        for profile in &self.ranking_profiles {
+            db_manager
+                .with_retry("save profile rankings", || db_manager.save_rankings_from_memory(&profile.name, &profile.rankings))
+                .await?;
+        }
+        db_manager
+            .with_retry("save category stats", || db_manager.save_category_stats(&self.category_stats))
+            .await?;
+        db_manager
+            .with_retry("save package stats", || db_manager.save_package_stats(&self.package_stats))
+            .await?;
+        db_manager
+            .with_retry("save event stats", || db_manager.save_event_stats(&self.event_stats))
+            .await?;
+        db_manager
+            .with_retry("save epoch stats", || db_manager.save_epoch_stats(&self.epoch_stats))
+            .await?;
+        db_manager
+            .with_retry("save hourly activity", || db_manager.save_hourly_activity(&self.hourly_activity))
+            .await?;
+        db_manager
+            .with_retry("save NFT stats", || db_manager.save_nft_stats(&self.nft_stats))
+            .await?;
+        db_manager
+            .with_retry("save lending stats", || db_manager.save_lending_stats(&self.lending_stats))
+            .await?;
+        db_manager
+            .with_retry("save bridge stats", || db_manager.save_bridge_stats(&self.bridge_stats))
+            .await?;
+        db_manager
+            .with_retry("save function stats", || db_manager.save_function_stats(&self.function_stats))
+            .await?;
+        
+        // Top users of the previous day are rewritten until the next day completes
+        let current_top_users = self.compute_top_users();
+        db_manager
+            .with_retry("save top users", || db_manager.save_top_users(&self.completed_day_top_users))
+            .await?;
+        db_manager
+            .with_retry("save top users", || db_manager.save_top_users(&current_top_users))
+            .await?;
+        
+        // Daily activity feeds the nightly retention cohorts and the unique-user sketches
+        if !self.pending_daily_activity.is_empty() {
+            db_manager
+                .with_retry("record daily activity", || db_manager.record_daily_activity(&self.pending_daily_activity))
+                .await?;
+            let now = self.clock.now().naive_utc();
+            db_manager
+                .with_retry("merge daily user sketches", || db_manager.merge_daily_user_sketches(&self.pending_daily_activity, now))
+                .await?;
+            self.pending_daily_activity.clear();
+        }
+        info!("💾 Updated DApp rankings, category, package, event, epoch and function stats and top users in database");
+
+        Ok(())
+    }
+
+    /// Load existing data from database
+    pub async fn get_data_from_database(&mut self, db_manager: &DatabaseManager) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        // Load existing DApp rankings from database
+        let ranking_records = db_manager.get_dapp_rankings().await?;
+        
+        self.dapp_rankings = ranking_records.into_iter().map(DAppRanking::from).collect();
+        self.rankings_updates.send_replace(Arc::new(self.dapp_rankings.clone()));
+             
+        info!("Loaded {} DApp rankings from database", self.dapp_rankings.len());
+        
+        self.load_registry_from_database(db_manager).await?;
+        Ok(())
+    }
+
+    /// Load the persisted registry state: admin registry changes, DApp metadata and
+    /// packages attributed through their publisher
+    pub async fn load_registry_from_database(&mut self, db_manager: &DatabaseManager) -> Result<()> {
+        let registry_records = db_manager.get_registry_entries().await?;
+        info!("Loaded {} admin registry changes from database", registry_records.len());
+        self.apply_registry_records(&registry_records);
+        
+        let metadata = db_manager.get_all_dapp_metadata().await?;
+        info!("Loaded metadata of {} DApps from database", metadata.len());
+        self.apply_dapp_metadata(metadata);
+        
+        let published_packages = db_manager.get_published_packages().await?;
+        let added = self.register_published_packages(&published_packages);
+        info!("Loaded {} packages attributed through their publisher", added);
+        Ok(())
+    }
+
+    /// Recompute the rankings and write them right away, bypassing the write interval
+    /// Used after registry changes so they show up without waiting for the ranking job
+    pub async fn recompute_rankings_now(&mut self, db_manager: &DatabaseManager) -> Result<()> {
+        self.prune_old_interactions();
+        self.update_dapp_rankings();
+        self.update_data_in_database(db_manager).await?;
+        self.pending_write = false;
+        self.write_coalescer.record_write(self.state_fingerprint(), self.clock.now());
+        Ok(())
+    }
+
+    /// Recompute the rankings of `window_label` from the interactions persisted over
+    /// the last `window` and write them; repairs the rankings without replaying checkpoints
+    /// The live window also replaces the in-memory rankings and is recorded in the history
+    /// Sender filters need the in-memory history and are not applied. New and returning
+    /// users and error rates are kept from the current rankings
+    pub async fn recompute_rankings_from_interactions(
+        &mut self,
+        db_manager: &DatabaseManager,
+        window_label: &str,
+        window: Duration,
+    ) -> Result<Vec<DAppRanking>> {
+        let now = self.clock.now();
+        let counted = db_manager
+            .with_retry("recompute rankings", || db_manager.recompute_rankings(window, now))
+            .await?;
+
+        // Only enabled DApps of the registry are counted, like the live rankings
+        let dapp_types: HashMap<&str, &str> = self.dapp_names
+            .values()
+            .filter(|entry| entry.enabled)
+            .map(|entry| (entry.dapp_name.as_str(), entry.dapp_type.as_str()))
+            .collect();
+        let current: HashMap<&str, &DAppRanking> = self.dapp_rankings
+            .iter()
+            .map(|ranking| (ranking.dapp_name.as_str(), ranking))
+            .collect();
+        let rankings: Vec<DAppRanking> = counted
+            .into_iter()
+            .filter_map(|mut ranking| {
+                ranking.dapp_type = dapp_types.get(ranking.dapp_name.as_str())?.to_string();
+                if let Some(current) = current.get(ranking.dapp_name.as_str()) {
+                    ranking.new_users_24h = current.new_users_24h;
+                    ranking.returning_users_24h = current.returning_users_24h;
+                    ranking.error_rate_24h = current.error_rate_24h;
+                }
+                Some(ranking)
+            })
+            .collect();
+
+        let rankings = self.rank_dapps(rankings, &self.dapp_rankings, now);
+        db_manager
+            .with_retry("save rankings", || db_manager.save_rankings_from_memory(window_label, &rankings))
+            .await?;
+        if window_label == db_manager.live_window() {
+            db_manager
+                .with_retry("save ranking history", || db_manager.save_ranking_history(&rankings))
+                .await?;
+            self.dapp_rankings = rankings.clone();
+            self.rankings_updates.send_replace(Arc::new(rankings.clone()));
+        }
+        Ok(rankings)
+    }
+
+    /// Attach stored metadata to the registry entries of every package of each DApp
+    pub fn apply_dapp_metadata(&mut self, metadata: Vec<DAppMetadataRecord>) {
+        let metadata: HashMap<String, DAppMetadata> = metadata
+            .into_iter()
+            .map(|record| (record.dapp_name.clone(), DAppMetadata::from(record)))
+            .collect();
+        for entry in self.dapp_names.values_mut() {
+            entry.metadata = metadata.get(&entry.dapp_name).cloned().unwrap_or_default();
+        }
+    }
+
+    /// Get all DApp interactions
+    pub fn get_dapp_interactions(&self) -> &Vec<DAppInteraction> {
+        &self.dapp_interactions
+    }
+
+    /// Get all DApp rankings
+    pub fn get_dapp_rankings(&self) -> &Vec<DAppRanking> {
+        &self.dapp_rankings
+    }
+
+    /// Get current per-category statistics
+    pub fn get_category_stats(&self) -> &Vec<CategoryStats> {
+        &self.category_stats
+    }
+
+    /// Get current per-package statistics
+    pub fn get_package_stats(&self) -> &Vec<DAppPackageStats> {
+        &self.package_stats
+    }
+
+    /// Get current 24h per-event-type counts
+    pub fn get_event_stats(&self) -> &Vec<DAppEventStats> {
+        &self.event_stats
+    }
+
+    /// Get current stats of the current and previous epoch
+    pub fn get_epoch_stats(&self) -> &Vec<DAppEpochStats> {
+        &self.epoch_stats
+    }
+
+    /// Get the current 7-day activity heat map of every DApp
+    pub fn get_hourly_activity(&self) -> &Vec<DAppHourlyActivity> {
+        &self.hourly_activity
+    }
+
+    /// Get the current 24h mint and transfer stats of NFT DApps
+    pub fn get_nft_stats(&self) -> &Vec<DAppNftStats> {
+        &self.nft_stats
+    }
+
+    /// Get the current 24h deposit and borrow stats of lending DApps
+    pub fn get_lending_stats(&self) -> &Vec<DAppLendingStats> {
+        &self.lending_stats
+    }
+
+    /// Get the current 24h inbound and outbound transfers of bridges
+    pub fn get_bridge_stats(&self) -> &Vec<BridgeStats> {
+        &self.bridge_stats
+    }
+
+    /// Get current 24h most called functions per DApp
+    pub fn get_function_stats(&self) -> &Vec<DAppFunctionStats> {
+        &self.function_stats
+    }
+
+    /// Write the current rankings to `path` for ad-hoc analysis
+    pub async fn export_rankings(&self, path: &Path, format: ExportFormat) -> Result<()> {
+        let rows: Vec<RankingRow> = self.dapp_rankings.iter().map(RankingRow::from).collect();
+        write_rows(&rows, path, format).await
+    }
+
+    /// Write per-package aggregates of the interactions in memory (last hour) to `path`
+    pub async fn export_interaction_aggregates(&self, path: &Path, format: ExportFormat) -> Result<()> {
+        write_rows(&aggregate_interactions(&self.dapp_interactions), path, format).await
+    }
+
+    /// Get top N DApps by ranking
+    pub fn get_top_dapps(&self, limit: usize) -> Vec<DAppRanking> {
+        self.dapp_rankings
+            .iter()
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Clear all existing data and reset to only track the 10 specified DApps
+    pub fn reset_to_tracked_dapps_only(&mut self) {
         // Clear all existing interactions
         self.dapp_interactions.clear();
         
         // Clear all existing rankings
         self.dapp_rankings.clear();
+        self.category_stats.clear();
+        self.package_stats.clear();
+        self.event_counts.clear();
+        self.event_stats.clear();
+        self.epoch_counts.clear();
+        self.epoch_stats.clear();
+        self.hourly_activity_counts.clear();
+        self.hourly_activity.clear();
+        self.nft_counts.clear();
+        self.nft_stats.clear();
+        self.lending_counts.clear();
+        self.lending_stats.clear();
+        self.bridge_counts.clear();
+        self.bridge_stats.clear();
+        self.swap_volume_counts.clear();
+        self.function_counts.clear();
+        self.function_stats.clear();
+        self.outcome_counts.clear();
         
         info!("Reset indexer: cleared all data. Now tracking only {} DApps", 
               self.dapp_names.len());
@@ -359,6 +1515,30 @@ impl DAppIndexer {
         // Reset in-memory data
         self.dapp_interactions.clear();
         self.dapp_rankings.clear();
+        self.category_stats.clear();
+        self.package_stats.clear();
+        self.event_counts.clear();
+        self.event_stats.clear();
+        self.epoch_counts.clear();
+        self.epoch_stats.clear();
+        self.hourly_activity_counts.clear();
+        self.hourly_activity.clear();
+        self.nft_counts.clear();
+        self.nft_stats.clear();
+        self.lending_counts.clear();
+        self.lending_stats.clear();
+        self.bridge_counts.clear();
+        self.bridge_stats.clear();
+        self.swap_volume_counts.clear();
+        self.function_counts.clear();
+        self.function_stats.clear();
+        self.outcome_counts.clear();
+        self.daily_sender_counts.clear();
+        self.daily_counts_day = None;
+        self.completed_day_top_users.clear();
+        self.pending_daily_activity.clear();
+        self.write_coalescer.reset();
+        self.pending_write = false;
         self.last_processed_checkpoint = 0;
         
         info!("✅ Complete reset finished - database and memory cleared");
@@ -368,28 +1548,986 @@ impl DAppIndexer {
     }
 }
 
+/**
+ * DAppIndexerBuilder customizes a DAppIndexer before it processes checkpoints
+ * Settings that are not supplied keep the defaults of `DAppIndexer::new()`
+ */
+#[derive(Debug, Clone)]
+pub struct DAppIndexerBuilder {
+    registry: Option<HashMap<String, DAppRegistryEntry>>,
+    ranking_window: Duration,
+    ranking_profiles: Vec<RankingProfile>,
+    new_users_window: Duration,
+    user_attribution: UserAttribution,
+    interaction_dedup: InteractionDedup,
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for DAppIndexerBuilder {
+    fn default() -> Self {
+        Self {
+            registry: None,
+            ranking_window: DEFAULT_RANKING_WINDOW,
+            ranking_profiles: Vec::new(),
+            new_users_window: DEFAULT_NEW_USERS_WINDOW,
+            user_attribution: UserAttribution::default(),
+            interaction_dedup: InteractionDedup::default(),
+            clock: system_clock(),
+        }
+    }
+}
+
+impl DAppIndexerBuilder {
+    /// Track these packages (package_id -> registry entry) instead of the built-in registry
+    pub fn registry(mut self, registry: HashMap<String, DAppRegistryEntry>) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Compute HAU and the rankings over `window` instead of the last hour
+    pub fn ranking_window(mut self, window: Duration) -> Self {
+        self.ranking_window = window;
+        self
+    }
+
+    /// Also rank the DApps over `window`, stored under `name` next to the live rankings
+    pub fn ranking_profile(mut self, name: impl Into<String>, window: Duration) -> Self {
+        self.ranking_profiles.push(RankingProfile::new(name, window));
+        self
+    }
+
+    /// Count new and returning users over `window` instead of the last 24 hours
+    pub fn new_users_window(mut self, window: Duration) -> Self {
+        self.new_users_window = window;
+        self
+    }
+
+    /// Set whether the sender, the gas sponsor or both count as the active user of a transaction
+    pub fn user_attribution(mut self, user_attribution: UserAttribution) -> Self {
+        self.user_attribution = user_attribution;
+        self
+    }
+
+    /// Set whether every event or every transaction counts as an interaction
+    pub fn interaction_dedup(mut self, interaction_dedup: InteractionDedup) -> Self {
+        self.interaction_dedup = interaction_dedup;
+        self
+    }
+
+    /// Measure the windows against `clock` instead of the system clock
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Create the indexer; all rankings start empty
+    pub fn build(self) -> DAppIndexer {
+        let dapp_names = self.registry.unwrap_or_else(DAppIndexer::initialize_dapp_mapping);
+        let name_index = Arc::new(DAppIndexer::build_name_index(&dapp_names));
+        let registry_updates = watch::channel(Arc::new(dapp_names.clone())).0;
+        DAppIndexer {
+            dapp_interactions: Vec::new(),
+            dapp_rankings: Vec::new(),
+            category_stats: Vec::new(),
+            package_stats: Vec::new(),
+            event_counts: ActivityCounter::default(),
+            event_stats: Vec::new(),
+            function_counts: ActivityCounter::default(),
+            function_stats: Vec::new(),
+            top_functions_limit: 10,
+            outcome_counts: ActivityCounter::default(),
+            epoch_counts: EpochCounter::default(),
+            epoch_stats: Vec::new(),
+            current_epoch: None,
+            hourly_activity_counts: HourlyActivityCounter::default(),
+            hourly_activity: Vec::new(),
+            nft_counts: NftActivityCounter::default(),
+            nft_stats: Vec::new(),
+            lending_counts: LendingActivityCounter::default(),
+            lending_stats: Vec::new(),
+            bridge_counts: BridgeActivityCounter::default(),
+            bridge_stats: Vec::new(),
+            swap_volume_counts: SwapVolumeCounter::default(),
+            price_source: Arc::new(StaticPrices::default()),
+            dapp_names,
+            publishers: HashMap::new(),
+            registry_updates,
+            last_processed_checkpoint: 0,
+            sender_hash_salt: None,
+            sender_filters: SenderFilterChain::default(),
+            sender_access: SenderAccessList::default(),
+            user_attribution: self.user_attribution,
+            daily_sender_counts: HashMap::new(),
+            daily_counts_day: None,
+            stats_timezone: Tz::UTC,
+            completed_day_top_users: Vec::new(),
+            top_users_limit: 20,
+            min_dau_for_ranking: 0,
+            score_formula: ScoreFormula::default(),
+            leadership: Leadership::default(),
+            dau_smoother: None,
+            write_coalescer: WriteCoalescer::default(),
+            pending_write: false,
+            retention_tracking: false,
+            pending_daily_activity: Vec::new(),
+            runtime_settings: watch::channel(RuntimeSettings::default()).1,
+            rankings_updates: watch::channel(Arc::new(Vec::new())).0,
+            name_index,
+            last_ranking_update: None,
+            warm_up_until: None,
+            ranking_window: self.ranking_window,
+            ranking_profiles: self.ranking_profiles,
+            new_users_window: self.new_users_window,
+            interaction_dedup: self.interaction_dedup,
+            clock: self.clock,
+        }
+    }
+}
+
+/// DApp interactions extracted from one checkpoint, ready to be applied to the indexer
+#[derive(Debug, Clone)]
+pub struct ExtractedCheckpoint {
+    pub checkpoint_number: u64,
+    /// Sui epoch the checkpoint belongs to
+    pub epoch: u64,
+    pub interactions: Vec<DAppInteraction>,
+    pub function_calls: Vec<DAppFunctionCall>,
+    pub transaction_outcomes: Vec<DAppTransactionOutcome>,
+    /// Transfers decoded from events of tracked bridges
+    pub bridge_transfers: Vec<BridgeTransfer>,
+    /// Coins spent in swaps through tracked DEXes and aggregators
+    pub swap_legs: Vec<SwapLeg>,
+    /// Deposits and borrows decoded from events of tracked lending DApps
+    pub lending_flows: Vec<LendingFlow>,
+    /// Packages published by a registered publisher, to add to the registry
+    pub published_packages: Vec<DAppPublishedPackage>,
+}
+
+/**
+ * InteractionExtractor holds a snapshot of the rules used to turn checkpoints into
+ * DApp interactions (tracked packages, sender access list, anonymization)
+ * It is cheap to clone and needs no lock, so every ingestion worker can extract in parallel
+ * The package registry follows the indexer, so packages added while running (e.g. through
+ * their publisher) are picked up by checkpoints extracted after they were applied
+ */
+#[derive(Clone)]
+pub struct InteractionExtractor {
+    dapp_names: watch::Receiver<Arc<HashMap<String, DAppRegistryEntry>>>,
+    publishers: Arc<HashMap<String, DAppRegistryEntry>>,
+    sender_hash_salt: Option<String>,
+    sender_access: Arc<SenderAccessList>,
+    user_attribution: UserAttribution,
+    interaction_dedup: InteractionDedup,
+    extraction_window: Duration,
+    clock: Arc<dyn Clock>,
+}
+
+impl InteractionExtractor {
+    /// Extract all DApp interactions of a checkpoint
+    #[instrument(skip_all, fields(checkpoint = data.checkpoint_summary.sequence_number, transactions = data.transactions.len()))]
+    pub fn extract(&self, data: &CheckpointData) -> ExtractedCheckpoint {
+        let dapp_names = self.dapp_names.borrow().clone();
+        extract_checkpoint(
+            &dapp_names,
+            &self.publishers,
+            self.sender_hash_salt.as_deref(),
+            &self.sender_access,
+            self.user_attribution,
+            self.interaction_dedup,
+            self.clock.now() - self.extraction_window,
+            data,
+        )
+    }
+
+    /// True if extracting the checkpoint cannot yield anything: it is older than the
+    /// extraction window, which covers both the ranking window and the 24h statistics,
+    /// and no publishers are followed whose packages it could publish
+    pub fn can_skip(&self, data: &CheckpointData) -> bool {
+        self.publishers.is_empty()
+            && checkpoint_time(data) < self.clock.now() - self.extraction_window
+    }
+}
+
+/// Extract DApp interactions from every transaction of a checkpoint
+/// Checkpoints older than the extraction window yield no interactions since they cannot affect
+/// rankings or statistics,
+/// but packages published by a registered publisher are discovered in any checkpoint
+/// Results keep the transaction order, whether or not the checkpoint was scanned in parallel
+#[allow(clippy::too_many_arguments)]
+fn extract_checkpoint(
+    dapp_names: &HashMap<String, DAppRegistryEntry>,
+    publishers: &HashMap<String, DAppRegistryEntry>,
+    sender_hash_salt: Option<&str>,
+    sender_access: &SenderAccessList,
+    user_attribution: UserAttribution,
+    interaction_dedup: InteractionDedup,
+    window_start: DateTime<Utc>,
+    data: &CheckpointData,
+) -> ExtractedCheckpoint {
+    let checkpoint_number = data.checkpoint_summary.sequence_number;
+    let epoch = data.checkpoint_summary.epoch;
+    let checkpoint_timestamp = checkpoint_time(data);
+    let mut interactions = Vec::new();
+    let mut function_calls = Vec::new();
+    let mut transaction_outcomes = Vec::new();
+    let mut bridge_transfers = Vec::new();
+    let mut swap_legs = Vec::new();
+    let mut lending_flows = Vec::new();
+    let mut published_packages = Vec::new();
+    let mut dapp_names = Cow::Borrowed(dapp_names);
+
+    // Skip checkpoints older than the extraction window to ensure we only process recent data
+    let recent = checkpoint_timestamp >= window_start;
+    if !recent && publishers.is_empty() {
+        return ExtractedCheckpoint { checkpoint_number, epoch, interactions, function_calls, transaction_outcomes, bridge_transfers, swap_legs, lending_flows, published_packages };
+    }
+    // Attribute new packages first, so events of the publishing transaction already count
+    for transaction in &data.transactions {
+        for package in discover_published_packages(publishers, &dapp_names, transaction, checkpoint_timestamp) {
+            dapp_names.to_mut().insert(package.package_id.clone(), publishers[&package.publisher].clone());
+            published_packages.push(package);
+        }
+    }
+    if !recent {
+        return ExtractedCheckpoint { checkpoint_number, epoch, interactions, function_calls, transaction_outcomes, bridge_transfers, swap_legs, lending_flows, published_packages };
+    }
+
+    // Transactions are independent, so large checkpoints are scanned in parallel
+    let dapp_names = dapp_names.as_ref();
+    let extract_transaction = |transaction: &CheckpointTransaction| {
+        (
+            extract_dapp_interactions(
+                dapp_names,
+                sender_hash_salt,
+                sender_access,
+                user_attribution,
+                interaction_dedup,
+                transaction,
+                checkpoint_timestamp,
+            ),
+            extract_function_calls(dapp_names, sender_access, transaction, checkpoint_timestamp),
+            extract_transaction_outcomes(dapp_names, sender_access, transaction, checkpoint_timestamp),
+            extract_bridge_transfers(dapp_names, sender_access, transaction, checkpoint_timestamp),
+            extract_swap_legs(dapp_names, sender_access, transaction, checkpoint_timestamp),
+            extract_lending_flows(dapp_names, sender_access, transaction, checkpoint_timestamp),
+        )
+    };
+    let extracted: Vec<_> = if data.transactions.len() >= PARALLEL_EXTRACTION_MIN_TRANSACTIONS {
+        data.transactions.par_iter().map(extract_transaction).collect()
+    } else {
+        data.transactions.iter().map(extract_transaction).collect()
+    };
+    for (transaction_interactions, transaction_function_calls, outcomes, transfers, legs, flows) in extracted {
+        interactions.extend(transaction_interactions);
+        function_calls.extend(transaction_function_calls);
+        transaction_outcomes.extend(outcomes);
+        bridge_transfers.extend(transfers);
+        swap_legs.extend(legs);
+        lending_flows.extend(flows);
+    }
+
+    ExtractedCheckpoint { checkpoint_number, epoch, interactions, function_calls, transaction_outcomes, bridge_transfers, swap_legs, lending_flows, published_packages }
+}
+
+/// Time of a checkpoint with millisecond precision
+/// Transactions carry no time of their own, so every event of a checkpoint happened at it
+fn checkpoint_time(data: &CheckpointData) -> DateTime<Utc> {
+    DateTime::from_timestamp_millis(data.checkpoint_summary.timestamp_ms as i64).unwrap_or_default()
+}
+
+/// Packages published or upgraded by a transaction whose sender is a registered publisher
+fn discover_published_packages(
+    publishers: &HashMap<String, DAppRegistryEntry>,
+    dapp_names: &HashMap<String, DAppRegistryEntry>,
+    transaction: &CheckpointTransaction,
+    checkpoint_timestamp: DateTime<Utc>,
+) -> Vec<DAppPublishedPackage> {
+    if publishers.is_empty() {
+        return Vec::new();
+    }
+    let publisher = transaction.transaction.data().transaction_data().sender().to_string();
+    let Some(entry) = publishers.get(&publisher) else {
+        return Vec::new();
+    };
+
+    transaction
+        .output_objects
+        .iter()
+        .filter(|object| object.is_package())
+        .map(|object| object.id().to_string())
+        .filter(|package_id| !dapp_names.contains_key(package_id))
+        .map(|package_id| DAppPublishedPackage {
+            package_id,
+            dapp_name: entry.dapp_name.clone(),
+            publisher: publisher.clone(),
+            discovered_at: checkpoint_timestamp.naive_utc(),
+        })
+        .collect()
+}
+
+/// Whether a transaction succeeded, once for every tracked DApp it touches
+/// Aborted transactions emit no events, so DApps are found through MoveCall commands as well
+fn extract_transaction_outcomes(
+    dapp_names: &HashMap<String, DAppRegistryEntry>,
+    sender_access: &SenderAccessList,
+    transaction: &CheckpointTransaction,
+    checkpoint_timestamp: DateTime<Utc>,
+) -> Vec<DAppTransactionOutcome> {
+    let transaction_data = transaction.transaction.data().transaction_data();
+    if !sender_access.permits(&transaction_data.sender().to_string()) {
+        return Vec::new();
+    }
+
+    let called = transaction_data.move_calls().into_iter().map(|(package, _, _)| package.to_string());
+    let emitting = transaction
+        .events
+        .iter()
+        .flat_map(|events| events.data.iter())
+        .map(|event| event.package_id.to_string());
+    let touched: HashSet<&str> = called
+        .chain(emitting)
+        .filter_map(|package_id| dapp_names.get(&package_id).filter(|entry| entry.enabled))
+        .map(|entry| entry.dapp_name.as_str())
+        .collect();
+
+    let success = transaction.effects.status().is_ok();
+    touched
+        .into_iter()
+        .map(|dapp_name| DAppTransactionOutcome {
+            dapp_name: dapp_name.to_string(),
+            success,
+            timestamp: checkpoint_timestamp,
+        })
+        .collect()
+}
+
+/// Decode the transfers among the events of tracked bridges
+/// An outbound transfer moves the coins the sender spent, an inbound one the coins
+/// that came into address-owned balances; they are credited to the transaction's
+/// first transfer of each direction, so a transaction's coins are only counted once
+/// Transfers of senders rejected by the access list are skipped
+fn extract_bridge_transfers(
+    dapp_names: &HashMap<String, DAppRegistryEntry>,
+    sender_access: &SenderAccessList,
+    transaction: &CheckpointTransaction,
+    checkpoint_timestamp: DateTime<Utc>,
+) -> Vec<BridgeTransfer> {
+    let mut transfers: Vec<BridgeTransfer> = transaction
+        .events
+        .iter()
+        .flat_map(|events| events.data.iter())
+        .filter_map(|event| {
+            let entry = dapp_names
+                .get(&event.package_id.to_string())
+                .filter(|entry| entry.enabled && entry.dapp_type == BRIDGE_DAPP_TYPE)?;
+            if !sender_access.permits(&event.sender.to_string()) {
+                return None;
+            }
+            let direction = decode_bridge_event(event.type_.name.as_str(), &event.contents)?;
+            Some(BridgeTransfer {
+                dapp_name: entry.dapp_name.clone(),
+                direction,
+                amounts: Vec::new(),
+                timestamp: checkpoint_timestamp,
+            })
+        })
+        .collect();
+
+    if let Some(transfer) = transfers.iter_mut().find(|transfer| transfer.direction == BridgeDirection::Outbound) {
+        let sender = transaction.transaction.data().transaction_data().sender();
+        transfer.amounts = spent_amounts(coin_balance_changes(transaction, Some(sender)));
+    }
+    if let Some(transfer) = transfers.iter_mut().find(|transfer| transfer.direction == BridgeDirection::Inbound) {
+        transfer.amounts = received_amounts(coin_balance_changes(transaction, None));
+    }
+    transfers
+}
+
+/// Decode the deposits and borrows among the events of tracked lending DApps
+/// Events of senders rejected by the access list are skipped
+fn extract_lending_flows(
+    dapp_names: &HashMap<String, DAppRegistryEntry>,
+    sender_access: &SenderAccessList,
+    transaction: &CheckpointTransaction,
+    checkpoint_timestamp: DateTime<Utc>,
+) -> Vec<LendingFlow> {
+    transaction
+        .events
+        .iter()
+        .flat_map(|events| events.data.iter())
+        .filter_map(|event| {
+            let entry = dapp_names
+                .get(&event.package_id.to_string())
+                .filter(|entry| entry.enabled && entry.dapp_type == LENDING_DAPP_TYPE)?;
+            if !sender_access.permits(&event.sender.to_string()) {
+                return None;
+            }
+            let event_type = format!("{}::{}", event.type_.module, event.type_.name);
+            let (kind, coin_type, amount) = decode_lending_amount(&event_type, &event.contents)?;
+            Some(LendingFlow {
+                dapp_name: entry.dapp_name.clone(),
+                kind,
+                coin_type,
+                amount,
+                timestamp: checkpoint_timestamp,
+            })
+        })
+        .collect()
+}
+
+/// Coins the sender spent in a successful swap through tracked DEXes and aggregators,
+/// credited to the aggregator or split across the DEXes emitting swap events
+/// Swaps of senders rejected by the access list are skipped
+fn extract_swap_legs(
+    dapp_names: &HashMap<String, DAppRegistryEntry>,
+    sender_access: &SenderAccessList,
+    transaction: &CheckpointTransaction,
+    checkpoint_timestamp: DateTime<Utc>,
+) -> Vec<SwapLeg> {
+    let transaction_data = transaction.transaction.data().transaction_data();
+    let sender = transaction_data.sender();
+    if !transaction.effects.status().is_ok() || !sender_access.permits(&sender.to_string()) {
+        return Vec::new();
+    }
+
+    // DApp name -> category of the DApps emitting swap events
+    let swapping: BTreeMap<&str, &str> = transaction
+        .events
+        .iter()
+        .flat_map(|events| events.data.iter())
+        .filter_map(|event| {
+            let entry = dapp_names
+                .get(&event.package_id.to_string())
+                .filter(|entry| entry.enabled && SWAP_DAPP_TYPES.contains(&entry.dapp_type.as_str()))?;
+            let event_type = format!("{}::{}", event.type_.module, event.type_.name);
+            is_swap_event(&entry.dapp_name, &event_type).then_some(entry)
+        })
+        .map(|entry| (entry.dapp_name.as_str(), entry.dapp_type.as_str()))
+        .collect();
+    if swapping.is_empty() {
+        return Vec::new();
+    }
+
+    // Gas the sender paid is not part of the swap
+    attribute_swap(&swapping, &spent_amounts(coin_balance_changes(transaction, Some(sender))), checkpoint_timestamp)
+}
+
+/// Balance changes per coin type of the coins owned by `owner`, or by any address
+/// if None, between the transaction's inputs and outputs
+/// The gas paid is added back, so it does not count as spent
+fn coin_balance_changes(transaction: &CheckpointTransaction, owner: Option<SuiAddress>) -> Vec<(String, i128)> {
+    let mut changes: Vec<(String, i128)> = owned_coins(&transaction.input_objects, owner)
+        .map(|(coin_type, value)| (coin_type, -(value as i128)))
+        .chain(owned_coins(&transaction.output_objects, owner).map(|(coin_type, value)| (coin_type, value as i128)))
+        .collect();
+    let gas_owner = transaction.transaction.data().transaction_data().gas_owner();
+    if owner.is_none_or(|owner| owner == gas_owner) {
+        changes.push((sui_coin_type(), transaction.effects.gas_cost_summary().net_gas_usage() as i128));
+    }
+    changes
+}
+
+/// (coin type, value) of the coins among `objects` owned by `owner`, or by any address if None
+fn owned_coins(objects: &[Object], owner: Option<SuiAddress>) -> impl Iterator<Item = (String, u64)> + '_ {
+    objects
+        .iter()
+        .filter(move |object| match object.owner {
+            Owner::AddressOwner(address) => owner.is_none_or(|owner| owner == address),
+            _ => false,
+        })
+        .filter_map(|object| Some((object.coin_type_maybe()?.to_string(), object.as_coin_maybe()?.value())))
+}
+
+/// Extract the MoveCall commands of a transaction that target tracked DApps
+/// Calls from senders rejected by the access list are skipped
+fn extract_function_calls(
+    dapp_names: &HashMap<String, DAppRegistryEntry>,
+    sender_access: &SenderAccessList,
+    transaction: &CheckpointTransaction,
+    checkpoint_timestamp: DateTime<Utc>,
+) -> Vec<DAppFunctionCall> {
+    let transaction_data = transaction.transaction.data().transaction_data();
+    if !sender_access.permits(&transaction_data.sender().to_string()) {
+        return Vec::new();
+    }
+
+    transaction_data
+        .move_calls()
+        .into_iter()
+        .filter_map(|(package, module, function)| {
+            let package_id = package.to_string();
+            let entry = dapp_names.get(&package_id).filter(|entry| entry.enabled)?;
+            Some(DAppFunctionCall {
+                package_id,
+                dapp_name: entry.dapp_name.clone(),
+                function: format!("{}::{}", module, function),
+                timestamp: checkpoint_timestamp,
+            })
+        })
+        .collect()
+}
+
+/// Extract DApp interactions from a checkpoint transaction
+/// Identifies when users interact with DApps by analyzing transaction events
+/// 
+/// # Arguments
+/// * `dapp_names` - Registry of tracked packages
+/// * `sender_hash_salt` - Salt for sender anonymization, if enabled
+/// * `sender_access` - Allowlist/denylist of senders
+/// * `user_attribution` - Whether the sender, the gas sponsor or both count as users
+/// * `transaction` - The checkpoint transaction to analyze
+/// * `checkpoint_timestamp` - When the checkpoint occurred
+/// 
+/// # Returns
+/// * Vec<DAppInteraction> containing all DApp interactions found
+#[instrument(level = "debug", skip_all)]
+fn extract_dapp_interactions(
+    dapp_names: &HashMap<String, DAppRegistryEntry>,
+    sender_hash_salt: Option<&str>,
+    sender_access: &SenderAccessList,
+    user_attribution: UserAttribution,
+    interaction_dedup: InteractionDedup,
+    transaction: &CheckpointTransaction,
+    checkpoint_timestamp: DateTime<Utc>,
+) -> Vec<DAppInteraction> {
+    let mut interactions = Vec::new();
+    // (DApp, user) pairs already counted for this transaction
+    let mut counted: HashSet<(String, String)> = HashSet::new();
+    let tx_digest = transaction.transaction.digest().to_string();
+    let gas_used = transaction.effects.gas_cost_summary().gas_used();
+    // Differs from the sender when the transaction is sponsored
+    let transaction_data = transaction.transaction.data().transaction_data();
+    let gas_owner = transaction_data.gas_owner().to_string();
+    let sponsored = transaction_data.gas_owner() != transaction_data.sender();
+    
+    // Process events to extract DApp interactions and senders
+    if let Some(events) = &transaction.events {
+        for event in &events.data {
+            // Extract package_id from event
+            let package_id = event.package_id.to_string();
+            
+            // Only process events from our tracked DApps that are not paused
+            if let Some(entry) = dapp_names.get(&package_id).filter(|entry| entry.enabled) {
+                // Extract sender from event, plus the sponsor if it counts as a user
+                for sender in user_attribution.users(event.sender.to_string(), gas_owner.clone()) {
+                    if sender.is_empty() {
+                        continue;
+                    }
+                    
+                    // Skip known bots, bridges and test wallets from the access list
+                    if !sender_access.permits(&sender) {
+                        continue;
+                    }
+                    
+                    // Replace the raw address with its salted hash if anonymization is enabled
+                    let sender = match sender_hash_salt {
+                        Some(salt) => anonymize_sender(salt, &sender),
+                        None => sender,
+                    };
+                    
+                    if interaction_dedup == InteractionDedup::PerTransaction
+                        && !counted.insert((entry.dapp_name.clone(), sender.clone()))
+                    {
+                        continue;
+                    }
+                    
+                    // Create DApp interaction
+                    interactions.push(DAppInteraction {
+                        package_id: package_id.clone(),
+                        sender,
+                        timestamp: checkpoint_timestamp,
+                        transaction_digest: tx_digest.clone(),
+                        dapp_name: Some(entry.dapp_name.clone()),
+                        gas_used,
+                        event_type: format!("{}::{}", event.type_.module, event.type_.name),
+                        sponsored,
+                    });
+                }
+            }
+            // Skip all other package_ids that are not in our tracked list
+        }
+    }
+
+    interactions
+}
+
+/// Ordering of DApps in the rankings, so equal scores produce the same order on every run:
+/// 1. Score, descending
+/// 2. HAU, descending
+/// 3. Transaction count, descending
+/// 4. DApp name, ascending
+pub fn compare_rankings(a: &DAppRanking, b: &DAppRanking) -> Ordering {
+    b.score
+        .total_cmp(&a.score)
+        .then_with(|| b.active_users.cmp(&a.active_users))
+        .then_with(|| b.tx_count.cmp(&a.tx_count))
+        .then_with(|| a.dapp_name.cmp(&b.dapp_name))
+}
+
+/// Count active users, transactions and gas per enabled DApp over the
+/// interactions since `window_start` of the senders the filters accept, before scoring and ranking
+/// DEXes and aggregators with swap volume, and lending DApps with deposit or borrow
+/// volume, get a row even without interactions in the window
+#[allow(clippy::too_many_arguments)]
+fn count_rankings(
+    interactions: &[DAppInteraction],
+    registry: &HashMap<String, DAppRegistryEntry>,
+    sender_filters: &SenderFilterChain,
+    window_start: DateTime<Utc>,
+    now: DateTime<Utc>,
+    error_rates: &HashMap<String, f64>,
+    swap_volumes: &HashMap<String, f64>,
+    lending_stats: &[DAppLendingStats],
+) -> Vec<DAppRanking> {
+    // Group interactions by DApp NAME (not package_id) and sender within the window
+    // This ensures DApps with multiple package IDs are counted as one unified DApp
+    let mut dapp_sender_interactions: HashMap<String, HashMap<String, Vec<&DAppInteraction>>> = HashMap::new();
+
+    // Swap volume covers 24h, so a DEX can have volume but no users in a shorter window
+    for entry in registry.values().filter(|entry| entry.enabled && SWAP_DAPP_TYPES.contains(&entry.dapp_type.as_str())) {
+        if swap_volumes.get(&entry.dapp_name).is_some_and(|volume| *volume > 0.0) {
+            dapp_sender_interactions.entry(entry.dapp_name.clone()).or_default();
+        }
+    }
+    // Deposit and borrow volumes are USD over 24h like swap volume
+    let lending_volumes: HashMap<&str, (f64, f64)> = lending_stats
+        .iter()
+        .map(|stats| (stats.dapp_name.as_str(), (stats.deposit_volume_24h_usd, stats.borrow_volume_24h_usd)))
+        .collect();
+    for entry in registry.values().filter(|entry| entry.enabled && entry.dapp_type == LENDING_DAPP_TYPE) {
+        if lending_volumes.get(entry.dapp_name.as_str()).is_some_and(|(deposits, borrows)| *deposits > 0.0 || *borrows > 0.0) {
+            dapp_sender_interactions.entry(entry.dapp_name.clone()).or_default();
+        }
+    }
+
+    // Process all DApp interactions from the window
+    for interaction in interactions {
+        if interaction.timestamp >= window_start {
+            // Only count interactions for DApps that are in our tracked mapping
+            if let Some(entry) = registry.get(&interaction.package_id).filter(|entry| entry.enabled) {
+                // Group by DApp NAME, not package_id
+                // This fixes the issue where DApps with multiple package IDs 
+                // would have inflated HAU counts
+                dapp_sender_interactions
+                    .entry(entry.dapp_name.clone()) // Use dapp_name as key instead of package_id
+                    .or_default()
+                    .entry(interaction.sender.clone())
+                    .or_default()
+                    .push(interaction);
+            }
+        }
+    }
+
+    // Convert to rankings - group by DApp name
+    dapp_sender_interactions
+        .into_iter()
+        .map(|(dapp_name, senders)| {
+            // Run every sender through the bot/sybil filter chain
+            let accepted: Vec<&Vec<&DAppInteraction>> = senders
+                .iter()
+                .filter(|(sender, interactions)| sender_filters.accepts(sender, interactions))
+                .map(|(_, interactions)| interactions)
+                .collect();
+            let active_users = accepted.len();
+            let filtered_users = senders.len() - active_users;
+            // Users with at least one transaction whose gas was paid by a sponsor
+            let sponsored_users = accepted
+                .iter()
+                .filter(|interactions| interactions.iter().any(|interaction| interaction.sponsored))
+                .count();
+            let mut interactions_per_user: Vec<usize> =
+                accepted.iter().map(|interactions| interactions.len()).collect();
+            interactions_per_user.sort_unstable();
+
+            // Transactions and gas of counted senders; a transaction emitting
+            // several events is only counted once
+            let mut transaction_gas: HashMap<&str, u64> = HashMap::new();
+            for interaction in accepted.iter().flat_map(|interactions| interactions.iter()) {
+                transaction_gas.insert(interaction.transaction_digest.as_str(), interaction.gas_used);
+            }
+
+            // Find the first package_id for this dapp_name (for reference)
+            let package_id = registry
+                .iter()
+                .find(|(_, entry)| entry.dapp_name == dapp_name)
+                .map(|(id, _)| id.clone())
+                .unwrap_or_else(|| "unknown".to_string());
+            
+            // Get dapp_type for this dapp_name
+            let dapp_type = registry
+                .iter()
+                .find(|(_, entry)| entry.dapp_name == dapp_name)
+                .map(|(_, entry)| entry.dapp_type.clone())
+                .unwrap_or_else(|| "Unknown".to_string());
+
+            let error_rate_24h = error_rates.get(&dapp_name).copied().unwrap_or(0.0);
+            // Only DEXes and aggregators have a swap volume
+            let volume_24h_usd = if SWAP_DAPP_TYPES.contains(&dapp_type.as_str()) {
+                swap_volumes.get(&dapp_name).copied().unwrap_or(0.0)
+            } else {
+                0.0
+            };
+            // Only lending DApps have deposit and borrow volumes
+            let (deposit_volume_24h_usd, borrow_volume_24h_usd) = if dapp_type == LENDING_DAPP_TYPE {
+                lending_volumes.get(dapp_name.as_str()).copied().unwrap_or_default()
+            } else {
+                (0.0, 0.0)
+            };
+
+            DAppRanking {
+                rank: 0, // Will be set after sorting
+                package_id, // Use first package_id as reference
+                dapp_name,
+                active_users: active_users as u32, // Active users over the window
+                filtered_users: filtered_users as u32, // Senders rejected by the filter chain
+                last_update: now,
+                dapp_type,
+                tracking_status: TRACKING_ACTIVE.to_string(),
+                new_users_24h: 0, // Filled in from first-seen data when persisting
+                returning_users_24h: 0,
+                tx_count: transaction_gas.len() as u32,
+                gas_used: transaction_gas.values().sum(),
+                score: 0.0, // Set below once all DApps are known
+                error_rate_24h,
+                dau_smoothed: active_users as f64, // Replaced by the moving average below if enabled
+                sponsored_user_pct: percentage(sponsored_users, active_users),
+                avg_gas_per_tx: average_gas(transaction_gas.values().sum(), transaction_gas.len()),
+                interactions_p50: percentile(&interactions_per_user, 50),
+                interactions_p90: percentile(&interactions_per_user, 90),
+                interactions_p99: percentile(&interactions_per_user, 99),
+                top_users_interaction_pct: top_users_share(&interactions_per_user, TOP_USERS_PERCENT),
+                volume_24h_usd,
+                deposit_volume_24h_usd,
+                borrow_volume_24h_usd,
+            }
+        })
+        .collect()
+}
+
+/// Score the rankings with `score_formula` and sort them, best first
+fn score_rankings(rankings: &mut [DAppRanking], score_formula: &ScoreFormula) {
+    let metrics: Vec<RankingMetrics> = rankings
+        .iter()
+        .map(|ranking| RankingMetrics {
+            dau: ranking.active_users as u64,
+            tx_count: ranking.tx_count as u64,
+            gas: ranking.gas_used,
+            dau_smoothed: ranking.dau_smoothed,
+            volume_usd: ranking.volume_24h_usd,
+        })
+        .collect();
+    for (ranking, score) in rankings.iter_mut().zip(score_formula.score_all(&metrics)) {
+        ranking.score = score;
+    }
+    rankings.sort_by(compare_rankings);
+}
+
+/// Rankings of the enabled DApps of `registry` over the interactions of the
+/// `window` ending at `now`, best first
+/// Unlike the live rankings nothing is filtered, smoothed, paused or collapsed:
+/// every sender counts, every DApp with interactions is ranked and scores use
+/// the default formula, so the result only depends on the arguments
+/// Swap and lending volumes are not decoded from interactions and stay 0
+pub fn compute_rankings(
+    interactions: &[DAppInteraction],
+    registry: &HashMap<String, DAppRegistryEntry>,
+    window: Duration,
+    now: DateTime<Utc>,
+) -> Vec<DAppRanking> {
+    let mut rankings =
+        count_rankings(interactions, registry, &SenderFilterChain::default(), now - window, now, &HashMap::new(), &HashMap::new(), &[]);
+    score_rankings(&mut rankings, &ScoreFormula::default());
+    for (index, ranking) in rankings.iter_mut().enumerate() {
+        ranking.rank = (index + 1) as u32;
+    }
+    rankings
+}
+
+/// Collapse the rankings with fewer than `min_dau` HAU into one "Others" row placed after the rest
+/// Its HAU and transactions are the sums over the collapsed DApps, so a user of
+/// several of them is counted for each; it has no score of its own
+pub fn collapse_below_min_dau(rankings: Vec<DAppRanking>, min_dau: u32, now: DateTime<Utc>) -> Vec<DAppRanking> {
+    let (mut ranked, collapsed): (Vec<DAppRanking>, Vec<DAppRanking>) =
+        rankings.into_iter().partition(|ranking| ranking.active_users >= min_dau);
+    if collapsed.is_empty() {
+        return ranked;
+    }
+
+    let tx_count: u32 = collapsed.iter().map(|ranking| ranking.tx_count).sum();
+    let failed_weight: f64 = collapsed.iter().map(|ranking| ranking.error_rate_24h * ranking.tx_count as f64).sum();
+    let active_users: u32 = collapsed.iter().map(|ranking| ranking.active_users).sum();
+    let sponsored_weight: f64 = collapsed.iter().map(|ranking| ranking.sponsored_user_pct * ranking.active_users as f64).sum();
+    let gas_used: u64 = collapsed.iter().map(|ranking| ranking.gas_used).sum();
+    ranked.push(DAppRanking {
+        rank: 0,
+        package_id: OTHERS_PACKAGE_ID.to_string(),
+        dapp_name: OTHERS_DAPP_NAME.to_string(),
+        active_users,
+        filtered_users: collapsed.iter().map(|ranking| ranking.filtered_users).sum(),
+        last_update: now,
+        dapp_type: OTHERS_DAPP_NAME.to_string(),
+        tracking_status: TRACKING_ACTIVE.to_string(),
+        new_users_24h: 0, // Filled in from first-seen data when persisting
+        returning_users_24h: 0,
+        tx_count,
+        gas_used,
+        score: 0.0,
+        // Error rates weighted by transaction count
+        error_rate_24h: if tx_count > 0 { failed_weight / tx_count as f64 } else { 0.0 },
+        dau_smoothed: collapsed.iter().map(|ranking| ranking.dau_smoothed).sum(),
+        // Sponsored shares weighted by HAU
+        sponsored_user_pct: if active_users > 0 { sponsored_weight / active_users as f64 } else { 0.0 },
+        avg_gas_per_tx: average_gas(gas_used, tx_count as usize),
+        // Percentiles cannot be merged; approximated by weighting each DApp's by its HAU
+        interactions_p50: weighted_by_hau(&collapsed, |ranking| ranking.interactions_p50),
+        interactions_p90: weighted_by_hau(&collapsed, |ranking| ranking.interactions_p90),
+        interactions_p99: weighted_by_hau(&collapsed, |ranking| ranking.interactions_p99),
+        top_users_interaction_pct: if active_users > 0 {
+            collapsed.iter().map(|ranking| ranking.top_users_interaction_pct * ranking.active_users as f64).sum::<f64>() / active_users as f64
+        } else {
+            0.0
+        },
+        volume_24h_usd: collapsed.iter().map(|ranking| ranking.volume_24h_usd).sum(),
+        deposit_volume_24h_usd: collapsed.iter().map(|ranking| ranking.deposit_volume_24h_usd).sum(),
+        borrow_volume_24h_usd: collapsed.iter().map(|ranking| ranking.borrow_volume_24h_usd).sum(),
+    });
+    ranked
+}
+
+/// `part` as a percentage of `total`, 0 when there is nothing to divide
+fn percentage(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / total as f64
+    }
+}
+
+/// HAU-weighted average of a per-DApp figure, rounded
+fn weighted_by_hau(rankings: &[DAppRanking], value: impl Fn(&DAppRanking) -> u32) -> u32 {
+    let dau: u32 = rankings.iter().map(|ranking| ranking.active_users).sum();
+    if dau == 0 {
+        return 0;
+    }
+    let weighted: f64 = rankings.iter().map(|ranking| value(ranking) as f64 * ranking.active_users as f64).sum();
+    (weighted / dau as f64).round() as u32
+}
+
+/// Nearest-rank percentile of ascending `sorted` values, 0 when empty
+/// Same as PostgreSQL's `percentile_disc`
+pub fn percentile(sorted: &[usize], percent: usize) -> u32 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (sorted.len() * percent).div_ceil(100).max(1);
+    sorted[rank - 1] as u32
+}
+
+/// Share (%) of all interactions made by the most active `percent`% of users
+/// (at least one user), given the ascending interactions per user
+pub fn top_users_share(sorted: &[usize], percent: usize) -> f64 {
+    let total: usize = sorted.iter().sum();
+    if total == 0 {
+        return 0.0;
+    }
+    let top_users = (sorted.len() * percent).div_ceil(100).max(1);
+    let top: usize = sorted.iter().rev().take(top_users).sum();
+    top as f64 * 100.0 / total as f64
+}
+
+/// Average gas per transaction, 0 without transactions
+pub fn average_gas(gas_used: u64, transactions: usize) -> f64 {
+    if transactions == 0 {
+        0.0
+    } else {
+        gas_used as f64 / transactions as f64
+    }
+}
+
+/// Hash a sender address with the given salt
+/// Returns a hex-encoded SHA-256 digest prefixed with `0x`, so it has the same shape as an address
+pub fn anonymize_sender(salt: &str, sender: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(sender.as_bytes());
+    let digest = hasher.finalize();
+    
+    let hex: String = digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("0x{}", hex)
+}
+
+/// Log the rankings when they are not written to the database
+fn log_dry_run_rankings(rankings: &[DAppRanking]) {
+    info!(dapps = rankings.len(), "🧪 {} DApp rankings computed, database writes disabled", rankings.len());
+    for ranking in rankings.iter().take(10) {
+        info!(rank = ranking.rank, dapp = %ranking.dapp_name, hau = ranking.active_users,
+              filtered = ranking.filtered_users, tx_count = ranking.tx_count, score = ranking.score,
+              "  {}. {} - {} HAU ({} filtered, {} tx, score {:.3})",
+              ranking.rank, ranking.dapp_name, ranking.active_users,
+              ranking.filtered_users, ranking.tx_count, ranking.score);
+    }
+}
+
 /// Start a background job to update rankings periodically
-pub async fn start_ranking_update_job(indexer: Arc<Mutex<DAppIndexer>>, db_manager: Arc<DatabaseManager>) {
+/// The interval follows `ranking_interval` from the runtime settings and changes take effect immediately
+/// Without a database (dry-run mode) rankings are logged and optionally exported to `export_path` instead
+pub async fn start_ranking_update_job(
+    indexer: Arc<Mutex<DAppIndexer>>,
+    db_manager: Option<Arc<DatabaseManager>>,
+    export_path: Option<PathBuf>,
+    mut runtime_settings: watch::Receiver<RuntimeSettings>,
+) {
     tokio::spawn(async move {
-        let mut interval = tokio::time::interval(Duration::from_secs(60)); // Changed from 120 to 60 seconds (1 minute)
+        let mut interval = tokio::time::interval(runtime_settings.borrow().ranking_interval);
+        let mut watching = true;
         
         loop {
-            interval.tick().await;
+            tokio::select! {
+                _ = interval.tick() => {}
+                changed = runtime_settings.changed(), if watching => {
+                    match changed {
+                        Ok(()) => {
+                            let ranking_interval = runtime_settings.borrow_and_update().ranking_interval;
+                            if ranking_interval != interval.period() {
+                                info!("⏱️ Ranking update interval changed to {}s", ranking_interval.as_secs());
+                                interval = tokio::time::interval(ranking_interval);
+                                interval.tick().await; // First tick completes immediately
+                            }
+                        }
+                        // The settings owner is gone; keep using the current interval
+                        Err(_) => watching = false,
+                    }
+                    continue;
+                }
+            }
             
             // Update rankings and prune old data
             let mut indexer_guard = indexer.lock().await;
+            if indexer_guard.is_warming_up() {
+                continue;
+            }
             
             // Always prune old interactions first
             indexer_guard.prune_old_interactions();
             
-            // Update rankings based on current 1h data
-            indexer_guard.update_dapp_rankings_1h();
+            // Update rankings based on the current window
+            indexer_guard.update_dapp_rankings();
             
-            // Save to database
-            if let Err(err) = indexer_guard.update_data_in_database(&db_manager).await {
-                error!("Failed to update rankings in database: {}", err);
+            // Save to database unless nothing changed since the last write
+            if let Some(db_manager) = &db_manager {
+                match indexer_guard.persist_coalesced(db_manager).await {
+                    Ok(true) => info!("✅ Background job: Updated DApp rankings in database"),
+                    Ok(false) => {}
+                    Err(err) => error!("Failed to update rankings in database: {}", err),
+                }
             } else {
-                info!("✅ Background job: Updated DApp rankings in database");
+                log_dry_run_rankings(indexer_guard.get_dapp_rankings());
+            }
+            
+            if let Some(path) = &export_path {
+                if let Err(err) = indexer_guard.export_rankings(path, ExportFormat::from_path(path)).await {
+                    error!("❌ Failed to export rankings to {}: {}", path.display(), err);
+                }
             }
         }
     });
@@ -398,6 +2536,7 @@ pub async fn start_ranking_update_job(indexer: Arc<Mutex<DAppIndexer>>, db_manag
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::InteractionBuilder;
 
     #[test]
     fn test_dapp_indexer_creation() {
@@ -406,4 +2545,355 @@ mod tests {
         assert_eq!(indexer.dapp_rankings.len(), 0);
         assert!(indexer.dapp_names.len() > 0);
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_anonymize_sender_is_deterministic() {
+        let sender = "0x2cdcc3b1306a49fcd5b8ccded57116ad86ab37a93ba9d91fa1ce06a8d22a21e9";
+        let hashed = anonymize_sender("salt", sender);
+        assert_eq!(hashed, anonymize_sender("salt", sender));
+        assert_ne!(hashed, anonymize_sender("other-salt", sender));
+        assert_ne!(hashed, sender);
+    }
+
+    fn ranking(dapp_name: &str, score: f64, active_users: u32, tx_count: u32) -> DAppRanking {
+        DAppRanking {
+            rank: 0,
+            package_id: "0x1".to_string(),
+            dapp_name: dapp_name.to_string(),
+            active_users,
+            filtered_users: 0,
+            last_update: DateTime::UNIX_EPOCH,
+            dapp_type: "DeFi".to_string(),
+            tracking_status: TRACKING_ACTIVE.to_string(),
+            new_users_24h: 0,
+            returning_users_24h: 0,
+            tx_count,
+            gas_used: 0,
+            score,
+            error_rate_24h: 0.0,
+            dau_smoothed: active_users as f64,
+            sponsored_user_pct: 0.0,
+            avg_gas_per_tx: 0.0,
+            interactions_p50: 1,
+            interactions_p90: 1,
+            interactions_p99: 1,
+            top_users_interaction_pct: 0.0,
+            volume_24h_usd: 0.0,
+            deposit_volume_24h_usd: 0.0,
+            borrow_volume_24h_usd: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_interaction_percentiles() {
+        // 98 users with one interaction, one with 10 and one with 500
+        let mut counts = vec![1; 98];
+        counts.extend([10, 500]);
+        assert_eq!((percentile(&counts, 50), percentile(&counts, 90), percentile(&counts, 99)), (1, 1, 10));
+        assert_eq!(percentile(&[3, 7], 50), 3);
+        assert_eq!(percentile(&[4], 99), 4);
+        assert_eq!(percentile(&[], 50), 0);
+
+        // The single top user of 100 made 500 of the 608 interactions
+        assert_eq!(format!("{:.1}", top_users_share(&counts, TOP_USERS_PERCENT)), "82.2");
+        assert_eq!(top_users_share(&[2, 2], TOP_USERS_PERCENT), 50.0);
+        assert_eq!(top_users_share(&[], TOP_USERS_PERCENT), 0.0);
+    }
+
+    #[test]
+    fn test_compare_rankings_breaks_ties_deterministically() {
+        let mut rankings = [
+            ranking("Zeta", 1.0, 10, 5),
+            ranking("Alpha", 1.0, 10, 5),
+            ranking("Busy", 1.0, 10, 9),
+            ranking("Popular", 1.0, 12, 1),
+            ranking("Leader", 2.0, 1, 1),
+        ];
+        rankings.sort_by(compare_rankings);
+
+        let names: Vec<String> = rankings.iter().map(|r| r.dapp_name.clone()).collect();
+        assert_eq!(names, ["Leader", "Popular", "Busy", "Alpha", "Zeta"]);
+
+        // The order does not depend on the input order
+        rankings.reverse();
+        rankings.sort_by(compare_rankings);
+        let reversed: Vec<String> = rankings.iter().map(|r| r.dapp_name.clone()).collect();
+        assert_eq!(reversed, names);
+    }
+
+    #[test]
+    fn test_dapps_below_min_dau_are_collapsed_into_others() {
+        let mut rankings = vec![ranking("Leader", 1.0, 50, 80), ranking("Small", 0.1, 4, 6), ranking("Tiny", 0.0, 1, 2)];
+        rankings[1].sponsored_user_pct = 50.0;
+        rankings[2].sponsored_user_pct = 100.0;
+        rankings[1].gas_used = 600;
+        rankings[2].gas_used = 200;
+        let collapsed = collapse_below_min_dau(rankings.clone(), 5, DateTime::UNIX_EPOCH);
+
+        let rows: Vec<(&str, u32, u32)> = collapsed
+            .iter()
+            .map(|ranking| (ranking.dapp_name.as_str(), ranking.active_users, ranking.tx_count))
+            .collect();
+        assert_eq!(rows, [("Leader", 50, 80), (OTHERS_DAPP_NAME, 5, 8)]);
+        assert_eq!(collapsed[1].sponsored_user_pct, 60.0);
+        assert_eq!(collapsed[1].avg_gas_per_tx, 100.0);
+        assert_eq!(collapsed[1].interactions_p99, 1);
+        assert_eq!(collapse_below_min_dau(rankings, 0, DateTime::UNIX_EPOCH).len(), 3);
+    }
+
+    #[test]
+    fn test_compute_rankings_over_a_window_of_interactions() {
+        let now = DateTime::UNIX_EPOCH + Duration::from_secs(100 * 3600);
+        let registry = HashMap::from([
+            ("0xa1".to_string(), DAppRegistryEntry::new("Cetus AMM", "DEX")),
+            ("0xa2".to_string(), DAppRegistryEntry::new("Cetus AMM", "DEX")),
+            ("0xb".to_string(), DAppRegistryEntry::new("Pyth", "Oracle")),
+        ]);
+        let interaction = |package_id: &str, sender: &str, minutes_ago: u64| {
+            InteractionBuilder::new()
+                .package(package_id)
+                .sender(sender)
+                .digest(&format!("{}-{}-{}", package_id, sender, minutes_ago))
+                .gas_used(10)
+                .at(now - Duration::from_secs(minutes_ago * 60))
+                .build()
+        };
+        let interactions = [
+            // Both packages of Cetus count toward one DApp
+            interaction("0xa1", "0x1", 5),
+            interaction("0xa2", "0x1", 10),
+            interaction("0xa2", "0x2", 20),
+            interaction("0xb", "0x3", 30),
+            // Outside the window or untracked
+            interaction("0xb", "0x4", 90),
+            interaction("0xc", "0x5", 5),
+        ];
+
+        let rankings = compute_rankings(&interactions, &registry, Duration::from_secs(60 * 60), now);
+        let rows: Vec<(u32, &str, u32, u32)> = rankings
+            .iter()
+            .map(|ranking| (ranking.rank, ranking.dapp_name.as_str(), ranking.active_users, ranking.tx_count))
+            .collect();
+        assert_eq!(rows, [(1, "Cetus AMM", 2, 3), (2, "Pyth", 1, 1)]);
+        assert!(rankings.iter().all(|ranking| ranking.last_update == now));
+        assert!(compute_rankings(&interactions, &registry, Duration::from_secs(60), now).is_empty());
+    }
+
+    #[test]
+    fn test_dexes_with_swap_volume_are_ranked_without_interactions_in_the_window() {
+        let now = DateTime::UNIX_EPOCH + Duration::from_secs(100 * 3600);
+        let registry = HashMap::from([
+            ("0xa".to_string(), DAppRegistryEntry::new("Cetus AMM", "DEX")),
+            ("0xb".to_string(), DAppRegistryEntry::new("7K Aggregator", "Aggregator")),
+            ("0xc".to_string(), DAppRegistryEntry::new("Turbos", "DEX")),
+            ("0xd".to_string(), DAppRegistryEntry::new("Pyth", "Oracle")),
+        ]);
+        let interactions = [InteractionBuilder::new().package("0xa").at(now).build()];
+        // Turbos swapped nothing; Pyth's entry is not a swap DApp
+        let swap_volumes = HashMap::from([
+            ("Cetus AMM".to_string(), 10.0),
+            ("7K Aggregator".to_string(), 250.0),
+            ("Turbos".to_string(), 0.0),
+            ("Pyth".to_string(), 99.0),
+        ]);
+
+        let mut rankings = count_rankings(
+            &interactions,
+            &registry,
+            &SenderFilterChain::default(),
+            now - Duration::from_secs(3600),
+            now,
+            &HashMap::new(),
+            &swap_volumes,
+            &[],
+        );
+        rankings.sort_by(|a, b| a.dapp_name.cmp(&b.dapp_name));
+        let rows: Vec<(&str, u32, f64)> = rankings
+            .iter()
+            .map(|ranking| (ranking.dapp_name.as_str(), ranking.active_users, ranking.volume_24h_usd))
+            .collect();
+        assert_eq!(rows, [("7K Aggregator", 0, 250.0), ("Cetus AMM", 1, 10.0)]);
+        assert_eq!(rankings[0].dapp_type, "Aggregator");
+        assert_eq!(rankings[0].package_id, "0xb");
+    }
+
+    #[test]
+    fn test_lending_dapps_get_their_deposit_and_borrow_volumes() {
+        let now = DateTime::UNIX_EPOCH + Duration::from_secs(100 * 3600);
+        let registry = HashMap::from([
+            ("0xa".to_string(), DAppRegistryEntry::new("Suilend", "Lending")),
+            ("0xb".to_string(), DAppRegistryEntry::new("NAVI", "Lending")),
+            ("0xc".to_string(), DAppRegistryEntry::new("Cetus AMM", "DEX")),
+        ]);
+        let interactions = [
+            InteractionBuilder::new().package("0xa").at(now).build(),
+            InteractionBuilder::new().package("0xc").at(now).build(),
+        ];
+        let lending = |dapp_name: &str, deposit_volume_24h_usd: f64, borrow_volume_24h_usd: f64| DAppLendingStats {
+            dapp_name: dapp_name.to_string(),
+            deposits_24h: 1,
+            withdrawals_24h: 0,
+            borrows_24h: 1,
+            repays_24h: 0,
+            unique_depositors_24h: 1,
+            unique_borrowers_24h: 1,
+            deposit_volume_24h_usd,
+            borrow_volume_24h_usd,
+            last_update: now,
+        };
+        // NAVI had no users in the window; Cetus AMM is not a lending DApp
+        let lending_stats = [lending("Suilend", 1_000.0, 400.0), lending("NAVI", 0.0, 75.0), lending("Cetus AMM", 5.0, 5.0)];
+
+        let mut rankings = count_rankings(
+            &interactions,
+            &registry,
+            &SenderFilterChain::default(),
+            now - Duration::from_secs(3600),
+            now,
+            &HashMap::new(),
+            &HashMap::new(),
+            &lending_stats,
+        );
+        rankings.sort_by(|a, b| a.dapp_name.cmp(&b.dapp_name));
+        let rows: Vec<(&str, u32, f64, f64)> = rankings
+            .iter()
+            .map(|ranking| (ranking.dapp_name.as_str(), ranking.active_users, ranking.deposit_volume_24h_usd, ranking.borrow_volume_24h_usd))
+            .collect();
+        assert_eq!(rows, [("Cetus AMM", 1, 0.0, 0.0), ("NAVI", 0, 0.0, 75.0), ("Suilend", 1, 1_000.0, 400.0)]);
+    }
+
+    #[test]
+    fn test_categories_sum_the_swap_volume_of_their_dapps() {
+        let now = DateTime::UNIX_EPOCH + Duration::from_secs(100 * 3600);
+        let mut indexer = DAppIndexer::builder()
+            .registry(HashMap::from([
+                ("0xa".to_string(), DAppRegistryEntry::new("Cetus AMM", "DEX")),
+                ("0xb".to_string(), DAppRegistryEntry::new("Cetus AMM", "DEX")),
+                ("0xc".to_string(), DAppRegistryEntry::new("Turbos", "DEX")),
+                ("0xd".to_string(), DAppRegistryEntry::new("7K Aggregator", "Aggregator")),
+                ("0xe".to_string(), DAppRegistryEntry::new("Suilend", "Lending")),
+            ]))
+            .build();
+        indexer.dapp_interactions = vec![
+            InteractionBuilder::new().package("0xa").at(now).build(),
+            InteractionBuilder::new().package("0xe").at(now).build(),
+        ];
+        // Cetus AMM is counted once despite two packages; 7K had no users in the window
+        let swap_volumes = HashMap::from([
+            ("Cetus AMM".to_string(), 1_000.0),
+            ("Turbos".to_string(), 250.0),
+            ("7K Aggregator".to_string(), 80.0),
+            ("Suilend".to_string(), 5.0),
+        ]);
+
+        let mut stats = indexer.compute_category_stats(now - Duration::from_secs(3600), now, &swap_volumes);
+        stats.sort_by(|a, b| a.dapp_type.cmp(&b.dapp_type));
+        let rows: Vec<(&str, u32, u32, f64)> = stats
+            .iter()
+            .map(|stats| (stats.dapp_type.as_str(), stats.active_users, stats.dapp_count, stats.volume_24h_usd))
+            .collect();
+        assert_eq!(rows, [("Aggregator", 0, 1, 80.0), ("DEX", 1, 2, 1_250.0), ("Lending", 1, 1, 0.0)]);
+    }
+
+    mod invariants {
+        use super::*;
+        use proptest::prelude::*;
+
+        /// Two packages of Cetus AMM, two single-package DApps and an untracked package
+        const PACKAGES: [&str; 5] = [
+            "0x6f5e582ede61fe5395b50c4a449ec11479a54d7ff8e0158247adfda60d98970b",
+            "0x3864c7c59a4889fec05d1aae4bc9dba5a0e0940594b424fbed44cb3f6ac4c032",
+            "0x04e20ddf36af412a4096f9014f4a565af9e812db9a05cc40254846cf6ed0ad91",
+            "0x9c12f3aa14a449a0a23c066589e269086f021a98939f21158cfacb16d19787c3",
+            "0x0000000000000000000000000000000000000000000000000000000000000abc",
+        ];
+
+        /// Ages stay a few minutes away from the 1h boundary so the test runtime cannot flip them
+        fn interaction_strategy() -> impl Strategy<Value = (usize, u8, u64)> {
+            let age_secs = prop_oneof![0u64..3300, 3900u64..7200];
+            (0..PACKAGES.len(), 0u8..16, age_secs)
+        }
+
+        fn indexer_with(interactions: &[(usize, u8, u64)]) -> DAppIndexer {
+            let now = Utc::now();
+            let mut indexer = DAppIndexer::new();
+            indexer.dapp_interactions = interactions
+                .iter()
+                .enumerate()
+                .map(|(index, &(package, sender, age_secs))| {
+                    InteractionBuilder::new()
+                        .package(PACKAGES[package])
+                        .sender(&format!("0x{:02x}", sender))
+                        .digest(&format!("tx{}", index))
+                        .gas_used(1)
+                        .at(now - Duration::from_secs(age_secs))
+                        .build()
+                })
+                .collect();
+            indexer
+        }
+
+        /// Distinct in-window senders per DApp name
+        fn expected_senders(indexer: &DAppIndexer, interactions: &[(usize, u8, u64)]) -> HashMap<String, HashSet<u8>> {
+            let mut senders: HashMap<String, HashSet<u8>> = HashMap::new();
+            for &(package, sender, age_secs) in interactions {
+                if let Some(entry) = indexer.dapp_names.get(PACKAGES[package]) {
+                    if age_secs < 3600 {
+                        senders.entry(entry.dapp_name.clone()).or_default().insert(sender);
+                    }
+                }
+            }
+            senders
+        }
+
+        proptest! {
+            #[test]
+            fn pruning_keeps_in_window_interactions(interactions in prop::collection::vec(interaction_strategy(), 0..200)) {
+                let mut indexer = indexer_with(&interactions);
+                let in_window: Vec<String> = indexer
+                    .dapp_interactions
+                    .iter()
+                    .zip(&interactions)
+                    .filter(|(interaction, &(_, _, age_secs))| {
+                        age_secs < 3600 && indexer.dapp_names.contains_key(&interaction.package_id)
+                    })
+                    .map(|(interaction, _)| interaction.transaction_digest.clone())
+                    .collect();
+
+                indexer.prune_old_interactions();
+
+                let kept: Vec<String> = indexer
+                    .dapp_interactions
+                    .iter()
+                    .map(|interaction| interaction.transaction_digest.clone())
+                    .collect();
+                prop_assert_eq!(kept, in_window);
+            }
+
+            #[test]
+            fn hau_counts_distinct_senders_per_dapp_name(interactions in prop::collection::vec(interaction_strategy(), 0..200)) {
+                let mut indexer = indexer_with(&interactions);
+                let expected = expected_senders(&indexer, &interactions);
+
+                indexer.update_dapp_rankings();
+
+                prop_assert_eq!(indexer.dapp_rankings.len(), expected.len());
+                for ranking in &indexer.dapp_rankings {
+                    // A sender of both Cetus AMM packages is counted once
+                    prop_assert_eq!(ranking.active_users as usize, expected[&ranking.dapp_name].len());
+                }
+            }
+
+            #[test]
+            fn rank_positions_are_a_permutation(interactions in prop::collection::vec(interaction_strategy(), 0..200)) {
+                let mut indexer = indexer_with(&interactions);
+                indexer.update_dapp_rankings();
+
+                let mut ranks: Vec<u32> = indexer.dapp_rankings.iter().map(|ranking| ranking.rank).collect();
+                ranks.sort_unstable();
+                let expected: Vec<u32> = (1..=indexer.dapp_rankings.len() as u32).collect();
+                prop_assert_eq!(ranks, expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! DApp name search for autocomplete.
+//!
+//! Tracked DApps are matched in memory against an index built from the DApp
+//! registry, so suggestions are instant and include DApps that are not ranked
+//! yet. Ranked DApps are also searched in Postgres with ILIKE and trigram
+//! similarity (`pg_trgm`), which tolerates typos.
+
+use diesel::sql_types::{Double, Text};
+use diesel::QueryableByName;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// A DApp matching a search query
+#[derive(QueryableByName, Debug, Clone, PartialEq, Serialize)]
+pub struct DAppSearchResult {
+    #[diesel(sql_type = Text)]
+    pub dapp_name: String,
+    #[diesel(sql_type = Text)]
+    pub dapp_type: String,
+    /// Match quality between 0 and 1, best first
+    #[diesel(sql_type = Double)]
+    pub score: f64,
+}
+
+/// How well `name` matches `query` (both lowercase), None if it does not
+fn match_score(name: &str, query: &str) -> Option<f64> {
+    if name == query {
+        Some(1.0)
+    } else if name.starts_with(query) {
+        Some(0.9)
+    } else if name.split(|c: char| !c.is_alphanumeric()).any(|word| word.starts_with(query)) {
+        Some(0.8)
+    } else if name.contains(query) {
+        Some(0.7)
+    } else {
+        None
+    }
+}
+
+/// In-memory index of tracked DApp names
+#[derive(Debug, Clone, Default)]
+pub struct DAppNameIndex {
+    /// lowercase name -> (name, category)
+    entries: BTreeMap<String, (String, String)>,
+}
+
+impl DAppNameIndex {
+    /// Build the index from (name, category) pairs; duplicate names are kept once
+    pub fn new<'a>(dapps: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        let entries = dapps
+            .into_iter()
+            .map(|(name, dapp_type)| (name.to_lowercase(), (name.to_string(), dapp_type.to_string())))
+            .collect();
+        Self { entries }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Tracked name of the DApp called `name`, case-insensitive
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries.get(&name.trim().to_lowercase()).map(|(name, _)| name.as_str())
+    }
+
+    /// DApps whose name matches `query`, case-insensitive
+    /// Exact matches come first, then name prefixes, word prefixes and substrings
+    pub fn search(&self, query: &str, limit: usize) -> Vec<DAppSearchResult> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let mut results: Vec<DAppSearchResult> = self
+            .entries
+            .iter()
+            .filter_map(|(lowercase, (name, dapp_type))| {
+                match_score(lowercase, &query).map(|score| DAppSearchResult {
+                    dapp_name: name.clone(),
+                    dapp_type: dapp_type.clone(),
+                    score,
+                })
+            })
+            .collect();
+        // Stable sort keeps alphabetical order within equal scores
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+        results.truncate(limit);
+        results
+    }
+}
+
+/// Append `more` to `results` up to `limit`, skipping names already present
+pub fn merge_results(mut results: Vec<DAppSearchResult>, more: Vec<DAppSearchResult>, limit: usize) -> Vec<DAppSearchResult> {
+    for result in more {
+        if results.len() >= limit {
+            break;
+        }
+        if !results.iter().any(|existing| existing.dapp_name == result.dapp_name) {
+            results.push(result);
+        }
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_name_index_orders_matches() {
+        let index = DAppNameIndex::new([
+            ("Cetus AMM", "DEX"),
+            ("Cetus Aggregator", "Aggregator"),
+            ("Cetus AMM", "DEX"),
+            ("7K Aggregator", "Aggregator"),
+            ("Suilend", "Lending"),
+        ]);
+        assert_eq!(index.len(), 4);
+
+        let names = |query: &str| -> Vec<String> {
+            index.search(query, 10).into_iter().map(|result| result.dapp_name).collect()
+        };
+        assert_eq!(names("cetus"), vec!["Cetus Aggregator", "Cetus AMM"]);
+        assert_eq!(names("agg"), vec!["7K Aggregator", "Cetus Aggregator"]);
+        assert_eq!(names("lend"), vec!["Suilend"]);
+        assert!(names("  ").is_empty());
+    }
+}
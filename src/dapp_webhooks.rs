@@ -0,0 +1,238 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Webhook subscriptions scoped to a single DApp.
+//!
+//! Subscriptions are registered through the admin API and stored in
+//! `webhook_subscriptions`, e.g. to notify a DApp's team when its active users
+//! change by more than 20%. On every ranking update the leader compares the
+//! active users of each subscribed DApp with the subscription's baseline, the
+//! count it last fired at; a larger change queues a notification in
+//! `webhook_deliveries` and moves the baseline. A DApp missing from the
+//! rankings counts as 0 users.
+//!
+//! Queued notifications are POSTed as JSON by a delivery loop. A delivery
+//! failing (no 2xx answer) is retried with exponential backoff from
+//! RETRY_BASE_DELAY up to RETRY_MAX_DELAY, until MAX_ATTEMPTS; the status,
+//! attempts and last error of every delivery are kept for the admin API.
+
+use crate::database::DatabaseManager;
+use crate::leader_election::Leadership;
+use crate::models::{DAppRanking, NewWebhookDelivery, WebhookDeliveryRecord};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{error, info, warn};
+
+/// Time between two runs of the delivery loop
+const DELIVERY_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Deliveries attempted per run of the delivery loop
+const DELIVERY_BATCH_SIZE: i64 = 100;
+
+/// Timeout of a single delivery
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Attempts after which a delivery is marked as failed
+pub const MAX_ATTEMPTS: u32 = 8;
+
+/// Delay before the first retry, doubled on every further attempt
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(30);
+
+/// Longest delay between two attempts
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(60 * 60);
+
+/// Header carrying the delivery id, so receivers can drop duplicates of a retried delivery
+pub const DELIVERY_ID_HEADER: &str = "X-SurfSui-Delivery";
+
+/// Body POSTed when a DApp's active users changed by more than the threshold
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DauChangeNotification {
+    /// Always `dau_change`
+    pub event: &'static str,
+    pub subscription_id: i32,
+    pub dapp_name: String,
+    /// Ranking window the users are counted over, e.g. 1h
+    pub window: String,
+    /// Active users when the subscription last fired
+    pub previous_users: u32,
+    pub active_users: u32,
+    pub change_pct: f64,
+    pub threshold_pct: f64,
+    /// Rank of the DApp, None when it is not ranked
+    pub rank: Option<u32>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Change from `baseline` to `users` in percent, if it is larger than `threshold_pct`
+/// A baseline of 0 users counts as 1, so a DApp coming back is reported
+pub fn dau_change_pct(baseline: u32, users: u32, threshold_pct: f64) -> Option<f64> {
+    if users == baseline {
+        return None;
+    }
+    let baseline = baseline.max(1) as f64;
+    let change = (users as f64 - baseline) / baseline * 100.0;
+    (change.abs() > threshold_pct).then_some(change)
+}
+
+/// Delay before the next attempt of a delivery that failed `attempts` times,
+/// None once it ran out of attempts
+pub fn retry_delay(attempts: u32) -> Option<Duration> {
+    if attempts >= MAX_ATTEMPTS {
+        return None;
+    }
+    let factor = 2u32.saturating_pow(attempts.saturating_sub(1));
+    Some(RETRY_BASE_DELAY.saturating_mul(factor).min(RETRY_MAX_DELAY))
+}
+
+/// Compare every active subscription with the rankings, queueing a notification
+/// for each change above its threshold
+///
+/// # Returns
+/// * Number of notifications queued
+pub async fn check_subscriptions(db_manager: &DatabaseManager, rankings: &[DAppRanking], window: &str) -> Result<usize> {
+    let by_name: HashMap<&str, &DAppRanking> =
+        rankings.iter().map(|ranking| (ranking.dapp_name.as_str(), ranking)).collect();
+    let mut queued = 0;
+    for subscription in db_manager.get_webhook_subscriptions(None).await? {
+        let ranking = by_name.get(subscription.dapp_name.as_str());
+        let users = ranking.map_or(0, |ranking| ranking.active_users);
+        let Some(baseline) = subscription.baseline_users else {
+            db_manager.set_webhook_baseline(subscription.id, users as i32).await?;
+            continue;
+        };
+        let Some(change_pct) = dau_change_pct(baseline.max(0) as u32, users, subscription.threshold_pct) else {
+            continue;
+        };
+
+        let notification = DauChangeNotification {
+            event: "dau_change",
+            subscription_id: subscription.id,
+            dapp_name: subscription.dapp_name.clone(),
+            window: window.to_string(),
+            previous_users: baseline.max(0) as u32,
+            active_users: users,
+            change_pct,
+            threshold_pct: subscription.threshold_pct,
+            rank: ranking.map(|ranking| ranking.rank),
+            timestamp: Utc::now(),
+        };
+        let delivery = NewWebhookDelivery { subscription_id: subscription.id, payload: serde_json::to_string(&notification)? };
+        db_manager.enqueue_webhook_delivery(&delivery, users as i32).await?;
+        info!("🪝 Active users of {} over {} changed {:+.1}%, notifying subscription {}",
+              subscription.dapp_name, window, change_pct, subscription.id);
+        queued += 1;
+    }
+    Ok(queued)
+}
+
+/// POST a delivery to `url`
+///
+/// # Returns
+/// * HTTP status of the answer, if any, and the error if it was not delivered
+async fn attempt_delivery(client: &reqwest::Client, delivery: &WebhookDeliveryRecord, url: &str) -> (Option<i32>, Option<String>) {
+    let sent = client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(DELIVERY_ID_HEADER, delivery.id.to_string())
+        .body(delivery.payload.clone())
+        .send()
+        .await;
+    match sent {
+        Ok(response) if response.status().is_success() => (Some(response.status().as_u16() as i32), None),
+        Ok(response) => (Some(response.status().as_u16() as i32), Some(format!("HTTP {}", response.status()))),
+        Err(err) => (None, Some(err.without_url().to_string())),
+    }
+}
+
+/// Attempt every delivery that is due, recording the outcome of each
+///
+/// # Returns
+/// * Number of deliveries attempted
+pub async fn deliver_due(db_manager: &DatabaseManager, client: &reqwest::Client) -> Result<usize> {
+    let due = db_manager.get_due_webhook_deliveries(Utc::now().naive_utc(), DELIVERY_BATCH_SIZE).await?;
+    for (delivery, url) in &due {
+        let (response_status, error) = attempt_delivery(client, delivery, url).await;
+        let attempts = delivery.attempts.max(0) as u32 + 1;
+        let retry_at = match &error {
+            Some(_) => retry_delay(attempts).map(|delay| Utc::now().naive_utc() + delay),
+            None => None,
+        };
+        if let Some(error) = &error {
+            match retry_at {
+                Some(retry_at) => warn!("⚠️ Webhook delivery {} failed ({}), retrying at {}", delivery.id, error, retry_at),
+                None => error!("❌ Webhook delivery {} failed after {} attempts: {}", delivery.id, attempts, error),
+            }
+        }
+        db_manager
+            .record_webhook_attempt(delivery.id, response_status, error.as_deref(), retry_at)
+            .await
+            .map_err(|err| anyhow!("Failed to record webhook delivery {}: {}", delivery.id, err))?;
+    }
+    Ok(due.len())
+}
+
+/// Start the tasks checking subscriptions on every ranking update and delivering queued notifications
+/// Only the leader checks and delivers
+pub async fn start_dapp_webhooks(
+    db_manager: Arc<DatabaseManager>,
+    mut rankings_updates: watch::Receiver<Arc<Vec<DAppRanking>>>,
+    window: String,
+    leadership: Leadership,
+) -> Result<()> {
+    let client = reqwest::Client::builder().timeout(DELIVERY_TIMEOUT).build()?;
+    info!("🪝 Starting per-DApp webhook subscriptions");
+
+    let checker_db = db_manager.clone();
+    let checker_leadership = leadership.clone();
+    tokio::spawn(async move {
+        while rankings_updates.changed().await.is_ok() {
+            let rankings = rankings_updates.borrow_and_update().clone();
+            if !checker_leadership.is_leader() || rankings.is_empty() {
+                continue;
+            }
+            if let Err(err) = check_subscriptions(&checker_db, &rankings, &window).await {
+                error!("❌ Failed to check webhook subscriptions: {}", err);
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(DELIVERY_INTERVAL);
+        loop {
+            interval.tick().await;
+            if !leadership.is_leader() {
+                continue;
+            }
+            if let Err(err) = deliver_due(&db_manager, &client).await {
+                error!("❌ Failed to deliver webhooks: {}", err);
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fires_above_threshold_and_backs_off() {
+        assert_eq!(dau_change_pct(100, 125, 20.0), Some(25.0));
+        assert_eq!(dau_change_pct(100, 75, 20.0), Some(-25.0));
+        assert_eq!(dau_change_pct(100, 120, 20.0), None);
+        // A DApp dropping out of the rankings, and coming back
+        assert_eq!(dau_change_pct(40, 0, 20.0), Some(-100.0));
+        assert_eq!(dau_change_pct(0, 5, 20.0), Some(400.0));
+        assert_eq!(dau_change_pct(0, 0, 20.0), None);
+
+        assert_eq!(retry_delay(1), Some(Duration::from_secs(30)));
+        assert_eq!(retry_delay(3), Some(Duration::from_secs(120)));
+        assert_eq!(retry_delay(MAX_ATTEMPTS - 1), Some(Duration::from_secs(32 * 60)));
+        assert_eq!(retry_delay(MAX_ATTEMPTS), None);
+    }
+}
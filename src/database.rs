@@ -3,49 +3,467 @@
 
 use diesel::prelude::*;
 use diesel::sql_query;
+use diesel::upsert::excluded;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use diesel_async::pooled_connection::bb8::Pool;
-use diesel_async::pooled_connection::AsyncDieselConnectionManager;
-use crate::models::{DAppRankingRecord, DAppRanking};
-use crate::schema::dapp_rankings;
-use anyhow::Result;
-use tracing::info;
+use diesel_async::pooled_connection::{AsyncDieselConnectionManager, ManagerConfig};
+use diesel_async::async_connection_wrapper::AsyncConnectionWrapper;
+use diesel::migration::MigrationSource;
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+use crate::models::{
+    BridgeStats, BridgeStatsRecord, CategoryStats, CategoryStatsRecord, DailyUserSketchRecord, DAppEpochStats, DAppEventStats, DAppEventStatsRecord, DAppFunctionStats, DAppFunctionStatsRecord, DAppHourlyActivity, DAppHourlyActivityRecord, DAppLendingStats, DAppLendingStatsRecord, DAppMetadataRecord, DAppNftStats, DAppNftStatsRecord, DAppPackageStats, DAppPackageStatsRecord, DAppPublishedPackage, DAppRankingHistoryRecord, DAppRankingRecord, DAppRankingSnapshotRecord, DAppRanking, DAppRegistryRecord,
+    DAppRetentionRecord, DAppTopUser, DAppTopUserRecord, DAppUnknownActivityRecord, EpochStatsRecord, NewCategoryStatsRecord, NewDAppCalibrationRecord, NewDAppInteractionRecord,
+    NewDAppRankingHistoryRecord, NewDAppTopUserRecord, NewDAppUserActivity, NewDAppUserFirstSeen, NewWebhookDelivery, NewWebhookSubscription, WebhookDeliveryRecord,
+    WebhookSubscriptionRecord, DELIVERY_DELIVERED, DELIVERY_FAILED, DELIVERY_PENDING, OTHERS_DAPP_NAME, TOP_USERS_PERCENT, TRACKING_ACTIVE,
+};
+use crate::dapp_indexer::DEFAULT_RANKING_WINDOW;
+use crate::dapp_search::DAppSearchResult;
+use crate::db_retry::{DbRetry, RetryPolicy};
+use crate::interaction_store::window_label;
+use crate::ranking_query::{RankingFilter, RankingPage, RankingSortColumn, SortOrder};
+use crate::ranking_snapshots::SnapshotPeriod;
+use crate::retention::RetentionWindow;
+use crate::user_sketch::{daily_sketches, UserSketch};
+use crate::schema::{
+    bridge_stats, category_stats, checkpoint_progress, daily_user_sketches, dapp_calibration, dapp_event_stats, dapp_function_stats, dapp_hourly_activity, dapp_interactions, dapp_lending_stats, dapp_metadata, dapp_nft_stats, dapp_package_stats, dapp_published_packages, dapp_ranking_history, dapp_rankings, dapp_registry, dapp_retention, dapp_top_users, dapp_unknown_activity, dapp_user_activity,
+    dapp_user_first_seen, epoch_stats, webhook_deliveries, webhook_subscriptions,
+};
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use diesel::sql_types::{Array, BigInt, Date, Double, Integer, Text, Timestamp, Timestamptz};
+use diesel::ConnectionError;
+use futures_util::FutureExt;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::CertificateDer;
+use rustls::{ClientConfig, RootCertStore};
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_postgres::config::SslMode;
+use tokio_postgres::NoTls;
+use tokio_postgres_rustls::MakeRustlsConnect;
+use tracing::{info, instrument};
+
+/// Migrations in `migrations/`, compiled into the binary
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
+/// Postgres' limit on bind parameters per statement
+const MAX_BIND_PARAMETERS: usize = 65_535;
+
+/// Maximum rows per multi-row INSERT of `columns` columns, keeping bind parameters under Postgres' limit
+const fn insert_chunk_size(columns: usize) -> usize {
+    MAX_BIND_PARAMETERS / columns
+}
+
+/// Rows per INSERT into `dapp_interactions`, which binds all 9 of its columns
+const INTERACTION_CHUNK_SIZE: usize = insert_chunk_size(9);
+
+/// Days category statistics snapshots are kept
+const CATEGORY_STATS_RETENTION_DAYS: i64 = 90;
+
+/// Connections kept by the pool unless configured otherwise
+pub const DEFAULT_POOL_SIZE: u32 = 10;
+
+/// Time allowed to get a connection unless configured otherwise
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How connections to Postgres are made and pooled
+/// TLS is used when the URL asks for `sslmode=require`; the server certificate
+/// is verified against the webpki roots, or `ssl_root_cert` if set. The
+/// `verify-ca` and `verify-full` modes are not accepted: `require` verifies the
+/// certificate and host name as `verify-full` does
+#[derive(Debug, Clone)]
+pub struct ConnectionSettings {
+    /// Maximum number of pooled connections
+    pub pool_size: u32,
+    /// Time allowed to establish or check out a connection
+    pub connect_timeout: Duration,
+    /// `statement_timeout` set on every connection, None for the server default
+    pub statement_timeout: Option<Duration>,
+    /// PEM file with the root certificates trusted for TLS connections
+    pub ssl_root_cert: Option<PathBuf>,
+}
+
+impl ConnectionSettings {
+    /// Check that `database_url` parses and asks for TLS when `ssl_root_cert` is set,
+    /// as a plaintext connection would never use the certificates
+    pub fn check_database_url(&self, database_url: &str) -> Result<()> {
+        let config: tokio_postgres::Config = database_url
+            .parse()
+            .context("DATABASE_URL must be a valid Postgres URL (sslmode must be disable, prefer or require)")?;
+        if self.ssl_root_cert.is_some() && !matches!(config.get_ssl_mode(), SslMode::Require) {
+            return Err(anyhow!("DATABASE_SSL_ROOT_CERT requires sslmode=require in DATABASE_URL"));
+        }
+        Ok(())
+    }
+}
+
+impl Default for ConnectionSettings {
+    fn default() -> Self {
+        Self {
+            pool_size: DEFAULT_POOL_SIZE,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            statement_timeout: None,
+            ssl_root_cert: None,
+        }
+    }
+}
+
+/// Opens connections with the TLS and session settings of a `ConnectionSettings`
+#[derive(Clone)]
+struct Connector {
+    tls: MakeRustlsConnect,
+    connect_timeout: Duration,
+    statement_timeout: Option<Duration>,
+}
+
+impl Connector {
+    fn new(settings: &ConnectionSettings) -> Result<Self> {
+        let mut roots = RootCertStore::empty();
+        match &settings.ssl_root_cert {
+            Some(path) => {
+                let certs = CertificateDer::pem_file_iter(path)
+                    .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+                    .with_context(|| format!("Failed to read root certificates from {}", path.display()))?;
+                let (added, _) = roots.add_parsable_certificates(certs);
+                if added == 0 {
+                    return Err(anyhow!("No valid root certificates in {}", path.display()));
+                }
+            }
+            None => roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned()),
+        }
+        let tls_config = ClientConfig::builder_with_provider(Arc::new(rustls::crypto::aws_lc_rs::default_provider()))
+            .with_safe_default_protocol_versions()?
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+
+        Ok(Self {
+            tls: MakeRustlsConnect::new(tls_config),
+            connect_timeout: settings.connect_timeout,
+            statement_timeout: settings.statement_timeout,
+        })
+    }
+
+    /// Open a connection, over TLS if the URL requires it
+    async fn connect(&self, database_url: &str) -> Result<AsyncPgConnection> {
+        let config: tokio_postgres::Config = database_url.parse()?;
+        let connecting = async {
+            if matches!(config.get_ssl_mode(), SslMode::Require) {
+                let (client, connection) = config.connect(self.tls.clone()).await?;
+                Ok::<_, anyhow::Error>(AsyncPgConnection::try_from_client_and_connection(client, connection).await?)
+            } else {
+                let (client, connection) = config.connect(NoTls).await?;
+                Ok(AsyncPgConnection::try_from_client_and_connection(client, connection).await?)
+            }
+        };
+        let mut conn = tokio::time::timeout(self.connect_timeout, connecting)
+            .await
+            .map_err(|_| anyhow!("Timed out connecting to the database after {:?}", self.connect_timeout))??;
+
+        if let Some(statement_timeout) = self.statement_timeout {
+            sql_query(format!("SET statement_timeout = {}", statement_timeout.as_millis()))
+                .execute(&mut conn)
+                .await?;
+        }
+        Ok(conn)
+    }
+}
+
+/// New vs returning user counts of one DApp
+#[derive(QueryableByName, Debug)]
+struct UserBreakdownRow {
+    #[diesel(sql_type = Text)]
+    dapp_name: String,
+    #[diesel(sql_type = BigInt)]
+    new_users: i64,
+    #[diesel(sql_type = BigInt)]
+    returning_users: i64,
+}
+
+/// Activity of one DApp over a window, derived from dapp_interactions
+#[derive(QueryableByName, Debug)]
+struct WindowActivityRow {
+    #[diesel(sql_type = Text)]
+    dapp_name: String,
+    #[diesel(sql_type = Text)]
+    package_id: String,
+    #[diesel(sql_type = BigInt)]
+    active_users: i64,
+    #[diesel(sql_type = BigInt)]
+    sponsored_users: i64,
+    #[diesel(sql_type = BigInt)]
+    interactions_p50: i64,
+    #[diesel(sql_type = BigInt)]
+    interactions_p90: i64,
+    #[diesel(sql_type = BigInt)]
+    interactions_p99: i64,
+    #[diesel(sql_type = Double)]
+    top_users_interaction_pct: f64,
+    #[diesel(sql_type = BigInt)]
+    tx_count: i64,
+    #[diesel(sql_type = BigInt)]
+    gas_used: i64,
+}
 
 pub struct DatabaseManager {
     pool: Pool<AsyncPgConnection>,
+    retry: DbRetry,
+    database_url: String,
+    connector: Connector,
+    /// Window label the live rankings are stored under
+    live_window: String,
 }
 
 impl DatabaseManager {
     pub async fn new(database_url: &str) -> Result<Self> {
-        let config = AsyncDieselConnectionManager::<AsyncPgConnection>::new(database_url);
-        let pool = Pool::builder().build(config).await?;
-        Ok(Self { pool })
+        Self::with_settings(database_url, ConnectionSettings::default()).await
+    }
+
+    /// Connect with the given pool, timeout and TLS settings
+    pub async fn with_settings(database_url: &str, settings: ConnectionSettings) -> Result<Self> {
+        let connector = Connector::new(&settings)?;
+        let mut manager_config = ManagerConfig::default();
+        let pool_connector = connector.clone();
+        manager_config.custom_setup = Box::new(move |database_url| {
+            let connector = pool_connector.clone();
+            let database_url = database_url.to_string();
+            async move {
+                connector
+                    .connect(&database_url)
+                    .await
+                    .map_err(|err| ConnectionError::BadConnection(format!("{:#}", err)))
+            }
+            .boxed()
+        });
+
+        let config = AsyncDieselConnectionManager::<AsyncPgConnection>::new_with_config(database_url, manager_config);
+        let pool = Pool::builder()
+            .max_size(settings.pool_size)
+            .connection_timeout(settings.connect_timeout)
+            .build(config)
+            .await?;
+        Ok(Self {
+            pool,
+            retry: DbRetry::default(),
+            database_url: database_url.to_string(),
+            connector,
+            live_window: window_label(DEFAULT_RANKING_WINDOW),
+        })
+    }
+
+    /// Open a dedicated connection outside the pool, with the same settings
+    pub async fn connect(&self) -> Result<AsyncPgConnection> {
+        self.connector.connect(&self.database_url).await
+    }
+
+    /// Apply the embedded migrations that have not been run yet
+    ///
+    /// # Returns
+    /// * Versions of the migrations that were applied
+    pub async fn run_migrations(&self) -> Result<Vec<String>> {
+        let conn = self.connect().await?;
+        // Migrations need a blocking connection, which must not run on the async runtime
+        tokio::task::spawn_blocking(move || {
+            let mut conn = AsyncConnectionWrapper::<AsyncPgConnection>::from(conn);
+            let applied = conn
+                .run_pending_migrations(MIGRATIONS)
+                .map_err(|err| anyhow!("Failed to run database migrations: {}", err))?;
+            Ok(applied.iter().map(|version| version.to_string()).collect())
+        })
+        .await?
+    }
+
+    /// Fail unless exactly the embedded migrations have been applied to the database
+    /// Pending migrations mean the schema is older than this binary, unknown ones that it is newer
+    pub async fn check_schema_version(&self) -> Result<()> {
+        let conn = self.connect().await?;
+        tokio::task::spawn_blocking(move || {
+            let mut conn = AsyncConnectionWrapper::<AsyncPgConnection>::from(conn);
+            let applied: Vec<String> = conn
+                .applied_migrations()
+                .map_err(|err| anyhow!("Failed to read applied migrations: {}", err))?
+                .iter()
+                .map(|version| version.to_string())
+                .collect();
+            let embedded: Vec<String> = MigrationSource::<diesel::pg::Pg>::migrations(&MIGRATIONS)
+                .map_err(|err| anyhow!("Failed to read embedded migrations: {}", err))?
+                .iter()
+                .map(|migration| migration.name().version().to_string())
+                .collect();
+
+            let pending: Vec<&String> = embedded.iter().filter(|version| !applied.contains(version)).collect();
+            if !pending.is_empty() {
+                return Err(anyhow!(
+                    "Database schema is out of date, {} migration(s) pending (first: {}); set RUN_MIGRATIONS=true or run `diesel migration run`",
+                    pending.len(), pending[0]
+                ));
+            }
+
+            let unknown: Vec<&String> = applied.iter().filter(|version| !embedded.contains(version)).collect();
+            if !unknown.is_empty() {
+                return Err(anyhow!(
+                    "Database schema is newer than this binary, unknown migration(s): {}",
+                    unknown.iter().map(|version| version.as_str()).collect::<Vec<_>>().join(", ")
+                ));
+            }
+
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Store and read the live rankings under `label`, the label of the configured ranking window
+    pub fn with_live_window(mut self, label: impl Into<String>) -> Self {
+        self.live_window = label.into();
+        self
+    }
+
+    /// Window label the live rankings are stored under
+    pub fn live_window(&self) -> &str {
+        &self.live_window
+    }
+
+    /// Replace the retry policy used by `with_retry`
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = DbRetry::new(policy);
+        self
+    }
+
+    /// Run a database operation with retries, failing fast while the database is unavailable
+    pub async fn with_retry<T, F, Fut>(&self, name: &str, operation: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        self.retry.run(name, operation).await
+    }
+
+    /// Returns false while operations are paused after repeated failures
+    pub fn is_available(&self) -> bool {
+        self.retry.is_available()
     }
 
     pub async fn get_connection(&self) -> Result<bb8::PooledConnection<'_, AsyncDieselConnectionManager<AsyncPgConnection>>> {
         Ok(self.pool.get().await?)
     }
 
-    pub async fn get_top_dapps(
-        &self,
-        limit: i64,
-    ) -> Result<Vec<DAppRankingRecord>> {
+    /// One page of rankings matching `filter`, with the total number of matches
+    pub async fn query_rankings(&self, filter: &RankingFilter) -> Result<RankingPage> {
         let mut conn = self.get_connection().await?;
-        
+
+        // Same filter for the page and the total count
+        let window = filter.window.as_deref().unwrap_or(&self.live_window);
+        let filtered = || {
+            let mut query = dapp_rankings::table
+                .filter(dapp_rankings::window_label.eq(window))
+                .into_boxed();
+            if let Some(dapp_type) = &filter.dapp_type {
+                query = query.filter(dapp_rankings::dapp_type.eq(dapp_type.clone()));
+            }
+            if let Some(min_dau) = filter.min_dau {
+                query = query.filter(dapp_rankings::active_users.ge(min_dau));
+            }
+            query
+        };
+
+        let total = filtered().count().get_result::<i64>(&mut conn).await?;
+
+        let query = filtered().select(DAppRankingRecord::as_select());
+        let query = match (filter.sort_by, filter.sort_order()) {
+            (RankingSortColumn::Rank, SortOrder::Asc) => query.order(dapp_rankings::rank_position.asc()),
+            (RankingSortColumn::Rank, SortOrder::Desc) => query.order(dapp_rankings::rank_position.desc()),
+            (RankingSortColumn::Hau, SortOrder::Asc) => query.order(dapp_rankings::active_users.asc()),
+            (RankingSortColumn::Hau, SortOrder::Desc) => query.order(dapp_rankings::active_users.desc()),
+            (RankingSortColumn::FilteredUsers, SortOrder::Asc) => query.order(dapp_rankings::filtered_users.asc()),
+            (RankingSortColumn::FilteredUsers, SortOrder::Desc) => query.order(dapp_rankings::filtered_users.desc()),
+            (RankingSortColumn::NewUsers, SortOrder::Asc) => query.order(dapp_rankings::new_users_24h.asc()),
+            (RankingSortColumn::NewUsers, SortOrder::Desc) => query.order(dapp_rankings::new_users_24h.desc()),
+            (RankingSortColumn::ReturningUsers, SortOrder::Asc) => query.order(dapp_rankings::returning_users_24h.asc()),
+            (RankingSortColumn::ReturningUsers, SortOrder::Desc) => query.order(dapp_rankings::returning_users_24h.desc()),
+            (RankingSortColumn::TxCount, SortOrder::Asc) => query.order(dapp_rankings::tx_count.asc()),
+            (RankingSortColumn::TxCount, SortOrder::Desc) => query.order(dapp_rankings::tx_count.desc()),
+            (RankingSortColumn::GasUsed, SortOrder::Asc) => query.order(dapp_rankings::gas_used.asc()),
+            (RankingSortColumn::GasUsed, SortOrder::Desc) => query.order(dapp_rankings::gas_used.desc()),
+            (RankingSortColumn::Score, SortOrder::Asc) => query.order(dapp_rankings::score.asc()),
+            (RankingSortColumn::Score, SortOrder::Desc) => query.order(dapp_rankings::score.desc()),
+            (RankingSortColumn::DauSmoothed, SortOrder::Asc) => query.order(dapp_rankings::dau_smoothed.asc()),
+            (RankingSortColumn::DauSmoothed, SortOrder::Desc) => query.order(dapp_rankings::dau_smoothed.desc()),
+            (RankingSortColumn::SponsoredUserPct, SortOrder::Asc) => query.order(dapp_rankings::sponsored_user_pct.asc()),
+            (RankingSortColumn::SponsoredUserPct, SortOrder::Desc) => query.order(dapp_rankings::sponsored_user_pct.desc()),
+            (RankingSortColumn::AvgGasPerTx, SortOrder::Asc) => query.order(dapp_rankings::avg_gas_per_tx.asc()),
+            (RankingSortColumn::AvgGasPerTx, SortOrder::Desc) => query.order(dapp_rankings::avg_gas_per_tx.desc()),
+            (RankingSortColumn::Name, SortOrder::Asc) => query.order(dapp_rankings::dapp_name.asc()),
+            (RankingSortColumn::Name, SortOrder::Desc) => query.order(dapp_rankings::dapp_name.desc()),
+        };
+
+        // Ties keep the ranking order, so pages are stable
+        let (offset, limit) = filter.page();
+        let rankings = query
+            .then_order_by(dapp_rankings::rank_position.asc())
+            .then_order_by(dapp_rankings::dapp_name.asc())
+            .offset(offset)
+            .limit(limit)
+            .load::<DAppRankingRecord>(&mut conn)
+            .await?;
+
+        Ok(RankingPage { rankings, total })
+    }
+
+    /// Ranked DApps whose name contains `query` or is similar to it (trigram similarity)
+    /// Case-insensitive; best matches first
+    pub async fn search_dapps(&self, query: &str, limit: i64) -> Result<Vec<DAppSearchResult>> {
+        let mut conn = self.get_connection().await?;
+
+        // Match the query literally inside ILIKE patterns
+        let escaped = query.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+        let results = sql_query(
+            "SELECT dapp_name, MIN(dapp_type) AS dapp_type, \
+                    MAX(GREATEST( \
+                        CASE WHEN lower(dapp_name) = lower($1) THEN 1.0 \
+                             WHEN dapp_name ILIKE $2 || '%' THEN 0.9 \
+                             WHEN dapp_name ILIKE '%' || $2 || '%' THEN 0.7 \
+                             ELSE 0.0 END, \
+                        similarity(dapp_name, $1)::float8 \
+                    )) AS score \
+             FROM dapp_rankings \
+             WHERE window_label = $4 AND (dapp_name ILIKE '%' || $2 || '%' OR dapp_name % $1) \
+             GROUP BY dapp_name \
+             ORDER BY score DESC, dapp_name \
+             LIMIT $3",
+        )
+        .bind::<Text, _>(query)
+        .bind::<Text, _>(escaped)
+        .bind::<BigInt, _>(limit)
+        .bind::<Text, _>(&self.live_window)
+        .load::<DAppSearchResult>(&mut conn)
+        .await?;
+
+        Ok(results)
+    }
+
+    /// Current rankings of one DApp in every stored window, the live window first
+    pub async fn get_dapp_ranking(&self, dapp_name: &str) -> Result<Vec<DAppRankingRecord>> {
+        let mut conn = self.get_connection().await?;
+
         let rankings = dapp_rankings::table
+            .filter(dapp_rankings::dapp_name.eq(dapp_name))
             .select(DAppRankingRecord::as_select())
-            .order(dapp_rankings::rank_position.asc())
-            .limit(limit)
+            .order((dapp_rankings::window_label.ne(&self.live_window), dapp_rankings::window_label.asc()))
             .load::<DAppRankingRecord>(&mut conn)
             .await?;
 
         Ok(rankings)
     }
 
+    /// Live rankings, best first
     pub async fn get_dapp_rankings(&self) -> Result<Vec<DAppRankingRecord>> {
         let mut conn = self.get_connection().await?;
         
         let rankings = dapp_rankings::table
+            .filter(dapp_rankings::window_label.eq(&self.live_window))
             .select(DAppRankingRecord::as_select())
             .order(dapp_rankings::rank_position.asc())
             .load::<DAppRankingRecord>(&mut conn)
@@ -54,49 +472,51 @@ impl DatabaseManager {
         Ok(rankings)
     }
 
-    pub async fn cleanup_unknown_dapps(&self) -> Result<()> {
-        let mut conn = self.get_connection().await?;
-
-        // Define tracked package IDs
-        let tracked_package_ids = vec![
-            // Existing DApps
-            "0xda12d621169da92ed8af5f6b332b7bec64c840bb49bb3d4206d6739cd76bad14", // FanTV AI
-            "0x2cdcc3b1306a49fcd5b8ccded57116ad86ab37a93ba9d91fa1ce06a8d22a21e9", // 6degrees
-            "0xa2f06318d797e3a2ba734069165e164870677f705d95d8a18b6d9aabbd588709", // Aftermath AMM
-            "0xada81624f2be6abd31f2433dac2642a03414cdb20d494314a4d3d889281fb5e",  // Pebble
-            "0x04e20ddf36af412a4096f9014f4a565af9e812db9a05cc40254846cf6ed0ad91", // Pyth
-            "0x9c12f3aa14a449a0a23c066589e269086f021a98939f21158cfacb16d19787c3", // Momentum
-            "0x7ea6e27ad7af6f3b8671d59df1aaebd7c03dddab893e52a714227b2f4fe91519", // 7K Aggregator
-            "0xb908f3c6fea6865d32e2048c520cdfe3b5c5bbcebb658117c41bad70f52b7ccc", // Claynosaurz
-            "0x21f544aff826a48e6bd5364498454d8487c4a90f84995604cd5c947c06b596c3", // Suilend
-            "0x9df4666296ee324a6f11e9f664e35e7fd6b6e8c9e9058ce6ee9ad5c5343c2f87", // Ika
-            // New DApps
-            "0x0000000000000000000000000000000000000000000000000000000000000002", // Sui
-            "0x0000000000000000000000000000000000000000000000000000000000000001", // Sui
-            "0x5306f64e312b581766351c07af79c72fcb1cd25147157fdc2f8ad76de9a3fb6a", // Portal
-            "0x2476333f61ab625ae25205b6726048295fe8b356d26ca841ddf93c69bbd616c8", // Turbos
-            "0x6f5e582ede61fe5395b50c4a449ec11479a54d7ff8e0158247adfda60d98970b", // Cetus AMM
-            "0x3864c7c59a4889fec05d1aae4bc9dba5a0e0940594b424fbed44cb3f6ac4c032", // Cetus AMM
-            "0x51966dc1d9d3e6d85aed55aa87eb9e78e928b4e74b4844a15ef7e3dfb5af3bae", // Cetus Aggregator
-            "0x7cdd26c4aa40c990d5ca780e0919b2de796be9bb41fba461d133bfacb0f677bc", // Cetus Aggregator
-            "0x2c68443db9e8c813b194010c11040a3ce59f47e4eb97a2ec805371505dad7459", // Wave
-            "0x6d264cc3d4b7b81a7e3e47403b335d1d933ceb03dacc4328214f10bf8937a239", // NAVI Lending
-            "0x8d196820b321bb3c56863b3eb0dd90a49f9eb52e3473373efcebf4388bf04416", // SpringSui
-            "0x5a6df33a03a69959065b5e87aecac72d0afff893a1923833a77dcfb0d2f42980", // Metastable
-        ];
-
-        // Delete rankings for Unknown DApps or untracked package IDs
-        let delete_rankings_query = format!(
-            "DELETE FROM dapp_rankings WHERE dapp_name = 'Unknown DApp' OR package_id NOT IN ({})",
-            tracked_package_ids.iter().map(|id| format!("'{}'", id)).collect::<Vec<_>>().join(", ")
-        );
+    /// Move rankings of Unknown DApps and packages missing from `tracked_package_ids` out of dapp_rankings
+    /// Their last observed activity is kept in dapp_unknown_activity for review
+    #[instrument(skip_all)]
+    pub async fn cleanup_unknown_dapps(&self, tracked_package_ids: &[String]) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+
+        // Move rankings of Unknown DApps or untracked package IDs into dapp_unknown_activity,
+        // in one statement so rows are never lost between the delete and the insert
+        let move_rankings_query = "WITH moved AS ( \
+                 DELETE FROM dapp_rankings WHERE dapp_name = 'Unknown DApp' OR package_id <> ALL($1) \
+                 RETURNING package_id, dapp_name, active_users, last_update \
+             ) \
+             INSERT INTO dapp_unknown_activity (package_id, dapp_name, observed_dau, last_seen) \
+             SELECT DISTINCT ON (package_id) package_id, dapp_name, active_users, last_update AT TIME ZONE 'UTC' FROM moved \
+             ORDER BY package_id, last_update DESC \
+             ON CONFLICT (package_id) DO UPDATE SET \
+                 dapp_name = EXCLUDED.dapp_name, \
+                 observed_dau = EXCLUDED.observed_dau, \
+                 last_seen = EXCLUDED.last_seen";
 
-        sql_query(&delete_rankings_query).execute(&mut conn).await?;
+        let moved = sql_query(move_rankings_query)
+            .bind::<Array<Text>, _>(tracked_package_ids)
+            .execute(&mut conn)
+            .await?;
 
-        info!("Cleaned up Unknown DApps and untracked rankings from database");
+        if moved > 0 {
+            info!("Moved {} Unknown DApp and untracked rankings to dapp_unknown_activity", moved);
+        }
         Ok(())
     }
 
+    /// Get the recorded activity of untracked packages, most recently seen first
+    pub async fn get_unknown_activity(&self, limit: i64) -> Result<Vec<DAppUnknownActivityRecord>> {
+        let mut conn = self.get_connection().await?;
+
+        let activity = dapp_unknown_activity::table
+            .select(DAppUnknownActivityRecord::as_select())
+            .order(dapp_unknown_activity::last_seen.desc())
+            .limit(limit)
+            .load::<DAppUnknownActivityRecord>(&mut conn)
+            .await?;
+
+        Ok(activity)
+    }
+
     /// Reset all DApp-related data in the database
     /// This clears all rankings to start fresh
     pub async fn reset_all_data(&self) -> Result<()> {
@@ -115,35 +535,1488 @@ impl DatabaseManager {
     }
 
     /// Save rankings from memory directly to database
-    /// This method takes in-memory rankings and saves them to the database
-    pub async fn save_rankings_from_memory(&self, rankings: &[DAppRanking]) -> Result<()> {
+    /// This method takes in-memory rankings and replaces the stored rankings of `window_label` with them
+    #[instrument(skip_all, fields(rows = rankings.len(), window = window_label))]
+    pub async fn save_rankings_from_memory(&self, window_label: &str, rankings: &[DAppRanking]) -> Result<()> {
         let mut conn = self.get_connection().await?;
 
-        // Clear existing rankings first
-        let delete_query = "DELETE FROM dapp_rankings";
-        sql_query(delete_query).execute(&mut conn).await?;
+        // Clear existing rankings of the window first
+        sql_query("DELETE FROM dapp_rankings WHERE window_label = $1")
+            .bind::<Text, _>(window_label)
+            .execute(&mut conn)
+            .await?;
 
         // Insert new rankings if we have any
         if !rankings.is_empty() {
             let values: Vec<String> = rankings.iter().map(|ranking| {
                 format!(
-                    "({}, '{}', '{}', {}, '{}', NOW())",
+                    "({}, '{}', '{}', {}, '{}', '{}', {}, '{}', {}, {}, {}, {}, {}, {}, {}, '{}', {}, {}, {}, {}, {}, {}, {}, {}, {})",
                     ranking.rank,
                     ranking.package_id.replace("'", "''"), // Escape single quotes
                     ranking.dapp_name.replace("'", "''"),  // Escape single quotes
-                    ranking.dau_1h,
-                    ranking.dapp_type.replace("'", "''")   // Escape single quotes
+                    ranking.active_users,
+                    ranking.dapp_type.replace("'", "''"),  // Escape single quotes
+                    ranking.last_update.format("%Y-%m-%d %H:%M:%S%.6f+00"),
+                    ranking.filtered_users,
+                    ranking.tracking_status.replace("'", "''"),
+                    ranking.new_users_24h,
+                    ranking.returning_users_24h,
+                    ranking.tx_count,
+                    ranking.gas_used,
+                    ranking.score,
+                    ranking.error_rate_24h,
+                    ranking.dau_smoothed,
+                    window_label.replace("'", "''"),
+                    ranking.sponsored_user_pct,
+                    ranking.avg_gas_per_tx,
+                    ranking.interactions_p50,
+                    ranking.interactions_p90,
+                    ranking.interactions_p99,
+                    ranking.top_users_interaction_pct,
+                    ranking.volume_24h_usd,
+                    ranking.deposit_volume_24h_usd,
+                    ranking.borrow_volume_24h_usd
                 )
             }).collect();
 
             let insert_query = format!(
-                "INSERT INTO dapp_rankings (rank_position, package_id, dapp_name, dau_1h, dapp_type, last_update) VALUES {}",
+                "INSERT INTO dapp_rankings (rank_position, package_id, dapp_name, active_users, dapp_type, last_update, filtered_users, tracking_status, new_users_24h, returning_users_24h, tx_count, gas_used, score, error_rate_24h, dau_smoothed, window_label, sponsored_user_pct, avg_gas_per_tx, interactions_p50, interactions_p90, interactions_p99, top_users_interaction_pct, volume_24h_usd, deposit_volume_24h_usd, borrow_volume_24h_usd) VALUES {}",
                 values.join(", ")
             );
 
             sql_query(&insert_query).execute(&mut conn).await?;
+
+            // WAU and MAU are merged from the daily sets by the active users job
+            sql_query(
+                "UPDATE dapp_rankings r SET wau = a.wau, mau = a.mau \
+                 FROM dapp_active_users a \
+                 WHERE a.dapp_name = r.dapp_name AND r.window_label = $1",
+            )
+            .bind::<Text, _>(window_label)
+            .execute(&mut conn)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Record one calibration sample comparing our DAU with an external source
+    pub async fn save_calibration_sample(&self, record: &NewDAppCalibrationRecord) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+
+        diesel::insert_into(dapp_calibration::table)
+            .values(record)
+            .execute(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Append a snapshot of per-category statistics for the live ranking window
+    /// Rows are kept as history so category trends can be charted over time: finished hours
+    /// are downsampled to their last snapshot and snapshots older than
+    /// CATEGORY_STATS_RETENTION_DAYS are deleted
+    #[instrument(skip_all, fields(rows = stats.len()))]
+    pub async fn save_category_stats(&self, stats: &[CategoryStats]) -> Result<()> {
+        if stats.is_empty() {
+            return Ok(());
+        }
+
+        let mut conn = self.get_connection().await?;
+
+        let records: Vec<NewCategoryStatsRecord> = stats.iter().map(|category| NewCategoryStatsRecord {
+            dapp_type: category.dapp_type.clone(),
+            window_label: self.live_window.clone(),
+            active_users: category.active_users as i32,
+            transaction_count: category.transaction_count as i32,
+            interaction_count: category.interaction_count as i32,
+            dapp_count: category.dapp_count as i32,
+            computed_at: category.last_update.naive_utc(),
+            volume_24h_usd: category.volume_24h_usd,
+        }).collect();
+
+        diesel::insert_into(category_stats::table)
+            .values(&records)
+            .execute(&mut conn)
+            .await?;
+
+        // The first save of an hour downsamples the hour before, so looking two hours back is enough
+        let now = stats[0].last_update.naive_utc();
+        sql_query(
+            "DELETE FROM category_stats c \
+             WHERE c.computed_at < $2 \
+                OR (c.computed_at >= $1 - INTERVAL '2 hours' AND c.computed_at < date_trunc('hour', $1) \
+                    AND EXISTS ( \
+                        SELECT 1 FROM category_stats later \
+                        WHERE later.dapp_type = c.dapp_type AND later.window_label = c.window_label \
+                          AND date_trunc('hour', later.computed_at) = date_trunc('hour', c.computed_at) \
+                          AND later.computed_at > c.computed_at \
+                    ))",
+        )
+        .bind::<Timestamp, _>(now)
+        .bind::<Timestamp, _>(now - chrono::Duration::days(CATEGORY_STATS_RETENTION_DAYS))
+        .execute(&mut conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Get the most recent statistics snapshot for every category
+    pub async fn get_latest_category_stats(&self) -> Result<Vec<CategoryStatsRecord>> {
+        let mut conn = self.get_connection().await?;
+
+        let stats = category_stats::table
+            .select(CategoryStatsRecord::as_select())
+            .distinct_on(category_stats::dapp_type)
+            .order((category_stats::dapp_type.asc(), category_stats::computed_at.desc()))
+            .load::<CategoryStatsRecord>(&mut conn)
+            .await?;
+
+        Ok(stats)
+    }
+
+    /// Get the statistics history of one category, newest first
+    pub async fn get_category_stats_history(
+        &self,
+        dapp_type: &str,
+        limit: i64,
+    ) -> Result<Vec<CategoryStatsRecord>> {
+        let mut conn = self.get_connection().await?;
+
+        let stats = category_stats::table
+            .filter(category_stats::dapp_type.eq(dapp_type))
+            .select(CategoryStatsRecord::as_select())
+            .order(category_stats::computed_at.desc())
+            .limit(limit)
+            .load::<CategoryStatsRecord>(&mut conn)
+            .await?;
+
+        Ok(stats)
+    }
+
+    /// Replace the per-package statistics with the latest 1h snapshot
+    #[instrument(skip_all, fields(rows = stats.len()))]
+    pub async fn save_package_stats(&self, stats: &[DAppPackageStats]) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+
+        let records: Vec<DAppPackageStatsRecord> = stats.iter().map(|package| DAppPackageStatsRecord {
+            package_id: package.package_id.clone(),
+            dapp_name: package.dapp_name.clone(),
+            dapp_type: package.dapp_type.clone(),
+            active_users: package.active_users as i32,
+            tx_count: package.transaction_count as i32,
+            interaction_count: package.interaction_count as i32,
+            last_update: package.last_update.naive_utc(),
+            avg_gas_per_tx: package.avg_gas_per_tx,
+        }).collect();
+
+        // Packages without activity in the window disappear
+        diesel::delete(dapp_package_stats::table)
+            .execute(&mut conn)
+            .await?;
+
+        if !records.is_empty() {
+            diesel::insert_into(dapp_package_stats::table)
+                .values(&records)
+                .execute(&mut conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the per-package statistics of one DApp, most active package first
+    pub async fn get_package_stats(&self, dapp_name: &str) -> Result<Vec<DAppPackageStatsRecord>> {
+        let mut conn = self.get_connection().await?;
+
+        let stats = dapp_package_stats::table
+            .filter(dapp_package_stats::dapp_name.eq(dapp_name))
+            .select(DAppPackageStatsRecord::as_select())
+            .order((dapp_package_stats::active_users.desc(), dapp_package_stats::package_id.asc()))
+            .load::<DAppPackageStatsRecord>(&mut conn)
+            .await?;
+
+        Ok(stats)
+    }
+
+    /// Replace the per-event-type counts with the latest 24h snapshot
+    #[instrument(skip_all, fields(rows = stats.len()))]
+    pub async fn save_event_stats(&self, stats: &[DAppEventStats]) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+
+        let records: Vec<DAppEventStatsRecord> = stats.iter().map(|event| DAppEventStatsRecord {
+            dapp_name: event.dapp_name.clone(),
+            event_type: event.event_type.clone(),
+            count_24h: event.count_24h as i64,
+            last_update: event.last_update.naive_utc(),
+        }).collect();
+
+        diesel::delete(dapp_event_stats::table)
+            .execute(&mut conn)
+            .await?;
+
+        for chunk in records.chunks(insert_chunk_size(4)) {
+            diesel::insert_into(dapp_event_stats::table)
+                .values(chunk)
+                .execute(&mut conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Insert or update the stats of the epochs still in memory
+    /// Rows of earlier epochs keep their final counts
+    #[instrument(skip_all, fields(rows = stats.len()))]
+    pub async fn save_epoch_stats(&self, stats: &[DAppEpochStats]) -> Result<()> {
+        if stats.is_empty() {
+            return Ok(());
+        }
+
+        let mut conn = self.get_connection().await?;
+
+        let records: Vec<EpochStatsRecord> = stats.iter().map(|stats| EpochStatsRecord {
+            epoch: stats.epoch as i64,
+            dapp_name: stats.dapp_name.clone(),
+            active_users: stats.active_users as i32,
+            transaction_count: stats.transaction_count as i32,
+            observed_from: stats.observed_from.naive_utc(),
+            last_update: stats.last_update.naive_utc(),
+        }).collect();
+
+        for chunk in records.chunks(insert_chunk_size(6)) {
+            diesel::insert_into(epoch_stats::table)
+                .values(chunk)
+                .on_conflict((epoch_stats::epoch, epoch_stats::dapp_name))
+                .do_update()
+                .set((
+                    epoch_stats::active_users.eq(excluded(epoch_stats::active_users)),
+                    epoch_stats::transaction_count.eq(excluded(epoch_stats::transaction_count)),
+                    epoch_stats::observed_from.eq(excluded(epoch_stats::observed_from)),
+                    epoch_stats::last_update.eq(excluded(epoch_stats::last_update)),
+                ))
+                .execute(&mut conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Replace the stored heat map with the current hourly activity
+    #[instrument(skip_all, fields(rows = activity.len()))]
+    pub async fn save_hourly_activity(&self, activity: &[DAppHourlyActivity]) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+
+        let records: Vec<DAppHourlyActivityRecord> = activity.iter().map(|activity| DAppHourlyActivityRecord {
+            dapp_name: activity.dapp_name.clone(),
+            hour_of_day: activity.hour_of_day as i32,
+            unique_users: activity.unique_users as i32,
+            observed_from: activity.observed_from.naive_utc(),
+            last_update: activity.last_update.naive_utc(),
+        }).collect();
+
+        diesel::delete(dapp_hourly_activity::table)
+            .execute(&mut conn)
+            .await?;
+
+        for chunk in records.chunks(insert_chunk_size(5)) {
+            diesel::insert_into(dapp_hourly_activity::table)
+                .values(chunk)
+                .execute(&mut conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Replace the stored NFT stats with the current ones
+    #[instrument(skip_all, fields(rows = stats.len()))]
+    pub async fn save_nft_stats(&self, stats: &[DAppNftStats]) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+
+        let records: Vec<DAppNftStatsRecord> = stats.iter().map(|stats| DAppNftStatsRecord {
+            dapp_name: stats.dapp_name.clone(),
+            mints_24h: stats.mints_24h as i64,
+            unique_minters_24h: stats.unique_minters_24h as i32,
+            transfers_24h: stats.transfers_24h as i64,
+            last_update: stats.last_update.naive_utc(),
+        }).collect();
+
+        diesel::delete(dapp_nft_stats::table)
+            .execute(&mut conn)
+            .await?;
+
+        for chunk in records.chunks(insert_chunk_size(5)) {
+            diesel::insert_into(dapp_nft_stats::table)
+                .values(chunk)
+                .execute(&mut conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the NFT stats of one DApp, None if it had no mints or transfers in the last 24h
+    pub async fn get_nft_stats(&self, dapp_name: &str) -> Result<Option<DAppNftStatsRecord>> {
+        let mut conn = self.get_connection().await?;
+
+        let stats = dapp_nft_stats::table
+            .filter(dapp_nft_stats::dapp_name.eq(dapp_name))
+            .select(DAppNftStatsRecord::as_select())
+            .first::<DAppNftStatsRecord>(&mut conn)
+            .await
+            .optional()?;
+
+        Ok(stats)
+    }
+
+    /// Replace the stored lending stats with the current ones
+    #[instrument(skip_all, fields(rows = stats.len()))]
+    pub async fn save_lending_stats(&self, stats: &[DAppLendingStats]) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+
+        let records: Vec<DAppLendingStatsRecord> = stats.iter().map(|stats| DAppLendingStatsRecord {
+            dapp_name: stats.dapp_name.clone(),
+            deposits_24h: stats.deposits_24h as i64,
+            withdrawals_24h: stats.withdrawals_24h as i64,
+            borrows_24h: stats.borrows_24h as i64,
+            repays_24h: stats.repays_24h as i64,
+            unique_depositors_24h: stats.unique_depositors_24h as i32,
+            unique_borrowers_24h: stats.unique_borrowers_24h as i32,
+            deposit_volume_24h_usd: stats.deposit_volume_24h_usd,
+            borrow_volume_24h_usd: stats.borrow_volume_24h_usd,
+            last_update: stats.last_update.naive_utc(),
+        }).collect();
+
+        diesel::delete(dapp_lending_stats::table)
+            .execute(&mut conn)
+            .await?;
+
+        for chunk in records.chunks(insert_chunk_size(10)) {
+            diesel::insert_into(dapp_lending_stats::table)
+                .values(chunk)
+                .execute(&mut conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the lending stats of one DApp, None if it had no lending actions in the last 24h
+    pub async fn get_lending_stats(&self, dapp_name: &str) -> Result<Option<DAppLendingStatsRecord>> {
+        let mut conn = self.get_connection().await?;
+
+        let stats = dapp_lending_stats::table
+            .filter(dapp_lending_stats::dapp_name.eq(dapp_name))
+            .select(DAppLendingStatsRecord::as_select())
+            .first::<DAppLendingStatsRecord>(&mut conn)
+            .await
+            .optional()?;
+
+        Ok(stats)
+    }
+
+    /// Replace the stored bridge stats with the current ones
+    #[instrument(skip_all, fields(rows = stats.len()))]
+    pub async fn save_bridge_stats(&self, stats: &[BridgeStats]) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+
+        let records: Vec<BridgeStatsRecord> = stats.iter().map(|stats| BridgeStatsRecord {
+            dapp_name: stats.dapp_name.clone(),
+            inbound_transfers_24h: stats.inbound_transfers_24h as i64,
+            outbound_transfers_24h: stats.outbound_transfers_24h as i64,
+            inbound_volume_24h_usd: stats.inbound_volume_24h_usd,
+            outbound_volume_24h_usd: stats.outbound_volume_24h_usd,
+            last_update: stats.last_update.naive_utc(),
+        }).collect();
+
+        diesel::delete(bridge_stats::table)
+            .execute(&mut conn)
+            .await?;
+
+        for chunk in records.chunks(insert_chunk_size(6)) {
+            diesel::insert_into(bridge_stats::table)
+                .values(chunk)
+                .execute(&mut conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the bridge stats of one DApp, None if it had no transfers in the last 24h
+    pub async fn get_bridge_stats(&self, dapp_name: &str) -> Result<Option<BridgeStatsRecord>> {
+        let mut conn = self.get_connection().await?;
+
+        let stats = bridge_stats::table
+            .filter(bridge_stats::dapp_name.eq(dapp_name))
+            .select(BridgeStatsRecord::as_select())
+            .first::<BridgeStatsRecord>(&mut conn)
+            .await
+            .optional()?;
+
+        Ok(stats)
+    }
+
+    /// Get the heat map of one DApp, by hour of day
+    pub async fn get_hourly_activity(&self, dapp_name: &str) -> Result<Vec<DAppHourlyActivityRecord>> {
+        let mut conn = self.get_connection().await?;
+
+        let activity = dapp_hourly_activity::table
+            .filter(dapp_hourly_activity::dapp_name.eq(dapp_name))
+            .select(DAppHourlyActivityRecord::as_select())
+            .order(dapp_hourly_activity::hour_of_day.asc())
+            .load::<DAppHourlyActivityRecord>(&mut conn)
+            .await?;
+
+        Ok(activity)
+    }
+
+    /// Get the stats of every DApp in `epoch`, or in the latest recorded epoch,
+    /// most active users first
+    pub async fn get_epoch_stats(&self, epoch: Option<i64>) -> Result<Vec<EpochStatsRecord>> {
+        let mut conn = self.get_connection().await?;
+
+        let epoch = match epoch {
+            Some(epoch) => epoch,
+            None => match epoch_stats::table
+                .select(diesel::dsl::max(epoch_stats::epoch))
+                .first::<Option<i64>>(&mut conn)
+                .await?
+            {
+                Some(epoch) => epoch,
+                None => return Ok(Vec::new()),
+            },
+        };
+
+        let stats = epoch_stats::table
+            .filter(epoch_stats::epoch.eq(epoch))
+            .select(EpochStatsRecord::as_select())
+            .order((epoch_stats::active_users.desc(), epoch_stats::dapp_name.asc()))
+            .load::<EpochStatsRecord>(&mut conn)
+            .await?;
+
+        Ok(stats)
+    }
+
+    /// Get the 24h event type counts of one DApp, most frequent first
+    pub async fn get_event_stats(&self, dapp_name: &str) -> Result<Vec<DAppEventStatsRecord>> {
+        let mut conn = self.get_connection().await?;
+
+        let stats = dapp_event_stats::table
+            .filter(dapp_event_stats::dapp_name.eq(dapp_name))
+            .select(DAppEventStatsRecord::as_select())
+            .order((dapp_event_stats::count_24h.desc(), dapp_event_stats::event_type.asc()))
+            .load::<DAppEventStatsRecord>(&mut conn)
+            .await?;
+
+        Ok(stats)
+    }
+
+    /// Replace the most called functions with the latest 24h snapshot
+    #[instrument(skip_all, fields(rows = stats.len()))]
+    pub async fn save_function_stats(&self, stats: &[DAppFunctionStats]) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+
+        let records: Vec<DAppFunctionStatsRecord> = stats.iter().map(|function| DAppFunctionStatsRecord {
+            dapp_name: function.dapp_name.clone(),
+            function_name: function.function.clone(),
+            call_count_24h: function.call_count_24h as i64,
+            rank_position: function.rank as i32,
+            last_update: function.last_update.naive_utc(),
+        }).collect();
+
+        diesel::delete(dapp_function_stats::table)
+            .execute(&mut conn)
+            .await?;
+
+        for chunk in records.chunks(insert_chunk_size(5)) {
+            diesel::insert_into(dapp_function_stats::table)
+                .values(chunk)
+                .execute(&mut conn)
+                .await?;
         }
 
         Ok(())
     }
-} 
\ No newline at end of file
+
+    /// Get the most called functions of one DApp, most called first
+    pub async fn get_function_stats(&self, dapp_name: &str) -> Result<Vec<DAppFunctionStatsRecord>> {
+        let mut conn = self.get_connection().await?;
+
+        let stats = dapp_function_stats::table
+            .filter(dapp_function_stats::dapp_name.eq(dapp_name))
+            .select(DAppFunctionStatsRecord::as_select())
+            .order(dapp_function_stats::rank_position.asc())
+            .load::<DAppFunctionStatsRecord>(&mut conn)
+            .await?;
+
+        Ok(stats)
+    }
+
+    /// Insert or replace the metadata of a DApp
+    #[instrument(skip_all, fields(dapp = %metadata.dapp_name))]
+    pub async fn save_dapp_metadata(&self, metadata: &DAppMetadataRecord) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+
+        diesel::insert_into(dapp_metadata::table)
+            .values(metadata)
+            .on_conflict(dapp_metadata::dapp_name)
+            .do_update()
+            .set((
+                dapp_metadata::website_url.eq(&metadata.website_url),
+                dapp_metadata::icon_url.eq(&metadata.icon_url),
+                dapp_metadata::twitter.eq(&metadata.twitter),
+                dapp_metadata::last_update.eq(metadata.last_update),
+            ))
+            .execute(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Get the metadata of every DApp that has some
+    pub async fn get_all_dapp_metadata(&self) -> Result<Vec<DAppMetadataRecord>> {
+        let mut conn = self.get_connection().await?;
+
+        let metadata = dapp_metadata::table
+            .select(DAppMetadataRecord::as_select())
+            .order(dapp_metadata::dapp_name.asc())
+            .load::<DAppMetadataRecord>(&mut conn)
+            .await?;
+
+        Ok(metadata)
+    }
+
+    /// Get the metadata of the given DApps; DApps without metadata are missing from the result
+    pub async fn get_dapp_metadata(&self, dapp_names: &[String]) -> Result<Vec<DAppMetadataRecord>> {
+        if dapp_names.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut conn = self.get_connection().await?;
+
+        let metadata = dapp_metadata::table
+            .filter(dapp_metadata::dapp_name.eq_any(dapp_names))
+            .select(DAppMetadataRecord::as_select())
+            .load::<DAppMetadataRecord>(&mut conn)
+            .await?;
+
+        Ok(metadata)
+    }
+
+    /// Record packages attributed to a DApp through their publisher
+    /// Packages that are already known keep their original attribution
+    #[instrument(skip_all, fields(rows = packages.len()))]
+    pub async fn save_published_packages(&self, packages: &[DAppPublishedPackage]) -> Result<()> {
+        if packages.is_empty() {
+            return Ok(());
+        }
+
+        let mut conn = self.get_connection().await?;
+
+        diesel::insert_into(dapp_published_packages::table)
+            .values(packages)
+            .on_conflict_do_nothing()
+            .execute(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Insert or replace the admin registry change of a package
+    pub async fn save_registry_entry(&self, entry: &DAppRegistryRecord) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+
+        diesel::insert_into(dapp_registry::table)
+            .values(entry)
+            .on_conflict(dapp_registry::package_id)
+            .do_update()
+            .set((
+                dapp_registry::dapp_name.eq(&entry.dapp_name),
+                dapp_registry::dapp_type.eq(&entry.dapp_type),
+                dapp_registry::active.eq(entry.active),
+                dapp_registry::updated_at.eq(entry.updated_at),
+            ))
+            .execute(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Get every admin registry change, oldest first
+    pub async fn get_registry_entries(&self) -> Result<Vec<DAppRegistryRecord>> {
+        let mut conn = self.get_connection().await?;
+
+        let entries = dapp_registry::table
+            .select(DAppRegistryRecord::as_select())
+            .order(dapp_registry::updated_at.asc())
+            .load::<DAppRegistryRecord>(&mut conn)
+            .await?;
+
+        Ok(entries)
+    }
+
+    /// Get every package attributed to a DApp through its publisher, oldest first
+    pub async fn get_published_packages(&self) -> Result<Vec<DAppPublishedPackage>> {
+        let mut conn = self.get_connection().await?;
+
+        let packages = dapp_published_packages::table
+            .select(DAppPublishedPackage::as_select())
+            .order(dapp_published_packages::discovered_at.asc())
+            .load::<DAppPublishedPackage>(&mut conn)
+            .await?;
+
+        Ok(packages)
+    }
+
+    /// Append a snapshot of the current rankings to the ranking history
+    #[instrument(skip_all, fields(rows = rankings.len()))]
+    pub async fn save_ranking_history(&self, rankings: &[DAppRanking]) -> Result<()> {
+        if rankings.is_empty() {
+            return Ok(());
+        }
+
+        let mut conn = self.get_connection().await?;
+        let recorded_at = Utc::now().naive_utc();
+
+        let records: Vec<NewDAppRankingHistoryRecord> = rankings.iter().map(|ranking| NewDAppRankingHistoryRecord {
+            dapp_name: ranking.dapp_name.clone(),
+            dapp_type: ranking.dapp_type.clone(),
+            rank_position: ranking.rank as i32,
+            active_users: ranking.active_users as i32,
+            tx_count: ranking.tx_count as i32,
+            gas_used: ranking.gas_used as i64,
+            score: ranking.score,
+            recorded_at,
+        }).collect();
+
+        diesel::insert_into(dapp_ranking_history::table)
+            .values(&records)
+            .execute(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Ranking snapshots of one DApp recorded in [from, to], oldest first
+    pub async fn get_ranking_history(
+        &self,
+        dapp_name: &str,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+        limit: i64,
+    ) -> Result<Vec<DAppRankingHistoryRecord>> {
+        let mut conn = self.get_connection().await?;
+
+        let history = dapp_ranking_history::table
+            .filter(dapp_ranking_history::dapp_name.eq(dapp_name))
+            .filter(dapp_ranking_history::recorded_at.between(from, to))
+            .select(DAppRankingHistoryRecord::as_select())
+            .order(dapp_ranking_history::recorded_at.asc())
+            .limit(limit)
+            .load::<DAppRankingHistoryRecord>(&mut conn)
+            .await?;
+
+        Ok(history)
+    }
+
+    /// Replace the stored top users for every (day, DApp) present in `top_users`
+    #[instrument(skip_all, fields(rows = top_users.len()))]
+    pub async fn save_top_users(&self, top_users: &[DAppTopUser]) -> Result<()> {
+        if top_users.is_empty() {
+            return Ok(());
+        }
+
+        let mut conn = self.get_connection().await?;
+
+        let records: Vec<NewDAppTopUserRecord> = top_users.iter().map(|user| NewDAppTopUserRecord {
+            day: user.day,
+            dapp_name: user.dapp_name.clone(),
+            sender: user.sender.clone(),
+            interaction_count: user.interaction_count as i64,
+            rank_position: user.rank as i32,
+        }).collect();
+
+        let mut days: Vec<_> = records.iter().map(|record| record.day).collect();
+        days.sort();
+        days.dedup();
+
+        // Delete previous snapshots for these days so senders that dropped out of the top N disappear
+        diesel::delete(dapp_top_users::table.filter(dapp_top_users::day.eq_any(days)))
+            .execute(&mut conn)
+            .await?;
+
+        diesel::insert_into(dapp_top_users::table)
+            .values(&records)
+            .execute(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Get the most active senders of a DApp for the latest recorded day
+    pub async fn get_top_users(&self, dapp_name: &str, limit: i64) -> Result<Vec<DAppTopUserRecord>> {
+        let mut conn = self.get_connection().await?;
+
+        let latest_day = dapp_top_users::table
+            .filter(dapp_top_users::dapp_name.eq(dapp_name))
+            .select(diesel::dsl::max(dapp_top_users::day))
+            .first::<Option<chrono::NaiveDate>>(&mut conn)
+            .await?;
+
+        let Some(latest_day) = latest_day else {
+            return Ok(Vec::new());
+        };
+
+        let top_users = dapp_top_users::table
+            .filter(dapp_top_users::dapp_name.eq(dapp_name))
+            .filter(dapp_top_users::day.eq(latest_day))
+            .select(DAppTopUserRecord::as_select())
+            .order(dapp_top_users::rank_position.asc())
+            .limit(limit)
+            .load::<DAppTopUserRecord>(&mut conn)
+            .await?;
+
+        Ok(top_users)
+    }
+
+    /// Get the most active senders of every DApp for the latest recorded day, by DApp then rank
+    pub async fn get_latest_top_users(&self) -> Result<Vec<DAppTopUserRecord>> {
+        let mut conn = self.get_connection().await?;
+
+        let latest_day = dapp_top_users::table
+            .select(diesel::dsl::max(dapp_top_users::day))
+            .first::<Option<chrono::NaiveDate>>(&mut conn)
+            .await?;
+
+        let Some(latest_day) = latest_day else {
+            return Ok(Vec::new());
+        };
+
+        let top_users = dapp_top_users::table
+            .filter(dapp_top_users::day.eq(latest_day))
+            .select(DAppTopUserRecord::as_select())
+            .order((dapp_top_users::dapp_name.asc(), dapp_top_users::rank_position.asc()))
+            .load::<DAppTopUserRecord>(&mut conn)
+            .await?;
+
+        Ok(top_users)
+    }
+
+    /// Record the first time each (DApp, sender) pair was seen
+    /// Pairs that are already known keep their original first_seen timestamp
+    #[instrument(skip_all, fields(rows = entries.len()))]
+    pub async fn record_first_seen(&self, entries: &[NewDAppUserFirstSeen]) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+
+        for chunk in entries.chunks(insert_chunk_size(3)) {
+            diesel::insert_into(dapp_user_first_seen::table)
+                .values(chunk)
+                .on_conflict_do_nothing()
+                .execute(&mut conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Split the given active (DApp, sender) pairs into new and returning users
+    /// A user is new when first seen at or after `since`
+    ///
+    /// # Returns
+    /// * dapp_name -> (new_users, returning_users)
+    pub async fn count_new_and_returning_users(
+        &self,
+        dapp_names: Vec<String>,
+        senders: Vec<String>,
+        since: NaiveDateTime,
+    ) -> Result<HashMap<String, (u32, u32)>> {
+        let mut conn = self.get_connection().await?;
+
+        let rows = sql_query(
+            "SELECT f.dapp_name, \
+                    COUNT(*) FILTER (WHERE f.first_seen >= $3) AS new_users, \
+                    COUNT(*) FILTER (WHERE f.first_seen < $3) AS returning_users \
+             FROM dapp_user_first_seen f \
+             JOIN UNNEST($1::varchar[], $2::varchar[]) AS w(dapp_name, sender) \
+               ON f.dapp_name = w.dapp_name AND f.sender = w.sender \
+             GROUP BY f.dapp_name",
+        )
+        .bind::<Array<Text>, _>(dapp_names)
+        .bind::<Array<Text>, _>(senders)
+        .bind::<Timestamp, _>(since)
+        .load::<UserBreakdownRow>(&mut conn)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.dapp_name, (row.new_users as u32, row.returning_users as u32)))
+            .collect())
+    }
+
+    /// Create the daily partitions of dapp_interactions for `days` that do not exist yet
+    /// Partitions span UTC days, whatever the time zone of the session
+    pub async fn create_interaction_partitions(&self, days: &[NaiveDate]) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+
+        for day in days {
+            let next_day = *day + chrono::Days::new(1);
+            sql_query(format!(
+                "CREATE TABLE IF NOT EXISTS {} PARTITION OF dapp_interactions \
+                 FOR VALUES FROM ('{} 00:00:00+00') TO ('{} 00:00:00+00')",
+                interaction_partition_name(*day),
+                day.format("%Y-%m-%d"),
+                next_day.format("%Y-%m-%d"),
+            ))
+            .execute(&mut conn)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Drop the daily partitions of dapp_interactions before `day`
+    ///
+    /// # Returns
+    /// * Names of the dropped partitions
+    pub async fn drop_interaction_partitions_before(&self, day: NaiveDate) -> Result<Vec<String>> {
+        #[derive(QueryableByName)]
+        struct PartitionRow {
+            #[diesel(sql_type = Text)]
+            name: String,
+        }
+
+        let mut conn = self.get_connection().await?;
+        let partitions = sql_query(
+            "SELECT child.relname::text AS name \
+             FROM pg_inherits \
+             JOIN pg_class parent ON parent.oid = pg_inherits.inhparent \
+             JOIN pg_class child ON child.oid = pg_inherits.inhrelid \
+             WHERE parent.relname = 'dapp_interactions'",
+        )
+        .load::<PartitionRow>(&mut conn)
+        .await?;
+
+        let cutoff = interaction_partition_name(day);
+        let mut dropped = Vec::new();
+        // Partition names sort by day, and only daily partitions are considered
+        for partition in partitions {
+            let is_daily = partition.name.len() == cutoff.len()
+                && partition.name.starts_with("dapp_interactions_")
+                && partition.name["dapp_interactions_".len()..].bytes().all(|byte| byte.is_ascii_digit());
+            if is_daily && partition.name < cutoff {
+                sql_query(format!("DROP TABLE IF EXISTS {}", partition.name)).execute(&mut conn).await?;
+                dropped.push(partition.name);
+            }
+        }
+        Ok(dropped)
+    }
+
+    /// Insert raw interactions; interactions already stored are ignored
+    /// The partitions of their days must exist
+    #[instrument(skip_all, fields(rows = interactions.len()))]
+    pub async fn save_interactions(&self, interactions: &[NewDAppInteractionRecord]) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+
+        for chunk in interactions.chunks(INTERACTION_CHUNK_SIZE) {
+            diesel::insert_into(dapp_interactions::table)
+                .values(chunk)
+                .on_conflict_do_nothing()
+                .execute(&mut conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Unranked rankings of the `window` before `now`, derived from the persisted interactions
+    /// Active users are the distinct senders of a DApp, sponsored users those with a
+    /// sponsored interaction; transactions and their gas are counted once per transaction. Rank, score and category are left to the caller
+    #[instrument(skip_all, fields(window_seconds = window.as_secs()))]
+    pub async fn recompute_rankings(&self, window: Duration, now: DateTime<Utc>) -> Result<Vec<DAppRanking>> {
+        let mut conn = self.get_connection().await?;
+        let since = now - chrono::Duration::from_std(window)?;
+
+        let rows = sql_query(
+            "SELECT users.dapp_name, users.package_id, users.active_users, users.sponsored_users, \
+                    transactions.tx_count, transactions.gas_used, \
+                    distribution.interactions_p50, distribution.interactions_p90, distribution.interactions_p99, \
+                    distribution.top_users_interaction_pct \
+             FROM ( \
+                 SELECT dapp_name, MIN(package_id) AS package_id, COUNT(DISTINCT sender) AS active_users, \
+                        COUNT(DISTINCT sender) FILTER (WHERE sponsored) AS sponsored_users \
+                 FROM dapp_interactions \
+                 WHERE dapp_name IS NOT NULL AND timestamp >= $1 \
+                 GROUP BY dapp_name \
+             ) users \
+             JOIN ( \
+                 SELECT dapp_name, COUNT(*) AS tx_count, SUM(gas_used)::bigint AS gas_used \
+                 FROM ( \
+                     SELECT DISTINCT dapp_name, transaction_digest, gas_used \
+                     FROM dapp_interactions \
+                     WHERE dapp_name IS NOT NULL AND timestamp >= $1 \
+                 ) distinct_transactions \
+                 GROUP BY dapp_name \
+             ) transactions USING (dapp_name) \
+             JOIN ( \
+                 SELECT dapp_name, \
+                        percentile_disc(0.5) WITHIN GROUP (ORDER BY interactions) AS interactions_p50, \
+                        percentile_disc(0.9) WITHIN GROUP (ORDER BY interactions) AS interactions_p90, \
+                        percentile_disc(0.99) WITHIN GROUP (ORDER BY interactions) AS interactions_p99, \
+                        (100.0 * SUM(interactions) FILTER (WHERE position <= CEIL(users * $2 / 100.0)) \
+                            / SUM(interactions))::double precision AS top_users_interaction_pct \
+                 FROM ( \
+                     SELECT dapp_name, COUNT(*) AS interactions, \
+                            ROW_NUMBER() OVER (PARTITION BY dapp_name ORDER BY COUNT(*) DESC) AS position, \
+                            COUNT(*) OVER (PARTITION BY dapp_name) AS users \
+                     FROM dapp_interactions \
+                     WHERE dapp_name IS NOT NULL AND timestamp >= $1 \
+                     GROUP BY dapp_name, sender \
+                 ) per_user \
+                 GROUP BY dapp_name \
+             ) distribution USING (dapp_name)",
+        )
+        .bind::<Timestamptz, _>(since)
+        .bind::<BigInt, _>(TOP_USERS_PERCENT as i64)
+        .load::<WindowActivityRow>(&mut conn)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| DAppRanking {
+                rank: 0,
+                package_id: row.package_id,
+                dapp_name: row.dapp_name,
+                active_users: row.active_users as u32,
+                filtered_users: 0,
+                last_update: now,
+                dapp_type: "Unknown".to_string(),
+                tracking_status: TRACKING_ACTIVE.to_string(),
+                new_users_24h: 0,
+                returning_users_24h: 0,
+                tx_count: row.tx_count as u32,
+                gas_used: row.gas_used as u64,
+                score: 0.0,
+                error_rate_24h: 0.0,
+                dau_smoothed: row.active_users as f64,
+                sponsored_user_pct: if row.active_users > 0 {
+                    row.sponsored_users as f64 * 100.0 / row.active_users as f64
+                } else {
+                    0.0
+                },
+                avg_gas_per_tx: if row.tx_count > 0 { row.gas_used as f64 / row.tx_count as f64 } else { 0.0 },
+                interactions_p50: row.interactions_p50 as u32,
+                interactions_p90: row.interactions_p90 as u32,
+                interactions_p99: row.interactions_p99 as u32,
+                top_users_interaction_pct: row.top_users_interaction_pct,
+                // Swap and lending volumes are decoded from checkpoints, not from stored interactions
+                volume_24h_usd: 0.0,
+                deposit_volume_24h_usd: 0.0,
+                borrow_volume_24h_usd: 0.0,
+            })
+            .collect())
+    }
+
+    /// Record (day, DApp, sender) activity; pairs already recorded for the day are ignored
+    #[instrument(skip_all, fields(rows = entries.len()))]
+    pub async fn record_daily_activity(&self, entries: &[NewDAppUserActivity]) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+
+        for chunk in entries.chunks(insert_chunk_size(3)) {
+            diesel::insert_into(dapp_user_activity::table)
+                .values(chunk)
+                .on_conflict_do_nothing()
+                .execute(&mut conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Delete the (day, DApp, sender) activity of the days before `before`
+    ///
+    /// # Returns
+    /// * Number of rows deleted
+    pub async fn prune_daily_activity(&self, before: NaiveDate) -> Result<usize> {
+        let mut conn = self.get_connection().await?;
+
+        let deleted = diesel::delete(dapp_user_activity::table.filter(dapp_user_activity::day.lt(before)))
+            .execute(&mut conn)
+            .await?;
+
+        Ok(deleted)
+    }
+
+    /// Merge the senders of `entries` into the unique-user sketches of their (day, DApp),
+    /// stamping the merged sketches with `now`
+    /// Merging is idempotent, so entries already merged may be merged again
+    #[instrument(skip_all, fields(rows = entries.len()))]
+    pub async fn merge_daily_user_sketches(&self, entries: &[NewDAppUserActivity], now: NaiveDateTime) -> Result<()> {
+        let mut sketches = daily_sketches(entries);
+        if sketches.is_empty() {
+            return Ok(());
+        }
+        let mut conn = self.get_connection().await?;
+
+        let days: Vec<NaiveDate> = sketches.keys().map(|(day, _)| *day).collect();
+        let dapp_names: Vec<&str> = sketches.keys().map(|(_, dapp_name)| dapp_name.as_str()).collect();
+        let stored = daily_user_sketches::table
+            .filter(daily_user_sketches::day.eq_any(&days))
+            .filter(daily_user_sketches::dapp_name.eq_any(&dapp_names))
+            .select(DailyUserSketchRecord::as_select())
+            .load::<DailyUserSketchRecord>(&mut conn)
+            .await?;
+        for record in stored {
+            if let Some(sketch) = sketches.get_mut(&(record.day, record.dapp_name)) {
+                sketch.merge(&UserSketch::from_bytes(&record.sketch)?);
+            }
+        }
+
+        let records: Vec<DailyUserSketchRecord> = sketches
+            .into_iter()
+            .map(|((day, dapp_name), sketch)| DailyUserSketchRecord { day, dapp_name, sketch: sketch.to_bytes(), updated_at: now })
+            .collect();
+        for chunk in records.chunks(insert_chunk_size(4)) {
+            diesel::insert_into(daily_user_sketches::table)
+                .values(chunk)
+                .on_conflict((daily_user_sketches::day, daily_user_sketches::dapp_name))
+                .do_update()
+                .set((
+                    daily_user_sketches::sketch.eq(excluded(daily_user_sketches::sketch)),
+                    daily_user_sketches::updated_at.eq(excluded(daily_user_sketches::updated_at)),
+                ))
+                .execute(&mut conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the unique-user sketches of a DApp for the days from `from` to `to`, both included
+    pub async fn get_daily_user_sketches(&self, dapp_name: &str, from: NaiveDate, to: NaiveDate) -> Result<Vec<DailyUserSketchRecord>> {
+        let mut conn = self.get_connection().await?;
+
+        let sketches = daily_user_sketches::table
+            .filter(daily_user_sketches::dapp_name.eq(dapp_name))
+            .filter(daily_user_sketches::day.between(from, to))
+            .select(DailyUserSketchRecord::as_select())
+            .order(daily_user_sketches::day.asc())
+            .load::<DailyUserSketchRecord>(&mut conn)
+            .await?;
+
+        Ok(sketches)
+    }
+
+    /// Recompute retention of every daily cohort first seen in [from_day, today)
+    /// A Dn rate is only filled in once cohort_day + n has fully elapsed before `today`
+    ///
+    /// # Returns
+    /// * Number of (cohort_day, DApp) rows written
+    pub async fn compute_retention(&self, from_day: NaiveDate, today: NaiveDate, timezone: Tz) -> Result<usize> {
+        let mut conn = self.get_connection().await?;
+
+        let rows = sql_query(
+            "INSERT INTO dapp_retention \
+                (cohort_day, dapp_name, cohort_size, d1_returned, d7_returned, d30_returned, \
+                 d1_rate, d7_rate, d30_rate, computed_at) \
+             SELECT c.cohort_day, c.dapp_name, COUNT(*)::int, \
+                    COUNT(a1.sender)::int, COUNT(a7.sender)::int, COUNT(a30.sender)::int, \
+                    CASE WHEN c.cohort_day + 1 < $2 THEN COUNT(a1.sender)::float8 / COUNT(*) END, \
+                    CASE WHEN c.cohort_day + 7 < $2 THEN COUNT(a7.sender)::float8 / COUNT(*) END, \
+                    CASE WHEN c.cohort_day + 30 < $2 THEN COUNT(a30.sender)::float8 / COUNT(*) END, \
+                    NOW() \
+             FROM ( \
+                 SELECT dapp_name, sender, cohort_day \
+                 FROM ( \
+                     SELECT dapp_name, sender, (first_seen AT TIME ZONE 'UTC' AT TIME ZONE $3)::date AS cohort_day \
+                     FROM dapp_user_first_seen \
+                 ) first_seen_days \
+                 WHERE cohort_day >= $1 AND cohort_day < $2 \
+             ) c \
+             LEFT JOIN dapp_user_activity a1 \
+               ON a1.dapp_name = c.dapp_name AND a1.sender = c.sender AND a1.day = c.cohort_day + 1 \
+             LEFT JOIN dapp_user_activity a7 \
+               ON a7.dapp_name = c.dapp_name AND a7.sender = c.sender AND a7.day = c.cohort_day + 7 \
+             LEFT JOIN dapp_user_activity a30 \
+               ON a30.dapp_name = c.dapp_name AND a30.sender = c.sender AND a30.day = c.cohort_day + 30 \
+             GROUP BY c.cohort_day, c.dapp_name \
+             ON CONFLICT (cohort_day, dapp_name) DO UPDATE SET \
+                cohort_size = EXCLUDED.cohort_size, \
+                d1_returned = EXCLUDED.d1_returned, \
+                d7_returned = EXCLUDED.d7_returned, \
+                d30_returned = EXCLUDED.d30_returned, \
+                d1_rate = EXCLUDED.d1_rate, \
+                d7_rate = EXCLUDED.d7_rate, \
+                d30_rate = EXCLUDED.d30_rate, \
+                computed_at = EXCLUDED.computed_at",
+        )
+        .bind::<Date, _>(from_day)
+        .bind::<Date, _>(today)
+        .bind::<Text, _>(timezone.name())
+        .execute(&mut conn)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// Get the retention history of a DApp, most recent cohort first
+    pub async fn get_retention(&self, dapp_name: &str, limit: i64) -> Result<Vec<DAppRetentionRecord>> {
+        let mut conn = self.get_connection().await?;
+
+        let retention = dapp_retention::table
+            .filter(dapp_retention::dapp_name.eq(dapp_name))
+            .select(DAppRetentionRecord::as_select())
+            .order(dapp_retention::cohort_day.desc())
+            .limit(limit)
+            .load::<DAppRetentionRecord>(&mut conn)
+            .await?;
+
+        Ok(retention)
+    }
+
+    /// Rank DApps by the return rate of their latest cohort for which the window has elapsed
+    pub async fn get_retention_ranking(&self, window: RetentionWindow) -> Result<Vec<DAppRetentionRecord>> {
+        let mut conn = self.get_connection().await?;
+        let rate_column = window.rate_column();
+
+        let query = format!(
+            "SELECT * FROM ( \
+                 SELECT DISTINCT ON (dapp_name) * FROM dapp_retention \
+                 WHERE {rate_column} IS NOT NULL \
+                 ORDER BY dapp_name, cohort_day DESC \
+             ) latest \
+             ORDER BY {rate_column} DESC, dapp_name"
+        );
+        let ranking = sql_query(query)
+            .load::<DAppRetentionRecord>(&mut conn)
+            .await?;
+
+        Ok(ranking)
+    }
+
+    /// Merge the daily sender sets of the last 7 and 30 days up to `today` into each
+    /// DApp's WAU and MAU, and copy them onto its rankings
+    /// Windows start on `wau_from` and `mau_from`; DApps idle since `mau_from` drop to 0
+    ///
+    /// # Returns
+    /// * Number of DApps with active users
+    pub async fn compute_rolling_active_users(&self, wau_from: NaiveDate, mau_from: NaiveDate, today: NaiveDate) -> Result<usize> {
+        let mut conn = self.get_connection().await?;
+
+        let rows = sql_query(
+            "INSERT INTO dapp_active_users (dapp_name, wau, mau, as_of, computed_at) \
+             SELECT dapp_name, (COUNT(DISTINCT sender) FILTER (WHERE day >= $1))::int, \
+                    COUNT(DISTINCT sender)::int, $3, NOW() \
+             FROM dapp_user_activity \
+             WHERE day >= $2 AND day <= $3 \
+             GROUP BY dapp_name \
+             ON CONFLICT (dapp_name) DO UPDATE SET \
+                wau = EXCLUDED.wau, \
+                mau = EXCLUDED.mau, \
+                as_of = EXCLUDED.as_of, \
+                computed_at = EXCLUDED.computed_at",
+        )
+        .bind::<Date, _>(wau_from)
+        .bind::<Date, _>(mau_from)
+        .bind::<Date, _>(today)
+        .execute(&mut conn)
+        .await?;
+
+        sql_query("DELETE FROM dapp_active_users WHERE as_of < $1")
+            .bind::<Date, _>(today)
+            .execute(&mut conn)
+            .await?;
+
+        sql_query(
+            "UPDATE dapp_rankings r SET (wau, mau) = ( \
+                 SELECT COALESCE(MAX(a.wau), 0), COALESCE(MAX(a.mau), 0) \
+                 FROM dapp_active_users a WHERE a.dapp_name = r.dapp_name \
+             )",
+        )
+        .execute(&mut conn)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// Freeze the `top_n` live rankings as the snapshot of the period starting on `period_start`
+    /// Ranks are renumbered without "Others" and paused DApps; an existing snapshot is kept
+    ///
+    /// # Returns
+    /// * The inserted snapshot, best ranked first
+    #[instrument(skip_all, fields(period = period.table_name(), %period_start))]
+    pub async fn save_ranking_snapshot(
+        &self,
+        period: SnapshotPeriod,
+        period_start: NaiveDate,
+        top_n: i64,
+    ) -> Result<Vec<DAppRankingSnapshotRecord>> {
+        let mut conn = self.get_connection().await?;
+        let table = period.table_name();
+
+        let query = format!(
+            "INSERT INTO {table} \
+                (period_start, rank_position, dapp_name, dapp_type, active_users, score, recorded_at) \
+             SELECT $1, ROW_NUMBER() OVER (ORDER BY rank_position)::int, dapp_name, dapp_type, active_users, score, NOW() \
+             FROM dapp_rankings \
+             WHERE window_label = $2 AND dapp_name <> $3 AND tracking_status = $4 \
+             ORDER BY rank_position \
+             LIMIT $5 \
+             ON CONFLICT (period_start, dapp_name) DO NOTHING \
+             RETURNING *"
+        );
+        let mut snapshot = sql_query(query)
+            .bind::<Date, _>(period_start)
+            .bind::<Text, _>(&self.live_window)
+            .bind::<Text, _>(OTHERS_DAPP_NAME)
+            .bind::<Text, _>(TRACKING_ACTIVE)
+            .bind::<BigInt, _>(top_n)
+            .load::<DAppRankingSnapshotRecord>(&mut conn)
+            .await?;
+        snapshot.sort_by_key(|entry| entry.rank_position);
+
+        Ok(snapshot)
+    }
+
+    /// Get the snapshot of the period starting on `period_start`, best ranked first
+    pub async fn get_ranking_snapshot(
+        &self,
+        period: SnapshotPeriod,
+        period_start: NaiveDate,
+    ) -> Result<Vec<DAppRankingSnapshotRecord>> {
+        let mut conn = self.get_connection().await?;
+        let table = period.table_name();
+
+        let snapshot = sql_query(format!("SELECT * FROM {table} WHERE period_start = $1 ORDER BY rank_position"))
+            .bind::<Date, _>(period_start)
+            .load::<DAppRankingSnapshotRecord>(&mut conn)
+            .await?;
+
+        Ok(snapshot)
+    }
+
+    /// Register a webhook subscription
+    pub async fn create_webhook_subscription(&self, subscription: &NewWebhookSubscription) -> Result<WebhookSubscriptionRecord> {
+        let mut conn = self.get_connection().await?;
+
+        let record = diesel::insert_into(webhook_subscriptions::table)
+            .values(subscription)
+            .returning(WebhookSubscriptionRecord::as_returning())
+            .get_result(&mut conn)
+            .await?;
+
+        Ok(record)
+    }
+
+    /// Get the active webhook subscriptions, of one DApp if given, oldest first
+    pub async fn get_webhook_subscriptions(&self, dapp_name: Option<&str>) -> Result<Vec<WebhookSubscriptionRecord>> {
+        let mut conn = self.get_connection().await?;
+
+        let mut query = webhook_subscriptions::table
+            .filter(webhook_subscriptions::active.eq(true))
+            .select(WebhookSubscriptionRecord::as_select())
+            .order(webhook_subscriptions::id.asc())
+            .into_boxed();
+        if let Some(dapp_name) = dapp_name {
+            query = query.filter(webhook_subscriptions::dapp_name.eq(dapp_name));
+        }
+        let subscriptions = query.load::<WebhookSubscriptionRecord>(&mut conn).await?;
+
+        Ok(subscriptions)
+    }
+
+    /// Deactivate a webhook subscription; its pending deliveries are dropped
+    ///
+    /// # Returns
+    /// * false if no active subscription has this id
+    pub async fn deactivate_webhook_subscription(&self, id: i32) -> Result<bool> {
+        let mut conn = self.get_connection().await?;
+
+        let updated = diesel::update(webhook_subscriptions::table)
+            .filter(webhook_subscriptions::id.eq(id))
+            .filter(webhook_subscriptions::active.eq(true))
+            .set(webhook_subscriptions::active.eq(false))
+            .execute(&mut conn)
+            .await?;
+        diesel::update(webhook_deliveries::table)
+            .filter(webhook_deliveries::subscription_id.eq(id))
+            .filter(webhook_deliveries::status.eq(DELIVERY_PENDING))
+            .set(webhook_deliveries::status.eq(DELIVERY_FAILED))
+            .execute(&mut conn)
+            .await?;
+
+        Ok(updated > 0)
+    }
+
+    /// Set the active users a subscription compares against, without notifying
+    pub async fn set_webhook_baseline(&self, id: i32, baseline_users: i32) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+
+        diesel::update(webhook_subscriptions::table)
+            .filter(webhook_subscriptions::id.eq(id))
+            .set(webhook_subscriptions::baseline_users.eq(baseline_users))
+            .execute(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Queue a notification of a subscription and move its baseline to `baseline_users`
+    /// Both happen in one statement, so a notification is never queued twice for the same change
+    pub async fn enqueue_webhook_delivery(&self, delivery: &NewWebhookDelivery, baseline_users: i32) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+
+        sql_query(
+            "WITH updated AS (
+                 UPDATE webhook_subscriptions SET baseline_users = $2 WHERE id = $1 AND active RETURNING id
+             )
+             INSERT INTO webhook_deliveries (subscription_id, payload) SELECT id, $3 FROM updated",
+        )
+        .bind::<Integer, _>(delivery.subscription_id)
+        .bind::<Integer, _>(baseline_users)
+        .bind::<Text, _>(&delivery.payload)
+        .execute(&mut conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Get up to `limit` pending deliveries due at `now` with the URL of their subscription, oldest first
+    pub async fn get_due_webhook_deliveries(&self, now: NaiveDateTime, limit: i64) -> Result<Vec<(WebhookDeliveryRecord, String)>> {
+        let mut conn = self.get_connection().await?;
+
+        let deliveries = webhook_deliveries::table
+            .inner_join(webhook_subscriptions::table)
+            .filter(webhook_deliveries::status.eq(DELIVERY_PENDING))
+            .filter(webhook_deliveries::next_attempt_at.le(now))
+            .select((WebhookDeliveryRecord::as_select(), webhook_subscriptions::url))
+            .order(webhook_deliveries::next_attempt_at.asc())
+            .limit(limit)
+            .load::<(WebhookDeliveryRecord, String)>(&mut conn)
+            .await?;
+
+        Ok(deliveries)
+    }
+
+    /// Record an attempt to deliver a notification
+    /// A failed attempt is retried at `retry_at`, or marks the delivery as failed if None
+    pub async fn record_webhook_attempt(
+        &self,
+        id: i64,
+        response_status: Option<i32>,
+        error: Option<&str>,
+        retry_at: Option<NaiveDateTime>,
+    ) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+        let now = Utc::now().naive_utc();
+        let (status, next_attempt_at, delivered_at) = match (error, retry_at) {
+            (None, _) => (DELIVERY_DELIVERED, now, Some(now)),
+            (Some(_), Some(retry_at)) => (DELIVERY_PENDING, retry_at, None),
+            (Some(_), None) => (DELIVERY_FAILED, now, None),
+        };
+
+        diesel::update(webhook_deliveries::table)
+            .filter(webhook_deliveries::id.eq(id))
+            .set((
+                webhook_deliveries::status.eq(status),
+                webhook_deliveries::attempts.eq(webhook_deliveries::attempts + 1),
+                webhook_deliveries::last_error.eq(error),
+                webhook_deliveries::response_status.eq(response_status),
+                webhook_deliveries::next_attempt_at.eq(next_attempt_at),
+                webhook_deliveries::delivered_at.eq(delivered_at),
+            ))
+            .execute(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Get the latest `limit` deliveries of a subscription, newest first
+    pub async fn get_webhook_deliveries(&self, subscription_id: i32, limit: i64) -> Result<Vec<WebhookDeliveryRecord>> {
+        let mut conn = self.get_connection().await?;
+
+        let deliveries = webhook_deliveries::table
+            .filter(webhook_deliveries::subscription_id.eq(subscription_id))
+            .select(WebhookDeliveryRecord::as_select())
+            .order(webhook_deliveries::created_at.desc())
+            .limit(limit)
+            .load::<WebhookDeliveryRecord>(&mut conn)
+            .await?;
+
+        Ok(deliveries)
+    }
+
+    /// Load the last processed checkpoint of an ingestion task, if any was saved
+    pub async fn load_checkpoint_progress(&self, task_name: &str) -> Result<Option<i64>> {
+        let mut conn = self.get_connection().await?;
+
+        let checkpoint_number = checkpoint_progress::table
+            .filter(checkpoint_progress::task_name.eq(task_name))
+            .select(checkpoint_progress::checkpoint_number)
+            .first::<i64>(&mut conn)
+            .await
+            .optional()?;
+
+        Ok(checkpoint_number)
+    }
+
+    /// Save the last processed checkpoint of an ingestion task
+    #[instrument(skip_all, fields(checkpoint = checkpoint_number))]
+    pub async fn save_checkpoint_progress(&self, task_name: &str, checkpoint_number: i64) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+        let now = Utc::now().naive_utc();
+
+        diesel::insert_into(checkpoint_progress::table)
+            .values((
+                checkpoint_progress::task_name.eq(task_name),
+                checkpoint_progress::checkpoint_number.eq(checkpoint_number),
+                checkpoint_progress::updated_at.eq(now),
+            ))
+            .on_conflict(checkpoint_progress::task_name)
+            .do_update()
+            .set((
+                checkpoint_progress::checkpoint_number.eq(checkpoint_number),
+                checkpoint_progress::updated_at.eq(now),
+            ))
+            .execute(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Name of the dapp_interactions partition holding `day`
+fn interaction_partition_name(day: NaiveDate) -> String {
+    format!("dapp_interactions_{}", day.format("%Y%m%d"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+
+    #[test]
+    fn test_interaction_chunks_stay_under_bind_parameter_limit() {
+        let record = |index: usize| NewDAppInteractionRecord {
+            timestamp: DateTime::UNIX_EPOCH,
+            transaction_digest: format!("tx{index}"),
+            interaction_index: 0,
+            package_id: "0x1".to_string(),
+            dapp_name: Some("Cetus AMM".to_string()),
+            sender: "0xa".to_string(),
+            gas_used: 10,
+            event_type: "pool::SwapEvent".to_string(),
+            sponsored: false,
+        };
+        let interactions: Vec<NewDAppInteractionRecord> = (0..INTERACTION_CHUNK_SIZE * 2 + 1).map(record).collect();
+
+        for chunk in interactions.chunks(INTERACTION_CHUNK_SIZE) {
+            let statement = diesel::insert_into(dapp_interactions::table).values(chunk).on_conflict_do_nothing();
+            let sql = diesel::debug_query::<diesel::pg::Pg, _>(&statement).to_string();
+            let parameters = sql.split(" -- binds").next().unwrap().matches('$').count();
+            assert_eq!(parameters, chunk.len() * 9);
+            assert!(parameters <= MAX_BIND_PARAMETERS, "{parameters} bind parameters in one INSERT");
+        }
+    }
+}
@@ -0,0 +1,232 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Retries for database operations.
+//!
+//! Database operations are retried with exponential backoff and full jitter.
+//! When an operation still fails after the maximum number of attempts the
+//! circuit opens: further operations fail fast until a cooldown has passed,
+//! after which a single operation is let through to probe whether Postgres has
+//! recovered. Broken pooled connections are replaced by the pool on checkout,
+//! so a retried operation reconnects as needed.
+
+use anyhow::{anyhow, Result};
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+
+/// How often and how long to retry a failing database operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts per operation, including the first one
+    pub max_attempts: u32,
+    /// Backoff cap before the first retry; doubled on every further retry
+    pub base_delay: Duration,
+    /// Upper bound of the backoff cap
+    pub max_delay: Duration,
+    /// How long the circuit stays open after an operation exhausted its attempts
+    pub circuit_cooldown: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(10),
+            circuit_cooldown: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Backoff cap before retry number `retry` (0-based)
+    pub fn backoff_cap(&self, retry: u32) -> Duration {
+        let factor = 1u32.checked_shl(retry.min(31)).unwrap_or(u32::MAX);
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+
+    /// Random delay between zero and the backoff cap, so that retries of
+    /// concurrent operations do not hit the database at the same time
+    fn backoff(&self, retry: u32) -> Duration {
+        let cap = self.backoff_cap(retry);
+        if cap.is_zero() {
+            return cap;
+        }
+        cap.mul_f64(random_fraction())
+    }
+}
+
+/// Uniform random number in [0, 1) drawn from the std hasher's random keys
+fn random_fraction() -> f64 {
+    let random = RandomState::new().build_hasher().finish();
+    (random >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Circuit breaker state shared by all operations of a `DatabaseManager`
+#[derive(Debug, Default)]
+struct CircuitState {
+    open_until: Option<Instant>,
+    probing: bool,
+}
+
+/// Retries database operations and stops them while Postgres is unavailable
+#[derive(Debug, Default)]
+pub struct DbRetry {
+    policy: RetryPolicy,
+    circuit: Mutex<CircuitState>,
+}
+
+impl DbRetry {
+    pub fn new(policy: RetryPolicy) -> Self {
+        Self { policy, circuit: Mutex::new(CircuitState::default()) }
+    }
+
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    /// Returns true unless the circuit is open
+    pub fn is_available(&self) -> bool {
+        let circuit = self.circuit.lock().unwrap();
+        match circuit.open_until {
+            Some(open_until) => Instant::now() >= open_until && !circuit.probing,
+            None => true,
+        }
+    }
+
+    /// Run `operation`, retrying failures with jittered exponential backoff
+    /// Fails immediately while the circuit is open
+    pub async fn run<T, F, Fut>(&self, name: &str, mut operation: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let probing = self.enter()?;
+
+        let mut attempt = 1;
+        loop {
+            match operation().await {
+                Ok(value) => {
+                    self.close(probing);
+                    return Ok(value);
+                }
+                // A probe gets a single attempt so a still-broken database keeps the circuit open
+                Err(err) if attempt < self.policy.max_attempts && !probing => {
+                    let delay = self.policy.backoff(attempt - 1);
+                    warn!(
+                        "⚠️ Database operation '{}' failed (attempt {}/{}), retrying in {}ms: {}",
+                        name, attempt, self.policy.max_attempts, delay.as_millis(), err
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(err) => {
+                    self.open();
+                    return Err(err.context(format!(
+                        "Database operation '{}' failed after {} attempt(s)", name, attempt
+                    )));
+                }
+            }
+        }
+    }
+
+    /// Check the circuit before an operation
+    /// Returns whether the operation is the probe after a cooldown
+    fn enter(&self) -> Result<bool> {
+        let mut circuit = self.circuit.lock().unwrap();
+        match circuit.open_until {
+            None => Ok(false),
+            Some(open_until) if Instant::now() >= open_until && !circuit.probing => {
+                circuit.probing = true;
+                Ok(true)
+            }
+            Some(_) => Err(anyhow!("Database circuit is open, skipping operation")),
+        }
+    }
+
+    fn close(&self, probing: bool) {
+        let mut circuit = self.circuit.lock().unwrap();
+        if circuit.open_until.is_some() && (probing || !circuit.probing) {
+            info!("✅ Database recovered, closing circuit");
+            *circuit = CircuitState::default();
+        }
+    }
+
+    fn open(&self) {
+        let mut circuit = self.circuit.lock().unwrap();
+        error!(
+            "❌ Database unavailable, pausing database operations for {}s",
+            self.policy.circuit_cooldown.as_secs()
+        );
+        circuit.open_until = Some(Instant::now() + self.policy.circuit_cooldown);
+        circuit.probing = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn test_policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::ZERO,
+            max_delay: Duration::ZERO,
+            circuit_cooldown: Duration::from_secs(60),
+        }
+    }
+
+    #[test]
+    fn test_backoff_cap_grows_and_is_bounded() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+            ..RetryPolicy::default()
+        };
+        assert_eq!(policy.backoff_cap(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff_cap(2), Duration::from_millis(400));
+        assert_eq!(policy.backoff_cap(10), Duration::from_secs(1));
+        assert_eq!(policy.backoff_cap(u32::MAX), Duration::from_secs(1));
+        assert!(policy.backoff(3) <= policy.backoff_cap(3));
+    }
+
+    #[tokio::test]
+    async fn test_retry_recovers_and_circuit_opens() {
+        let retry = DbRetry::new(test_policy());
+        let calls = AtomicU32::new(0);
+
+        // Succeeds on the last attempt
+        let value = retry
+            .run("flaky", || async {
+                match calls.fetch_add(1, Ordering::SeqCst) {
+                    0 | 1 => Err(anyhow!("connection reset")),
+                    _ => Ok(7),
+                }
+            })
+            .await
+            .unwrap();
+        assert_eq!(value, 7);
+        assert!(retry.is_available());
+
+        // Exhausting the attempts opens the circuit
+        let failed: Result<()> = retry.run("down", || async { Err(anyhow!("connection refused")) }).await;
+        assert!(failed.is_err());
+        assert!(!retry.is_available());
+
+        // Open circuit fails fast without running the operation
+        calls.store(0, Ordering::SeqCst);
+        let skipped = retry
+            .run("skipped", || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            })
+            .await;
+        assert!(skipped.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
+}
@@ -0,0 +1,117 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Active users and transactions of every tracked DApp per Sui epoch.
+//!
+//! Many ecosystem reports are framed per epoch (about a day on mainnet) rather
+//! than per calendar day. Epochs are taken from the checkpoint summaries; the
+//! previous epoch stays open next to the current one, so checkpoints applied
+//! slightly out of order by parallel workers still land in their epoch.
+
+use crate::models::{DAppEpochStats, DAppInteraction};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Epochs kept in memory: the current one and the one before
+pub const RETAINED_EPOCHS: u64 = 2;
+
+#[derive(Debug, Clone, Default)]
+struct DAppEpochActivity {
+    senders: HashSet<String>,
+    transactions: HashSet<String>,
+}
+
+#[derive(Debug, Clone)]
+struct EpochActivity {
+    /// Earliest interaction counted in the epoch
+    observed_from: DateTime<Utc>,
+    /// Latest interaction counted in the epoch
+    observed_until: DateTime<Utc>,
+    dapps: HashMap<String, DAppEpochActivity>,
+}
+
+/// Distinct senders and transactions per (epoch, DApp)
+#[derive(Debug, Clone, Default)]
+pub struct EpochCounter {
+    epochs: BTreeMap<u64, EpochActivity>,
+}
+
+impl EpochCounter {
+    /// Count the interactions of a checkpoint of `epoch`
+    pub fn record(&mut self, epoch: u64, interactions: &[DAppInteraction]) {
+        for interaction in interactions {
+            let Some(dapp_name) = &interaction.dapp_name else {
+                continue;
+            };
+            let activity = self.epochs.entry(epoch).or_insert_with(|| EpochActivity {
+                observed_from: interaction.timestamp,
+                observed_until: interaction.timestamp,
+                dapps: HashMap::new(),
+            });
+            activity.observed_from = activity.observed_from.min(interaction.timestamp);
+            activity.observed_until = activity.observed_until.max(interaction.timestamp);
+
+            let dapp = activity.dapps.entry(dapp_name.clone()).or_default();
+            dapp.senders.insert(interaction.sender.clone());
+            dapp.transactions.insert(interaction.transaction_digest.clone());
+        }
+    }
+
+    /// Drop the epochs before the retained ones, counting back from `current_epoch`
+    pub fn prune(&mut self, current_epoch: u64) {
+        self.epochs = self.epochs.split_off(&(current_epoch + 1).saturating_sub(RETAINED_EPOCHS));
+    }
+
+    /// Stats of every DApp in the retained epochs, by epoch then DApp name
+    pub fn stats(&self) -> Vec<DAppEpochStats> {
+        let mut stats: Vec<DAppEpochStats> = self
+            .epochs
+            .iter()
+            .flat_map(|(epoch, activity)| {
+                activity.dapps.iter().map(move |(dapp_name, dapp)| DAppEpochStats {
+                    epoch: *epoch,
+                    dapp_name: dapp_name.clone(),
+                    active_users: dapp.senders.len() as u32,
+                    transaction_count: dapp.transactions.len() as u32,
+                    observed_from: activity.observed_from,
+                    last_update: activity.observed_until,
+                })
+            })
+            .collect();
+        stats.sort_by(|a, b| a.epoch.cmp(&b.epoch).then_with(|| a.dapp_name.cmp(&b.dapp_name)));
+        stats
+    }
+
+    pub fn clear(&mut self) {
+        self.epochs.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::InteractionBuilder;
+    use std::time::Duration;
+
+    #[test]
+    fn test_counts_distinct_senders_and_transactions_per_epoch() {
+        let mut counter = EpochCounter::default();
+        counter.record(7, &[
+            InteractionBuilder::new().dapp("Cetus AMM").sender("0xa").digest("tx1").seconds(20).build(),
+            InteractionBuilder::new().dapp("Cetus AMM").sender("0xa").digest("tx2").seconds(10).build(),
+            InteractionBuilder::new().dapp("Cetus AMM").sender("0xb").digest("tx2").seconds(30).build(),
+        ]);
+        counter.record(8, &[InteractionBuilder::new().dapp("Cetus AMM").sender("0xa").digest("tx3").seconds(40).build()]);
+
+        let stats: Vec<(u64, u32, u32)> = counter
+            .stats()
+            .iter()
+            .map(|stats| (stats.epoch, stats.active_users, stats.transaction_count))
+            .collect();
+        assert_eq!(stats, [(7, 2, 2), (8, 1, 1)]);
+        assert_eq!(counter.stats()[0].observed_from, DateTime::UNIX_EPOCH + Duration::from_secs(10));
+
+        counter.prune(9);
+        assert_eq!(counter.stats().len(), 1);
+    }
+}
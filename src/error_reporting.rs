@@ -0,0 +1,402 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reporting errors and panics to an error tracker.
+//!
+//! Logs scroll away. With ERROR_REPORTING_URL set, every ERROR event and every
+//! panic is also sent to an error tracker with the fields of the event and of
+//! the spans it happened in. Workers, the extractor and `apply_checkpoint` run
+//! in spans carrying the `checkpoint` number, so a worker panic or a database
+//! error during a checkpoint is reported with the checkpoint it failed on.
+//!
+//! A Sentry DSN (`https://<key>@<host>/<project>`) is sent to Sentry's store
+//! endpoint; any other URL receives the report POSTed as JSON. Reports are sent
+//! by a background task and dropped when it falls behind. The same message is
+//! reported at most once per REPORT_COOLDOWN, so an outage logging an error on
+//! every checkpoint does not flood the tracker; the next report of the message
+//! carries how many were suppressed.
+
+use crate::config::secret_var;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::json;
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{error, Event, Level, Metadata, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// Reports waiting to be sent before new ones are dropped
+const REPORT_QUEUE_SIZE: usize = 256;
+
+/// Minimum time between two reports of the same message
+const REPORT_COOLDOWN: Duration = Duration::from_secs(5 * 60);
+
+/// Timeout of a single report request
+const REPORT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Field the panic hook marks its events with
+const PANIC_FIELD: &str = "panic";
+
+/// Span field reported as a Sentry tag, so issues can be searched by it
+const CHECKPOINT_FIELD: &str = "checkpoint";
+
+/// Where errors are reported
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorReportingSettings {
+    /// Sentry DSN or URL reports are POSTed to
+    pub url: String,
+    /// Environment the reports are tagged with, e.g. production
+    pub environment: Option<String>,
+}
+
+impl ErrorReportingSettings {
+    /// Read `ERROR_REPORTING_URL` (or the file named by `ERROR_REPORTING_URL_FILE`)
+    /// and `ERROR_REPORTING_ENVIRONMENT`
+    /// Returns None when no URL is set, which disables reporting
+    pub fn from_env() -> Result<Option<Self>> {
+        let Some(url) = secret_var("ERROR_REPORTING_URL")?.filter(|url| !url.trim().is_empty()) else {
+            return Ok(None);
+        };
+        let environment = std::env::var("ERROR_REPORTING_ENVIRONMENT").ok().filter(|env| !env.is_empty());
+        Ok(Some(Self { url, environment }))
+    }
+}
+
+/// Severity of a report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportLevel {
+    Error,
+    /// A panic
+    Fatal,
+}
+
+/// An error event with its context
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ErrorReport {
+    pub message: String,
+    pub level: ReportLevel,
+    /// Module the event was logged from
+    pub target: String,
+    /// Fields of the event and of its spans, the innermost winning
+    pub fields: BTreeMap<String, String>,
+    pub timestamp: DateTime<Utc>,
+    /// Reports of the same message suppressed since the previous one
+    pub suppressed: u64,
+}
+
+/// Where reports are sent
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ReportDestination {
+    /// Sentry's store endpoint of a project
+    Sentry { store_url: String, key: String },
+    /// Any other URL, receiving reports as JSON
+    Webhook { url: String },
+}
+
+/// Sentry's store endpoint for a DSN such as `https://<key>@<host>/<project>`,
+/// or the URL itself if it is not a DSN
+fn parse_destination(url: &str) -> Result<ReportDestination> {
+    let (scheme, rest) = url
+        .split_once("://")
+        .filter(|(scheme, _)| scheme.starts_with("http"))
+        .ok_or_else(|| anyhow!("ERROR_REPORTING_URL must be a Sentry DSN or an HTTP/HTTPS URL"))?;
+    let Some((key, location)) = rest.split_once('@') else {
+        return Ok(ReportDestination::Webhook { url: url.to_string() });
+    };
+    let (host, project) = location
+        .trim_end_matches('/')
+        .rsplit_once('/')
+        .ok_or_else(|| anyhow!("Sentry DSN must end with the project id"))?;
+    // The secret of legacy DSNs (`<key>:<secret>@`) is not needed
+    let key = key.split(':').next().unwrap_or(key);
+    Ok(ReportDestination::Sentry {
+        store_url: format!("{}://{}/api/{}/store/", scheme, host, project),
+        key: key.to_string(),
+    })
+}
+
+/// Random 32 hex digit event id, drawn from the std hasher's random keys
+fn event_id() -> String {
+    let random = || RandomState::new().build_hasher().finish();
+    format!("{:016x}{:016x}", random(), random())
+}
+
+/// Sends reports to a Sentry project or a webhook
+pub struct ErrorReporter {
+    destination: ReportDestination,
+    environment: Option<String>,
+    client: reqwest::Client,
+}
+
+impl ErrorReporter {
+    pub fn new(settings: &ErrorReportingSettings) -> Result<Self> {
+        let client = reqwest::Client::builder().timeout(REPORT_TIMEOUT).build()?;
+        Ok(Self { destination: parse_destination(&settings.url)?, environment: settings.environment.clone(), client })
+    }
+
+    /// Sentry event of a report
+    fn sentry_event(&self, report: &ErrorReport) -> serde_json::Value {
+        let mut extra: BTreeMap<&str, &str> =
+            report.fields.iter().map(|(name, value)| (name.as_str(), value.as_str())).collect();
+        let suppressed = report.suppressed.to_string();
+        if report.suppressed > 0 {
+            extra.insert("suppressed", &suppressed);
+        }
+        let tags: BTreeMap<&str, &str> = report
+            .fields
+            .get_key_value(CHECKPOINT_FIELD)
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .into_iter()
+            .collect();
+        json!({
+            "event_id": event_id(),
+            "timestamp": report.timestamp.to_rfc3339(),
+            "level": report.level,
+            "logger": report.target,
+            "platform": "other",
+            "release": concat!(env!("CARGO_PKG_NAME"), "@", env!("CARGO_PKG_VERSION")),
+            "environment": self.environment,
+            "message": { "formatted": report.message },
+            "tags": tags,
+            "extra": extra,
+        })
+    }
+
+    pub async fn report(&self, report: &ErrorReport) -> Result<()> {
+        let request = match &self.destination {
+            ReportDestination::Sentry { store_url, key } => self
+                .client
+                .post(store_url)
+                .header(
+                    "X-Sentry-Auth",
+                    format!(
+                        "Sentry sentry_version=7, sentry_client={}/{}, sentry_key={}",
+                        env!("CARGO_PKG_NAME"),
+                        env!("CARGO_PKG_VERSION"),
+                        key
+                    ),
+                )
+                .json(&self.sentry_event(report)),
+            ReportDestination::Webhook { url } => self.client.post(url).json(&json!({
+                "environment": self.environment,
+                "report": report,
+            })),
+        };
+        request.send().await?.error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Lets a message through at most once per REPORT_COOLDOWN
+#[derive(Debug, Default)]
+struct ReportThrottle {
+    /// message -> when it was last reported, reports suppressed since
+    reported: HashMap<String, (Instant, u64)>,
+}
+
+impl ReportThrottle {
+    /// Number of reports suppressed since the previous one if `message` may be
+    /// reported at `now`, None if it is suppressed
+    fn admit(&mut self, message: &str, now: Instant) -> Option<u64> {
+        // Messages with suppressed reports are kept until they recur to carry the count
+        self.reported.retain(|_, (reported, suppressed)| {
+            now.duration_since(*reported) < REPORT_COOLDOWN || *suppressed > 0
+        });
+        match self.reported.get_mut(message) {
+            Some((reported, suppressed)) if now.duration_since(*reported) < REPORT_COOLDOWN => {
+                *suppressed += 1;
+                None
+            }
+            Some((reported, suppressed)) => {
+                *reported = now;
+                Some(std::mem::take(suppressed))
+            }
+            None => {
+                self.reported.insert(message.to_string(), (now, 0));
+                Some(0)
+            }
+        }
+    }
+}
+
+/// Collects fields as strings
+struct FieldVisitor<'a>(&'a mut BTreeMap<String, String>);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0.insert(field.name().to_string(), format!("{:?}", value));
+    }
+}
+
+/// Fields of a span, kept in its extensions
+struct SpanFields(BTreeMap<String, String>);
+
+/// Whether the error reporting layer needs to see `metadata`: spans up to
+/// INFO, whose fields give the context, and ERROR events
+pub fn is_reported(metadata: &Metadata<'_>) -> bool {
+    (metadata.is_span() && *metadata.level() <= Level::INFO) || *metadata.level() == Level::ERROR
+}
+
+/// Tracing layer turning ERROR events into reports
+/// Should be filtered with `is_reported`, so other events are not even recorded
+pub struct ErrorReportingLayer {
+    sender: mpsc::Sender<ErrorReport>,
+}
+
+impl ErrorReportingLayer {
+    pub fn new(sender: mpsc::Sender<ErrorReport>) -> Self {
+        Self { sender }
+    }
+}
+
+impl<S> Layer<S> for ErrorReportingLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = BTreeMap::new();
+        attrs.record(&mut FieldVisitor(&mut fields));
+        span.extensions_mut().insert(SpanFields(fields));
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(SpanFields(fields)) = span.extensions_mut().get_mut::<SpanFields>() {
+                values.record(&mut FieldVisitor(fields));
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if *event.metadata().level() != Level::ERROR {
+            return;
+        }
+        let mut fields = BTreeMap::new();
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                if let Some(SpanFields(span_fields)) = span.extensions().get::<SpanFields>() {
+                    fields.extend(span_fields.iter().map(|(name, value)| (name.clone(), value.clone())));
+                }
+            }
+        }
+        event.record(&mut FieldVisitor(&mut fields));
+        let message = fields.remove("message").unwrap_or_default();
+        let level = if fields.remove(PANIC_FIELD).is_some() { ReportLevel::Fatal } else { ReportLevel::Error };
+        // Dropped when the reporter falls behind or is gone
+        let _ = self.sender.try_send(ErrorReport {
+            message,
+            level,
+            target: event.metadata().target().to_string(),
+            fields,
+            timestamp: Utc::now(),
+            suppressed: 0,
+        });
+    }
+}
+
+/// Log panics as ERROR events marked as panics, within the panicking thread's
+/// spans, before running the previous hook
+pub fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let payload = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "non-string panic payload".to_string());
+        let location = info.location().map(|location| location.to_string()).unwrap_or_default();
+        error!(panic = true, location = %location, "💥 Panic: {}", payload);
+        previous(info);
+    }));
+}
+
+/// Start the task sending reports to the tracker and return the layer feeding it
+/// Must be called within a tokio runtime
+pub fn start_error_reporting(settings: &ErrorReportingSettings) -> Result<ErrorReportingLayer> {
+    let reporter = ErrorReporter::new(settings)?;
+    let (sender, mut receiver) = mpsc::channel::<ErrorReport>(REPORT_QUEUE_SIZE);
+    tokio::spawn(async move {
+        let mut throttle = ReportThrottle::default();
+        while let Some(mut report) = receiver.recv().await {
+            let Some(suppressed) = throttle.admit(&report.message, Instant::now()) else {
+                continue;
+            };
+            report.suppressed = suppressed;
+            // Logged to stderr: an ERROR event would be reported again
+            if let Err(err) = reporter.report(&report).await {
+                eprintln!("Failed to report error: {}", err);
+            }
+        }
+    });
+    install_panic_hook();
+    Ok(ErrorReportingLayer::new(sender))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing::info_span;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_reports_errors_with_span_context_to_sentry() {
+        assert_eq!(
+            parse_destination("https://abc123@o1.ingest.sentry.io/42").unwrap(),
+            ReportDestination::Sentry { store_url: "https://o1.ingest.sentry.io/api/42/store/".to_string(), key: "abc123".to_string() }
+        );
+        assert_eq!(
+            parse_destination("https://alerts.example.com/errors").unwrap(),
+            ReportDestination::Webhook { url: "https://alerts.example.com/errors".to_string() }
+        );
+        assert!(parse_destination("sentry.io/42").is_err());
+
+        let (sender, mut receiver) = mpsc::channel(8);
+        let subscriber = tracing_subscriber::registry().with(ErrorReportingLayer::new(sender));
+        tracing::subscriber::with_default(subscriber, || {
+            let _checkpoint = info_span!("apply_checkpoint", checkpoint = 1234).entered();
+            tracing::warn!("not reported");
+            error!(operation = "save rankings", "❌ Failed to update database");
+        });
+        let report = receiver.try_recv().unwrap();
+        assert!(receiver.try_recv().is_err());
+        assert_eq!((report.message.as_str(), report.level), ("❌ Failed to update database", ReportLevel::Error));
+        assert_eq!(report.fields["checkpoint"], "1234");
+        assert_eq!(report.fields["operation"], "save rankings");
+
+        let reporter = ErrorReporter::new(&ErrorReportingSettings {
+            url: "https://abc123@o1.ingest.sentry.io/42".to_string(),
+            environment: Some("production".to_string()),
+        })
+        .unwrap();
+        let event = reporter.sentry_event(&report);
+        assert_eq!(event["tags"]["checkpoint"], "1234");
+        assert_eq!(event["environment"], "production");
+        assert_eq!(event["event_id"].as_str().unwrap().len(), 32);
+
+        // The same message is reported once per cooldown
+        let mut throttle = ReportThrottle::default();
+        let now = Instant::now();
+        assert_eq!(throttle.admit("db down", now), Some(0));
+        assert_eq!(throttle.admit("db down", now + Duration::from_secs(1)), None);
+        assert_eq!(throttle.admit("checkpoint stuck", now + Duration::from_secs(1)), Some(0));
+        assert_eq!(throttle.admit("db down", now + REPORT_COOLDOWN), Some(1));
+    }
+}
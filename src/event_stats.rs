@@ -0,0 +1,335 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Activity per event type, per called Move function and per transaction
+//! outcome of every tracked DApp over the last 24 hours.
+//!
+//! Shows which features drive a DApp's activity (swaps vs deposits vs mints),
+//! which entrypoints users actually hit and how often its transactions abort. Interactions are only kept for
+//! the ranking window, so these are counted separately in hourly buckets.
+
+use crate::models::{DAppEventStats, DAppFunctionCall, DAppFunctionStats, DAppInteraction, DAppTransactionOutcome};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+/// Hours covered by the rolling counts
+pub const EVENT_STATS_WINDOW_HOURS: u64 = 24;
+
+/// Label of successful transactions in the outcome counts
+const OUTCOME_SUCCESS: &str = "success";
+/// Label of failed transactions in the outcome counts
+const OUTCOME_FAILURE: &str = "failure";
+
+fn hour_bucket(timestamp: DateTime<Utc>) -> u64 {
+    timestamp.timestamp().max(0) as u64 / 3600
+}
+
+/// First hour bucket inside the window ending at `now`
+fn first_hour(now: DateTime<Utc>) -> u64 {
+    (hour_bucket(now) + 1).saturating_sub(EVENT_STATS_WINDOW_HOURS)
+}
+
+/// Rolling 24h count per (DApp, label), where the label is an event type or a function
+#[derive(Debug, Clone, Default)]
+pub struct ActivityCounter {
+    /// hour since the epoch -> (dapp_name, label) -> count
+    buckets: BTreeMap<u64, HashMap<(String, String), u64>>,
+}
+
+impl ActivityCounter {
+    /// Count one occurrence of `label` for the DApp at `timestamp`
+    pub fn add(&mut self, dapp_name: &str, label: &str, timestamp: DateTime<Utc>) {
+        self.add_amount(dapp_name, label, timestamp, 1);
+    }
+
+    /// Add `amount` to the count of `label` for the DApp at `timestamp`
+    pub fn add_amount(&mut self, dapp_name: &str, label: &str, timestamp: DateTime<Utc>, amount: u64) {
+        let count = self
+            .buckets
+            .entry(hour_bucket(timestamp))
+            .or_default()
+            .entry((dapp_name.to_string(), label.to_string()))
+            .or_default();
+        *count = count.saturating_add(amount);
+    }
+
+    /// Count the event types of the interactions
+    pub fn record_events(&mut self, interactions: &[DAppInteraction]) {
+        for interaction in interactions {
+            if let Some(dapp_name) = &interaction.dapp_name {
+                self.add(dapp_name, &interaction.event_type, interaction.timestamp);
+            }
+        }
+    }
+
+    /// Count the called functions
+    pub fn record_calls(&mut self, calls: &[DAppFunctionCall]) {
+        for call in calls {
+            self.add(&call.dapp_name, &call.function, call.timestamp);
+        }
+    }
+
+    /// Count successful and failed transactions
+    pub fn record_outcomes(&mut self, outcomes: &[DAppTransactionOutcome]) {
+        for outcome in outcomes {
+            let label = if outcome.success { OUTCOME_SUCCESS } else { OUTCOME_FAILURE };
+            self.add(&outcome.dapp_name, label, outcome.timestamp);
+        }
+    }
+
+    /// Drop the hours that left the 24h window
+    pub fn prune(&mut self, now: DateTime<Utc>) {
+        self.buckets = self.buckets.split_off(&first_hour(now));
+    }
+
+    /// (dapp_name, label, count) of the last 24 hours, grouped by DApp and
+    /// most frequent label first
+    pub fn totals(&self, now: DateTime<Utc>) -> Vec<(String, String, u64)> {
+        let mut totals: HashMap<(&str, &str), u64> = HashMap::new();
+        for counts in self.buckets.range(first_hour(now)..).map(|(_, counts)| counts) {
+            for ((dapp_name, label), count) in counts {
+                let total = totals.entry((dapp_name.as_str(), label.as_str())).or_default();
+                *total = total.saturating_add(*count);
+            }
+        }
+
+        let mut totals: Vec<(String, String, u64)> = totals
+            .into_iter()
+            .map(|((dapp_name, label), count)| (dapp_name.to_string(), label.to_string(), count))
+            .collect();
+        totals.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| b.2.cmp(&a.2)).then_with(|| a.1.cmp(&b.1)));
+        totals
+    }
+
+    pub fn clear(&mut self) {
+        self.buckets.clear();
+    }
+}
+
+/// Distinct users per DApp over the last 24 hours
+/// Users are remembered with their last activity, so the window is exact
+/// rather than rounded to hours
+#[derive(Debug, Clone, Default)]
+pub struct RecentUsers {
+    /// dapp_name -> sender -> last activity
+    last_seen: HashMap<String, HashMap<String, DateTime<Utc>>>,
+}
+
+impl RecentUsers {
+    /// Remember `sender` as a user of the DApp at `timestamp`
+    pub fn add(&mut self, dapp_name: &str, sender: &str, timestamp: DateTime<Utc>) {
+        let last_seen = self
+            .last_seen
+            .entry(dapp_name.to_string())
+            .or_default()
+            .entry(sender.to_string())
+            .or_insert(timestamp);
+        *last_seen = (*last_seen).max(timestamp);
+    }
+
+    /// Forget users not seen in the 24 hours before `now`
+    pub fn prune(&mut self, now: DateTime<Utc>) {
+        let window_start = now - Duration::from_secs(EVENT_STATS_WINDOW_HOURS * 60 * 60);
+        for senders in self.last_seen.values_mut() {
+            senders.retain(|_, last_seen| *last_seen >= window_start);
+        }
+        self.last_seen.retain(|_, senders| !senders.is_empty());
+    }
+
+    /// Number of distinct users of the DApp
+    pub fn count(&self, dapp_name: &str) -> u32 {
+        self.last_seen.get(dapp_name).map_or(0, |senders| senders.len() as u32)
+    }
+
+    pub fn clear(&mut self) {
+        self.last_seen.clear();
+    }
+}
+
+/// Interactions per event type of every DApp over the last 24 hours
+pub fn event_stats(counter: &ActivityCounter, now: DateTime<Utc>) -> Vec<DAppEventStats> {
+    counter
+        .totals(now)
+        .into_iter()
+        .map(|(dapp_name, event_type, count)| DAppEventStats {
+            dapp_name,
+            event_type,
+            count_24h: count,
+            last_update: now,
+        })
+        .collect()
+}
+
+/// The `limit` most called functions of every DApp over the last 24 hours
+pub fn top_functions(counter: &ActivityCounter, limit: usize, now: DateTime<Utc>) -> Vec<DAppFunctionStats> {
+    let mut ranks: HashMap<String, u32> = HashMap::new();
+    counter
+        .totals(now)
+        .into_iter()
+        .filter_map(|(dapp_name, function, count)| {
+            let rank = ranks.entry(dapp_name.clone()).or_default();
+            *rank += 1;
+            (*rank as usize <= limit).then_some(DAppFunctionStats {
+                rank: *rank,
+                dapp_name,
+                function,
+                call_count_24h: count,
+                last_update: now,
+            })
+        })
+        .collect()
+}
+
+/// Share of failed transactions of every DApp over the last 24 hours
+pub fn error_rates(counter: &ActivityCounter, now: DateTime<Utc>) -> HashMap<String, f64> {
+    let mut outcomes: HashMap<String, (u64, u64)> = HashMap::new();
+    for (dapp_name, label, count) in counter.totals(now) {
+        let (failed, total) = outcomes.entry(dapp_name).or_default();
+        if label == OUTCOME_FAILURE {
+            *failed += count;
+        }
+        *total += count;
+    }
+    outcomes
+        .into_iter()
+        .map(|(dapp_name, (failed, total))| (dapp_name, failed as f64 / total as f64))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::InteractionBuilder;
+
+    #[test]
+    fn test_counts_cover_last_24_hours() {
+        let now = DateTime::UNIX_EPOCH + Duration::from_secs(100 * 3600 + 1800);
+        let mut counter = ActivityCounter::default();
+        counter.record_events(&[
+            InteractionBuilder::new().event_type("pool::SwapEvent").hours_before(now, 0).build(),
+            InteractionBuilder::new().event_type("pool::SwapEvent").hours_before(now, 23).build(),
+            InteractionBuilder::new().event_type("pool::AddLiquidityEvent").hours_before(now, 2).build(),
+            InteractionBuilder::new().event_type("pool::SwapEvent").hours_before(now, 24).build(),
+        ]);
+
+        let counts: Vec<(String, u64)> = event_stats(&counter, now)
+            .into_iter()
+            .map(|stats| (stats.event_type, stats.count_24h))
+            .collect();
+        assert_eq!(
+            counts,
+            [("pool::SwapEvent".to_string(), 2), ("pool::AddLiquidityEvent".to_string(), 1)]
+        );
+
+        counter.prune(now);
+        assert_eq!(counter.buckets.len(), 3);
+    }
+
+    #[test]
+    fn test_top_functions_keeps_most_called_per_dapp() {
+        let now = DateTime::UNIX_EPOCH + Duration::from_secs(100 * 3600);
+        let mut counter = ActivityCounter::default();
+        for (dapp_name, function, calls) in [
+            ("Cetus AMM", "pool_script::swap_a2b", 5),
+            ("Cetus AMM", "pool_script::open_position", 2),
+            ("Cetus AMM", "pool_script::collect_fee", 1),
+            ("Suilend", "lending_market::deposit", 3),
+        ] {
+            for _ in 0..calls {
+                counter.add(dapp_name, function, now);
+            }
+        }
+
+        let top: Vec<(String, u32, u64)> = top_functions(&counter, 2, now)
+            .into_iter()
+            .map(|stats| (stats.function, stats.rank, stats.call_count_24h))
+            .collect();
+        assert_eq!(
+            top,
+            [
+                ("pool_script::swap_a2b".to_string(), 1, 5),
+                ("pool_script::open_position".to_string(), 2, 2),
+                ("lending_market::deposit".to_string(), 1, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_error_rates_share_of_failed_transactions() {
+        let now = DateTime::UNIX_EPOCH + Duration::from_secs(100 * 3600);
+        let outcome = |dapp_name: &str, success: bool| DAppTransactionOutcome {
+            dapp_name: dapp_name.to_string(),
+            success,
+            timestamp: now,
+        };
+        let mut counter = ActivityCounter::default();
+        counter.record_outcomes(&[
+            outcome("Cetus AMM", true),
+            outcome("Cetus AMM", true),
+            outcome("Cetus AMM", true),
+            outcome("Cetus AMM", false),
+            outcome("Suilend", true),
+        ]);
+
+        let rates = error_rates(&counter, now);
+        assert_eq!(rates["Cetus AMM"], 0.25);
+        assert_eq!(rates["Suilend"], 0.0);
+    }
+
+    #[test]
+    fn test_recent_users_cover_exactly_24_hours() {
+        let now = DateTime::UNIX_EPOCH + Duration::from_secs(100 * 3600 + 1800);
+        let mut users = RecentUsers::default();
+        users.add("Suilend", "0xa", now - Duration::from_secs(30 * 3600));
+        users.add("Suilend", "0xa", now - Duration::from_secs(3600));
+        users.add("Suilend", "0xb", now - Duration::from_secs(24 * 3600 + 60));
+        users.add("Suilend", "0xc", now - Duration::from_secs(24 * 3600 - 60));
+        users.add("NAVI", "0xa", now - Duration::from_secs(25 * 3600));
+        users.prune(now);
+
+        // 0xa is kept by its later activity, 0xb left the window a minute ago
+        assert_eq!(users.count("Suilend"), 2);
+        assert_eq!(users.count("NAVI"), 0);
+    }
+
+    #[test]
+    fn test_window_boundary_at_exactly_24_hours() {
+        let now = DateTime::UNIX_EPOCH + Duration::from_secs(100 * 3600 + 1800);
+        let day = Duration::from_secs(24 * 3600);
+
+        // Counts cover the current hour and the 23 before it: an event exactly 24h
+        // old is in the hour that just left the window, one 23h30 old in the first hour kept
+        let mut counter = ActivityCounter::default();
+        counter.add("Cetus AMM", "pool::SwapEvent", now - day);
+        counter.add("Cetus AMM", "pool::SwapEvent", now - Duration::from_secs(23 * 3600 + 1800));
+        counter.add("Cetus AMM", "pool::SwapEvent", now);
+        counter.prune(now);
+        assert_eq!(counter.totals(now), [("Cetus AMM".to_string(), "pool::SwapEvent".to_string(), 2)]);
+
+        // Users are exact: seen exactly 24h ago is still in, a millisecond earlier is out
+        let mut users = RecentUsers::default();
+        users.add("Suilend", "0xa", now - day);
+        users.add("Suilend", "0xb", now - day - Duration::from_millis(1));
+        users.prune(now);
+        assert_eq!(users.count("Suilend"), 1);
+    }
+
+    #[test]
+    fn test_unknown_dapps_and_event_types() {
+        let now = DateTime::UNIX_EPOCH + Duration::from_secs(100 * 3600);
+        let mut counter = ActivityCounter::default();
+        counter.record_events(&[
+            InteractionBuilder::new().dapp("Cetus AMM").event_type("pool::SomeFutureEvent").at(now).build(),
+            // Not attributed to any DApp
+            InteractionBuilder::new().package("0xunknown").event_type("pool::SwapEvent").at(now).build(),
+        ]);
+
+        // Event types are not interpreted, so unknown ones are counted under their own name
+        let counts: Vec<(String, String, u64)> = event_stats(&counter, now)
+            .into_iter()
+            .map(|stats| (stats.dapp_name, stats.event_type, stats.count_24h))
+            .collect();
+        assert_eq!(counts, [("Cetus AMM".to_string(), "pool::SomeFutureEvent".to_string(), 1)]);
+    }
+}
@@ -0,0 +1,244 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! CSV/JSON export of rankings, top users and interaction aggregates.
+//!
+//! Exports are flat rows with timestamps in RFC 3339, so they open directly in
+//! spreadsheets or load into a notebook. Files are replaced atomically, so a
+//! reader never sees a partially written export.
+
+use crate::models::{DAppInteraction, DAppRanking, DAppTopUserRecord};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::str::FromStr;
+
+/// File format of an export
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+impl FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "json" => Ok(ExportFormat::Json),
+            "csv" => Ok(ExportFormat::Csv),
+            _ => Err(anyhow!("Unknown export format '{}', expected csv or json", value)),
+        }
+    }
+}
+
+impl ExportFormat {
+    /// Format matching the extension of `path`, JSON unless it ends in `.csv`
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("csv") => ExportFormat::Csv,
+            _ => ExportFormat::Json,
+        }
+    }
+}
+
+/// One exported ranking
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RankingRow {
+    pub rank: u32,
+    pub dapp_name: String,
+    pub package_id: String,
+    pub dapp_type: String,
+    pub hau: u32,
+    pub filtered_users: u32,
+    pub tracking_status: String,
+    pub new_users_24h: u32,
+    pub returning_users_24h: u32,
+    pub tx_count: u32,
+    pub gas_used: u64,
+    pub score: f64,
+    pub error_rate_24h: f64,
+    pub dau_smoothed: f64,
+    pub sponsored_user_pct: f64,
+    pub avg_gas_per_tx: f64,
+    pub interactions_p50: u32,
+    pub interactions_p90: u32,
+    pub interactions_p99: u32,
+    pub top_users_interaction_pct: f64,
+    pub volume_24h_usd: f64,
+    pub deposit_volume_24h_usd: f64,
+    pub borrow_volume_24h_usd: f64,
+    pub last_update: String,
+}
+
+impl From<&DAppRanking> for RankingRow {
+    fn from(ranking: &DAppRanking) -> Self {
+        Self {
+            rank: ranking.rank,
+            dapp_name: ranking.dapp_name.clone(),
+            package_id: ranking.package_id.clone(),
+            dapp_type: ranking.dapp_type.clone(),
+            hau: ranking.active_users,
+            filtered_users: ranking.filtered_users,
+            tracking_status: ranking.tracking_status.clone(),
+            new_users_24h: ranking.new_users_24h,
+            returning_users_24h: ranking.returning_users_24h,
+            tx_count: ranking.tx_count,
+            gas_used: ranking.gas_used,
+            score: ranking.score,
+            error_rate_24h: ranking.error_rate_24h,
+            dau_smoothed: ranking.dau_smoothed,
+            sponsored_user_pct: ranking.sponsored_user_pct,
+            avg_gas_per_tx: ranking.avg_gas_per_tx,
+            interactions_p50: ranking.interactions_p50,
+            interactions_p90: ranking.interactions_p90,
+            interactions_p99: ranking.interactions_p99,
+            top_users_interaction_pct: ranking.top_users_interaction_pct,
+            volume_24h_usd: ranking.volume_24h_usd,
+            deposit_volume_24h_usd: ranking.deposit_volume_24h_usd,
+            borrow_volume_24h_usd: ranking.borrow_volume_24h_usd,
+            last_update: ranking.last_update.to_rfc3339(),
+        }
+    }
+}
+
+/// One exported top user
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TopUserRow {
+    pub day: String,
+    pub dapp_name: String,
+    pub rank: i32,
+    pub sender: String,
+    /// Default SuiNS name of the sender, empty when unknown or enrichment is disabled
+    pub suins_name: Option<String>,
+    pub interaction_count: i64,
+}
+
+impl From<DAppTopUserRecord> for TopUserRow {
+    fn from(record: DAppTopUserRecord) -> Self {
+        Self {
+            day: record.day.to_string(),
+            dapp_name: record.dapp_name,
+            rank: record.rank_position,
+            sender: record.sender,
+            suins_name: None,
+            interaction_count: record.interaction_count,
+        }
+    }
+}
+
+/// Interactions in memory for one package of a DApp
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InteractionAggregate {
+    pub dapp_name: String,
+    pub package_id: String,
+    pub interactions: u64,
+    pub unique_senders: u64,
+    pub transactions: u64,
+    /// Gas of each distinct transaction, counted once (MIST)
+    pub gas_used: u64,
+    pub first_seen: String,
+    pub last_seen: String,
+}
+
+/// Aggregate interactions per (DApp, package), sorted by DApp name then package
+pub fn aggregate_interactions(interactions: &[DAppInteraction]) -> Vec<InteractionAggregate> {
+    #[derive(Default)]
+    struct Totals<'a> {
+        interactions: u64,
+        senders: HashSet<&'a str>,
+        transactions: HashMap<&'a str, u64>,
+        first_seen: Option<DateTime<Utc>>,
+        last_seen: Option<DateTime<Utc>>,
+    }
+
+    let mut totals: HashMap<(&str, &str), Totals> = HashMap::new();
+    for interaction in interactions {
+        let dapp_name = interaction.dapp_name.as_deref().unwrap_or("Unknown");
+        let entry = totals.entry((dapp_name, interaction.package_id.as_str())).or_default();
+        entry.interactions += 1;
+        entry.senders.insert(&interaction.sender);
+        entry.transactions.insert(&interaction.transaction_digest, interaction.gas_used);
+        entry.first_seen = Some(entry.first_seen.map_or(interaction.timestamp, |seen| seen.min(interaction.timestamp)));
+        entry.last_seen = Some(entry.last_seen.map_or(interaction.timestamp, |seen| seen.max(interaction.timestamp)));
+    }
+
+    let format_time = |time: Option<DateTime<Utc>>| {
+        time.map(|time| time.to_rfc3339()).unwrap_or_default()
+    };
+    let mut aggregates: Vec<InteractionAggregate> = totals
+        .into_iter()
+        .map(|((dapp_name, package_id), totals)| InteractionAggregate {
+            dapp_name: dapp_name.to_string(),
+            package_id: package_id.to_string(),
+            interactions: totals.interactions,
+            unique_senders: totals.senders.len() as u64,
+            transactions: totals.transactions.len() as u64,
+            gas_used: totals.transactions.values().sum(),
+            first_seen: format_time(totals.first_seen),
+            last_seen: format_time(totals.last_seen),
+        })
+        .collect();
+    aggregates.sort_by(|a, b| a.dapp_name.cmp(&b.dapp_name).then_with(|| a.package_id.cmp(&b.package_id)));
+    aggregates
+}
+
+/// Encode `rows` in `format`
+pub fn encode_rows<T: Serialize>(rows: &[T], format: ExportFormat) -> Result<Vec<u8>> {
+    match format {
+        ExportFormat::Json => Ok(serde_json::to_vec_pretty(rows)?),
+        ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            for row in rows {
+                writer.serialize(row)?;
+            }
+            writer.into_inner().map_err(|err| anyhow!("Failed to finish CSV export: {}", err))
+        }
+    }
+}
+
+/// Write `rows` to `path` in `format`, replacing the file atomically
+pub async fn write_rows<T: Serialize>(rows: &[T], path: &Path, format: ExportFormat) -> Result<()> {
+    let bytes = encode_rows(rows, format)?;
+    let tmp_path = path.with_extension("tmp");
+    tokio::fs::write(&tmp_path, bytes).await?;
+    tokio::fs::rename(&tmp_path, path).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::InteractionBuilder;
+
+    #[test]
+    fn test_aggregate_interactions_exports_csv() {
+        let interactions = vec![
+            InteractionBuilder::new().sender("0xa").digest("tx1").gas_used(100).seconds(10).build(),
+            InteractionBuilder::new().sender("0xa").digest("tx1").gas_used(100).seconds(10).build(),
+            InteractionBuilder::new().sender("0xb").digest("tx2").gas_used(50).seconds(20).build(),
+        ];
+
+        let aggregates = aggregate_interactions(&interactions);
+        assert_eq!(aggregates.len(), 1);
+        assert_eq!(aggregates[0].interactions, 3);
+        assert_eq!(aggregates[0].unique_senders, 2);
+        assert_eq!(aggregates[0].transactions, 2);
+        assert_eq!(aggregates[0].gas_used, 150);
+
+        let csv = String::from_utf8(encode_rows(&aggregates, ExportFormat::Csv).unwrap()).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("dapp_name,package_id,interactions,unique_senders,transactions,gas_used,first_seen,last_seen")
+        );
+        assert_eq!(
+            lines.next(),
+            Some("Cetus AMM,0x1,3,2,2,150,1970-01-01T00:00:10+00:00,1970-01-01T00:00:20+00:00")
+        );
+        assert_eq!(ExportFormat::from_path(Path::new("out/rankings.CSV")), ExportFormat::Csv);
+    }
+}
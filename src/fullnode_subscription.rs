@@ -0,0 +1,194 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Checkpoint subscription to a Sui fullnode.
+//!
+//! Remote storage buckets lag the chain head by the time it takes to upload a
+//! checkpoint. With FULLNODE_CHECKPOINT_URL set, a task follows the fullnode's
+//! latest checkpoint through `RPC_URL` and downloads each new checkpoint from
+//! the fullnode's REST API (`{url}/checkpoints/{sequence_number}/full`, BCS
+//! encoded) into `{checkpoints_dir}/{sequence_number}.chk`, where the executor
+//! reads local checkpoints before asking remote storage.
+//!
+//! The subscription only runs near the head: while ingestion is further than
+//! `HEAD_DISTANCE` checkpoints behind, remote storage catches it up. Checkpoints
+//! the fullnode fails to serve are skipped and, like any other gap in the
+//! local directory, are read from remote storage instead.
+
+use crate::warm_up::CheckpointLocator;
+use anyhow::{anyhow, Result};
+use prometheus::{register_int_counter_with_registry, register_int_gauge_with_registry, IntCounter, IntGauge, Registry};
+use std::fs;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use sui_types::messages_checkpoint::CheckpointSequenceNumber;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+/// How often the fullnode is asked for its latest checkpoint
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Timeout of a single checkpoint download
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Ingestion further behind the head than this is left to remote storage
+const HEAD_DISTANCE: u64 = 1_000;
+
+/// Encoding tag the executor expects in front of a BCS encoded checkpoint file
+const BCS_BLOB_ENCODING: u8 = 1;
+
+/// Prometheus metrics of the fullnode subscription
+#[derive(Clone)]
+pub struct FullnodeSubscriptionMetrics {
+    downloaded: IntCounter,
+    failures: IntCounter,
+    latest_checkpoint: IntGauge,
+}
+
+impl FullnodeSubscriptionMetrics {
+    pub fn new(registry: &Registry) -> Result<Self> {
+        Ok(Self {
+            downloaded: register_int_counter_with_registry!(
+                "fullnode_checkpoints_downloaded",
+                "Checkpoints downloaded from the fullnode subscription",
+                registry
+            )?,
+            failures: register_int_counter_with_registry!(
+                "fullnode_checkpoint_failures",
+                "Checkpoints the fullnode failed to serve, left to remote storage",
+                registry
+            )?,
+            latest_checkpoint: register_int_gauge_with_registry!(
+                "fullnode_latest_checkpoint",
+                "Latest checkpoint reported by the fullnode",
+                registry
+            )?,
+        })
+    }
+}
+
+/// Downloads full checkpoints from a fullnode's REST API
+pub struct FullnodeCheckpointSource {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl FullnodeCheckpointSource {
+    pub fn new(url: impl Into<String>) -> Result<Self> {
+        let client = reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build()?;
+        Ok(Self { url: url.into().trim_end_matches('/').to_string(), client })
+    }
+
+    /// BCS encoded checkpoint `sequence_number`, None if the fullnode does not have it yet
+    pub async fn fetch_checkpoint(&self, sequence_number: CheckpointSequenceNumber) -> Result<Option<Vec<u8>>> {
+        let response = self
+            .client
+            .get(format!("{}/checkpoints/{}/full", self.url, sequence_number))
+            .header(reqwest::header::ACCEPT, "application/bcs")
+            .send()
+            .await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let bytes = response.error_for_status()?.bytes().await?;
+        if bytes.is_empty() {
+            return Err(anyhow!("empty response for checkpoint {}", sequence_number));
+        }
+        Ok(Some(bytes.to_vec()))
+    }
+}
+
+/// Checkpoints to download: from the later of `next` and the committed watermark up
+/// to `latest`, None while the watermark is more than HEAD_DISTANCE behind
+fn checkpoints_to_fetch(
+    next: CheckpointSequenceNumber,
+    watermark: CheckpointSequenceNumber,
+    latest: CheckpointSequenceNumber,
+) -> Option<RangeInclusive<CheckpointSequenceNumber>> {
+    (latest.saturating_sub(watermark) <= HEAD_DISTANCE).then(|| next.max(watermark)..=latest)
+}
+
+/// Write a BCS encoded checkpoint where the executor reads local checkpoints
+/// The file is renamed into place so the executor never reads a partial file
+pub fn write_checkpoint_file(dir: &Path, sequence_number: CheckpointSequenceNumber, bcs: &[u8]) -> Result<()> {
+    let path = dir.join(format!("{}.chk", sequence_number));
+    let partial = dir.join(format!("{}.chk.partial", sequence_number));
+    let mut contents = Vec::with_capacity(bcs.len() + 1);
+    contents.push(BCS_BLOB_ENCODING);
+    contents.extend_from_slice(bcs);
+    fs::write(&partial, contents)?;
+    fs::rename(&partial, &path)?;
+    Ok(())
+}
+
+/// Start the task downloading new checkpoints from the fullnode into `checkpoints_dir`
+/// `watermark` follows the committed progress, so only checkpoints near the head are fetched
+pub async fn start_fullnode_subscription(
+    source: FullnodeCheckpointSource,
+    locator: CheckpointLocator,
+    checkpoints_dir: PathBuf,
+    watermark: watch::Receiver<CheckpointSequenceNumber>,
+    metrics: FullnodeSubscriptionMetrics,
+) {
+    info!("📡 Subscribing to fullnode checkpoints from {}", source.url);
+    tokio::spawn(async move {
+        let mut next = 0;
+        let mut ticker = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            ticker.tick().await;
+
+            let latest = match locator.latest_checkpoint().await {
+                Ok(latest) => latest,
+                Err(err) => {
+                    warn!("⚠️ Failed to read the fullnode's latest checkpoint: {}", err);
+                    continue;
+                }
+            };
+            metrics.latest_checkpoint.set(latest as i64);
+
+            let committed = *watermark.borrow();
+            for sequence_number in checkpoints_to_fetch(next, committed, latest).into_iter().flatten() {
+                match source.fetch_checkpoint(sequence_number).await {
+                    Ok(Some(bcs)) => {
+                        let dir = checkpoints_dir.clone();
+                        let written =
+                            tokio::task::spawn_blocking(move || write_checkpoint_file(&dir, sequence_number, &bcs)).await;
+                        match written {
+                            Ok(Ok(())) => metrics.downloaded.inc(),
+                            Ok(Err(err)) => warn!("⚠️ Failed to write checkpoint {}: {}", sequence_number, err),
+                            Err(err) => warn!("⚠️ Checkpoint {} write task panicked: {}", sequence_number, err),
+                        }
+                    }
+                    // Not served yet, retried on the next poll
+                    Ok(None) => break,
+                    // Left to remote storage
+                    Err(err) => {
+                        metrics.failures.inc();
+                        warn!("⚠️ Fullnode failed to serve checkpoint {}, falling back to remote storage: {}",
+                              sequence_number, err);
+                    }
+                }
+                next = sequence_number + 1;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_checkpoints_near_the_head_are_fetched() {
+        assert_eq!(checkpoints_to_fetch(0, 9_500, 10_000), Some(9_500..=10_000));
+        assert_eq!(checkpoints_to_fetch(9_990, 9_500, 10_000), Some(9_990..=10_000));
+        assert_eq!(checkpoints_to_fetch(0, 5_000, 10_000), None);
+        assert!(checkpoints_to_fetch(10_001, 9_500, 10_000).unwrap().is_empty());
+
+        let dir = tempfile::tempdir().unwrap();
+        write_checkpoint_file(dir.path(), 7, b"bcs").unwrap();
+        assert_eq!(fs::read(dir.path().join("7.chk")).unwrap(), [BCS_BLOB_ENCODING, b'b', b'c', b's']);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
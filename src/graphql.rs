@@ -0,0 +1,284 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! GraphQL API over the persisted rankings.
+//!
+//! Served by the HTTP API at `/graphql` (POST for queries, GET for the GraphiQL
+//! explorer), so frontends can fetch exactly the fields they need in one
+//! request:
+//! - `rankings(window, category, limit)` - current rankings, optionally of one category
+//! - `dapp(name)` - current ranking of one DApp with its retention cohorts and top users
+//! - `rankingHistory(dapp, from, to)` - persisted ranking snapshots of one DApp
+
+use crate::database::DatabaseManager;
+use crate::models::{DAppRankingHistoryRecord, DAppRankingRecord, DAppRetentionRecord, DAppTopUserRecord};
+use crate::ranking_query::RankingFilter;
+use crate::suins::SuinsResolver;
+use async_graphql::{Context, EmptyMutation, EmptySubscription, Enum, Object, Schema, SimpleObject};
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use std::sync::Arc;
+
+pub type RankingSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// Build the schema, resolving queries against `db_manager`
+/// Top users carry their SuiNS names when `suins_resolver` is set
+pub fn build_schema(db_manager: Arc<DatabaseManager>, suins_resolver: Option<Arc<SuinsResolver>>) -> RankingSchema {
+    let schema = Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(db_manager)
+        .limit_depth(8);
+    match suins_resolver {
+        Some(suins_resolver) => schema.data(suins_resolver).finish(),
+        None => schema.finish(),
+    }
+}
+
+/// Activity window the rankings are computed over
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RankingWindow {
+    /// Last hour (Hourly Active Users)
+    #[default]
+    H1,
+}
+
+/// Current ranking of a DApp
+#[derive(SimpleObject)]
+pub struct Ranking {
+    pub rank: i32,
+    pub dapp_name: String,
+    pub package_id: String,
+    pub dapp_type: String,
+    /// Hourly Active Users after sender filtering
+    pub hau: i32,
+    pub filtered_users: i32,
+    pub tracking_status: String,
+    pub new_users_24h: i32,
+    pub returning_users_24h: i32,
+    pub tx_count: i32,
+    /// Gas used in MIST
+    pub gas_used: i64,
+    pub score: f64,
+    /// Share of the DApp's transactions that failed in the last 24h
+    pub error_rate_24h: f64,
+    /// Moving average of Hourly Active Users over the last ranking computations
+    pub dau_smoothed: f64,
+    /// Share (%) of active users whose gas was sponsored
+    pub sponsored_user_pct: f64,
+    /// Average gas per transaction (MIST)
+    pub avg_gas_per_tx: f64,
+    /// Median interactions per active user
+    pub interactions_p50: i32,
+    /// 90th percentile of interactions per active user
+    pub interactions_p90: i32,
+    /// 99th percentile of interactions per active user
+    pub interactions_p99: i32,
+    /// Share (%) of interactions made by the top 1% of active users
+    pub top_users_interaction_pct: f64,
+    /// USD value swapped over the last 24h (DEX and Aggregator only)
+    pub volume_24h_usd: f64,
+    /// USD value deposited over the last 24h (Lending only)
+    pub deposit_volume_24h_usd: f64,
+    /// USD value borrowed over the last 24h (Lending only)
+    pub borrow_volume_24h_usd: f64,
+    /// Distinct users over the last 7 days
+    pub wau: i32,
+    /// Distinct users over the last 30 days
+    pub mau: i32,
+    /// When the ranking was computed
+    pub last_update: DateTime<Utc>,
+}
+
+impl From<DAppRankingRecord> for Ranking {
+    fn from(record: DAppRankingRecord) -> Self {
+        Self {
+            rank: record.rank_position,
+            dapp_name: record.dapp_name,
+            package_id: record.package_id,
+            dapp_type: record.dapp_type,
+            hau: record.active_users,
+            filtered_users: record.filtered_users,
+            tracking_status: record.tracking_status,
+            new_users_24h: record.new_users_24h,
+            returning_users_24h: record.returning_users_24h,
+            tx_count: record.tx_count,
+            gas_used: record.gas_used,
+            score: record.score,
+            error_rate_24h: record.error_rate_24h,
+            dau_smoothed: record.dau_smoothed,
+            sponsored_user_pct: record.sponsored_user_pct,
+            avg_gas_per_tx: record.avg_gas_per_tx,
+            interactions_p50: record.interactions_p50,
+            interactions_p90: record.interactions_p90,
+            interactions_p99: record.interactions_p99,
+            top_users_interaction_pct: record.top_users_interaction_pct,
+            volume_24h_usd: record.volume_24h_usd,
+            deposit_volume_24h_usd: record.deposit_volume_24h_usd,
+            borrow_volume_24h_usd: record.borrow_volume_24h_usd,
+            wau: record.wau,
+            mau: record.mau,
+            last_update: record.last_update,
+        }
+    }
+}
+
+/// Persisted ranking snapshot of a DApp
+#[derive(SimpleObject)]
+pub struct RankingSnapshot {
+    pub rank: i32,
+    pub hau: i32,
+    pub tx_count: i32,
+    pub gas_used: i64,
+    pub score: f64,
+    pub recorded_at: NaiveDateTime,
+}
+
+impl From<DAppRankingHistoryRecord> for RankingSnapshot {
+    fn from(record: DAppRankingHistoryRecord) -> Self {
+        Self {
+            rank: record.rank_position,
+            hau: record.active_users,
+            tx_count: record.tx_count,
+            gas_used: record.gas_used,
+            score: record.score,
+            recorded_at: record.recorded_at,
+        }
+    }
+}
+
+/// D1/D7/D30 return rates of one daily cohort
+#[derive(SimpleObject)]
+pub struct RetentionCohort {
+    pub cohort_day: chrono::NaiveDate,
+    pub cohort_size: i32,
+    pub d1_rate: Option<f64>,
+    pub d7_rate: Option<f64>,
+    pub d30_rate: Option<f64>,
+}
+
+impl From<DAppRetentionRecord> for RetentionCohort {
+    fn from(record: DAppRetentionRecord) -> Self {
+        Self {
+            cohort_day: record.cohort_day,
+            cohort_size: record.cohort_size,
+            d1_rate: record.d1_rate,
+            d7_rate: record.d7_rate,
+            d30_rate: record.d30_rate,
+        }
+    }
+}
+
+/// Most active sender of a DApp on one day
+#[derive(SimpleObject)]
+pub struct TopUser {
+    pub day: chrono::NaiveDate,
+    pub sender: String,
+    /// Default SuiNS name of the sender, null when unknown or enrichment is disabled
+    pub suins_name: Option<String>,
+    pub interaction_count: i64,
+    pub rank: i32,
+}
+
+impl From<DAppTopUserRecord> for TopUser {
+    fn from(record: DAppTopUserRecord) -> Self {
+        Self {
+            day: record.day,
+            sender: record.sender,
+            suins_name: None,
+            interaction_count: record.interaction_count,
+            rank: record.rank_position,
+        }
+    }
+}
+
+/// A DApp with its current ranking; related data is loaded only when requested
+pub struct DApp {
+    name: String,
+    ranking: Ranking,
+}
+
+#[Object]
+impl DApp {
+    async fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Current ranking
+    async fn ranking(&self) -> &Ranking {
+        &self.ranking
+    }
+
+    /// Most recent retention cohorts, newest first
+    async fn retention(&self, ctx: &Context<'_>, #[graphql(default = 30)] limit: i64) -> async_graphql::Result<Vec<RetentionCohort>> {
+        let db_manager = ctx.data::<Arc<DatabaseManager>>()?;
+        let cohorts = db_manager.get_retention(&self.name, limit.clamp(1, 365)).await?;
+        Ok(cohorts.into_iter().map(RetentionCohort::from).collect())
+    }
+
+    /// Most active senders of the latest completed or current day
+    async fn top_users(&self, ctx: &Context<'_>, #[graphql(default = 10)] limit: i64) -> async_graphql::Result<Vec<TopUser>> {
+        let db_manager = ctx.data::<Arc<DatabaseManager>>()?;
+        let users = db_manager.get_top_users(&self.name, limit.clamp(1, 1000)).await?;
+        let mut users: Vec<TopUser> = users.into_iter().map(TopUser::from).collect();
+        if let Some(suins_resolver) = ctx.data_opt::<Arc<SuinsResolver>>() {
+            let senders: Vec<String> = users.iter().map(|user| user.sender.clone()).collect();
+            let names = suins_resolver.resolve_all(senders.iter().map(String::as_str)).await;
+            for user in &mut users {
+                user.suins_name = names.get(&user.sender).cloned();
+            }
+        }
+        Ok(users)
+    }
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Current rankings, best first
+    async fn rankings(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] window: RankingWindow,
+        category: Option<String>,
+        #[graphql(default = 100)] limit: i64,
+    ) -> async_graphql::Result<Vec<Ranking>> {
+        let db_manager = ctx.data::<Arc<DatabaseManager>>()?;
+        let filter = RankingFilter { dapp_type: category, limit, ..RankingFilter::default() };
+        let page = match window {
+            RankingWindow::H1 => db_manager.query_rankings(&filter).await?,
+        };
+        Ok(page.rankings.into_iter().map(Ranking::from).collect())
+    }
+
+    /// One DApp by name, null if it is not ranked
+    async fn dapp(&self, ctx: &Context<'_>, name: String) -> async_graphql::Result<Option<DApp>> {
+        let db_manager = ctx.data::<Arc<DatabaseManager>>()?;
+        let ranking = db_manager
+            .get_dapp_rankings()
+            .await?
+            .into_iter()
+            .find(|record| record.dapp_name == name)
+            .map(Ranking::from);
+        Ok(ranking.map(|ranking| DApp { name, ranking }))
+    }
+
+    /// Ranking snapshots of a DApp between `from` and `to` (UTC), oldest first
+    /// Defaults to the last 24 hours
+    async fn ranking_history(
+        &self,
+        ctx: &Context<'_>,
+        dapp: String,
+        from: Option<NaiveDateTime>,
+        to: Option<NaiveDateTime>,
+        #[graphql(default = 1000)] limit: i64,
+    ) -> async_graphql::Result<Vec<RankingSnapshot>> {
+        let to = to.unwrap_or_else(|| Utc::now().naive_utc());
+        let from = from.unwrap_or(to - Duration::hours(24));
+        if from > to {
+            return Err("`from` must not be after `to`".into());
+        }
+
+        let db_manager = ctx.data::<Arc<DatabaseManager>>()?;
+        let history = db_manager.get_ranking_history(&dapp, from, to, limit.clamp(1, 10_000)).await?;
+        Ok(history.into_iter().map(RankingSnapshot::from).collect())
+    }
+}
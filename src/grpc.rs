@@ -0,0 +1,141 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! gRPC API over the live rankings, for internal services that prefer
+//! protobuf over JSON.
+//!
+//! Unlike the HTTP API, rankings are served from the indexer's memory as soon
+//! as they are computed (see `proto/dapp_rankings.proto`):
+//! - `GetRankings` - current rankings, optionally of one category
+//! - `GetDApp` - current ranking of one DApp
+//! - `WatchRankings` - current rankings, then every update
+
+use crate::models::DAppRanking;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::watch;
+use tokio_stream::wrappers::WatchStream;
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response, Status};
+use tracing::{error, info};
+
+pub mod proto {
+    tonic::include_proto!("dapp_rankings.v1");
+}
+
+use proto::d_app_rankings_server::{DAppRankings, DAppRankingsServer};
+use proto::{
+    GetDAppRequest, GetDAppResponse, GetRankingsRequest, GetRankingsResponse, RankingsUpdate,
+    WatchRankingsRequest,
+};
+
+impl From<&DAppRanking> for proto::DAppRanking {
+    fn from(ranking: &DAppRanking) -> Self {
+        Self {
+            rank: ranking.rank,
+            dapp_name: ranking.dapp_name.clone(),
+            package_id: ranking.package_id.clone(),
+            dapp_type: ranking.dapp_type.clone(),
+            hau: ranking.active_users,
+            filtered_users: ranking.filtered_users,
+            tracking_status: ranking.tracking_status.clone(),
+            new_users_24h: ranking.new_users_24h,
+            returning_users_24h: ranking.returning_users_24h,
+            tx_count: ranking.tx_count,
+            gas_used: ranking.gas_used,
+            score: ranking.score,
+            error_rate_24h: ranking.error_rate_24h,
+            dau_smoothed: ranking.dau_smoothed,
+            sponsored_user_pct: ranking.sponsored_user_pct,
+            avg_gas_per_tx: ranking.avg_gas_per_tx,
+            interactions_p50: ranking.interactions_p50,
+            interactions_p90: ranking.interactions_p90,
+            interactions_p99: ranking.interactions_p99,
+            top_users_interaction_pct: ranking.top_users_interaction_pct,
+            volume_24h_usd: ranking.volume_24h_usd,
+            deposit_volume_24h_usd: ranking.deposit_volume_24h_usd,
+            borrow_volume_24h_usd: ranking.borrow_volume_24h_usd,
+            last_update_ms: ranking.last_update.timestamp_millis(),
+        }
+    }
+}
+
+/// Rankings of `category` (all if None), at most `limit` of them (all if 0)
+fn select_rankings(rankings: &[DAppRanking], category: Option<&str>, limit: u32) -> Vec<proto::DAppRanking> {
+    let limit = if limit == 0 { usize::MAX } else { limit as usize };
+    rankings
+        .iter()
+        .filter(|ranking| category.is_none_or(|category| ranking.dapp_type == category))
+        .take(limit)
+        .map(proto::DAppRanking::from)
+        .collect()
+}
+
+/// Serves the rankings published by a `DAppIndexer`
+pub struct RankingsService {
+    rankings: watch::Receiver<Arc<Vec<DAppRanking>>>,
+}
+
+impl RankingsService {
+    pub fn new(rankings: watch::Receiver<Arc<Vec<DAppRanking>>>) -> Self {
+        Self { rankings }
+    }
+}
+
+type RankingsUpdateStream = Pin<Box<dyn Stream<Item = Result<RankingsUpdate, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl DAppRankings for RankingsService {
+    async fn get_rankings(&self, request: Request<GetRankingsRequest>) -> Result<Response<GetRankingsResponse>, Status> {
+        let request = request.into_inner();
+        let rankings = self.rankings.borrow().clone();
+        Ok(Response::new(GetRankingsResponse {
+            rankings: select_rankings(&rankings, request.category.as_deref(), request.limit),
+        }))
+    }
+
+    async fn get_d_app(&self, request: Request<GetDAppRequest>) -> Result<Response<GetDAppResponse>, Status> {
+        let dapp_name = request.into_inner().dapp_name;
+        let rankings = self.rankings.borrow().clone();
+        match rankings.iter().find(|ranking| ranking.dapp_name == dapp_name) {
+            Some(ranking) => Ok(Response::new(GetDAppResponse { ranking: Some(ranking.into()) })),
+            None => Err(Status::not_found(format!("DApp '{}' is not ranked", dapp_name))),
+        }
+    }
+
+    type WatchRankingsStream = RankingsUpdateStream;
+
+    // The stream item type is dictated by tonic
+    #[allow(clippy::result_large_err)]
+    async fn watch_rankings(&self, request: Request<WatchRankingsRequest>) -> Result<Response<Self::WatchRankingsStream>, Status> {
+        let request = request.into_inner();
+        // Slow clients skip intermediate updates and always get the latest rankings
+        let updates = WatchStream::new(self.rankings.clone()).map(move |rankings| {
+            Ok(RankingsUpdate {
+                rankings: select_rankings(&rankings, request.category.as_deref(), request.limit),
+            })
+        });
+        Ok(Response::new(Box::pin(updates)))
+    }
+}
+
+/// Start the gRPC server on `bind_address`
+pub async fn start_grpc_server(rankings: watch::Receiver<Arc<Vec<DAppRanking>>>, bind_address: SocketAddr) -> anyhow::Result<()> {
+    let listener = tokio::net::TcpListener::bind(bind_address).await?;
+    let incoming = tokio_stream::wrappers::TcpListenerStream::new(listener);
+    let service = DAppRankingsServer::new(RankingsService::new(rankings));
+
+    info!("📡 gRPC server listening on {}", bind_address);
+    tokio::spawn(async move {
+        if let Err(err) = tonic::transport::Server::builder()
+            .add_service(service)
+            .serve_with_incoming(incoming)
+            .await
+        {
+            error!("❌ gRPC server stopped: {}", err);
+        }
+    });
+
+    Ok(())
+}
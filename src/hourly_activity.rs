@@ -0,0 +1,125 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Activity heat map of every tracked DApp.
+//!
+//! Counts the distinct users active on a DApp in each hour of the day (UTC)
+//! over the trailing 7 days, so frontends can render when a DApp is used and
+//! regional usage patterns stand out. Every sender is remembered once per
+//! (DApp, hour of day) with the time it was last seen there, which keeps
+//! memory bounded by the active users rather than by the interactions.
+
+use crate::models::{DAppHourlyActivity, DAppInteraction};
+use chrono::{DateTime, Timelike, Utc};
+use std::collections::{BTreeSet, HashMap};
+use std::time::Duration;
+
+/// Period the heat map covers
+pub const HOURLY_ACTIVITY_WINDOW: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// UTC hour of day (0-23) of `timestamp`
+fn hour_of_day(timestamp: DateTime<Utc>) -> u32 {
+    timestamp.hour()
+}
+
+/// Distinct senders per (DApp, hour of day) over the last 7 days
+#[derive(Debug, Clone, Default)]
+pub struct HourlyActivityCounter {
+    /// (dapp_name, hour of day) -> sender -> last interaction in that hour
+    senders: HashMap<(String, u32), HashMap<String, DateTime<Utc>>>,
+    /// Earliest interaction counted, to tell whether the window is fully covered
+    observed_from: Option<DateTime<Utc>>,
+}
+
+impl HourlyActivityCounter {
+    /// Count the senders of the interactions
+    pub fn record(&mut self, interactions: &[DAppInteraction]) {
+        for interaction in interactions {
+            let Some(dapp_name) = &interaction.dapp_name else {
+                continue;
+            };
+            let last_seen = self
+                .senders
+                .entry((dapp_name.clone(), hour_of_day(interaction.timestamp)))
+                .or_default()
+                .entry(interaction.sender.clone())
+                .or_insert(interaction.timestamp);
+            *last_seen = (*last_seen).max(interaction.timestamp);
+            self.observed_from = Some(self.observed_from.map_or(interaction.timestamp, |from| from.min(interaction.timestamp)));
+        }
+    }
+
+    /// Forget senders not seen in their hour during the 7 days before `now`
+    pub fn prune(&mut self, now: DateTime<Utc>) {
+        let window_start = now - HOURLY_ACTIVITY_WINDOW;
+        for senders in self.senders.values_mut() {
+            senders.retain(|_, last_seen| *last_seen >= window_start);
+        }
+        self.senders.retain(|_, senders| !senders.is_empty());
+        self.observed_from = self.observed_from.map(|from| from.max(window_start));
+    }
+
+    /// All 24 hours of every DApp active in the window, by DApp then hour
+    /// Hours without users are included so each DApp has a full row of the heat map
+    pub fn stats(&self, now: DateTime<Utc>) -> Vec<DAppHourlyActivity> {
+        let Some(observed_from) = self.observed_from else {
+            return Vec::new();
+        };
+        let dapps: BTreeSet<&str> = self.senders.keys().map(|(dapp_name, _)| dapp_name.as_str()).collect();
+        dapps
+            .into_iter()
+            .flat_map(|dapp_name| {
+                (0..24).map(move |hour_of_day| DAppHourlyActivity {
+                    dapp_name: dapp_name.to_string(),
+                    hour_of_day,
+                    unique_users: self
+                        .senders
+                        .get(&(dapp_name.to_string(), hour_of_day))
+                        .map_or(0, |senders| senders.len() as u32),
+                    observed_from,
+                    last_update: now,
+                })
+            })
+            .collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.senders.clear();
+        self.observed_from = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::InteractionBuilder;
+
+    #[test]
+    fn test_counts_distinct_senders_per_hour_of_day_over_seven_days() {
+        const DAY: u64 = 24 * 60 * 60;
+        let mut counter = HourlyActivityCounter::default();
+        counter.record(&[
+            // 0xa at 02:00 on two days counts once
+            InteractionBuilder::new().dapp("Cetus AMM").sender("0xa").seconds(2 * 3600).build(),
+            InteractionBuilder::new().dapp("Cetus AMM").sender("0xa").seconds(DAY + 2 * 3600 + 60).build(),
+            InteractionBuilder::new().dapp("Cetus AMM").sender("0xb").seconds(5 * DAY + 2 * 3600).build(),
+            InteractionBuilder::new().dapp("Cetus AMM").sender("0xa").seconds(5 * DAY + 14 * 3600).build(),
+        ]);
+
+        let now = DateTime::UNIX_EPOCH + Duration::from_secs(7 * DAY + 3 * 3600);
+        let users: Vec<(u32, u32)> = counter
+            .stats(now)
+            .iter()
+            .filter(|activity| activity.unique_users > 0)
+            .map(|activity| (activity.hour_of_day, activity.unique_users))
+            .collect();
+        assert_eq!(users, [(2, 2), (14, 1)]);
+        assert_eq!(counter.stats(now).len(), 24);
+
+        // 0xa was last seen at 02:00 more than 7 days ago; 0xb is still in the window
+        counter.prune(now + Duration::from_secs(DAY));
+        let stats = counter.stats(now);
+        assert_eq!(stats[2].unique_users, 1);
+        assert_eq!(stats[2].observed_from, DateTime::UNIX_EPOCH + Duration::from_secs(DAY + 3 * 3600));
+    }
+}
@@ -0,0 +1,242 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Optional persistence of raw DApp interactions to Postgres.
+//!
+//! The live tables only keep aggregates. With PERSIST_INTERACTIONS enabled the
+//! aggregator also hands every applied interaction to this writer, which
+//! inserts them into `dapp_interactions` in batches, so any window can be
+//! reconstructed from the database. The table is partitioned by UTC day: the
+//! partition of a day is created before its first insert, and partitions older
+//! than the retention period are dropped every hour.
+
+use crate::clock::Clock;
+use crate::database::DatabaseManager;
+use crate::models::{DAppInteraction, NewDAppInteractionRecord};
+use chrono::{Days, NaiveDate};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{error, info};
+
+/// How often buffered interactions are written when fewer than a batch arrived
+const FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Buffered interactions that trigger a write
+const BATCH_SIZE: usize = 5_000;
+
+/// Interactions kept buffered while writes fail; older ones are dropped beyond it
+const MAX_BUFFERED: usize = 500_000;
+
+/// How often partitions are created ahead and expired ones dropped
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Rows of `interactions`, numbering the interactions of each transaction in order
+fn to_records(interactions: Vec<DAppInteraction>) -> Vec<NewDAppInteractionRecord> {
+    let mut next_index: HashMap<String, i32> = HashMap::new();
+    interactions
+        .into_iter()
+        .map(|interaction| {
+            let index = next_index.entry(interaction.transaction_digest.clone()).or_default();
+            let record = NewDAppInteractionRecord {
+                timestamp: interaction.timestamp,
+                transaction_digest: interaction.transaction_digest,
+                interaction_index: *index,
+                package_id: interaction.package_id,
+                dapp_name: interaction.dapp_name,
+                sender: interaction.sender,
+                gas_used: interaction.gas_used as i64,
+                event_type: interaction.event_type,
+                sponsored: interaction.sponsored,
+            };
+            *index += 1;
+            record
+        })
+        .collect()
+}
+
+/// Parse a recompute window such as `30m`, `24h` or `7d`
+pub fn parse_window(value: &str) -> anyhow::Result<Duration> {
+    let invalid = || anyhow::anyhow!("Invalid window '{}', expected a number followed by m, h or d (e.g. 24h)", value);
+    let value = value.trim();
+    let (amount, unit_seconds) = match value.char_indices().last() {
+        Some((index, 'm')) => (&value[..index], 60),
+        Some((index, 'h')) => (&value[..index], 60 * 60),
+        Some((index, 'd')) => (&value[..index], 24 * 60 * 60),
+        _ => return Err(invalid()),
+    };
+    match amount.parse::<u64>() {
+        Ok(amount) if amount > 0 => Ok(Duration::from_secs(amount * unit_seconds)),
+        _ => Err(invalid()),
+    }
+}
+
+/// Label of a window in the largest unit that divides it, the inverse of `parse_window`
+pub fn window_label(window: Duration) -> String {
+    let minutes = (window.as_secs() / 60).max(1);
+    match minutes {
+        minutes if minutes % (24 * 60) == 0 => format!("{}d", minutes / (24 * 60)),
+        minutes if minutes % 60 == 0 => format!("{}h", minutes / 60),
+        minutes => format!("{}m", minutes),
+    }
+}
+
+struct InteractionWriter {
+    db_manager: Arc<DatabaseManager>,
+    /// Days whose partition is known to exist
+    partitions: HashSet<NaiveDate>,
+    buffer: Vec<NewDAppInteractionRecord>,
+    /// Full days kept before today, 0 to keep every partition
+    retention_days: u32,
+    /// Decides which day is today
+    clock: Arc<dyn Clock>,
+}
+
+impl InteractionWriter {
+    /// Make sure the partitions of `days` exist
+    async fn create_partitions(&mut self, days: Vec<NaiveDate>) -> anyhow::Result<()> {
+        let missing: Vec<NaiveDate> = days.into_iter().filter(|day| !self.partitions.contains(day)).collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        let db_manager = &self.db_manager;
+        db_manager
+            .with_retry("create interaction partitions", || db_manager.create_interaction_partitions(&missing))
+            .await?;
+        self.partitions.extend(missing);
+        Ok(())
+    }
+
+    /// Write the buffered interactions; they stay buffered if the write fails
+    async fn flush(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+
+        let days: HashSet<NaiveDate> = self.buffer.iter().map(|record| record.timestamp.date_naive()).collect();
+        let result = match self.create_partitions(days.into_iter().collect()).await {
+            Ok(()) => {
+                let db_manager = &self.db_manager;
+                let buffer = &self.buffer;
+                db_manager
+                    .with_retry("save interactions", || db_manager.save_interactions(buffer))
+                    .await
+            }
+            Err(err) => Err(err),
+        };
+
+        match result {
+            Ok(()) => self.buffer.clear(),
+            Err(err) => {
+                error!("❌ Failed to persist {} interactions: {}", self.buffer.len(), err);
+                if self.buffer.len() > MAX_BUFFERED {
+                    let dropped = self.buffer.len() - MAX_BUFFERED;
+                    self.buffer.drain(..dropped);
+                    error!("❌ Dropped {} buffered interactions that could not be persisted", dropped);
+                }
+            }
+        }
+    }
+
+    /// Create today's and tomorrow's partitions and drop the expired ones
+    async fn maintain(&mut self) {
+        let today = self.clock.now().date_naive();
+        if let Err(err) = self.create_partitions(vec![today, today + Days::new(1)]).await {
+            error!("❌ Failed to create interaction partitions: {}", err);
+        }
+
+        if self.retention_days == 0 {
+            return;
+        }
+        let cutoff = today - Days::new(self.retention_days as u64);
+        match self.db_manager.drop_interaction_partitions_before(cutoff).await {
+            Ok(dropped) if !dropped.is_empty() => {
+                self.partitions.retain(|day| *day >= cutoff);
+                info!("🧹 Dropped {} interaction partitions before {}: {}", dropped.len(), cutoff, dropped.join(", "));
+            }
+            Ok(_) => {}
+            Err(err) => error!("❌ Failed to drop expired interaction partitions: {}", err),
+        }
+    }
+}
+
+/// Start the task persisting raw interactions to `dapp_interactions`
+/// Partitions older than `retention_days` full days are dropped (0 keeps them all)
+pub async fn start_interaction_store(
+    db_manager: Arc<DatabaseManager>,
+    retention_days: u32,
+    clock: Arc<dyn Clock>,
+) -> mpsc::Sender<Vec<DAppInteraction>> {
+    let mut writer = InteractionWriter {
+        db_manager,
+        partitions: HashSet::new(),
+        buffer: Vec::new(),
+        retention_days,
+        clock,
+    };
+    let (sender, mut receiver) = mpsc::channel::<Vec<DAppInteraction>>(256);
+    info!("🗄️ Persisting raw interactions to dapp_interactions ({})",
+          if retention_days == 0 { "kept forever".to_string() } else { format!("{} days retention", retention_days) });
+
+    tokio::spawn(async move {
+        let mut flush_ticker = tokio::time::interval(FLUSH_INTERVAL);
+        let mut maintenance_ticker = tokio::time::interval(MAINTENANCE_INTERVAL);
+        loop {
+            tokio::select! {
+                received = receiver.recv() => match received {
+                    Some(interactions) => {
+                        writer.buffer.extend(to_records(interactions));
+                        if writer.buffer.len() >= BATCH_SIZE {
+                            writer.flush().await;
+                        }
+                    }
+                    None => {
+                        writer.flush().await;
+                        break;
+                    }
+                },
+                _ = flush_ticker.tick() => writer.flush().await,
+                _ = maintenance_ticker.tick() => writer.maintain().await,
+            }
+        }
+    });
+
+    sender
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::InteractionBuilder;
+
+    #[test]
+    fn test_interactions_are_numbered_per_transaction() {
+        let records = to_records(vec![
+            InteractionBuilder::new().digest("tx1").event_type("pool::SwapEvent").gas_used(10).seconds(86_400).build(),
+            InteractionBuilder::new().digest("tx2").event_type("pool::SwapEvent").gas_used(10).seconds(86_400).build(),
+            InteractionBuilder::new().digest("tx1").event_type("pool::SwapEvent").gas_used(10).seconds(86_400).build(),
+        ]);
+        let keys: Vec<(&str, i32)> = records
+            .iter()
+            .map(|record| (record.transaction_digest.as_str(), record.interaction_index))
+            .collect();
+        assert_eq!(keys, [("tx1", 0), ("tx2", 0), ("tx1", 1)]);
+        assert_eq!(records[0].timestamp.date_naive(), NaiveDate::from_ymd_opt(1970, 1, 2).unwrap());
+    }
+
+    #[test]
+    fn test_parse_window() {
+        assert_eq!(parse_window("30m").unwrap(), Duration::from_secs(30 * 60));
+        assert_eq!(parse_window("24h").unwrap(), Duration::from_secs(24 * 60 * 60));
+        assert_eq!(parse_window("7d").unwrap(), Duration::from_secs(7 * 24 * 60 * 60));
+        assert!(parse_window("0h").is_err());
+        assert!(parse_window("24").is_err());
+        assert!(parse_window("h").is_err());
+
+        for label in ["30m", "1h", "36h", "7d"] {
+            assert_eq!(window_label(parse_window(label).unwrap()), label);
+        }
+        assert_eq!(window_label(Duration::from_secs(24 * 60 * 60)), "1d");
+    }
+}
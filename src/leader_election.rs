@@ -0,0 +1,127 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Leader election between indexer replicas.
+//!
+//! Replicas compete for a Postgres session-level advisory lock held on a
+//! dedicated connection. The replica holding it is the leader and is the only
+//! one writing to the database; standbys keep ingesting checkpoints into
+//! memory, so a standby that takes over already has the current window. The
+//! lock is released by Postgres as soon as the leader's session ends, and the
+//! leader steps down when its lock connection stops answering.
+
+use crate::database::DatabaseManager;
+use anyhow::Result;
+use diesel::sql_query;
+use diesel::sql_types::{BigInt, Bool};
+use diesel::QueryableByName;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{error, info, warn};
+
+/// Advisory lock key used unless configured otherwise
+pub const DEFAULT_LOCK_KEY: i64 = 0x5355_5246_5355_4931; // "SURFSUI1"
+
+/// Settings for leader election
+#[derive(Debug, Clone)]
+pub struct LeaderElectionSettings {
+    /// Advisory lock identifying the replicas that compete with each other
+    pub lock_key: i64,
+    /// How often standbys try to take the lock and the leader checks it still holds it
+    pub check_interval: Duration,
+}
+
+#[derive(QueryableByName, Debug)]
+struct LockResult {
+    #[diesel(sql_type = Bool)]
+    acquired: bool,
+}
+
+/// Whether this instance may write to the database
+#[derive(Debug, Clone)]
+pub struct Leadership {
+    is_leader: watch::Receiver<bool>,
+}
+
+impl Default for Leadership {
+    /// Always the leader, for deployments without leader election
+    fn default() -> Self {
+        Self { is_leader: watch::channel(true).1 }
+    }
+}
+
+impl Leadership {
+    pub fn is_leader(&self) -> bool {
+        *self.is_leader.borrow()
+    }
+
+    /// Wait until this instance is the leader
+    pub async fn wait_until_leader(&mut self) {
+        // An error means the election task is gone, leaving the last value in place
+        let _ = self.is_leader.wait_for(|is_leader| *is_leader).await;
+    }
+}
+
+/// Connect and try to take the advisory lock
+/// Returns the connection holding the lock, or None if another session holds it
+async fn try_acquire(db_manager: &DatabaseManager, lock_key: i64) -> Result<Option<AsyncPgConnection>> {
+    let mut conn = db_manager.connect().await?;
+    let result: LockResult = sql_query("SELECT pg_try_advisory_lock($1) AS acquired")
+        .bind::<BigInt, _>(lock_key)
+        .get_result(&mut conn)
+        .await?;
+    Ok(result.acquired.then_some(conn))
+}
+
+/// Returns true while the session holding the lock is alive
+/// Session-level advisory locks are only released when the session ends
+async fn still_holds_lock(conn: &mut AsyncPgConnection) -> bool {
+    match sql_query("SELECT true AS acquired").get_result::<LockResult>(conn).await {
+        Ok(result) => result.acquired,
+        Err(err) => {
+            warn!("⚠️ Leader lock check failed: {}", err);
+            false
+        }
+    }
+}
+
+/// Start competing for leadership with the other replicas using the same lock key
+/// The lock is held on a dedicated connection outside the pool of `db_manager`
+pub async fn start_leader_election(db_manager: Arc<DatabaseManager>, settings: LeaderElectionSettings) -> Leadership {
+    let (sender, is_leader) = watch::channel(false);
+
+    tokio::spawn(async move {
+        let mut standby_logged = false;
+        loop {
+            match try_acquire(&db_manager, settings.lock_key).await {
+                Ok(Some(mut conn)) => {
+                    info!("👑 Acquired leader lock, this instance now writes to the database");
+                    sender.send_replace(true);
+                    standby_logged = false;
+
+                    loop {
+                        tokio::time::sleep(settings.check_interval).await;
+                        if !still_holds_lock(&mut conn).await {
+                            break;
+                        }
+                    }
+
+                    sender.send_replace(false);
+                    error!("❌ Lost leader lock, stopped writing to the database");
+                }
+                Ok(None) => {
+                    if !standby_logged {
+                        info!("🕰️ Another instance holds the leader lock, running as warm standby");
+                        standby_logged = true;
+                    }
+                }
+                Err(err) => warn!("⚠️ Failed to try the leader lock: {}", err),
+            }
+            tokio::time::sleep(settings.check_interval).await;
+        }
+    });
+
+    Leadership { is_leader }
+}
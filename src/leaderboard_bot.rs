@@ -0,0 +1,262 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Leaderboard posts to Discord and Telegram.
+//!
+//! With DISCORD_WEBHOOK_URL or TELEGRAM_BOT_TOKEN and TELEGRAM_CHAT_ID set, the
+//! leader posts the top 10 DApps every LEADERBOARD_POST_INTERVAL, with the
+//! movers since the previous post. A DApp of the top 10 moving at least
+//! LEADERBOARD_MOVE_THRESHOLD ranks, or a new leader, is posted right away, at
+//! most once per MIN_CHANGE_POST_GAP so a DApp bouncing around its rank does
+//! not flood the channel. Ranks are counted without "Others" and paused DApps.
+
+use crate::leader_election::Leadership;
+use crate::models::{DAppRanking, OTHERS_PACKAGE_ID};
+use crate::notifier::{DiscordNotifier, FanOutNotifier, Notifier, TelegramNotifier};
+use anyhow::Result;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::time::Instant;
+use tracing::{error, info};
+
+/// DApps in a leaderboard post
+const LEADERBOARD_SIZE: usize = 10;
+
+/// Minimum time between two posts triggered by rank changes
+const MIN_CHANGE_POST_GAP: Duration = Duration::from_secs(15 * 60);
+
+/// Telegram bot and chat the leaderboard is sent to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TelegramSettings {
+    pub bot_token: String,
+    /// Numeric id of the chat, or @username of a public channel
+    pub chat_id: String,
+}
+
+/// Settings for posting the leaderboard
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeaderboardBotSettings {
+    pub discord_webhook_url: Option<String>,
+    pub telegram: Option<TelegramSettings>,
+    /// Time between two scheduled posts
+    pub interval: Duration,
+    /// Ranks a DApp of the top 10 must move to be posted right away (0 disables)
+    pub move_threshold: u32,
+    /// Label of the ranking window, e.g. 1h
+    pub window_label: String,
+}
+
+/// Name and rank of each DApp of a leaderboard, best first
+type Leaderboard = Vec<(String, u32)>;
+
+/// Top DApps of `rankings` without "Others" and paused DApps, ranked by position
+fn leaderboard(rankings: &[DAppRanking]) -> Vec<&DAppRanking> {
+    rankings
+        .iter()
+        .filter(|ranking| ranking.package_id != OTHERS_PACKAGE_ID && ranking.tracking_status != "paused")
+        .take(LEADERBOARD_SIZE)
+        .collect()
+}
+
+/// Name and rank of each DApp of the leaderboard of `rankings`
+fn leaderboard_ranks(rankings: &[DAppRanking]) -> Leaderboard {
+    leaderboard(rankings)
+        .iter()
+        .enumerate()
+        .map(|(position, ranking)| (ranking.dapp_name.clone(), position as u32 + 1))
+        .collect()
+}
+
+/// Whether `current` differs enough from the `posted` leaderboard to be posted right away
+fn is_big_change(current: &Leaderboard, posted: &Leaderboard, move_threshold: u32) -> bool {
+    if posted.is_empty() || move_threshold == 0 {
+        return false;
+    }
+    if current.first().map(|(name, _)| name) != posted.first().map(|(name, _)| name) {
+        return true;
+    }
+    let posted_ranks: HashMap<&str, u32> = posted.iter().map(|(name, rank)| (name.as_str(), *rank)).collect();
+    current.iter().any(|(name, rank)| {
+        posted_ranks.get(name.as_str()).is_some_and(|posted_rank| posted_rank.abs_diff(*rank) >= move_threshold)
+    })
+}
+
+/// Post of the top DApps of `rankings`, with the movers since the `posted` leaderboard
+fn leaderboard_message(rankings: &[DAppRanking], posted: &Leaderboard, window_label: &str) -> String {
+    let top = leaderboard(rankings);
+    let posted_ranks: HashMap<&str, u32> = posted.iter().map(|(name, rank)| (name.as_str(), *rank)).collect();
+    let mut lines = vec![format!("🏆 Top {} Sui DApps by users over the last {}", top.len(), window_label)];
+
+    let mut climbers: Vec<(&str, u32, u32)> = Vec::new();
+    let mut fallers: Vec<(&str, u32, u32)> = Vec::new();
+    let mut new_entries: Vec<(&str, u32)> = Vec::new();
+    for (position, ranking) in top.iter().enumerate() {
+        let rank = position as u32 + 1;
+        let movement = match posted_ranks.get(ranking.dapp_name.as_str()) {
+            _ if posted.is_empty() => String::new(),
+            None => {
+                new_entries.push((&ranking.dapp_name, rank));
+                " 🆕".to_string()
+            }
+            Some(&posted_rank) if posted_rank > rank => {
+                climbers.push((&ranking.dapp_name, posted_rank - rank, rank));
+                format!(" ▲{}", posted_rank - rank)
+            }
+            Some(&posted_rank) if posted_rank < rank => {
+                fallers.push((&ranking.dapp_name, rank - posted_rank, rank));
+                format!(" ▼{}", rank - posted_rank)
+            }
+            Some(_) => String::new(),
+        };
+        lines.push(format!("{}. {} - {} users{}", rank, ranking.dapp_name, ranking.active_users, movement));
+    }
+
+    let dropped: Vec<&str> = posted
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .filter(|name| !top.iter().any(|ranking| ranking.dapp_name == *name))
+        .collect();
+    if climbers.is_empty() && fallers.is_empty() && new_entries.is_empty() && dropped.is_empty() {
+        return lines.join("\n");
+    }
+    lines.push(String::new());
+    // Ties go to the best ranked DApp
+    if let Some((name, gain, rank)) = climbers.iter().max_by_key(|(_, gain, rank)| (*gain, Reverse(*rank))) {
+        lines.push(format!("📈 {} climbed {} to #{}", name, gain, rank));
+    }
+    if let Some((name, loss, rank)) = fallers.iter().max_by_key(|(_, loss, rank)| (*loss, Reverse(*rank))) {
+        lines.push(format!("📉 {} fell {} to #{}", name, loss, rank));
+    }
+    for (name, rank) in new_entries {
+        lines.push(format!("🆕 {} entered at #{}", name, rank));
+    }
+    if !dropped.is_empty() {
+        lines.push(format!("👋 Dropped out: {}", dropped.join(", ")));
+    }
+    lines.join("\n")
+}
+
+/// Discord and Telegram notifiers of `settings`
+fn leaderboard_notifier(settings: &LeaderboardBotSettings) -> Result<FanOutNotifier> {
+    let mut notifiers: Vec<Arc<dyn Notifier>> = Vec::new();
+    if let Some(url) = &settings.discord_webhook_url {
+        notifiers.push(Arc::new(DiscordNotifier::new(url)?));
+    }
+    if let Some(telegram) = &settings.telegram {
+        notifiers.push(Arc::new(TelegramNotifier::new(&telegram.bot_token, &telegram.chat_id)?));
+    }
+    Ok(FanOutNotifier(notifiers))
+}
+
+/// Start the task posting the leaderboard on schedule and on big rank changes
+pub async fn start_leaderboard_bot(
+    mut rankings_updates: watch::Receiver<Arc<Vec<DAppRanking>>>,
+    settings: LeaderboardBotSettings,
+    leadership: Leadership,
+) -> Result<()> {
+    let notifier = leaderboard_notifier(&settings)?;
+    info!("📣 Posting the top {} DApps every {}s and on moves of {}+ ranks",
+          LEADERBOARD_SIZE, settings.interval.as_secs(), settings.move_threshold);
+
+    tokio::spawn(async move {
+        let mut posted: Leaderboard = Vec::new();
+        let mut last_post: Option<Instant> = None;
+        // The first post is scheduled a full interval after startup, not on every restart
+        let mut schedule = tokio::time::interval_at(Instant::now() + settings.interval, settings.interval);
+        loop {
+            let scheduled = tokio::select! {
+                _ = schedule.tick() => true,
+                changed = rankings_updates.changed() => match changed {
+                    Ok(()) => false,
+                    Err(_) => break,
+                },
+            };
+            let rankings = rankings_updates.borrow_and_update().clone();
+            if !leadership.is_leader() || rankings.is_empty() {
+                continue;
+            }
+
+            let current = leaderboard_ranks(&rankings);
+            let due = scheduled
+                || (is_big_change(&current, &posted, settings.move_threshold)
+                    && last_post.is_none_or(|last_post| last_post.elapsed() >= MIN_CHANGE_POST_GAP));
+            if !due {
+                continue;
+            }
+            match notifier.notify(&leaderboard_message(&rankings, &posted, &settings.window_label)).await {
+                Ok(()) => {
+                    posted = current;
+                    last_post = Some(Instant::now());
+                }
+                Err(err) => error!("❌ Failed to post the leaderboard: {}", err),
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dapp_indexer::compute_rankings;
+    use crate::models::{DAppInteraction, DAppRegistryEntry};
+    use crate::test_utils::InteractionBuilder;
+    use chrono::{DateTime, Utc};
+
+    fn rankings(users: &[(&str, usize)]) -> Vec<DAppRanking> {
+        let now = DateTime::<Utc>::UNIX_EPOCH + Duration::from_secs(100 * 3600);
+        let registry: HashMap<String, DAppRegistryEntry> = users
+            .iter()
+            .map(|(name, _)| (format!("0x{}", name), DAppRegistryEntry::new(name, "DeFi")))
+            .collect();
+        let interactions: Vec<DAppInteraction> = users
+            .iter()
+            .flat_map(|(name, count)| {
+                (0..*count).map(move |sender| {
+                    InteractionBuilder::new()
+                        .package(&format!("0x{}", name))
+                        .sender(&format!("0x{}", sender))
+                        .digest(&format!("{}-{}", name, sender))
+                        .gas_used(10)
+                        .at(now - Duration::from_secs(60))
+                        .build()
+                })
+            })
+            .collect();
+        compute_rankings(&interactions, &registry, Duration::from_secs(3600), now)
+    }
+
+    #[test]
+    fn test_posts_top_dapps_with_movers() {
+        let before = rankings(&[("Cetus", 9), ("Pyth", 8), ("Suilend", 7), ("Navi", 6), ("Scallop", 5)]);
+        let first = leaderboard_message(&before, &Vec::new(), "1h");
+        assert!(first.starts_with("🏆 Top 5 Sui DApps by users over the last 1h\n1. Cetus - 9 users\n"));
+        assert!(!first.contains('▲'));
+
+        let after = rankings(&[("Cetus", 9), ("Scallop", 8), ("Pyth", 7), ("Suilend", 6), ("Momentum", 5)]);
+        let posted = leaderboard_ranks(&before);
+        assert!(is_big_change(&leaderboard_ranks(&after), &posted, 3));
+        assert!(!is_big_change(&leaderboard_ranks(&after), &posted, 4));
+        assert!(!is_big_change(&leaderboard_ranks(&after), &posted, 0));
+        assert_eq!(leaderboard_message(&after, &posted, "1h"), [
+            "🏆 Top 5 Sui DApps by users over the last 1h",
+            "1. Cetus - 9 users",
+            "2. Scallop - 8 users ▲3",
+            "3. Pyth - 7 users ▼1",
+            "4. Suilend - 6 users ▼1",
+            "5. Momentum - 5 users 🆕",
+            "",
+            "📈 Scallop climbed 3 to #2",
+            "📉 Pyth fell 1 to #3",
+            "🆕 Momentum entered at #5",
+            "👋 Dropped out: Navi",
+        ].join("\n"));
+
+        // A new leader is always posted
+        let new_leader = rankings(&[("Pyth", 10), ("Cetus", 9), ("Suilend", 7), ("Navi", 6), ("Scallop", 5)]);
+        assert!(is_big_change(&leaderboard_ranks(&new_leader), &posted, 5));
+    }
+}
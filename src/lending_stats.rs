@@ -0,0 +1,396 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Deposit and borrow activity of lending protocols over the last 24 hours.
+//!
+//! DApps of the `Lending` category also get protocol-specific figures. Their
+//! events are matched against the exact types Suilend
+//! (`lending_market::DepositEvent`, `BorrowEvent`, ...) and NAVI
+//! (`lending::DepositEvent`, `BorrowEvent`, ...) emit: deposits, withdrawals,
+//! borrows and repayments are counted in hourly buckets like the event stats,
+//! and depositors and borrowers are remembered with their last action so
+//! distinct users cover exactly the last 24 hours. Deposit and borrow events
+//! are also decoded for their coin type and amount, counted raw per coin and
+//! only valued in USD when the stats are computed, through a `PriceSource`;
+//! coins it has no price for are left out of the volumes.
+
+use crate::event_stats::{ActivityCounter, RecentUsers};
+use crate::models::{DAppInteraction, DAppLendingStats};
+use crate::swap_volume::{normalize_coin_type, PriceSource};
+use std::collections::BTreeMap;
+use chrono::{DateTime, Utc};
+
+/// Registry category whose DApps get lending stats
+pub const LENDING_DAPP_TYPE: &str = "Lending";
+
+/// Label of deposit events in the hourly counts
+const DEPOSIT: &str = "deposit";
+/// Label of withdraw events in the hourly counts
+const WITHDRAW: &str = "withdraw";
+/// Label of borrow events in the hourly counts
+const BORROW: &str = "borrow";
+/// Label of repay events in the hourly counts
+const REPAY: &str = "repay";
+
+/// Lending action an interaction's event type decodes to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LendingEventKind {
+    Deposit,
+    Withdraw,
+    Borrow,
+    Repay,
+}
+
+/// Lending events of Suilend (`lending_market`) and NAVI (`lending`), as `module::struct`
+const LENDING_EVENTS: [(&str, LendingEventKind); 8] = [
+    ("lending_market::DepositEvent", LendingEventKind::Deposit),
+    ("lending_market::WithdrawEvent", LendingEventKind::Withdraw),
+    ("lending_market::BorrowEvent", LendingEventKind::Borrow),
+    ("lending_market::RepayEvent", LendingEventKind::Repay),
+    ("lending::DepositEvent", LendingEventKind::Deposit),
+    ("lending::WithdrawEvent", LendingEventKind::Withdraw),
+    ("lending::BorrowEvent", LendingEventKind::Borrow),
+    ("lending::RepayEvent", LendingEventKind::Repay),
+];
+
+/// Coin types of NAVI's reserves by reserve id; events of other reserves get no volume
+const NAVI_RESERVES: [(u8, &str); 9] = [
+    (0, "0x2::sui::SUI"),
+    (1, "0x5d4b302506645c37ff133b98c4b50a5ae14841659738d6d733d59d0d217a93bf::coin::COIN"),
+    (2, "0xc060006111016b8a020ad5b33834984a437aaa7d3c74c18e09a95d48aceab08c::coin::COIN"),
+    (3, "0xaf8cd5edc19c4512f4259f0bee101a40d41ebed738ade5874359610ef8eeced5::coin::COIN"),
+    (4, "0x06864a6f921804860930db6ddbe2e16acdf8504495ea7481637a1c8b9a8fe54b::cetus::CETUS"),
+    (5, "0x549e8b69270defbfafd4f94e17ec44cdbdd99820b33bda2278dea3b9a32d3f55::cert::CERT"),
+    (6, "0xbde4ba4c2e274a60ce15c1cfff9e5c42e41654ac8b6d906a57efa4bd3c29f47d::hasui::HASUI"),
+    (7, "0xa99b8952d4f7d947ea77fe0ecdcc9e5fc0bcab2841d6e2a5aa00c3044e5544b5::navx::NAVX"),
+    (10, "0xdba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7::usdc::USDC"),
+];
+
+impl LendingEventKind {
+    /// Decode an event type given as `module::struct`; None for other events
+    pub fn from_event_type(event_type: &str) -> Option<Self> {
+        LENDING_EVENTS
+            .iter()
+            .find(|(lending_event, _)| *lending_event == event_type)
+            .map(|(_, kind)| *kind)
+    }
+
+    /// Label in the hourly counts
+    fn label(&self) -> &'static str {
+        match self {
+            LendingEventKind::Deposit => DEPOSIT,
+            LendingEventKind::Withdraw => WITHDRAW,
+            LendingEventKind::Borrow => BORROW,
+            LendingEventKind::Repay => REPAY,
+        }
+    }
+}
+
+/**
+ * LendingFlow is a deposit or borrow decoded from an event of a tracked lending DApp
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LendingFlow {
+    pub dapp_name: String,              // Lending DApp the event belongs to
+    pub kind: LendingEventKind,         // Deposit or Borrow
+    pub coin_type: String,              // Canonical type of the deposited or borrowed coin
+    pub amount: u64,                    // Amount in the coin's base units
+    pub timestamp: DateTime<Utc>,       // When the transaction occurred
+}
+
+/// Decode the coin type and amount of a deposit or borrow event given as
+/// `module::struct` with its BCS contents; None for other events
+///
+/// Suilend's `DepositEvent` and `BorrowEvent` are `{ lending_market_id: address,
+/// coin_type: TypeName, reserve_id: address, obligation_id: address, amount: u64, .. }`.
+/// Deposits are in cTokens, which are worth slightly more than the underlying
+/// coin, so Suilend's deposit volume is a small underestimate. NAVI's are
+/// `{ reserve: u8, sender: address, amount: u64 }`, the reserve being looked up
+/// in `NAVI_RESERVES`
+pub fn decode_lending_amount(event_type: &str, contents: &[u8]) -> Option<(LendingEventKind, String, u64)> {
+    let kind = LendingEventKind::from_event_type(event_type)
+        .filter(|kind| matches!(kind, LendingEventKind::Deposit | LendingEventKind::Borrow))?;
+    let (coin_type, amount) = if event_type.starts_with("lending_market::") {
+        let mut rest = contents.get(32..)?;
+        let mut length = 0usize;
+        for shift in (0..64).step_by(7) {
+            let (&byte, tail) = rest.split_first()?;
+            rest = tail;
+            length |= ((byte & 0x7f) as usize) << shift;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+        // TypeName holds the address without its 0x prefix
+        let type_name = std::str::from_utf8(rest.get(..length)?).ok()?;
+        let amount = rest.get(length + 32 + 32..length + 32 + 32 + 8)?;
+        (normalize_coin_type(&format!("0x{}", type_name))?, u64::from_le_bytes(amount.try_into().ok()?))
+    } else {
+        let reserve = *contents.first()?;
+        let (_, coin_type) = NAVI_RESERVES.iter().find(|(id, _)| *id == reserve)?;
+        let amount = contents.get(1 + 32..1 + 32 + 8)?;
+        (normalize_coin_type(coin_type)?, u64::from_le_bytes(amount.try_into().ok()?))
+    };
+    Some((kind, coin_type, amount))
+}
+
+/// Rolling 24h lending actions, depositors, borrowers and amounts per lending DApp
+#[derive(Debug, Clone, Default)]
+pub struct LendingActivityCounter {
+    /// Hourly counts per (DApp, action)
+    counts: ActivityCounter,
+    /// Depositors per DApp with their last deposit
+    depositors: RecentUsers,
+    /// Borrowers per DApp with their last borrow
+    borrowers: RecentUsers,
+    /// Hourly raw deposited amounts, labelled by coin type
+    deposit_amounts: ActivityCounter,
+    /// Hourly raw borrowed amounts, labelled by coin type
+    borrow_amounts: ActivityCounter,
+}
+
+impl LendingActivityCounter {
+    /// Count the lending actions among the interactions of lending DApps
+    pub fn record<'a>(&mut self, interactions: impl IntoIterator<Item = &'a DAppInteraction>) {
+        for interaction in interactions {
+            let Some(dapp_name) = &interaction.dapp_name else {
+                continue;
+            };
+            let Some(kind) = LendingEventKind::from_event_type(&interaction.event_type) else {
+                continue;
+            };
+            self.counts.add(dapp_name, kind.label(), interaction.timestamp);
+            match kind {
+                LendingEventKind::Deposit => self.depositors.add(dapp_name, &interaction.sender, interaction.timestamp),
+                LendingEventKind::Borrow => self.borrowers.add(dapp_name, &interaction.sender, interaction.timestamp),
+                LendingEventKind::Withdraw | LendingEventKind::Repay => {}
+            }
+        }
+    }
+
+    /// Add the amounts of decoded deposits and borrows
+    pub fn record_flows(&mut self, flows: &[LendingFlow]) {
+        for flow in flows {
+            let amounts = match flow.kind {
+                LendingEventKind::Deposit => &mut self.deposit_amounts,
+                LendingEventKind::Borrow => &mut self.borrow_amounts,
+                LendingEventKind::Withdraw | LendingEventKind::Repay => continue,
+            };
+            amounts.add_amount(&flow.dapp_name, &flow.coin_type, flow.timestamp, flow.amount);
+        }
+    }
+
+    /// Forget actions older than 24 hours
+    pub fn prune(&mut self, now: DateTime<Utc>) {
+        self.counts.prune(now);
+        self.depositors.prune(now);
+        self.borrowers.prune(now);
+        self.deposit_amounts.prune(now);
+        self.borrow_amounts.prune(now);
+    }
+
+    /// Stats of every lending DApp with actions in the last 24 hours, by DApp,
+    /// with deposit and borrow amounts valued by `prices`
+    pub fn stats(&self, prices: &dyn PriceSource, now: DateTime<Utc>) -> Vec<DAppLendingStats> {
+        let mut stats: BTreeMap<String, DAppLendingStats> = BTreeMap::new();
+        let new_stats = |dapp_name: String| DAppLendingStats {
+            unique_depositors_24h: self.depositors.count(&dapp_name),
+            unique_borrowers_24h: self.borrowers.count(&dapp_name),
+            dapp_name,
+            deposits_24h: 0,
+            withdrawals_24h: 0,
+            borrows_24h: 0,
+            repays_24h: 0,
+            deposit_volume_24h_usd: 0.0,
+            borrow_volume_24h_usd: 0.0,
+            last_update: now,
+        };
+        for (dapp_name, label, count) in self.counts.totals(now) {
+            let entry = stats.entry(dapp_name.clone()).or_insert_with(|| new_stats(dapp_name));
+            match label.as_str() {
+                DEPOSIT => entry.deposits_24h = count,
+                WITHDRAW => entry.withdrawals_24h = count,
+                BORROW => entry.borrows_24h = count,
+                _ => entry.repays_24h = count,
+            }
+        }
+        for (dapp_name, coin_type, amount) in self.deposit_amounts.totals(now) {
+            let value = prices.usd_value(&coin_type, amount).unwrap_or(0.0);
+            stats.entry(dapp_name.clone()).or_insert_with(|| new_stats(dapp_name)).deposit_volume_24h_usd += value;
+        }
+        for (dapp_name, coin_type, amount) in self.borrow_amounts.totals(now) {
+            let value = prices.usd_value(&coin_type, amount).unwrap_or(0.0);
+            stats.entry(dapp_name.clone()).or_insert_with(|| new_stats(dapp_name)).borrow_volume_24h_usd += value;
+        }
+        stats.into_values().collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.counts.clear();
+        self.depositors.clear();
+        self.borrowers.clear();
+        self.deposit_amounts.clear();
+        self.borrow_amounts.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swap_volume::StaticPrices;
+    use crate::test_utils::InteractionBuilder;
+    use std::time::Duration;
+
+    const USDC: &str = "0xdba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7::usdc::USDC";
+
+    /// BCS contents of a Suilend deposit or borrow event
+    fn suilend_event(coin_type: &str, amount: u64) -> Vec<u8> {
+        let type_name = coin_type.trim_start_matches("0x");
+        let mut contents = vec![0x11; 32];
+        contents.push(type_name.len() as u8);
+        contents.extend(type_name.as_bytes());
+        contents.extend([0x22; 64]);
+        contents.extend(amount.to_le_bytes());
+        // Borrows also carry the origination fee
+        contents.extend(5u64.to_le_bytes());
+        contents
+    }
+
+    /// BCS contents of a NAVI deposit or borrow event
+    fn navi_event(reserve: u8, amount: u64) -> Vec<u8> {
+        let mut contents = vec![reserve];
+        contents.extend([0x33; 32]);
+        contents.extend(amount.to_le_bytes());
+        contents
+    }
+
+    fn flow(dapp_name: &str, kind: LendingEventKind, coin_type: &str, amount: u64, hours_ago: i64, now: DateTime<Utc>) -> LendingFlow {
+        LendingFlow {
+            dapp_name: dapp_name.to_string(),
+            kind,
+            coin_type: normalize_coin_type(coin_type).unwrap(),
+            amount,
+            timestamp: now - chrono::Duration::hours(hours_ago),
+        }
+    }
+
+
+    #[test]
+    fn test_counts_suilend_and_navi_actions_over_24h() {
+        assert_eq!(LendingEventKind::from_event_type("lending_market::DepositEvent"), Some(LendingEventKind::Deposit));
+        assert_eq!(LendingEventKind::from_event_type("lending::RepayEvent"), Some(LendingEventKind::Repay));
+        assert_eq!(LendingEventKind::from_event_type("lending::LiquidationEvent"), None);
+        // Names made of several actions, or merely containing one, are not guessed at
+        assert_eq!(LendingEventKind::from_event_type("lending_market::RepayBorrowEvent"), None);
+        assert_eq!(LendingEventKind::from_event_type("lending::WithdrawDepositEvent"), None);
+        assert_eq!(LendingEventKind::from_event_type("lending_market::DepositEventV2"), None);
+        assert_eq!(LendingEventKind::from_event_type("pool::DepositEvent"), None);
+
+        let now = DateTime::UNIX_EPOCH + Duration::from_secs(100 * 3600);
+        let mut counter = LendingActivityCounter::default();
+        counter.record(&[
+            InteractionBuilder::new().dapp("Suilend").sender("0xa").event_type("lending_market::DepositEvent").hours_before(now, 1).build(),
+            InteractionBuilder::new().dapp("Suilend").sender("0xa").event_type("lending_market::DepositEvent").hours_before(now, 2).build(),
+            InteractionBuilder::new().dapp("Suilend").sender("0xb").event_type("lending_market::BorrowEvent").hours_before(now, 3).build(),
+            InteractionBuilder::new().dapp("Suilend").sender("0xb").event_type("lending_market::RepayEvent").hours_before(now, 30).build(),
+            InteractionBuilder::new().dapp("NAVI").sender("0xc").event_type("lending::WithdrawEvent").hours_before(now, 1).build(),
+            InteractionBuilder::new().dapp("NAVI").sender("0xc").event_type("lending::LiquidationEvent").hours_before(now, 1).build(),
+        ]);
+        counter.prune(now);
+
+        let stats = counter.stats(&StaticPrices::default(), now);
+        let stats: Vec<(&str, u64, u64, u64, u64, u32, u32)> = stats
+            .iter()
+            .map(|stats| (
+                stats.dapp_name.as_str(),
+                stats.deposits_24h,
+                stats.withdrawals_24h,
+                stats.borrows_24h,
+                stats.repays_24h,
+                stats.unique_depositors_24h,
+                stats.unique_borrowers_24h,
+            ))
+            .collect();
+        assert_eq!(stats, [("NAVI", 0, 1, 0, 0, 0, 0), ("Suilend", 2, 0, 1, 0, 1, 1)]);
+    }
+
+    #[test]
+    fn test_decodes_suilend_and_navi_deposit_and_borrow_amounts() {
+        let usdc = normalize_coin_type(USDC).unwrap();
+        assert_eq!(
+            decode_lending_amount("lending_market::DepositEvent", &suilend_event(USDC, 2_000_000)),
+            Some((LendingEventKind::Deposit, usdc.clone(), 2_000_000))
+        );
+        assert_eq!(
+            decode_lending_amount("lending_market::BorrowEvent", &suilend_event(USDC, 750_000)),
+            Some((LendingEventKind::Borrow, usdc.clone(), 750_000))
+        );
+        assert_eq!(
+            decode_lending_amount("lending::DepositEvent", &navi_event(0, 3_000_000_000)),
+            Some((LendingEventKind::Deposit, normalize_coin_type("0x2::sui::SUI").unwrap(), 3_000_000_000))
+        );
+        assert_eq!(
+            decode_lending_amount("lending::BorrowEvent", &navi_event(10, 1_000_000)),
+            Some((LendingEventKind::Borrow, usdc, 1_000_000))
+        );
+        // Withdrawals and repayments are not volume, unknown reserves have no coin type
+        assert_eq!(decode_lending_amount("lending_market::WithdrawEvent", &suilend_event(USDC, 1)), None);
+        assert_eq!(decode_lending_amount("lending::RepayEvent", &navi_event(0, 1)), None);
+        assert_eq!(decode_lending_amount("lending::DepositEvent", &navi_event(200, 1)), None);
+        // Truncated contents are rejected rather than misread
+        assert_eq!(decode_lending_amount("lending::DepositEvent", &navi_event(0, 1)[..20]), None);
+        assert_eq!(decode_lending_amount("lending_market::DepositEvent", &suilend_event(USDC, 1)[..100]), None);
+    }
+
+    #[test]
+    fn test_values_deposits_and_borrows_of_the_last_24h() {
+        let now = DateTime::UNIX_EPOCH + Duration::from_secs(100 * 3600);
+        let prices = StaticPrices::parse(&format!("{}=1:6,0x2::sui::SUI=2:9", USDC)).unwrap();
+        let mut counter = LendingActivityCounter::default();
+        counter.record_flows(&[
+            flow("Suilend", LendingEventKind::Deposit, USDC, 2_000_000, 1, now),
+            flow("Suilend", LendingEventKind::Deposit, "0x2::sui::SUI", 1_500_000_000, 2, now),
+            flow("Suilend", LendingEventKind::Borrow, USDC, 500_000, 3, now),
+            // Older than 24h
+            flow("Suilend", LendingEventKind::Borrow, USDC, 9_000_000, 30, now),
+            // No price
+            flow("NAVI", LendingEventKind::Deposit, "0x2::unpriced::COIN", 7, 1, now),
+            flow("NAVI", LendingEventKind::Borrow, "0x2::sui::SUI", 500_000_000, 1, now),
+        ]);
+        counter.prune(now);
+
+        let stats = counter.stats(&prices, now);
+        let volumes: Vec<(&str, f64, f64)> = stats
+            .iter()
+            .map(|stats| (stats.dapp_name.as_str(), stats.deposit_volume_24h_usd, stats.borrow_volume_24h_usd))
+            .collect();
+        assert_eq!(volumes, [("NAVI", 0.0, 1.0), ("Suilend", 5.0, 0.5)]);
+    }
+
+    #[test]
+    fn test_unknown_events_and_dapps_are_ignored() {
+        let now = DateTime::UNIX_EPOCH + Duration::from_secs(100 * 3600);
+        let mut counter = LendingActivityCounter::default();
+        counter.record(&[
+            // A protocol with only unknown events gets no stats
+            InteractionBuilder::new().dapp("Scallop").event_type("reserve::FlashLoanEvent").at(now).build(),
+            InteractionBuilder::new().dapp("Scallop").event_type("lending::LiquidationEvent").at(now).build(),
+            // Not attributed to any DApp
+            InteractionBuilder::new().package("0xunknown").event_type("lending::DepositEvent").at(now).build(),
+            // Its hour left the window
+            InteractionBuilder::new().dapp("NAVI").event_type("lending::BorrowEvent").hours_before(now, 24).build(),
+            InteractionBuilder::new().dapp("NAVI").event_type("lending::BorrowEvent").hours_before(now, 23).build(),
+        ]);
+        // Withdrawals and repayments carry no volume
+        counter.record_flows(&[flow("NAVI", LendingEventKind::Repay, USDC, 1_000_000, 1, now)]);
+        counter.prune(now);
+
+        let prices = StaticPrices::parse(&format!("{}=1:6", USDC)).unwrap();
+        let stats = counter.stats(&prices, now);
+        let stats: Vec<(&str, u64, u64, f64)> = stats
+            .iter()
+            .map(|stats| (stats.dapp_name.as_str(), stats.deposits_24h, stats.borrows_24h, stats.borrow_volume_24h_usd))
+            .collect();
+        assert_eq!(stats, [("NAVI", 0, 1, 0.0)]);
+    }
+}
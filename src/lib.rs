@@ -7,17 +7,77 @@
 //! specifically focusing on DApp ranking based on Hourly Active Users (HAU).
 
 // Core modules
+pub mod active_users;
+pub mod aggregator;
+pub mod anomaly;
+pub mod api;
+pub mod archiver;
+pub mod backtest;
+pub mod bridge_stats;
+pub mod calibration;
+#[cfg(feature = "client")]
+pub mod client;
+pub mod checkpoint_gc;
+pub mod clock;
 pub mod config;
+pub mod config_file;
 pub mod database;
+pub mod db_retry;
 pub mod dapp_indexer;
+pub mod dapp_search;
+pub mod dapp_webhooks;
+pub mod epoch_stats;
+pub mod error_reporting;
+pub mod event_stats;
+pub mod export;
+pub mod fullnode_subscription;
+pub mod graphql;
+pub mod grpc;
+pub mod hourly_activity;
+pub mod interaction_store;
+pub mod leader_election;
+pub mod leaderboard_bot;
+pub mod lending_stats;
+pub mod logging;
+pub mod memory_guard;
 pub mod models;
+pub mod nft_stats;
+pub mod notifier;
+pub mod profiling;
+pub mod progress_store;
+pub mod pushgateway;
+pub mod ranking_profiles;
+pub mod ranking_query;
+pub mod ranking_snapshots;
+pub mod ranking_score;
+pub mod redis_cache;
+pub mod rate_limit;
+pub mod remote_storage;
+pub mod response_cache;
+pub mod retention;
+pub mod runtime_config;
+pub mod runtime_metrics;
 pub mod schema;
+pub mod sender_filter;
+#[cfg(any(test, feature = "simulation"))]
+pub mod simulation;
+pub mod snapshot_export;
+pub mod startup;
+pub mod suins;
+pub mod swap_volume;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+pub mod user_sketch;
+pub mod warm_up;
+pub mod write_coalescer;
 
 // Re-export commonly used types
 pub use config::{init_config, get_config};
 pub use database::DatabaseManager;
-pub use dapp_indexer::DAppIndexer;
-pub use models::{DAppInteraction, DAppRanking, DAppRankingRecord};
+pub use dapp_indexer::{compute_rankings, DAppIndexer};
+pub use runtime_config::{RuntimeConfig, RuntimeSettings};
+pub use models::{CategoryStats, DAppInteraction, DAppRanking, DAppRankingRecord};
+pub use sender_filter::{SenderAccessList, SenderFilterChain, SenderFilterRule};
 
 // Re-export Sui types for checkpoint processing
 pub use sui_types::full_checkpoint_content::{CheckpointData, CheckpointTransaction};
@@ -0,0 +1,142 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Log output setup.
+//!
+//! Logs are written either in the human-readable format used during
+//! development or as one JSON object per line, so production deployments can
+//! ship them to a log pipeline and query structured fields such as the
+//! checkpoint number or DApp name. The level filter sits behind a reload handle
+//! so verbosity can change at runtime.
+//!
+//! Spans can additionally be exported over OTLP/HTTP to a collector such as
+//! Jaeger or Tempo, to see where checkpoint processing spends its time.
+//!
+//! With ERROR_REPORTING_URL set, ERROR events and panics are also reported to
+//! an error tracker (see the `error_reporting` module).
+//!
+//! With the `tokio-console` feature (and `RUSTFLAGS="--cfg tokio_unstable"`),
+//! a console-subscriber layer serves the runtime's tasks to `tokio-console` on
+//! TOKIO_CONSOLE_BIND (127.0.0.1:6669 by default). The log level only filters
+//! the log and span output, so the console still sees the runtime's own
+//! trace-level instrumentation.
+
+use crate::error_reporting::{is_reported, start_error_reporting, ErrorReportingSettings};
+use anyhow::{anyhow, Result};
+use opentelemetry::trace::TracerProvider;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use std::str::FromStr;
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::filter::filter_fn;
+use tracing_subscriber::{fmt, reload, Layer, Registry};
+
+/// Handle changing the log level of the installed subscriber
+pub type LogLevelHandle = reload::Handle<LevelFilter, Registry>;
+
+/// Output format of the logs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Colored, human-readable lines
+    #[default]
+    Pretty,
+    /// One JSON object per line with event fields at the top level
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "pretty" | "text" => Ok(LogFormat::Pretty),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(anyhow!("Unknown log format '{}', expected json or pretty", value)),
+        }
+    }
+}
+
+/// Where spans are exported over OTLP/HTTP
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OtlpSettings {
+    /// Collector base URL, e.g. http://localhost:4318
+    pub endpoint: String,
+    /// `service.name` of the exported spans
+    pub service_name: String,
+}
+
+impl OtlpSettings {
+    /// Read the standard `OTEL_EXPORTER_OTLP_ENDPOINT` and `OTEL_SERVICE_NAME` variables
+    /// Returns None when no endpoint is set, which disables span export
+    pub fn from_env() -> Option<Self> {
+        let endpoint = std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok().filter(|endpoint| !endpoint.is_empty())?;
+        let service_name = std::env::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| "dapp-ranking-indexer".to_string());
+        Some(Self { endpoint, service_name })
+    }
+}
+
+/// Flushes exported spans when dropped
+pub struct TracingGuard {
+    provider: Option<SdkTracerProvider>,
+}
+
+impl Drop for TracingGuard {
+    fn drop(&mut self) {
+        if let Some(provider) = self.provider.take() {
+            if let Err(err) = provider.shutdown() {
+                eprintln!("Failed to flush exported spans: {}", err);
+            }
+        }
+    }
+}
+
+fn tracer_provider(settings: &OtlpSettings) -> Result<SdkTracerProvider> {
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(format!("{}/v1/traces", settings.endpoint.trim_end_matches('/')))
+        .build()?;
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name(settings.service_name.clone()).build())
+        .build())
+}
+
+/// Install the global subscriber writing logs in `format` at INFO level,
+/// exporting spans to `otlp` and reporting errors to `error_reporting` if set
+/// Must be called within a tokio runtime when error reporting is enabled
+pub fn init_logging(
+    format: LogFormat,
+    otlp: Option<&OtlpSettings>,
+    error_reporting: Option<&ErrorReportingSettings>,
+) -> Result<(LogLevelHandle, TracingGuard)> {
+    let (level_filter, handle) = reload::Layer::new(LevelFilter::INFO);
+    let provider = otlp.map(tracer_provider).transpose()?;
+    let mut layers: Vec<Box<dyn Layer<Registry> + Send + Sync>> = Vec::new();
+    if let Some(provider) = &provider {
+        layers.push(tracing_opentelemetry::layer().with_tracer(provider.tracer("suins-indexer")).boxed());
+    }
+    layers.push(match format {
+        LogFormat::Pretty => fmt::layer()
+            .with_target(false)  // Don't show module targets
+            .with_ansi(true)     // Enable colored output
+            .boxed(),
+        LogFormat::Json => fmt::layer()
+            .json()
+            .flatten_event(true)
+            .with_current_span(false)
+            .boxed(),
+    });
+    let error_reporting = error_reporting.map(start_error_reporting).transpose()?;
+    // The level filters logs and exported spans only, not error reports or the console layer
+    let subscriber = tracing_subscriber::registry()
+        .with(layers.with_filter(level_filter))
+        .with(error_reporting.map(|layer| layer.with_filter(filter_fn(is_reported))));
+    #[cfg(feature = "tokio-console")]
+    let subscriber = subscriber.with(console_subscriber::spawn());
+    subscriber.init();
+
+    Ok((handle, TracingGuard { provider }))
+}
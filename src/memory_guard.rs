@@ -0,0 +1,323 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Memory usage of the in-memory windows and a cap on it.
+//!
+//! Every REPORT_INTERVAL the interactions kept for the ranking window and for
+//! each ranking profile, and the senders counted for today's top users, are
+//! reported as `indexer_memory_entries` and `indexer_memory_estimated_bytes`
+//! by store. Bytes are estimated from the struct sizes and string capacities,
+//! not measured by the allocator, so they are a lower bound.
+//!
+//! With MEMORY_CAP_MB set, going over the cap degrades the windows instead of
+//! letting the pod be OOM-killed, until they fit in CAP_TARGET_PERCENT of it:
+//! 1. Interactions are compacted to one per transaction, DApp and sender, which
+//!    keeps HAU, transaction counts and gas exact and only changes the
+//!    per-user interaction percentiles and rate-based sender filters.
+//! 2. If that is not enough, the oldest interactions are dropped, from the
+//!    ranking profiles first and from the ranking window last, so the windows
+//!    effectively shrink until traffic calms down.
+
+use crate::dapp_indexer::DAppIndexer;
+use crate::models::DAppInteraction;
+use anyhow::Result;
+use prometheus::{
+    register_int_counter_with_registry, register_int_gauge_vec_with_registry, register_int_gauge_with_registry,
+    IntCounter, IntGauge, IntGaugeVec, Registry,
+};
+use std::collections::HashSet;
+use std::mem::size_of;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::warn;
+
+/// Time between two reports of the memory usage
+const REPORT_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Share of the cap the windows are brought back to once it is exceeded
+const CAP_TARGET_PERCENT: usize = 90;
+
+/// Store label of the interactions of the ranking window
+pub const RANKING_WINDOW_STORE: &str = "ranking_window";
+/// Store label of the senders counted for today's top users
+pub const DAILY_SENDERS_STORE: &str = "daily_senders";
+
+/// Entries and estimated size of one in-memory store
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoreUsage {
+    pub store: String,
+    pub entries: usize,
+    pub estimated_bytes: usize,
+}
+
+/// What enforcing the cap removed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CapEnforcement {
+    /// Interactions merged into another one of the same transaction, DApp and sender
+    pub compacted: usize,
+    /// Oldest interactions dropped from the windows
+    pub pruned: usize,
+}
+
+/// Estimated heap and inline size of an interaction
+pub fn interaction_bytes(interaction: &DAppInteraction) -> usize {
+    size_of::<DAppInteraction>()
+        + interaction.package_id.capacity()
+        + interaction.sender.capacity()
+        + interaction.transaction_digest.capacity()
+        + interaction.dapp_name.as_ref().map_or(0, String::capacity)
+        + interaction.event_type.capacity()
+}
+
+fn interactions_bytes(interactions: &[DAppInteraction]) -> usize {
+    interactions.iter().map(interaction_bytes).sum()
+}
+
+/// Usage of every in-memory store of `indexer`: the ranking window, each
+/// ranking profile under its name, and the daily sender counts
+pub fn memory_usage(indexer: &DAppIndexer) -> Vec<StoreUsage> {
+    let mut usage = vec![StoreUsage {
+        store: RANKING_WINDOW_STORE.to_string(),
+        entries: indexer.dapp_interactions.len(),
+        estimated_bytes: interactions_bytes(&indexer.dapp_interactions),
+    }];
+    usage.extend(indexer.ranking_profiles.iter().map(|profile| StoreUsage {
+        store: profile.name.clone(),
+        entries: profile.interactions.len(),
+        estimated_bytes: interactions_bytes(&profile.interactions),
+    }));
+
+    let (mut entries, mut estimated_bytes) = (0, 0);
+    for (dapp_name, senders) in &indexer.daily_sender_counts {
+        entries += senders.len();
+        estimated_bytes += dapp_name.capacity()
+            + senders
+                .keys()
+                .map(|sender| size_of::<(String, u64)>() + sender.capacity())
+                .sum::<usize>();
+    }
+    usage.push(StoreUsage { store: DAILY_SENDERS_STORE.to_string(), entries, estimated_bytes });
+    usage
+}
+
+/// Keep the first interaction of every (transaction, DApp, sender)
+/// Returns how many were removed
+fn compact_interactions(interactions: &mut Vec<DAppInteraction>) -> usize {
+    let keep: Vec<bool> = {
+        let mut seen: HashSet<(&str, &str, &str)> = HashSet::new();
+        interactions
+            .iter()
+            .map(|interaction| {
+                seen.insert((
+                    interaction.transaction_digest.as_str(),
+                    interaction.package_id.as_str(),
+                    interaction.sender.as_str(),
+                ))
+            })
+            .collect()
+    };
+    let before = interactions.len();
+    let mut keep = keep.into_iter();
+    interactions.retain(|_| keep.next().unwrap_or(true));
+    interactions.shrink_to_fit();
+    before - interactions.len()
+}
+
+/// Drop the oldest interactions until at least `bytes` are freed
+/// Interactions are appended in checkpoint order, so the oldest come first
+/// Returns how many interactions and bytes were dropped
+fn drop_oldest(interactions: &mut Vec<DAppInteraction>, bytes: usize) -> (usize, usize) {
+    let (mut count, mut freed) = (0, 0);
+    for interaction in interactions.iter() {
+        if freed >= bytes {
+            break;
+        }
+        freed += interaction_bytes(interaction);
+        count += 1;
+    }
+    interactions.drain(..count);
+    interactions.shrink_to_fit();
+    (count, freed)
+}
+
+/// Bring the interactions of `indexer` back under CAP_TARGET_PERCENT of
+/// `cap_bytes` if they exceed it, compacting them first and dropping the oldest
+/// if compaction is not enough
+/// Returns None when the usage is within the cap
+pub fn enforce_memory_cap(indexer: &mut DAppIndexer, cap_bytes: usize) -> Option<CapEnforcement> {
+    let total = |indexer: &DAppIndexer| -> usize {
+        memory_usage(indexer).iter().map(|usage| usage.estimated_bytes).sum()
+    };
+    if total(indexer) <= cap_bytes {
+        return None;
+    }
+    let target = cap_bytes / 100 * CAP_TARGET_PERCENT;
+
+    let mut enforcement = CapEnforcement {
+        compacted: compact_interactions(&mut indexer.dapp_interactions),
+        pruned: 0,
+    };
+    for profile in &mut indexer.ranking_profiles {
+        enforcement.compacted += compact_interactions(&mut profile.interactions);
+    }
+
+    let mut excess = total(indexer).saturating_sub(target);
+    // Longest windows first, the live ranking window last
+    let mut profiles: Vec<_> = indexer.ranking_profiles.iter_mut().collect();
+    profiles.sort_by_key(|profile| std::cmp::Reverse(profile.window));
+    let stores = profiles
+        .into_iter()
+        .map(|profile| &mut profile.interactions)
+        .chain(std::iter::once(&mut indexer.dapp_interactions));
+    for interactions in stores {
+        if excess == 0 {
+            break;
+        }
+        let (count, freed) = drop_oldest(interactions, excess);
+        enforcement.pruned += count;
+        excess = excess.saturating_sub(freed);
+    }
+    Some(enforcement)
+}
+
+/// Prometheus metrics of the in-memory stores and the cap
+#[derive(Clone)]
+pub struct MemoryMetrics {
+    entries: IntGaugeVec,
+    estimated_bytes: IntGaugeVec,
+    cap_bytes: IntGauge,
+    compacted: IntCounter,
+    pruned: IntCounter,
+}
+
+impl MemoryMetrics {
+    pub fn new(registry: &Registry) -> Result<Self> {
+        Ok(Self {
+            entries: register_int_gauge_vec_with_registry!(
+                "indexer_memory_entries",
+                "Entries held in memory by store",
+                &["store"],
+                registry
+            )?,
+            estimated_bytes: register_int_gauge_vec_with_registry!(
+                "indexer_memory_estimated_bytes",
+                "Estimated size of the entries held in memory by store",
+                &["store"],
+                registry
+            )?,
+            cap_bytes: register_int_gauge_with_registry!(
+                "indexer_memory_cap_bytes",
+                "Estimated size the in-memory stores are capped at, 0 if uncapped",
+                registry
+            )?,
+            compacted: register_int_counter_with_registry!(
+                "indexer_memory_cap_compacted",
+                "Interactions merged into another of the same transaction, DApp and sender to stay under the cap",
+                registry
+            )?,
+            pruned: register_int_counter_with_registry!(
+                "indexer_memory_cap_pruned",
+                "Oldest interactions dropped from the windows to stay under the cap",
+                registry
+            )?,
+        })
+    }
+
+    fn record(&self, usage: &[StoreUsage]) {
+        for store in usage {
+            self.entries.with_label_values(&[&store.store]).set(store.entries as i64);
+            self.estimated_bytes.with_label_values(&[&store.store]).set(store.estimated_bytes as i64);
+        }
+    }
+}
+
+/// Start the task reporting the memory usage of `indexer` every REPORT_INTERVAL
+/// and enforcing `cap_bytes`, if set
+pub async fn start_memory_report_job(
+    indexer: Arc<Mutex<DAppIndexer>>,
+    cap_bytes: Option<usize>,
+    metrics: MemoryMetrics,
+) {
+    metrics.cap_bytes.set(cap_bytes.unwrap_or(0) as i64);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(REPORT_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let mut indexer_guard = indexer.lock().await;
+            let enforcement = cap_bytes.and_then(|cap_bytes| enforce_memory_cap(&mut indexer_guard, cap_bytes));
+            let usage = memory_usage(&indexer_guard);
+            drop(indexer_guard);
+
+            metrics.record(&usage);
+            if let Some(enforcement) = enforcement {
+                metrics.compacted.inc_by(enforcement.compacted as u64);
+                metrics.pruned.inc_by(enforcement.pruned as u64);
+                warn!("🧠 Memory cap exceeded: compacted {} and dropped {} oldest interactions, {} bytes left",
+                      enforcement.compacted, enforcement.pruned,
+                      usage.iter().map(|usage| usage.estimated_bytes).sum::<usize>());
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dapp_indexer::compute_rankings;
+    use crate::models::DAppRegistryEntry;
+    use crate::test_utils::InteractionBuilder;
+    use chrono::{DateTime, Utc};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_compacts_then_drops_oldest_interactions_over_the_cap() {
+        let start = DateTime::<Utc>::UNIX_EPOCH + Duration::from_secs(100 * 3600);
+        let interaction = |sender: &str, transaction: &str, minute: u64| {
+            InteractionBuilder::new()
+                .package("0xa")
+                .dapp("Cetus AMM")
+                .sender(sender)
+                .digest(transaction)
+                .gas_used(10)
+                .at(start + Duration::from_secs(minute * 60))
+                .build()
+        };
+        let mut indexer = DAppIndexer::builder().ranking_profile("dau_24h", Duration::from_secs(24 * 3600)).build();
+        // Three events of each transaction
+        for minute in 0..10 {
+            for _ in 0..3 {
+                indexer.dapp_interactions.push(interaction(&format!("0x{}", minute % 4), &format!("tx{}", minute), minute));
+                indexer.ranking_profiles[0]
+                    .interactions
+                    .push(interaction(&format!("0x{}", minute % 4), &format!("tx{}", minute), minute));
+            }
+        }
+        let usage = memory_usage(&indexer);
+        assert_eq!(usage[0].store, RANKING_WINDOW_STORE);
+        assert_eq!((usage[0].entries, usage[1].entries), (30, 30));
+        let total: usize = usage.iter().map(|usage| usage.estimated_bytes).sum();
+        assert_eq!(enforce_memory_cap(&mut indexer, total), None);
+
+        // Compaction alone brings 30 interactions per window down to 10
+        let registry = HashMap::from([("0xa".to_string(), DAppRegistryEntry::new("Cetus AMM", "DEX"))]);
+        let rank = |interactions: &[DAppInteraction]| {
+            let ranking = &compute_rankings(interactions, &registry, Duration::from_secs(3600), start + Duration::from_secs(3600))[0];
+            (ranking.active_users, ranking.tx_count, ranking.gas_used)
+        };
+        let before = rank(&indexer.dapp_interactions);
+        let enforcement = enforce_memory_cap(&mut indexer, total / 2).unwrap();
+        assert_eq!(enforcement, CapEnforcement { compacted: 40, pruned: 0 });
+        assert_eq!(rank(&indexer.dapp_interactions), before);
+
+        // Then the oldest interactions go, from the profile first
+        let compacted: usize = memory_usage(&indexer).iter().map(|usage| usage.estimated_bytes).sum();
+        let enforcement = enforce_memory_cap(&mut indexer, compacted * 2 / 3).unwrap();
+        assert_eq!(enforcement.compacted, 0);
+        assert!(enforcement.pruned > 0);
+        assert_eq!(indexer.dapp_interactions.len(), 10);
+        assert_eq!(indexer.ranking_profiles[0].interactions.len(), 10 - enforcement.pruned);
+        assert_eq!(indexer.ranking_profiles[0].interactions[0].transaction_digest, format!("tx{}", enforcement.pruned));
+    }
+}
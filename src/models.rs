@@ -1,11 +1,13 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::schema::dapp_rankings;
+use crate::schema::{
+    bridge_stats, category_stats, daily_user_sketches, dapp_calibration, dapp_event_stats, dapp_function_stats, dapp_hourly_activity, dapp_interactions, dapp_lending_stats, dapp_metadata, dapp_nft_stats, dapp_package_stats, dapp_published_packages, dapp_ranking_history, dapp_ranking_weekly, dapp_rankings, dapp_registry, dapp_retention, dapp_top_users, dapp_unknown_activity, dapp_user_activity,
+    dapp_user_first_seen, epoch_stats, webhook_deliveries, webhook_subscriptions,
+};
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::time::SystemTime;
-use chrono::{NaiveDateTime};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 
 /**
  * DAppInteraction represents a user interaction with a DApp
@@ -16,9 +18,12 @@ use chrono::{NaiveDateTime};
 pub struct DAppInteraction {
     pub package_id: String,        // DApp package identifier
     pub sender: String,             // User address who interacted
-    pub timestamp: SystemTime,      // When the interaction occurred
+    pub timestamp: DateTime<Utc>,   // When the interaction occurred (checkpoint time, ms precision)
     pub transaction_digest: String, // Unique transaction identifier
     pub dapp_name: Option<String>,  // Human-readable DApp name (if mapped)
+    pub gas_used: u64,              // Gas used by the whole transaction (MIST)
+    pub event_type: String,         // Emitted event type as module::struct
+    pub sponsored: bool,            // Gas was paid by a sponsor rather than the sender
 }
 
 // DApp Ranking Models
@@ -29,9 +34,39 @@ pub struct DAppRankingRecord {
     pub rank_position: i32,
     pub package_id: String,
     pub dapp_name: String,
-    pub dau_1h: i32,  // 1-hour Hourly Active Users count
+    #[serde(alias = "dau_1h")]
+    pub active_users: i32,  // Active users over the window (HAU for the default 1h window)
     pub dapp_type: String,
-    pub last_update: Option<NaiveDateTime>,
+    pub last_update: DateTime<Utc>,  // When the ranking was computed
+    #[serde(alias = "filtered_users_1h")]
+    pub filtered_users: i32,  // Senders excluded from HAU by bot/sybil filters
+    pub tracking_status: String,  // "active" or "paused"
+    pub new_users_24h: i32,  // Active users first seen within the last 24h
+    pub returning_users_24h: i32,  // Active users first seen before the last 24h
+    #[serde(alias = "tx_count_1h")]
+    pub tx_count: i32,  // Distinct transactions of counted users
+    #[serde(alias = "gas_used_1h")]
+    pub gas_used: i64,  // Gas used by those transactions (MIST)
+    pub score: f64,  // Composite ranking score the rank is based on
+    pub error_rate_24h: f64,  // Share of the DApp's transactions that failed in the last 24h
+    pub dau_smoothed: f64,  // Moving average of HAU over the last ranking computations
+    pub window_label: String,  // Window the figures are counted over (RANKING_WINDOW for the live rankings)
+    pub sponsored_user_pct: f64,  // Share (%) of active users whose gas was sponsored
+    pub avg_gas_per_tx: f64,  // Average gas per transaction (MIST)
+    pub interactions_p50: i32,  // Median interactions per active user
+    pub interactions_p90: i32,  // 90th percentile of interactions per active user
+    pub interactions_p99: i32,  // 99th percentile of interactions per active user
+    pub top_users_interaction_pct: f64,  // Share (%) of interactions made by the top 1% of active users
+    #[serde(default)]
+    pub volume_24h_usd: f64,  // USD value swapped over the last 24h (DEX and Aggregator only)
+    #[serde(default)]
+    pub wau: i32,  // Distinct users over the last 7 days in the stats timezone, today included
+    #[serde(default)]
+    pub mau: i32,  // Distinct users over the last 30 days in the stats timezone, today included
+    #[serde(default)]
+    pub deposit_volume_24h_usd: f64,  // USD value deposited over the last 24h (Lending only)
+    #[serde(default)]
+    pub borrow_volume_24h_usd: f64,  // USD value borrowed over the last 24h (Lending only)
 }
 
 #[derive(Insertable, AsChangeset, Debug)]
@@ -41,20 +76,773 @@ pub struct NewDAppRankingRecord {
     pub rank_position: i32,
     pub package_id: String,
     pub dapp_name: String,
-    pub dau_1h: i32,  // 1-hour Hourly Active Users count
+    pub active_users: i32,  // Active users over the window (HAU for the default 1h window)
     pub dapp_type: String,
-    pub last_update: Option<NaiveDateTime>,
+    pub last_update: DateTime<Utc>,  // When the ranking was computed
+    pub filtered_users: i32,  // Senders excluded from HAU by bot/sybil filters
+    pub tracking_status: String,  // "active" or "paused"
+    pub new_users_24h: i32,  // Active users first seen within the last 24h
+    pub returning_users_24h: i32,  // Active users first seen before the last 24h
+    pub tx_count: i32,  // Distinct transactions of counted users
+    pub gas_used: i64,  // Gas used by those transactions (MIST)
+    pub score: f64,  // Composite ranking score the rank is based on
+    pub error_rate_24h: f64,  // Share of the DApp's transactions that failed in the last 24h
+    pub dau_smoothed: f64,  // Moving average of HAU over the last ranking computations
+    pub window_label: String,  // Window the figures are counted over (RANKING_WINDOW for the live rankings)
+    pub sponsored_user_pct: f64,  // Share (%) of active users whose gas was sponsored
+    pub avg_gas_per_tx: f64,  // Average gas per transaction (MIST)
+    pub interactions_p50: i32,  // Median interactions per active user
+    pub interactions_p90: i32,  // 90th percentile of interactions per active user
+    pub interactions_p99: i32,  // 99th percentile of interactions per active user
+    pub top_users_interaction_pct: f64,  // Share (%) of interactions made by the top 1% of active users
+    pub volume_24h_usd: f64,  // USD value swapped over the last 24h (DEX and Aggregator only)
+    pub deposit_volume_24h_usd: f64,  // USD value deposited over the last 24h (Lending only)
+    pub borrow_volume_24h_usd: f64,  // USD value borrowed over the last 24h (Lending only)
 }
 
 /**
- * DAppRanking represents the 1h ranking of a DApp based on Hourly Active Users
+ * DAppRankingHistoryRecord is one persisted snapshot of a DApp's ranking
+ */
+#[derive(Queryable, Selectable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = dapp_ranking_history)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct DAppRankingHistoryRecord {
+    pub dapp_name: String,
+    pub dapp_type: String,
+    pub rank_position: i32,
+    #[serde(alias = "dau_1h")]
+    pub active_users: i32,  // Active users over the live ranking window
+    #[serde(alias = "tx_count_1h")]
+    pub tx_count: i32,  // Distinct transactions of counted users
+    #[serde(alias = "gas_used_1h")]
+    pub gas_used: i64,  // Gas used by those transactions (MIST)
+    pub score: f64,  // Composite ranking score the rank is based on
+    pub recorded_at: NaiveDateTime,  // When the snapshot was persisted (UTC)
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = dapp_ranking_history)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewDAppRankingHistoryRecord {
+    pub dapp_name: String,
+    pub dapp_type: String,
+    pub rank_position: i32,
+    pub active_users: i32,
+    pub tx_count: i32,
+    pub gas_used: i64,
+    pub score: f64,
+    pub recorded_at: NaiveDateTime,
+}
+
+/**
+ * DAppRanking represents the ranking of a DApp over the ranking window, based on its active users
  */
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DAppRanking {
     pub rank: u32,                  // Current ranking position
     pub package_id: String,         // DApp package identifier
     pub dapp_name: String,          // Human-readable DApp name
-    pub dau_1h: u32,               // 1-hour Hourly Active Users count
-    pub last_update: SystemTime,    // Last time ranking was calculated
+    #[serde(alias = "dau_1h")]
+    pub active_users: u32,          // Active users over the window (HAU for the default 1h window)
+    #[serde(alias = "filtered_users_1h")]
+    pub filtered_users: u32,        // Senders excluded from HAU by bot/sybil filters
+    pub last_update: DateTime<Utc>, // Last time ranking was calculated
     pub dapp_type: String,          // DApp category/type
+    pub tracking_status: String,    // "active" or "paused" (paused keeps last known figures)
+    pub new_users_24h: u32,         // Active users first seen within the last 24h
+    pub returning_users_24h: u32,   // Active users first seen before the last 24h
+    #[serde(alias = "tx_count_1h")]
+    pub tx_count: u32,              // Distinct transactions of counted users
+    #[serde(alias = "gas_used_1h")]
+    pub gas_used: u64,              // Gas used by those transactions (MIST)
+    pub score: f64,                 // Composite ranking score the rank is based on
+    pub error_rate_24h: f64,        // Share of the DApp's transactions that failed in the last 24h
+    pub dau_smoothed: f64,          // Moving average of HAU over the last ranking computations
+    pub sponsored_user_pct: f64,    // Share (%) of active users whose gas was sponsored
+    pub avg_gas_per_tx: f64,        // Average gas per transaction (MIST)
+    pub interactions_p50: u32,      // Median interactions per active user
+    pub interactions_p90: u32,      // 90th percentile of interactions per active user
+    pub interactions_p99: u32,      // 99th percentile of interactions per active user
+    pub top_users_interaction_pct: f64, // Share (%) of interactions made by the top 1% of active users
+    #[serde(default)]
+    pub volume_24h_usd: f64,        // USD value swapped over the last 24h (DEX and Aggregator only)
+    #[serde(default)]
+    pub deposit_volume_24h_usd: f64, // USD value deposited over the last 24h (Lending only)
+    #[serde(default)]
+    pub borrow_volume_24h_usd: f64, // USD value borrowed over the last 24h (Lending only)
+}
+
+impl From<DAppRankingRecord> for DAppRanking {
+    fn from(record: DAppRankingRecord) -> Self {
+        Self {
+            rank: record.rank_position as u32,
+            package_id: record.package_id,
+            dapp_name: record.dapp_name,
+            active_users: record.active_users as u32,
+            filtered_users: record.filtered_users as u32,
+            last_update: record.last_update,
+            dapp_type: record.dapp_type,
+            tracking_status: record.tracking_status,
+            new_users_24h: record.new_users_24h as u32,
+            returning_users_24h: record.returning_users_24h as u32,
+            tx_count: record.tx_count as u32,
+            gas_used: record.gas_used as u64,
+            score: record.score,
+            error_rate_24h: record.error_rate_24h,
+            dau_smoothed: record.dau_smoothed,
+            sponsored_user_pct: record.sponsored_user_pct,
+            avg_gas_per_tx: record.avg_gas_per_tx,
+            interactions_p50: record.interactions_p50 as u32,
+            interactions_p90: record.interactions_p90 as u32,
+            interactions_p99: record.interactions_p99 as u32,
+            top_users_interaction_pct: record.top_users_interaction_pct,
+            volume_24h_usd: record.volume_24h_usd,
+            deposit_volume_24h_usd: record.deposit_volume_24h_usd,
+            borrow_volume_24h_usd: record.borrow_volume_24h_usd,
+        }
+    }
+}
+
+/// Tracking status of a DApp that is counted normally
+pub const TRACKING_ACTIVE: &str = "active";
+/// Tracking status of a DApp whose counting is paused (e.g. after an exploit)
+pub const TRACKING_PAUSED: &str = "paused";
+
+/// Name (and category) of the ranking row aggregating DApps below the minimum HAU
+pub const OTHERS_DAPP_NAME: &str = "Others";
+/// Package ID of the aggregated "Others" ranking row
+pub const OTHERS_PACKAGE_ID: &str = "others";
+
+/// Most active share of users (%) whose part of all interactions measures concentration
+pub const TOP_USERS_PERCENT: usize = 1;
+
+/**
+ * DAppRegistryEntry describes a tracked DApp package
+ * Several package IDs can share the same dapp_name and are aggregated in rankings
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DAppRegistryEntry {
+    pub dapp_name: String,          // Human-readable DApp name
+    pub dapp_type: String,          // DApp category/type
+    pub enabled: bool,              // When false, interactions are no longer counted
+    pub metadata: DAppMetadata,     // Website, icon and Twitter shown by frontends
+}
+
+impl DAppRegistryEntry {
+    pub fn new(dapp_name: &str, dapp_type: &str) -> Self {
+        Self {
+            dapp_name: dapp_name.to_string(),
+            dapp_type: dapp_type.to_string(),
+            enabled: true,
+            metadata: DAppMetadata::default(),
+        }
+    }
+}
+
+/**
+ * A package attributed to a DApp because one of the DApp's publisher addresses
+ * published (or upgraded) it
+ */
+#[derive(Queryable, Selectable, Insertable, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[diesel(table_name = dapp_published_packages)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct DAppPublishedPackage {
+    pub package_id: String,         // Newly published package
+    pub dapp_name: String,          // DApp the publisher belongs to
+    pub publisher: String,          // Address that published the package
+    pub discovered_at: NaiveDateTime, // Timestamp of the publishing checkpoint (UTC)
+}
+
+/**
+ * A registry change made through the admin API
+ * Applied over the built-in registry at startup; inactive entries remove the package
+ */
+#[derive(Queryable, Selectable, Insertable, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[diesel(table_name = dapp_registry)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct DAppRegistryRecord {
+    pub package_id: String,         // Package the change applies to
+    pub dapp_name: String,          // DApp the package belongs to
+    pub dapp_type: String,          // Category of the DApp
+    pub active: bool,               // false when the package was removed from tracking
+    pub updated_at: NaiveDateTime,  // When the change was made (UTC)
+}
+
+/**
+ * DAppMetadata is optional presentation info of a DApp
+ * Stored per DApp name in dapp_metadata and joined into API responses
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DAppMetadata {
+    pub website_url: Option<String>, // Project website
+    pub icon_url: Option<String>,    // Logo shown next to the DApp name
+    pub twitter: Option<String>,     // Twitter/X handle
+}
+
+// DApp Metadata Models
+#[derive(Queryable, Selectable, Insertable, Debug, Clone, Serialize, Deserialize)]
+#[diesel(table_name = dapp_metadata)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct DAppMetadataRecord {
+    pub dapp_name: String,
+    pub website_url: Option<String>,
+    pub icon_url: Option<String>,
+    pub twitter: Option<String>,
+    pub last_update: NaiveDateTime,
+}
+
+impl From<DAppMetadataRecord> for DAppMetadata {
+    fn from(record: DAppMetadataRecord) -> Self {
+        Self {
+            website_url: record.website_url,
+            icon_url: record.icon_url,
+            twitter: record.twitter,
+        }
+    }
+}
+
+/**
+ * One comparison of our DAU against an external data source for a DApp
+ */
+#[derive(Insertable, Debug)]
+#[diesel(table_name = dapp_calibration)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewDAppCalibrationRecord {
+    pub dapp_name: String,
+    pub external_source: String,
+    pub external_dau: i32,
+    pub indexed_dau: i32,
+    pub divergence_pct: f64,  // (indexed - external) / external * 100
+    pub recorded_at: NaiveDateTime,
+}
+
+/**
+ * CategoryStats rolls up activity of all DApps sharing a dapp_type over the ranking window
+ * Activity is counted in users, transactions and interactions; DEX and Aggregator
+ * categories also carry the 24h swap volume of their DApps
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryStats {
+    pub dapp_type: String,          // DApp category/type
+    pub active_users: u32,          // Unique users across the category (deduplicated)
+    pub transaction_count: u32,     // Distinct transactions touching the category
+    pub interaction_count: u32,     // Total DApp interactions in the category
+    pub dapp_count: u32,            // Number of active DApps in the category
+    #[serde(default)]
+    pub volume_24h_usd: f64,        // USD value swapped over the last 24h (DEX and Aggregator only)
+    pub last_update: DateTime<Utc>, // Last time stats were calculated
+}
+
+// Category Statistics Models
+#[derive(Queryable, Selectable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = category_stats)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct CategoryStatsRecord {
+    pub id: i32,
+    pub dapp_type: String,
+    pub window_label: String,
+    pub active_users: i32,
+    pub transaction_count: i32,
+    pub interaction_count: i32,
+    pub dapp_count: i32,
+    pub computed_at: NaiveDateTime,
+    #[serde(default)]
+    pub volume_24h_usd: f64,  // USD value swapped over the last 24h (DEX and Aggregator only)
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = category_stats)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewCategoryStatsRecord {
+    pub dapp_type: String,
+    pub window_label: String,
+    pub active_users: i32,
+    pub transaction_count: i32,
+    pub interaction_count: i32,
+    pub dapp_count: i32,
+    pub computed_at: NaiveDateTime,
+    pub volume_24h_usd: f64,
+}
+
+/**
+ * DAppPackageStats tracks the 1h activity of a single package
+ * DApps with several packages are merged in rankings; these stats keep each package visible
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DAppPackageStats {
+    pub package_id: String,         // Package identifier
+    pub dapp_name: String,          // DApp the package belongs to
+    pub dapp_type: String,          // DApp category/type
+    pub active_users: u32,          // Unique users of this package
+    pub transaction_count: u32,     // Distinct transactions touching the package
+    pub interaction_count: u32,     // Total interactions with the package
+    pub avg_gas_per_tx: f64,        // Average gas of those transactions (MIST)
+    pub last_update: DateTime<Utc>, // Last time stats were calculated
+}
+
+// Package Statistics Models
+#[derive(Queryable, Selectable, Insertable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = dapp_package_stats)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct DAppPackageStatsRecord {
+    pub package_id: String,
+    pub dapp_name: String,
+    pub dapp_type: String,
+    #[serde(alias = "dau_1h")]
+    pub active_users: i32,
+    #[serde(alias = "tx_count_1h")]
+    pub tx_count: i32,
+    #[serde(alias = "interaction_count_1h")]
+    pub interaction_count: i32,
+    pub last_update: NaiveDateTime,
+    pub avg_gas_per_tx: f64,
+}
+
+/**
+ * DAppEventStats counts the interactions of a DApp with one event type over 24 hours
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DAppEventStats {
+    pub dapp_name: String,          // Human-readable DApp name
+    pub event_type: String,         // Event type as module::struct
+    pub count_24h: u64,             // Interactions with this event type in the last 24 hours
+    pub last_update: DateTime<Utc>, // Last time counts were calculated
+}
+
+// Event Type Statistics Models
+#[derive(Queryable, Selectable, Insertable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = dapp_event_stats)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct DAppEventStatsRecord {
+    pub dapp_name: String,
+    pub event_type: String,
+    pub count_24h: i64,
+    pub last_update: NaiveDateTime,
+}
+
+/**
+ * DAppEpochStats counts the active users and transactions of a DApp in one Sui epoch
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DAppEpochStats {
+    pub epoch: u64,                 // Sui epoch
+    pub dapp_name: String,          // Human-readable DApp name
+    pub active_users: u32,          // Distinct senders in the epoch
+    pub transaction_count: u32,     // Distinct transactions in the epoch
+    pub observed_from: DateTime<Utc>, // First interaction counted in the epoch
+    pub last_update: DateTime<Utc>, // Latest interaction counted in the epoch
+}
+
+// Epoch Statistics Models
+#[derive(Queryable, Selectable, Insertable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = epoch_stats)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct EpochStatsRecord {
+    pub epoch: i64,
+    pub dapp_name: String,
+    pub active_users: i32,
+    pub transaction_count: i32,
+    pub observed_from: NaiveDateTime,
+    pub last_update: NaiveDateTime,
+}
+
+/**
+ * DAppHourlyActivity counts the distinct users of a DApp in one hour of the day (UTC)
+ * over the last 7 days, one cell of its activity heat map
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DAppHourlyActivity {
+    pub dapp_name: String,          // Human-readable DApp name
+    pub hour_of_day: u32,           // Hour of the day in UTC (0-23)
+    pub unique_users: u32,          // Distinct senders in this hour on any of the last 7 days
+    pub observed_from: DateTime<Utc>, // First interaction counted; later than 7 days ago after a restart
+    pub last_update: DateTime<Utc>, // When the counts were computed
+}
+
+// DApp Hourly Activity Models
+#[derive(Queryable, Selectable, Insertable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = dapp_hourly_activity)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct DAppHourlyActivityRecord {
+    pub dapp_name: String,
+    pub hour_of_day: i32,
+    pub unique_users: i32,
+    pub observed_from: NaiveDateTime,
+    pub last_update: NaiveDateTime,
+}
+
+/**
+ * DAppNftStats holds the mint and transfer activity of an NFT DApp over the last 24 hours
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DAppNftStats {
+    pub dapp_name: String,          // Human-readable DApp name
+    pub mints_24h: u64,             // Mint events in the last 24h
+    pub unique_minters_24h: u32,    // Distinct senders of those mints
+    pub transfers_24h: u64,         // Transfer events in the last 24h
+    pub last_update: DateTime<Utc>, // When the counts were computed
+}
+
+// DApp NFT Stats Models
+#[derive(Queryable, Selectable, Insertable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = dapp_nft_stats)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct DAppNftStatsRecord {
+    pub dapp_name: String,
+    pub mints_24h: i64,
+    pub unique_minters_24h: i32,
+    pub transfers_24h: i64,
+    pub last_update: NaiveDateTime,
+}
+
+/**
+ * DAppLendingStats holds the deposit and borrow activity of a lending DApp over the last 24 hours
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DAppLendingStats {
+    pub dapp_name: String,             // Human-readable DApp name
+    pub deposits_24h: u64,             // Deposit events in the last 24h
+    pub withdrawals_24h: u64,          // Withdraw events in the last 24h
+    pub borrows_24h: u64,              // Borrow events in the last 24h
+    pub repays_24h: u64,               // Repay events in the last 24h
+    pub unique_depositors_24h: u32,    // Distinct senders of those deposits
+    pub unique_borrowers_24h: u32,     // Distinct senders of those borrows
+    pub deposit_volume_24h_usd: f64,   // USD value deposited in the last 24h
+    pub borrow_volume_24h_usd: f64,    // USD value borrowed in the last 24h
+    pub last_update: DateTime<Utc>,    // When the counts were computed
+}
+
+// DApp Lending Stats Models
+#[derive(Queryable, Selectable, Insertable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = dapp_lending_stats)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct DAppLendingStatsRecord {
+    pub dapp_name: String,
+    pub deposits_24h: i64,
+    pub withdrawals_24h: i64,
+    pub borrows_24h: i64,
+    pub repays_24h: i64,
+    pub unique_depositors_24h: i32,
+    pub unique_borrowers_24h: i32,
+    pub deposit_volume_24h_usd: f64,
+    pub borrow_volume_24h_usd: f64,
+    pub last_update: NaiveDateTime,
+}
+
+/**
+ * BridgeStats holds the inbound and outbound transfers of a bridge over the last 24 hours
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BridgeStats {
+    pub dapp_name: String,              // Human-readable DApp name
+    pub inbound_transfers_24h: u64,     // Transfers into Sui in the last 24h
+    pub outbound_transfers_24h: u64,    // Transfers out of Sui in the last 24h
+    pub inbound_volume_24h_usd: f64,    // USD value brought into Sui in the last 24h
+    pub outbound_volume_24h_usd: f64,   // USD value taken out of Sui in the last 24h
+    pub last_update: DateTime<Utc>,     // When the counts were computed
+}
+
+// Bridge Stats Models
+#[derive(Queryable, Selectable, Insertable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = bridge_stats)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct BridgeStatsRecord {
+    pub dapp_name: String,
+    pub inbound_transfers_24h: i64,
+    pub outbound_transfers_24h: i64,
+    pub inbound_volume_24h_usd: f64,
+    pub outbound_volume_24h_usd: f64,
+    pub last_update: NaiveDateTime,
+}
+
+/**
+ * DAppFunctionCall is a MoveCall command of a transaction targeting a tracked package
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DAppFunctionCall {
+    pub package_id: String,         // Called package
+    pub dapp_name: String,          // DApp the package belongs to
+    pub function: String,           // Called function as module::function
+    pub timestamp: DateTime<Utc>,   // When the transaction occurred
+}
+
+/**
+ * DAppTransactionOutcome records whether a transaction touching a tracked DApp succeeded
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DAppTransactionOutcome {
+    pub dapp_name: String,          // DApp called by or emitting events in the transaction
+    pub success: bool,              // False if the transaction aborted
+    pub timestamp: DateTime<Utc>,   // When the transaction occurred
+}
+
+/**
+ * DAppFunctionStats is one of the most called functions of a DApp over 24 hours
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DAppFunctionStats {
+    pub dapp_name: String,          // Human-readable DApp name
+    pub function: String,           // Function as module::function
+    pub call_count_24h: u64,        // MoveCalls of this function in the last 24 hours
+    pub rank: u32,                  // Position among the DApp's functions
+    pub last_update: DateTime<Utc>, // Last time counts were calculated
+}
+
+// Function Statistics Models
+#[derive(Queryable, Selectable, Insertable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = dapp_function_stats)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct DAppFunctionStatsRecord {
+    pub dapp_name: String,
+    pub function_name: String,
+    pub call_count_24h: i64,
+    pub rank_position: i32,
+    pub last_update: NaiveDateTime,
+}
+
+/**
+ * Last observed activity of an untracked package, kept for review instead of deleted
+ */
+#[derive(Queryable, Selectable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = dapp_unknown_activity)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct DAppUnknownActivityRecord {
+    pub package_id: String,
+    pub dapp_name: String,
+    pub observed_dau: i32,
+    pub last_seen: NaiveDateTime,
+}
+
+/**
+ * DAppTopUser is one of the most active senders of a DApp on a given day of the stats timezone
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DAppTopUser {
+    pub day: NaiveDate,             // Day (in the stats timezone) the counts belong to
+    pub dapp_name: String,          // Human-readable DApp name
+    pub sender: String,             // User address (or its anonymized hash)
+    pub interaction_count: u64,     // Interactions of this sender with the DApp that day
+    pub rank: u32,                  // Position among the DApp's senders that day
+}
+
+// DApp Top Users Models
+#[derive(Queryable, Selectable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = dapp_top_users)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct DAppTopUserRecord {
+    pub day: NaiveDate,
+    pub dapp_name: String,
+    pub sender: String,
+    pub interaction_count: i64,
+    pub rank_position: i32,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = dapp_top_users)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewDAppTopUserRecord {
+    pub day: NaiveDate,
+    pub dapp_name: String,
+    pub sender: String,
+    pub interaction_count: i64,
+    pub rank_position: i32,
+}
+
+/**
+ * A raw DApp interaction persisted to the partitioned dapp_interactions table
+ * `interaction_index` numbers the interactions of a transaction, so replayed
+ * checkpoints do not insert duplicates
+ */
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = dapp_interactions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewDAppInteractionRecord {
+    pub timestamp: DateTime<Utc>,
+    pub transaction_digest: String,
+    pub interaction_index: i32,
+    pub package_id: String,
+    pub dapp_name: Option<String>,
+    pub sender: String,
+    pub gas_used: i64,
+    pub event_type: String,
+    pub sponsored: bool,
+}
+
+/**
+ * First time a sender was seen interacting with a DApp
+ * Used to split active users into new and returning users
+ */
+#[derive(Insertable, Debug)]
+#[diesel(table_name = dapp_user_first_seen)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewDAppUserFirstSeen {
+    pub dapp_name: String,
+    pub sender: String,
+    pub first_seen: NaiveDateTime,
+}
+
+/**
+ * A sender active on a DApp on a given day of the stats timezone
+ * Used to measure how many users of a cohort come back on later days
+ */
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = dapp_user_activity)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewDAppUserActivity {
+    pub day: NaiveDate,
+    pub dapp_name: String,
+    pub sender: String,
+}
+
+/**
+ * HyperLogLog sketch of the senders active on a DApp on a given day of the stats timezone
+ * See `user_sketch` for the serialized format
+ */
+#[derive(Queryable, Selectable, Insertable, Debug, Clone)]
+#[diesel(table_name = daily_user_sketches)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct DailyUserSketchRecord {
+    pub day: NaiveDate,
+    pub dapp_name: String,
+    pub sketch: Vec<u8>,
+    pub updated_at: NaiveDateTime,
+}
+
+// Ranking Snapshot Models
+// Weekly and monthly snapshots share their columns; both are read through `dapp_ranking_weekly`
+#[derive(QueryableByName, Debug, Clone, Serialize, Deserialize)]
+#[diesel(table_name = dapp_ranking_weekly)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct DAppRankingSnapshotRecord {
+    pub period_start: NaiveDate,    // First day of the week or month the snapshot closes
+    pub rank_position: i32,
+    pub dapp_name: String,
+    pub dapp_type: String,
+    pub active_users: i32,          // HAU when the period ended
+    pub score: f64,
+    pub recorded_at: NaiveDateTime, // When the snapshot was taken (UTC)
+}
+
+// DApp Retention Models
+#[derive(Queryable, QueryableByName, Selectable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = dapp_retention)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct DAppRetentionRecord {
+    pub cohort_day: NaiveDate,      // Day (in the stats timezone) the cohort's users were first seen
+    pub dapp_name: String,
+    pub cohort_size: i32,           // First-time users on cohort_day
+    pub d1_returned: i32,           // Cohort users active again 1 day later
+    pub d7_returned: i32,           // Cohort users active again 7 days later
+    pub d30_returned: i32,          // Cohort users active again 30 days later
+    pub d1_rate: Option<f64>,       // d1_returned / cohort_size, NULL until day 1 has elapsed
+    pub d7_rate: Option<f64>,       // d7_returned / cohort_size, NULL until day 7 has elapsed
+    pub d30_rate: Option<f64>,      // d30_returned / cohort_size, NULL until day 30 has elapsed
+    pub computed_at: NaiveDateTime,
+}
+
+// Webhook Subscription Models
+#[derive(Queryable, Selectable, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[diesel(table_name = webhook_subscriptions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct WebhookSubscriptionRecord {
+    pub id: i32,
+    pub dapp_name: String,
+    pub url: String,                     // URL notifications are POSTed to
+    pub threshold_pct: f64,              // Change of active users, in percent, that fires the webhook
+    pub baseline_users: Option<i32>,     // Active users at the last notification, NULL until first ranked
+    pub active: bool,                    // false once the subscription is deleted
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = webhook_subscriptions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewWebhookSubscription {
+    pub dapp_name: String,
+    pub url: String,
+    pub threshold_pct: f64,
+}
+
+/// Delivery states of a webhook notification
+pub const DELIVERY_PENDING: &str = "pending";
+pub const DELIVERY_DELIVERED: &str = "delivered";
+pub const DELIVERY_FAILED: &str = "failed";
+
+#[derive(Queryable, Selectable, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[diesel(table_name = webhook_deliveries)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct WebhookDeliveryRecord {
+    pub id: i64,
+    pub subscription_id: i32,
+    pub payload: String,                 // JSON body POSTed to the subscription's URL
+    pub status: String,                  // pending, delivered or failed
+    pub attempts: i32,
+    pub last_error: Option<String>,      // Error of the last failed attempt
+    pub response_status: Option<i32>,    // HTTP status of the last attempt that got a response
+    pub next_attempt_at: NaiveDateTime,  // When a pending delivery is tried next (UTC)
+    pub created_at: NaiveDateTime,
+    pub delivered_at: Option<NaiveDateTime>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = webhook_deliveries)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewWebhookDelivery {
+    pub subscription_id: i32,
+    pub payload: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(last_update: DateTime<Utc>) -> DAppRankingRecord {
+        DAppRankingRecord {
+            rank_position: 1,
+            package_id: "0x1".to_string(),
+            dapp_name: "Cetus AMM".to_string(),
+            active_users: 42,
+            dapp_type: "DEX".to_string(),
+            last_update,
+            filtered_users: 0,
+            tracking_status: TRACKING_ACTIVE.to_string(),
+            new_users_24h: 0,
+            returning_users_24h: 0,
+            tx_count: 50,
+            gas_used: 5_000,
+            score: 1.0,
+            error_rate_24h: 0.0,
+            dau_smoothed: 42.0,
+            window_label: "1h".to_string(),
+            sponsored_user_pct: 0.0,
+            avg_gas_per_tx: 100.0,
+            interactions_p50: 1,
+            interactions_p90: 2,
+            interactions_p99: 3,
+            top_users_interaction_pct: 5.0,
+            volume_24h_usd: 12_500.0,
+            wau: 300,
+            mau: 900,
+            deposit_volume_24h_usd: 0.0,
+            borrow_volume_24h_usd: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_ranking_times_round_trip_with_millisecond_precision() {
+        let last_update = DateTime::from_timestamp_millis(1_750_000_000_123).unwrap();
+
+        // Stored rows keep the computation time of the in-memory ranking
+        let ranking = DAppRanking::from(record(last_update));
+        assert_eq!(ranking.last_update, last_update);
+
+        // Rankings serialize as RFC 3339 in UTC and read back unchanged
+        let json = serde_json::to_value(&ranking).unwrap();
+        assert_eq!(json["last_update"], "2025-06-15T15:06:40.123Z");
+        assert_eq!(serde_json::from_value::<DAppRanking>(json).unwrap().last_update, last_update);
+
+        // Records served by the API read back unchanged in the client
+        let json = serde_json::to_string(&record(last_update)).unwrap();
+        assert_eq!(serde_json::from_str::<DAppRankingRecord>(&json).unwrap().last_update, last_update);
+    }
 }
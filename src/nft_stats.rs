@@ -0,0 +1,157 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Mint and transfer activity of NFT DApps over the last 24 hours.
+//!
+//! HAU says little about a collection, so DApps of the `NFT` category also get
+//! category-specific figures. Their events are decoded by type name: an event
+//! struct whose name contains `Mint` (e.g. `MintEvent`, `NFTMinted`) is a
+//! mint, one containing `Transfer` a transfer. Mints and transfers are counted
+//! in hourly buckets like the event stats; minters are remembered with their
+//! last mint so unique minters cover exactly the last 24 hours.
+
+use crate::event_stats::{ActivityCounter, RecentUsers};
+use crate::models::{DAppInteraction, DAppNftStats};
+use std::collections::BTreeMap;
+use chrono::{DateTime, Utc};
+
+/// Registry category whose DApps get NFT stats
+pub const NFT_DAPP_TYPE: &str = "NFT";
+
+/// Label of mint events in the hourly counts
+const MINT: &str = "mint";
+/// Label of transfer events in the hourly counts
+const TRANSFER: &str = "transfer";
+
+/// NFT event an interaction's event type decodes to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NftEventKind {
+    Mint,
+    Transfer,
+}
+
+impl NftEventKind {
+    /// Decode an event type given as `module::struct`; None for other events
+    pub fn from_event_type(event_type: &str) -> Option<Self> {
+        let name = event_type.rsplit("::").next().unwrap_or(event_type).to_lowercase();
+        if name.contains(MINT) {
+            Some(NftEventKind::Mint)
+        } else if name.contains(TRANSFER) {
+            Some(NftEventKind::Transfer)
+        } else {
+            None
+        }
+    }
+}
+
+/// Rolling 24h mints, transfers and minters per NFT DApp
+#[derive(Debug, Clone, Default)]
+pub struct NftActivityCounter {
+    /// Hourly mint and transfer counts per DApp
+    counts: ActivityCounter,
+    /// Minters per DApp with their last mint
+    minters: RecentUsers,
+}
+
+impl NftActivityCounter {
+    /// Count the mint and transfer events among the interactions of NFT DApps
+    pub fn record<'a>(&mut self, interactions: impl IntoIterator<Item = &'a DAppInteraction>) {
+        for interaction in interactions {
+            let Some(dapp_name) = &interaction.dapp_name else {
+                continue;
+            };
+            match NftEventKind::from_event_type(&interaction.event_type) {
+                Some(NftEventKind::Mint) => {
+                    self.counts.add(dapp_name, MINT, interaction.timestamp);
+                    self.minters.add(dapp_name, &interaction.sender, interaction.timestamp);
+                }
+                Some(NftEventKind::Transfer) => self.counts.add(dapp_name, TRANSFER, interaction.timestamp),
+                None => {}
+            }
+        }
+    }
+
+    /// Forget mints and transfers older than 24 hours
+    pub fn prune(&mut self, now: DateTime<Utc>) {
+        self.counts.prune(now);
+        self.minters.prune(now);
+    }
+
+    /// Stats of every NFT DApp with mints or transfers in the last 24 hours, by DApp
+    pub fn stats(&self, now: DateTime<Utc>) -> Vec<DAppNftStats> {
+        let mut stats: BTreeMap<String, DAppNftStats> = BTreeMap::new();
+        for (dapp_name, label, count) in self.counts.totals(now) {
+            let entry = stats.entry(dapp_name.clone()).or_insert_with(|| DAppNftStats {
+                unique_minters_24h: self.minters.count(&dapp_name),
+                dapp_name,
+                mints_24h: 0,
+                transfers_24h: 0,
+                last_update: now,
+            });
+            match label.as_str() {
+                MINT => entry.mints_24h = count,
+                _ => entry.transfers_24h = count,
+            }
+        }
+        stats.into_values().collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.counts.clear();
+        self.minters.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::InteractionBuilder;
+    use std::time::Duration;
+
+    #[test]
+    fn test_counts_mints_minters_and_transfers_over_24h() {
+        assert_eq!(NftEventKind::from_event_type("collection::NFTMinted"), Some(NftEventKind::Mint));
+        assert_eq!(NftEventKind::from_event_type("kiosk::ItemTransferred"), Some(NftEventKind::Transfer));
+        assert_eq!(NftEventKind::from_event_type("kiosk::ItemListed"), None);
+
+        let now = DateTime::UNIX_EPOCH + Duration::from_secs(100 * 3600);
+        let mut counter = NftActivityCounter::default();
+        counter.record(&[
+            InteractionBuilder::new().dapp("Claynosaurz").sender("0xa").event_type("collection::MintEvent").hours_before(now, 1).build(),
+            InteractionBuilder::new().dapp("Claynosaurz").sender("0xa").event_type("collection::MintEvent").hours_before(now, 2).build(),
+            InteractionBuilder::new().dapp("Claynosaurz").sender("0xb").event_type("collection::MintEvent").hours_before(now, 3).build(),
+            InteractionBuilder::new().dapp("Claynosaurz").sender("0xc").event_type("collection::MintEvent").hours_before(now, 30).build(),
+            InteractionBuilder::new().dapp("Claynosaurz").sender("0xa").event_type("collection::TransferEvent").hours_before(now, 1).build(),
+            InteractionBuilder::new().dapp("Claynosaurz").sender("0xa").event_type("kiosk::ItemListed").hours_before(now, 1).build(),
+        ]);
+        counter.prune(now);
+
+        let stats = counter.stats(now);
+        assert_eq!(stats.len(), 1);
+        assert_eq!((stats[0].mints_24h, stats[0].unique_minters_24h, stats[0].transfers_24h), (3, 2, 1));
+    }
+
+    #[test]
+    fn test_unknown_events_and_dapps_are_ignored() {
+        let now = DateTime::UNIX_EPOCH + Duration::from_secs(100 * 3600);
+        let mut counter = NftActivityCounter::default();
+        counter.record(&[
+            // A collection with only unknown events gets no stats
+            InteractionBuilder::new().dapp("Prime Machin").event_type("kiosk::ItemListed").at(now).build(),
+            InteractionBuilder::new().dapp("Prime Machin").event_type("royalty::FeeCollected").at(now).build(),
+            // Not attributed to any DApp
+            InteractionBuilder::new().package("0xunknown").event_type("collection::MintEvent").at(now).build(),
+            // Its hour left the window
+            InteractionBuilder::new().dapp("Claynosaurz").event_type("collection::MintEvent").hours_before(now, 24).build(),
+            InteractionBuilder::new().dapp("Claynosaurz").event_type("collection::MintEvent").hours_before(now, 23).build(),
+        ]);
+        counter.prune(now);
+
+        let stats = counter.stats(now);
+        let stats: Vec<(&str, u64, u64)> = stats
+            .iter()
+            .map(|stats| (stats.dapp_name.as_str(), stats.mints_24h, stats.transfers_24h))
+            .collect();
+        assert_eq!(stats, [("Claynosaurz", 1, 0)]);
+    }
+}
@@ -0,0 +1,167 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Notifications meant to be read by people.
+//!
+//! Reports such as the weekly and monthly winners go through a `Notifier`.
+//! With NOTIFICATION_WEBHOOK_URL set they are POSTed as `{"text": ...}`, which
+//! Slack-compatible incoming webhooks render as is; otherwise they are only
+//! logged. Discord webhooks and Telegram bots have notifiers of their own, used
+//! by the leaderboard bot.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::info;
+
+/// Timeout of a single webhook request
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest message Discord accepts, in characters
+const DISCORD_MESSAGE_LIMIT: usize = 2000;
+
+/// Longest message Telegram accepts, in characters
+const TELEGRAM_MESSAGE_LIMIT: usize = 4096;
+
+/// Destination of textual reports
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    async fn notify(&self, text: &str) -> Result<()>;
+}
+
+/// Writes reports to the log
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogNotifier;
+
+#[async_trait]
+impl Notifier for LogNotifier {
+    async fn notify(&self, text: &str) -> Result<()> {
+        info!("📣 {}", text);
+        Ok(())
+    }
+}
+
+/// POSTs reports to a webhook as `{"text": ...}`
+pub struct WebhookNotifier {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: impl Into<String>) -> Result<Self> {
+        let client = reqwest::Client::builder().timeout(WEBHOOK_TIMEOUT).build()?;
+        Ok(Self { url: url.into(), client })
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    async fn notify(&self, text: &str) -> Result<()> {
+        self.client
+            .post(&self.url)
+            .json(&json!({ "text": text }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+/// `text` cut to `limit` characters, ending with an ellipsis when cut
+fn truncate(text: &str, limit: usize) -> String {
+    match text.char_indices().nth(limit.saturating_sub(1)) {
+        Some((end, _)) if text.chars().count() > limit => format!("{}…", &text[..end]),
+        _ => text.to_string(),
+    }
+}
+
+/// POSTs reports to a Discord channel webhook
+pub struct DiscordNotifier {
+    webhook_url: String,
+    client: reqwest::Client,
+}
+
+impl DiscordNotifier {
+    pub fn new(webhook_url: impl Into<String>) -> Result<Self> {
+        let client = reqwest::Client::builder().timeout(WEBHOOK_TIMEOUT).build()?;
+        Ok(Self { webhook_url: webhook_url.into(), client })
+    }
+}
+
+#[async_trait]
+impl Notifier for DiscordNotifier {
+    async fn notify(&self, text: &str) -> Result<()> {
+        self.client
+            .post(&self.webhook_url)
+            .json(&json!({ "content": truncate(text, DISCORD_MESSAGE_LIMIT) }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Sends reports to a Telegram chat through a bot
+pub struct TelegramNotifier {
+    bot_token: String,
+    /// Numeric id of the chat, or @username of a public channel
+    chat_id: String,
+    client: reqwest::Client,
+}
+
+impl TelegramNotifier {
+    pub fn new(bot_token: impl Into<String>, chat_id: impl Into<String>) -> Result<Self> {
+        let client = reqwest::Client::builder().timeout(WEBHOOK_TIMEOUT).build()?;
+        Ok(Self { bot_token: bot_token.into(), chat_id: chat_id.into(), client })
+    }
+}
+
+#[async_trait]
+impl Notifier for TelegramNotifier {
+    async fn notify(&self, text: &str) -> Result<()> {
+        // The URL holds the token, so errors are reported without it
+        self.client
+            .post(format!("https://api.telegram.org/bot{}/sendMessage", self.bot_token))
+            .json(&json!({
+                "chat_id": self.chat_id,
+                "text": truncate(text, TELEGRAM_MESSAGE_LIMIT),
+                "disable_web_page_preview": true,
+            }))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|err| anyhow!("Telegram sendMessage failed: {}", err.without_url()))?;
+        Ok(())
+    }
+}
+
+/// Sends every report to all of its notifiers
+pub struct FanOutNotifier(pub Vec<Arc<dyn Notifier>>);
+
+#[async_trait]
+impl Notifier for FanOutNotifier {
+    /// Fails if any notifier failed, after trying all of them
+    async fn notify(&self, text: &str) -> Result<()> {
+        let mut errors = Vec::new();
+        for notifier in &self.0 {
+            if let Err(err) = notifier.notify(text).await {
+                errors.push(err.to_string());
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!("{}", errors.join("; ")))
+        }
+    }
+}
+
+/// The webhook notifier if a URL is configured, the log otherwise
+pub fn notifier(webhook_url: Option<&str>) -> Result<Arc<dyn Notifier>> {
+    Ok(match webhook_url {
+        Some(url) => Arc::new(WebhookNotifier::new(url)?),
+        None => Arc::new(LogNotifier),
+    })
+}
@@ -0,0 +1,112 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! In-process CPU and heap profiling.
+//!
+//! CPU profiles are sampled with `pprof` for a fixed duration and returned as a
+//! pprof protobuf (readable by `go tool pprof`) or as an SVG flamegraph, so
+//! backfill throughput can be profiled on a live indexer instead of reproducing
+//! the load locally.
+//!
+//! Heap profiles are dumped from jemalloc's sampling profiler (readable by
+//! `jeprof`). They require building with the `heap-profiling` feature, which
+//! makes jemalloc the global allocator with profiling enabled.
+
+use anyhow::{anyhow, Context, Result};
+use pprof::protos::Message;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// Whether this binary was built with heap profiling support
+pub const HEAP_PROFILING_AVAILABLE: bool = cfg!(feature = "heap-profiling");
+
+/// The CPU profiler is process-wide, so only one profile can run at a time
+static CPU_PROFILE_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Output format of a CPU profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CpuProfileFormat {
+    /// pprof protobuf, for `go tool pprof`
+    Pprof,
+    /// SVG flamegraph
+    Flamegraph,
+}
+
+/// Sample the CPU for `duration` and render the profile
+///
+/// Returns `Ok(None)` if another CPU profile is already in progress.
+///
+/// # Arguments
+/// * `duration` - How long to sample
+/// * `frequency` - Samples per second
+/// * `format` - Output format of the profile
+pub async fn capture_cpu_profile(
+    duration: Duration,
+    frequency: i32,
+    format: CpuProfileFormat,
+) -> Result<Option<Vec<u8>>> {
+    if CPU_PROFILE_RUNNING.swap(true, Ordering::SeqCst) {
+        return Ok(None);
+    }
+
+    // The profiler guard is not Send, so sampling runs on a blocking thread
+    let result = tokio::task::spawn_blocking(move || run_cpu_profile(duration, frequency, format))
+        .await
+        .context("CPU profiling task failed");
+
+    CPU_PROFILE_RUNNING.store(false, Ordering::SeqCst);
+    result?.map(Some)
+}
+
+fn run_cpu_profile(duration: Duration, frequency: i32, format: CpuProfileFormat) -> Result<Vec<u8>> {
+    let guard = pprof::ProfilerGuardBuilder::default()
+        .frequency(frequency)
+        .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+        .build()
+        .context("Failed to start CPU profiler")?;
+
+    std::thread::sleep(duration);
+
+    let report = guard.report().build().context("Failed to build CPU profile report")?;
+    let mut body = Vec::new();
+    match format {
+        CpuProfileFormat::Pprof => {
+            let profile = report.pprof().context("Failed to encode CPU profile")?;
+            profile.encode(&mut body).context("Failed to encode CPU profile")?;
+        }
+        CpuProfileFormat::Flamegraph => {
+            report.flamegraph(&mut body).context("Failed to render flamegraph")?;
+        }
+    }
+
+    Ok(body)
+}
+
+/// Dump the current jemalloc heap profile
+#[cfg(feature = "heap-profiling")]
+pub fn dump_heap_profile() -> Result<Vec<u8>> {
+    use std::ffi::CString;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or(0);
+    let path = std::env::temp_dir().join(format!("suins-indexer-heap-{}-{}.prof", std::process::id(), nanos));
+    let c_path = CString::new(path.to_string_lossy().as_bytes())?;
+
+    // SAFETY: prof.dump takes a NUL-terminated file path that outlives the call
+    unsafe { tikv_jemalloc_ctl::raw::write(b"prof.dump\0", c_path.as_ptr()) }
+        .map_err(|err| anyhow!("jemalloc prof.dump failed (is profiling enabled?): {}", err))?;
+
+    let profile = std::fs::read(&path)
+        .with_context(|| format!("Failed to read heap profile {}", path.display()));
+    let _ = std::fs::remove_file(&path);
+    profile
+}
+
+/// Dump the current jemalloc heap profile
+#[cfg(not(feature = "heap-profiling"))]
+pub fn dump_heap_profile() -> Result<Vec<u8>> {
+    Err(anyhow!("Heap profiling requires building with the `heap-profiling` feature"))
+}
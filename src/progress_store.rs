@@ -0,0 +1,227 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Checkpoint progress stores.
+//!
+//! The ingestion executor records the last processed checkpoint per task in a
+//! progress store. Besides the ingestion-core `FileProgressStore`, progress can
+//! be kept in the `checkpoint_progress` table so it is not tied to the disk of a
+//! single machine.
+
+use crate::database::DatabaseManager;
+use crate::leader_election::Leadership;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use sui_data_ingestion_core::{FileProgressStore, ProgressStore};
+use sui_types::messages_checkpoint::CheckpointSequenceNumber;
+use tokio::sync::watch;
+use tracing::info;
+
+/// Where checkpoint progress is stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressStoreKind {
+    /// Local progress file
+    File,
+    /// `checkpoint_progress` table in Postgres
+    Postgres,
+}
+
+impl FromStr for ProgressStoreKind {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "file" => Ok(ProgressStoreKind::File),
+            "postgres" | "database" => Ok(ProgressStoreKind::Postgres),
+            _ => Err(anyhow!("Unknown progress store '{}', expected file or postgres", value)),
+        }
+    }
+}
+
+/// Progress store keeping one watermark per task in the `checkpoint_progress` table
+/// Only the leader saves progress; standbys start from the leader's watermark
+pub struct PostgresProgressStore {
+    db_manager: Arc<DatabaseManager>,
+    leadership: Leadership,
+}
+
+impl PostgresProgressStore {
+    pub fn new(db_manager: Arc<DatabaseManager>) -> Self {
+        Self { db_manager, leadership: Leadership::default() }
+    }
+
+    /// Save progress only while this instance is the leader
+    pub fn with_leadership(mut self, leadership: Leadership) -> Self {
+        self.leadership = leadership;
+        self
+    }
+}
+
+#[async_trait]
+impl ProgressStore for PostgresProgressStore {
+    async fn load(&mut self, task_name: String) -> Result<CheckpointSequenceNumber> {
+        let checkpoint_number = self
+            .db_manager
+            .with_retry("load checkpoint progress", || self.db_manager.load_checkpoint_progress(&task_name))
+            .await?;
+        Ok(checkpoint_number.unwrap_or(0) as CheckpointSequenceNumber)
+    }
+
+    async fn save(&mut self, task_name: String, checkpoint_number: CheckpointSequenceNumber) -> Result<()> {
+        if !self.leadership.is_leader() {
+            return Ok(());
+        }
+        self.db_manager
+            .with_retry("save checkpoint progress", || {
+                self.db_manager.save_checkpoint_progress(&task_name, checkpoint_number as i64)
+            })
+            .await
+    }
+}
+
+/// Progress store selected at startup
+pub enum IndexerProgressStore {
+    File(FileProgressStore),
+    Postgres(PostgresProgressStore),
+}
+
+#[async_trait]
+impl ProgressStore for IndexerProgressStore {
+    async fn load(&mut self, task_name: String) -> Result<CheckpointSequenceNumber> {
+        match self {
+            IndexerProgressStore::File(store) => store.load(task_name).await,
+            IndexerProgressStore::Postgres(store) => store.load(task_name).await,
+        }
+    }
+
+    async fn save(&mut self, task_name: String, checkpoint_number: CheckpointSequenceNumber) -> Result<()> {
+        match self {
+            IndexerProgressStore::File(store) => store.save(task_name, checkpoint_number).await,
+            IndexerProgressStore::Postgres(store) => store.save(task_name, checkpoint_number).await,
+        }
+    }
+}
+
+/// Where a rewound progress store starts the tasks
+enum Rewind {
+    /// This many checkpoints before the saved progress
+    Checkpoints(u64),
+    /// At this checkpoint, unless the saved progress is older
+    To(CheckpointSequenceNumber),
+}
+
+/// Progress store wrapper starting each task before its saved watermark, so
+/// recent checkpoints are processed again after a restart
+pub struct RewoundProgressStore<P> {
+    inner: P,
+    rewind: Rewind,
+}
+
+impl<P: ProgressStore> RewoundProgressStore<P> {
+    /// Start `checkpoints` before the saved progress
+    pub fn new(inner: P, checkpoints: u64) -> Self {
+        Self { inner, rewind: Rewind::Checkpoints(checkpoints) }
+    }
+
+    /// Start at `checkpoint_number`, or at the saved progress if it is older
+    pub fn to_checkpoint(inner: P, checkpoint_number: CheckpointSequenceNumber) -> Self {
+        Self { inner, rewind: Rewind::To(checkpoint_number) }
+    }
+}
+
+#[async_trait]
+impl<P: ProgressStore> ProgressStore for RewoundProgressStore<P> {
+    async fn load(&mut self, task_name: String) -> Result<CheckpointSequenceNumber> {
+        let checkpoint_number = self.inner.load(task_name.clone()).await?;
+        let rewound = match self.rewind {
+            Rewind::Checkpoints(checkpoints) => checkpoint_number.saturating_sub(checkpoints),
+            Rewind::To(start) => checkpoint_number.min(start),
+        };
+        if rewound < checkpoint_number {
+            info!("⏪ {}: replaying from checkpoint {} (saved progress {})", task_name, rewound, checkpoint_number);
+        }
+        Ok(rewound)
+    }
+
+    async fn save(&mut self, task_name: String, checkpoint_number: CheckpointSequenceNumber) -> Result<()> {
+        self.inner.save(task_name, checkpoint_number).await
+    }
+}
+
+/// Progress store wrapper publishing the committed watermark
+/// The watermark is the lowest checkpoint number saved across all tasks
+pub struct WatchedProgressStore<P> {
+    inner: P,
+    task_watermarks: HashMap<String, CheckpointSequenceNumber>,
+    watermark: watch::Sender<CheckpointSequenceNumber>,
+}
+
+impl<P: ProgressStore> WatchedProgressStore<P> {
+    pub fn new(inner: P) -> (Self, watch::Receiver<CheckpointSequenceNumber>) {
+        let (watermark, receiver) = watch::channel(0);
+        let store = Self { inner, task_watermarks: HashMap::new(), watermark };
+        (store, receiver)
+    }
+
+    fn publish(&mut self, task_name: String, checkpoint_number: CheckpointSequenceNumber) {
+        self.task_watermarks.insert(task_name, checkpoint_number);
+        if let Some(watermark) = self.task_watermarks.values().min() {
+            self.watermark.send_replace(*watermark);
+        }
+    }
+}
+
+#[async_trait]
+impl<P: ProgressStore> ProgressStore for WatchedProgressStore<P> {
+    async fn load(&mut self, task_name: String) -> Result<CheckpointSequenceNumber> {
+        let checkpoint_number = self.inner.load(task_name.clone()).await?;
+        self.publish(task_name, checkpoint_number);
+        Ok(checkpoint_number)
+    }
+
+    async fn save(&mut self, task_name: String, checkpoint_number: CheckpointSequenceNumber) -> Result<()> {
+        self.inner.save(task_name.clone(), checkpoint_number).await?;
+        self.publish(task_name, checkpoint_number);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct MemoryProgressStore(HashMap<String, CheckpointSequenceNumber>);
+
+    #[async_trait]
+    impl ProgressStore for MemoryProgressStore {
+        async fn load(&mut self, task_name: String) -> Result<CheckpointSequenceNumber> {
+            Ok(self.0.get(&task_name).copied().unwrap_or(0))
+        }
+
+        async fn save(&mut self, task_name: String, checkpoint_number: CheckpointSequenceNumber) -> Result<()> {
+            self.0.insert(task_name, checkpoint_number);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rewound_store_starts_before_saved_progress() {
+        let inner = MemoryProgressStore(HashMap::from([("indexing".to_string(), 20_000), ("new".to_string(), 100)]));
+        let mut store = RewoundProgressStore::new(inner, 18_000);
+        assert_eq!(store.load("indexing".to_string()).await.unwrap(), 2_000);
+        assert_eq!(store.load("new".to_string()).await.unwrap(), 0);
+
+        // Progress is saved as reported
+        store.save("indexing".to_string(), 2_001).await.unwrap();
+        assert_eq!(store.inner.0["indexing"], 2_001);
+
+        let mut store = RewoundProgressStore::to_checkpoint(store.inner, 5_000);
+        assert_eq!(store.load("indexing".to_string()).await.unwrap(), 2_001);
+        assert_eq!(store.load("new".to_string()).await.unwrap(), 100);
+        store.save("indexing".to_string(), 9_000).await.unwrap();
+        assert_eq!(store.load("indexing".to_string()).await.unwrap(), 5_000);
+    }
+}
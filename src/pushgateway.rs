@@ -0,0 +1,120 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Pushing metrics to a Prometheus Pushgateway.
+//!
+//! The indexer serves its metrics on the basic Prometheus server, which is of
+//! little use for short-lived jobs such as a backtest or a one-off backfill:
+//! they are gone before the next scrape. With PROMETHEUS_PUSHGATEWAY_URL set,
+//! the gathered metrics are PUT to `<url>/metrics/job/<job>` every interval
+//! and once more when a job finishes, replacing the metrics previously pushed
+//! for that job. Any `Registry` can be pushed, so library users embedding the
+//! aggregator or the workers register their metrics in a registry of their own
+//! and push or serve it as they see fit.
+
+use anyhow::{anyhow, Result};
+use prometheus::{Encoder, Registry, TextEncoder};
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+use tracing::{error, info};
+
+/// Timeout of a single push
+const PUSH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where and how often metrics are pushed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PushGatewaySettings {
+    /// Base URL of the Pushgateway
+    pub url: String,
+    /// Job label the metrics are grouped under
+    pub job: String,
+    /// Time between two pushes of a long-running indexer
+    pub interval: Duration,
+}
+
+/// Pushes the metrics of one registry to a Pushgateway
+#[derive(Clone)]
+pub struct MetricsPusher {
+    registry: Registry,
+    push_url: String,
+    client: reqwest::Client,
+}
+
+impl MetricsPusher {
+    pub fn new(registry: Registry, settings: &PushGatewaySettings) -> Result<Self> {
+        let client = reqwest::Client::builder().timeout(PUSH_TIMEOUT).build()?;
+        Ok(Self { registry, push_url: push_url(&settings.url, &settings.job), client })
+    }
+
+    /// Push the current value of every metric, replacing those pushed before
+    pub async fn push(&self) -> Result<()> {
+        self.client
+            .put(&self.push_url)
+            .header(reqwest::header::CONTENT_TYPE, TextEncoder::new().format_type())
+            .body(encode_metrics(&self.registry)?)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+/// URL of the metrics group of `job`, with the job name escaped for the path
+fn push_url(base_url: &str, job: &str) -> String {
+    let job: String = job
+        .bytes()
+        .map(|byte| match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' | b'.' => (byte as char).to_string(),
+            byte => format!("%{:02X}", byte),
+        })
+        .collect();
+    format!("{}/metrics/job/{}", base_url.trim_end_matches('/'), job)
+}
+
+/// Every metric of `registry` in the Prometheus text format
+pub fn encode_metrics(registry: &Registry) -> Result<Vec<u8>> {
+    let mut buffer = Vec::new();
+    TextEncoder::new()
+        .encode(&registry.gather(), &mut buffer)
+        .map_err(|err| anyhow!("Failed to encode metrics: {}", err))?;
+    Ok(buffer)
+}
+
+/// Start the task pushing the metrics of `registry` every interval
+/// Returns the pusher, to push a last time before exiting
+pub async fn start_metrics_push_job(registry: Registry, settings: PushGatewaySettings) -> Result<MetricsPusher> {
+    let pusher = MetricsPusher::new(registry, &settings)?;
+    info!("📮 Pushing metrics to {} every {}s", pusher.push_url, settings.interval.as_secs());
+
+    let job_pusher = pusher.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(settings.interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if let Err(err) = job_pusher.push().await {
+                error!("❌ Failed to push metrics to the Pushgateway: {}", err);
+            }
+        }
+    });
+    Ok(pusher)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prometheus::{register_int_counter_with_registry, Opts};
+
+    #[test]
+    fn test_encodes_a_custom_registry_for_its_job() {
+        assert_eq!(push_url("http://gateway:9091/", "backtest"), "http://gateway:9091/metrics/job/backtest");
+        assert_eq!(push_url("http://gateway:9091", "dapp ranking/1"), "http://gateway:9091/metrics/job/dapp%20ranking%2F1");
+
+        let registry = Registry::new_custom(Some("surfsui".to_string()), None).unwrap();
+        let checkpoints =
+            register_int_counter_with_registry!(Opts::new("checkpoints", "Checkpoints processed"), registry).unwrap();
+        checkpoints.inc_by(3);
+        let metrics = String::from_utf8(encode_metrics(&registry).unwrap()).unwrap();
+        assert!(metrics.contains("surfsui_checkpoints 3"), "{}", metrics);
+    }
+}
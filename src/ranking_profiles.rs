@@ -0,0 +1,96 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Named ranking profiles sharing one indexer.
+//!
+//! Besides the live rankings over RANKING_WINDOW, RANKING_PROFILES adds
+//! leaderboards over other windows, e.g. `dau_24h=24h,wau=7d`. Checkpoints are
+//! ingested and extracted once; every profile keeps the interactions of its own
+//! window and is ranked with the same registry, sender filters and score
+//! formula whenever the live rankings are. Profiles are stored in
+//! `dapp_rankings` with their name as `window_label` and served by
+//! `GET /rankings?window=<name>`.
+
+use crate::interaction_store::parse_window;
+use crate::models::{DAppInteraction, DAppRanking, DAppRegistryEntry};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Rankings over a window of their own, next to the live rankings
+#[derive(Debug, Clone)]
+pub struct RankingProfile {
+    /// Window label the rankings are stored under
+    pub name: String,
+    pub window: Duration,
+    /// Interactions of tracked DApps within the window
+    pub interactions: Vec<DAppInteraction>,
+    /// Latest rankings, best first
+    pub rankings: Vec<DAppRanking>,
+}
+
+impl RankingProfile {
+    pub fn new(name: impl Into<String>, window: Duration) -> Self {
+        Self { name: name.into(), window, interactions: Vec::new(), rankings: Vec::new() }
+    }
+
+    /// Start of the window ending at `now`
+    pub fn window_start(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now - self.window
+    }
+
+    /// Keep the interactions that fall within the window
+    pub fn record(&mut self, interactions: &[DAppInteraction], now: DateTime<Utc>) {
+        let window_start = self.window_start(now);
+        self.interactions.extend(
+            interactions.iter().filter(|interaction| interaction.timestamp >= window_start).cloned(),
+        );
+    }
+
+    /// Drop interactions older than the window or of DApps no longer tracked
+    pub fn prune(&mut self, now: DateTime<Utc>, dapp_names: &HashMap<String, DAppRegistryEntry>) {
+        let window_start = self.window_start(now);
+        self.interactions.retain(|interaction| {
+            interaction.timestamp >= window_start && dapp_names.contains_key(&interaction.package_id)
+        });
+    }
+}
+
+/// Parse `name=window` pairs separated by commas, e.g. `dau_24h=24h,wau=7d`
+/// Names are letters, digits, `_` and `-`, and must be unique
+pub fn parse_ranking_profiles(value: &str) -> Result<Vec<(String, Duration)>> {
+    let mut profiles: Vec<(String, Duration)> = Vec::new();
+    for pair in value.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+        let (name, window) = pair
+            .split_once('=')
+            .ok_or_else(|| anyhow!("Invalid ranking profile '{}', expected name=window (e.g. dau_24h=24h)", pair))?;
+        let name = name.trim();
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+            return Err(anyhow!("Invalid ranking profile name '{}', use letters, digits, _ and -", name));
+        }
+        if profiles.iter().any(|(existing, _)| existing == name) {
+            return Err(anyhow!("Ranking profile '{}' is configured twice", name));
+        }
+        profiles.push((name.to_string(), parse_window(window)?));
+    }
+    Ok(profiles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ranking_profiles() {
+        assert_eq!(parse_ranking_profiles("dau_24h=24h, wau=7d").unwrap(), [
+            ("dau_24h".to_string(), Duration::from_secs(24 * 60 * 60)),
+            ("wau".to_string(), Duration::from_secs(7 * 24 * 60 * 60)),
+        ]);
+        assert!(parse_ranking_profiles("").unwrap().is_empty());
+        assert!(parse_ranking_profiles("24h").is_err());
+        assert!(parse_ranking_profiles("dau 24h=24h").is_err());
+        assert!(parse_ranking_profiles("wau=7d,wau=1d").is_err());
+        assert!(parse_ranking_profiles("wau=week").is_err());
+    }
+}
@@ -0,0 +1,167 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Filtering, sorting and pagination of the persisted rankings.
+//!
+//! Backs leaderboard views that page through the rankings, sort them by any
+//! metric and narrow them down to one category or to DApps above a minimum
+//! number of Hourly Active Users.
+
+use crate::models::DAppRankingRecord;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::str::FromStr;
+
+/// Largest page a single query returns
+pub const MAX_PAGE_SIZE: i64 = 1000;
+
+/// Metric column rankings can be sorted by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RankingSortColumn {
+    /// Rank position (ascending is best first)
+    #[default]
+    Rank,
+    Hau,
+    FilteredUsers,
+    NewUsers,
+    ReturningUsers,
+    TxCount,
+    GasUsed,
+    Score,
+    DauSmoothed,
+    SponsoredUserPct,
+    AvgGasPerTx,
+    Name,
+}
+
+impl FromStr for RankingSortColumn {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "rank" => Ok(RankingSortColumn::Rank),
+            "hau" | "dau" | "dau_1h" | "active_users" => Ok(RankingSortColumn::Hau),
+            "filtered_users" => Ok(RankingSortColumn::FilteredUsers),
+            "new_users" | "new_users_24h" => Ok(RankingSortColumn::NewUsers),
+            "returning_users" | "returning_users_24h" => Ok(RankingSortColumn::ReturningUsers),
+            "tx_count" | "tx_count_1h" => Ok(RankingSortColumn::TxCount),
+            "gas_used" | "gas_used_1h" => Ok(RankingSortColumn::GasUsed),
+            "score" => Ok(RankingSortColumn::Score),
+            "dau_smoothed" | "hau_smoothed" => Ok(RankingSortColumn::DauSmoothed),
+            "sponsored_user_pct" | "sponsored" => Ok(RankingSortColumn::SponsoredUserPct),
+            "avg_gas_per_tx" | "avg_gas" => Ok(RankingSortColumn::AvgGasPerTx),
+            "name" | "dapp_name" => Ok(RankingSortColumn::Name),
+            _ => Err(anyhow!(
+                "Unknown sort column '{}', expected rank, hau, filtered_users, new_users, returning_users, tx_count, gas_used, score, dau_smoothed, sponsored_user_pct, avg_gas_per_tx or name",
+                value
+            )),
+        }
+    }
+}
+
+impl RankingSortColumn {
+    /// Name accepted by `from_str`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RankingSortColumn::Rank => "rank",
+            RankingSortColumn::Hau => "hau",
+            RankingSortColumn::FilteredUsers => "filtered_users",
+            RankingSortColumn::NewUsers => "new_users",
+            RankingSortColumn::ReturningUsers => "returning_users",
+            RankingSortColumn::TxCount => "tx_count",
+            RankingSortColumn::GasUsed => "gas_used",
+            RankingSortColumn::Score => "score",
+            RankingSortColumn::DauSmoothed => "dau_smoothed",
+            RankingSortColumn::SponsoredUserPct => "sponsored_user_pct",
+            RankingSortColumn::AvgGasPerTx => "avg_gas_per_tx",
+            RankingSortColumn::Name => "name",
+        }
+    }
+
+    /// Direction used when none is requested: rank and name ascending, metrics descending
+    pub fn default_order(&self) -> SortOrder {
+        match self {
+            RankingSortColumn::Rank | RankingSortColumn::Name => SortOrder::Asc,
+            _ => SortOrder::Desc,
+        }
+    }
+}
+
+/// Sort direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    /// Name accepted by `from_str`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "asc",
+            SortOrder::Desc => "desc",
+        }
+    }
+}
+
+impl FromStr for SortOrder {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "asc" => Ok(SortOrder::Asc),
+            "desc" => Ok(SortOrder::Desc),
+            _ => Err(anyhow!("Unknown sort order '{}', expected asc or desc", value)),
+        }
+    }
+}
+
+/// Which rankings to return and in which order
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankingFilter {
+    /// Only DApps of this category
+    pub dapp_type: Option<String>,
+    /// Only DApps with at least this many Hourly Active Users
+    pub min_dau: Option<i32>,
+    /// Rankings of this window or ranking profile instead of the live rankings
+    pub window: Option<String>,
+    pub sort_by: RankingSortColumn,
+    /// Defaults to the column's natural direction
+    pub order: Option<SortOrder>,
+    pub offset: i64,
+    pub limit: i64,
+}
+
+impl Default for RankingFilter {
+    fn default() -> Self {
+        Self {
+            dapp_type: None,
+            min_dau: None,
+            window: None,
+            sort_by: RankingSortColumn::default(),
+            order: None,
+            offset: 0,
+            limit: 100,
+        }
+    }
+}
+
+impl RankingFilter {
+    /// Direction the rankings are sorted in
+    pub fn sort_order(&self) -> SortOrder {
+        self.order.unwrap_or_else(|| self.sort_by.default_order())
+    }
+
+    /// Offset and limit clamped to valid values
+    pub fn page(&self) -> (i64, i64) {
+        (self.offset.max(0), self.limit.clamp(1, MAX_PAGE_SIZE))
+    }
+}
+
+/// One page of rankings
+#[derive(Debug, Serialize)]
+pub struct RankingPage {
+    pub rankings: Vec<DAppRankingRecord>,
+    /// Number of rankings matching the filter across all pages
+    pub total: i64,
+}
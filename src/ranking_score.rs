@@ -0,0 +1,257 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Composite ranking score.
+//!
+//! DApps are ranked by a weighted sum of metrics such as
+//! `0.6*DAU + 0.3*tx_count + 0.1*gas`, or `0.5*DAU + 0.5*volume` to weigh in
+//! the 24h swap volume of DEXes and aggregators. Each metric is divided by its maximum
+//! across the ranked DApps before weighting, so weights express relative
+//! importance regardless of the metrics' units. The default formula `DAU`
+//! ranks strictly by Hourly Active Users.
+//!
+//! Raw HAU is noisy, so an exponentially weighted moving average over the last
+//! ranking computations (`dau_smoothed`) can be ranked by instead, avoiding
+//! rank flapping between close DApps.
+
+use anyhow::{anyhow, Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+/// A metric that can appear in the score formula
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoreMetric {
+    /// Hourly Active Users after sender filtering
+    Dau,
+    /// Distinct transactions in the window
+    TxCount,
+    /// Gas used by those transactions (MIST)
+    Gas,
+    /// Moving average of Hourly Active Users over the last ranking computations
+    DauSmoothed,
+    /// USD swap volume over the last 24h, 0 outside DEXes and aggregators
+    Volume,
+}
+
+impl ScoreMetric {
+    fn name(&self) -> &'static str {
+        match self {
+            ScoreMetric::Dau => "dau",
+            ScoreMetric::TxCount => "tx_count",
+            ScoreMetric::Gas => "gas",
+            ScoreMetric::DauSmoothed => "dau_smoothed",
+            ScoreMetric::Volume => "volume",
+        }
+    }
+}
+
+impl FromStr for ScoreMetric {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "dau" | "hau" => Ok(ScoreMetric::Dau),
+            "tx_count" | "tx" | "transactions" => Ok(ScoreMetric::TxCount),
+            "gas" => Ok(ScoreMetric::Gas),
+            "dau_smoothed" | "hau_smoothed" => Ok(ScoreMetric::DauSmoothed),
+            "volume" | "volume_24h_usd" => Ok(ScoreMetric::Volume),
+            other => Err(anyhow!("Unknown ranking metric '{}', expected dau, tx_count, gas, dau_smoothed or volume", other)),
+        }
+    }
+}
+
+/// Raw metrics of one DApp for the ranking window
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RankingMetrics {
+    pub dau: u64,
+    pub tx_count: u64,
+    pub gas: u64,
+    pub dau_smoothed: f64,
+    pub volume_usd: f64,
+}
+
+impl RankingMetrics {
+    fn get(&self, metric: ScoreMetric) -> f64 {
+        match metric {
+            ScoreMetric::Dau => self.dau as f64,
+            ScoreMetric::TxCount => self.tx_count as f64,
+            ScoreMetric::Gas => self.gas as f64,
+            ScoreMetric::DauSmoothed => self.dau_smoothed,
+            ScoreMetric::Volume => self.volume_usd,
+        }
+    }
+}
+
+/// Weighted sum of normalized metrics used to order the rankings
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoreFormula {
+    terms: Vec<(f64, ScoreMetric)>,
+}
+
+impl Default for ScoreFormula {
+    fn default() -> Self {
+        Self { terms: vec![(1.0, ScoreMetric::Dau)] }
+    }
+}
+
+impl ScoreFormula {
+    /// Score every DApp, normalizing each metric by its maximum across `metrics`
+    pub fn score_all(&self, metrics: &[RankingMetrics]) -> Vec<f64> {
+        let maxima: Vec<f64> = self
+            .terms
+            .iter()
+            .map(|(_, metric)| metrics.iter().map(|m| m.get(*metric)).fold(0.0, f64::max))
+            .collect();
+
+        metrics
+            .iter()
+            .map(|m| {
+                self.terms
+                    .iter()
+                    .zip(&maxima)
+                    .filter(|(_, max)| **max > 0.0)
+                    .map(|((weight, metric), max)| weight * m.get(*metric) / *max)
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+impl FromStr for ScoreFormula {
+    type Err = anyhow::Error;
+
+    /// Parse a formula such as `0.6*DAU + 0.3*tx_count + 0.1*gas`
+    /// A term without a weight (e.g. `DAU`) has weight 1
+    fn from_str(formula: &str) -> Result<Self> {
+        let mut terms: Vec<(f64, ScoreMetric)> = Vec::new();
+
+        for term in formula.split('+') {
+            let term = term.trim();
+            if term.is_empty() {
+                return Err(anyhow!("Empty term in ranking formula '{}'", formula));
+            }
+
+            let (weight, metric) = match term.split_once('*') {
+                Some((weight, metric)) => {
+                    let weight = weight
+                        .trim()
+                        .parse::<f64>()
+                        .with_context(|| format!("Invalid weight in ranking term '{}'", term))?;
+                    (weight, metric.parse::<ScoreMetric>()?)
+                }
+                None => (1.0, term.parse::<ScoreMetric>()?),
+            };
+
+            if !weight.is_finite() || weight <= 0.0 {
+                return Err(anyhow!("Weight of '{}' must be a positive number", metric.name()));
+            }
+            if terms.iter().any(|(_, existing)| *existing == metric) {
+                return Err(anyhow!("Metric '{}' appears more than once in the ranking formula", metric.name()));
+            }
+            terms.push((weight, metric));
+        }
+
+        Ok(Self { terms })
+    }
+}
+
+impl fmt::Display for ScoreFormula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let terms: Vec<String> = self
+            .terms
+            .iter()
+            .map(|(weight, metric)| format!("{}*{}", weight, metric.name()))
+            .collect();
+        write!(f, "{}", terms.join(" + "))
+    }
+}
+
+/// Exponentially weighted moving average of each DApp's HAU across ranking computations
+#[derive(Debug, Clone)]
+pub struct DauSmoother {
+    /// Weight of the latest computation
+    alpha: f64,
+    /// dapp_name -> current average
+    averages: HashMap<String, f64>,
+}
+
+impl DauSmoother {
+    /// Average over about the last `window` computations (alpha = 2 / (window + 1))
+    pub fn new(window: u32) -> Self {
+        Self {
+            alpha: 2.0 / (window.max(1) as f64 + 1.0),
+            averages: HashMap::new(),
+        }
+    }
+
+    /// Fold the latest HAU of a DApp into its average; the first value seeds it
+    pub fn update(&mut self, dapp_name: &str, dau: u32) -> f64 {
+        let dau = dau as f64;
+        let average = self
+            .averages
+            .entry(dapp_name.to_string())
+            .and_modify(|average| *average += self.alpha * (dau - *average))
+            .or_insert(dau);
+        *average
+    }
+
+    /// Forget DApps that are no longer ranked, so they start over when they return
+    pub fn retain(&mut self, dapp_names: &HashSet<&str>) {
+        self.averages.retain(|dapp_name, _| dapp_names.contains(dapp_name.as_str()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_validate_formula() {
+        let formula: ScoreFormula = "0.6*DAU + 0.3*tx_count + 0.1*gas".parse().unwrap();
+        assert_eq!(formula.to_string(), "0.6*dau + 0.3*tx_count + 0.1*gas");
+        assert_eq!("DAU".parse::<ScoreFormula>().unwrap(), ScoreFormula::default());
+
+        assert!("0.5*dau + 0.5*dau".parse::<ScoreFormula>().is_err());
+        assert!("0.5*tvl".parse::<ScoreFormula>().is_err());
+        assert!("-1*gas".parse::<ScoreFormula>().is_err());
+        assert!("dau +".parse::<ScoreFormula>().is_err());
+    }
+
+    #[test]
+    fn test_score_normalizes_metrics() {
+        let formula: ScoreFormula = "0.5*dau + 0.5*gas".parse().unwrap();
+        let metrics = [
+            RankingMetrics { dau: 100, gas: 1_000, ..Default::default() },
+            RankingMetrics { dau: 50, gas: 1_000_000, ..Default::default() },
+        ];
+        let scores = formula.score_all(&metrics);
+        assert!((scores[0] - 0.5005).abs() < 1e-9);
+        assert!((scores[1] - 0.75).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_swap_volume_weighs_in_the_score() {
+        let formula: ScoreFormula = "0.5*dau + 0.5*volume_24h_usd".parse().unwrap();
+        assert_eq!(formula.to_string(), "0.5*dau + 0.5*volume");
+        // A DEX with fewer users but most of the volume outranks a busier non-DEX
+        let metrics = [
+            RankingMetrics { dau: 100, ..Default::default() },
+            RankingMetrics { dau: 60, volume_usd: 2_000_000.0, ..Default::default() },
+        ];
+        let scores = formula.score_all(&metrics);
+        assert!((scores[0] - 0.5).abs() < 1e-9);
+        assert!((scores[1] - 0.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_smoothed_dau_dampens_spikes() {
+        let mut smoother = DauSmoother::new(3);
+        assert_eq!(smoother.update("Cetus AMM", 100), 100.0);
+        assert_eq!(smoother.update("Cetus AMM", 200), 150.0);
+        assert_eq!(smoother.update("Cetus AMM", 100), 125.0);
+
+        smoother.retain(&HashSet::new());
+        assert_eq!(smoother.update("Cetus AMM", 40), 40.0);
+    }
+}
@@ -0,0 +1,269 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Weekly and monthly ranking snapshots.
+//!
+//! On the first day of a week (Monday) or month, in the stats timezone, the
+//! top-N of the live rankings is frozen into `dapp_ranking_weekly` or
+//! `dapp_ranking_monthly` as the result of the period that just ended. The
+//! snapshot is compared with the one of the period before, and a winners
+//! report naming the biggest movers is sent through the notifier. The job
+//! checks every hour, so a snapshot missed by a restart around midnight is
+//! taken later that day; a period boundary the indexer was down for the whole
+//! day of is skipped rather than filled with later rankings.
+
+use crate::clock::Clock;
+use crate::database::DatabaseManager;
+use crate::leader_election::Leadership;
+use crate::models::DAppRankingSnapshotRecord;
+use crate::notifier::Notifier;
+use anyhow::Result;
+use chrono::{Datelike, Days, NaiveDate};
+use chrono_tz::Tz;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info};
+
+/// DApps frozen per period unless configured otherwise
+pub const DEFAULT_SNAPSHOT_TOP_N: usize = 10;
+
+/// Period a ranking snapshot closes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotPeriod {
+    /// Monday to Sunday
+    Weekly,
+    /// Calendar month
+    Monthly,
+}
+
+impl SnapshotPeriod {
+    pub const ALL: [SnapshotPeriod; 2] = [SnapshotPeriod::Weekly, SnapshotPeriod::Monthly];
+
+    /// Table holding the snapshots of this period
+    pub fn table_name(&self) -> &'static str {
+        match self {
+            SnapshotPeriod::Weekly => "dapp_ranking_weekly",
+            SnapshotPeriod::Monthly => "dapp_ranking_monthly",
+        }
+    }
+
+    fn noun(&self) -> &'static str {
+        match self {
+            SnapshotPeriod::Weekly => "week",
+            SnapshotPeriod::Monthly => "month",
+        }
+    }
+
+    /// First day of the period containing `day`
+    pub fn start_of(&self, day: NaiveDate) -> NaiveDate {
+        match self {
+            SnapshotPeriod::Weekly => day - Days::new(day.weekday().num_days_from_monday() as u64),
+            SnapshotPeriod::Monthly => day.with_day(1).unwrap_or(day),
+        }
+    }
+
+    /// First day of the period before the one starting on `start`
+    pub fn previous_start(&self, start: NaiveDate) -> NaiveDate {
+        match self {
+            SnapshotPeriod::Weekly => start - Days::new(7),
+            SnapshotPeriod::Monthly => self.start_of(start - Days::new(1)),
+        }
+    }
+
+    /// Human-readable name of the period starting on `start`
+    fn describe(&self, start: NaiveDate) -> String {
+        match self {
+            SnapshotPeriod::Weekly => format!("the week of {}", start),
+            SnapshotPeriod::Monthly => start.format("%B %Y").to_string(),
+        }
+    }
+}
+
+/// Winners of the period starting on `period_start`, with the movement of each DApp
+/// against the `previous` snapshot and a summary of the biggest movers
+pub fn winners_report(
+    period: SnapshotPeriod,
+    period_start: NaiveDate,
+    snapshot: &[DAppRankingSnapshotRecord],
+    previous: &[DAppRankingSnapshotRecord],
+) -> String {
+    let previous_ranks: HashMap<&str, i32> =
+        previous.iter().map(|entry| (entry.dapp_name.as_str(), entry.rank_position)).collect();
+    let mut lines = vec![format!("🏆 Top {} DApps of {}", snapshot.len(), period.describe(period_start))];
+
+    // Rank gained since the previous snapshot, None for new entries
+    let mut gains: Vec<(&str, i32)> = Vec::new();
+    let mut new_entries: Vec<&str> = Vec::new();
+    for entry in snapshot {
+        let movement = match previous_ranks.get(entry.dapp_name.as_str()) {
+            _ if previous.is_empty() => String::new(),
+            None => {
+                new_entries.push(&entry.dapp_name);
+                " (new)".to_string()
+            }
+            Some(&previous_rank) => {
+                let gain = previous_rank - entry.rank_position;
+                gains.push((&entry.dapp_name, gain));
+                match gain {
+                    0 => String::new(),
+                    gain if gain > 0 => format!(" (▲{})", gain),
+                    gain => format!(" (▼{})", -gain),
+                }
+            }
+        };
+        lines.push(format!("{}. {} - {} users{}", entry.rank_position, entry.dapp_name, entry.active_users, movement));
+    }
+
+    if previous.is_empty() {
+        lines.push(format!("No previous {} to compare with.", period.noun()));
+        return lines.join("\n");
+    }
+
+    lines.push(String::new());
+    lines.push("Movers and shakers:".to_string());
+    if let Some((dapp_name, gain)) = gains.iter().filter(|(_, gain)| *gain > 0).max_by_key(|(_, gain)| *gain) {
+        lines.push(format!("📈 Biggest climber: {} (+{})", dapp_name, gain));
+    }
+    if let Some((dapp_name, gain)) = gains.iter().filter(|(_, gain)| *gain < 0).min_by_key(|(_, gain)| *gain) {
+        lines.push(format!("📉 Biggest faller: {} ({})", dapp_name, gain));
+    }
+    if !new_entries.is_empty() {
+        lines.push(format!("🆕 New in the top {}: {}", snapshot.len(), new_entries.join(", ")));
+    }
+    let ranked: Vec<&str> = snapshot.iter().map(|entry| entry.dapp_name.as_str()).collect();
+    let dropped: Vec<&str> = previous
+        .iter()
+        .map(|entry| entry.dapp_name.as_str())
+        .filter(|dapp_name| !ranked.contains(dapp_name))
+        .collect();
+    if !dropped.is_empty() {
+        lines.push(format!("👋 Dropped out: {}", dropped.join(", ")));
+    }
+    lines.join("\n")
+}
+
+/// Freeze the top `top_n` DApps of every period that ended at the start of `today`
+/// and report the winners; periods already frozen are left as they are
+///
+/// # Returns
+/// * Number of snapshots taken
+pub async fn take_due_snapshots(
+    db_manager: &DatabaseManager,
+    top_n: usize,
+    today: NaiveDate,
+    notifier: &dyn Notifier,
+) -> Result<usize> {
+    let mut taken = 0;
+    for period in SnapshotPeriod::ALL {
+        // Only on the first day of a period, so a late run never freezes later rankings
+        if period.start_of(today) != today {
+            continue;
+        }
+        let closed = period.previous_start(today);
+        if !db_manager.get_ranking_snapshot(period, closed).await?.is_empty() {
+            continue;
+        }
+
+        let snapshot = db_manager.save_ranking_snapshot(period, closed, top_n as i64).await?;
+        if snapshot.is_empty() {
+            continue;
+        }
+        taken += 1;
+        info!("🏆 Froze the top {} DApps of {}", snapshot.len(), period.describe(closed));
+
+        let previous = db_manager.get_ranking_snapshot(period, period.previous_start(closed)).await?;
+        if let Err(err) = notifier.notify(&winners_report(period, closed, &snapshot, &previous)).await {
+            error!("❌ Failed to send the {}ly winners report: {}", period.noun(), err);
+        }
+    }
+    Ok(taken)
+}
+
+/// Time left from `seconds` since the Unix epoch until the next full hour
+pub(crate) fn duration_until_next_hour(seconds: u64) -> Duration {
+    Duration::from_secs(3600 - seconds % 3600)
+}
+
+/// Start the job freezing weekly and monthly rankings, checked at every full hour
+/// Periods start at midnight of `timezone`; only the leader takes snapshots
+pub async fn start_ranking_snapshot_job(
+    db_manager: Arc<DatabaseManager>,
+    top_n: usize,
+    timezone: Tz,
+    leadership: Leadership,
+    clock: Arc<dyn Clock>,
+    notifier: Arc<dyn Notifier>,
+) {
+    tokio::spawn(async move {
+        loop {
+            let seconds = clock.now().timestamp().max(0) as u64;
+            tokio::time::sleep(duration_until_next_hour(seconds)).await;
+            if !leadership.is_leader() {
+                continue;
+            }
+
+            let today = clock.now().with_timezone(&timezone).date_naive();
+            if let Err(err) = take_due_snapshots(&db_manager, top_n, today, notifier.as_ref()).await {
+                error!("❌ Failed to take ranking snapshots: {}", err);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(value: &str) -> NaiveDate {
+        value.parse().unwrap()
+    }
+
+    fn entry(rank_position: i32, dapp_name: &str) -> DAppRankingSnapshotRecord {
+        DAppRankingSnapshotRecord {
+            period_start: day("2025-06-09"),
+            rank_position,
+            dapp_name: dapp_name.to_string(),
+            dapp_type: "DeFi".to_string(),
+            active_users: 1_000 / rank_position,
+            score: 0.0,
+            recorded_at: day("2025-06-16").and_hms_opt(0, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_period_boundaries() {
+        // 2025-06-18 is a Wednesday
+        assert_eq!(SnapshotPeriod::Weekly.start_of(day("2025-06-18")), day("2025-06-16"));
+        assert_eq!(SnapshotPeriod::Weekly.start_of(day("2025-06-16")), day("2025-06-16"));
+        assert_eq!(SnapshotPeriod::Weekly.previous_start(day("2025-06-16")), day("2025-06-09"));
+        assert_eq!(SnapshotPeriod::Monthly.start_of(day("2025-06-18")), day("2025-06-01"));
+        assert_eq!(SnapshotPeriod::Monthly.previous_start(day("2025-03-01")), day("2025-02-01"));
+        assert_eq!(SnapshotPeriod::Monthly.previous_start(day("2025-01-01")), day("2024-12-01"));
+        assert_eq!(duration_until_next_hour(7_200), Duration::from_secs(3_600));
+        assert_eq!(duration_until_next_hour(7_260), Duration::from_secs(3_540));
+    }
+
+    #[test]
+    fn test_winners_report_names_movers() {
+        let previous = [entry(1, "Cetus AMM"), entry(2, "Pyth"), entry(3, "Suilend")];
+        let snapshot = [entry(1, "Suilend"), entry(2, "Cetus AMM"), entry(3, "Momentum")];
+        let report = winners_report(SnapshotPeriod::Weekly, day("2025-06-09"), &snapshot, &previous);
+        assert_eq!(report, [
+            "🏆 Top 3 DApps of the week of 2025-06-09",
+            "1. Suilend - 1000 users (▲2)",
+            "2. Cetus AMM - 500 users (▼1)",
+            "3. Momentum - 333 users (new)",
+            "",
+            "Movers and shakers:",
+            "📈 Biggest climber: Suilend (+2)",
+            "📉 Biggest faller: Cetus AMM (-1)",
+            "🆕 New in the top 3: Momentum",
+            "👋 Dropped out: Pyth",
+        ].join("\n"));
+
+        let first = winners_report(SnapshotPeriod::Monthly, day("2025-06-01"), &snapshot, &[]);
+        assert!(first.starts_with("🏆 Top 3 DApps of June 2025\n1. Suilend - 1000 users\n"));
+        assert!(first.ends_with("No previous month to compare with."));
+    }
+}
@@ -0,0 +1,159 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! API-key authentication and token-bucket rate limiting of the HTTP API.
+//!
+//! Requests carrying a configured key in `X-API-Key` are limited per key at
+//! that key's rate. Requests without a key are limited per client IP at the
+//! anonymous rate, or rejected when keys are configured and no anonymous rate
+//! is. Each bucket holds a minute's worth of requests, so short bursts pass.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Header carrying the API key
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Buckets kept before idle ones are evicted
+const MAX_BUCKETS: usize = 100_000;
+
+/// A bucket idle this long is full again, so it can be dropped
+const REFILL_PERIOD: Duration = Duration::from_secs(60);
+
+/// Requests per minute allowed for each key and for anonymous clients
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RateLimits {
+    /// API key -> requests per minute (0 is unlimited)
+    pub api_keys: HashMap<String, u32>,
+    /// Requests per minute per client IP without a key (0 is unlimited);
+    /// None rejects keyless requests when keys are configured
+    pub anonymous_per_minute: Option<u32>,
+}
+
+/// Outcome of a rate limit check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitDecision {
+    Allowed,
+    /// Missing or unknown API key
+    Unauthorized,
+    /// Out of tokens; retry after the given delay
+    Limited(Duration),
+}
+
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn full(per_minute: u32, now: Instant) -> Self {
+        Self { tokens: per_minute as f64, last_refill: now }
+    }
+
+    /// Take one token after refilling `per_minute` tokens per minute, up to `per_minute`
+    fn try_take(&mut self, per_minute: u32, now: Instant) -> Result<(), Duration> {
+        let capacity = per_minute as f64;
+        let per_second = capacity / REFILL_PERIOD.as_secs_f64();
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * per_second).min(capacity);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / per_second))
+        }
+    }
+}
+
+/// Checks API keys and rate limits of incoming requests
+#[derive(Debug, Default)]
+pub struct ApiRateLimiter {
+    limits: RateLimits,
+    /// "key:<api key>" or "ip:<client ip>" -> bucket
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+}
+
+impl ApiRateLimiter {
+    pub fn new(limits: RateLimits) -> Self {
+        Self { limits, buckets: Mutex::new(HashMap::new()) }
+    }
+
+    /// Returns true if requests are authenticated or limited at all
+    pub fn is_enabled(&self) -> bool {
+        !self.limits.api_keys.is_empty() || self.limits.anonymous_per_minute.is_some()
+    }
+
+    /// Count a request with `api_key` from `client_ip` and decide whether it may proceed
+    pub fn check(&self, api_key: Option<&str>, client_ip: &str, now: Instant) -> RateLimitDecision {
+        let (bucket_key, per_minute) = match (api_key, self.limits.anonymous_per_minute) {
+            (Some(api_key), _) => match self.limits.api_keys.get(api_key) {
+                Some(&per_minute) => (format!("key:{}", api_key), per_minute),
+                None => return RateLimitDecision::Unauthorized,
+            },
+            (None, Some(per_minute)) => (format!("ip:{}", client_ip), per_minute),
+            (None, None) if self.limits.api_keys.is_empty() => return RateLimitDecision::Allowed,
+            (None, None) => return RateLimitDecision::Unauthorized,
+        };
+        if per_minute == 0 {
+            return RateLimitDecision::Allowed;
+        }
+
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= MAX_BUCKETS {
+            buckets.retain(|_, bucket| now.saturating_duration_since(bucket.last_refill) < REFILL_PERIOD);
+        }
+        match buckets
+            .entry(bucket_key)
+            .or_insert_with(|| TokenBucket::full(per_minute, now))
+            .try_take(per_minute, now)
+        {
+            Ok(()) => RateLimitDecision::Allowed,
+            Err(retry_after) => RateLimitDecision::Limited(retry_after),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(anonymous_per_minute: Option<u32>) -> ApiRateLimiter {
+        ApiRateLimiter::new(RateLimits {
+            api_keys: HashMap::from([("partner".to_string(), 2), ("internal".to_string(), 0)]),
+            anonymous_per_minute,
+        })
+    }
+
+    #[test]
+    fn test_keys_are_limited_and_refilled() {
+        let limiter = limiter(None);
+        let now = Instant::now();
+
+        assert_eq!(limiter.check(Some("partner"), "1.1.1.1", now), RateLimitDecision::Allowed);
+        assert_eq!(limiter.check(Some("partner"), "2.2.2.2", now), RateLimitDecision::Allowed);
+        assert_eq!(
+            limiter.check(Some("partner"), "1.1.1.1", now),
+            RateLimitDecision::Limited(Duration::from_secs(30))
+        );
+        assert_eq!(limiter.check(Some("partner"), "1.1.1.1", now + Duration::from_secs(30)), RateLimitDecision::Allowed);
+        assert_eq!(limiter.check(Some("internal"), "1.1.1.1", now), RateLimitDecision::Allowed);
+    }
+
+    #[test]
+    fn test_keyless_requests_follow_anonymous_limit() {
+        let now = Instant::now();
+        assert_eq!(limiter(None).check(None, "1.1.1.1", now), RateLimitDecision::Unauthorized);
+        assert_eq!(limiter(None).check(Some("unknown"), "1.1.1.1", now), RateLimitDecision::Unauthorized);
+
+        let limiter = limiter(Some(1));
+        assert_eq!(limiter.check(None, "1.1.1.1", now), RateLimitDecision::Allowed);
+        assert!(matches!(limiter.check(None, "1.1.1.1", now), RateLimitDecision::Limited(_)));
+        assert_eq!(limiter.check(None, "2.2.2.2", now), RateLimitDecision::Allowed);
+
+        assert_eq!(ApiRateLimiter::default().check(None, "1.1.1.1", now), RateLimitDecision::Allowed);
+    }
+}
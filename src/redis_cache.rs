@@ -0,0 +1,171 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Mirror of the current rankings in Redis.
+//!
+//! With REDIS_URL set, every newly computed ranking is written to Redis, so
+//! high-traffic frontends can read it without going through the API and
+//! Postgres. Under REDIS_KEY_PREFIX:
+//! - `<prefix>:scores`: sorted set of the ranked DApp names by score, read
+//!   with `ZREVRANGE <prefix>:scores 0 9 WITHSCORES`
+//! - `<prefix>:rankings`: the ranking list as JSON, best first, "Others" last
+//! - `<prefix>:updated_at`: RFC 3339 time the rankings were computed at
+//!
+//! The keys are replaced in one MULTI/EXEC transaction, so readers never see
+//! half of an update. Only the leader writes. A failed write is logged and the
+//! next update rewrites every key; the connection is re-established on demand.
+
+use crate::leader_election::Leadership;
+use crate::models::{DAppRanking, OTHERS_PACKAGE_ID};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use prometheus::{register_int_counter_with_registry, IntCounter, Registry};
+use redis::aio::ConnectionManager;
+use std::sync::Arc;
+use tokio::sync::watch;
+use tracing::{error, info};
+
+/// Where the rankings are mirrored
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedisSettings {
+    /// Redis connection URL, e.g. redis://localhost:6379/0 or rediss:// for TLS
+    pub url: String,
+    /// Prefix of the keys the rankings are written to
+    pub key_prefix: String,
+}
+
+/// Prometheus counters of the Redis mirror
+#[derive(Clone)]
+pub struct RedisMirrorMetrics {
+    writes: IntCounter,
+    failures: IntCounter,
+}
+
+impl RedisMirrorMetrics {
+    pub fn new(registry: &Registry) -> Result<Self> {
+        Ok(Self {
+            writes: register_int_counter_with_registry!(
+                "redis_rankings_writes",
+                "Rankings written to Redis",
+                registry
+            )?,
+            failures: register_int_counter_with_registry!(
+                "redis_rankings_write_failures",
+                "Rankings that could not be written to Redis",
+                registry
+            )?,
+        })
+    }
+}
+
+/// Transaction replacing the mirrored rankings with `rankings`
+fn rankings_pipeline(key_prefix: &str, rankings: &[DAppRanking], updated_at: DateTime<Utc>) -> Result<redis::Pipeline> {
+    let scores_key = format!("{}:scores", key_prefix);
+    let scores: Vec<(f64, &str)> = rankings
+        .iter()
+        .filter(|ranking| ranking.package_id != OTHERS_PACKAGE_ID)
+        .map(|ranking| (ranking.score, ranking.dapp_name.as_str()))
+        .collect();
+
+    let mut pipeline = redis::pipe();
+    pipeline.atomic().del(&scores_key).ignore();
+    if !scores.is_empty() {
+        pipeline.zadd_multiple(&scores_key, &scores).ignore();
+    }
+    pipeline
+        .set(format!("{}:rankings", key_prefix), serde_json::to_string(rankings)?)
+        .ignore()
+        .set(format!("{}:updated_at", key_prefix), updated_at.to_rfc3339())
+        .ignore();
+    Ok(pipeline)
+}
+
+/// Start the task mirroring every newly computed ranking to Redis
+pub async fn start_redis_rankings_mirror(
+    mut rankings_updates: watch::Receiver<Arc<Vec<DAppRanking>>>,
+    settings: RedisSettings,
+    metrics: RedisMirrorMetrics,
+    leadership: Leadership,
+) -> Result<()> {
+    let client = redis::Client::open(settings.url.as_str())?;
+    info!("🧊 Mirroring rankings to Redis under {}:*", settings.key_prefix);
+
+    tokio::spawn(async move {
+        let mut connection: Option<ConnectionManager> = None;
+        while rankings_updates.changed().await.is_ok() {
+            let rankings = rankings_updates.borrow_and_update().clone();
+            if !leadership.is_leader() {
+                continue;
+            }
+            let updated_at = rankings.first().map_or_else(Utc::now, |ranking| ranking.last_update);
+
+            let written = async {
+                let pipeline = rankings_pipeline(&settings.key_prefix, &rankings, updated_at)?;
+                let connection = match &mut connection {
+                    Some(connection) => connection,
+                    None => connection.insert(ConnectionManager::new(client.clone()).await?),
+                };
+                pipeline.query_async::<_, ()>(connection).await?;
+                anyhow::Ok(())
+            }
+            .await;
+            match written {
+                Ok(()) => metrics.writes.inc(),
+                Err(err) => {
+                    metrics.failures.inc();
+                    error!("❌ Failed to mirror rankings to Redis: {}", err);
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dapp_indexer::{collapse_below_min_dau, compute_rankings};
+    use crate::models::{DAppRegistryEntry, OTHERS_DAPP_NAME};
+    use crate::test_utils::InteractionBuilder;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    fn packed(rankings: &[DAppRanking], updated_at: DateTime<Utc>) -> String {
+        String::from_utf8(rankings_pipeline("surfsui", rankings, updated_at).unwrap().get_packed_pipeline()).unwrap()
+    }
+
+    #[test]
+    fn test_replaces_scores_and_json_in_one_transaction() {
+        let now = DateTime::<Utc>::UNIX_EPOCH + Duration::from_secs(100 * 3600);
+        let registry = HashMap::from([
+            ("0xa".to_string(), DAppRegistryEntry::new("Cetus AMM", "DEX")),
+            ("0xb".to_string(), DAppRegistryEntry::new("Pyth", "Oracle")),
+        ]);
+        let interaction = |package_id: &str, sender: &str| {
+            InteractionBuilder::new()
+                .package(package_id)
+                .sender(sender)
+                .digest(&format!("{}-{}", package_id, sender))
+                .gas_used(10)
+                .at(now - Duration::from_secs(60))
+                .build()
+        };
+        let interactions = [interaction("0xa", "0x1"), interaction("0xa", "0x2"), interaction("0xb", "0x3")];
+        // Pyth has a single user and is collapsed into "Others"
+        let rankings = collapse_below_min_dau(compute_rankings(&interactions, &registry, Duration::from_secs(3600), now), 2, now);
+
+        let pipeline = packed(&rankings, now);
+        for command in ["MULTI", "DEL", "ZADD", "EXEC"] {
+            assert!(pipeline.contains(&format!("\r\n{}\r\n", command)), "{} missing", command);
+        }
+        assert_eq!(pipeline.matches("\r\nSET\r\n").count(), 2);
+        assert!(pipeline.contains("surfsui:scores") && pipeline.contains("surfsui:rankings"));
+        // "Others" is in the JSON but has no score to rank by
+        let zadd = &pipeline[pipeline.find("ZADD").unwrap()..pipeline.find("\r\nSET\r\n").unwrap()];
+        assert!(zadd.contains("Cetus AMM") && !zadd.contains(OTHERS_DAPP_NAME));
+        assert!(pipeline.contains(&format!("\"dapp_name\":\"{}\"", OTHERS_DAPP_NAME)));
+
+        // An empty ranking clears the sorted set
+        assert!(!packed(&[], now).contains("ZADD"));
+    }
+}
@@ -0,0 +1,172 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Failover across remote checkpoint storage endpoints.
+//!
+//! The ingestion executor reads checkpoints from a single remote URL. When
+//! several endpoints are configured, the executor is pointed at a local proxy
+//! instead. The proxy serves every request from the current endpoint and moves
+//! on to the next one when it is throttled, down or returns a server error; the
+//! endpoint that answered becomes the current one. Failures are counted per
+//! endpoint in Prometheus.
+
+use anyhow::{anyhow, Result};
+use axum::body::Body;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use prometheus::{register_int_counter_vec_with_registry, IntCounterVec, Registry};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// Timeout of a single checkpoint download from one endpoint
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Split a comma-separated `REMOTE_STORAGE` value into endpoint URLs
+pub fn parse_remote_storage(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|endpoint| endpoint.trim().trim_end_matches('/'))
+        .filter(|endpoint| !endpoint.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Remote endpoints tried in order, starting from the last one that worked
+pub struct RemoteStorageFailover {
+    endpoints: Vec<String>,
+    current: AtomicUsize,
+    client: reqwest::Client,
+    failures: IntCounterVec,
+}
+
+/// Outcome of fetching one object from one endpoint
+enum FetchOutcome {
+    Found(Vec<u8>),
+    NotFound,
+    /// Throttled, unreachable or server error; try the next endpoint
+    Failed(String),
+}
+
+impl RemoteStorageFailover {
+    pub fn new(endpoints: Vec<String>, registry: &Registry) -> Result<Self> {
+        if endpoints.is_empty() {
+            return Err(anyhow!("At least one remote storage endpoint is required"));
+        }
+        let client = reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build()?;
+        let failures = register_int_counter_vec_with_registry!(
+            "remote_storage_endpoint_failures",
+            "Failed checkpoint downloads per remote storage endpoint",
+            &["endpoint"],
+            registry
+        )?;
+        Ok(Self { endpoints, current: AtomicUsize::new(0), client, failures })
+    }
+
+    /// Endpoint currently serving requests
+    pub fn current_endpoint(&self) -> &str {
+        &self.endpoints[self.current.load(Ordering::Relaxed)]
+    }
+
+    /// Fetch `path`, failing over to the next endpoints until one answers
+    /// A missing object is not a failure: it is usually a checkpoint that does not exist yet
+    async fn fetch(&self, path: &str) -> Result<Option<Vec<u8>>> {
+        let start = self.current.load(Ordering::Relaxed);
+
+        for offset in 0..self.endpoints.len() {
+            let index = (start + offset) % self.endpoints.len();
+            let endpoint = &self.endpoints[index];
+
+            let found = match self.fetch_from(endpoint, path).await {
+                FetchOutcome::Found(bytes) => Some(bytes),
+                FetchOutcome::NotFound => None,
+                FetchOutcome::Failed(reason) => {
+                    self.failures.with_label_values(&[endpoint]).inc();
+                    warn!("⚠️ Remote storage {} failed for {}: {}", endpoint, path, reason);
+                    continue;
+                }
+            };
+
+            if index != start
+                && self.current.compare_exchange(start, index, Ordering::Relaxed, Ordering::Relaxed).is_ok()
+            {
+                info!("🔀 Remote storage failed over to {}", endpoint);
+            }
+            return Ok(found);
+        }
+
+        Err(anyhow!("All {} remote storage endpoints failed for {}", self.endpoints.len(), path))
+    }
+
+    async fn fetch_from(&self, endpoint: &str, path: &str) -> FetchOutcome {
+        let response = match self.client.get(format!("{}/{}", endpoint, path)).send().await {
+            Ok(response) => response,
+            Err(err) => return FetchOutcome::Failed(err.to_string()),
+        };
+
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return FetchOutcome::NotFound;
+        }
+        if !status.is_success() {
+            return FetchOutcome::Failed(format!("HTTP {}", status));
+        }
+
+        match response.bytes().await {
+            Ok(bytes) => FetchOutcome::Found(bytes.to_vec()),
+            Err(err) => FetchOutcome::Failed(err.to_string()),
+        }
+    }
+}
+
+async fn proxy_object(
+    State(failover): State<Arc<RemoteStorageFailover>>,
+    Path(path): Path<String>,
+) -> Response {
+    match failover.fetch(&path).await {
+        Ok(Some(bytes)) => Body::from(bytes).into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(err) => {
+            error!("❌ {}", err);
+            StatusCode::BAD_GATEWAY.into_response()
+        }
+    }
+}
+
+/// Start the local proxy in front of the remote endpoints
+///
+/// # Returns
+/// * Base URL of the proxy, to be used as the executor's remote storage
+pub async fn start_remote_storage_proxy(failover: RemoteStorageFailover) -> Result<String> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let address = listener.local_addr()?;
+    let app = Router::new()
+        .route("/{*path}", get(proxy_object))
+        .with_state(Arc::new(failover));
+
+    tokio::spawn(async move {
+        if let Err(err) = axum::serve(listener, app).await {
+            error!("❌ Remote storage proxy stopped: {}", err);
+        }
+    });
+
+    Ok(format!("http://{}", address))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_remote_storage() {
+        assert_eq!(
+            parse_remote_storage(" https://a.example/ ,https://b.example,, "),
+            vec!["https://a.example".to_string(), "https://b.example".to_string()]
+        );
+        assert!(parse_remote_storage("").is_empty());
+    }
+}
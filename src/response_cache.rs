@@ -0,0 +1,131 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! In-process cache of HTTP API responses.
+//!
+//! Successful GET responses are cached by path and query string until the
+//! next ranking update is due, so repeated leaderboard requests are served
+//! without querying Postgres. Every cached response carries an `ETag`, and
+//! requests whose `If-None-Match` matches it get an empty 304.
+
+use axum::body::{to_bytes, Body, Bytes};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use moka::future::Cache;
+use moka::Expiry;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A response body with the headers it was served with
+#[derive(Debug)]
+pub struct CachedResponse {
+    headers: HeaderMap,
+    body: Bytes,
+    etag: HeaderValue,
+    /// How long the response stays cached
+    ttl: Duration,
+}
+
+impl CachedResponse {
+    /// The response, or an empty 304 if `if_none_match` matches its ETag
+    pub fn to_response(&self, if_none_match: Option<&HeaderValue>) -> Response {
+        if if_none_match.is_some_and(|value| etag_matches(value, &self.etag)) {
+            return (StatusCode::NOT_MODIFIED, [(header::ETAG, self.etag.clone())]).into_response();
+        }
+        let mut response = Response::new(Body::from(self.body.clone()));
+        *response.headers_mut() = self.headers.clone();
+        response.headers_mut().insert(header::ETAG, self.etag.clone());
+        response
+    }
+}
+
+/// Expires each response after its own TTL, so TTL changes apply to new entries
+struct ResponseExpiry;
+
+impl Expiry<String, Arc<CachedResponse>> for ResponseExpiry {
+    fn expire_after_create(&self, _key: &String, value: &Arc<CachedResponse>, _created_at: Instant) -> Option<Duration> {
+        Some(value.ttl)
+    }
+}
+
+/// Cache of API responses keyed by path and query string
+#[derive(Clone)]
+pub struct ResponseCache {
+    cache: Cache<String, Arc<CachedResponse>>,
+}
+
+impl ResponseCache {
+    /// Cache holding up to `max_entries` responses
+    pub fn new(max_entries: u64) -> Self {
+        Self {
+            cache: Cache::builder().max_capacity(max_entries).expire_after(ResponseExpiry).build(),
+        }
+    }
+
+    pub async fn get(&self, key: &str) -> Option<Arc<CachedResponse>> {
+        self.cache.get(key).await
+    }
+
+    /// Buffer a successful response and cache it for `ttl`
+    /// Other responses are returned as they are and not cached
+    pub async fn store(&self, key: String, response: Response, ttl: Duration) -> Result<Arc<CachedResponse>, Response> {
+        if response.status() != StatusCode::OK {
+            return Err(response);
+        }
+
+        let (parts, body) = response.into_parts();
+        let body = match to_bytes(body, usize::MAX).await {
+            Ok(body) => body,
+            Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR.into_response()),
+        };
+        let cached = Arc::new(CachedResponse {
+            headers: parts.headers,
+            etag: etag_of(&body),
+            body,
+            ttl,
+        });
+        self.cache.insert(key, cached.clone()).await;
+        Ok(cached)
+    }
+
+    /// Drop every cached response, e.g. after rankings were recomputed out of schedule
+    pub fn invalidate_all(&self) {
+        self.cache.invalidate_all();
+    }
+}
+
+/// Strong ETag derived from the response body
+fn etag_of(body: &[u8]) -> HeaderValue {
+    let digest = Sha256::digest(body);
+    let hex: String = digest[..8].iter().map(|byte| format!("{:02x}", byte)).collect();
+    HeaderValue::from_str(&format!("\"{}\"", hex)).expect("hex ETag is a valid header value")
+}
+
+/// Returns true if an `If-None-Match` header value lists `etag` (or is `*`)
+fn etag_matches(if_none_match: &HeaderValue, etag: &HeaderValue) -> bool {
+    let (Ok(candidates), Ok(etag)) = (if_none_match.to_str(), etag.to_str()) else {
+        return false;
+    };
+    candidates
+        .split(',')
+        .map(|candidate| candidate.trim().trim_start_matches("W/"))
+        .any(|candidate| candidate == "*" || candidate == etag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_etag_matches_listed_and_weak_tags() {
+        let etag = etag_of(b"[]");
+        let tag = etag.to_str().unwrap().to_string();
+
+        assert!(etag_matches(&HeaderValue::from_str(&tag).unwrap(), &etag));
+        assert!(etag_matches(&HeaderValue::from_str(&format!("\"other\", W/{}", tag)).unwrap(), &etag));
+        assert!(etag_matches(&HeaderValue::from_static("*"), &etag));
+        assert!(!etag_matches(&HeaderValue::from_static("\"other\""), &etag));
+        assert_ne!(etag_of(b"[1]"), etag);
+    }
+}
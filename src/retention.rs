@@ -0,0 +1,115 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! User retention cohorts.
+//!
+//! Every first-time user of a DApp belongs to the cohort of the day (in the
+//! stats timezone) they were first seen. A nightly job measures which share of each cohort is active
+//! again exactly 1, 7 and 30 days later and stores the D1/D7/D30 return rates
+//! in `dapp_retention`, so DApps can be ranked by stickiness instead of raw DAU.
+//! The same job then deletes the per-day sender activity older than any cohort
+//! still recomputed, so raw sender addresses are not kept indefinitely.
+
+use crate::active_users::MAU_DAYS;
+use crate::clock::Clock;
+use crate::database::DatabaseManager;
+use crate::leader_election::Leadership;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Days, NaiveDate, Timelike, Utc};
+use chrono_tz::Tz;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info};
+
+/// Cohorts younger than this are recomputed every night; older ones are final
+/// since their D30 day has elapsed
+const RETENTION_LOOKBACK_DAYS: u64 = 31;
+
+// Daily activity is kept for the lookback, which must cover the monthly active users
+const _: () = assert!(RETENTION_LOOKBACK_DAYS >= MAU_DAYS);
+
+/// Return window of a retention rate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetentionWindow {
+    D1,
+    D7,
+    D30,
+}
+
+impl RetentionWindow {
+    /// Column of `dapp_retention` holding the return rate of this window
+    pub fn rate_column(&self) -> &'static str {
+        match self {
+            RetentionWindow::D1 => "d1_rate",
+            RetentionWindow::D7 => "d7_rate",
+            RetentionWindow::D30 => "d30_rate",
+        }
+    }
+}
+
+impl FromStr for RetentionWindow {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "d1" => Ok(RetentionWindow::D1),
+            "d7" => Ok(RetentionWindow::D7),
+            "d30" => Ok(RetentionWindow::D30),
+            _ => Err(anyhow!("Unknown retention window '{}', expected d1, d7 or d30", value)),
+        }
+    }
+}
+
+/// Recompute retention for all cohorts that can still change, then delete the daily
+/// activity before them, which no cohort nor WAU/MAU reads anymore
+/// Days are taken in `timezone`
+pub async fn update_retention(db_manager: &DatabaseManager, today: NaiveDate, timezone: Tz) -> Result<usize> {
+    let from_day = today
+        .checked_sub_days(Days::new(RETENTION_LOOKBACK_DAYS))
+        .unwrap_or(today);
+    let rows = db_manager.compute_retention(from_day, today, timezone).await?;
+
+    let pruned = db_manager.prune_daily_activity(from_day).await?;
+    if pruned > 0 {
+        info!("🗑️ Deleted {} daily activity rows before {}", pruned, from_day);
+    }
+    Ok(rows)
+}
+
+/// Time left from `now` until the next occurrence of `hour`:00 UTC
+fn duration_until_hour(hour: u32, now: DateTime<Utc>) -> Duration {
+    let seconds_into_day = now.num_seconds_from_midnight() as u64;
+    let target = hour as u64 * 60 * 60;
+    let seconds = if target > seconds_into_day {
+        target - seconds_into_day
+    } else {
+        24 * 60 * 60 - seconds_into_day + target
+    };
+    Duration::from_secs(seconds)
+}
+
+/// Start the nightly job recomputing retention cohorts at `run_hour_utc`
+/// Cohorts are bucketed into days of `timezone`; only the leader runs it
+pub async fn start_retention_job(
+    db_manager: Arc<DatabaseManager>,
+    run_hour_utc: u32,
+    timezone: Tz,
+    leadership: Leadership,
+    clock: Arc<dyn Clock>,
+) {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(duration_until_hour(run_hour_utc, clock.now())).await;
+            if !leadership.is_leader() {
+                continue;
+            }
+
+            let today = clock.now().with_timezone(&timezone).date_naive();
+            match update_retention(&db_manager, today, timezone).await {
+                Ok(rows) => info!("📈 Updated retention for {} DApp cohorts", rows),
+                Err(err) => error!("❌ Failed to update retention cohorts: {}", err),
+            }
+        }
+    });
+}
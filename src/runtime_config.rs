@@ -0,0 +1,226 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Runtime-tunable settings.
+//!
+//! A small set of knobs (ranking interval, ranking update thresholds, log
+//! verbosity) is published through a `tokio::sync::watch` channel. Operators can
+//! change them during an incident through the admin API or by editing the
+//! runtime config file, without restarting and losing the in-memory window.
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::watch;
+use tracing::level_filters::LevelFilter;
+use tracing::{error, info};
+
+/// Current values of the runtime-tunable settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RuntimeSettings {
+    /// How often the background job recomputes and persists rankings
+    pub ranking_interval: Duration,
+    /// Rankings are recomputed on every checkpoint whose number is a multiple of this (0 disables)
+    pub ranking_checkpoint_interval: u64,
+    /// Rankings are recomputed when a checkpoint has more interactions than this
+    pub ranking_min_interactions: usize,
+    /// Rankings are recomputed on a checkpoint once they are older than this (None disables)
+    pub ranking_recompute_interval: Option<Duration>,
+    /// Log verbosity (error, warn, info, debug, trace)
+    pub log_level: String,
+}
+
+impl Default for RuntimeSettings {
+    fn default() -> Self {
+        Self {
+            ranking_interval: Duration::from_secs(60),
+            ranking_checkpoint_interval: 10,
+            ranking_min_interactions: 5,
+            ranking_recompute_interval: None,
+            log_level: "info".to_string(),
+        }
+    }
+}
+
+/// Partial update of the runtime settings; missing fields are left unchanged
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RuntimeSettingsUpdate {
+    pub ranking_interval_seconds: Option<u64>,
+    pub ranking_checkpoint_interval: Option<u64>,
+    pub ranking_min_interactions: Option<usize>,
+    /// 0 disables the wall-clock trigger
+    pub ranking_recompute_interval_seconds: Option<u64>,
+    pub log_level: Option<String>,
+}
+
+impl RuntimeSettings {
+    /// Return a copy of the settings with the update applied, validating new values
+    pub fn apply(&self, update: &RuntimeSettingsUpdate) -> Result<Self> {
+        let mut settings = self.clone();
+
+        if let Some(seconds) = update.ranking_interval_seconds {
+            if seconds < 60 {
+                return Err(anyhow!("ranking_interval_seconds must be at least 60 seconds"));
+            }
+            settings.ranking_interval = Duration::from_secs(seconds);
+        }
+
+        if let Some(interval) = update.ranking_checkpoint_interval {
+            settings.ranking_checkpoint_interval = interval;
+        }
+
+        if let Some(min_interactions) = update.ranking_min_interactions {
+            settings.ranking_min_interactions = min_interactions;
+        }
+
+        if let Some(seconds) = update.ranking_recompute_interval_seconds {
+            settings.ranking_recompute_interval = (seconds > 0).then(|| Duration::from_secs(seconds));
+        }
+
+        if let Some(level) = &update.log_level {
+            parse_log_level(level)?;
+            settings.log_level = level.to_lowercase();
+        }
+
+        Ok(settings)
+    }
+
+    /// Whether a checkpoint should trigger a ranking recomputation
+    ///
+    /// # Arguments
+    /// * `checkpoint_number` - Sequence number of the applied checkpoint
+    /// * `interactions` - Interactions found in the checkpoint
+    /// * `since_last_update` - Time since rankings were last recomputed, None if never
+    pub fn should_recompute_rankings(
+        &self,
+        checkpoint_number: u64,
+        interactions: usize,
+        since_last_update: Option<Duration>,
+    ) -> bool {
+        let by_count = self.ranking_checkpoint_interval > 0
+            && checkpoint_number.is_multiple_of(self.ranking_checkpoint_interval);
+        let by_volume = interactions > self.ranking_min_interactions;
+        let by_age = self.ranking_recompute_interval.is_some_and(|interval| {
+            since_last_update.is_none_or(|elapsed| elapsed >= interval)
+        });
+        by_count || by_volume || by_age
+    }
+
+    /// Log verbosity as a tracing level filter
+    pub fn log_level_filter(&self) -> LevelFilter {
+        parse_log_level(&self.log_level).unwrap_or(LevelFilter::INFO)
+    }
+}
+
+/// Parse a log level name into a tracing level filter
+pub fn parse_log_level(level: &str) -> Result<LevelFilter> {
+    level
+        .parse::<LevelFilter>()
+        .map_err(|_| anyhow!("Invalid log level '{}'", level))
+}
+
+/// Owner of the runtime settings channel
+#[derive(Clone)]
+pub struct RuntimeConfig {
+    sender: Arc<watch::Sender<RuntimeSettings>>,
+}
+
+impl RuntimeConfig {
+    pub fn new(initial: RuntimeSettings) -> Self {
+        let (sender, _) = watch::channel(initial);
+        Self { sender: Arc::new(sender) }
+    }
+
+    /// Subscribe to setting changes
+    pub fn subscribe(&self) -> watch::Receiver<RuntimeSettings> {
+        self.sender.subscribe()
+    }
+
+    /// Current settings
+    pub fn current(&self) -> RuntimeSettings {
+        self.sender.borrow().clone()
+    }
+
+    /// Validate and publish an update to all subscribers
+    pub fn update(&self, update: &RuntimeSettingsUpdate) -> Result<RuntimeSettings> {
+        let settings = self.current().apply(update)?;
+        self.sender.send_if_modified(|current| {
+            if *current == settings {
+                return false;
+            }
+            *current = settings.clone();
+            true
+        });
+        info!("🎛️ Runtime settings updated: {:?}", settings);
+        Ok(settings)
+    }
+}
+
+/// Read a runtime settings update from a JSON file
+fn read_update_file(path: &PathBuf) -> Result<RuntimeSettingsUpdate> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read runtime config {}", path.display()))?;
+    serde_json::from_str(&contents)
+        .with_context(|| format!("Invalid runtime config {}", path.display()))
+}
+
+/// Start a background job that re-applies the runtime config file whenever it changes
+pub async fn start_runtime_config_file_watcher(
+    runtime_config: RuntimeConfig,
+    path: PathBuf,
+    poll_interval: Duration,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(poll_interval);
+        let mut last_modified: Option<SystemTime> = None;
+
+        loop {
+            interval.tick().await;
+
+            let modified = match std::fs::metadata(&path).and_then(|metadata| metadata.modified()) {
+                Ok(modified) => modified,
+                Err(_) => continue, // File is optional; wait until it appears
+            };
+            if last_modified == Some(modified) {
+                continue;
+            }
+            last_modified = Some(modified);
+
+            match read_update_file(&path).and_then(|update| runtime_config.update(&update)) {
+                Ok(_) => info!("🔄 Reloaded runtime config from {}", path.display()),
+                Err(err) => error!("❌ Failed to apply runtime config: {}", err),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ranking_recompute_triggers() {
+        let settings = RuntimeSettings {
+            ranking_checkpoint_interval: 0,
+            ranking_min_interactions: 5,
+            ranking_recompute_interval: Some(Duration::from_secs(30)),
+            ..RuntimeSettings::default()
+        };
+        assert!(!settings.should_recompute_rankings(10, 5, Some(Duration::from_secs(29))));
+        assert!(settings.should_recompute_rankings(11, 6, Some(Duration::from_secs(1))));
+        assert!(settings.should_recompute_rankings(11, 0, Some(Duration::from_secs(30))));
+        assert!(settings.should_recompute_rankings(11, 0, None));
+
+        let updated = settings
+            .apply(&RuntimeSettingsUpdate {
+                ranking_checkpoint_interval: Some(10),
+                ranking_recompute_interval_seconds: Some(0),
+                ..RuntimeSettingsUpdate::default()
+            })
+            .unwrap();
+        assert!(updated.should_recompute_rankings(20, 0, None));
+        assert!(!updated.should_recompute_rankings(21, 0, None));
+    }
+}
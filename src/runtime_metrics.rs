@@ -0,0 +1,197 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tokio runtime metrics in Prometheus.
+//!
+//! When the executor stalls, the usual suspect is contention on the shared
+//! indexer mutex: workers and jobs pile up waiting for the lock while the
+//! runtime's threads sit idle or keep a single task busy. The runtime is
+//! sampled every SAMPLE_INTERVAL into `tokio_*` metrics: workers, alive tasks,
+//! the global queue depth, and per worker the time spent busy and how often it
+//! parked. Builds with `--cfg tokio_unstable` also export per-worker poll
+//! counts and mean poll times, blocking threads and spawned tasks, which show
+//! long polls holding a worker. Task-level detail comes from `tokio-console`
+//! (see the `logging` module).
+
+use anyhow::Result;
+use prometheus::{
+    register_counter_vec_with_registry, register_int_counter_vec_with_registry, register_int_gauge_with_registry,
+    CounterVec, IntCounterVec, IntGauge, Registry,
+};
+#[cfg(tokio_unstable)]
+use prometheus::{register_gauge_vec_with_registry, register_int_counter_with_registry, GaugeVec, IntCounter};
+use std::time::Duration;
+use tokio::runtime::{Handle, RuntimeMetrics};
+
+/// Time between two samples of the runtime
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Prometheus metrics of a tokio runtime
+pub struct RuntimeMetricsCollector {
+    workers: IntGauge,
+    alive_tasks: IntGauge,
+    global_queue_depth: IntGauge,
+    worker_busy_seconds: CounterVec,
+    worker_parks: IntCounterVec,
+    /// Busy time and park count of every worker at the previous sample
+    previous: Vec<(Duration, u64)>,
+    #[cfg(tokio_unstable)]
+    unstable: UnstableRuntimeMetrics,
+}
+
+/// Metrics only available with `--cfg tokio_unstable`
+#[cfg(tokio_unstable)]
+struct UnstableRuntimeMetrics {
+    worker_polls: IntCounterVec,
+    worker_mean_poll_seconds: GaugeVec,
+    blocking_threads: IntGauge,
+    spawned_tasks: IntCounter,
+    /// Poll count of every worker and spawned tasks at the previous sample
+    previous_polls: Vec<u64>,
+    previous_spawned: u64,
+}
+
+impl RuntimeMetricsCollector {
+    pub fn new(registry: &Registry) -> Result<Self> {
+        Ok(Self {
+            workers: register_int_gauge_with_registry!(
+                "tokio_workers",
+                "Worker threads of the tokio runtime",
+                registry
+            )?,
+            alive_tasks: register_int_gauge_with_registry!(
+                "tokio_alive_tasks",
+                "Tasks currently alive in the tokio runtime",
+                registry
+            )?,
+            global_queue_depth: register_int_gauge_with_registry!(
+                "tokio_global_queue_depth",
+                "Tasks waiting in the runtime's global queue",
+                registry
+            )?,
+            worker_busy_seconds: register_counter_vec_with_registry!(
+                "tokio_worker_busy_seconds",
+                "Time each worker thread spent running tasks",
+                &["worker"],
+                registry
+            )?,
+            worker_parks: register_int_counter_vec_with_registry!(
+                "tokio_worker_parks",
+                "Times each worker thread parked for lack of work",
+                &["worker"],
+                registry
+            )?,
+            previous: Vec::new(),
+            #[cfg(tokio_unstable)]
+            unstable: UnstableRuntimeMetrics {
+                worker_polls: register_int_counter_vec_with_registry!(
+                    "tokio_worker_polls",
+                    "Task polls of each worker thread",
+                    &["worker"],
+                    registry
+                )?,
+                worker_mean_poll_seconds: register_gauge_vec_with_registry!(
+                    "tokio_worker_mean_poll_seconds",
+                    "Moving average of the poll time of each worker thread",
+                    &["worker"],
+                    registry
+                )?,
+                blocking_threads: register_int_gauge_with_registry!(
+                    "tokio_blocking_threads",
+                    "Threads of the runtime's blocking pool",
+                    registry
+                )?,
+                spawned_tasks: register_int_counter_with_registry!(
+                    "tokio_spawned_tasks",
+                    "Tasks spawned on the tokio runtime",
+                    registry
+                )?,
+                previous_polls: Vec::new(),
+                previous_spawned: 0,
+            },
+        })
+    }
+
+    /// Record the current state of the runtime
+    pub fn sample(&mut self, metrics: &RuntimeMetrics) {
+        let workers = metrics.num_workers();
+        self.workers.set(workers as i64);
+        self.alive_tasks.set(metrics.num_alive_tasks() as i64);
+        self.global_queue_depth.set(metrics.global_queue_depth() as i64);
+
+        self.previous.resize(workers, (Duration::ZERO, 0));
+        for worker in 0..workers {
+            let label = worker.to_string();
+            let busy = metrics.worker_total_busy_duration(worker);
+            let parks = metrics.worker_park_count(worker);
+            let (previous_busy, previous_parks) = self.previous[worker];
+            self.worker_busy_seconds
+                .with_label_values(&[&label])
+                .inc_by(busy.saturating_sub(previous_busy).as_secs_f64());
+            self.worker_parks.with_label_values(&[&label]).inc_by(parks.saturating_sub(previous_parks));
+            self.previous[worker] = (busy, parks);
+        }
+
+        #[cfg(tokio_unstable)]
+        self.unstable.sample(metrics, workers);
+    }
+}
+
+#[cfg(tokio_unstable)]
+impl UnstableRuntimeMetrics {
+    fn sample(&mut self, metrics: &RuntimeMetrics, workers: usize) {
+        self.previous_polls.resize(workers, 0);
+        for worker in 0..workers {
+            let label = worker.to_string();
+            let polls = metrics.worker_poll_count(worker);
+            self.worker_polls
+                .with_label_values(&[&label])
+                .inc_by(polls.saturating_sub(self.previous_polls[worker]));
+            self.previous_polls[worker] = polls;
+            self.worker_mean_poll_seconds
+                .with_label_values(&[&label])
+                .set(metrics.worker_mean_poll_time(worker).as_secs_f64());
+        }
+        self.blocking_threads.set(metrics.num_blocking_threads() as i64);
+        let spawned = metrics.spawned_tasks_count();
+        self.spawned_tasks.inc_by(spawned.saturating_sub(self.previous_spawned));
+        self.previous_spawned = spawned;
+    }
+}
+
+/// Start the task sampling the current runtime into `registry` every SAMPLE_INTERVAL
+pub async fn start_runtime_metrics_job(registry: &Registry) -> Result<()> {
+    let mut collector = RuntimeMetricsCollector::new(registry)?;
+    let metrics = Handle::current().metrics();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+        loop {
+            interval.tick().await;
+            collector.sample(&metrics);
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_samples_workers_and_tasks_of_the_runtime() {
+        let registry = Registry::new();
+        let mut collector = RuntimeMetricsCollector::new(&registry).unwrap();
+        let (release, released) = tokio::sync::oneshot::channel::<()>();
+        let task = tokio::spawn(async move {
+            let _ = released.await;
+        });
+
+        collector.sample(&Handle::current().metrics());
+        assert_eq!(collector.workers.get(), 2);
+        assert!(collector.alive_tasks.get() >= 1);
+        assert_eq!(collector.previous.len(), 2);
+
+        release.send(()).unwrap();
+        task.await.unwrap();
+    }
+}
@@ -1,5 +1,30 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    bridge_stats (dapp_name) {
+        dapp_name -> Varchar,
+        inbound_transfers_24h -> Int8,
+        outbound_transfers_24h -> Int8,
+        last_update -> Timestamp,
+        inbound_volume_24h_usd -> Float8,
+        outbound_volume_24h_usd -> Float8,
+    }
+}
+
+diesel::table! {
+    category_stats (id) {
+        id -> Int4,
+        dapp_type -> Varchar,
+        window_label -> Varchar,
+        active_users -> Int4,
+        transaction_count -> Int4,
+        interaction_count -> Int4,
+        dapp_count -> Int4,
+        computed_at -> Timestamp,
+        volume_24h_usd -> Float8,
+    }
+}
+
 diesel::table! {
     cetus_add_liquidity_events (id) {
         id -> Varchar,
@@ -28,6 +53,23 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    checkpoint_progress (task_name) {
+        task_name -> Varchar,
+        checkpoint_number -> Int8,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    daily_user_sketches (day, dapp_name) {
+        day -> Date,
+        dapp_name -> Varchar,
+        sketch -> Bytea,
+        updated_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     daily_statistics (id) {
         id -> Int4,
@@ -44,13 +86,265 @@ diesel::table! {
 }
 
 diesel::table! {
-    dapp_rankings (package_id) {
+    dapp_active_users (dapp_name) {
+        dapp_name -> Varchar,
+        wau -> Int4,
+        mau -> Int4,
+        as_of -> Date,
+        computed_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    dapp_calibration (id) {
+        id -> Int4,
+        dapp_name -> Varchar,
+        external_source -> Varchar,
+        external_dau -> Int4,
+        indexed_dau -> Int4,
+        divergence_pct -> Float8,
+        recorded_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    dapp_event_stats (dapp_name, event_type) {
+        dapp_name -> Varchar,
+        event_type -> Varchar,
+        count_24h -> Int8,
+        last_update -> Timestamp,
+    }
+}
+
+diesel::table! {
+    dapp_function_stats (dapp_name, function_name) {
+        dapp_name -> Varchar,
+        function_name -> Varchar,
+        call_count_24h -> Int8,
         rank_position -> Int4,
+        last_update -> Timestamp,
+    }
+}
+
+diesel::table! {
+    dapp_hourly_activity (dapp_name, hour_of_day) {
+        dapp_name -> Varchar,
+        hour_of_day -> Int4,
+        unique_users -> Int4,
+        observed_from -> Timestamp,
+        last_update -> Timestamp,
+    }
+}
+
+diesel::table! {
+    dapp_interactions (timestamp, transaction_digest, interaction_index) {
+        timestamp -> Timestamptz,
+        transaction_digest -> Varchar,
+        interaction_index -> Int4,
+        package_id -> Varchar,
+        dapp_name -> Nullable<Varchar>,
+        sender -> Varchar,
+        gas_used -> Int8,
+        event_type -> Varchar,
+        sponsored -> Bool,
+    }
+}
+
+diesel::table! {
+    dapp_lending_stats (dapp_name) {
+        dapp_name -> Varchar,
+        deposits_24h -> Int8,
+        withdrawals_24h -> Int8,
+        borrows_24h -> Int8,
+        repays_24h -> Int8,
+        unique_depositors_24h -> Int4,
+        unique_borrowers_24h -> Int4,
+        deposit_volume_24h_usd -> Float8,
+        borrow_volume_24h_usd -> Float8,
+        last_update -> Timestamp,
+    }
+}
+
+diesel::table! {
+    dapp_metadata (dapp_name) {
+        dapp_name -> Varchar,
+        website_url -> Nullable<Varchar>,
+        icon_url -> Nullable<Varchar>,
+        twitter -> Nullable<Varchar>,
+        last_update -> Timestamp,
+    }
+}
+
+diesel::table! {
+    dapp_nft_stats (dapp_name) {
+        dapp_name -> Varchar,
+        mints_24h -> Int8,
+        unique_minters_24h -> Int4,
+        transfers_24h -> Int8,
+        last_update -> Timestamp,
+    }
+}
+
+diesel::table! {
+    dapp_package_stats (package_id) {
         package_id -> Varchar,
         dapp_name -> Varchar,
-        dau_1h -> Int4,
         dapp_type -> Varchar,
-        last_update -> Nullable<Timestamp>,
+        active_users -> Int4,
+        tx_count -> Int4,
+        interaction_count -> Int4,
+        last_update -> Timestamp,
+        avg_gas_per_tx -> Float8,
+    }
+}
+
+diesel::table! {
+    dapp_published_packages (package_id) {
+        package_id -> Varchar,
+        dapp_name -> Varchar,
+        publisher -> Varchar,
+        discovered_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    dapp_ranking_history (id) {
+        id -> Int8,
+        dapp_name -> Varchar,
+        dapp_type -> Varchar,
+        rank_position -> Int4,
+        active_users -> Int4,
+        tx_count -> Int4,
+        gas_used -> Int8,
+        score -> Float8,
+        recorded_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    dapp_ranking_monthly (period_start, dapp_name) {
+        period_start -> Date,
+        rank_position -> Int4,
+        dapp_name -> Varchar,
+        dapp_type -> Varchar,
+        active_users -> Int4,
+        score -> Float8,
+        recorded_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    dapp_ranking_weekly (period_start, dapp_name) {
+        period_start -> Date,
+        rank_position -> Int4,
+        dapp_name -> Varchar,
+        dapp_type -> Varchar,
+        active_users -> Int4,
+        score -> Float8,
+        recorded_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    dapp_rankings (dapp_name, window_label) {
+        rank_position -> Int4,
+        package_id -> Varchar,
+        dapp_name -> Varchar,
+        active_users -> Int4,
+        dapp_type -> Varchar,
+        last_update -> Timestamptz,
+        filtered_users -> Int4,
+        tracking_status -> Varchar,
+        new_users_24h -> Int4,
+        returning_users_24h -> Int4,
+        tx_count -> Int4,
+        gas_used -> Int8,
+        score -> Float8,
+        error_rate_24h -> Float8,
+        dau_smoothed -> Float8,
+        window_label -> Varchar,
+        sponsored_user_pct -> Float8,
+        avg_gas_per_tx -> Float8,
+        interactions_p50 -> Int4,
+        interactions_p90 -> Int4,
+        interactions_p99 -> Int4,
+        top_users_interaction_pct -> Float8,
+        volume_24h_usd -> Float8,
+        wau -> Int4,
+        mau -> Int4,
+        deposit_volume_24h_usd -> Float8,
+        borrow_volume_24h_usd -> Float8,
+    }
+}
+
+diesel::table! {
+    dapp_registry (package_id) {
+        package_id -> Varchar,
+        dapp_name -> Varchar,
+        dapp_type -> Varchar,
+        active -> Bool,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    dapp_retention (cohort_day, dapp_name) {
+        cohort_day -> Date,
+        dapp_name -> Varchar,
+        cohort_size -> Int4,
+        d1_returned -> Int4,
+        d7_returned -> Int4,
+        d30_returned -> Int4,
+        d1_rate -> Nullable<Float8>,
+        d7_rate -> Nullable<Float8>,
+        d30_rate -> Nullable<Float8>,
+        computed_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    dapp_top_users (day, dapp_name, sender) {
+        day -> Date,
+        dapp_name -> Varchar,
+        sender -> Varchar,
+        interaction_count -> Int8,
+        rank_position -> Int4,
+    }
+}
+
+diesel::table! {
+    dapp_unknown_activity (package_id) {
+        package_id -> Varchar,
+        dapp_name -> Varchar,
+        observed_dau -> Int4,
+        last_seen -> Timestamp,
+    }
+}
+
+diesel::table! {
+    dapp_user_activity (day, dapp_name, sender) {
+        day -> Date,
+        dapp_name -> Varchar,
+        sender -> Varchar,
+    }
+}
+
+diesel::table! {
+    dapp_user_first_seen (dapp_name, sender) {
+        dapp_name -> Varchar,
+        sender -> Varchar,
+        first_seen -> Timestamp,
+    }
+}
+
+diesel::table! {
+    epoch_stats (epoch, dapp_name) {
+        epoch -> Int8,
+        dapp_name -> Varchar,
+        active_users -> Int4,
+        transaction_count -> Int4,
+        observed_from -> Timestamp,
+        last_update -> Timestamp,
     }
 }
 
@@ -108,14 +402,70 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    webhook_deliveries (id) {
+        id -> Int8,
+        subscription_id -> Int4,
+        payload -> Text,
+        status -> Varchar,
+        attempts -> Int4,
+        last_error -> Nullable<Varchar>,
+        response_status -> Nullable<Int4>,
+        next_attempt_at -> Timestamp,
+        created_at -> Timestamp,
+        delivered_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    webhook_subscriptions (id) {
+        id -> Int4,
+        dapp_name -> Varchar,
+        url -> Varchar,
+        threshold_pct -> Float8,
+        baseline_users -> Nullable<Int4>,
+        active -> Bool,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::joinable!(webhook_deliveries -> webhook_subscriptions (subscription_id));
+
 diesel::allow_tables_to_appear_in_same_query!(
+    bridge_stats,
+    category_stats,
     cetus_add_liquidity_events,
     cetus_remove_liquidity_events,
     cetus_swap_events,
+    checkpoint_progress,
     daily_statistics,
+    daily_user_sketches,
+    dapp_active_users,
+    dapp_calibration,
+    dapp_event_stats,
+    dapp_function_stats,
+    dapp_hourly_activity,
+    dapp_interactions,
+    dapp_lending_stats,
+    dapp_metadata,
+    dapp_nft_stats,
+    dapp_package_stats,
+    dapp_published_packages,
+    dapp_ranking_history,
+    dapp_ranking_monthly,
+    dapp_ranking_weekly,
     dapp_rankings,
+    dapp_registry,
+    dapp_retention,
+    dapp_top_users,
+    dapp_unknown_activity,
+    dapp_user_activity,
+    dapp_user_first_seen,
+    epoch_stats,
     hourly_statistics,
     liquidity_events,
     swap_events,
     volume_data,
+    webhook_deliveries,
+    webhook_subscriptions,
 );
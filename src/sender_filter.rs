@@ -0,0 +1,245 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Bot and sybil filtering for Hourly Active Users (HAU).
+//!
+//! Senders pass through a chain of rules before they count toward a DApp's HAU.
+//! Senders rejected by any rule are counted separately so bot traffic can be
+//! quantified per DApp instead of silently disappearing.
+//!
+//! Known wallets (market makers, bridges, internal test wallets) can also be
+//! excluded outright via a `SenderAccessList`, which is applied during
+//! extraction so those senders never enter the interaction window.
+//!
+//! For sponsored transactions the gas owner differs from the sender, so
+//! `UserAttribution` decides whether the sender, the sponsor or both count as
+//! the active user.
+
+use crate::models::DAppInteraction;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::str::FromStr;
+
+/// A single rule in the sender filter chain
+#[derive(Debug, Clone)]
+pub enum SenderFilterRule {
+    /// Reject senders whose address is in the set
+    Denylist(HashSet<String>),
+    /// Reject senders with fewer distinct transactions than this within the window
+    MinDistinctTransactions(usize),
+    /// Reject senders with more interactions than this in any single minute
+    MaxInteractionsPerMinute(usize),
+}
+
+impl SenderFilterRule {
+    /// Returns true if the sender should be excluded from HAU
+    ///
+    /// # Arguments
+    /// * `sender` - Sender address (or its anonymized hash)
+    /// * `interactions` - All in-window interactions of this sender with one DApp
+    fn rejects(&self, sender: &str, interactions: &[&DAppInteraction]) -> bool {
+        match self {
+            SenderFilterRule::Denylist(addresses) => addresses.contains(sender),
+            SenderFilterRule::MinDistinctTransactions(min) => {
+                let distinct: HashSet<&str> = interactions
+                    .iter()
+                    .map(|interaction| interaction.transaction_digest.as_str())
+                    .collect();
+                distinct.len() < *min
+            }
+            SenderFilterRule::MaxInteractionsPerMinute(max) => {
+                let mut per_minute: HashMap<u64, usize> = HashMap::new();
+                for interaction in interactions {
+                    *per_minute.entry(minute_bucket(interaction.timestamp)).or_default() += 1;
+                }
+                per_minute.values().any(|count| count > max)
+            }
+        }
+    }
+}
+
+/// Ordered chain of sender filter rules; an empty chain accepts every sender
+#[derive(Debug, Clone, Default)]
+pub struct SenderFilterChain {
+    pub rules: Vec<SenderFilterRule>,
+}
+
+impl SenderFilterChain {
+    pub fn new(rules: Vec<SenderFilterRule>) -> Self {
+        Self { rules }
+    }
+
+    /// Returns true if the sender passes every rule in the chain
+    pub fn accepts(&self, sender: &str, interactions: &[&DAppInteraction]) -> bool {
+        !self.rules.iter().any(|rule| rule.rejects(sender, interactions))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
+
+/// Sender allowlist/denylist applied during interaction extraction
+/// When an allowlist is present only listed senders are kept; the denylist always wins
+#[derive(Debug, Clone, Default)]
+pub struct SenderAccessList {
+    pub allowlist: Option<HashSet<String>>,
+    pub denylist: HashSet<String>,
+}
+
+impl SenderAccessList {
+    /// Load the access list from optional address files
+    /// Files contain one address per line; blank lines and `#` comments are ignored
+    pub fn from_files(allowlist_path: Option<&Path>, denylist_path: Option<&Path>) -> Result<Self> {
+        let allowlist = allowlist_path.map(read_address_file).transpose()?;
+        let denylist = denylist_path
+            .map(read_address_file)
+            .transpose()?
+            .unwrap_or_default();
+
+        Ok(Self { allowlist, denylist })
+    }
+
+    /// Returns true if interactions from this sender should be recorded
+    pub fn permits(&self, sender: &str) -> bool {
+        let sender = sender.to_lowercase();
+        if self.denylist.contains(&sender) {
+            return false;
+        }
+        match &self.allowlist {
+            Some(allowlist) => allowlist.contains(&sender),
+            None => true,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.allowlist.is_none() && self.denylist.is_empty()
+    }
+}
+
+/// Which address of a transaction counts as the active user
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UserAttribution {
+    /// The transaction sender
+    #[default]
+    Sender,
+    /// The gas owner, i.e. the sponsor of sponsored transactions
+    Sponsor,
+    /// Both the sender and the gas owner
+    Both,
+}
+
+impl FromStr for UserAttribution {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "sender" => Ok(UserAttribution::Sender),
+            "sponsor" | "gas_owner" => Ok(UserAttribution::Sponsor),
+            "both" => Ok(UserAttribution::Both),
+            _ => Err(anyhow!("Unknown user attribution '{}', expected sender, sponsor or both", value)),
+        }
+    }
+}
+
+impl UserAttribution {
+    /// Name accepted by `from_str`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UserAttribution::Sender => "sender",
+            UserAttribution::Sponsor => "sponsor",
+            UserAttribution::Both => "both",
+        }
+    }
+
+    /// Addresses counted as active users of a transaction
+    /// The gas owner equals the sender for unsponsored transactions, so `Both` never counts a user twice
+    pub fn users(&self, sender: String, gas_owner: String) -> Vec<String> {
+        match self {
+            UserAttribution::Sender => vec![sender],
+            UserAttribution::Sponsor => vec![gas_owner],
+            UserAttribution::Both if sender == gas_owner => vec![sender],
+            UserAttribution::Both => vec![sender, gas_owner],
+        }
+    }
+}
+
+/// Read a file of addresses, one per line, normalized to lowercase
+fn read_address_file(path: &Path) -> Result<HashSet<String>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read address list {}", path.display()))?;
+
+    Ok(contents
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty())
+        .map(str::to_lowercase)
+        .collect())
+}
+
+/// Minute-granularity bucket of a timestamp, used by the rate heuristic
+fn minute_bucket(timestamp: DateTime<Utc>) -> u64 {
+    timestamp.timestamp().max(0) as u64 / 60
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::InteractionBuilder;
+
+    #[test]
+    fn test_min_distinct_transactions() {
+        let chain = SenderFilterChain::new(vec![SenderFilterRule::MinDistinctTransactions(2)]);
+        let a = InteractionBuilder::new().dapp("Test").sender("0xsender").digest("tx1").seconds(0).build();
+        let b = InteractionBuilder::new().dapp("Test").sender("0xsender").digest("tx1").seconds(1).build();
+        let c = InteractionBuilder::new().dapp("Test").sender("0xsender").digest("tx2").seconds(2).build();
+        assert!(!chain.accepts("0xsender", &[&a, &b]));
+        assert!(chain.accepts("0xsender", &[&a, &b, &c]));
+    }
+
+    #[test]
+    fn test_max_interactions_per_minute_and_denylist() {
+        let chain = SenderFilterChain::new(vec![
+            SenderFilterRule::Denylist(HashSet::from(["0xbot".to_string()])),
+            SenderFilterRule::MaxInteractionsPerMinute(2),
+        ]);
+        let transactions = |spacing_secs: u64| -> Vec<DAppInteraction> {
+            (0..3)
+                .map(|i| InteractionBuilder::new().dapp("Test").sender("0xsender").digest(&format!("tx{}", i)).seconds(i * spacing_secs).build())
+                .collect()
+        };
+        let burst = transactions(1);
+        let spread = transactions(60);
+        assert!(!chain.accepts("0xsender", &burst.iter().collect::<Vec<_>>()));
+        assert!(chain.accepts("0xsender", &spread.iter().collect::<Vec<_>>()));
+        assert!(!chain.accepts("0xbot", &spread.iter().collect::<Vec<_>>()));
+    }
+
+    #[test]
+    fn test_sender_access_list_from_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let allowlist_path = dir.path().join("allowlist.txt");
+        let denylist_path = dir.path().join("denylist.txt");
+        std::fs::write(&allowlist_path, "# team wallets\n0xAAA\n0xbbb  # bridge\n\n").unwrap();
+        std::fs::write(&denylist_path, "0xbbb\n").unwrap();
+
+        let access = SenderAccessList::from_files(Some(&allowlist_path), Some(&denylist_path)).unwrap();
+        assert!(access.permits("0xaaa"));
+        assert!(!access.permits("0xbbb"));
+        assert!(!access.permits("0xccc"));
+    }
+
+    #[test]
+    fn test_user_attribution_of_sponsored_transactions() {
+        let users = |attribution: &str, gas_owner: &str| {
+            UserAttribution::from_str(attribution).unwrap().users("0xuser".to_string(), gas_owner.to_string())
+        };
+        assert_eq!(users("sender", "0xsponsor"), ["0xuser"]);
+        assert_eq!(users("sponsor", "0xsponsor"), ["0xsponsor"]);
+        assert_eq!(users("both", "0xsponsor"), ["0xuser", "0xsponsor"]);
+        assert_eq!(users("both", "0xuser"), ["0xuser"]);
+        assert!(UserAttribution::from_str("payer").is_err());
+    }
+}
@@ -0,0 +1,280 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Synthetic checkpoints for load testing (requires the `simulation` feature).
+//!
+//! `simulate` replaces the executor with a generator producing a checkpoint
+//! every CHECKPOINT_INTERVAL, stamped with the current time, and hands each one
+//! to the same worker, aggregator, ranking job and database writes as live
+//! ingestion, so the aggregation and database layers can be load-tested before
+//! a mainnet surge. Every transaction comes from one of a fixed population of
+//! users and emits events of one DApp of the mix, picked by weight. Users are
+//! skewed like on mainnet: low user indices are picked far more often, so a
+//! few heavy users account for much of the activity. The generator is seeded
+//! and repeats the same checkpoints for the same settings.
+//!
+//! When the pipeline cannot keep up, the bounded checkpoint queue holds the
+//! generator back and the achieved event rate logged every LOG_INTERVAL drops
+//! below the target.
+
+use crate::models::DAppRegistryEntry;
+use crate::test_utils::{dapp_event, user_address, CheckpointBuilder};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+use sui_data_ingestion_core::Worker;
+use sui_types::full_checkpoint_content::CheckpointData;
+use tokio::time::MissedTickBehavior;
+use tracing::{info, warn};
+
+/// Time between two generated checkpoints, close to mainnet's
+pub const CHECKPOINT_INTERVAL: Duration = Duration::from_millis(250);
+/// Achieved rates are logged this often
+const LOG_INTERVAL: Duration = Duration::from_secs(10);
+/// Exponent skewing the picked user index towards 0
+const USER_SKEW: i32 = 3;
+
+/// Load generated by `simulate`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationSettings {
+    /// Package of every simulated DApp with its share of the transactions
+    pub dapp_mix: Vec<(String, u32)>,
+    /// Distinct users sending transactions
+    pub users: u64,
+    /// Target DApp events per second
+    pub events_per_second: u64,
+    pub events_per_transaction: usize,
+    pub seed: u64,
+}
+
+impl Default for SimulationSettings {
+    fn default() -> Self {
+        Self {
+            dapp_mix: Vec::new(),
+            users: 10_000,
+            events_per_second: 1_000,
+            events_per_transaction: 2,
+            seed: 0,
+        }
+    }
+}
+
+/// One weighted package per enabled DApp of the registry, equal weights
+pub fn default_dapp_mix(registry: &HashMap<String, DAppRegistryEntry>) -> Vec<(String, u32)> {
+    dapp_packages(registry).into_values().map(|package_id| (package_id, 1)).collect()
+}
+
+/// Parse `name=weight` pairs separated by commas, e.g. `Cetus AMM=50,Pyth=30`
+/// Each DApp is simulated through one of its packages in `registry`
+pub fn parse_dapp_mix(value: &str, registry: &HashMap<String, DAppRegistryEntry>) -> Result<Vec<(String, u32)>> {
+    let packages = dapp_packages(registry);
+    let mut mix = Vec::new();
+    for pair in value.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+        let (name, weight) = pair
+            .rsplit_once('=')
+            .ok_or_else(|| anyhow!("Invalid DApp mix entry '{}', expected name=weight (e.g. Pyth=30)", pair))?;
+        let package_id = packages
+            .get(name.trim())
+            .ok_or_else(|| anyhow!("DApp mix contains unknown or disabled DApp: {}", name.trim()))?;
+        let weight = match weight.trim().parse::<u32>() {
+            Ok(weight) if weight > 0 => weight,
+            _ => return Err(anyhow!("Invalid weight '{}' of {}, expected a positive integer", weight, name.trim())),
+        };
+        mix.push((package_id.clone(), weight));
+    }
+    if mix.is_empty() {
+        return Err(anyhow!("DApp mix is empty"));
+    }
+    Ok(mix)
+}
+
+/// dapp_name -> lowest package id, over the enabled DApps
+fn dapp_packages(registry: &HashMap<String, DAppRegistryEntry>) -> BTreeMap<String, String> {
+    let mut packages: BTreeMap<String, String> = BTreeMap::new();
+    for (package_id, entry) in registry.iter().filter(|(_, entry)| entry.enabled) {
+        let lowest = packages.entry(entry.dapp_name.clone()).or_insert_with(|| package_id.clone());
+        if package_id < lowest {
+            *lowest = package_id.clone();
+        }
+    }
+    packages
+}
+
+/// SplitMix64, small and good enough to spread synthetic load
+#[derive(Debug, Clone)]
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Builds synthetic checkpoints following `SimulationSettings`
+#[derive(Debug, Clone)]
+pub struct CheckpointGenerator {
+    settings: SimulationSettings,
+    total_weight: u64,
+    rng: SplitMix64,
+    next_sequence_number: u64,
+}
+
+impl CheckpointGenerator {
+    pub fn new(settings: SimulationSettings) -> Result<Self> {
+        if settings.dapp_mix.is_empty() {
+            return Err(anyhow!("Simulation needs at least one DApp"));
+        }
+        if settings.users == 0 || settings.events_per_second == 0 || settings.events_per_transaction == 0 {
+            return Err(anyhow!("Simulation needs at least one user, event per second and event per transaction"));
+        }
+        let total_weight = settings.dapp_mix.iter().map(|(_, weight)| *weight as u64).sum();
+        let rng = SplitMix64(settings.seed);
+        Ok(Self { settings, total_weight, rng, next_sequence_number: 0 })
+    }
+
+    /// DApp events in every checkpoint, to reach the target rate at CHECKPOINT_INTERVAL
+    pub fn events_per_checkpoint(&self) -> usize {
+        ((self.settings.events_per_second as f64 * CHECKPOINT_INTERVAL.as_secs_f64()).ceil() as usize).max(1)
+    }
+
+    /// The next checkpoint, with `timestamp` as its time
+    pub fn next_checkpoint(&mut self, timestamp: DateTime<Utc>) -> CheckpointData {
+        let mut builder = CheckpointBuilder::new(self.next_sequence_number).at(timestamp);
+        self.next_sequence_number += 1;
+
+        let mut remaining = self.events_per_checkpoint();
+        while remaining > 0 {
+            let events = remaining.min(self.settings.events_per_transaction);
+            remaining -= events;
+            let user = self.pick_user();
+            let package_id = self.pick_package().to_string();
+            let sender = user_address(user);
+            let events = (0..events).map(|_| dapp_event(&package_id, sender)).collect();
+            builder = builder.transaction_with_events((user % 256) as u8, events);
+        }
+        builder.build()
+    }
+
+    fn pick_user(&mut self) -> u64 {
+        let user = (self.rng.next_f64().powi(USER_SKEW) * self.settings.users as f64) as u64;
+        user.min(self.settings.users - 1)
+    }
+
+    fn pick_package(&mut self) -> &str {
+        let mut target = self.rng.next_u64() % self.total_weight;
+        for (package_id, weight) in &self.settings.dapp_mix {
+            if target < *weight as u64 {
+                return package_id;
+            }
+            target -= *weight as u64;
+        }
+        &self.settings.dapp_mix[0].0
+    }
+}
+
+/// Hand a generated checkpoint to `worker` every CHECKPOINT_INTERVAL until
+/// `duration` has passed or on Ctrl-C
+pub async fn run_simulation<W: Worker>(
+    mut generator: CheckpointGenerator,
+    worker: &W,
+    duration: Option<Duration>,
+) -> Result<()> {
+    let events_per_checkpoint = generator.events_per_checkpoint() as u64;
+    info!("🧪 Simulating {} events/s of {} DApps from {} users ({} events per checkpoint)",
+          generator.settings.events_per_second, generator.settings.dapp_mix.len(),
+          generator.settings.users, events_per_checkpoint);
+
+    let started = Instant::now();
+    let mut ticks = tokio::time::interval(CHECKPOINT_INTERVAL);
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut last_log = Instant::now();
+    let mut logged_checkpoints = 0u64;
+    let mut checkpoints = 0u64;
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    while duration.is_none_or(|duration| started.elapsed() < duration) {
+        tokio::select! {
+            _ = ticks.tick() => {}
+            _ = &mut ctrl_c => break,
+        }
+        let checkpoint = generator.next_checkpoint(Utc::now());
+        worker.process_checkpoint(&checkpoint).await?;
+        checkpoints += 1;
+
+        if last_log.elapsed() >= LOG_INTERVAL {
+            let rate = (checkpoints - logged_checkpoints) * events_per_checkpoint / last_log.elapsed().as_secs().max(1);
+            if rate < generator.settings.events_per_second * 9 / 10 {
+                warn!("🐢 Simulation at {} events/s, below the target of {}: the pipeline cannot keep up",
+                      rate, generator.settings.events_per_second);
+            } else {
+                info!("🧪 Simulation at {} events/s ({} checkpoints so far)", rate, checkpoints);
+            }
+            last_log = Instant::now();
+            logged_checkpoints = checkpoints;
+        }
+    }
+
+    info!("🧪 Simulation done: {} checkpoints, {} events in {:.0?}",
+          checkpoints, checkpoints * events_per_checkpoint, started.elapsed());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dapp_indexer::DAppIndexer;
+
+    const CETUS: &str = "0x6f5e582ede61fe5395b50c4a449ec11479a54d7ff8e0158247adfda60d98970b";
+    const PYTH: &str = "0x04e20ddf36af412a4096f9014f4a565af9e812db9a05cc40254846cf6ed0ad91";
+
+    #[test]
+    fn test_generated_checkpoints_follow_the_dapp_mix() {
+        let registry = HashMap::from([
+            (CETUS.to_string(), DAppRegistryEntry::new("Cetus AMM", "DEX")),
+            (PYTH.to_string(), DAppRegistryEntry::new("Pyth", "Oracle")),
+        ]);
+        assert_eq!(default_dapp_mix(&registry).len(), 2);
+        assert!(parse_dapp_mix("Cetus=1", &registry).is_err());
+        assert!(parse_dapp_mix("Pyth=0", &registry).is_err());
+        let settings = SimulationSettings {
+            dapp_mix: parse_dapp_mix("Cetus AMM=3, Pyth=1", &registry).unwrap(),
+            users: 50,
+            events_per_second: 4_000,
+            ..SimulationSettings::default()
+        };
+        assert_eq!(settings.dapp_mix, [(CETUS.to_string(), 3), (PYTH.to_string(), 1)]);
+
+        let mut generator = CheckpointGenerator::new(settings.clone()).unwrap();
+        assert_eq!(generator.events_per_checkpoint(), 1_000);
+        let now = Utc::now();
+        let checkpoint = generator.next_checkpoint(now);
+        assert_eq!(checkpoint.checkpoint_summary.sequence_number, 0);
+        assert_eq!(generator.next_checkpoint(now).checkpoint_summary.sequence_number, 1);
+
+        let extractor = DAppIndexer::builder().registry(registry).build().extractor();
+        let extracted = extractor.extract(&checkpoint);
+        assert_eq!(extracted.interactions.len(), 1_000);
+        let cetus = extracted.interactions.iter().filter(|interaction| interaction.package_id == CETUS).count();
+        assert!((650..850).contains(&cetus), "{} Cetus interactions", cetus);
+        let first_user = user_address(0).to_string();
+        let heavy = extracted.interactions.iter().filter(|interaction| interaction.sender == first_user).count();
+        assert!(heavy > 1_000 / 50, "the first user sent only {} events", heavy);
+
+        // Seeded: the same settings generate the same checkpoints
+        let replayed = CheckpointGenerator::new(settings).unwrap().next_checkpoint(now);
+        let senders = |checkpoint: &CheckpointData| -> Vec<String> {
+            extractor.extract(checkpoint).interactions.into_iter().map(|interaction| interaction.sender).collect()
+        };
+        assert_eq!(senders(&replayed), senders(&checkpoint));
+    }
+}
@@ -0,0 +1,353 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Daily export of the rankings and category stats to an object-store bucket.
+//!
+//! With SNAPSHOT_EXPORT_BUCKET set, the rankings and per-category stats are
+//! uploaded once a day for downstream data lakes, as JSON or Snappy-compressed
+//! Parquet. Any S3-compatible store works: AWS S3, Google Cloud Storage through
+//! its XML API (`https://storage.googleapis.com` with HMAC keys), MinIO or R2.
+//! Object keys come from SNAPSHOT_EXPORT_PATH, a template of:
+//! - `{date}`, or `{year}`, `{month}` and `{day}`: the day of the snapshot
+//! - `{kind}`: `rankings` or `categories`
+//! - `{ext}`: `json` or `parquet`
+//!
+//! The job checks at every full hour. At the first check of a day, in the stats
+//! timezone, the current rankings are exported as the snapshot of the day that
+//! just ended. A snapshot already in the bucket, written by another replica or
+//! before a restart, is left as it is. Only the leader exports.
+
+use crate::clock::Clock;
+use crate::dapp_indexer::DAppIndexer;
+use crate::export::{encode_rows, ExportFormat, RankingRow};
+use crate::leader_election::Leadership;
+use crate::models::{CategoryStats, DAppRanking};
+use crate::ranking_snapshots::duration_until_next_hour;
+use anyhow::{anyhow, Result};
+use arrow_array::{ArrayRef, Float64Array, RecordBatch, StringArray, UInt32Array, UInt64Array};
+use chrono::{Datelike, Days, NaiveDate};
+use chrono_tz::Tz;
+use object_store::aws::AmazonS3Builder;
+use object_store::path::Path as ObjectPath;
+use object_store::ObjectStore;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use serde::Serialize;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{error, info};
+
+/// Object key template unless configured otherwise
+pub const DEFAULT_PATH_TEMPLATE: &str = "snapshots/date={date}/{kind}.{ext}";
+
+/// File format of the snapshots
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SnapshotFormat {
+    #[default]
+    Json,
+    Parquet,
+}
+
+impl FromStr for SnapshotFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "json" => Ok(SnapshotFormat::Json),
+            "parquet" => Ok(SnapshotFormat::Parquet),
+            _ => Err(anyhow!("Unknown snapshot format '{}', expected json or parquet", value)),
+        }
+    }
+}
+
+impl SnapshotFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SnapshotFormat::Json => "json",
+            SnapshotFormat::Parquet => "parquet",
+        }
+    }
+}
+
+/// Template of the object keys snapshots are written to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotPathTemplate(String);
+
+impl FromStr for SnapshotPathTemplate {
+    type Err = anyhow::Error;
+
+    /// Accept a template naming the day and the kind of the snapshot with known placeholders only
+    fn from_str(value: &str) -> Result<Self> {
+        let mut rest = value;
+        while let Some(start) = rest.find('{') {
+            let end = rest[start..].find('}').ok_or_else(|| anyhow!("Unclosed placeholder in '{}'", value))?;
+            let placeholder = &rest[start + 1..start + end];
+            if !["date", "year", "month", "day", "kind", "ext"].contains(&placeholder) {
+                return Err(anyhow!("Unknown placeholder {{{}}} in '{}'", placeholder, value));
+            }
+            rest = &rest[start + end + 1..];
+        }
+        let dated = value.contains("{date}") || ["{year}", "{month}", "{day}"].iter().all(|part| value.contains(part));
+        if !dated || !value.contains("{kind}") {
+            return Err(anyhow!("'{}' must contain {{date}} (or {{year}}, {{month}} and {{day}}) and {{kind}}", value));
+        }
+        Ok(Self(value.trim_matches('/').to_string()))
+    }
+}
+
+impl std::fmt::Display for SnapshotPathTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl SnapshotPathTemplate {
+    /// Object key of the `kind` snapshot of `day`
+    pub fn render(&self, day: NaiveDate, kind: &str, format: SnapshotFormat) -> String {
+        self.0
+            .replace("{date}", &day.to_string())
+            .replace("{year}", &format!("{:04}", day.year()))
+            .replace("{month}", &format!("{:02}", day.month()))
+            .replace("{day}", &format!("{:02}", day.day()))
+            .replace("{kind}", kind)
+            .replace("{ext}", format.extension())
+    }
+}
+
+/// Bucket the snapshots are exported to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotExportSettings {
+    pub bucket: String,
+    /// Endpoint of an S3-compatible store, AWS S3 if unset
+    pub endpoint: Option<String>,
+    pub region: Option<String>,
+    /// Access key pair; the standard AWS_* variables are used if unset
+    pub access_key_id: Option<String>,
+    pub secret_access_key: Option<String>,
+    pub path_template: SnapshotPathTemplate,
+    pub format: SnapshotFormat,
+}
+
+impl SnapshotExportSettings {
+    /// Client of the bucket
+    pub fn open(&self) -> Result<Arc<dyn ObjectStore>> {
+        let mut builder = AmazonS3Builder::from_env().with_bucket_name(&self.bucket);
+        if let Some(endpoint) = &self.endpoint {
+            builder = builder.with_endpoint(endpoint).with_allow_http(endpoint.starts_with("http://"));
+        }
+        if let Some(region) = &self.region {
+            builder = builder.with_region(region);
+        }
+        if let (Some(access_key_id), Some(secret_access_key)) = (&self.access_key_id, &self.secret_access_key) {
+            builder = builder.with_access_key_id(access_key_id).with_secret_access_key(secret_access_key);
+        }
+        Ok(Arc::new(builder.build()?))
+    }
+}
+
+/// One exported category
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CategoryRow {
+    pub dapp_type: String,
+    pub active_users: u32,
+    pub transaction_count: u32,
+    pub interaction_count: u32,
+    pub dapp_count: u32,
+    pub volume_24h_usd: f64,
+    pub last_update: String,
+}
+
+impl From<&CategoryStats> for CategoryRow {
+    fn from(stats: &CategoryStats) -> Self {
+        Self {
+            dapp_type: stats.dapp_type.clone(),
+            active_users: stats.active_users,
+            transaction_count: stats.transaction_count,
+            interaction_count: stats.interaction_count,
+            dapp_count: stats.dapp_count,
+            volume_24h_usd: stats.volume_24h_usd,
+            last_update: stats.last_update.to_rfc3339(),
+        }
+    }
+}
+
+fn ranking_batch(rows: &[RankingRow]) -> Result<RecordBatch> {
+    let text = |value: fn(&RankingRow) -> &str| -> ArrayRef { Arc::new(StringArray::from_iter_values(rows.iter().map(value))) };
+    let count = |value: fn(&RankingRow) -> u32| -> ArrayRef { Arc::new(UInt32Array::from_iter_values(rows.iter().map(value))) };
+    let ratio = |value: fn(&RankingRow) -> f64| -> ArrayRef { Arc::new(Float64Array::from_iter_values(rows.iter().map(value))) };
+    Ok(RecordBatch::try_from_iter([
+        ("rank", count(|row| row.rank)),
+        ("dapp_name", text(|row| &row.dapp_name)),
+        ("package_id", text(|row| &row.package_id)),
+        ("dapp_type", text(|row| &row.dapp_type)),
+        ("hau", count(|row| row.hau)),
+        ("filtered_users", count(|row| row.filtered_users)),
+        ("tracking_status", text(|row| &row.tracking_status)),
+        ("new_users_24h", count(|row| row.new_users_24h)),
+        ("returning_users_24h", count(|row| row.returning_users_24h)),
+        ("tx_count", count(|row| row.tx_count)),
+        ("gas_used", Arc::new(UInt64Array::from_iter_values(rows.iter().map(|row| row.gas_used))) as ArrayRef),
+        ("score", ratio(|row| row.score)),
+        ("error_rate_24h", ratio(|row| row.error_rate_24h)),
+        ("dau_smoothed", ratio(|row| row.dau_smoothed)),
+        ("sponsored_user_pct", ratio(|row| row.sponsored_user_pct)),
+        ("avg_gas_per_tx", ratio(|row| row.avg_gas_per_tx)),
+        ("interactions_p50", count(|row| row.interactions_p50)),
+        ("interactions_p90", count(|row| row.interactions_p90)),
+        ("interactions_p99", count(|row| row.interactions_p99)),
+        ("top_users_interaction_pct", ratio(|row| row.top_users_interaction_pct)),
+        ("volume_24h_usd", ratio(|row| row.volume_24h_usd)),
+        ("deposit_volume_24h_usd", ratio(|row| row.deposit_volume_24h_usd)),
+        ("borrow_volume_24h_usd", ratio(|row| row.borrow_volume_24h_usd)),
+        ("last_update", text(|row| &row.last_update)),
+    ])?)
+}
+
+fn category_batch(rows: &[CategoryRow]) -> Result<RecordBatch> {
+    let count = |value: fn(&CategoryRow) -> u32| -> ArrayRef { Arc::new(UInt32Array::from_iter_values(rows.iter().map(value))) };
+    Ok(RecordBatch::try_from_iter([
+        ("dapp_type", Arc::new(StringArray::from_iter_values(rows.iter().map(|row| &row.dapp_type))) as ArrayRef),
+        ("active_users", count(|row| row.active_users)),
+        ("transaction_count", count(|row| row.transaction_count)),
+        ("interaction_count", count(|row| row.interaction_count)),
+        ("dapp_count", count(|row| row.dapp_count)),
+        ("volume_24h_usd", Arc::new(Float64Array::from_iter_values(rows.iter().map(|row| row.volume_24h_usd))) as ArrayRef),
+        ("last_update", Arc::new(StringArray::from_iter_values(rows.iter().map(|row| &row.last_update))) as ArrayRef),
+    ])?)
+}
+
+fn encode_batch(batch: &RecordBatch) -> Result<Vec<u8>> {
+    let properties = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+    let mut buffer = Vec::new();
+    let mut writer = ArrowWriter::try_new(&mut buffer, batch.schema(), Some(properties))?;
+    writer.write(batch)?;
+    writer.close()?;
+    Ok(buffer)
+}
+
+/// Upload the snapshot of `day` unless the bucket already holds it
+///
+/// # Returns
+/// * Whether the snapshot was written
+pub async fn export_snapshot(
+    store: &dyn ObjectStore,
+    path_template: &SnapshotPathTemplate,
+    format: SnapshotFormat,
+    day: NaiveDate,
+    rankings: &[DAppRanking],
+    categories: &[CategoryStats],
+) -> Result<bool> {
+    let location = |kind: &str| ObjectPath::from(path_template.render(day, kind, format));
+    // Rankings are written last, so their presence means the whole snapshot is there
+    match store.head(&location("rankings")).await {
+        Ok(_) => return Ok(false),
+        Err(object_store::Error::NotFound { .. }) => {}
+        Err(err) => return Err(err.into()),
+    }
+
+    let rankings: Vec<RankingRow> = rankings.iter().map(RankingRow::from).collect();
+    let categories: Vec<CategoryRow> = categories.iter().map(CategoryRow::from).collect();
+    let (rankings, categories) = match format {
+        SnapshotFormat::Json => (encode_rows(&rankings, ExportFormat::Json)?, encode_rows(&categories, ExportFormat::Json)?),
+        SnapshotFormat::Parquet => (encode_batch(&ranking_batch(&rankings)?)?, encode_batch(&category_batch(&categories)?)?),
+    };
+    store.put(&location("categories"), categories.into()).await?;
+    store.put(&location("rankings"), rankings.into()).await?;
+    Ok(true)
+}
+
+/// Start the job exporting the snapshot of every day that ends, checked at every full hour
+/// Days end at midnight of `timezone`; only the leader exports
+pub async fn start_snapshot_export_job(
+    indexer: Arc<Mutex<DAppIndexer>>,
+    settings: SnapshotExportSettings,
+    timezone: Tz,
+    leadership: Leadership,
+) -> Result<()> {
+    let store = settings.open()?;
+    let clock: Arc<dyn Clock> = indexer.lock().await.clock();
+    info!("🪣 Exporting daily {} snapshots to {}/{}", settings.format.extension(), settings.bucket, settings.path_template);
+
+    tokio::spawn(async move {
+        let mut exported: Option<NaiveDate> = None;
+        loop {
+            let seconds = clock.now().timestamp().max(0) as u64;
+            tokio::time::sleep(duration_until_next_hour(seconds)).await;
+            let Some(day) = clock.now().with_timezone(&timezone).date_naive().checked_sub_days(Days::new(1)) else {
+                continue;
+            };
+            if exported == Some(day) || !leadership.is_leader() {
+                continue;
+            }
+
+            let (rankings, categories) = {
+                let indexer = indexer.lock().await;
+                (indexer.dapp_rankings.clone(), indexer.category_stats.clone())
+            };
+            let written = export_snapshot(
+                store.as_ref(),
+                &settings.path_template,
+                settings.format,
+                day,
+                &rankings,
+                &categories,
+            )
+            .await;
+            match written {
+                Ok(written) => {
+                    if written {
+                        info!("🪣 Exported the snapshot of {} ({} rankings, {} categories)", day, rankings.len(), categories.len());
+                    }
+                    exported = Some(day);
+                }
+                Err(err) => error!("❌ Failed to export the snapshot of {}: {}", day, err),
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Utc};
+    use object_store::memory::InMemory;
+
+    fn category(dapp_type: &str, active_users: u32) -> CategoryStats {
+        CategoryStats {
+            dapp_type: dapp_type.to_string(),
+            active_users,
+            transaction_count: active_users * 2,
+            interaction_count: active_users * 3,
+            dapp_count: 1,
+            volume_24h_usd: 0.0,
+            last_update: DateTime::<Utc>::UNIX_EPOCH,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_exports_each_day_once_under_its_date() {
+        let template: SnapshotPathTemplate = "lake/{year}/{month}/{day}/{kind}.{ext}".parse().unwrap();
+        let day: NaiveDate = "2025-06-09".parse().unwrap();
+        assert_eq!(template.render(day, "rankings", SnapshotFormat::Parquet), "lake/2025/06/09/rankings.parquet");
+        assert!("snapshots/{kind}.json".parse::<SnapshotPathTemplate>().is_err());
+        assert!("{date}/{kind}.{suffix}".parse::<SnapshotPathTemplate>().is_err());
+
+        let store = InMemory::new();
+        let categories = [category("DEX", 10), category("Oracle", 4)];
+        assert!(export_snapshot(&store, &template, SnapshotFormat::Parquet, day, &[], &categories).await.unwrap());
+        let written = store.get(&ObjectPath::from("lake/2025/06/09/categories.parquet")).await.unwrap();
+        assert!(written.bytes().await.unwrap().starts_with(b"PAR1"));
+        assert!(store.head(&ObjectPath::from("lake/2025/06/09/rankings.parquet")).await.is_ok());
+
+        // The snapshot of a day is never overwritten
+        assert!(!export_snapshot(&store, &template, SnapshotFormat::Parquet, day, &[], &[]).await.unwrap());
+        let json: SnapshotPathTemplate = DEFAULT_PATH_TEMPLATE.parse().unwrap();
+        assert!(export_snapshot(&store, &json, SnapshotFormat::Json, day, &[], &categories).await.unwrap());
+        let written = store.get(&ObjectPath::from("snapshots/date=2025-06-09/categories.json")).await.unwrap();
+        let rows: Vec<serde_json::Value> = serde_json::from_slice(&written.bytes().await.unwrap()).unwrap();
+        assert_eq!(rows[1]["dapp_type"], "Oracle");
+    }
+}
@@ -0,0 +1,103 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Startup modes.
+//!
+//! What the indexer does with the state persisted by its previous run:
+//! - `resume` loads the stored rankings and continues from the saved checkpoint
+//! - `reset` clears the stored rankings and starts counting from scratch
+//! - `reconcile` loads the stored rankings like `resume`, but starts ingestion
+//!   `RECONCILE_CHECKPOINTS` before the saved checkpoint so the in-memory
+//!   window is rebuilt before the rankings are next recomputed
+//!
+//! Registry changes made through the admin API are loaded in every mode.
+
+use crate::dapp_indexer::DAppIndexer;
+use crate::database::DatabaseManager;
+use crate::interaction_store::window_label;
+use anyhow::{anyhow, Result};
+use std::fmt;
+use std::str::FromStr;
+use tracing::info;
+
+/// Checkpoints replayed by `reconcile` unless configured otherwise,
+/// about an hour of mainnet checkpoints
+pub const DEFAULT_RECONCILE_CHECKPOINTS: u64 = 18_000;
+
+/// How the indexer treats the state persisted by its previous run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartupMode {
+    /// Load the stored rankings and continue from the saved checkpoint
+    Resume,
+    /// Clear the stored rankings and start from scratch
+    Reset,
+    /// Load the stored rankings and replay recent checkpoints to rebuild the window
+    Reconcile,
+}
+
+impl FromStr for StartupMode {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "resume" => Ok(StartupMode::Resume),
+            "reset" => Ok(StartupMode::Reset),
+            "reconcile" => Ok(StartupMode::Reconcile),
+            _ => Err(anyhow!("Unknown startup mode '{}', expected resume, reset or reconcile", value)),
+        }
+    }
+}
+
+impl fmt::Display for StartupMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            StartupMode::Resume => "resume",
+            StartupMode::Reset => "reset",
+            StartupMode::Reconcile => "reconcile",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Prepare the indexer's state from the database according to `mode`
+pub async fn restore_state(indexer: &mut DAppIndexer, db_manager: &DatabaseManager, mode: StartupMode) -> Result<()> {
+    match mode {
+        StartupMode::Reset => {
+            indexer.reset_database_and_memory(db_manager).await?;
+            // Registry changes made through the admin API survive the reset
+            indexer.load_registry_from_database(db_manager).await?;
+            info!("🚀 Starting fresh with clean database and memory");
+        }
+        StartupMode::Resume | StartupMode::Reconcile => {
+            indexer
+                .get_data_from_database(db_manager)
+                .await
+                .map_err(|err| anyhow!("Failed to load DApp rankings: {}", err))?;
+
+            let rankings = indexer.get_dapp_rankings();
+            if !rankings.is_empty() {
+                info!("🏆 Current Top DApps ({} active users):", window_label(indexer.ranking_window));
+                for ranking in rankings.iter().take(5) {
+                    info!("  {}. {} - {} users", ranking.rank, ranking.dapp_name, ranking.active_users);
+                }
+            } else {
+                info!("ℹ️ No existing DApp rankings found in database");
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_startup_mode_round_trips() {
+        for mode in [StartupMode::Resume, StartupMode::Reset, StartupMode::Reconcile] {
+            assert_eq!(mode.to_string().parse::<StartupMode>().unwrap(), mode);
+        }
+        assert_eq!("RESET".parse::<StartupMode>().unwrap(), StartupMode::Reset);
+        assert!("restart".parse::<StartupMode>().is_err());
+    }
+}
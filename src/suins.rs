@@ -0,0 +1,132 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! SuiNS enrichment of sender addresses.
+//!
+//! Resolves addresses to their default SuiNS name (e.g. `alice.sui`) through a
+//! fullnode's JSON-RPC `suix_resolveNameServiceNames`, so top-user reports and
+//! exports are human-readable. Lookups are cached, including addresses without
+//! a name, and a failed lookup only leaves the name empty.
+
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Timeout of a single RPC request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Cached lookups kept before expired entries are evicted
+const MAX_CACHE_ENTRIES: usize = 100_000;
+
+/// Resolves addresses to SuiNS names with a TTL cache
+#[derive(Debug)]
+pub struct SuinsResolver {
+    rpc_url: String,
+    client: reqwest::Client,
+    cache_ttl: Duration,
+    /// address -> (name, when it was resolved)
+    cache: Mutex<HashMap<String, (Option<String>, Instant)>>,
+}
+
+impl SuinsResolver {
+    /// Create a resolver querying the fullnode JSON-RPC at `rpc_url`
+    pub fn new(rpc_url: impl Into<String>, cache_ttl: Duration) -> Result<Self> {
+        let client = reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build()?;
+        Ok(Self {
+            rpc_url: rpc_url.into(),
+            client,
+            cache_ttl,
+            cache: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Default SuiNS name of `address`, None if it has none or the lookup failed
+    pub async fn resolve(&self, address: &str) -> Option<String> {
+        if let Some((name, resolved_at)) = self.cache.lock().unwrap().get(address) {
+            if resolved_at.elapsed() < self.cache_ttl {
+                return name.clone();
+            }
+        }
+
+        let name = match self.lookup(address).await {
+            Ok(name) => name,
+            Err(err) => {
+                // Not cached, so the next report retries
+                warn!("⚠️ SuiNS lookup failed for {}: {}", address, err);
+                return None;
+            }
+        };
+
+        let mut cache = self.cache.lock().unwrap();
+        if cache.len() >= MAX_CACHE_ENTRIES {
+            let ttl = self.cache_ttl;
+            cache.retain(|_, (_, resolved_at)| resolved_at.elapsed() < ttl);
+        }
+        cache.insert(address.to_string(), (name.clone(), Instant::now()));
+        name
+    }
+
+    /// SuiNS names of the addresses that have one
+    pub async fn resolve_all<'a>(&self, addresses: impl IntoIterator<Item = &'a str>) -> HashMap<String, String> {
+        let mut names = HashMap::new();
+        for address in addresses {
+            if names.contains_key(address) {
+                continue;
+            }
+            if let Some(name) = self.resolve(address).await {
+                names.insert(address.to_string(), name);
+            }
+        }
+        names
+    }
+
+    async fn lookup(&self, address: &str) -> Result<Option<String>> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "suix_resolveNameServiceNames",
+            "params": [address, null, 1],
+        });
+        let response: Value = self
+            .client
+            .post(&self.rpc_url)
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        parse_resolved_name(&response)
+    }
+}
+
+/// First name of a `suix_resolveNameServiceNames` response
+fn parse_resolved_name(response: &Value) -> Result<Option<String>> {
+    if let Some(error) = response.get("error") {
+        return Err(anyhow!("RPC error: {}", error));
+    }
+    let names = response
+        .pointer("/result/data")
+        .and_then(Value::as_array)
+        .ok_or_else(|| anyhow!("Malformed SuiNS response: {}", response))?;
+    Ok(names.first().and_then(Value::as_str).map(str::to_string))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_resolved_name() {
+        let named = json!({"jsonrpc": "2.0", "id": 1, "result": {"data": ["alice.sui"], "hasNextPage": false}});
+        let unnamed = json!({"jsonrpc": "2.0", "id": 1, "result": {"data": [], "hasNextPage": false}});
+        let failed = json!({"jsonrpc": "2.0", "id": 1, "error": {"code": -32602, "message": "Invalid params"}});
+
+        assert_eq!(parse_resolved_name(&named).unwrap().as_deref(), Some("alice.sui"));
+        assert_eq!(parse_resolved_name(&unnamed).unwrap(), None);
+        assert!(parse_resolved_name(&failed).is_err());
+    }
+}
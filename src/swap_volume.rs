@@ -0,0 +1,346 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! USD swap volume of DEXes and aggregators over the last 24 hours.
+//!
+//! HAU says how many people use a DEX, not how much they trade through it, so
+//! DApps of the `DEX` and `Aggregator` categories also get their swap volume.
+//! A successful transaction emitting one of the DApp's swap events, listed in
+//! `SWAP_EVENTS`, is a swap. What the sender swapped is read from the
+//! sender's coin objects: every coin type whose balance dropped between the
+//! transaction's inputs and outputs was spent, with the gas the sender paid
+//! added back to SUI. A routed swap emits events of the aggregator and of the
+//! DEXes it went through; it is credited to the aggregator alone, or split
+//! across the DEXes without one. Amounts are counted raw per coin type and
+//! only valued when the stats are computed, through a `PriceSource`; coins it
+//! has no price for are left out of the volume.
+
+use crate::event_stats::ActivityCounter;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+use sui_types::gas_coin::GAS;
+
+/// Registry categories whose DApps get swap volume
+pub const SWAP_DAPP_TYPES: [&str; 2] = ["DEX", AGGREGATOR_DAPP_TYPE];
+
+/// Registry category of the DApps routing swaps through DEXes
+pub const AGGREGATOR_DAPP_TYPE: &str = "Aggregator";
+
+/// Swap events of the tracked DEXes and aggregators, as (DApp, `module::struct`)
+/// Their other events (fee updates, pauses, config changes) are not swaps, even
+/// when named after them; a DApp missing from this list gets no volume
+pub const SWAP_EVENTS: [(&str, &str); 7] = [
+    ("Cetus AMM", "pool::SwapEvent"),
+    ("Turbos", "pool::SwapEvent"),
+    ("Momentum", "trade::SwapEvent"),
+    ("Aftermath AMM", "events::SwapEvent"),
+    ("Aftermath AMM", "events::SwapEventV2"),
+    ("Cetus Aggregator", "router::SwapEvent"),
+    ("7K Aggregator", "settle::SwapEvent"),
+];
+
+/// True for the swap events of `dapp_name`, given as `module::struct`
+pub fn is_swap_event(dapp_name: &str, event_type: &str) -> bool {
+    SWAP_EVENTS.iter().any(|(swap_dapp, swap_event)| *swap_dapp == dapp_name && *swap_event == event_type)
+}
+
+/// Canonical form of a coin type, so `0x2::sui::SUI` and its long address form match
+pub fn normalize_coin_type(coin_type: &str) -> Option<String> {
+    sui_types::parse_sui_type_tag(coin_type).ok().map(|type_tag| type_tag.to_string())
+}
+
+/// Canonical coin type of SUI, the coin gas is paid in
+pub fn sui_coin_type() -> String {
+    GAS::type_tag().to_string()
+}
+
+/**
+ * SwapLeg is a coin spent by a sender in a swap through a tracked DEX or aggregator
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwapLeg {
+    pub dapp_name: String,              // DEX or aggregator the swap went through
+    pub coin_type: String,              // Canonical type of the spent coin
+    pub amount: u64,                    // Spent amount in the coin's base units
+    pub timestamp: DateTime<Utc>,       // When the transaction occurred
+}
+
+/// Coins spent in a transaction given the sender's balance changes per coin type
+/// (negative when the balance dropped), sorted by coin type
+/// Coin types whose balance did not drop were received or untouched
+pub fn spent_amounts(changes: impl IntoIterator<Item = (String, i128)>) -> Vec<(String, u64)> {
+    net_changes(changes)
+        .into_iter()
+        .filter(|(_, change)| *change < 0)
+        .map(|(coin_type, change)| (coin_type, u64::try_from(-change).unwrap_or(u64::MAX)))
+        .collect()
+}
+
+/// Coins received in a transaction given balance changes per coin type, the
+/// counterpart of `spent_amounts`, sorted by coin type
+pub fn received_amounts(changes: impl IntoIterator<Item = (String, i128)>) -> Vec<(String, u64)> {
+    net_changes(changes)
+        .into_iter()
+        .filter(|(_, change)| *change > 0)
+        .map(|(coin_type, change)| (coin_type, u64::try_from(change).unwrap_or(u64::MAX)))
+        .collect()
+}
+
+/// Balance changes summed per coin type
+fn net_changes(changes: impl IntoIterator<Item = (String, i128)>) -> BTreeMap<String, i128> {
+    let mut balances: BTreeMap<String, i128> = BTreeMap::new();
+    for (coin_type, change) in changes {
+        *balances.entry(coin_type).or_default() += change;
+    }
+    balances
+}
+
+/// Legs crediting the coins `spent` in one transaction to the DApps that emitted
+/// its swap events, given as DApp name -> category
+/// A routed swap emits the aggregator's event and those of the DEXes it went
+/// through, so the spend goes to the aggregator when there is one; otherwise it
+/// is split evenly across the DEXes. Either way it is only counted once
+pub fn attribute_swap(swapping: &BTreeMap<&str, &str>, spent: &[(String, u64)], timestamp: DateTime<Utc>) -> Vec<SwapLeg> {
+    let aggregators: Vec<&str> = swapping
+        .iter()
+        .filter(|(_, dapp_type)| **dapp_type == AGGREGATOR_DAPP_TYPE)
+        .map(|(dapp_name, _)| *dapp_name)
+        .collect();
+    let credited: Vec<&str> = if aggregators.is_empty() { swapping.keys().copied().collect() } else { aggregators };
+    let shares = credited.len() as u64;
+    if shares == 0 {
+        return Vec::new();
+    }
+
+    spent
+        .iter()
+        .flat_map(|(coin_type, amount)| {
+            // The first DApps get the remainder, so the shares add up to the amount
+            credited.iter().enumerate().map(move |(index, dapp_name)| SwapLeg {
+                dapp_name: dapp_name.to_string(),
+                coin_type: coin_type.clone(),
+                amount: amount / shares + u64::from((index as u64) < amount % shares),
+                timestamp,
+            })
+        })
+        .filter(|leg| leg.amount > 0)
+        .collect()
+}
+
+/// Values coins in USD
+pub trait PriceSource: Send + Sync {
+    /// USD value of `amount` base units of the canonical `coin_type`, None if it has no price
+    fn usd_value(&self, coin_type: &str, amount: u64) -> Option<f64>;
+}
+
+/// Fixed USD prices, configured as `coin_type=price:decimals` pairs
+/// There is no price feed: the prices never change while the indexer runs, so
+/// volumes drift from market value as prices move until `COIN_PRICES_USD` is updated
+#[derive(Debug, Clone, Default)]
+pub struct StaticPrices {
+    /// Canonical coin type -> (USD price of one coin, decimals)
+    prices: HashMap<String, (f64, u8)>,
+}
+
+impl StaticPrices {
+    /// Parse `coin_type=price:decimals` pairs separated by commas,
+    /// e.g. `0x2::sui::SUI=3.5:9`
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut prices = HashMap::new();
+        for pair in spec.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+            let (coin_type, value) = pair
+                .split_once('=')
+                .ok_or_else(|| anyhow!("'{}' is not a coin_type=price:decimals pair", pair))?;
+            let (price, decimals) = value
+                .split_once(':')
+                .ok_or_else(|| anyhow!("'{}' has no decimals, expected price:decimals", value))?;
+            let coin_type = normalize_coin_type(coin_type.trim())
+                .ok_or_else(|| anyhow!("'{}' is not a coin type", coin_type.trim()))?;
+            let price: f64 = price.trim().parse().with_context(|| format!("invalid price '{}'", price))?;
+            let decimals: u8 = decimals.trim().parse().with_context(|| format!("invalid decimals '{}'", decimals))?;
+            if !price.is_finite() || price < 0.0 {
+                return Err(anyhow!("price of {} must be a non-negative number", coin_type));
+            }
+            if prices.insert(coin_type.clone(), (price, decimals)).is_some() {
+                return Err(anyhow!("{} is priced twice", coin_type));
+            }
+        }
+        Ok(Self { prices })
+    }
+
+    /// Number of priced coin types
+    pub fn len(&self) -> usize {
+        self.prices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prices.is_empty()
+    }
+}
+
+impl PriceSource for StaticPrices {
+    fn usd_value(&self, coin_type: &str, amount: u64) -> Option<f64> {
+        let (price, decimals) = self.prices.get(coin_type)?;
+        Some(amount as f64 / 10f64.powi(*decimals as i32) * price)
+    }
+}
+
+/// Rolling 24h spent amounts per (DApp, coin type) of swaps
+#[derive(Debug, Clone, Default)]
+pub struct SwapVolumeCounter {
+    /// Hourly raw amounts, labelled by coin type
+    amounts: ActivityCounter,
+}
+
+impl SwapVolumeCounter {
+    pub fn record(&mut self, legs: &[SwapLeg]) {
+        for leg in legs {
+            self.amounts.add_amount(&leg.dapp_name, &leg.coin_type, leg.timestamp, leg.amount);
+        }
+    }
+
+    /// Forget swaps older than 24 hours
+    pub fn prune(&mut self, now: DateTime<Utc>) {
+        self.amounts.prune(now);
+    }
+
+    /// USD volume of every DApp with swaps in the last 24 hours, valued by `prices`
+    pub fn volumes(&self, prices: &dyn PriceSource, now: DateTime<Utc>) -> HashMap<String, f64> {
+        let mut volumes: HashMap<String, f64> = HashMap::new();
+        for (dapp_name, coin_type, amount) in self.amounts.totals(now) {
+            let value = prices.usd_value(&coin_type, amount).unwrap_or(0.0);
+            *volumes.entry(dapp_name).or_default() += value;
+        }
+        volumes
+    }
+
+    pub fn clear(&mut self) {
+        self.amounts.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const USDC: &str = "0xdba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7::usdc::USDC";
+
+    #[test]
+    fn test_spent_amounts_are_the_balance_drops() {
+        let spent = spent_amounts([
+            // SUI coin split and partly swapped, the rest returned to the sender
+            ("SUI".to_string(), -5_000),
+            ("SUI".to_string(), 1_000),
+            // USDC received from the swap
+            ("USDC".to_string(), 12),
+            // Coin merged back into an unchanged balance
+            ("CETUS".to_string(), -7),
+            ("CETUS".to_string(), 7),
+        ]);
+        assert_eq!(spent, [("SUI".to_string(), 4_000)]);
+    }
+
+    #[test]
+    fn test_received_amounts_are_the_balance_rises() {
+        let received = received_amounts([
+            // Wrapped coins minted to the recipient, part of them paid to a relayer
+            ("WETH".to_string(), 49_000),
+            ("WETH".to_string(), 1_000),
+            // Gas paid, added back by the caller
+            ("SUI".to_string(), -300),
+            ("SUI".to_string(), 300),
+        ]);
+        assert_eq!(received, [("WETH".to_string(), 50_000)]);
+    }
+
+    #[test]
+    fn test_only_listed_swap_events_are_swaps() {
+        assert!(is_swap_event("Cetus AMM", "pool::SwapEvent"));
+        assert!(is_swap_event("Aftermath AMM", "events::SwapEventV2"));
+        // Named after swaps without being one
+        assert!(!is_swap_event("Cetus AMM", "pool::SwapFeeUpdated"));
+        assert!(!is_swap_event("Cetus AMM", "pool::SwapPaused"));
+        assert!(!is_swap_event("Turbos", "config::PoolSwapConfigChanged"));
+        // Another DApp's swap event, or a DApp without listed events
+        assert!(!is_swap_event("Momentum", "pool::SwapEvent"));
+        assert!(!is_swap_event("Pyth", "pool::SwapEvent"));
+    }
+
+    #[test]
+    fn test_routed_swap_is_credited_once() {
+        let spent = [("SUI".to_string(), 1_000), ("USDC".to_string(), 5)];
+        let credited = |swapping: &[(&str, &str)]| -> Vec<(String, String, u64)> {
+            attribute_swap(&swapping.iter().copied().collect(), &spent, DateTime::UNIX_EPOCH)
+                .into_iter()
+                .map(|leg| (leg.dapp_name, leg.coin_type, leg.amount))
+                .collect()
+        };
+        let leg = |dapp_name: &str, coin_type: &str, amount| (dapp_name.to_string(), coin_type.to_string(), amount);
+
+        // An aggregator routing through a DEX gets the whole spend, the DEX nothing
+        assert_eq!(
+            credited(&[("7K Aggregator", "Aggregator"), ("Cetus AMM", "DEX")]),
+            [leg("7K Aggregator", "SUI", 1_000), leg("7K Aggregator", "USDC", 5)]
+        );
+        // A swap split over two DEXes without an aggregator is shared between them
+        assert_eq!(
+            credited(&[("Cetus AMM", "DEX"), ("Turbos", "DEX")]),
+            [
+                leg("Cetus AMM", "SUI", 500),
+                leg("Turbos", "SUI", 500),
+                leg("Cetus AMM", "USDC", 3),
+                leg("Turbos", "USDC", 2),
+            ]
+        );
+        assert_eq!(credited(&[("Cetus AMM", "DEX")]), [leg("Cetus AMM", "SUI", 1_000), leg("Cetus AMM", "USDC", 5)]);
+        assert!(credited(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_parses_and_applies_static_prices() {
+        let prices = StaticPrices::parse(&format!("0x2::sui::SUI=3.5:9, {}=1:6", USDC)).unwrap();
+        assert_eq!(prices.len(), 2);
+        // Coin types are matched in their canonical form
+        let sui = normalize_coin_type("0x0000000000000000000000000000000000000000000000000000000000000002::sui::SUI").unwrap();
+        assert_eq!(sui, sui_coin_type());
+        assert_eq!(prices.usd_value(&sui, 2_000_000_000), Some(7.0));
+        assert_eq!(prices.usd_value(&normalize_coin_type(USDC).unwrap(), 2_500_000), Some(2.5));
+        assert_eq!(prices.usd_value("0x3::unknown::COIN", 1), None);
+
+        assert!(StaticPrices::parse("").unwrap().is_empty());
+        assert!(StaticPrices::parse("0x2::sui::SUI=3.5").is_err());
+        assert!(StaticPrices::parse("0x2::sui::SUI=cheap:9").is_err());
+        assert!(StaticPrices::parse("not a type=1:9").is_err());
+        assert!(StaticPrices::parse("0x2::sui::SUI=1:9,0x2::sui::SUI=2:9").is_err());
+    }
+
+    #[test]
+    fn test_values_swaps_of_the_last_24h() {
+        let now = DateTime::UNIX_EPOCH + Duration::from_secs(100 * 3600);
+        let sui = sui_coin_type();
+        let usdc = normalize_coin_type(USDC).unwrap();
+        let leg = |coin_type: &str, amount, hours_ago: u64| SwapLeg {
+            dapp_name: "Cetus AMM".to_string(),
+            coin_type: coin_type.to_string(),
+            amount,
+            timestamp: now - Duration::from_secs(hours_ago * 3600),
+        };
+        let mut counter = SwapVolumeCounter::default();
+        counter.record(&[
+            leg(&sui, 1_000_000_000, 1),
+            leg(&sui, 1_000_000_000, 2),
+            leg(&usdc, 3_000_000, 3),
+            leg(&sui, 9_000_000_000, 30),
+            // Unpriced coins count for nothing
+            leg("0x3::unknown::COIN", 1_000, 1),
+        ]);
+        counter.prune(now);
+
+        let prices = StaticPrices::parse(&format!("0x2::sui::SUI=3.5:9,{}=1:6", USDC)).unwrap();
+        let volumes = counter.volumes(&prices, now);
+        assert_eq!(volumes.len(), 1);
+        assert!((volumes["Cetus AMM"] - 10.0).abs() < 1e-9);
+    }
+}
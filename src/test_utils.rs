@@ -0,0 +1,483 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Checkpoint fixtures for tests (requires the `test-utils` feature outside this crate).
+//!
+//! Builds `CheckpointData` with chosen senders, DApp events and timestamps, so
+//! extraction, windowing and ranking can be tested without real mainnet
+//! checkpoints:
+//!
+//! ```ignore
+//! let checkpoint = CheckpointBuilder::new(10)
+//!     .minutes_ago(5)
+//!     .transaction(1, &[CETUS_PACKAGE])
+//!     .transaction(2, &[CETUS_PACKAGE, PYTH_PACKAGE])
+//!     .build();
+//! ```
+//!
+//! Counters and filters taking interactions directly use `InteractionBuilder`.
+
+use crate::models::DAppInteraction;
+use chrono::{DateTime, Utc};
+use std::str::FromStr;
+use std::time::Duration;
+use sui_types::base_types::{ObjectID, SuiAddress};
+use sui_types::event::Event;
+use sui_types::full_checkpoint_content::CheckpointData;
+use sui_types::identifier::Identifier;
+use sui_types::test_checkpoint_data_builder::TestCheckpointDataBuilder;
+use sui_types::type_tag::StructTag;
+
+/// Address of the fixture sender with the given index
+pub fn sender_address(sender_idx: u8) -> SuiAddress {
+    TestCheckpointDataBuilder::derive_address(sender_idx)
+}
+
+/// Address of the fixture user with the given index, for fixtures with more than 256 senders
+pub fn user_address(user_idx: u64) -> SuiAddress {
+    TestCheckpointDataBuilder::derive_object_id(user_idx).into()
+}
+
+/// An event emitted by the package `package_id` on behalf of `sender`
+///
+/// # Panics
+/// If `package_id` is not a valid object ID
+pub fn dapp_event(package_id: &str, sender: SuiAddress) -> Event {
+    let package_id = ObjectID::from_str(package_id).expect("package_id must be a valid object ID");
+    Event {
+        package_id,
+        transaction_module: Identifier::new("fixture").unwrap(),
+        sender,
+        type_: StructTag {
+            address: package_id.into(),
+            module: Identifier::new("fixture").unwrap(),
+            name: Identifier::new("FixtureEvent").unwrap(),
+            type_params: vec![],
+        },
+        contents: vec![],
+    }
+}
+
+/// Builder of a single checkpoint fixture
+pub struct CheckpointBuilder {
+    builder: TestCheckpointDataBuilder,
+}
+
+impl CheckpointBuilder {
+    /// Checkpoint `sequence_number` with the current time as timestamp
+    pub fn new(sequence_number: u64) -> Self {
+        Self { builder: TestCheckpointDataBuilder::new(sequence_number) }.at(Utc::now())
+    }
+
+    /// Set the checkpoint timestamp
+    pub fn at(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.builder = self.builder.with_timestamp_ms(timestamp.timestamp_millis().max(0) as u64);
+        self
+    }
+
+    /// Set the checkpoint timestamp to `minutes` before now
+    pub fn minutes_ago(self, minutes: u64) -> Self {
+        self.at(Utc::now() - Duration::from_secs(minutes * 60))
+    }
+
+    /// Set the epoch the checkpoint belongs to
+    pub fn epoch(mut self, epoch: u64) -> Self {
+        self.builder = self.builder.with_epoch(epoch);
+        self
+    }
+
+    /// Add a transaction from sender `sender_idx` emitting one event per package in `package_ids`
+    pub fn transaction(self, sender_idx: u8, package_ids: &[&str]) -> Self {
+        let sender = sender_address(sender_idx);
+        let events = package_ids.iter().map(|package_id| dapp_event(package_id, sender)).collect();
+        self.transaction_with_events(sender_idx, events)
+    }
+
+    /// Add a transaction from sender `sender_idx` emitting exactly `events`
+    pub fn transaction_with_events(mut self, sender_idx: u8, events: Vec<Event>) -> Self {
+        self.builder = self
+            .builder
+            .start_transaction(sender_idx)
+            .with_events(events)
+            .finish_transaction();
+        self
+    }
+
+    /// Add a transaction from sender `sender_idx` calling `module::function` of `package_id`
+    ///
+    /// # Panics
+    /// If `package_id` is not a valid object ID
+    pub fn move_call(mut self, sender_idx: u8, package_id: &str, module: &str, function: &str) -> Self {
+        let package_id = ObjectID::from_str(package_id).expect("package_id must be a valid object ID");
+        self.builder = self
+            .builder
+            .start_transaction(sender_idx)
+            .add_move_call(package_id, module, function)
+            .finish_transaction();
+        self
+    }
+
+    pub fn build(mut self) -> CheckpointData {
+        self.builder.build_checkpoint()
+    }
+}
+
+/// Builder of a single `DAppInteraction` fixture, for tests of the counters and filters
+/// that take interactions rather than checkpoints
+///
+/// Starts as a `pool::SwapEvent` of sender `0xa` on "Cetus AMM" (package `0x1`)
+/// in transaction `tx` at the Unix epoch, with no gas and no sponsor
+pub struct InteractionBuilder {
+    interaction: DAppInteraction,
+}
+
+impl Default for InteractionBuilder {
+    fn default() -> Self {
+        Self {
+            interaction: DAppInteraction {
+                package_id: "0x1".to_string(),
+                sender: "0xa".to_string(),
+                timestamp: DateTime::UNIX_EPOCH,
+                transaction_digest: "tx".to_string(),
+                dapp_name: Some("Cetus AMM".to_string()),
+                gas_used: 0,
+                event_type: "pool::SwapEvent".to_string(),
+                sponsored: false,
+            },
+        }
+    }
+}
+
+impl InteractionBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn dapp(mut self, dapp_name: &str) -> Self {
+        self.interaction.dapp_name = Some(dapp_name.to_string());
+        self
+    }
+
+    /// Set the emitting package and leave its DApp to be looked up in the registry,
+    /// as for rankings computed from a registry
+    pub fn package(mut self, package_id: &str) -> Self {
+        self.interaction.package_id = package_id.to_string();
+        self.interaction.dapp_name = None;
+        self
+    }
+
+    pub fn sender(mut self, sender: &str) -> Self {
+        self.interaction.sender = sender.to_string();
+        self
+    }
+
+    /// Set the emitted event type, as module::struct
+    pub fn event_type(mut self, event_type: &str) -> Self {
+        self.interaction.event_type = event_type.to_string();
+        self
+    }
+
+    pub fn digest(mut self, transaction_digest: &str) -> Self {
+        self.interaction.transaction_digest = transaction_digest.to_string();
+        self
+    }
+
+    pub fn gas_used(mut self, gas_used: u64) -> Self {
+        self.interaction.gas_used = gas_used;
+        self
+    }
+
+    pub fn at(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.interaction.timestamp = timestamp;
+        self
+    }
+
+    /// Set the timestamp to `seconds` after the Unix epoch
+    pub fn seconds(self, seconds: u64) -> Self {
+        self.at(DateTime::UNIX_EPOCH + Duration::from_secs(seconds))
+    }
+
+    /// Set the timestamp to `hours` before `now`
+    pub fn hours_before(self, now: DateTime<Utc>, hours: u64) -> Self {
+        self.at(now - Duration::from_secs(hours * 3600))
+    }
+
+    pub fn build(self) -> DAppInteraction {
+        self.interaction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{Clock, MockClock};
+    use crate::dapp_indexer::{DAppIndexer, InteractionDedup};
+    use crate::models::{DAppPublishedPackage, DAppRegistryEntry};
+    use crate::swap_volume::{sui_coin_type, StaticPrices, SwapLeg};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    const CETUS: &str = "0x6f5e582ede61fe5395b50c4a449ec11479a54d7ff8e0158247adfda60d98970b";
+    const CETUS_V2: &str = "0x3864c7c59a4889fec05d1aae4bc9dba5a0e0940594b424fbed44cb3f6ac4c032";
+    const PYTH: &str = "0x04e20ddf36af412a4096f9014f4a565af9e812db9a05cc40254846cf6ed0ad91";
+    const UNTRACKED: &str = "0x0000000000000000000000000000000000000000000000000000000000000abc";
+
+    #[test]
+    fn test_extracts_only_tracked_packages() {
+        let checkpoint = CheckpointBuilder::new(1)
+            .transaction(1, &[CETUS, UNTRACKED])
+            .transaction(2, &[UNTRACKED])
+            .build();
+
+        let extracted = DAppIndexer::new().extractor().extract(&checkpoint);
+        assert_eq!(extracted.checkpoint_number, 1);
+        assert_eq!(extracted.interactions.len(), 1);
+        let interaction = &extracted.interactions[0];
+        assert_eq!(interaction.package_id, CETUS);
+        assert_eq!(interaction.sender, sender_address(1).to_string());
+        assert_eq!(interaction.dapp_name.as_deref(), Some("Cetus AMM"));
+    }
+
+    #[tokio::test]
+    async fn test_checkpoints_outside_window_are_ignored() {
+        let mut indexer = DAppIndexer::new();
+        let stale = CheckpointBuilder::new(1).minutes_ago(25 * 60).transaction(1, &[CETUS]).build();
+        let earlier = CheckpointBuilder::new(2).minutes_ago(90).transaction(2, &[CETUS]).build();
+        let recent = CheckpointBuilder::new(3).minutes_ago(30).transaction(3, &[CETUS]).build();
+
+        assert!(indexer.process_checkpoint(&stale, None).await.is_empty());
+        // Older than the ranking window, but still counted in the 24h statistics
+        assert_eq!(indexer.process_checkpoint(&earlier, None).await.len(), 1);
+        assert_eq!(indexer.process_checkpoint(&recent, None).await.len(), 1);
+        assert_eq!(indexer.get_dapp_interactions().len(), 1);
+        indexer.update_dapp_rankings();
+        let events: u64 = indexer.event_stats.iter().map(|stats| stats.count_24h).sum();
+        assert_eq!(events, 2);
+    }
+
+    #[tokio::test]
+    async fn test_window_edge_is_exact_to_the_millisecond() {
+        let start = DateTime::UNIX_EPOCH + Duration::from_secs(1_750_000_000);
+        let clock = MockClock::new(start);
+        let mut indexer = DAppIndexer::builder().clock(Arc::new(clock.clone())).build();
+        let event_time = start - Duration::from_secs(60 * 60) + Duration::from_millis(250);
+        let checkpoint = CheckpointBuilder::new(1).at(event_time).transaction(1, &[CETUS]).build();
+
+        let interactions = indexer.process_checkpoint(&checkpoint, None).await;
+        assert_eq!(interactions[0].timestamp, event_time);
+        indexer.update_dapp_rankings();
+        assert_eq!(indexer.get_dapp_rankings()[0].active_users, 1);
+
+        clock.advance(Duration::from_millis(500));
+        indexer.prune_old_interactions();
+        assert!(indexer.get_dapp_interactions().is_empty());
+    }
+
+    #[test]
+    fn test_builder_customizes_registry_window_and_dedup() {
+        let indexer = DAppIndexer::builder()
+            .registry(HashMap::from([(UNTRACKED.to_string(), DAppRegistryEntry::new("Bluefin", "DEX"))]))
+            .ranking_window(Duration::from_secs(2 * 60 * 60))
+            .interaction_dedup(InteractionDedup::PerTransaction)
+            .build();
+        let checkpoint = CheckpointBuilder::new(1)
+            .minutes_ago(90)
+            .transaction(1, &[UNTRACKED, UNTRACKED, CETUS])
+            .build();
+
+        let interactions = indexer.extractor().extract(&checkpoint).interactions;
+        assert_eq!(interactions.len(), 1);
+        assert_eq!(interactions[0].dapp_name.as_deref(), Some("Bluefin"));
+    }
+
+    #[tokio::test]
+    async fn test_ranking_profiles_share_ingestion_with_their_own_window() {
+        let mut indexer = DAppIndexer::builder().ranking_profile("dau_24h", Duration::from_secs(24 * 60 * 60)).build();
+        let checkpoints = [
+            CheckpointBuilder::new(9).minutes_ago(180).transaction(1, &[PYTH]).transaction(2, &[PYTH]).build(),
+            CheckpointBuilder::new(10).minutes_ago(5).transaction(3, &[CETUS]).build(),
+        ];
+        for checkpoint in &checkpoints {
+            indexer.process_checkpoint(checkpoint, None).await;
+        }
+
+        // The 3h old checkpoint only counts toward the 24h profile
+        let live: Vec<(&str, u32)> =
+            indexer.get_dapp_rankings().iter().map(|ranking| (ranking.dapp_name.as_str(), ranking.active_users)).collect();
+        assert_eq!(live, [("Cetus AMM", 1)]);
+        let profile = &indexer.ranking_profiles[0];
+        let daily: Vec<(&str, u32)> =
+            profile.rankings.iter().map(|ranking| (ranking.dapp_name.as_str(), ranking.active_users)).collect();
+        assert_eq!(daily, [("Pyth", 2), ("Cetus AMM", 1)]);
+        assert_eq!(profile.interactions.len(), 3);
+        assert_eq!(indexer.get_dapp_interactions().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_interactions_expire_as_the_clock_advances() {
+        let start = Utc::now();
+        let clock = MockClock::new(start);
+        let mut indexer = DAppIndexer::builder().clock(Arc::new(clock.clone())).build();
+        let checkpoint = CheckpointBuilder::new(10).at(start).transaction(1, &[CETUS]).build();
+        indexer.process_checkpoint(&checkpoint, None).await;
+        assert_eq!(indexer.get_dapp_rankings()[0].active_users, 1);
+        assert_eq!(indexer.last_ranking_update, Some(start));
+
+        clock.advance(Duration::from_secs(61 * 60));
+        indexer.prune_old_interactions();
+        indexer.update_dapp_rankings();
+        assert!(indexer.get_dapp_interactions().is_empty());
+        assert!(indexer.get_dapp_rankings().is_empty());
+        assert_eq!(indexer.last_ranking_update, Some(clock.now()));
+        assert!(!indexer.extractor().extract(&checkpoint).interactions.is_empty());
+
+        clock.advance(Duration::from_secs(24 * 60 * 60));
+        assert!(indexer.extractor().extract(&checkpoint).interactions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rankings_order_dapps_by_hourly_active_users() {
+        let mut indexer = DAppIndexer::new();
+        let checkpoints = [
+            CheckpointBuilder::new(9).minutes_ago(10).transaction(1, &[PYTH]).transaction(2, &[CETUS]).build(),
+            // Repeat interactions of a sender count once
+            CheckpointBuilder::new(10).transaction(2, &[CETUS]).transaction(3, &[CETUS]).build(),
+        ];
+        for checkpoint in &checkpoints {
+            indexer.process_checkpoint(checkpoint, None).await;
+        }
+
+        // Rankings are recomputed every 10th checkpoint by default
+        let rankings: Vec<(String, u32)> = indexer
+            .get_dapp_rankings()
+            .iter()
+            .map(|ranking| (ranking.dapp_name.clone(), ranking.active_users))
+            .collect();
+        assert_eq!(rankings, [("Cetus AMM".to_string(), 2), ("Pyth".to_string(), 1)]);
+        assert_eq!(indexer.get_dapp_rankings()[0].rank, 1);
+    }
+
+    #[tokio::test]
+    async fn test_process_stream_applies_every_checkpoint() {
+        let mut indexer = DAppIndexer::new();
+        let checkpoints = futures_util::stream::iter((1..=10).map(|sequence_number| {
+            CheckpointBuilder::new(sequence_number).transaction(sequence_number as u8, &[CETUS]).build()
+        }));
+
+        assert_eq!(indexer.process_stream(checkpoints, None).await, 10);
+        assert_eq!(indexer.last_processed_checkpoint, 10);
+        assert_eq!(indexer.get_dapp_rankings()[0].active_users, 10);
+    }
+
+    #[test]
+    fn test_parallel_extraction_keeps_transaction_order() {
+        let checkpoint = (1..=100)
+            .fold(CheckpointBuilder::new(1), |builder, sender| builder.transaction(sender, &[CETUS]))
+            .build();
+
+        let extracted = DAppIndexer::new().extractor().extract(&checkpoint);
+        let senders: Vec<String> = extracted.interactions.iter().map(|interaction| interaction.sender.clone()).collect();
+        let expected: Vec<String> = (1..=100).map(|sender| sender_address(sender).to_string()).collect();
+        assert_eq!(senders, expected);
+    }
+
+    #[tokio::test]
+    async fn test_package_stats_split_multi_package_dapps() {
+        let mut indexer = DAppIndexer::new();
+        let checkpoint = CheckpointBuilder::new(10)
+            .transaction(1, &[CETUS, CETUS_V2])
+            .transaction(2, &[CETUS_V2])
+            .build();
+        indexer.process_checkpoint(&checkpoint, None).await;
+
+        // The leaderboard counts sender 1 once, per-package stats count it for both packages
+        assert_eq!(indexer.get_dapp_rankings()[0].active_users, 2);
+        let packages: Vec<(&str, u32)> = indexer
+            .get_package_stats()
+            .iter()
+            .map(|stats| (stats.package_id.as_str(), stats.active_users))
+            .collect();
+        assert_eq!(packages, [(CETUS_V2, 2), (CETUS, 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_move_calls_rank_functions_per_dapp() {
+        let mut indexer = DAppIndexer::new();
+        let checkpoint = CheckpointBuilder::new(10)
+            .move_call(1, CETUS, "pool_script", "swap_a2b")
+            .move_call(2, CETUS_V2, "pool_script", "swap_a2b")
+            .move_call(3, CETUS, "pool_script", "open_position")
+            .move_call(4, UNTRACKED, "pool_script", "swap_a2b")
+            .build();
+
+        let extracted = indexer.extractor().extract(&checkpoint);
+        assert_eq!(extracted.function_calls.len(), 3);
+        indexer.apply_checkpoint(extracted, None).await;
+
+        let functions: Vec<(&str, u64)> = indexer
+            .get_function_stats()
+            .iter()
+            .map(|stats| (stats.function.as_str(), stats.call_count_24h))
+            .collect();
+        assert_eq!(functions, [("pool_script::swap_a2b", 2), ("pool_script::open_position", 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_published_packages_reach_running_extractors() {
+        let mut indexer = DAppIndexer::new();
+        let extractor = indexer.extractor();
+        let checkpoint = CheckpointBuilder::new(1).transaction(1, &[UNTRACKED]).build();
+        assert!(extractor.extract(&checkpoint).interactions.is_empty());
+
+        let package = DAppPublishedPackage {
+            package_id: UNTRACKED.to_string(),
+            dapp_name: "Cetus AMM".to_string(),
+            publisher: sender_address(9).to_string(),
+            discovered_at: chrono::Utc::now().naive_utc(),
+        };
+        assert_eq!(indexer.register_published_packages(&[package]), 1);
+
+        let interactions = extractor.extract(&checkpoint).interactions;
+        assert_eq!(interactions.len(), 1);
+        assert_eq!(interactions[0].dapp_name.as_deref(), Some("Cetus AMM"));
+    }
+
+    #[test]
+    fn test_registry_changes_reach_running_extractors() {
+        let mut indexer = DAppIndexer::new();
+        let extractor = indexer.extractor();
+        let checkpoint = CheckpointBuilder::new(1).transaction(1, &[UNTRACKED, PYTH]).build();
+
+        indexer.upsert_registry_entry(UNTRACKED, "Bluefin", "DEX");
+        assert!(indexer.remove_registry_entry(PYTH).is_some());
+        assert!(indexer.remove_registry_entry(PYTH).is_none());
+
+        let interactions = extractor.extract(&checkpoint).interactions;
+        assert_eq!(interactions.len(), 1);
+        assert_eq!(interactions[0].dapp_name.as_deref(), Some("Bluefin"));
+        assert_eq!(indexer.search_dapps("bluef", 5).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_only_swap_dapps_carry_the_priced_swap_volume() {
+        let mut indexer = DAppIndexer::new();
+        indexer.set_price_source(Arc::new(StaticPrices::parse("0x2::sui::SUI=3.5:9").unwrap()));
+        let checkpoint = CheckpointBuilder::new(1).minutes_ago(5).transaction(1, &[CETUS, PYTH]).build();
+        indexer.process_checkpoint(&checkpoint, None).await;
+        let leg = |dapp_name: &str| SwapLeg {
+            dapp_name: dapp_name.to_string(),
+            coin_type: sui_coin_type(),
+            amount: 2_000_000_000,
+            timestamp: Utc::now() - Duration::from_secs(5 * 60),
+        };
+        indexer.swap_volume_counts.record(&[leg("Cetus AMM"), leg("Pyth")]);
+        indexer.update_dapp_rankings();
+
+        let volumes: HashMap<&str, f64> = indexer
+            .get_dapp_rankings()
+            .iter()
+            .map(|ranking| (ranking.dapp_name.as_str(), ranking.volume_24h_usd))
+            .collect();
+        assert_eq!(volumes, HashMap::from([("Cetus AMM", 7.0), ("Pyth", 0.0)]));
+    }
+}
@@ -0,0 +1,186 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Daily unique-user sketches.
+//!
+//! The distinct users of a DApp on a day (in the stats timezone) are kept as a
+//! HyperLogLog sketch in `daily_user_sketches`, next to the raw sender sets of
+//! `dapp_user_activity`. A sketch takes at most 16 KiB whatever the number of
+//! users and holds no address, and sketches are merged by taking the maximum of
+//! each register: the union of any days is estimated by merging their sketches,
+//! with a standard error of about 0.8%, without storing or scanning addresses.
+//!
+//! Senders are hashed with SHA-256, so sketches built by different processes
+//! and versions merge consistently. Merging is idempotent, so a day's sketch is
+//! updated by merging the senders of every flush into the stored one.
+
+use crate::models::NewDAppUserActivity;
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+/// Bits of the hash selecting a register
+pub const PRECISION: u8 = 14;
+
+/// Version of the serialized format
+const FORMAT_VERSION: u8 = 1;
+
+/// Registers stored as one byte each, in order
+const DENSE: u8 = 0;
+
+/// Non-zero registers stored as (index as u16 LE, value) pairs
+const SPARSE: u8 = 1;
+
+/// HyperLogLog sketch of a set of senders
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserSketch {
+    registers: Vec<u8>,
+}
+
+impl Default for UserSketch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UserSketch {
+    pub fn new() -> Self {
+        Self { registers: vec![0; 1 << PRECISION] }
+    }
+
+    pub fn insert(&mut self, sender: &str) {
+        let digest = Sha256::digest(sender.as_bytes());
+        let hash = u64::from_be_bytes(digest[..8].try_into().expect("SHA-256 digests are 32 bytes"));
+        let index = (hash >> (64 - PRECISION)) as usize;
+        // Position of the first 1 bit after the index bits, bounded by a sentinel bit
+        let rank = ((hash << PRECISION) | (1 << (PRECISION - 1))).leading_zeros() as u8 + 1;
+        let register = &mut self.registers[index];
+        *register = (*register).max(rank);
+    }
+
+    /// Add the senders of `other` to this sketch
+    pub fn merge(&mut self, other: &UserSketch) {
+        for (register, other) in self.registers.iter_mut().zip(&other.registers) {
+            *register = (*register).max(*other);
+        }
+    }
+
+    /// Estimated number of distinct senders
+    pub fn estimate(&self) -> u64 {
+        let m = self.registers.len() as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self.registers.iter().map(|&register| 2f64.powi(-(register as i32))).sum();
+        let estimate = alpha * m * m / sum;
+        let zeros = self.registers.iter().filter(|&&register| register == 0).count();
+        // Linear counting is more accurate on small sets
+        if estimate <= 2.5 * m && zeros > 0 {
+            return (m * (m / zeros as f64).ln()).round() as u64;
+        }
+        estimate.round() as u64
+    }
+
+    /// Serialize as a version, the precision and the registers, sparse when that is smaller
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let set: Vec<(usize, u8)> = self
+            .registers
+            .iter()
+            .enumerate()
+            .filter(|(_, &register)| register > 0)
+            .map(|(index, &register)| (index, register))
+            .collect();
+        let mut bytes = vec![FORMAT_VERSION, PRECISION];
+        if set.len() * 3 < self.registers.len() {
+            bytes.push(SPARSE);
+            for (index, register) in set {
+                bytes.extend_from_slice(&(index as u16).to_le_bytes());
+                bytes.push(register);
+            }
+        } else {
+            bytes.push(DENSE);
+            bytes.extend_from_slice(&self.registers);
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let [version, precision, encoding, body @ ..] = bytes else {
+            return Err(anyhow!("User sketch of {} bytes is truncated", bytes.len()));
+        };
+        if *version != FORMAT_VERSION {
+            return Err(anyhow!("Unknown user sketch format version {}", version));
+        }
+        if *precision != PRECISION {
+            return Err(anyhow!("User sketch has precision {}, expected {}", precision, PRECISION));
+        }
+        let mut sketch = Self::new();
+        match *encoding {
+            DENSE if body.len() == sketch.registers.len() => sketch.registers.copy_from_slice(body),
+            SPARSE if body.len() % 3 == 0 => {
+                for pair in body.chunks_exact(3) {
+                    let index = u16::from_le_bytes([pair[0], pair[1]]) as usize;
+                    let register = sketch
+                        .registers
+                        .get_mut(index)
+                        .ok_or_else(|| anyhow!("User sketch register {} out of range", index))?;
+                    *register = pair[2];
+                }
+            }
+            _ => return Err(anyhow!("Malformed user sketch (encoding {}, {} bytes)", encoding, body.len())),
+        }
+        Ok(sketch)
+    }
+}
+
+/// Sketches of the senders of `activity` per (day, DApp)
+pub fn daily_sketches(activity: &[NewDAppUserActivity]) -> HashMap<(NaiveDate, String), UserSketch> {
+    let mut sketches: HashMap<(NaiveDate, String), UserSketch> = HashMap::new();
+    for entry in activity {
+        sketches
+            .entry((entry.day, entry.dapp_name.clone()))
+            .or_default()
+            .insert(&entry.sender);
+    }
+    sketches
+}
+
+/// Estimated number of distinct senders across serialized sketches, e.g. of several days
+pub fn union_estimate<'a>(sketches: impl IntoIterator<Item = &'a [u8]>) -> Result<u64> {
+    let mut union = UserSketch::new();
+    for bytes in sketches {
+        union.merge(&UserSketch::from_bytes(bytes)?);
+    }
+    Ok(union.estimate())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sketch(senders: std::ops::Range<u32>) -> UserSketch {
+        let mut sketch = UserSketch::new();
+        for sender in senders {
+            sketch.insert(&format!("0x{:064x}", sender));
+        }
+        sketch
+    }
+
+    #[test]
+    fn test_merged_days_estimate_their_union() {
+        let small = sketch(0..20);
+        assert_eq!(small.estimate(), 20);
+        // A small day is stored sparse
+        assert!(small.to_bytes().len() <= 3 + 20 * 3);
+        assert_eq!(UserSketch::from_bytes(&small.to_bytes()).unwrap(), small);
+
+        // Two overlapping days of 30k users, 50k distinct
+        let monday = sketch(0..30_000).to_bytes();
+        let tuesday = sketch(20_000..50_000).to_bytes();
+        assert_eq!(monday.len(), 3 + (1 << PRECISION));
+        let union = union_estimate([monday.as_slice(), tuesday.as_slice(), monday.as_slice()]).unwrap();
+        assert!((union as f64 - 50_000.0).abs() < 50_000.0 * 0.03, "estimated {}", union);
+
+        assert!(UserSketch::from_bytes(&[FORMAT_VERSION, PRECISION + 1, DENSE]).is_err());
+        assert!(UserSketch::from_bytes(&[FORMAT_VERSION, PRECISION, SPARSE, 0xff, 0xff, 1]).is_err());
+    }
+}
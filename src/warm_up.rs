@@ -0,0 +1,145 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Warm-up replay on startup.
+//!
+//! Rankings and 24h statistics are computed from in-memory windows that are
+//! empty after a restart. Checkpoints are extracted as far back as the longest
+//! of the ranking window, the ranking profile windows and the 24h statistics
+//! window, so WARM_UP_HOURS beyond that only slows down the replay.
+//! With WARM_UP_HOURS set, ingestion starts at the first
+//! checkpoint of the last WARM_UP_HOURS hours (or at the saved progress if it
+//! is older), located by binary search over checkpoint timestamps through a
+//! fullnode's JSON-RPC. Rankings are held back until the replay reaches the
+//! latest checkpoint seen at startup, so the first published rankings already
+//! cover the whole window.
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use std::future::Future;
+use std::time::Duration;
+use tracing::info;
+
+/// Timeout of a single RPC request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Checkpoints to replay so the in-memory windows are complete when going live
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WarmUpPlan {
+    /// First checkpoint of the warm-up window
+    pub start_checkpoint: u64,
+    /// Latest checkpoint at startup; rankings go live once it is processed
+    pub live_checkpoint: u64,
+}
+
+/// Reads checkpoint sequence numbers and timestamps from a fullnode's JSON-RPC
+pub struct CheckpointLocator {
+    rpc_url: String,
+    client: reqwest::Client,
+}
+
+impl CheckpointLocator {
+    pub fn new(rpc_url: impl Into<String>) -> Result<Self> {
+        let client = reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build()?;
+        Ok(Self { rpc_url: rpc_url.into(), client })
+    }
+
+    /// Latest executed checkpoint
+    pub async fn latest_checkpoint(&self) -> Result<u64> {
+        let result = self.call("sui_getLatestCheckpointSequenceNumber", json!([])).await?;
+        parse_u64(&result).context("Invalid latest checkpoint sequence number")
+    }
+
+    /// Timestamp of checkpoint `sequence_number` in milliseconds since the Unix epoch
+    pub async fn checkpoint_timestamp_ms(&self, sequence_number: u64) -> Result<u64> {
+        let result = self.call("sui_getCheckpoint", json!([sequence_number.to_string()])).await?;
+        parse_u64(&result["timestampMs"])
+            .with_context(|| format!("Invalid timestamp of checkpoint {}", sequence_number))
+    }
+
+    /// Plan the replay of the checkpoints of the last `window`
+    pub async fn plan_warm_up(&self, window: Duration) -> Result<WarmUpPlan> {
+        let live_checkpoint = self.latest_checkpoint().await?;
+        let since_ms = (Utc::now() - window).timestamp_millis().max(0) as u64;
+        let start_checkpoint =
+            first_checkpoint_since(0, live_checkpoint, since_ms, |checkpoint| self.checkpoint_timestamp_ms(checkpoint))
+                .await?;
+        info!("🔥 Warm-up: replaying checkpoints {} to {} ({} hours)",
+              start_checkpoint, live_checkpoint, window.as_secs() / 3600);
+        Ok(WarmUpPlan { start_checkpoint, live_checkpoint })
+    }
+
+    /// First checkpoint up to `latest` whose timestamp is at least `time`, `latest` if there is none
+    pub async fn first_checkpoint_at(&self, time: DateTime<Utc>, latest: u64) -> Result<u64> {
+        let since_ms = time.timestamp_millis().max(0) as u64;
+        first_checkpoint_since(0, latest, since_ms, |checkpoint| self.checkpoint_timestamp_ms(checkpoint)).await
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+        let mut response: Value = self
+            .client
+            .post(&self.rpc_url)
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if let Some(error) = response.get("error") {
+            return Err(anyhow!("RPC error: {}", error));
+        }
+        Ok(response["result"].take())
+    }
+}
+
+/// JSON-RPC returns 64-bit numbers as strings
+fn parse_u64(value: &Value) -> Result<u64> {
+    match value {
+        Value::String(value) => Ok(value.parse()?),
+        Value::Number(value) => value.as_u64().ok_or_else(|| anyhow!("{} is not an unsigned integer", value)),
+        value => Err(anyhow!("expected a number, got {}", value)),
+    }
+}
+
+/// First checkpoint in [low, high] whose timestamp is at least `since_ms`,
+/// `high` if there is none; checkpoint timestamps never decrease
+async fn first_checkpoint_since<F, Fut>(mut low: u64, mut high: u64, since_ms: u64, mut timestamp_ms: F) -> Result<u64>
+where
+    F: FnMut(u64) -> Fut,
+    Fut: Future<Output = Result<u64>>,
+{
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if timestamp_ms(middle).await? >= since_ms {
+            high = middle;
+        } else {
+            low = middle + 1;
+        }
+    }
+    Ok(high)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_first_checkpoint_since() {
+        let timestamps = [100, 200, 200, 300, 400];
+        let search = |since_ms| first_checkpoint_since(0, 4, since_ms, |checkpoint| async move { Ok(timestamps[checkpoint as usize]) });
+        assert_eq!(search(0).await.unwrap(), 0);
+        assert_eq!(search(200).await.unwrap(), 1);
+        assert_eq!(search(250).await.unwrap(), 3);
+        assert_eq!(search(1_000).await.unwrap(), 4);
+
+        assert_eq!(parse_u64(&json!("42")).unwrap(), 42);
+        assert!(parse_u64(&json!(null)).is_err());
+    }
+}
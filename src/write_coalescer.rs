@@ -0,0 +1,86 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Coalescing of database writes.
+//!
+//! Rankings can be recomputed on nearly every checkpoint, and each persistence
+//! round rewrites the rankings table. The coalescer limits persistence to at
+//! most once per interval and skips rounds where the persisted state would not
+//! change, identified by a fingerprint of that state.
+
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+/// Decides when in-memory state should be written to the database
+#[derive(Debug, Clone)]
+pub struct WriteCoalescer {
+    min_interval: Duration,
+    last_write: Option<DateTime<Utc>>,
+    last_fingerprint: Option<u64>,
+}
+
+impl WriteCoalescer {
+    /// # Arguments
+    /// * `min_interval` - Minimum time between two writes
+    pub fn new(min_interval: Duration) -> Self {
+        Self {
+            min_interval,
+            last_write: None,
+            last_fingerprint: None,
+        }
+    }
+
+    /// Returns true if state with this fingerprint should be written now
+    /// Unchanged state is never rewritten; changed state waits until the interval has passed
+    pub fn should_write(&self, fingerprint: u64, now: DateTime<Utc>) -> bool {
+        if self.last_fingerprint == Some(fingerprint) {
+            return false;
+        }
+        match self.last_write {
+            Some(last_write) => (now - last_write).to_std().unwrap_or_default() >= self.min_interval,
+            None => true,
+        }
+    }
+
+    /// Remember a successful write of state with this fingerprint
+    pub fn record_write(&mut self, fingerprint: u64, now: DateTime<Utc>) {
+        self.last_write = Some(now);
+        self.last_fingerprint = Some(fingerprint);
+    }
+
+    /// Forget the last write so the next state is written regardless of its fingerprint
+    pub fn reset(&mut self) {
+        self.last_write = None;
+        self.last_fingerprint = None;
+    }
+}
+
+impl Default for WriteCoalescer {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(30))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_coalescer_skips_unchanged_and_throttles() {
+        let mut coalescer = WriteCoalescer::new(Duration::from_secs(30));
+        let start = Utc::now();
+
+        assert!(coalescer.should_write(1, start));
+        coalescer.record_write(1, start);
+
+        // Unchanged state is skipped even after the interval
+        assert!(!coalescer.should_write(1, start + Duration::from_secs(60)));
+
+        // Changed state waits for the interval
+        assert!(!coalescer.should_write(2, start + Duration::from_secs(10)));
+        assert!(coalescer.should_write(2, start + Duration::from_secs(30)));
+
+        coalescer.reset();
+        assert!(coalescer.should_write(1, start));
+    }
+}